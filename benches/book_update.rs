@@ -0,0 +1,59 @@
+// Criterion baseline for the live BOOKLAT alert (ipc/book_latency.rs): parse + book update
+// of a Bybit delta, timed per message into the same Histogram the Hot Thread fills, so the
+// p99 printed here compares 1:1 with `BOOKLAT:` lines and DEFAULT_BUDGET_NS.
+// Two depths: if both p99s move, the code got slower; if only the deep one does, it's depth.
+//
+//     cargo bench --bench book_update -- --save-baseline main
+//     cargo bench --bench book_update -- --baseline main
+
+use std::time::{Duration, Instant};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use hft_rust::core::orderbook::L2OrderBook;
+use hft_rust::core::parser;
+use hft_rust::ipc::book_latency::{Histogram, DEFAULT_BUDGET_NS};
+
+/// Delta with `levels` bids and as many asks around 16888.
+fn delta(levels: usize) -> String {
+    let side = |sign: f64| {
+        (0..levels)
+            .map(|i| format!(r#"["{:.2}","{}"]"#, 16888.0 + sign * (1.0 + i as f64 * 0.5), 0.5 + i as f64 * 0.1))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        r#"{{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1672304486868,"data":{{"s":"BTCUSDT","b":[{}],"a":[{}],"u":12345,"seq":123456}}}}"#,
+        side(-1.0),
+        side(1.0)
+    )
+}
+
+fn book_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("book_update");
+    for levels in [3, 25] {
+        let message = delta(levels);
+        let mut book = L2OrderBook::new();
+        let mut hist = Histogram::new(250);
+        group.bench_with_input(BenchmarkId::from_parameter(levels * 2), &message, |b, message| {
+            b.iter_custom(|iters| {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    // simd-json parses in place: a fresh copy per message, outside the timing
+                    let mut bytes = message.as_bytes().to_vec();
+                    let t = Instant::now();
+                    parser::parse_and_update(&mut bytes, &mut book).unwrap();
+                    let elapsed = t.elapsed();
+                    hist.record(elapsed.as_nanos() as u64);
+                    total += elapsed;
+                }
+                total
+            });
+        });
+        let p99 = hist.percentile(99.0);
+        println!("book_update/{}: p99 {} ns, {} ns/level (live budget {} ns){}", levels * 2, p99, p99 / (levels as u64 * 2),
+            DEFAULT_BUDGET_NS, if p99 > DEFAULT_BUDGET_NS { " OVER BUDGET" } else { "" });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, book_update);
+criterion_main!(benches);
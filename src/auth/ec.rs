@@ -0,0 +1,112 @@
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use crate::auth::secrets::redacted_debug;
+use crate::core::base64;
+
+// ES256 request signing for Coinbase API keys (CDP keys): every REST request carries a short
+// lived JWT (`Authorization: Bearer ...`), signed with the key's P-256 private key and bound to
+// one method + host + path. Unlike the HMAC schemes there is no shared secret: the exchange
+// only holds the public key. The key arrives as PEM, either SEC1 (`EC PRIVATE KEY`, what the
+// Coinbase console hands out) or PKCS#8 (`PRIVATE KEY`); ring takes PKCS#8 only, so SEC1 is
+// wrapped first.
+// JWT lifetime Coinbase accepts
+pub const JWT_TTL_SECS: u64 = 120;
+const JWT_ISSUER: &str = "cdp";
+// AlgorithmIdentifier of a P-256 key: id-ecPublicKey, prime256v1
+const P256_ALGORITHM: [u8; 21] = [
+    0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01,
+    0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07,
+];
+
+/// DER length octets.
+fn der_len(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else if len <= 0xff {
+        out.extend_from_slice(&[0x81, len as u8]);
+    } else {
+        out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]);
+    }
+}
+
+/// PKCS#8 PrivateKeyInfo around a SEC1 ECPrivateKey of a P-256 key.
+fn sec1_to_pkcs8(sec1: &[u8]) -> Vec<u8> {
+    let mut octets = vec![0x04];
+    der_len(sec1.len(), &mut octets);
+    octets.extend_from_slice(sec1);
+    let body_len = 3 + P256_ALGORITHM.len() + octets.len();
+    let mut out = vec![0x30];
+    der_len(body_len, &mut out);
+    out.extend_from_slice(&[0x02, 0x01, 0x00]);
+    out.extend_from_slice(&P256_ALGORITHM);
+    out.extend_from_slice(&octets);
+    out
+}
+
+/// Label and DER body of the first PEM block in `pem`.
+fn pem_block(pem: &str) -> Option<(&str, Vec<u8>)> {
+    let start = pem.find("-----BEGIN ")? + "-----BEGIN ".len();
+    let label_end = start + pem[start..].find("-----")?;
+    let label = &pem[start..label_end];
+    let body_start = label_end + "-----".len();
+    let body_end = body_start + pem[body_start..].find("-----END ")?;
+    Some((label, base64::decode(&pem[body_start..body_end])?))
+}
+
+/// P-256 signing key of one API key, named as the exchange knows it.
+pub struct EcSigner {
+    key_name: String,
+    key_pair: EcdsaKeyPair,
+    rng: SystemRandom,
+}
+
+redacted_debug!(EcSigner { key_name });
+
+impl EcSigner {
+    /// `pem`: SEC1 `EC PRIVATE KEY` or PKCS#8 `PRIVATE KEY`. Literal `\n` (a key pasted into
+    /// an env var) count as line breaks.
+    pub fn from_pem(key_name: &str, pem: &str) -> Result<Self, String> {
+        let pem = pem.replace("\\n", "\n");
+        let (label, der) = pem_block(&pem).ok_or_else(|| "not a PEM private key".to_string())?;
+        let pkcs8 = match label {
+            "EC PRIVATE KEY" => sec1_to_pkcs8(&der),
+            "PRIVATE KEY" => der,
+            other => return Err(format!("unsupported PEM block {:?}", other)),
+        };
+        Self::from_pkcs8(key_name, &pkcs8)
+    }
+
+    pub fn from_pkcs8(key_name: &str, pkcs8: &[u8]) -> Result<Self, String> {
+        let rng = SystemRandom::new();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng)
+            .map_err(|e| format!("not a P-256 private key: {}", e))?;
+        Ok(Self { key_name: key_name.trim().to_string(), key_pair, rng })
+    }
+
+    pub fn key_name(&self) -> &str {
+        &self.key_name
+    }
+
+    /// Uncompressed public point (65 bytes), to check against the exchange's record.
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// r || s (64 bytes) over SHA-256 of `msg`.
+    pub fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, String> {
+        self.key_pair.sign(&self.rng, msg).map(|sig| sig.as_ref().to_vec()).map_err(|_| "ECDSA signing failed".to_string())
+    }
+
+    /// ES256 JWT for one request: `uri` is "METHOD host/path" (no scheme, no query), valid
+    /// from `now_secs` for `JWT_TTL_SECS`.
+    pub fn jwt(&self, uri: &str, now_secs: u64) -> Result<String, String> {
+        let mut nonce = [0u8; 16];
+        self.rng.fill(&mut nonce).map_err(|_| "no randomness for the JWT nonce".to_string())?;
+        let header = format!(r#"{{"alg":"ES256","kid":"{}","nonce":"{}","typ":"JWT"}}"#, self.key_name, hex::encode(nonce));
+        let claims = format!(r#"{{"sub":"{}","iss":"{}","nbf":{},"exp":{},"uri":"{}"}}"#,
+            self.key_name, JWT_ISSUER, now_secs, now_secs + JWT_TTL_SECS, uri);
+        let signing_input = format!("{}.{}", base64::encode_url(header.as_bytes()), base64::encode_url(claims.as_bytes()));
+        let signature = self.sign(signing_input.as_bytes())?;
+        Ok(format!("{}.{}", signing_input, base64::encode_url(&signature)))
+    }
+}
@@ -1 +1,10 @@
+//! Bybit request signing (HMAC-SHA256 over the V5 pre-sign string), the Binance USDⓈ-M
+//! query signer, ES256 JWTs for Coinbase (CDP) keys (`ec`), and the Bybit API credentials
+//! it signs with (`secrets`: env, OS keyring, encrypted file or stdin).
+
+pub mod ec;
+pub mod secrets;
 pub mod signer;
+
+#[cfg(test)]
+mod secrets_tests;
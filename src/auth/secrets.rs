@@ -0,0 +1,347 @@
+use std::cell::OnceCell;
+use std::io::{BufRead, Write};
+use std::num::NonZeroU32;
+use std::process::Command;
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::pbkdf2;
+
+// API credentials at startup. The key pair is read once, before any thread starts, from the
+// backend picked by HFT_SECRETS, so a trading box needs no plaintext .env:
+//   env            BYBIT_API_KEY / BYBIT_SECRET_KEY from the environment or ./.env (default)
+//   keyring[:SVC]  OS keyring, service SVC (default hft_rust), one entry per variable name:
+//                  libsecret `secret-tool` on Linux, `security` (Keychain) on macOS
+//   file:PATH      file written by `hft_rust --seal-secrets PATH`: PBKDF2-HMAC-SHA256 key from a
+//                  passphrase (HFT_SECRETS_PASSPHRASE or a stdin line), ChaCha20-Poly1305
+//   stdin          key and secret as two lines on stdin (pipe from a password manager)
+// Lines typed on a terminal are not echoed. Other venues' keys (BINANCE_API_KEY, OKX_PASSPHRASE,
+// ...) come from the same backend by variable name: a keyring account, a `NAME=value` line of
+// the sealed file, a stdin line.
+pub const KEY_VAR: &str = "BYBIT_API_KEY";
+pub const SECRET_VAR: &str = "BYBIT_SECRET_KEY";
+pub const PASSPHRASE_VAR: &str = "HFT_SECRETS_PASSPHRASE";
+const DEFAULT_SERVICE: &str = "hft_rust";
+// Sealed file: "hftsec1:<salt hex>:<nonce hex>:<ciphertext+tag hex>"
+const FILE_MAGIC: &str = "hftsec1";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+
+/// `Debug` of a credentials struct that prints only the listed public fields: never the
+/// secret (or the passphrase), not even in a panic message.
+#[macro_export]
+macro_rules! redacted_debug {
+    ($ty:ident { $($field:ident),* }) => {
+        impl std::fmt::Debug for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($ty))$(.field(stringify!($field), &self.$field))*.finish_non_exhaustive()
+            }
+        }
+    }
+}
+pub use crate::redacted_debug;
+
+/// Venue keys `names` from `provider`, in order.
+pub fn lookup_all<const N: usize>(provider: &(impl SecretProvider + ?Sized), names: [&str; N]) -> Result<[String; N], String> {
+    let mut values = [const { String::new() }; N];
+    for (value, name) in values.iter_mut().zip(names) {
+        *value = provider.lookup(name)?;
+    }
+    Ok(values)
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Credentials {
+    pub api_key: String,
+    pub api_secret: String,
+}
+
+redacted_debug!(Credentials { api_key });
+
+impl Credentials {
+    pub fn new(api_key: &str, api_secret: &str) -> Result<Self, String> {
+        let (api_key, api_secret) = (api_key.trim(), api_secret.trim());
+        if api_key.is_empty() || api_secret.is_empty() {
+            return Err("empty API key or secret".to_string());
+        }
+        Ok(Self { api_key: api_key.to_string(), api_secret: api_secret.to_string() })
+    }
+}
+
+/// Where the keys come from. Called at startup only.
+pub trait SecretProvider {
+    fn name(&self) -> String;
+
+    /// One secret by its variable name (`BYBIT_API_KEY`, `BINANCE_SECRET_KEY`, ...).
+    fn lookup(&self, name: &str) -> Result<String, String>;
+
+    /// The Bybit pair.
+    fn load(&self) -> Result<Credentials, String> {
+        Credentials::new(&self.lookup(KEY_VAR)?, &self.lookup(SECRET_VAR)?)
+    }
+}
+
+/// `HFT_SECRETS` value -> provider.
+pub fn provider_from_spec(spec: &str) -> Result<Box<dyn SecretProvider>, String> {
+    let (kind, arg) = match spec.trim().split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (spec.trim(), None),
+    };
+    match (kind, arg) {
+        ("env" | "", None) => Ok(Box::new(EnvProvider)),
+        ("keyring", service) => Ok(Box::new(KeyringProvider {
+            service: service.filter(|s| !s.is_empty()).unwrap_or(DEFAULT_SERVICE).to_string(),
+        })),
+        ("file", Some(path)) if !path.is_empty() => Ok(Box::new(EncryptedFileProvider::new(path))),
+        ("stdin", None) => Ok(Box::new(StdinProvider)),
+        _ => Err(format!("Bad HFT_SECRETS {:?} (env | keyring[:SERVICE] | file:PATH | stdin)", spec)),
+    }
+}
+
+/// Environment / ./.env (dotenv is loaded by main before this runs).
+pub struct EnvProvider;
+
+impl SecretProvider for EnvProvider {
+    fn name(&self) -> String {
+        "env".to_string()
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        std::env::var(name).map_err(|_| format!("{} is not set", name))
+    }
+}
+
+/// OS keyring through its command line tool (no native bindings).
+pub struct KeyringProvider {
+    pub service: String,
+}
+
+impl SecretProvider for KeyringProvider {
+    fn name(&self) -> String {
+        format!("keyring:{}", self.service)
+    }
+
+    fn lookup(&self, account: &str) -> Result<String, String> {
+        let mut cmd = if cfg!(target_os = "macos") {
+            let mut cmd = Command::new("security");
+            cmd.args(["find-generic-password", "-s", &self.service, "-a", account, "-w"]);
+            cmd
+        } else {
+            let mut cmd = Command::new("secret-tool");
+            cmd.args(["lookup", "service", &self.service, "account", account]);
+            cmd
+        };
+        let out = cmd.output().map_err(|e| format!("keyring tool: {}", e))?;
+        if !out.status.success() {
+            return Err(format!("keyring has no {}/{}", self.service, account));
+        }
+        String::from_utf8(out.stdout).map_err(|_| format!("keyring {}/{} is not UTF-8", self.service, account))
+    }
+}
+
+/// File sealed by `seal_with`, opened (one passphrase) on the first lookup.
+pub struct EncryptedFileProvider {
+    pub path: String,
+    opened: OnceCell<Sealed>,
+}
+
+impl EncryptedFileProvider {
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string(), opened: OnceCell::new() }
+    }
+
+    fn opened(&self) -> Result<&Sealed, String> {
+        if let Some(sealed) = self.opened.get() {
+            return Ok(sealed);
+        }
+        let file = std::fs::read_to_string(&self.path).map_err(|e| format!("{}: {}", self.path, e))?;
+        let sealed = open_with(&file, &passphrase()?)?;
+        Ok(self.opened.get_or_init(|| sealed))
+    }
+}
+
+impl SecretProvider for EncryptedFileProvider {
+    fn name(&self) -> String {
+        format!("file:{}", self.path)
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        let (credentials, venue_keys) = self.opened()?;
+        match name {
+            KEY_VAR => Ok(credentials.api_key.clone()),
+            SECRET_VAR => Ok(credentials.api_secret.clone()),
+            _ => venue_keys.iter().find(|(key, _)| key == name).map(|(_, value)| value.clone())
+                .ok_or_else(|| format!("{} has no {}", self.path, name)),
+        }
+    }
+
+    fn load(&self) -> Result<Credentials, String> {
+        self.opened().map(|(credentials, _)| credentials.clone())
+    }
+}
+
+/// Two lines on stdin: key, then secret.
+pub struct StdinProvider;
+
+impl SecretProvider for StdinProvider {
+    fn name(&self) -> String {
+        "stdin".to_string()
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        prompt(&format!("{}: ", name))
+    }
+
+    fn load(&self) -> Result<Credentials, String> {
+        let key = prompt("API key: ")?;
+        let secret = prompt("API secret: ")?;
+        Credentials::new(&key, &secret)
+    }
+}
+
+/// One line from stdin, the prompt on stderr (stdout may be the log). Typed on a terminal,
+/// the line is not echoed; piped input is read as is.
+pub fn prompt(label: &str) -> Result<String, String> {
+    eprint!("{}", label);
+    let _ = std::io::stderr().flush();
+    let _echo_off = EchoOff::stdin();
+    let mut line = String::new();
+    match std::io::stdin().lock().read_line(&mut line) {
+        Ok(0) => Err(format!("stdin closed before '{}'", label.trim_end_matches([':', ' ']))),
+        Ok(_) => Ok(line.trim_end_matches(['\r', '\n']).to_string()),
+        Err(e) => Err(format!("stdin: {}", e)),
+    }
+}
+
+// Terminal echo off (the newline still shows) until dropped
+#[cfg(unix)]
+struct EchoOff(libc::termios);
+
+#[cfg(unix)]
+impl EchoOff {
+    /// None when stdin is not a terminal.
+    fn stdin() -> Option<Self> {
+        // SAFETY: plain termios calls on fd 0 with a zero-initialised struct filled by tcgetattr
+        unsafe {
+            let mut saved: libc::termios = std::mem::zeroed();
+            if libc::isatty(libc::STDIN_FILENO) != 1 || libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+                return None;
+            }
+            let mut hidden = saved;
+            hidden.c_lflag &= !libc::ECHO;
+            hidden.c_lflag |= libc::ECHONL;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &hidden) != 0 {
+                return None;
+            }
+            Some(Self(saved))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        // SAFETY: restores the attributes read by `stdin`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct EchoOff;
+
+#[cfg(not(unix))]
+impl EchoOff {
+    fn stdin() -> Option<Self> {
+        None
+    }
+}
+
+/// HFT_SECRETS_PASSPHRASE, else a stdin line.
+pub fn passphrase() -> Result<String, String> {
+    match std::env::var(PASSPHRASE_VAR) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => prompt("Secrets passphrase: "),
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey, String> {
+    if passphrase.is_empty() {
+        return Err("empty passphrase".to_string());
+    }
+    let mut key = [0u8; 32];
+    let iterations = NonZeroU32::new(PBKDF2_ITERATIONS).expect("non-zero iterations");
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let unbound = UnboundKey::new(&aead::CHACHA20_POLY1305, &key).map_err(|_| "bad key length".to_string())?;
+    Ok(LessSafeKey::new(unbound))
+}
+
+/// The Bybit pair and the other venues' keys as (variable name, value).
+type Sealed = (Credentials, Vec<(String, String)>);
+
+/// Encrypts the pair under `passphrase` (fresh salt and nonce) into the sealed-file line.
+pub fn seal(credentials: &Credentials, passphrase: &str) -> Result<String, String> {
+    seal_with(credentials, &[], passphrase)
+}
+
+/// `seal` with other venues' keys, one `NAME=value` line each after the pair.
+pub fn seal_with(credentials: &Credentials, venue_keys: &[(String, String)], passphrase: &str) -> Result<String, String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| "no system randomness".to_string())?;
+    rng.fill(&mut nonce).map_err(|_| "no system randomness".to_string())?;
+    let mut data = format!("{}\n{}", credentials.api_key, credentials.api_secret);
+    for (name, value) in venue_keys {
+        if name.is_empty() || name.contains(['=', '\n']) || value.contains('\n') {
+            return Err(format!("bad venue key {:?}", name));
+        }
+        data.push_str(&format!("\n{}={}", name, value.trim()));
+    }
+    let mut data = data.into_bytes();
+    derive_key(passphrase, &salt)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(FILE_MAGIC.as_bytes()), &mut data)
+        .map_err(|_| "encryption failed".to_string())?;
+    Ok(format!("{}:{}:{}:{}", FILE_MAGIC, hex::encode(salt), hex::encode(nonce), hex::encode(data)))
+}
+
+/// `seal_with` into `path`, readable by the owner only.
+pub fn seal_to_file(path: &str, credentials: &Credentials, venue_keys: &[(String, String)], passphrase: &str) -> Result<(), String> {
+    let sealed = seal_with(credentials, venue_keys, passphrase)?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).map_err(|e| format!("{}: {}", path, e))?;
+    writeln!(file, "{}", sealed).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Inverse of `seal`: the pair only. A wrong passphrase and a corrupted file look the same.
+pub fn open(sealed: &str, passphrase: &str) -> Result<Credentials, String> {
+    open_with(sealed, passphrase).map(|(credentials, _)| credentials)
+}
+
+/// Inverse of `seal_with`.
+pub fn open_with(sealed: &str, passphrase: &str) -> Result<Sealed, String> {
+    let mut parts = sealed.trim().split(':');
+    let (Some(FILE_MAGIC), Some(salt), Some(nonce), Some(data), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err("not a sealed secrets file".to_string());
+    };
+    let salt = hex::decode(salt).map_err(|_| "bad salt".to_string())?;
+    let mut nonce_bytes = [0u8; aead::NONCE_LEN];
+    hex::decode_to_slice(nonce, &mut nonce_bytes).map_err(|_| "bad nonce".to_string())?;
+    let mut data = hex::decode(data).map_err(|_| "bad ciphertext".to_string())?;
+    let plain = derive_key(passphrase, &salt)?
+        .open_in_place(Nonce::assume_unique_for_key(nonce_bytes), Aad::from(FILE_MAGIC.as_bytes()), &mut data)
+        .map_err(|_| "wrong passphrase or corrupted file".to_string())?;
+    let plain = std::str::from_utf8(plain).map_err(|_| "sealed data is not UTF-8".to_string())?;
+    let mut lines = plain.split('\n');
+    let (Some(key), Some(secret)) = (lines.next(), lines.next()) else {
+        return Err("sealed data has no secret".to_string());
+    };
+    let venue_keys = lines
+        .map(|line| line.split_once('=').map(|(name, value)| (name.to_string(), value.to_string())).ok_or("bad venue key line"))
+        .collect::<Result<_, _>>()?;
+    Ok((Credentials::new(key, secret)?, venue_keys))
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use crate::auth::secrets::{lookup_all, open, open_with, provider_from_spec, seal, seal_to_file, seal_with, Credentials, EnvProvider, PASSPHRASE_VAR};
+
+    fn pair() -> Credentials {
+        Credentials::new(" key-123 ", "secret-456\n").unwrap()
+    }
+
+    #[test]
+    fn sealed_pair_opens_only_with_its_passphrase() {
+        let sealed = seal(&pair(), "correct horse").unwrap();
+        let fields: Vec<&str> = sealed.split(':').collect();
+        assert_eq!((fields.len(), fields[0]), (4, "hftsec1"));
+        assert!(!sealed.contains("secret-456"));
+        // Fresh salt and nonce every time
+        assert_ne!(seal(&pair(), "correct horse").unwrap(), sealed);
+
+        let opened = open(&format!("{}\n", sealed), "correct horse").unwrap();
+        assert_eq!((opened.api_key.as_str(), opened.api_secret.as_str()), ("key-123", "secret-456"));
+        assert_eq!(open(&sealed, "wrong horse").unwrap_err(), "wrong passphrase or corrupted file");
+        assert_eq!(open(&sealed, "").unwrap_err(), "empty passphrase");
+        assert!(seal(&pair(), "").is_err());
+    }
+
+    #[test]
+    fn tampered_or_malformed_files_are_refused() {
+        let sealed = seal(&pair(), "pw").unwrap();
+        let (head, ct) = sealed.rsplit_once(':').unwrap();
+        // One flipped ciphertext nibble fails the tag
+        let flipped = if ct.starts_with('0') { "1" } else { "0" };
+        assert_eq!(open(&format!("{}:{}{}", head, flipped, &ct[1..]), "pw").unwrap_err(), "wrong passphrase or corrupted file");
+
+        let [_, salt, nonce, _] = <[&str; 4]>::try_from(sealed.split(':').collect::<Vec<_>>()).unwrap();
+        for (bad, err) in [
+            (format!("hftsec2:{}:{}:{}", salt, nonce, ct), "not a sealed secrets file"),
+            (format!("hftsec1:{}:{}", salt, nonce), "not a sealed secrets file"),
+            (format!("{}:extra", sealed), "not a sealed secrets file"),
+            (format!("hftsec1:zz:{}:{}", nonce, ct), "bad salt"),
+            (format!("hftsec1:{}:{}:{}", salt, &nonce[2..], ct), "bad nonce"),
+            (format!("hftsec1:{}:{}:{}x", salt, nonce, ct), "bad ciphertext"),
+        ] {
+            assert_eq!(open(&bad, "pw").unwrap_err(), err, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn sealed_file_is_owner_only() {
+        let path = std::env::temp_dir().join(format!("hft_secrets_{}.sealed", std::process::id()));
+        let path = path.to_str().unwrap();
+        seal_to_file(path, &pair(), &[], "pw").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let provider = provider_from_spec(&format!("file:{}", path)).unwrap();
+        let sealed = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(provider.name(), format!("file:{}", path));
+        assert_eq!(open(&sealed, "pw").unwrap(), pair());
+    }
+
+    #[test]
+    fn hft_secrets_picks_the_provider() {
+        let name = |spec: &str| provider_from_spec(spec).map(|provider| provider.name());
+        assert_eq!(name("").unwrap(), "env");
+        assert_eq!(name(" env ").unwrap(), "env");
+        assert_eq!(name("keyring").unwrap(), "keyring:hft_rust");
+        assert_eq!(name("keyring:").unwrap(), "keyring:hft_rust");
+        assert_eq!(name("keyring:bybit-prod").unwrap(), "keyring:bybit-prod");
+        assert_eq!(name("file:/etc/hft/keys.sealed").unwrap(), "file:/etc/hft/keys.sealed");
+        assert_eq!(name("stdin").unwrap(), "stdin");
+        for bad in ["file", "file:", "env:x", "stdin:x", "vault"] {
+            assert!(name(bad).unwrap_err().contains("Bad HFT_SECRETS"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn credentials_never_print_the_secret() {
+        let printed = format!("{:?}", pair());
+        assert!(printed.contains("key-123") && !printed.contains("secret-456"), "{}", printed);
+        assert!(Credentials::new("key", " ").is_err());
+        assert!(Credentials::new("", "secret").is_err());
+    }
+
+    #[test]
+    fn lookup_all_names_the_first_missing_variable() {
+        std::env::set_var("HFT_TEST_ENV_VARS_KEY", "key");
+        assert_eq!(lookup_all(&EnvProvider, ["HFT_TEST_ENV_VARS_KEY"]).unwrap(), ["key".to_string()]);
+        assert_eq!(lookup_all(&EnvProvider, ["HFT_TEST_ENV_VARS_KEY", "HFT_TEST_ENV_VARS_UNSET"]).unwrap_err(), "HFT_TEST_ENV_VARS_UNSET is not set");
+    }
+
+    #[test]
+    fn sealed_file_carries_other_venues_keys() {
+        let venue_keys = [("BINANCE_API_KEY".to_string(), "bn-key".to_string()), ("BINANCE_SECRET_KEY".to_string(), "bn=secret".to_string())];
+        let sealed = seal_with(&pair(), &venue_keys, "pw").unwrap();
+        assert!(!sealed.contains("bn-key"));
+        let (credentials, opened) = open_with(&sealed, "pw").unwrap();
+        assert_eq!((credentials, opened.as_slice()), (pair(), &venue_keys[..]));
+        // The pair alone still opens, and old pair-only files have no venue keys
+        assert_eq!(open(&sealed, "pw").unwrap(), pair());
+        assert!(open_with(&seal(&pair(), "pw").unwrap(), "pw").unwrap().1.is_empty());
+        assert!(seal_with(&pair(), &[("A=B".to_string(), "x".to_string())], "pw").is_err());
+
+        // One passphrase for every lookup of the file provider
+        let path = std::env::temp_dir().join(format!("hft_venue_secrets_{}.sealed", std::process::id()));
+        let path = path.to_str().unwrap();
+        seal_to_file(path, &pair(), &venue_keys, "pw").unwrap();
+        std::env::set_var(PASSPHRASE_VAR, "pw");
+        let provider = provider_from_spec(&format!("file:{}", path)).unwrap();
+        assert_eq!(provider.load().unwrap(), pair());
+        std::env::remove_var(PASSPHRASE_VAR);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(lookup_all(&*provider, ["BINANCE_API_KEY", "BINANCE_SECRET_KEY", "BYBIT_API_KEY"]).unwrap(), ["bn-key", "bn=secret", "key-123"]);
+        assert!(provider.lookup("OKX_PASSPHRASE").unwrap_err().contains("has no OKX_PASSPHRASE"));
+    }
+}
@@ -33,3 +33,29 @@ impl Signer {
         hex::encode_to_slice(tag.as_ref(), out_hex).expect("Hex encoding failed");
     }
 }
+
+/// Binance USDⓈ-M signer: `signature = hex(HMAC-SHA256(secret, query))`, where the query is
+/// the request's parameters as sent (timestamp and recvWindow included). Unlike Bybit,
+/// nothing is prepended: the API key travels in the `X-MBX-APIKEY` header only.
+pub struct BinanceSigner {
+    key: hmac::Key,
+}
+
+impl BinanceSigner {
+    pub fn new(secret: &str) -> Self {
+        Self { key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()) }
+    }
+
+    /// Hex signature of `query` into a pre-allocated buffer (64 chars).
+    pub fn sign_query(&self, query: &[u8], out_hex: &mut [u8; 64]) {
+        let tag = hmac::sign(&self.key, query);
+        hex::encode_to_slice(tag.as_ref(), out_hex).expect("Hex encoding failed");
+    }
+
+    /// `query` with `&signature=...` appended, ready to send.
+    pub fn signed_query(&self, query: &str) -> String {
+        let mut sig = [0u8; 64];
+        self.sign_query(query.as_bytes(), &mut sig);
+        format!("{}&signature={}", query, std::str::from_utf8(&sig).unwrap_or(""))
+    }
+}
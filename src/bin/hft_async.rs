@@ -0,0 +1,22 @@
+// Paper-trading runner on the tokio runtime (feature `tokio-runtime`): the public Bybit
+// stream drives the same book, strategy and OMS as the mio binary, and every strategy
+// action is logged instead of sent. No keys, no orders; latency is not a goal here.
+// Actions rest on a paper exchange that fills them from the book and public trades under
+// HFT_FILL_MODEL (optimistic | queue | pessimistic, default queue); run once per model to
+// bracket the result. Same as `hft_rust --paper` (see `net::async_ws::run_paper`).
+//
+//   HFT_FILL_MODEL=pessimistic cargo run --release --features tokio-runtime --bin hft_async
+
+use hft_rust::config::EngineConfig;
+use hft_rust::net::async_ws;
+use hft_rust::strategy::paper::PaperConfig;
+
+fn main() {
+    // Same config file as the mio binary (HFT_CONFIG or ./config.toml)
+    let result = EngineConfig::from_env()
+        .and_then(|(engine_config, _)| async_ws::run_paper(&engine_config, PaperConfig::from_env()));
+    if let Err(e) = result {
+        eprintln!("FATAL: {}", e);
+        std::process::exit(1);
+    }
+}
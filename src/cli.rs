@@ -0,0 +1,88 @@
+//! Command line of the `hft_rust` binary.
+//!
+//! Hand-rolled: a handful of flags do not justify a parser dependency. Precedence is
+//! config file < `HFT_*` environment < command line, so a flag always wins.
+//! Both `--flag value` and `--flag=value` are accepted.
+
+use crate::strategy::paper::FillModel;
+use crate::strategy::preset::Preset;
+
+pub const USAGE: &str = "\
+Usage: hft_rust [OPTIONS]
+
+  --config <FILE>         engine config (TOML); default HFT_CONFIG, then ./config.toml
+  --symbol <SYMBOL>       Bybit symbol to trade (e.g. BTCUSDT), overrides [instrument]
+  --preset <NAME>         conservative | normal | aggressive: spread band, size, take-profit
+                          and hold times in one switch (default HFT_PRESET, then the config's)
+  --paper[=MODEL]         paper trading on the public stream, no keys and no orders;
+                          MODEL = optimistic | queue | pessimistic (default HFT_FILL_MODEL, queue)
+                          (needs a build with --features tokio-runtime)
+  --log-mode <MODE>       minimal (errors only) or a level spec, e.g. net=debug,orders=error
+  --core-pinning <on|off> pin threads per the [pinning] map (default on)
+  --seal-secrets <FILE>   read API key, secret, other venues' NAME=value keys and the
+                          passphrase from stdin, write them encrypted to FILE for
+                          HFT_SECRETS=file:FILE, then exit
+  -h, --help              this text";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliArgs {
+    pub config: Option<String>,
+    pub symbol: Option<String>,
+    pub preset: Option<Preset>,
+    pub paper: bool,
+    /// `--paper=MODEL`.
+    pub fill_model: Option<FillModel>,
+    pub log_mode: Option<String>,
+    pub core_pinning: Option<bool>,
+    /// `--seal-secrets FILE`: write an encrypted credentials file and exit.
+    pub seal_secrets: Option<String>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parses the arguments after the program name.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut cli = CliArgs::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            match flag.as_str() {
+                "-h" | "--help" => cli.help = true,
+                "--paper" => {
+                    cli.paper = true;
+                    if let Some(spec) = inline {
+                        cli.fill_model = Some(FillModel::from_spec(&spec)
+                            .ok_or_else(|| format!("--paper: unknown fill model '{}' (optimistic|queue|pessimistic)", spec))?);
+                    }
+                }
+                "--config" | "--symbol" | "--preset" | "--log-mode" | "--core-pinning" | "--seal-secrets" => {
+                    let value = match inline {
+                        Some(value) => value,
+                        None => args.next().ok_or_else(|| format!("{} needs a value", flag))?,
+                    };
+                    if value.is_empty() {
+                        return Err(format!("{} needs a value", flag));
+                    }
+                    match flag.as_str() {
+                        "--config" => cli.config = Some(value),
+                        "--symbol" => cli.symbol = Some(value.to_ascii_uppercase()),
+                        "--preset" => cli.preset = Some(Preset::from_spec(&value)
+                            .ok_or_else(|| format!("--preset: unknown preset '{}' (conservative|normal|aggressive)", value))?),
+                        "--log-mode" => cli.log_mode = Some(value),
+                        "--seal-secrets" => cli.seal_secrets = Some(value),
+                        _ => cli.core_pinning = Some(match value.as_str() {
+                            "on" | "1" | "true" => true,
+                            "off" | "0" | "false" => false,
+                            _ => return Err(format!("--core-pinning: expected on|off, got '{}'", value)),
+                        }),
+                    }
+                }
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+        Ok(cli)
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::cli::CliArgs;
+    use crate::strategy::paper::FillModel;
+    use crate::strategy::preset::Preset;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn flags_with_separate_and_inline_values() {
+        let cli = parse(&["--config", "prod.toml", "--symbol=btcusdt", "--preset", "Aggressive", "--paper=pessimistic",
+                          "--log-mode", "minimal", "--core-pinning", "off", "--seal-secrets=keys.sealed"]).unwrap();
+        assert_eq!(cli.config.as_deref(), Some("prod.toml"));
+        assert_eq!(cli.symbol.as_deref(), Some("BTCUSDT"));
+        assert_eq!(cli.preset, Some(Preset::Aggressive));
+        assert!(cli.paper);
+        assert_eq!(cli.fill_model, Some(FillModel::Pessimistic));
+        assert_eq!(cli.log_mode.as_deref(), Some("minimal"));
+        assert_eq!(cli.core_pinning, Some(false));
+        assert_eq!(cli.seal_secrets.as_deref(), Some("keys.sealed"));
+
+        assert_eq!(parse(&[]).unwrap(), CliArgs::default());
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse(&["--symbol"]).is_err());
+        assert!(parse(&["--seal-secrets="]).is_err());
+        assert!(parse(&["--core-pinning", "maybe"]).is_err());
+        assert!(parse(&["--paper=fast"]).is_err());
+        assert!(parse(&["--preset=yolo"]).is_err());
+        assert!(parse(&["--turbo"]).is_err());
+    }
+}
@@ -0,0 +1,1502 @@
+//! Engine parameters from a TOML file, loaded once at startup.
+//!
+//! Everything that used to need a recompile to change: the traded instrument, endpoints,
+//! quoting parameters and regimes, risk limits, recv_window and clock-skew margins. Every field is optional;
+//! what the file leaves out keeps the built-in default, and a missing default file means
+//! all defaults. A `preset` (strategy::preset) replaces the spread band, take-profit and hold
+//! times of [quote] and every symbol and scales their clips.
+//! Environment overrides (`HFT_*`) go on top through `apply_env`, so a one-off run can still
+//! tweak a value without editing the file; they are parsed strictly and validated like the file.
+//!
+//! ```toml
+//! preset = "conservative"
+//!
+//! [instrument]
+//! name = "BTC-PERP"
+//! base = "BTC"
+//! bybit_symbol = "BTCUSDT"
+//! tick_size = 0.1
+//! qty_step = 0.001
+//! min_qty = 0.001
+//!
+//! [quote]
+//! min_spread = 0.002
+//! quote_qty = 0.01
+//!
+//! [regimes]
+//! tps = [30.0, 80.0, 150.0]
+//! extreme = { spread = 0.015, requote_scale = 2.0, impulse_threshold = 0.006, size_factor = 0.25 }
+//!
+//! [endpoints.race]
+//! samples = 5
+//! timeout_ms = 500
+//!
+//! [[symbols]]
+//! instrument = { name = "ETH-PERP", base = "ETH", bybit_symbol = "ETHUSDT", tick_size = 0.01, qty_step = 0.01, min_qty = 0.01 }
+//! quote = { quote_qty = 0.1 }
+//!
+//! [watchdog]
+//! stall_ms = 1000
+//!
+//! [send_gate.side_throttle]
+//! creates = 8
+//! cooldown_ms = 1000
+//!
+//! [records]
+//! heatmap_file = ""
+//!
+//! [oms]
+//! min_quote_lifetime_ms = 250
+//! front_queue_fraction = 0.2
+//!
+//! [time]
+//! recv_window_ms = 10000
+//! rest_margin_ms = 3000
+//!
+//! [pinning]
+//! hot = 2
+//! cold = 3
+//! require_isolated = true
+//!
+//! [tls.pins]
+//! "stream.bybit.com" = ["sha256/<base64>", "sha256/<backup>"]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::core::batch::BatchPolicy;
+use crate::core::time_sync::TimeSyncConfig;
+use crate::ipc::book_latency;
+use crate::ipc::drop_copy::DropCopyConfig;
+use crate::ipc::egress::{EgressConfig, EgressOp};
+use crate::ipc::error_stats::ErrorStatsConfig;
+use crate::ipc::heatmap::HeatmapConfig;
+use crate::ipc::incident::IncidentConfig;
+use crate::ipc::session::Conn;
+use crate::ipc::watchdog::WatchdogConfig;
+use crate::net::feed_watch::FeedWatchConfig;
+use crate::net::resolver::RaceConfig;
+use crate::net::startup::StartupConfig;
+use crate::net::tls_client::CertPins;
+use crate::strategy::experiment::ExperimentConfig;
+use crate::strategy::fade::FadeConfig;
+use crate::strategy::jitter::JitterConfig;
+use crate::strategy::last_price::LastPriceConfig;
+use crate::strategy::margin::MarginConfig;
+use crate::strategy::market_maker::{CloseConfig, CloseOrderType, QuoteParams};
+use crate::strategy::obligation::ObligationConfig;
+use crate::strategy::order_manager::OrderManagerConfig;
+use crate::strategy::pnl_audit::PnlAuditConfig;
+use crate::strategy::preset::Preset;
+use crate::strategy::regime::RegimeConfig;
+use crate::strategy::requote::RequoteConfig;
+use crate::strategy::rest_quoting::{RestQuotingConfig, RestQuotingMode};
+use crate::strategy::risk::RiskConfig;
+use crate::strategy::router::{Venue, VENUE_COUNT};
+use crate::strategy::send_gate::SendGateConfig;
+use crate::strategy::shadow;
+use crate::strategy::side_throttle::ThrottleConfig;
+use crate::strategy::strategy_log::{LogCategory, LogSampling};
+use crate::strategy::symbols::{CancelScope, Category, Instrument, SymbolRegistry, PRIMARY_SYMBOL};
+use crate::strategy::warmup::WarmupConfig;
+
+/// Kernel list of cores removed from the scheduler (`isolcpus=`), e.g. "2-3,6".
+const ISOLATED_CPUS_FILE: &str = "/sys/devices/system/cpu/isolated";
+
+/// Read when `HFT_CONFIG` is not set; may be absent.
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineConfig {
+    /// Risk posture bundle applied over [quote] and [[symbols]] quotes (`apply_preset`).
+    pub preset: Option<Preset>,
+    pub instrument: InstrumentConfig,
+    pub endpoints: EndpointConfig,
+    pub quote: QuoteParams,
+    /// Named quoting regimes and the thresholds that switch between them.
+    pub regimes: RegimeConfig,
+    pub risk: RiskConfig,
+    /// Order manager of every symbol slot (and of the shadow and paper strategies).
+    pub oms: OmsConfig,
+    /// recv_window and the request timestamp margins.
+    pub time: TimeSyncConfig,
+    /// More contracts quoted from the same Hot Thread, next to [instrument].
+    pub symbols: Vec<SymbolConfig>,
+    pub pinning: PinningConfig,
+    pub tls: TlsConfig,
+    pub startup: StartupSection,
+    pub venues: VenuesSection,
+    pub watchdog: WatchdogSection,
+    /// Order rate and loop breaker of every symbol slot.
+    pub send_gate: SendGateSection,
+    pub rest_quoting: RestQuotingSection,
+    pub margin: MarginSection,
+    pub obligation: ObligationSection,
+    pub strategy: StrategySection,
+    pub experiment: ExperimentSection,
+    pub shadow: ShadowSection,
+    pub pnl_audit: PnlAuditSection,
+    pub feed_watch: FeedWatchSection,
+    pub metrics: MetricsSection,
+    pub records: RecordsSection,
+}
+
+/// One `[[symbols]]` entry: an instrument and, optionally, its own quoting parameters.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SymbolConfig {
+    pub instrument: InstrumentConfig,
+    /// None = the [quote] table. A partial table starts from the built-in defaults, not [quote].
+    pub quote: Option<QuoteParams>,
+}
+
+/// The traded instrument (the primary symbol of the registry).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InstrumentConfig {
+    /// Internal name, used in logs and lookups.
+    pub name: String,
+    pub base: String,
+    pub quote: String,
+    /// linear | inverse | spot | option
+    pub category: String,
+    pub tick_size: f64,
+    pub qty_step: f64,
+    pub min_qty: f64,
+    pub bybit_symbol: String,
+    pub binance_symbol: Option<String>,
+    /// OKX instId ("BTC-USDT-SWAP"): reference book over the OKX public WS.
+    pub okx_symbol: Option<String>,
+}
+
+impl Default for InstrumentConfig {
+    fn default() -> Self {
+        let registry = SymbolRegistry::default();
+        let inst = registry.get(PRIMARY_SYMBOL).expect("Primary instrument not registered");
+        Self {
+            name: inst.name.to_string(),
+            base: inst.base.to_string(),
+            quote: inst.quote.to_string(),
+            category: inst.category.as_str().to_string(),
+            tick_size: inst.tick_size,
+            qty_step: inst.qty_step,
+            min_qty: inst.min_qty,
+            bybit_symbol: inst.venue_symbol(Venue::Bybit).unwrap_or_default().to_string(),
+            binance_symbol: inst.venue_symbol(Venue::Binance).map(str::to_string),
+            okx_symbol: inst.venue_symbol(Venue::Okx).map(str::to_string),
+        }
+    }
+}
+
+// Registry strings are &'static; the config lives as long as the process anyway
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
+
+impl InstrumentConfig {
+    /// Checks the fields; returns the parsed category.
+    pub fn validate(&self) -> Result<Category, String> {
+        let category = Category::from_spec(self.category.trim())
+            .ok_or_else(|| format!("instrument.category {:?} (linear|inverse|spot|option)", self.category))?;
+        if self.name.is_empty() || self.bybit_symbol.is_empty() {
+            return Err("instrument.name and instrument.bybit_symbol must be set".to_string());
+        }
+        for (key, v) in [("tick_size", self.tick_size), ("qty_step", self.qty_step), ("min_qty", self.min_qty)] {
+            if v.is_nan() || v <= 0.0 {
+                return Err(format!("instrument.{} must be > 0 (got {})", key, v));
+            }
+        }
+        Ok(category)
+    }
+
+    /// Validated registry entry. Call once at startup: the strings are leaked.
+    pub fn to_instrument(&self) -> Result<Instrument, String> {
+        let category = self.validate()?;
+        let mut venue_symbols = [None; VENUE_COUNT];
+        venue_symbols[Venue::Bybit as usize] = Some(leak(&self.bybit_symbol));
+        venue_symbols[Venue::Binance as usize] = self.binance_symbol.as_deref().map(leak);
+        venue_symbols[Venue::Okx as usize] = self.okx_symbol.as_deref().map(leak);
+        Ok(Instrument {
+            name: leak(&self.name),
+            base: leak(&self.base),
+            quote: leak(&self.quote),
+            category,
+            tick_size: self.tick_size,
+            qty_step: self.qty_step,
+            min_qty: self.min_qty,
+            venue_symbols,
+        })
+    }
+}
+
+/// Which Bybit deployment every host points at. Testnet and demo need their own API keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Mainnet,
+    Testnet,
+    /// Demo trading: mainnet market data, simulated account and order entry.
+    Demo,
+}
+
+impl Environment {
+    /// `mainnet` | `testnet` | `demo` (HFT_ENVIRONMENT).
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "mainnet" => Some(Environment::Mainnet),
+            "testnet" => Some(Environment::Testnet),
+            "demo" => Some(Environment::Demo),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Environment::Mainnet => "mainnet",
+            Environment::Testnet => "testnet",
+            Environment::Demo => "demo",
+        }
+    }
+
+    /// Public market data stream.
+    pub fn public_ws_host(&self) -> &'static str {
+        match self {
+            Environment::Mainnet | Environment::Demo => "stream.bybit.com",
+            Environment::Testnet => "stream-testnet.bybit.com",
+        }
+    }
+
+    /// Private and trade streams.
+    pub fn private_ws_host(&self) -> &'static str {
+        match self {
+            Environment::Mainnet => "stream.bybit.com",
+            Environment::Testnet => "stream-testnet.bybit.com",
+            Environment::Demo => "stream-demo.bybit.com",
+        }
+    }
+
+    pub fn rest_url(&self) -> &'static str {
+        match self {
+            Environment::Mainnet => "https://api.bybit.com",
+            Environment::Testnet => "https://api-testnet.bybit.com",
+            Environment::Demo => "https://api-demo.bybit.com",
+        }
+    }
+
+    /// Binance USDⓈ-M REST for hedge orders; testnet and demo go to Binance's futures testnet.
+    pub fn binance_fapi_url(&self) -> &'static str {
+        match self {
+            Environment::Mainnet => crate::net::binance::BINANCE_FAPI_URL,
+            Environment::Testnet | Environment::Demo => crate::net::binance::BINANCE_FAPI_TESTNET_URL,
+        }
+    }
+
+    /// Maintenance schedule (/v5/system/status); demo follows mainnet's.
+    pub fn status_url(&self) -> &'static str {
+        match self {
+            Environment::Mainnet | Environment::Demo => "https://api.bybit.com/v5/system/status",
+            Environment::Testnet => "https://api-testnet.bybit.com/v5/system/status",
+        }
+    }
+}
+
+/// Bybit hosts and paths. Hosts come from `environment` unless set here; the public path
+/// defaults to `/v5/public/{category}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndpointConfig {
+    pub environment: Environment,
+    /// Overrides the environment's host for all three streams.
+    pub ws_host: Option<String>,
+    pub public_path: Option<String>,
+    pub private_path: String,
+    pub trade_path: String,
+    /// Overrides the environment's REST base URL.
+    pub rest_url: Option<String>,
+    /// Second public connection to another host (other IPs) carrying the same topics;
+    /// `net::feed_arbiter` keeps the first copy of each message.
+    pub backup_public_host: Option<String>,
+    /// Startup race between the addresses of each host.
+    pub race: EndpointRaceConfig,
+}
+
+/// `[endpoints.race]`: `samples` TCP connects per address, each bounded by `timeout_ms`; the
+/// fastest address of each pool is dialled first (`net::resolver::EndpointCache::race`).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndpointRaceConfig {
+    pub enabled: bool,
+    pub samples: u32,
+    pub timeout_ms: u64,
+}
+
+impl Default for EndpointRaceConfig {
+    fn default() -> Self {
+        let defaults = RaceConfig::default();
+        Self { enabled: defaults.enabled, samples: defaults.samples, timeout_ms: defaults.timeout.as_millis() as u64 }
+    }
+}
+
+impl EndpointRaceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && (self.samples == 0 || self.timeout_ms == 0) {
+            return Err(format!("endpoints.race: samples and timeout_ms must be positive (got {}, {})", self.samples, self.timeout_ms));
+        }
+        Ok(())
+    }
+
+    pub fn race_config(&self) -> RaceConfig {
+        RaceConfig { enabled: self.enabled, samples: self.samples, timeout: Duration::from_millis(self.timeout_ms) }
+    }
+}
+
+impl Default for EndpointConfig {
+    fn default() -> Self {
+        Self {
+            environment: Environment::default(),
+            ws_host: None,
+            public_path: None,
+            private_path: "/v5/private".to_string(),
+            trade_path: "/v5/trade".to_string(),
+            rest_url: None,
+            backup_public_host: None,
+            race: EndpointRaceConfig::default(),
+        }
+    }
+}
+
+impl EndpointConfig {
+    pub fn public_path(&self, category: Category) -> String {
+        self.public_path.clone().unwrap_or_else(|| format!("/v5/public/{}", category.as_str()))
+    }
+
+    pub fn public_host(&self) -> &str {
+        self.ws_host.as_deref().unwrap_or(self.environment.public_ws_host())
+    }
+
+    /// Private and trade streams.
+    pub fn private_host(&self) -> &str {
+        self.ws_host.as_deref().unwrap_or(self.environment.private_ws_host())
+    }
+
+    pub fn rest_url(&self) -> &str {
+        self.rest_url.as_deref().unwrap_or(self.environment.rest_url())
+    }
+}
+
+/// OrderManager gates (`strategy::order_manager`) that make sense to tune per deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OmsConfig {
+    /// Quotes younger than this are not amended or cancelled (risk pulls are exempt).
+    pub min_quote_lifetime_ms: u64,
+    /// Share of the level ahead of us at or below which an order is at the front of the queue.
+    pub front_queue_fraction: f64,
+    /// Reprice (relative) too small to give up a front-of-queue slot for.
+    pub small_move_pct: f64,
+    /// Share of the level ahead of us at or above which an order is at the back of the queue.
+    pub back_queue_fraction: f64,
+    /// Past this age a front slot no longer holds off an amend, and a back-of-queue order is
+    /// requoted without a trigger.
+    pub stale_quote_age_ms: u64,
+    /// Start in safe mode: only position-reducing quotes pass until `reduce-only off`.
+    pub reduce_only: bool,
+}
+
+impl Default for OmsConfig {
+    fn default() -> Self {
+        let defaults = OrderManagerConfig::default();
+        Self {
+            min_quote_lifetime_ms: defaults.min_quote_lifetime.as_millis() as u64,
+            front_queue_fraction: defaults.front_queue_fraction,
+            small_move_pct: defaults.small_move_pct,
+            back_queue_fraction: defaults.back_queue_fraction,
+            stale_quote_age_ms: defaults.stale_quote_age.as_millis() as u64,
+            reduce_only: false,
+        }
+    }
+}
+
+impl OmsConfig {
+    /// Fractions within [0, 1], front not past back, a move below 100%.
+    pub fn validate(&self) -> Result<(), String> {
+        for (key, v) in [("front_queue_fraction", self.front_queue_fraction), ("back_queue_fraction", self.back_queue_fraction)] {
+            if !(0.0..=1.0).contains(&v) {
+                return Err(format!("oms.{} must be within [0, 1] (got {})", key, v));
+            }
+        }
+        if self.front_queue_fraction > self.back_queue_fraction {
+            return Err(format!("oms.front_queue_fraction {} is past back_queue_fraction {}", self.front_queue_fraction, self.back_queue_fraction));
+        }
+        if !(0.0..1.0).contains(&self.small_move_pct) {
+            return Err(format!("oms.small_move_pct must be within [0, 1) (got {})", self.small_move_pct));
+        }
+        Ok(())
+    }
+
+    pub fn order_manager(&self) -> OrderManagerConfig {
+        OrderManagerConfig {
+            min_quote_lifetime: Duration::from_millis(self.min_quote_lifetime_ms),
+            front_queue_fraction: self.front_queue_fraction,
+            small_move_pct: self.small_move_pct,
+            back_queue_fraction: self.back_queue_fraction,
+            stale_quote_age: Duration::from_millis(self.stale_quote_age_ms),
+            ..OrderManagerConfig::default()
+        }
+    }
+}
+
+/// `[watchdog]`: the Cold Thread's watch over the Hot Thread heartbeat (`ipc::watchdog`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchdogSection {
+    /// No beat for this long is a stall; 0 disables the watchdog.
+    pub stall_ms: u64,
+    /// REST cancel-all on a stall (false: alert only).
+    pub cancel_all: bool,
+    /// Heartbeat file for an external supervisor.
+    pub file: Option<String>,
+}
+
+impl Default for WatchdogSection {
+    fn default() -> Self {
+        let defaults = WatchdogConfig::default();
+        Self { stall_ms: defaults.stall.as_millis() as u64, cancel_all: defaults.cancel_all, file: defaults.file }
+    }
+}
+
+impl WatchdogSection {
+    pub fn watchdog(&self) -> WatchdogConfig {
+        WatchdogConfig {
+            enabled: self.stall_ms > 0,
+            stall: Duration::from_millis(self.stall_ms),
+            cancel_all: self.cancel_all,
+            file: self.file.clone().filter(|f| !f.is_empty()),
+            ..WatchdogConfig::default()
+        }
+    }
+}
+
+/// `[send_gate]`: order rate of every symbol slot and the per-side loop breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SendGateSection {
+    /// Creates and amends per rolling second.
+    pub max_orders_per_sec: usize,
+    pub side_throttle: SideThrottleSection,
+}
+
+impl Default for SendGateSection {
+    fn default() -> Self {
+        Self { max_orders_per_sec: SendGateConfig::default().max_orders_per_sec, side_throttle: SideThrottleSection::default() }
+    }
+}
+
+impl SendGateSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_orders_per_sec == 0 {
+            return Err("send_gate.max_orders_per_sec must be > 0".to_string());
+        }
+        self.side_throttle.validate()
+    }
+
+    pub fn send_gate(&self) -> SendGateConfig {
+        SendGateConfig { max_orders_per_sec: self.max_orders_per_sec, throttle: self.side_throttle.throttle() }
+    }
+}
+
+/// `[send_gate.side_throttle]` (`strategy::side_throttle`).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SideThrottleSection {
+    pub enabled: bool,
+    pub window_ms: u64,
+    /// Creates on one side inside the window that count as a loop.
+    pub creates: usize,
+    /// Rejects on one side inside the window that count as a loop.
+    pub rejects: usize,
+    /// First cooldown, doubled per trip in a row up to `max_cooldown_ms`.
+    pub cooldown_ms: u64,
+    pub max_cooldown_ms: u64,
+    /// Quiet time after which the next trip starts from `cooldown_ms` again.
+    pub calm_ms: u64,
+}
+
+impl Default for SideThrottleSection {
+    fn default() -> Self {
+        let defaults = ThrottleConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            window_ms: defaults.window.as_millis() as u64,
+            creates: defaults.max_creates,
+            rejects: defaults.max_rejects,
+            cooldown_ms: defaults.cooldown.as_millis() as u64,
+            max_cooldown_ms: defaults.max_cooldown.as_millis() as u64,
+            calm_ms: defaults.calm.as_millis() as u64,
+        }
+    }
+}
+
+impl SideThrottleSection {
+    /// `"off"` or `"window_ms=2000,creates=8,rejects=3,cooldown_ms=1000,max_ms=60000,calm_ms=30000"`
+    /// (HFT_SIDE_THROTTLE).
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        if spec.trim() == "off" {
+            self.enabled = false;
+            return Ok(());
+        }
+        self.enabled = true;
+        for (key, value) in spec_items(spec)? {
+            match key {
+                "window_ms" => self.window_ms = number(key, value)?,
+                "creates" => self.creates = number(key, value)?,
+                "rejects" => self.rejects = number(key, value)?,
+                "cooldown_ms" => self.cooldown_ms = number(key, value)?,
+                "max_ms" => self.max_cooldown_ms = number(key, value)?,
+                "calm_ms" => self.calm_ms = number(key, value)?,
+                _ => return Err(format!("unknown key {:?} (window_ms, creates, rejects, cooldown_ms, max_ms, calm_ms)", key)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.window_ms == 0 || self.creates == 0 || self.rejects == 0 {
+            return Err("send_gate.side_throttle: window_ms, creates and rejects must be > 0".to_string());
+        }
+        if self.cooldown_ms > self.max_cooldown_ms {
+            return Err(format!("send_gate.side_throttle.cooldown_ms {} is past max_cooldown_ms {}", self.cooldown_ms, self.max_cooldown_ms));
+        }
+        Ok(())
+    }
+
+    pub fn throttle(&self) -> ThrottleConfig {
+        ThrottleConfig {
+            enabled: self.enabled,
+            window: Duration::from_millis(self.window_ms),
+            max_creates: self.creates,
+            max_rejects: self.rejects,
+            cooldown: Duration::from_millis(self.cooldown_ms),
+            max_cooldown: Duration::from_millis(self.max_cooldown_ms),
+            calm: Duration::from_millis(self.calm_ms),
+        }
+    }
+}
+
+/// `[rest_quoting]`: quotes as signed REST calls instead of the Trade WS (`strategy::rest_quoting`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RestQuotingSection {
+    /// off | fallback (while the Trade WS is down) | always
+    pub mode: String,
+    /// Minimum time between two creates/amends of one side.
+    pub interval_ms: u64,
+    /// Open-order poll period while quoting over REST.
+    pub poll_ms: u64,
+}
+
+impl Default for RestQuotingSection {
+    fn default() -> Self {
+        let defaults = RestQuotingConfig::default();
+        Self { mode: "off".to_string(), interval_ms: defaults.interval.as_millis() as u64, poll_ms: defaults.poll.as_millis() as u64 }
+    }
+}
+
+impl RestQuotingSection {
+    /// `"off"`, `"fallback"` or `"always"`, optionally with overrides:
+    /// `"fallback,interval_ms=1000,poll_ms=2000"` (HFT_REST_QUOTING).
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            if RestQuotingMode::from_spec(part).is_some() {
+                self.mode = part.to_string();
+                continue;
+            }
+            let (key, value) = item(part)?;
+            match key {
+                "interval_ms" => self.interval_ms = number(key, value)?,
+                "poll_ms" => self.poll_ms = number(key, value)?,
+                _ => return Err(format!("unknown key {:?} (off|fallback|always, interval_ms, poll_ms)", key)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.mode()?;
+        if self.interval_ms == 0 || self.poll_ms == 0 {
+            return Err("rest_quoting: interval_ms and poll_ms must be > 0".to_string());
+        }
+        Ok(())
+    }
+
+    fn mode(&self) -> Result<RestQuotingMode, String> {
+        RestQuotingMode::from_spec(&self.mode).ok_or_else(|| format!("rest_quoting.mode {:?} (off|fallback|always)", self.mode))
+    }
+
+    pub fn rest_quoting(&self) -> RestQuotingConfig {
+        RestQuotingConfig {
+            mode: self.mode().unwrap_or(RestQuotingMode::Off),
+            interval: Duration::from_millis(self.interval_ms),
+            poll: Duration::from_millis(self.poll_ms),
+        }
+    }
+}
+
+/// `[feed_watch]`: longest silence per connection before quotes are pulled and it is
+/// reconnected (`net::feed_watch`).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedWatchSection {
+    pub enabled: bool,
+    pub public_ms: u64,
+    pub private_ms: u64,
+    pub trade_ms: u64,
+}
+
+impl Default for FeedWatchSection {
+    fn default() -> Self {
+        let defaults = FeedWatchConfig::default();
+        let ms = |conn: Conn| defaults.silence[conn as usize].as_millis() as u64;
+        Self { enabled: defaults.enabled, public_ms: ms(Conn::Public), private_ms: ms(Conn::Private), trade_ms: ms(Conn::Trade) }
+    }
+}
+
+impl FeedWatchSection {
+    /// `"off"` or limits in ms like `"public=3000,trade=60000"` (HFT_FEED_WATCH).
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        if spec.trim() == "off" {
+            self.enabled = false;
+            return Ok(());
+        }
+        self.enabled = true;
+        for (key, value) in spec_items(spec)? {
+            match key {
+                "public" => self.public_ms = number(key, value)?,
+                "private" => self.private_ms = number(key, value)?,
+                "trade" => self.trade_ms = number(key, value)?,
+                _ => return Err(format!("unknown connection {:?} (public, private, trade)", key)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && [self.public_ms, self.private_ms, self.trade_ms].contains(&0) {
+            return Err("feed_watch: public_ms, private_ms and trade_ms must be > 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn feed_watch(&self) -> FeedWatchConfig {
+        FeedWatchConfig { enabled: self.enabled, silence: [self.public_ms, self.private_ms, self.trade_ms].map(Duration::from_millis) }
+    }
+}
+
+/// `[shadow]`: candidate params run on paper next to the live strategy (`strategy::shadow`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShadowSection {
+    /// Overrides of the live [quote] params, `"min_spread=0.003,qty=0.6"`; None = no shadow.
+    pub quote: Option<String>,
+    pub report_min: u64,
+}
+
+impl Default for ShadowSection {
+    fn default() -> Self {
+        Self { quote: None, report_min: shadow::DEFAULT_REPORT_SECS / 60 }
+    }
+}
+
+impl ShadowSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(spec) = &self.quote {
+            QuoteParams::default().apply_spec(spec).map_err(|e| format!("shadow.quote: {}", e))?;
+        }
+        if self.report_min == 0 {
+            return Err("shadow.report_min must be > 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn report_every(&self) -> Duration {
+        Duration::from_secs(self.report_min * 60)
+    }
+}
+
+/// `[experiment]`: A/B slices on the primary symbol, A = the live params (`strategy::experiment`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExperimentSection {
+    /// Overrides of A for variant B, `"min_spread=0.003,requote=0.0015"`; None = no experiment.
+    pub variant_b: Option<String>,
+    pub slice_min: u64,
+}
+
+impl Default for ExperimentSection {
+    fn default() -> Self {
+        Self { variant_b: None, slice_min: ExperimentConfig::default().slice.as_secs() / 60 }
+    }
+}
+
+impl ExperimentSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(spec) = &self.variant_b {
+            QuoteParams::default().apply_spec(spec).map_err(|e| format!("experiment.variant_b: {}", e))?;
+        }
+        if self.slice_min == 0 {
+            return Err("experiment.slice_min must be > 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn slice(&self) -> Duration {
+        Duration::from_secs(self.slice_min * 60)
+    }
+}
+
+/// `[margin]`: pre-trade margin check of the send gates (`strategy::margin`).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarginSection {
+    /// Until the position topic reports a symbol's leverage.
+    pub leverage: f64,
+    /// Fraction of the available balance never committed to new orders.
+    pub buffer: f64,
+}
+
+impl Default for MarginSection {
+    fn default() -> Self {
+        let defaults = MarginConfig::default();
+        Self { leverage: defaults.leverage, buffer: defaults.buffer }
+    }
+}
+
+impl MarginSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.leverage.is_nan() || self.leverage <= 0.0 {
+            return Err(format!("margin.leverage must be > 0 (got {})", self.leverage));
+        }
+        if !(0.0..1.0).contains(&self.buffer) {
+            return Err(format!("margin.buffer must be within [0, 1) (got {})", self.buffer));
+        }
+        Ok(())
+    }
+
+    pub fn margin(&self) -> MarginConfig {
+        MarginConfig { leverage: self.leverage, buffer: self.buffer }
+    }
+}
+
+/// `[obligation]`: uptime of an exchange market-making program per symbol (`strategy::obligation`).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObligationSection {
+    pub enabled: bool,
+    /// Widest bid/ask distance that still counts, bps of the mid.
+    pub max_spread_bps: f64,
+    /// Smallest quote per side that counts (base coin).
+    pub min_qty: f64,
+    /// Program threshold; a closed window below it alerts.
+    pub min_uptime: f64,
+}
+
+impl Default for ObligationSection {
+    fn default() -> Self {
+        let defaults = ObligationConfig::default();
+        Self { enabled: defaults.enabled, max_spread_bps: defaults.max_spread_bps, min_qty: defaults.min_qty, min_uptime: defaults.min_uptime }
+    }
+}
+
+impl ObligationSection {
+    /// `"off"`, `"on"` or `"spread_bps=20,min_qty=5,uptime=0.9"` (HFT_MM_OBLIGATION); anything
+    /// but `"off"` enables tracking.
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        self.enabled = spec.trim() != "off";
+        if matches!(spec.trim(), "on" | "off") {
+            return Ok(());
+        }
+        for (key, value) in spec_items(spec)? {
+            match key {
+                "spread_bps" => self.max_spread_bps = number(key, value)?,
+                "min_qty" => self.min_qty = number(key, value)?,
+                "uptime" => self.min_uptime = number(key, value)?,
+                _ => return Err(format!("unknown key {:?} (on, off, spread_bps, min_qty, uptime)", key)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_spread_bps.is_nan() || self.max_spread_bps <= 0.0 || self.min_qty.is_nan() || self.min_qty < 0.0 {
+            return Err(format!("obligation: max_spread_bps must be > 0 and min_qty >= 0 (got {}, {})", self.max_spread_bps, self.min_qty));
+        }
+        if !(0.0..=1.0).contains(&self.min_uptime) {
+            return Err(format!("obligation.min_uptime must be within [0, 1] (got {})", self.min_uptime));
+        }
+        Ok(())
+    }
+
+    pub fn obligation(&self) -> ObligationConfig {
+        ObligationConfig { enabled: self.enabled, max_spread_bps: self.max_spread_bps, min_qty: self.min_qty, min_uptime: self.min_uptime }
+    }
+}
+
+/// `[startup]`: what happens before the first quote.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StartupSection {
+    /// DNS to first snapshot per connection; above it the startup report is an alert.
+    pub budget_ms: u64,
+    /// Leftover orders cancelled at start: symbols (every configured symbol) | settle (the
+    /// whole settle coin).
+    pub cancel_scope: String,
+}
+
+impl Default for StartupSection {
+    fn default() -> Self {
+        Self { budget_ms: StartupConfig::default().budget.as_millis() as u64, cancel_scope: "symbols".to_string() }
+    }
+}
+
+impl StartupSection {
+    pub fn validate(&self) -> Result<(), String> {
+        self.cancel_scope()?;
+        Ok(())
+    }
+
+    pub fn cancel_scope(&self) -> Result<CancelScope, String> {
+        CancelScope::from_spec(self.cancel_scope.trim()).ok_or_else(|| format!("startup.cancel_scope {:?} (symbols|settle)", self.cancel_scope))
+    }
+
+    pub fn startup(&self) -> StartupConfig {
+        StartupConfig { budget: Duration::from_millis(self.budget_ms) }
+    }
+}
+
+/// `[metrics]`: alert thresholds of the Hot Thread metrics.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsSection {
+    /// p99 book parse + update above this alerts.
+    pub book_budget_ns: u64,
+    /// Requests per second per op the egress headroom is measured against (`create`,
+    /// `amend`, `cancel`, `cancel_all`, `trading_stop`, ...); ops left out keep Bybit's default.
+    pub egress_limits: BTreeMap<String, u32>,
+    /// Egress headroom under this is flagged in the report.
+    pub egress_warn_headroom: f64,
+}
+
+impl Default for MetricsSection {
+    fn default() -> Self {
+        Self { book_budget_ns: book_latency::DEFAULT_BUDGET_NS, egress_limits: BTreeMap::new(), egress_warn_headroom: EgressConfig::default().warn_headroom }
+    }
+}
+
+impl MetricsSection {
+    /// Overrides like `"create=20,amend=20,warn=0.3"` (HFT_EGRESS_LIMITS), for accounts on a
+    /// raised tier.
+    pub fn apply_egress_spec(&mut self, spec: &str) -> Result<(), String> {
+        for (key, value) in spec_items(spec)? {
+            match key {
+                "warn" => self.egress_warn_headroom = number(key, value)?,
+                op => {
+                    self.egress_limits.insert(op.to_string(), number(key, value)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(op) = self.egress_limits.keys().find(|op| EgressOp::from_name(op).is_none()) {
+            return Err(format!("metrics.egress_limits: unknown op {:?}", op));
+        }
+        if !(0.0..=1.0).contains(&self.egress_warn_headroom) {
+            return Err(format!("metrics.egress_warn_headroom must be within [0, 1] (got {})", self.egress_warn_headroom));
+        }
+        Ok(())
+    }
+
+    pub fn egress(&self) -> EgressConfig {
+        let mut config = EgressConfig { warn_headroom: self.egress_warn_headroom, ..EgressConfig::default() };
+        for (name, limit) in &self.egress_limits {
+            if let Some(op) = EgressOp::from_name(name) {
+                config.limits[op as usize] = *limit;
+            }
+        }
+        config
+    }
+}
+
+/// `[records]`: files the Drop-Copy and Cold threads write. An empty path turns one off.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordsSection {
+    /// Every order and execution, JSON lines (`ipc::drop_copy`).
+    pub drop_copy_file: String,
+    /// Optional TCP sink for the same lines (`host:port`).
+    pub drop_copy_addr: Option<String>,
+    /// One file per incident (halt trips, adverse fills).
+    pub incident_dir: String,
+    /// A fill this far through the mid (bps) is an incident.
+    pub adverse_fill_bps: f64,
+    /// Liquidity heatmap CSV and its sampling period.
+    pub heatmap_file: String,
+    pub heatmap_interval_ms: u64,
+    /// Exchange error-code history, read back at start.
+    pub error_stats_file: String,
+    pub error_report_days: u64,
+}
+
+impl Default for RecordsSection {
+    fn default() -> Self {
+        let (drop_copy, incident, heatmap, errors) =
+            (DropCopyConfig::default(), IncidentConfig::default(), HeatmapConfig::default(), ErrorStatsConfig::default());
+        Self {
+            drop_copy_file: drop_copy.file.unwrap_or_default(),
+            drop_copy_addr: drop_copy.tcp_addr,
+            incident_dir: incident.dir.unwrap_or_default(),
+            adverse_fill_bps: incident.adverse_fill_bps,
+            heatmap_file: heatmap.file.unwrap_or_default(),
+            heatmap_interval_ms: heatmap.interval.as_millis() as u64,
+            error_stats_file: errors.file.unwrap_or_default(),
+            error_report_days: errors.report_days,
+        }
+    }
+}
+
+// "" = off
+fn file_or_off(file: &str) -> Option<String> {
+    (!file.is_empty()).then(|| file.to_string())
+}
+
+impl RecordsSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.heatmap_interval_ms == 0 || self.error_report_days == 0 {
+            return Err("records: heatmap_interval_ms and error_report_days must be > 0".to_string());
+        }
+        if self.adverse_fill_bps.is_nan() || self.adverse_fill_bps <= 0.0 {
+            return Err(format!("records.adverse_fill_bps must be > 0 (got {})", self.adverse_fill_bps));
+        }
+        Ok(())
+    }
+
+    pub fn drop_copy(&self) -> DropCopyConfig {
+        DropCopyConfig { file: file_or_off(&self.drop_copy_file), tcp_addr: self.drop_copy_addr.clone(), ..DropCopyConfig::default() }
+    }
+
+    pub fn incidents(&self) -> IncidentConfig {
+        IncidentConfig { dir: file_or_off(&self.incident_dir), adverse_fill_bps: self.adverse_fill_bps }
+    }
+
+    pub fn heatmap(&self) -> HeatmapConfig {
+        HeatmapConfig { file: file_or_off(&self.heatmap_file), interval: Duration::from_millis(self.heatmap_interval_ms) }
+    }
+
+    pub fn error_stats(&self) -> ErrorStatsConfig {
+        ErrorStatsConfig { file: file_or_off(&self.error_stats_file), report_days: self.error_report_days }
+    }
+}
+
+/// `[pnl_audit]`: reconcile of execution-stream fees and PnL against the transaction log.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PnlAuditSection {
+    /// Window length, one statement pull per window; 0 disables the audit.
+    pub interval_ms: u64,
+    /// Largest difference per component (settle coin) that still matches.
+    pub tolerance: f64,
+}
+
+impl Default for PnlAuditSection {
+    fn default() -> Self {
+        let defaults = PnlAuditConfig::default();
+        Self { interval_ms: defaults.interval_ms, tolerance: defaults.tolerance }
+    }
+}
+
+impl PnlAuditSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tolerance.is_nan() || self.tolerance < 0.0 {
+            return Err(format!("pnl_audit.tolerance must be >= 0 (got {})", self.tolerance));
+        }
+        Ok(())
+    }
+
+    pub fn pnl_audit(&self) -> PnlAuditConfig {
+        PnlAuditConfig { enabled: self.interval_ms > 0, interval_ms: self.interval_ms, tolerance: self.tolerance, ..PnlAuditConfig::default() }
+    }
+}
+
+/// `[venues]`: connections besides Bybit, and the Binance hedge.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VenuesSection {
+    /// Binance bookTicker reference quotes (symbols with a binance_symbol).
+    pub binance_book: bool,
+    /// Binance user data stream (hedge fills, account updates).
+    pub binance_user: bool,
+    /// OKX books5 reference books (symbols with an okx_symbol).
+    pub okx: bool,
+    /// Bybit executions hedged on Binance, scaled by this ratio; 0 = no hedging.
+    pub hedge_ratio: f64,
+    /// Share of every Binance rate limit the hedge client keeps free.
+    pub hedge_headroom: f64,
+}
+
+impl Default for VenuesSection {
+    fn default() -> Self {
+        Self { binance_book: false, binance_user: false, okx: true, hedge_ratio: 0.0, hedge_headroom: 0.2 }
+    }
+}
+
+impl VenuesSection {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hedge_ratio.is_nan() || self.hedge_ratio < 0.0 {
+            return Err(format!("venues.hedge_ratio must be >= 0 (got {})", self.hedge_ratio));
+        }
+        if !(0.0..1.0).contains(&self.hedge_headroom) {
+            return Err(format!("venues.hedge_headroom must be within [0, 1) (got {})", self.hedge_headroom));
+        }
+        Ok(())
+    }
+}
+
+/// `[strategy]`: controllers of every symbol's MarketMaker beyond the [quote] params.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategySection {
+    /// Max |touch / last trade - 1| the strategy acts on; 0 turns the guard off.
+    pub last_price_band: f64,
+    /// Oldest lot age before the exit joins the touch / closes at market; None = the preset's.
+    pub exit_join_ms: Option<u64>,
+    pub exit_cross_ms: Option<u64>,
+    /// market | limit-first
+    pub close_mode: String,
+    /// limit-first: time at the touch before the market close.
+    pub close_limit_ms: u64,
+    /// Widen and shrink after fills on both sides.
+    pub fade: bool,
+    pub fade_cooldown_ms: u64,
+    /// Reduced size after a start, reconnect or resync.
+    pub warmup: bool,
+    pub warmup_ms: u64,
+    pub warmup_fraction: f64,
+    /// Oldest a resting quote may get.
+    pub requote_max_age_ms: u64,
+    /// Drift, as a fraction of the quoted spread, that refreshes a fresh quote.
+    pub requote_decay: f64,
+    /// off | same-ts | coalesce=<us>; None = the built-in policy.
+    pub batch_policy: Option<String>,
+    /// Random requote delay and price offset.
+    pub jitter: bool,
+    /// Quote size drawn from base * [1 - band, 1 + band]; 0 = constant size.
+    pub size_band: f64,
+    /// 1-in-N sampling per log category (`requote = 1`, `batch = 0`); categories left out
+    /// keep the built-in rate.
+    pub log_sampling: BTreeMap<String, u32>,
+}
+
+impl Default for StrategySection {
+    fn default() -> Self {
+        let (close, fade, warmup, requote) =
+            (CloseConfig::default(), FadeConfig::default(), WarmupConfig::default(), RequoteConfig::default());
+        Self {
+            last_price_band: LastPriceConfig::default().max_deviation,
+            exit_join_ms: None,
+            exit_cross_ms: None,
+            close_mode: "market".to_string(),
+            close_limit_ms: close.limit_timeout.as_millis() as u64,
+            fade: fade.enabled,
+            fade_cooldown_ms: fade.cooldown.as_millis() as u64,
+            warmup: warmup.enabled,
+            warmup_ms: warmup.duration.as_millis() as u64,
+            warmup_fraction: warmup.start_fraction,
+            requote_max_age_ms: requote.max_age.as_millis() as u64,
+            requote_decay: requote.decay,
+            batch_policy: None,
+            jitter: JitterConfig::default().enabled,
+            size_band: JitterConfig::default().size_band,
+            log_sampling: BTreeMap::new(),
+        }
+    }
+}
+
+impl StrategySection {
+    /// `"requote=1,batch=0,wall=10"` (HFT_LOG_SAMPLING) on top of `log_sampling`.
+    pub fn apply_log_spec(&mut self, spec: &str) -> Result<(), String> {
+        for (key, value) in spec_items(spec)? {
+            self.log_sampling.insert(key.to_string(), number(key, value)?);
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.close_mode()?;
+        self.batch_policy()?;
+        self.log_sampling()?;
+        if self.last_price_band.is_nan() || self.last_price_band < 0.0 {
+            return Err(format!("strategy.last_price_band must be >= 0 (got {})", self.last_price_band));
+        }
+        if !(0.0..=1.0).contains(&self.warmup_fraction) || self.warmup_fraction == 0.0 {
+            return Err(format!("strategy.warmup_fraction must be within (0, 1] (got {})", self.warmup_fraction));
+        }
+        if !(0.0..1.0).contains(&self.size_band) {
+            return Err(format!("strategy.size_band must be within [0, 1) (got {})", self.size_band));
+        }
+        if self.requote_decay.is_nan() || self.requote_decay < 0.0 {
+            return Err(format!("strategy.requote_decay must be >= 0 (got {})", self.requote_decay));
+        }
+        if let (Some(join), Some(cross)) = (self.exit_join_ms, self.exit_cross_ms) {
+            if join > cross {
+                return Err(format!("strategy.exit_join_ms {} is past exit_cross_ms {}", join, cross));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn close_mode(&self) -> Result<CloseOrderType, String> {
+        CloseOrderType::from_spec(&self.close_mode).ok_or_else(|| format!("strategy.close_mode {:?} (market|limit-first)", self.close_mode))
+    }
+
+    pub fn batch_policy(&self) -> Result<Option<BatchPolicy>, String> {
+        self.batch_policy.as_deref().map(|spec| {
+            BatchPolicy::from_spec(spec).ok_or_else(|| format!("strategy.batch_policy {:?} (off|same-ts|coalesce=<us>)", spec))
+        }).transpose()
+    }
+
+    /// The built-in sampling with `log_sampling` on top.
+    pub fn log_sampling(&self) -> Result<LogSampling, String> {
+        let mut sampling = LogSampling::default();
+        for (name, every) in &self.log_sampling {
+            let category = LogCategory::from_name(name)
+                .ok_or_else(|| format!("strategy.log_sampling: unknown category {:?} (batch, requote, impulse, wall, position, mode)", name))?;
+            sampling.set(category, *every);
+        }
+        Ok(sampling)
+    }
+}
+
+// "key=value" items of an HFT_* spec, trimmed. An item without '=' is an error.
+fn spec_items(spec: &str) -> Result<Vec<(&str, &str)>, String> {
+    spec.split(',').map(str::trim).filter(|s| !s.is_empty()).map(item).collect()
+}
+
+fn item(part: &str) -> Result<(&str, &str), String> {
+    part.split_once('=').map(|(k, v)| (k.trim(), v.trim())).ok_or_else(|| format!("bad item {:?} (key=value)", part))
+}
+
+fn number<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    parse(value).map_err(|e| format!("{}: {}", key, e))
+}
+
+// One HFT_* variable into `field`, if set; the error names the variable.
+fn set<T>(var: &impl Fn(&str) -> Option<String>, name: &str, field: &mut T, parse: impl FnOnce(&str) -> Result<T, String>) -> Result<(), String> {
+    if let Some(value) = var(name) {
+        *field = parse(value.trim()).map_err(|e| format!("{}={:?}: {}", name, value, e))?;
+    }
+    Ok(())
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("bad number {:?}", value))
+}
+
+// on/off switch of an HFT_* variable
+fn flag(value: &str) -> Result<bool, String> {
+    match value {
+        "1" | "true" | "on" => Ok(true),
+        "0" | "false" | "off" => Ok(false),
+        _ => Err(format!("bad switch {:?} (on|off)", value)),
+    }
+}
+
+/// Certificate pinning of the WS hosts (`net::tls_client::CertPins`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Host -> base64 SHA-256 of the SubjectPublicKeyInfo of its leaf, an intermediate or the
+    /// root; one match on the verified path is enough. List a backup key so a rotation doesn't lock us out.
+    pub pins: BTreeMap<String, Vec<String>>,
+}
+
+impl TlsConfig {
+    pub fn cert_pins(&self) -> Result<CertPins, String> {
+        CertPins::new(&self.pins)
+    }
+}
+
+/// Which core each thread is pinned to. None = left to the OS scheduler. The Hot Thread
+/// should own its core: nothing else is placed there unless the map says so.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PinningConfig {
+    /// mio loop and strategy.
+    pub hot: Option<usize>,
+    /// Logger, network and maintenance monitors.
+    pub cold: Option<usize>,
+    /// Blocking REST calls; mostly idle, unpinned by default.
+    pub rest: Option<usize>,
+    pub drop_copy: Option<usize>,
+    /// Cores reserved for per-connection network threads (not split out yet): validated and
+    /// kept free of the other threads, nothing runs there today.
+    pub network: Vec<usize>,
+    /// Hot and network cores are expected in the kernel's isolated set (`isolcpus`); a warning
+    /// at startup if they are not.
+    pub require_isolated: bool,
+}
+
+impl Default for PinningConfig {
+    fn default() -> Self {
+        Self { hot: Some(0), cold: Some(1), rest: None, drop_copy: None, network: Vec::new(), require_isolated: false }
+    }
+}
+
+impl PinningConfig {
+    /// Nothing pinned (`--core-pinning off`).
+    pub fn unpinned() -> Self {
+        Self { hot: None, cold: None, ..Self::default() }
+    }
+
+    /// "hot=2,cold=3,rest=4,drop_copy=5,network=6+7,isolated=1" (`HFT_PINNING`); `off` unpins
+    /// one thread.
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        let core = |key: &str, value: &str| if value == "off" { Ok(None) } else { number(key, value).map(Some) };
+        for (key, value) in spec_items(spec)? {
+            match key {
+                "hot" => self.hot = core(key, value)?,
+                "cold" => self.cold = core(key, value)?,
+                "rest" => self.rest = core(key, value)?,
+                "drop_copy" => self.drop_copy = core(key, value)?,
+                "network" => self.network = value.split('+').map(|c| number(key, c.trim())).collect::<Result<_, _>>()?,
+                "isolated" => self.require_isolated = flag(value).map_err(|e| format!("{}: {}", key, e))?,
+                _ => return Err(format!("unknown thread {:?} (hot, cold, rest, drop_copy, network, isolated)", key)),
+            }
+        }
+        Ok(())
+    }
+
+    /// The map checked against the cores that exist (`available`) and the isolated ones.
+    /// Missing cores are dropped (that thread runs unpinned); threads sharing the hot core and
+    /// hot/network cores outside `isolated` only warn. Returns the usable map and the warnings.
+    pub fn resolve(&self, available: &[usize], isolated: &[usize]) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut check = |name: &str, core: Option<usize>| match core {
+            Some(id) if !available.contains(&id) => {
+                warnings.push(format!("{} core {} not present (cores {:?}), {} runs unpinned", name, id, available, name));
+                None
+            }
+            core => core,
+        };
+        let mut plan = Self {
+            hot: check("hot", self.hot),
+            cold: check("cold", self.cold),
+            rest: check("rest", self.rest),
+            drop_copy: check("drop_copy", self.drop_copy),
+            network: self.network.iter().filter_map(|&id| check("network", Some(id))).collect(),
+            require_isolated: self.require_isolated,
+        };
+        if let Some(hot) = plan.hot {
+            for (name, core) in [("cold", plan.cold), ("rest", plan.rest), ("drop_copy", plan.drop_copy)] {
+                if core == Some(hot) {
+                    warnings.push(format!("{} shares the hot core {}", name, hot));
+                }
+            }
+            if plan.network.contains(&hot) {
+                warnings.push(format!("network cores {:?} include the hot core {}", plan.network, hot));
+                plan.network.retain(|&id| id != hot);
+            }
+        }
+        if self.require_isolated {
+            let wanted = plan.hot.into_iter().chain(plan.network.iter().copied());
+            let missing: Vec<usize> = wanted.filter(|id| !isolated.contains(id)).collect();
+            if !missing.is_empty() {
+                warnings.push(format!("cores {:?} requested isolated but the kernel isolates {:?} (boot with isolcpus=)", missing, isolated));
+            }
+        }
+        (plan, warnings)
+    }
+}
+
+/// Cores in the kernel's isolated set; empty when there is none or it cannot be read (non-Linux).
+pub fn isolated_cores() -> Vec<usize> {
+    fs::read_to_string(ISOLATED_CPUS_FILE).map(|list| parse_cpu_list(&list)).unwrap_or_default()
+}
+
+/// "0-2,5" -> [0, 1, 2, 5]. Bad items are skipped.
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cores = Vec::new();
+    for item in list.trim().split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match item.split_once('-') {
+            Some((from, to)) => {
+                if let (Ok(from), Ok(to)) = (from.parse::<usize>(), to.parse::<usize>()) {
+                    cores.extend(from..=to);
+                }
+            }
+            None => cores.extend(item.parse::<usize>().ok()),
+        }
+    }
+    cores
+}
+
+impl EngineConfig {
+    /// Parses `path`. Ok(None) if the file does not exist.
+    pub fn load(path: &str) -> Result<Option<Self>, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Cannot read {}: {}", path, e)),
+        };
+        let config: Self = toml::from_str(&text).map_err(|e| format!("Bad config {}: {}", path, e))?;
+        config.validate().map_err(|e| format!("Bad config {}: {}", path, e))?;
+        Ok(Some(config))
+    }
+
+    /// Every section checked; `load` refuses a file that fails, `apply_env` the overrides.
+    pub fn validate(&self) -> Result<(), String> {
+        self.instrument.validate()?;
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            symbol.instrument.validate().map_err(|e| format!("symbols[{}]: {}", i, e))?;
+        }
+        self.endpoints.race.validate()?;
+        self.oms.validate()?;
+        self.tls.cert_pins().map_err(|e| format!("tls.pins: {}", e))?;
+        self.startup.validate()?;
+        self.venues.validate()?;
+        self.send_gate.validate()?;
+        self.rest_quoting.validate()?;
+        self.margin.validate()?;
+        self.obligation.validate()?;
+        self.strategy.validate()?;
+        self.experiment.validate()?;
+        self.shadow.validate()?;
+        self.pnl_audit.validate()?;
+        self.feed_watch.validate()?;
+        self.metrics.validate()?;
+        self.records.validate()
+    }
+
+    /// `HFT_*` overrides on top of the file, read through `var` (`std::env::var(..).ok()` in the
+    /// binary). A value that does not parse is an error naming the variable, not a silent
+    /// fallback; the result is validated like a loaded file.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        let env = |name: &str, apply: &mut dyn FnMut(&str) -> Result<(), String>| match var(name) {
+            Some(value) => apply(value.trim()).map_err(|e| format!("{}={:?}: {}", name, value, e)),
+            None => Ok(()),
+        };
+        set(&var, "HFT_PRESET", &mut self.preset, |v| {
+            Preset::from_spec(v).map(Some).ok_or_else(|| "conservative|normal|aggressive".to_string())
+        })?;
+        set(&var, "HFT_ENVIRONMENT", &mut self.endpoints.environment, |v| {
+            Environment::from_spec(v).ok_or_else(|| "mainnet|testnet|demo".to_string())
+        })?;
+        set(&var, "HFT_CATEGORY", &mut self.instrument.category, |v| Ok(v.to_string()))?;
+        // an exchange rotated to a key nobody pinned yet
+        set(&var, "HFT_TLS_PINS", &mut self.tls.pins, |v| match v {
+            "off" => Ok(BTreeMap::new()),
+            _ => Err("off is the only value".to_string()),
+        })?;
+        set(&var, "HFT_RECV_WINDOW_MS", &mut self.time.recv_window_ms, parse)?;
+        set(&var, "HFT_REDUCE_ONLY", &mut self.oms.reduce_only, flag)?;
+        env("HFT_PINNING", &mut |v| self.pinning.apply_spec(v))?;
+        set(&var, "HFT_CANCEL_SCOPE", &mut self.startup.cancel_scope, |v| Ok(v.to_string()))?;
+        set(&var, "HFT_STARTUP_BUDGET_MS", &mut self.startup.budget_ms, parse)?;
+
+        set(&var, "HFT_BINANCE_BOOK", &mut self.venues.binance_book, flag)?;
+        set(&var, "HFT_BINANCE_USER", &mut self.venues.binance_user, flag)?;
+        set(&var, "HFT_OKX", &mut self.venues.okx, flag)?;
+        set(&var, "HFT_BINANCE_HEDGE", &mut self.venues.hedge_ratio, |v| Ok(if v == "off" { 0.0 } else { parse(v)? }))?;
+        set(&var, "HFT_BINANCE_HEADROOM", &mut self.venues.hedge_headroom, parse)?;
+
+        set(&var, "HFT_WATCHDOG_MS", &mut self.watchdog.stall_ms, parse)?;
+        set(&var, "HFT_WATCHDOG_CANCEL", &mut self.watchdog.cancel_all, flag)?;
+        set(&var, "HFT_WATCHDOG_FILE", &mut self.watchdog.file, |v| Ok(Some(v.to_string())))?;
+
+        set(&var, "HFT_MAX_ORDERS_PER_SEC", &mut self.send_gate.max_orders_per_sec, parse)?;
+        env("HFT_SIDE_THROTTLE", &mut |v| self.send_gate.side_throttle.apply_spec(v))?;
+        env("HFT_REST_QUOTING", &mut |v| self.rest_quoting.apply_spec(v))?;
+        set(&var, "HFT_LEVERAGE", &mut self.margin.leverage, parse)?;
+        set(&var, "HFT_MARGIN_BUFFER", &mut self.margin.buffer, parse)?;
+        env("HFT_MM_OBLIGATION", &mut |v| self.obligation.apply_spec(v))?;
+
+        let strategy = &mut self.strategy;
+        set(&var, "HFT_LAST_PRICE_BAND", &mut strategy.last_price_band, parse)?;
+        set(&var, "HFT_EXIT_JOIN_MS", &mut strategy.exit_join_ms, |v| Ok(Some(parse(v)?)))?;
+        set(&var, "HFT_EXIT_CROSS_MS", &mut strategy.exit_cross_ms, |v| Ok(Some(parse(v)?)))?;
+        set(&var, "HFT_CLOSE_MODE", &mut strategy.close_mode, |v| Ok(v.to_string()))?;
+        set(&var, "HFT_CLOSE_LIMIT_MS", &mut strategy.close_limit_ms, parse)?;
+        set(&var, "HFT_QUOTE_FADE", &mut strategy.fade, flag)?;
+        set(&var, "HFT_FADE_COOLDOWN_MS", &mut strategy.fade_cooldown_ms, parse)?;
+        set(&var, "HFT_QUOTE_WARMUP", &mut strategy.warmup, flag)?;
+        set(&var, "HFT_WARMUP_MS", &mut strategy.warmup_ms, parse)?;
+        set(&var, "HFT_WARMUP_FRACTION", &mut strategy.warmup_fraction, parse)?;
+        set(&var, "HFT_REQUOTE_MAX_AGE_MS", &mut strategy.requote_max_age_ms, parse)?;
+        set(&var, "HFT_REQUOTE_DECAY", &mut strategy.requote_decay, parse)?;
+        set(&var, "HFT_BATCH_POLICY", &mut strategy.batch_policy, |v| Ok(Some(v.to_string())))?;
+        set(&var, "HFT_QUOTE_JITTER", &mut strategy.jitter, flag)?;
+        set(&var, "HFT_SIZE_BAND", &mut strategy.size_band, parse)?;
+        env("HFT_LOG_SAMPLING", &mut |v| strategy.apply_log_spec(v))?;
+
+        set(&var, "HFT_AB_VARIANT_B", &mut self.experiment.variant_b, |v| Ok(Some(v.to_string())))?;
+        set(&var, "HFT_AB_SLICE_MIN", &mut self.experiment.slice_min, parse)?;
+        set(&var, "HFT_SHADOW", &mut self.shadow.quote, |v| Ok(Some(v.to_string())))?;
+        set(&var, "HFT_SHADOW_REPORT_MIN", &mut self.shadow.report_min, parse)?;
+        set(&var, "HFT_PNL_AUDIT_MS", &mut self.pnl_audit.interval_ms, parse)?;
+        set(&var, "HFT_PNL_AUDIT_TOLERANCE", &mut self.pnl_audit.tolerance, parse)?;
+        env("HFT_FEED_WATCH", &mut |v| self.feed_watch.apply_spec(v))?;
+        set(&var, "HFT_BOOK_BUDGET_NS", &mut self.metrics.book_budget_ns, parse)?;
+        env("HFT_EGRESS_LIMITS", &mut |v| self.metrics.apply_egress_spec(v))?;
+
+        // "off" turns a file off, as an empty path does in the config
+        let file = |v: &str| if v == "off" { String::new() } else { v.to_string() };
+        let records = &mut self.records;
+        set(&var, "HFT_DROPCOPY_FILE", &mut records.drop_copy_file, |v| Ok(file(v)))?;
+        set(&var, "HFT_DROPCOPY_ADDR", &mut records.drop_copy_addr, |v| Ok(Some(v.to_string())))?;
+        set(&var, "HFT_INCIDENT_DIR", &mut records.incident_dir, |v| Ok(file(v)))?;
+        set(&var, "HFT_ADVERSE_FILL_BPS", &mut records.adverse_fill_bps, parse)?;
+        set(&var, "HFT_HEATMAP_FILE", &mut records.heatmap_file, |v| Ok(file(v)))?;
+        set(&var, "HFT_HEATMAP_MS", &mut records.heatmap_interval_ms, parse)?;
+        set(&var, "HFT_ERROR_STATS_FILE", &mut records.error_stats_file, |v| Ok(file(v)))?;
+        set(&var, "HFT_ERROR_REPORT_DAYS", &mut records.error_report_days, parse)?;
+        self.validate()
+    }
+
+    /// `explicit` if given (must exist), else `config.toml` if present, else defaults.
+    /// Returns the config and the file it came from.
+    pub fn locate(explicit: Option<&str>) -> Result<(Self, Option<String>), String> {
+        let path = explicit.unwrap_or(DEFAULT_CONFIG_FILE);
+        match Self::load(path)? {
+            Some(config) => Ok((config, Some(path.to_string()))),
+            None if explicit.is_some() => Err(format!("Config file {} not found", path)),
+            None => Ok((Self::default(), None)),
+        }
+    }
+
+    /// Applies `preset` to [quote] and to every symbol's own quote table, once: it scales
+    /// the clips. Later hold-time overrides go on the strategies (`Preset::aging`).
+    pub fn apply_preset(&mut self, preset: Preset) {
+        self.preset = Some(preset);
+        preset.apply(&mut self.quote);
+        for symbol in &mut self.symbols {
+            if let Some(quote) = symbol.quote.as_mut() {
+                preset.apply(quote);
+            }
+        }
+    }
+
+    /// `locate` with `HFT_CONFIG`, then the `HFT_*` overrides.
+    pub fn from_env() -> Result<(Self, Option<String>), String> {
+        let (mut config, path) = Self::locate(std::env::var("HFT_CONFIG").ok().as_deref())?;
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok((config, path))
+    }
+}
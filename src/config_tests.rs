@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use crate::config::{parse_cpu_list, EngineConfig, Environment, PinningConfig, SymbolConfig};
+    use crate::ipc::egress::EgressOp;
+    use crate::ipc::session::Conn;
+    use crate::strategy::market_maker::CloseOrderType;
+    use crate::strategy::rest_quoting::RestQuotingMode;
+    use crate::strategy::strategy_log::LogCategory;
+    use crate::strategy::market_maker::QuoteParams;
+    use crate::strategy::preset::Preset;
+
+    #[test]
+    fn pinning_map_is_checked_against_the_host() {
+        assert_eq!(parse_cpu_list("2-4,7\n"), vec![2, 3, 4, 7]);
+
+        let mut pinning = PinningConfig::default();
+        pinning.apply_spec("hot=2,cold=9,rest=2,network=2+3,isolated=1").unwrap();
+        let (plan, warnings) = pinning.resolve(&[0, 1, 2, 3], &[3]);
+        // Missing core -> unpinned; the hot core is never handed out as a network core
+        assert_eq!((plan.hot, plan.cold, plan.rest), (Some(2), None, Some(2)));
+        assert_eq!(plan.network, vec![3]);
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.contains("isolated") && w.contains("[2]")));
+        assert!(pinning.apply_spec("hot=two").is_err());
+        assert!(pinning.apply_spec("gpu=3").is_err());
+        pinning.apply_spec("cold=off").unwrap();
+        assert_eq!(pinning.cold, None);
+    }
+
+    #[test]
+    fn preset_replaces_the_band_and_scales_every_clip() {
+        let mut config = EngineConfig::default();
+        config.quote.min_spread = 0.002;
+        config.quote.quote_qty = 1.0;
+        let own = QuoteParams { quote_notional: Some(60.0), ..QuoteParams::default() };
+        config.symbols = vec![SymbolConfig { quote: Some(own), ..SymbolConfig::default() }, SymbolConfig::default()];
+        let preset = Preset::Conservative;
+        config.apply_preset(preset);
+        let params = preset.params();
+        assert_eq!((config.quote.min_spread, config.quote.max_spread), (params.min_spread, params.max_spread));
+        assert_eq!((config.quote.quote_qty, config.quote.take_profit_pct), (0.5, params.take_profit_pct));
+        assert_eq!(config.symbols[0].quote.unwrap().quote_notional, Some(30.0));
+        assert_eq!(config.symbols[1].quote, None); // follows [quote]
+        assert_eq!(config.preset, Some(preset));
+        assert_eq!(preset.aging().cross_after, Duration::from_millis(params.cross_after_ms));
+
+        // Normal is the built-in defaults
+        let mut quote = QuoteParams::default();
+        Preset::Normal.apply(&mut quote);
+        assert_eq!(quote, QuoteParams::default());
+        assert_eq!(Preset::from_spec(" Aggressive"), Some(Preset::Aggressive));
+        assert_eq!(Preset::from_spec("yolo"), None);
+    }
+
+    #[test]
+    fn oms_section_reaches_the_order_manager() {
+        let config: EngineConfig = toml::from_str("[oms]\nmin_quote_lifetime_ms = 400\n").unwrap();
+        assert_eq!(config.oms.order_manager().min_quote_lifetime, Duration::from_millis(400));
+        let defaults = EngineConfig::default().oms.order_manager();
+        assert_eq!(defaults.min_quote_lifetime, Duration::from_millis(250));
+        assert!(toml::from_str::<EngineConfig>("[oms]\nmin_quote_lifetime = 400\n").is_err());
+
+        let config: EngineConfig = toml::from_str("[oms]\nfront_queue_fraction = 0.1\nstale_quote_age_ms = 2000\n").unwrap();
+        let oms = config.oms.order_manager();
+        assert_eq!((oms.front_queue_fraction, oms.back_queue_fraction), (0.1, 0.7));
+        assert_eq!(oms.stale_quote_age, Duration::from_secs(2));
+        assert!(config.oms.validate().is_ok());
+        for bad in ["front_queue_fraction = 0.8", "back_queue_fraction = 1.5", "small_move_pct = -0.1"] {
+            let config: EngineConfig = toml::from_str(&format!("[oms]\n{}\n", bad)).unwrap();
+            assert!(config.oms.validate().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn tls_pins_are_validated_on_load() {
+        let path = std::env::temp_dir().join(format!("hft_tls_pins_{}.toml", std::process::id()));
+        std::fs::write(&path, "[tls.pins]\n\"stream.bybit.com\" = [\"sha256/C5+lpZ7tcVwmwQIMcRtPbsQtWLABXhQzejna0wHFr8M=\"]\n").unwrap();
+        let config = EngineConfig::load(path.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(config.tls.cert_pins().unwrap().hosts().collect::<Vec<_>>(), ["stream.bybit.com"]);
+
+        std::fs::write(&path, "[tls.pins]\n\"stream.bybit.com\" = [\"sha256/short\"]\n").unwrap();
+        let err = EngineConfig::load(path.to_str().unwrap()).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.contains("tls.pins: stream.bybit.com: bad pin"), "{}", err);
+    }
+
+    #[test]
+    fn endpoint_race_is_read_and_validated_on_load() {
+        let race = EngineConfig::default().endpoints.race.race_config();
+        assert_eq!((race.enabled, race.samples, race.timeout), (true, 3, Duration::from_secs(1)));
+
+        let path = std::env::temp_dir().join(format!("hft_endpoint_race_{}.toml", std::process::id()));
+        std::fs::write(&path, "[endpoints.race]\nsamples = 5\ntimeout_ms = 250\n").unwrap();
+        let race = EngineConfig::load(path.to_str().unwrap()).unwrap().unwrap().endpoints.race.race_config();
+        assert_eq!((race.enabled, race.samples, race.timeout), (true, 5, Duration::from_millis(250)));
+
+        std::fs::write(&path, "[endpoints.race]\nsamples = 0\n").unwrap();
+        let err = EngineConfig::load(path.to_str().unwrap()).unwrap_err();
+        // Off: the other values don't matter
+        std::fs::write(&path, "[endpoints.race]\nenabled = false\nsamples = 0\n").unwrap();
+        let off = EngineConfig::load(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.contains("endpoints.race"), "{}", err);
+        assert!(!off.unwrap().unwrap().endpoints.race.enabled);
+    }
+
+    fn with_env(vars: &[(&str, &str)]) -> Result<EngineConfig, String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let mut config = EngineConfig::default();
+        config.apply_env(|name| vars.get(name).cloned())?;
+        Ok(config)
+    }
+
+    #[test]
+    fn hft_overrides_land_in_their_sections() {
+        let config = with_env(&[
+            ("HFT_PRESET", "conservative"),
+            ("HFT_ENVIRONMENT", "demo"),
+            ("HFT_WATCHDOG_MS", "0"),
+            ("HFT_WATCHDOG_CANCEL", "false"),
+            ("HFT_SIDE_THROTTLE", "creates=4,max_ms=8000"),
+            ("HFT_REST_QUOTING", "fallback,poll_ms=500"),
+            ("HFT_FEED_WATCH", "public=3000,trade=60000"),
+            ("HFT_MM_OBLIGATION", "spread_bps=20"),
+            ("HFT_BINANCE_HEDGE", "off"),
+            ("HFT_OKX", "off"),
+            ("HFT_DROPCOPY_FILE", "off"),
+            ("HFT_EXIT_JOIN_MS", "1500"),
+            ("HFT_CLOSE_MODE", "limit-first"),
+            ("HFT_LOG_SAMPLING", "requote=1"),
+            ("HFT_EGRESS_LIMITS", "create=20,warn=0.3"),
+            ("HFT_SHADOW", "min_spread=0.003"),
+            ("HFT_REDUCE_ONLY", "on"),
+        ]).unwrap();
+        assert_eq!((config.preset, config.endpoints.environment), (Some(Preset::Conservative), Environment::Demo));
+        let watchdog = config.watchdog.watchdog();
+        assert!(!watchdog.enabled && !watchdog.cancel_all);
+
+        let throttle = config.send_gate.send_gate().throttle;
+        assert!(throttle.enabled);
+        assert_eq!((throttle.max_creates, throttle.max_cooldown), (4, Duration::from_secs(8)));
+        let rest = config.rest_quoting.rest_quoting();
+        assert_eq!((rest.mode, rest.poll), (RestQuotingMode::Fallback, Duration::from_millis(500)));
+        let feed_watch = config.feed_watch.feed_watch();
+        assert_eq!(feed_watch.silence, [Duration::from_secs(3), Duration::from_secs(45), Duration::from_secs(60)]);
+        let obligation = config.obligation.obligation();
+        assert!(obligation.enabled && obligation.max_spread_bps == 20.0);
+
+        assert_eq!((config.venues.hedge_ratio, config.venues.okx), (0.0, false));
+        assert!(config.oms.reduce_only);
+        assert_eq!(config.records.drop_copy().file, None);
+        assert_eq!(config.records.incidents().dir.as_deref(), Some("incidents"));
+        assert_eq!(config.strategy.exit_join_ms, Some(1500));
+        assert_eq!(config.strategy.close_mode(), Ok(CloseOrderType::LimitFirst));
+        assert_eq!(config.strategy.log_sampling().unwrap().every[LogCategory::Requote as usize], 1);
+        let egress = config.metrics.egress();
+        assert_eq!((egress.limits[EgressOp::Create as usize], egress.warn_headroom), (20, 0.3));
+        assert_eq!(config.shadow.quote.as_deref(), Some("min_spread=0.003"));
+
+        // Nothing set: the built-in runtime defaults
+        let config = with_env(&[]).unwrap();
+        assert!(config.watchdog.watchdog().enabled);
+        assert_eq!(config.feed_watch.feed_watch().silence[Conn::Public as usize], Duration::from_secs(5));
+        assert_eq!(config.rest_quoting.rest_quoting().mode, RestQuotingMode::Off);
+        assert_eq!(config.strategy.batch_policy(), Ok(None));
+    }
+
+    #[test]
+    fn a_bad_override_is_an_error_naming_the_variable() {
+        for (name, value) in [
+            ("HFT_WATCHDOG_MS", "5s"),
+            ("HFT_OKX", "maybe"),
+            ("HFT_REDUCE_ONLY", "yes"),
+            ("HFT_ENVIRONMENT", "staging"),
+            ("HFT_SIDE_THROTTLE", "creates"),
+            ("HFT_FEED_WATCH", "public=3000,bogus=1"),
+            ("HFT_REST_QUOTING", "sometimes"),
+            ("HFT_EGRESS_LIMITS", "create=fast"),
+            ("HFT_PINNING", "hot=x"),
+        ] {
+            let err = with_env(&[(name, value)]).unwrap_err();
+            assert!(err.starts_with(name), "{}: {}", name, err);
+        }
+        // Parses, but the section check refuses it
+        for (name, value, section) in [
+            ("HFT_CLOSE_MODE", "limit", "strategy.close_mode"),
+            ("HFT_MARGIN_BUFFER", "1.5", "margin.buffer"),
+            ("HFT_SHADOW", "spread=0.003", "shadow.quote"),
+            ("HFT_LOG_SAMPLING", "chatter=5", "strategy.log_sampling"),
+            ("HFT_EXIT_JOIN_MS", "90000", "strategy.exit_join_ms"),
+            ("HFT_CANCEL_SCOPE", "all", "startup.cancel_scope"),
+        ] {
+            let mut vars = vec![(name, value)];
+            if name == "HFT_EXIT_JOIN_MS" {
+                vars.push(("HFT_EXIT_CROSS_MS", "60000"));
+            }
+            let err = with_env(&vars).unwrap_err();
+            assert!(err.contains(section), "{}: {}", name, err);
+        }
+    }
+
+    #[test]
+    fn sections_are_read_from_the_file_and_checked_on_load() {
+        let path = std::env::temp_dir().join(format!("hft_sections_{}.toml", std::process::id()));
+        std::fs::write(&path, "[watchdog]\nstall_ms = 800\nfile = \"hot.beat\"\n\n[send_gate]\nmax_orders_per_sec = 5\n\
+            [send_gate.side_throttle]\nenabled = false\n\n[records]\nheatmap_file = \"\"\n\n\
+            [metrics.egress_limits]\namend = 25\n\n[strategy.log_sampling]\nbatch = 0\n").unwrap();
+        let config = EngineConfig::load(path.to_str().unwrap()).unwrap().unwrap();
+        let watchdog = config.watchdog.watchdog();
+        assert_eq!((watchdog.stall, watchdog.file.as_deref()), (Duration::from_millis(800), Some("hot.beat")));
+        let gate = config.send_gate.send_gate();
+        assert_eq!((gate.max_orders_per_sec, gate.throttle.enabled), (5, false));
+        assert_eq!(config.records.heatmap().file, None);
+        assert_eq!(config.metrics.egress().limits[EgressOp::Amend as usize], 25);
+        assert_eq!(config.strategy.log_sampling().unwrap().every[LogCategory::Batch as usize], 0);
+
+        for (bad, section) in [
+            ("[send_gate]\nmax_orders_per_sec = 0\n", "send_gate.max_orders_per_sec"),
+            ("[metrics.egress_limits]\nmodify = 10\n", "metrics.egress_limits"),
+            ("[obligation]\nmin_uptime = 1.2\n", "obligation.min_uptime"),
+            ("[rest_quoting]\nmode = \"on\"\n", "rest_quoting.mode"),
+            ("[experiment]\nvariant_b = \"min_spread=wide\"\n", "experiment.variant_b"),
+        ] {
+            std::fs::write(&path, bad).unwrap();
+            let err = EngineConfig::load(path.to_str().unwrap()).unwrap_err();
+            assert!(err.contains(section), "{}: {}", section, err);
+        }
+        // The shipped example is the defaults
+        let example = EngineConfig::load(concat!(env!("CARGO_MANIFEST_DIR"), "/config.example.toml")).unwrap().unwrap();
+        let defaults = EngineConfig::default();
+        assert_eq!((&example.watchdog, &example.send_gate, &example.strategy), (&defaults.watchdog, &defaults.send_gate, &defaults.strategy));
+        assert_eq!((&example.records, &example.metrics, &example.feed_watch), (&defaults.records, &defaults.metrics, &defaults.feed_watch));
+        assert_eq!((&example.rest_quoting, &example.startup, &example.experiment, &example.shadow),
+            (&defaults.rest_quoting, &defaults.startup, &defaults.experiment, &defaults.shadow));
+        assert_eq!((example.margin, example.obligation, example.venues, example.pnl_audit),
+            (defaults.margin, defaults.obligation, defaults.venues, defaults.pnl_audit));
+
+        std::fs::write(&path, "[watchdog]\nstall = 800\n").unwrap();
+        let unknown = EngineConfig::load(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert!(unknown.is_err());
+    }
+}
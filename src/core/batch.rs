@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+use crate::core::parser::BookUpdate;
+
+// Bybit can push several deltas stamped with the same exchange ms (one matching-engine
+// burst split over frames). Reacting to each of them requotes off half-applied state;
+// skipping every one that repeats the ts (the old rule) also drops genuine updates that
+// just happen to share a millisecond, and the book's final state is only acted on when
+// the next ms arrives.
+const DEFAULT_COALESCE_WINDOW_US: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchPolicy {
+    /// Evaluate every book message.
+    Off,
+    /// Skip every message whose exchange ts equals the previous one (legacy rule).
+    SameTs,
+    /// Skip a message only if it continues the current burst: same exchange ts,
+    /// next update id (no gap) and arrived within `window` of the burst's first
+    /// evaluated message. Anything else starts a new burst and is evaluated.
+    Coalesce { window: Duration },
+}
+
+impl Default for BatchPolicy {
+    fn default() -> Self {
+        BatchPolicy::Coalesce { window: Duration::from_micros(DEFAULT_COALESCE_WINDOW_US) }
+    }
+}
+
+impl BatchPolicy {
+    /// Parses `off`, `same-ts` or `coalesce=<us>` (HFT_BATCH_POLICY).
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec.trim() {
+            "off" => Some(BatchPolicy::Off),
+            "same-ts" => Some(BatchPolicy::SameTs),
+            other => {
+                let us = other.strip_prefix("coalesce=")?.trim().parse::<u64>().ok()?;
+                Some(BatchPolicy::Coalesce { window: Duration::from_micros(us) })
+            }
+        }
+    }
+}
+
+/// Decides which book messages the strategy evaluates.
+pub struct BatchDetector {
+    pub policy: BatchPolicy,
+    last_ts: u64,
+    last_update_id: u64,
+    burst_start: Option<Instant>,
+}
+
+impl BatchDetector {
+    pub fn new(policy: BatchPolicy) -> Self {
+        Self { policy, last_ts: 0, last_update_id: 0, burst_start: None }
+    }
+
+    /// Returns true if the strategy should evaluate the book after `update`,
+    /// false if the message is coalesced into the burst in progress.
+    /// A ts of 0 (internal calls) is always evaluated.
+    pub fn admit(&mut self, update: &BookUpdate, arrival: Instant) -> bool {
+        let same_ts = update.ts > 0 && update.ts == self.last_ts;
+        // Feeds without an update id fall back to ts + arrival time only
+        let contiguous = !update.snapshot
+            && (update.update_id == 0 || update.update_id == self.last_update_id.wrapping_add(1));
+        if update.ts > 0 {
+            self.last_ts = update.ts;
+        }
+        self.last_update_id = update.update_id;
+
+        match self.policy {
+            BatchPolicy::Off => true,
+            BatchPolicy::SameTs => !same_ts,
+            BatchPolicy::Coalesce { window } => {
+                let in_window = self.burst_start.is_some_and(|start| arrival.saturating_duration_since(start) < window);
+                if same_ts && contiguous && in_window {
+                    return false;
+                }
+                self.burst_start = Some(arrival);
+                true
+            }
+        }
+    }
+}
+
+impl Default for BatchDetector {
+    fn default() -> Self {
+        Self::new(BatchPolicy::default())
+    }
+}
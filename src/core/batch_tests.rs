@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::core::batch::{BatchDetector, BatchPolicy};
+    use crate::core::orderbook::L2OrderBook;
+    use crate::core::parser::{self, BookUpdate};
+
+    // Bybit V5 orderbook.50 deltas (format of the public linear stream, trimmed): one
+    // burst of three deltas stamped with the same ms, then the next ms.
+    const BURST: [&str; 4] = [
+        r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1687940967466,"data":{"s":"BTCUSDT","b":[["30247.20","30.028"]],"a":[],"u":177400507,"seq":66544703342},"cts":1687940967464}"#,
+        r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1687940967466,"data":{"s":"BTCUSDT","b":[["30247.20","0"]],"a":[["30247.90","1.120"]],"u":177400508,"seq":66544703343},"cts":1687940967464}"#,
+        r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1687940967466,"data":{"s":"BTCUSDT","b":[["30247.10","4.500"]],"a":[],"u":177400509,"seq":66544703345},"cts":1687940967465}"#,
+        r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1687940967467,"data":{"s":"BTCUSDT","b":[],"a":[["30247.90","0"]],"u":177400510,"seq":66544703350},"cts":1687940967466}"#,
+    ];
+
+    fn replay(burst: &[&str]) -> Vec<BookUpdate> {
+        let mut book = L2OrderBook::new();
+        burst
+            .iter()
+            .map(|msg| parser::parse_book_update(&mut msg.as_bytes().to_vec(), &mut book).unwrap())
+            .collect()
+    }
+
+    /// Feeds `updates` arriving `gaps[i]` after the previous one; returns the admit decisions.
+    fn run(policy: BatchPolicy, updates: &[BookUpdate], gaps: &[Duration]) -> Vec<bool> {
+        let mut detector = BatchDetector::new(policy);
+        let mut arrival = Instant::now();
+        updates
+            .iter()
+            .zip(gaps)
+            .map(|(u, gap)| {
+                arrival += *gap;
+                detector.admit(u, arrival)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parser_reports_ts_and_update_id() {
+        let updates = replay(&BURST);
+        assert_eq!(updates[0], BookUpdate { ts: 1687940967466, update_id: 177400507, snapshot: false, levels: 1 });
+        assert_eq!(updates[3].update_id, 177400510);
+    }
+
+    #[test]
+    fn coalesces_a_tight_burst() {
+        let updates = replay(&BURST);
+        let gaps = [Duration::ZERO, Duration::from_micros(40), Duration::from_micros(35), Duration::from_micros(900)];
+        let window = Duration::from_micros(500);
+        assert_eq!(run(BatchPolicy::Coalesce { window }, &updates, &gaps), [true, false, false, true]);
+        assert_eq!(run(BatchPolicy::SameTs, &updates, &gaps), [true, false, false, true]);
+        assert_eq!(run(BatchPolicy::Off, &updates, &gaps), [true, true, true, true]);
+    }
+
+    #[test]
+    fn same_ms_updates_outside_the_window_are_evaluated() {
+        // Same exchange ms, but the third delta reached us well after the burst started:
+        // the legacy rule drops it, the coalescing window does not.
+        let updates = replay(&BURST);
+        let gaps = [Duration::ZERO, Duration::from_micros(40), Duration::from_millis(2), Duration::from_micros(100)];
+        let window = Duration::from_micros(500);
+        assert_eq!(run(BatchPolicy::Coalesce { window }, &updates, &gaps), [true, false, true, true]);
+        assert_eq!(run(BatchPolicy::SameTs, &updates, &gaps), [true, false, false, true]);
+    }
+
+    #[test]
+    fn update_id_gap_breaks_the_burst() {
+        let mut updates = replay(&BURST);
+        updates[2].update_id += 5; // lost messages in between
+        let gaps = [Duration::ZERO; 4];
+        let window = Duration::from_micros(500);
+        assert_eq!(run(BatchPolicy::Coalesce { window }, &updates, &gaps), [true, false, true, true]);
+    }
+
+    #[test]
+    fn policy_spec() {
+        assert_eq!(BatchPolicy::from_spec("off"), Some(BatchPolicy::Off));
+        assert_eq!(BatchPolicy::from_spec("same-ts"), Some(BatchPolicy::SameTs));
+        assert_eq!(BatchPolicy::from_spec("coalesce=250"), Some(BatchPolicy::Coalesce { window: Duration::from_micros(250) }));
+        assert_eq!(BatchPolicy::from_spec("coalesce=abc"), None);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::core::orderbook::{L2OrderBook, Level, Side};
+use crate::core::parser::BookUpdate;
+
+// A missed or misapplied delta leaves the book in a state the exchange never had:
+// bid at or through the ask, levels out of order, zero/negative sizes. Quoting off
+// it means pricing against a market that doesn't exist, so the book is dropped and
+// re-fetched instead.
+// The ticker stream carries Bybit's own top of book (bid1/ask1, every 100ms): our touch
+// trading through theirs means our book missed updates even when it looks sane locally.
+// Book and ticker streams race, so it must hold on consecutive tickers to count.
+const DEFAULT_THROUGH_CHECKS: u32 = 2;
+
+/// What is wrong with the book. `level` is the index into the side's array.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BookFault {
+    /// Best bid above the best ask.
+    Crossed { bid: f64, ask: f64 },
+    /// Best bid equal to the best ask.
+    Locked { px: f64 },
+    /// Level not strictly better than the next one (bids descending, asks ascending).
+    Unsorted { side: Side, level: usize },
+    /// Non-finite or non-positive price/qty, or a populated level after an empty one.
+    BadLevel { side: Side, level: usize },
+    /// Our touch is through the exchange's opposite touch (ticker bid1/ask1).
+    TradeThrough { side: Side, ours: f64, exchange: f64 },
+}
+
+/// Checks a single book state; the first fault found wins (sides before the touch).
+pub fn check(book: &L2OrderBook) -> Option<BookFault> {
+    if let Some(fault) = check_side(Side::Buy, &book.bids).or_else(|| check_side(Side::Sell, &book.asks)) {
+        return Some(fault);
+    }
+    let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+    if bid == 0.0 || ask == 0.0 {
+        return None; // one side empty: not tradable, but not corrupt either
+    }
+    if bid > ask {
+        Some(BookFault::Crossed { bid, ask })
+    } else if bid == ask {
+        Some(BookFault::Locked { px: bid })
+    } else {
+        None
+    }
+}
+
+fn check_side(side: Side, levels: &[Level]) -> Option<BookFault> {
+    let depth = levels.iter().take_while(|lvl| lvl.price != 0.0).count();
+    for (i, lvl) in levels.iter().enumerate() {
+        let populated = i < depth;
+        let sane = lvl.price.is_finite() && lvl.price > 0.0 && lvl.qty.is_finite() && lvl.qty > 0.0;
+        if (populated && !sane) || (!populated && lvl.price != 0.0) {
+            return Some(BookFault::BadLevel { side, level: i });
+        }
+    }
+    for i in 1..depth {
+        let ordered = match side {
+            Side::Buy => levels[i - 1].price > levels[i].price,
+            Side::Sell => levels[i - 1].price < levels[i].price,
+        };
+        if !ordered {
+            return Some(BookFault::Unsorted { side, level: i });
+        }
+    }
+    None
+}
+
+/// Runs `check` after every applied update. Reports a fault once, then stays quiet
+/// until a fresh snapshot has been applied (the resync it asked for).
+pub struct BookSanityMonitor {
+    pub faults: u64,
+    /// Consecutive tickers a trade-through must survive before it is a fault.
+    pub through_checks: u32,
+    awaiting_snapshot: bool,
+    exchange_bid: f64,
+    exchange_ask: f64,
+    through_streak: u32,
+}
+
+impl BookSanityMonitor {
+    pub fn new() -> Self {
+        Self {
+            faults: 0,
+            through_checks: DEFAULT_THROUGH_CHECKS,
+            awaiting_snapshot: false,
+            exchange_bid: 0.0,
+            exchange_ask: 0.0,
+            through_streak: 0,
+        }
+    }
+
+    /// Feeds the book right after `update` was applied. Returns the fault that calls for
+    /// a resync; None if the book is fine or a resync is already pending.
+    pub fn on_update(&mut self, book: &L2OrderBook, update: &BookUpdate) -> Option<BookFault> {
+        if self.awaiting_snapshot {
+            if !update.snapshot {
+                return None;
+            }
+            self.awaiting_snapshot = false;
+        }
+        let fault = check(book)?;
+        Some(self.raise(fault))
+    }
+
+    /// Feeds the exchange's top of book from a ticker (deltas carry only changed fields).
+    /// Returns a trade-through fault once it has held for `through_checks` tickers.
+    pub fn on_ticker(&mut self, book: &L2OrderBook, bid1: Option<f64>, ask1: Option<f64>) -> Option<BookFault> {
+        if let Some(bid) = bid1 {
+            self.exchange_bid = bid;
+        }
+        if let Some(ask) = ask1 {
+            self.exchange_ask = ask;
+        }
+        if self.awaiting_snapshot || !book.snapshot_applied {
+            return None;
+        }
+        let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+        let through = if bid > 0.0 && self.exchange_ask > 0.0 && bid > self.exchange_ask {
+            Some(BookFault::TradeThrough { side: Side::Buy, ours: bid, exchange: self.exchange_ask })
+        } else if ask > 0.0 && self.exchange_bid > 0.0 && ask < self.exchange_bid {
+            Some(BookFault::TradeThrough { side: Side::Sell, ours: ask, exchange: self.exchange_bid })
+        } else {
+            None
+        };
+        let Some(fault) = through else {
+            self.through_streak = 0;
+            return None;
+        };
+        self.through_streak += 1;
+        if self.through_streak < self.through_checks {
+            return None;
+        }
+        Some(self.raise(fault))
+    }
+
+    fn raise(&mut self, fault: BookFault) -> BookFault {
+        self.faults += 1;
+        self.awaiting_snapshot = true;
+        self.through_streak = 0;
+        fault
+    }
+}
+
+impl Default for BookSanityMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::book_check::{self, BookFault, BookSanityMonitor};
+    use crate::core::orderbook::{L2OrderBook, Side};
+    use crate::core::parser::BookUpdate;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> L2OrderBook {
+        let mut book = L2OrderBook::new();
+        for &(px, qty) in bids {
+            book.update(Side::Buy, px, qty);
+        }
+        for &(px, qty) in asks {
+            book.update(Side::Sell, px, qty);
+        }
+        book.snapshot_applied = true;
+        book
+    }
+
+    #[test]
+    fn healthy_book_passes() {
+        let b = book(&[(100.0, 1.0), (99.9, 2.0)], &[(100.1, 1.0), (100.2, 3.0)]);
+        assert_eq!(book_check::check(&b), None);
+    }
+
+    #[test]
+    fn crossed_and_locked_touch() {
+        // A missed delete leaves a stale bid through the ask
+        let b = book(&[(100.2, 1.0), (100.0, 1.0)], &[(100.1, 1.0)]);
+        assert_eq!(book_check::check(&b), Some(BookFault::Crossed { bid: 100.2, ask: 100.1 }));
+        let b = book(&[(100.1, 1.0)], &[(100.1, 2.0)]);
+        assert_eq!(book_check::check(&b), Some(BookFault::Locked { px: 100.1 }));
+    }
+
+    #[test]
+    fn broken_levels() {
+        let mut b = book(&[(100.0, 1.0), (99.9, 1.0)], &[(100.1, 1.0)]);
+        b.bids[1].qty = -1.0;
+        assert_eq!(book_check::check(&b), Some(BookFault::BadLevel { side: Side::Buy, level: 1 }));
+
+        let mut b = book(&[(100.0, 1.0)], &[(100.1, 1.0), (100.2, 1.0)]);
+        b.asks.swap(0, 1);
+        assert_eq!(book_check::check(&b), Some(BookFault::Unsorted { side: Side::Sell, level: 1 }));
+
+        let mut b = book(&[(100.0, 1.0)], &[(100.1, 1.0)]);
+        b.asks[3].price = 100.5; // populated level behind a hole
+        b.asks[3].qty = 1.0;
+        assert_eq!(book_check::check(&b), Some(BookFault::BadLevel { side: Side::Sell, level: 3 }));
+    }
+
+    #[test]
+    fn monitor_reports_once_until_resnapshot() {
+        let delta = BookUpdate { ts: 1, update_id: 10, snapshot: false, levels: 1 };
+        let snapshot = BookUpdate { ts: 2, update_id: 1, snapshot: true, levels: 2 };
+        let crossed = book(&[(100.2, 1.0)], &[(100.1, 1.0)]);
+        let fresh = book(&[(100.0, 1.0)], &[(100.1, 1.0)]);
+
+        let mut monitor = BookSanityMonitor::new();
+        assert!(monitor.on_update(&crossed, &delta).is_some());
+        assert!(monitor.on_update(&crossed, &delta).is_none()); // resync pending
+        assert!(monitor.on_update(&fresh, &snapshot).is_none());
+        assert!(monitor.on_update(&crossed, &delta).is_some());
+        assert_eq!(monitor.faults, 2);
+    }
+
+    #[test]
+    fn trade_through_must_persist_across_tickers() {
+        // Our best bid 100.2 is above Bybit's own ask1 100.1: we missed the deletes
+        let stale = book(&[(100.2, 1.0)], &[(100.3, 1.0)]);
+        let mut monitor = BookSanityMonitor::new();
+        assert!(monitor.on_ticker(&stale, Some(100.0), Some(100.1)).is_none()); // may be a stream race
+        assert_eq!(
+            monitor.on_ticker(&stale, None, None), // delta without price changes: last known touch
+            Some(BookFault::TradeThrough { side: Side::Buy, ours: 100.2, exchange: 100.1 })
+        );
+
+        let fine = book(&[(100.0, 1.0)], &[(100.1, 1.0)]);
+        let mut monitor = BookSanityMonitor::new();
+        assert!(monitor.on_ticker(&stale, Some(100.0), Some(100.1)).is_none());
+        assert!(monitor.on_ticker(&fine, None, None).is_none()); // book caught up, streak reset
+        assert!(monitor.on_ticker(&stale, None, None).is_none());
+    }
+}
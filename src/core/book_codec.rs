@@ -0,0 +1,161 @@
+use crate::core::orderbook::{L2OrderBook, Level, Side};
+use crate::core::rounding;
+
+// Compact order book delta encoding for long recordings.
+//
+// Record layout (all integers LEB128 varints, signed ones zigzag-encoded):
+//   kind        u8      0 = delta, 1 = snapshot (decoder clears the book first)
+//   ts          svarint exchange ts minus the previous record's ts
+//   n_bids      varint
+//   n_asks      varint
+//   levels      n_bids + n_asks times:
+//     price     svarint price in ticks minus the previous level's (the first level of a
+//                       record is relative to the first level of the previous record)
+//     qty       varint  qty in lots, 0 = level removed
+//
+// Prices and quantities are snapped to the instrument grid (tick / lot), so a typical
+// delta level costs 2-4 bytes instead of two f64s plus JSON.
+
+const KIND_DELTA: u8 = 0;
+const KIND_SNAPSHOT: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CodecError {
+    Truncated,
+    VarintOverflow,
+    BadKind(u8),
+}
+
+/// Instrument grid the codec snaps to (see `symbols::Instrument`).
+#[derive(Debug, Clone, Copy)]
+pub struct BookGrid {
+    pub tick_size: f64,
+    pub lot_size: f64,
+}
+
+impl BookGrid {
+    fn ticks(&self, price: f64) -> i64 {
+        rounding::round_steps(price, self.tick_size) as i64
+    }
+
+    fn lots(&self, qty: f64) -> u64 {
+        rounding::round_steps(qty, self.lot_size).max(0.0) as u64
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn put_svarint(out: &mut Vec<u8>, v: i64) {
+    put_varint(out, ((v << 1) ^ (v >> 63)) as u64);
+}
+
+/// Encoder side (recorder). Keeps the references the next record is relative to.
+pub struct BookEncoder {
+    grid: BookGrid,
+    last_ts: u64,
+    ref_ticks: i64,
+}
+
+impl BookEncoder {
+    pub fn new(grid: BookGrid) -> Self {
+        Self { grid, last_ts: 0, ref_ticks: 0 }
+    }
+
+    /// Appends one book message to `out`.
+    pub fn encode(&mut self, snapshot: bool, ts: u64, bids: &[Level], asks: &[Level], out: &mut Vec<u8>) {
+        out.push(if snapshot { KIND_SNAPSHOT } else { KIND_DELTA });
+        put_svarint(out, ts.wrapping_sub(self.last_ts) as i64);
+        self.last_ts = ts;
+        put_varint(out, bids.len() as u64);
+        put_varint(out, asks.len() as u64);
+
+        let mut prev = self.ref_ticks;
+        for (i, level) in bids.iter().chain(asks.iter()).enumerate() {
+            let ticks = self.grid.ticks(level.price);
+            put_svarint(out, ticks - prev);
+            put_varint(out, self.grid.lots(level.qty));
+            if i == 0 {
+                self.ref_ticks = ticks;
+            }
+            prev = ticks;
+        }
+    }
+}
+
+/// Decoder side (backtester). Reads records in order from one recording buffer.
+pub struct BookDecoder<'a> {
+    grid: BookGrid,
+    buf: &'a [u8],
+    pos: usize,
+    last_ts: u64,
+    ref_ticks: i64,
+}
+
+impl<'a> BookDecoder<'a> {
+    pub fn new(grid: BookGrid, buf: &'a [u8]) -> Self {
+        Self { grid, buf, pos: 0, last_ts: 0, ref_ticks: 0 }
+    }
+
+    fn byte(&mut self) -> Result<u8, CodecError> {
+        let b = *self.buf.get(self.pos).ok_or(CodecError::Truncated)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            v |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(CodecError::VarintOverflow)
+    }
+
+    fn svarint(&mut self) -> Result<i64, CodecError> {
+        let v = self.varint()?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+
+    /// Applies the next record to `book`. Returns its exchange ts, or None at the end of the buffer.
+    pub fn next_into(&mut self, book: &mut L2OrderBook) -> Result<Option<u64>, CodecError> {
+        if self.pos == self.buf.len() {
+            return Ok(None);
+        }
+        let kind = self.byte()?;
+        if kind != KIND_DELTA && kind != KIND_SNAPSHOT {
+            return Err(CodecError::BadKind(kind));
+        }
+        let ts = self.last_ts.wrapping_add(self.svarint()? as u64);
+        self.last_ts = ts;
+        let n_bids = self.varint()? as usize;
+        let n_asks = self.varint()? as usize;
+
+        if kind == KIND_SNAPSHOT {
+            book.clear();
+        }
+        let mut prev = self.ref_ticks;
+        for i in 0..n_bids + n_asks {
+            let ticks = prev + self.svarint()?;
+            let lots = self.varint()?;
+            if i == 0 {
+                self.ref_ticks = ticks;
+            }
+            prev = ticks;
+            let side = if i < n_bids { Side::Buy } else { Side::Sell };
+            book.update(side, ticks as f64 * self.grid.tick_size, lots as f64 * self.grid.lot_size);
+        }
+        if kind == KIND_SNAPSHOT {
+            book.snapshot_applied = true;
+        }
+        Ok(Some(ts))
+    }
+}
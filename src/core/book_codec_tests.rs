@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::book_codec::{BookDecoder, BookEncoder, BookGrid, CodecError};
+    use crate::core::orderbook::{L2OrderBook, Level};
+
+    const GRID: BookGrid = BookGrid { tick_size: 0.001, lot_size: 0.1 };
+
+    fn lvl(price: f64, qty: f64) -> Level {
+        Level { price, qty }
+    }
+
+    fn assert_px(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn snapshot_then_deltas_roundtrip() {
+        let mut enc = BookEncoder::new(GRID);
+        let mut buf = Vec::new();
+        enc.encode(true, 1_700_000_000_000, &[lvl(12.345, 10.0), lvl(12.344, 5.5)], &[lvl(12.347, 3.2), lvl(12.350, 100.0)], &mut buf);
+        enc.encode(false, 1_700_000_000_020, &[lvl(12.344, 0.0), lvl(12.346, 1.0)], &[], &mut buf);
+        enc.encode(false, 1_700_000_000_020, &[], &[lvl(12.347, 7.0)], &mut buf);
+
+        let mut dec = BookDecoder::new(GRID, &buf);
+        let mut book = L2OrderBook::new();
+
+        assert_eq!(dec.next_into(&mut book), Ok(Some(1_700_000_000_000)));
+        assert!(book.snapshot_applied);
+        assert_px(book.bids[0].price, 12.345);
+        assert_px(book.asks[1].price, 12.350);
+
+        assert_eq!(dec.next_into(&mut book), Ok(Some(1_700_000_000_020)));
+        assert_px(book.bids[0].price, 12.346);
+        assert_px(book.bids[1].price, 12.345);
+        assert_eq!(book.bids[2].price, 0.0); // 12.344 removed
+
+        assert_eq!(dec.next_into(&mut book), Ok(Some(1_700_000_000_020)));
+        assert_px(book.asks[0].qty, 7.0);
+        assert_eq!(dec.next_into(&mut book), Ok(None));
+    }
+
+    #[test]
+    fn snapshot_replaces_book() {
+        let mut enc = BookEncoder::new(GRID);
+        let mut buf = Vec::new();
+        enc.encode(true, 1, &[lvl(10.0, 1.0)], &[lvl(10.1, 1.0)], &mut buf);
+        enc.encode(true, 2, &[lvl(9.0, 2.0)], &[lvl(9.1, 2.0)], &mut buf);
+
+        let mut dec = BookDecoder::new(GRID, &buf);
+        let mut book = L2OrderBook::new();
+        dec.next_into(&mut book).unwrap();
+        dec.next_into(&mut book).unwrap();
+        assert_px(book.bids[0].price, 9.0);
+        assert_eq!(book.bids[1].price, 0.0);
+    }
+
+    #[test]
+    fn fifty_level_snapshot_is_compact() {
+        let bids: Vec<Level> = (0..50).map(|i| lvl(12.345 - i as f64 * 0.001, 10.0 + i as f64)).collect();
+        let asks: Vec<Level> = (0..50).map(|i| lvl(12.346 + i as f64 * 0.001, 10.0 + i as f64)).collect();
+        let mut buf = Vec::new();
+        BookEncoder::new(GRID).encode(true, 1_700_000_000_000, &bids, &asks, &mut buf);
+        // 100 levels as raw f64 pairs would be 1600 bytes
+        assert!(buf.len() < 400, "{} bytes", buf.len());
+    }
+
+    #[test]
+    fn truncated_and_garbage_input() {
+        let mut buf = Vec::new();
+        BookEncoder::new(GRID).encode(false, 5, &[lvl(1.0, 1.0)], &[], &mut buf);
+        let mut book = L2OrderBook::new();
+        assert_eq!(BookDecoder::new(GRID, &buf[..buf.len() - 1]).next_into(&mut book), Err(CodecError::Truncated));
+        assert_eq!(BookDecoder::new(GRID, &[7]).next_into(&mut book), Err(CodecError::BadKind(7)));
+        assert_eq!(BookDecoder::new(GRID, &[0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).next_into(&mut book), Err(CodecError::VarintOverflow));
+    }
+}
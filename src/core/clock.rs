@@ -0,0 +1,122 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Exchange minus local wall clock (ms), published by the Hot Thread from the Trade WS
+// `Timenow` header. Read by `LiveClock::exchange_time_ms`.
+static EXCHANGE_OFFSET_MS: AtomicI64 = AtomicI64::new(0);
+
+/// Time source for strategy-side components (MarketMaker, RiskEngine, OrderManager).
+/// Live trading uses `LiveClock`; the backtester drives a `SimClock` so timers
+/// (time stop, heartbeat, quote lifetime) fire identically on recorded data.
+pub trait Clock {
+    /// Wall clock, microseconds since the Unix epoch (order link ids, request headers).
+    fn now_us(&self) -> u64;
+    /// Monotonic time for durations and timers.
+    fn monotonic_now(&self) -> Instant;
+    /// Best estimate of the exchange's clock, ms since the Unix epoch.
+    fn exchange_time_ms(&self) -> u64;
+}
+
+/// Real clocks. Zero-sized: every component holds its own copy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LiveClock;
+
+impl LiveClock {
+    pub fn set_exchange_offset_ms(offset_ms: i64) {
+        EXCHANGE_OFFSET_MS.store(offset_ms, Ordering::Relaxed);
+    }
+}
+
+impl Clock for LiveClock {
+    fn now_us(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+    }
+
+    fn monotonic_now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn exchange_time_ms(&self) -> u64 {
+        ((self.now_us() / 1000) as i64 + EXCHANGE_OFFSET_MS.load(Ordering::Relaxed)) as u64
+    }
+}
+
+/// Simulated clock: time only moves when the driver says so. Share one instance
+/// between components through `&SimClock` or `Rc<SimClock>`.
+/// Monotonic time is `origin + elapsed`, so `Instant` arithmetic keeps working.
+#[derive(Debug)]
+pub struct SimClock {
+    origin: Instant,
+    start_wall_us: u64,
+    elapsed: Cell<Duration>,
+    exchange_ms: Cell<u64>,
+}
+
+impl SimClock {
+    pub fn new(start_wall_ms: u64) -> Self {
+        Self {
+            origin: Instant::now(),
+            start_wall_us: start_wall_ms * 1000,
+            elapsed: Cell::new(Duration::ZERO),
+            exchange_ms: Cell::new(start_wall_ms),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.elapsed.set(self.elapsed.get() + by);
+    }
+
+    /// Moves simulated time to a recorded exchange timestamp (never backwards) and
+    /// makes it the exchange time as well.
+    pub fn set_exchange_time_ms(&self, ts_ms: u64) {
+        let target = Duration::from_micros((ts_ms * 1000).saturating_sub(self.start_wall_us));
+        if target > self.elapsed.get() {
+            self.elapsed.set(target);
+        }
+        self.exchange_ms.set(ts_ms);
+    }
+}
+
+impl Clock for SimClock {
+    fn now_us(&self) -> u64 {
+        self.start_wall_us + self.elapsed.get().as_micros() as u64
+    }
+
+    fn monotonic_now(&self) -> Instant {
+        self.origin + self.elapsed.get()
+    }
+
+    fn exchange_time_ms(&self) -> u64 {
+        self.exchange_ms.get()
+    }
+}
+
+impl<T: Clock + ?Sized> Clock for &T {
+    fn now_us(&self) -> u64 {
+        (**self).now_us()
+    }
+
+    fn monotonic_now(&self) -> Instant {
+        (**self).monotonic_now()
+    }
+
+    fn exchange_time_ms(&self) -> u64 {
+        (**self).exchange_time_ms()
+    }
+}
+
+impl<T: Clock + ?Sized> Clock for Rc<T> {
+    fn now_us(&self) -> u64 {
+        (**self).now_us()
+    }
+
+    fn monotonic_now(&self) -> Instant {
+        (**self).monotonic_now()
+    }
+
+    fn exchange_time_ms(&self) -> u64 {
+        (**self).exchange_time_ms()
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::core::clock::{Clock, SimClock};
+    use crate::strategy::market_maker::ActionType;
+    use crate::strategy::order_manager::{OrderManager, OrderManagerConfig, Veto};
+
+    #[test]
+    fn sim_clock_only_moves_when_driven() {
+        let clock = SimClock::new(1_700_000_000_000);
+        let t0 = clock.monotonic_now();
+        assert_eq!(clock.monotonic_now(), t0);
+        assert_eq!(clock.now_us(), 1_700_000_000_000_000);
+
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(clock.monotonic_now() - t0, Duration::from_millis(250));
+        assert_eq!(clock.now_us(), 1_700_000_000_250_000);
+    }
+
+    #[test]
+    fn exchange_time_never_moves_monotonic_backwards() {
+        let clock = SimClock::new(1_000);
+        let t0 = clock.monotonic_now();
+        clock.set_exchange_time_ms(3_000);
+        assert_eq!(clock.exchange_time_ms(), 3_000);
+        assert_eq!(clock.monotonic_now() - t0, Duration::from_secs(2));
+
+        clock.set_exchange_time_ms(2_500); // late/out-of-order message
+        assert_eq!(clock.exchange_time_ms(), 2_500);
+        assert_eq!(clock.monotonic_now() - t0, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn order_manager_quote_lifetime_on_simulated_time() {
+        let clock = SimClock::new(0);
+        let mut om = OrderManager::with_clock(OrderManagerConfig::default(), &clock);
+        let create = ActionType::CreateOrder { price: 10.0, qty: 1.0, side: "Buy", link_id: "b-1".to_string() };
+        let amend = ActionType::AmendOrder { price: 9.0, qty: 1.0, side: "Buy", link_id: "b-1".to_string() };
+
+        om.on_sent(&create, om.clock().monotonic_now());
+        clock.advance(Duration::from_millis(100));
+        assert!(matches!(om.check(&amend, om.clock().monotonic_now()), Err(Veto::QuoteTooYoung { .. })));
+
+        clock.advance(Duration::from_millis(200));
+        assert_eq!(om.check(&amend, om.clock().monotonic_now()), Ok(()));
+    }
+}
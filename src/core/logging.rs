@@ -0,0 +1,167 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// Per-subsystem verbosity. One atomic byte per subsystem, written only by the admin
+// console (rare), read with a Relaxed load at every log site: no locks, no contention.
+// Errors (`eprintln!`) are never gated.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Connections, handshakes, TLS, DNS, time sync.
+    Net,
+    /// Quoting decisions and strategy diagnostics.
+    Strategy,
+    /// Position updates and risk checks.
+    Risk,
+    /// Order entry, OMS, pipeline, REST fallback.
+    Orders,
+    /// Raw payloads and decode details.
+    Parser,
+}
+
+pub const SUBSYSTEMS: [Subsystem; 5] = [
+    Subsystem::Net,
+    Subsystem::Strategy,
+    Subsystem::Risk,
+    Subsystem::Orders,
+    Subsystem::Parser,
+];
+
+impl Subsystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Net => "net",
+            Subsystem::Strategy => "strategy",
+            Subsystem::Risk => "risk",
+            Subsystem::Orders => "orders",
+            Subsystem::Parser => "parser",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SUBSYSTEMS.iter().copied().find(|s| s.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Errors only.
+    Error = 0,
+    Info = 1,
+    /// Per-message detail (raw payloads, per-read sizes). Floods at market-data rates.
+    Debug = 2,
+}
+
+impl LogLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" | "off" => Some(LogLevel::Error),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    fn from_u8(v: u8) -> Self {
+        match v {
+            0 => LogLevel::Error,
+            1 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+// Indexed by `Subsystem as usize`; everything starts at Info (the old verbose mode).
+static LEVELS: [AtomicU8; 5] = [
+    AtomicU8::new(LogLevel::Info as u8),
+    AtomicU8::new(LogLevel::Info as u8),
+    AtomicU8::new(LogLevel::Info as u8),
+    AtomicU8::new(LogLevel::Info as u8),
+    AtomicU8::new(LogLevel::Info as u8),
+];
+
+pub fn level(sub: Subsystem) -> LogLevel {
+    LogLevel::from_u8(LEVELS[sub as usize].load(Ordering::Relaxed))
+}
+
+pub fn set_level(sub: Subsystem, level: LogLevel) {
+    LEVELS[sub as usize].store(level as u8, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub fn enabled(sub: Subsystem, level: LogLevel) -> bool {
+    LEVELS[sub as usize].load(Ordering::Relaxed) >= level as u8
+}
+
+/// Applies `sub=level` pairs separated by commas, e.g. `net=debug,strategy=error`;
+/// `all=<level>` sets every subsystem. Entries are applied in order; a bad entry is
+/// reported and the rest still apply.
+pub fn apply_spec(spec: &str) -> Result<(), String> {
+    let mut bad = Vec::new();
+    for item in spec.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+        let parsed = item.split_once('=').and_then(|(sub, lvl)| {
+            let level = LogLevel::from_name(lvl.trim())?;
+            match sub.trim() {
+                "all" => Some((None, level)),
+                name => Subsystem::from_name(name).map(|s| (Some(s), level)),
+            }
+        });
+        match parsed {
+            Some((Some(sub), level)) => set_level(sub, level),
+            Some((None, level)) => SUBSYSTEMS.iter().for_each(|&s| set_level(s, level)),
+            None => bad.push(item),
+        }
+    }
+    if bad.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Bad log level entries {:?} (<net|strategy|risk|orders|parser|all>=<error|info|debug>)", bad))
+    }
+}
+
+/// Current levels as a spec (`net=info,strategy=debug,...`).
+pub fn summary() -> String {
+    SUBSYSTEMS
+        .iter()
+        .map(|&s| format!("{}={}", s.name(), level(s).name()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `info!(net, "...", args)`: printed when the subsystem is at Info or Debug.
+#[macro_export]
+macro_rules! info {
+    ($sub:ident, $($arg:tt)*) => {
+        if $crate::core::logging::enabled($crate::core::logging::subsystem!($sub), $crate::core::logging::LogLevel::Info) {
+            println!($($arg)*);
+        }
+    }
+}
+
+/// `debug!(parser, "...", args)`: printed only when the subsystem is at Debug.
+#[macro_export]
+macro_rules! debug {
+    ($sub:ident, $($arg:tt)*) => {
+        if $crate::core::logging::enabled($crate::core::logging::subsystem!($sub), $crate::core::logging::LogLevel::Debug) {
+            println!($($arg)*);
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! subsystem {
+    (net) => { $crate::core::logging::Subsystem::Net };
+    (strategy) => { $crate::core::logging::Subsystem::Strategy };
+    (risk) => { $crate::core::logging::Subsystem::Risk };
+    (orders) => { $crate::core::logging::Subsystem::Orders };
+    (parser) => { $crate::core::logging::Subsystem::Parser };
+}
+pub use crate::{debug, info, subsystem};
@@ -1,6 +1,37 @@
+//! Data structures and codecs with no IO: order book, parser/serializer, base64, clocks, batching, logging.
+
 pub mod orderbook;
 pub mod parser;
 pub mod serializer;
+pub mod book_codec;
+pub mod clock;
+pub mod batch;
+pub mod logging;
+pub mod book_check;
+pub mod time_sync;
+pub mod rounding;
+pub mod base64;
 
 #[cfg(test)]
 mod bench_parser;
+
+#[cfg(test)]
+mod parser_tests;
+
+#[cfg(test)]
+mod book_codec_tests;
+
+#[cfg(test)]
+mod clock_tests;
+
+#[cfg(test)]
+mod batch_tests;
+
+#[cfg(test)]
+mod book_check_tests;
+
+#[cfg(test)]
+mod time_sync_tests;
+
+#[cfg(test)]
+mod rounding_tests;
@@ -15,12 +15,15 @@ pub struct Level {
 
 /// L2 OrderBook with fixed depth (20 levels).
 /// Aligned to 64 bytes to fit in cache lines and avoid false sharing.
-/// Memory Layout: 20 * 16 bytes (bids) + 20 * 16 bytes (asks) = 640 bytes. 
-/// Fits easily in L1.
+/// Memory Layout: 20 * 16 bytes (bids) + 20 * 16 bytes (asks) = 640 bytes,
+/// plus the snapshot flag (padded to 704). Fits easily in L1.
 #[repr(C, align(64))]
 pub struct L2OrderBook {
     pub bids: [Level; 20],
     pub asks: [Level; 20],
+    /// A full snapshot has been applied since the last `clear`. Deltas on top of a
+    /// zeroed book describe nothing real.
+    pub snapshot_applied: bool,
 }
 
 impl Default for L2OrderBook {
@@ -28,6 +31,7 @@ impl Default for L2OrderBook {
         Self {
             bids: [Level::default(); 20],
             asks: [Level::default(); 20],
+            snapshot_applied: false,
         }
     }
 }
@@ -107,6 +111,42 @@ impl L2OrderBook {
             }
         }
     }
+
+    /// Drops all levels (snapshot incoming or stream restarted).
+    pub fn clear(&mut self) {
+        self.bids = [Level::default(); 20];
+        self.asks = [Level::default(); 20];
+        self.snapshot_applied = false;
+    }
+
+    /// Number of populated levels on one side.
+    pub fn depth(&self, side: Side) -> usize {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.iter().take_while(|lvl| lvl.price != 0.0).count()
+    }
+
+    /// Safe to trade on: a snapshot was applied and both sides have at least `min_levels`.
+    pub fn is_ready(&self, min_levels: usize) -> bool {
+        self.snapshot_applied && self.depth(Side::Buy) >= min_levels && self.depth(Side::Sell) >= min_levels
+    }
+
+    /// Resting quantity at an exact price level (0.0 if the level is not in our top 20).
+    pub fn qty_at(&self, side: Side, price: f64) -> f64 {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        for lvl in levels.iter() {
+            if lvl.price == 0.0 { break; }
+            if (lvl.price - price).abs() < 1e-9 {
+                return lvl.qty;
+            }
+        }
+        0.0
+    }
 }
 
 // Display for debugging
@@ -6,12 +6,30 @@ use simd_json::prelude::*;
 // For HFT challenge, we often just look for "b" (bids) and "a" (asks) arrays 
 // inside the JSON and iterate them.
 
+/// Metadata of one applied book message (batch detection).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BookUpdate {
+    /// Exchange ts (ms); several deltas can share one.
+    pub ts: u64,
+    /// `data.u`: +1 per message on the stream, 0 if absent.
+    pub update_id: u64,
+    pub snapshot: bool,
+    /// Price levels in the message (bids + asks): the size that drives parse cost.
+    pub levels: u32,
+}
+
 pub fn parse_and_update(data: &mut [u8], book: &mut L2OrderBook) -> Result<u64, simd_json::Error> {
+    parse_book_update(data, book).map(|u| u.ts)
+}
+
+/// `parse_and_update` that also returns the update id and message type.
+pub fn parse_book_update(data: &mut [u8], book: &mut L2OrderBook) -> Result<BookUpdate, simd_json::Error> {
     // 1. Parse into Tape (Mutable, in-place)
     let tape = simd_json::to_borrowed_value(data)?;
 
     // Extract Timestamp (ts)
     let ts = tape.get("ts").and_then(|v| v.as_u64()).unwrap_or(0);
+    let update_id = tape.get("data").and_then(|d| d.get("u")).and_then(|v| v.as_u64()).unwrap_or(0);
 
     // 2. Navigate without intermediate structs
     // Bybit structure: { "topic": "...", "data": { "b": [[p, q], ...], "a": [[p, q], ...] } }
@@ -19,11 +37,19 @@ pub fn parse_and_update(data: &mut [u8], book: &mut L2OrderBook) -> Result<u64,
     // We look for "data" -> "b" and "data" -> "a"
     // Note: get() returns reference to Value
     
+    // Snapshot replaces the whole book (sent on subscribe and on Bybit-side resets)
+    let is_snapshot = tape.get("type").and_then(|v| v.as_str()) == Some("snapshot");
+    if is_snapshot {
+        book.clear();
+    }
+
+    let mut levels = 0u32;
     if let Some(data_obj) = tape.get("data") {
         
         // Process Bids
         if let Some(bids) = data_obj.get("b") {
             if let Some(arr) = bids.as_array() {
+                 levels += arr.len() as u32;
                  for item in arr {
                      // item is [price_string, qty_string] in Bybit usually
                      // or [price_num, qty_num] depending on API version.
@@ -46,6 +72,7 @@ pub fn parse_and_update(data: &mut [u8], book: &mut L2OrderBook) -> Result<u64,
         // Process Asks
         if let Some(asks) = data_obj.get("a") {
              if let Some(arr) = asks.as_array() {
+                 levels += arr.len() as u32;
                  for item in arr {
                      if let Some(arr_entry) = item.as_array() {
                          if arr_entry.len() >= 2 {
@@ -61,6 +88,227 @@ pub fn parse_and_update(data: &mut [u8], book: &mut L2OrderBook) -> Result<u64,
             }
         }
     }
+
+    if is_snapshot {
+        book.snapshot_applied = true;
+    }
     
-    Ok(ts)
+    Ok(BookUpdate { ts, update_id, snapshot: is_snapshot, levels })
+}
+
+// --- Other public topics: tickers / publicTrade / kline ---
+// Same approach as the book: borrow the tape, pull the fields we need, no intermediate structs.
+// Events are Copy (no symbol strings: one symbol per connection). Array payloads
+// (trades, klines) are handed to a callback one by one instead of collected into a Vec.
+
+#[derive(Debug)]
+pub enum ParseError {
+    Json(simd_json::Error),
+    /// Valid JSON, but not the schema we expected.
+    Schema(&'static str),
+}
+
+impl From<simd_json::Error> for ParseError {
+    fn from(e: simd_json::Error) -> Self {
+        ParseError::Json(e)
+    }
+}
+
+/// `tickers.{symbol}`. Deltas carry only changed fields, hence the Options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TickerEvent {
+    pub ts: u64,
+    pub snapshot: bool,
+    pub last_price: Option<f64>,
+    pub mark_price: Option<f64>,
+    pub index_price: Option<f64>,
+    pub bid1_price: Option<f64>,
+    pub bid1_size: Option<f64>,
+    pub ask1_price: Option<f64>,
+    pub ask1_size: Option<f64>,
+    pub funding_rate: Option<f64>,
+    pub open_interest: Option<f64>,
+}
+
+/// One entry of `publicTrade.{symbol}`. `side` is the taker side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeEvent {
+    pub ts: u64,
+    pub side: Side,
+    pub price: f64,
+    pub qty: f64,
+    pub block_trade: bool,
+}
+
+/// One candle of `kline.{interval}.{symbol}`. `confirm` = candle closed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KlineEvent {
+    pub start: u64,
+    pub end: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub turnover: f64,
+    pub confirm: bool,
+}
+
+/// Normalized public market event, whatever topic it came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketEvent {
+    Ticker(TickerEvent),
+    Trade(TradeEvent),
+    Kline(KlineEvent),
+}
+
+// Bybit sends numbers as strings ("16578.50"); accept plain numbers too.
+pub(crate) fn num_field(obj: &simd_json::BorrowedValue, key: &str) -> Option<f64> {
+    let v = obj.get(key)?;
+    match v.as_str() {
+        Some(s) => s.parse::<f64>().ok(),
+        None => v.cast_f64(),
+    }
+}
+
+pub(crate) fn u64_field(obj: &simd_json::BorrowedValue, key: &str) -> Option<u64> {
+    let v = obj.get(key)?;
+    match v.as_str() {
+        Some(s) => s.parse::<u64>().ok(),
+        None => v.as_u64(),
+    }
+}
+
+/// String field, "" when absent or not a string.
+pub(crate) fn str_field<'v>(obj: &'v simd_json::BorrowedValue<'v>, key: &str) -> &'v str {
+    obj.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Array field, empty when absent or not an array.
+pub(crate) fn list<'v>(obj: &'v simd_json::BorrowedValue<'v>, key: &str) -> &'v [simd_json::BorrowedValue<'v>] {
+    obj.get(key).and_then(|v| v.as_array()).map(|a| a.as_slice()).unwrap_or(&[])
+}
+
+pub fn parse_ticker(data: &mut [u8]) -> Result<TickerEvent, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    let d = tape.get("data").ok_or(ParseError::Schema("ticker: missing data"))?;
+    Ok(TickerEvent {
+        ts: tape.get("ts").and_then(|v| v.as_u64()).unwrap_or(0),
+        snapshot: tape.get("type").and_then(|v| v.as_str()) == Some("snapshot"),
+        last_price: num_field(d, "lastPrice"),
+        mark_price: num_field(d, "markPrice"),
+        index_price: num_field(d, "indexPrice"),
+        bid1_price: num_field(d, "bid1Price"),
+        bid1_size: num_field(d, "bid1Size"),
+        ask1_price: num_field(d, "ask1Price"),
+        ask1_size: num_field(d, "ask1Size"),
+        funding_rate: num_field(d, "fundingRate"),
+        open_interest: num_field(d, "openInterest"),
+    })
+}
+
+/// Calls `on_trade` for each trade in the message. Returns the number of trades.
+pub fn parse_trades<F: FnMut(TradeEvent)>(data: &mut [u8], mut on_trade: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    let arr = tape
+        .get("data")
+        .and_then(|v| v.as_array())
+        .ok_or(ParseError::Schema("publicTrade: data is not an array"))?;
+    let mut n = 0;
+    for t in arr {
+        let side = match t.get("S").and_then(|v| v.as_str()) {
+            Some("Buy") => Side::Buy,
+            Some("Sell") => Side::Sell,
+            _ => return Err(ParseError::Schema("publicTrade: bad side")),
+        };
+        let price = num_field(t, "p").ok_or(ParseError::Schema("publicTrade: missing price"))?;
+        let qty = num_field(t, "v").ok_or(ParseError::Schema("publicTrade: missing qty"))?;
+        on_trade(TradeEvent {
+            ts: u64_field(t, "T").unwrap_or(0),
+            side,
+            price,
+            qty,
+            block_trade: t.get("BT").and_then(|v| v.as_bool()).unwrap_or(false),
+        });
+        n += 1;
+    }
+    Ok(n)
+}
+
+/// Calls `on_kline` for each candle in the message. Returns the number of candles.
+pub fn parse_klines<F: FnMut(KlineEvent)>(data: &mut [u8], mut on_kline: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    let arr = tape
+        .get("data")
+        .and_then(|v| v.as_array())
+        .ok_or(ParseError::Schema("kline: data is not an array"))?;
+    let mut n = 0;
+    for k in arr {
+        let field = |key| num_field(k, key).ok_or(ParseError::Schema("kline: missing OHLCV field"));
+        on_kline(KlineEvent {
+            start: u64_field(k, "start").unwrap_or(0),
+            end: u64_field(k, "end").unwrap_or(0),
+            open: field("open")?,
+            high: field("high")?,
+            low: field("low")?,
+            close: field("close")?,
+            volume: field("volume")?,
+            turnover: field("turnover")?,
+            confirm: k.get("confirm").and_then(|v| v.as_bool()).unwrap_or(false),
+        });
+        n += 1;
+    }
+    Ok(n)
+}
+
+/// Dispatches on the topic prefix. Orderbook messages are not handled here
+/// (they go through `parse_and_update`); unknown topics yield `Ok(0)`.
+pub fn parse_market_event<F: FnMut(MarketEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    let topic = topic_kind(data);
+    match topic {
+        TopicKind::Ticker => parse_ticker(data).map(|t| {
+            on_event(MarketEvent::Ticker(t));
+            1
+        }),
+        TopicKind::Trade => parse_trades(data, |t| on_event(MarketEvent::Trade(t))),
+        TopicKind::Kline => parse_klines(data, |k| on_event(MarketEvent::Kline(k))),
+        TopicKind::Other => Ok(0),
+    }
+}
+
+/// Symbol a public message is about: the last dot-separated part of its topic
+/// ("orderbook.50.BTCUSDT" -> "BTCUSDT"). Raw bytes, so call it before parsing in place.
+pub fn topic_symbol(data: &[u8]) -> Option<&[u8]> {
+    let topic = &data[topic_start(data)?..];
+    let topic = &topic[..topic.iter().position(|&b| b == b'"')?];
+    let dot = topic.iter().rposition(|&b| b == b'.')?;
+    Some(&topic[dot + 1..])
+}
+
+enum TopicKind {
+    Ticker,
+    Trade,
+    Kline,
+    Other,
+}
+
+// simd-json parses in place and mangles the buffer, so the topic is sniffed from the
+// raw bytes before the real parse. Bybit puts "topic" first in public messages.
+fn topic_start(data: &[u8]) -> Option<usize> {
+    const KEY: &[u8] = b"\"topic\":\"";
+    data.windows(KEY.len()).position(|w| w == KEY).map(|pos| pos + KEY.len())
+}
+
+fn topic_kind(data: &[u8]) -> TopicKind {
+    let Some(start) = topic_start(data) else { return TopicKind::Other };
+    let rest = &data[start..];
+    if rest.starts_with(b"tickers.") {
+        TopicKind::Ticker
+    } else if rest.starts_with(b"publicTrade.") {
+        TopicKind::Trade
+    } else if rest.starts_with(b"kline.") {
+        TopicKind::Kline
+    } else {
+        TopicKind::Other
+    }
 }
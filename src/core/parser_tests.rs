@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::orderbook::Side;
+    use crate::core::parser::{self, MarketEvent, ParseError};
+
+    // Captured from wss://stream.bybit.com/v5/public/linear (trimmed to the fields we read).
+    const TICKER_SNAPSHOT: &str = r#"{"topic":"tickers.BTCUSDT","type":"snapshot","data":{"symbol":"BTCUSDT","tickDirection":"PlusTick","price24hPcnt":"0.017103","lastPrice":"17216.00","prevPrice24h":"16926.50","highPrice24h":"17281.50","lowPrice24h":"16915.00","prevPrice1h":"17238.00","markPrice":"17217.33","indexPrice":"17227.36","openInterest":"68744.761","openInterestValue":"1183601235.91","turnover24h":"1570383121.943499","volume24h":"91705.276","nextFundingTime":"1673280000000","fundingRate":"-0.000212","bid1Price":"17215.50","bid1Size":"84.489","ask1Price":"17216.00","ask1Size":"83.020"},"cs":24987956059,"ts":1673272861686}"#;
+    const TICKER_DELTA: &str = r#"{"topic":"tickers.BTCUSDT","type":"delta","data":{"symbol":"BTCUSDT","markPrice":"17218.10","bid1Price":"17215.00","bid1Size":"12.000"},"cs":24987956060,"ts":1673272861786}"#;
+    const PUBLIC_TRADE: &str = r#"{"topic":"publicTrade.BTCUSDT","type":"snapshot","ts":1672304486868,"data":[{"T":1672304486865,"s":"BTCUSDT","S":"Buy","v":"0.001","p":"16578.50","L":"PlusTick","i":"20f43950-d8dd-5b31-9112-a178eb6023af","BT":false},{"T":1672304486866,"s":"BTCUSDT","S":"Sell","v":"0.250","p":"16578.00","L":"MinusTick","i":"20f43950-d8dd-5b31-9112-a178eb6023b0","BT":true}]}"#;
+    const KLINE: &str = r#"{"topic":"kline.5.BTCUSDT","data":[{"start":1672324800000,"end":1672325099999,"interval":"5","open":"16649.5","close":"16677","high":"16677","low":"16608","volume":"2.081","turnover":"34666.4005","confirm":false,"timestamp":1672324988882}],"ts":1672324988882,"type":"snapshot"}"#;
+
+    fn bytes(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn ticker_snapshot() {
+        let t = parser::parse_ticker(&mut bytes(TICKER_SNAPSHOT)).unwrap();
+        assert!(t.snapshot);
+        assert_eq!(t.ts, 1673272861686);
+        assert_eq!(t.last_price, Some(17216.0));
+        assert_eq!(t.mark_price, Some(17217.33));
+        assert_eq!(t.bid1_price, Some(17215.5));
+        assert_eq!(t.ask1_size, Some(83.02));
+        assert_eq!(t.funding_rate, Some(-0.000212));
+    }
+
+    #[test]
+    fn ticker_delta_keeps_missing_fields_empty() {
+        let t = parser::parse_ticker(&mut bytes(TICKER_DELTA)).unwrap();
+        assert!(!t.snapshot);
+        assert_eq!(t.mark_price, Some(17218.1));
+        assert_eq!(t.bid1_size, Some(12.0));
+        assert_eq!(t.last_price, None);
+        assert_eq!(t.ask1_price, None);
+    }
+
+    #[test]
+    fn public_trades() {
+        let mut trades = Vec::new();
+        let n = parser::parse_trades(&mut bytes(PUBLIC_TRADE), |t| trades.push(t)).unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(trades[0].side, Side::Buy);
+        assert_eq!(trades[0].price, 16578.5);
+        assert_eq!(trades[0].qty, 0.001);
+        assert_eq!(trades[0].ts, 1672304486865);
+        assert!(!trades[0].block_trade);
+        assert_eq!(trades[1].side, Side::Sell);
+        assert!(trades[1].block_trade);
+    }
+
+    #[test]
+    fn kline() {
+        let mut klines = Vec::new();
+        let n = parser::parse_klines(&mut bytes(KLINE), |k| klines.push(k)).unwrap();
+        assert_eq!(n, 1);
+        let k = klines[0];
+        assert_eq!(k.start, 1672324800000);
+        assert_eq!((k.open, k.high, k.low, k.close), (16649.5, 16677.0, 16608.0, 16677.0));
+        assert_eq!(k.volume, 2.081);
+        assert!(!k.confirm);
+    }
+
+    #[test]
+    fn dispatch_by_topic() {
+        let mut events = Vec::new();
+        for fixture in [TICKER_SNAPSHOT, PUBLIC_TRADE, KLINE] {
+            parser::parse_market_event(&mut bytes(fixture), |e| events.push(e)).unwrap();
+        }
+        assert!(matches!(events[0], MarketEvent::Ticker(_)));
+        assert!(matches!(events[1], MarketEvent::Trade(_)));
+        assert!(matches!(events[2], MarketEvent::Trade(_)));
+        assert!(matches!(events[3], MarketEvent::Kline(_)));
+
+        let book = r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1,"data":{"b":[],"a":[]}}"#;
+        assert_eq!(parser::parse_market_event(&mut bytes(book), |_| {}).unwrap(), 0);
+    }
+
+    #[test]
+    fn symbol_from_topic() {
+        let book = r#"{"topic":"orderbook.50.ETHUSDT","type":"delta","ts":1,"data":{"b":[],"a":[]}}"#;
+        assert_eq!(parser::topic_symbol(book.as_bytes()), Some(&b"ETHUSDT"[..]));
+        assert_eq!(parser::topic_symbol(TICKER_DELTA.as_bytes()), Some(&b"BTCUSDT"[..]));
+        assert_eq!(parser::topic_symbol(KLINE.as_bytes()), Some(&b"BTCUSDT"[..]));
+        assert_eq!(parser::topic_symbol(br#"{"op":"subscribe","success":true}"#), None);
+    }
+
+    #[test]
+    fn book_ready_only_after_snapshot() {
+        use crate::core::orderbook::L2OrderBook;
+        let mut book = L2OrderBook::new();
+
+        let delta = r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1,"data":{"b":[["100.0","1"],["99.9","1"]],"a":[["100.1","1"],["100.2","1"]]}}"#;
+        parser::parse_and_update(&mut bytes(delta), &mut book).unwrap();
+        assert!(!book.is_ready(2));
+
+        let snapshot = r#"{"topic":"orderbook.50.BTCUSDT","type":"snapshot","ts":2,"data":{"b":[["101.0","1"],["100.9","1"]],"a":[["101.1","1"]]}}"#;
+        parser::parse_and_update(&mut bytes(snapshot), &mut book).unwrap();
+        // Snapshot replaced the delta levels
+        assert_eq!(book.bids[0].price, 101.0);
+        assert_eq!(book.depth(Side::Buy), 2);
+        assert!(book.is_ready(1));
+        assert!(!book.is_ready(2)); // one ask only
+    }
+
+    #[test]
+    fn schema_errors() {
+        let bad_side = r#"{"topic":"publicTrade.BTCUSDT","data":[{"T":1,"S":"Up","v":"1","p":"1"}]}"#;
+        assert!(matches!(parser::parse_trades(&mut bytes(bad_side), |_| {}), Err(ParseError::Schema(_))));
+
+        let no_close = r#"{"topic":"kline.5.BTCUSDT","data":[{"open":"1","high":"1","low":"1","volume":"1","turnover":"1"}]}"#;
+        assert!(matches!(parser::parse_klines(&mut bytes(no_close), |_| {}), Err(ParseError::Schema(_))));
+
+        assert!(matches!(parser::parse_ticker(&mut bytes("{\"topic\":")), Err(ParseError::Json(_))));
+    }
+}
@@ -0,0 +1,136 @@
+// Tick and lot grid arithmetic. Every price and quantity that reaches the exchange is put on
+// the instrument's grid here: strategy targets, exit prices, clips, closes and the order
+// serializer. `value / step` is never an exact integer in floats (100.05 / 0.1 =
+// 1000.4999999999999), so each rule nudges the step count by a tolerance relative to its
+// size before rounding. A price exactly between two ticks rounds away from zero, a lot
+// exactly between two steps likewise, and a quantity below one step floors to 0.
+// Tolerance for float noise, in steps (relative to the step count above 1)
+pub const STEP_EPSILON: f64 = 1e-9;
+// On-grid check tolerance, in steps: validation accepts what the rounding produces with room
+// to spare (a value printed with the grid's decimals and parsed back is on it too)
+const GRID_TOLERANCE: f64 = 1e-6;
+// Bybit prints no more than 8 decimals
+const MAX_DECIMALS: usize = 8;
+
+#[inline(always)]
+fn tolerance(steps: f64, eps: f64) -> f64 {
+    eps * steps.abs().max(1.0)
+}
+
+/// Whole steps in `value`, nearest (ties away from zero).
+#[inline(always)]
+pub fn round_steps(value: f64, step: f64) -> f64 {
+    let steps = value / step;
+    (steps + tolerance(steps, STEP_EPSILON).copysign(steps)).round()
+}
+
+/// Whole steps in `value`, rounded down.
+#[inline(always)]
+pub fn floor_steps(value: f64, step: f64) -> f64 {
+    let steps = value / step;
+    (steps + tolerance(steps, STEP_EPSILON)).floor()
+}
+
+/// Whole steps in `value`, rounded up.
+#[inline(always)]
+pub fn ceil_steps(value: f64, step: f64) -> f64 {
+    let steps = value / step;
+    (steps - tolerance(steps, STEP_EPSILON)).ceil()
+}
+
+/// `value` to the nearest multiple of `step`; unchanged without a grid (`step <= 0`).
+#[inline(always)]
+pub fn round_to(value: f64, step: f64) -> f64 {
+    if step <= 0.0 { value } else { round_steps(value, step) * step }
+}
+
+/// `value` down to a multiple of `step`; unchanged without a grid.
+#[inline(always)]
+pub fn floor_to(value: f64, step: f64) -> f64 {
+    if step <= 0.0 { value } else { floor_steps(value, step) * step }
+}
+
+/// `value` up to a multiple of `step`; unchanged without a grid.
+#[inline(always)]
+pub fn ceil_to(value: f64, step: f64) -> f64 {
+    if step <= 0.0 { value } else { ceil_steps(value, step) * step }
+}
+
+/// `value` is a multiple of `step` (anything is, without a grid).
+pub fn on_grid(value: f64, step: f64) -> bool {
+    if step <= 0.0 {
+        return true;
+    }
+    let steps = value / step;
+    (steps - steps.round()).abs() < tolerance(steps, GRID_TOLERANCE)
+}
+
+/// Decimals that print every multiple of `step` exactly (0.01 -> 2, 0.5 -> 1, 10 -> 0).
+pub fn step_decimals(step: f64) -> usize {
+    let mut decimals = 0;
+    let mut scaled = step;
+    while decimals < MAX_DECIMALS && (scaled - scaled.round()).abs() > STEP_EPSILON {
+        scaled *= 10.0;
+        decimals += 1;
+    }
+    decimals
+}
+
+/// Price and lot grid of one instrument (`Instrument::grid`). Quantities are in the unit
+/// the grid belongs to: the exchange's for an `Instrument`, base coin for the strategy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    pub tick_size: f64,
+    pub qty_step: f64,
+    pub min_qty: f64,
+}
+
+impl Grid {
+    pub const fn new(tick_size: f64, qty_step: f64, min_qty: f64) -> Self {
+        Self { tick_size, qty_step, min_qty }
+    }
+
+    /// Nearest tick.
+    #[inline(always)]
+    pub fn price(&self, price: f64) -> f64 {
+        round_to(price, self.tick_size)
+    }
+
+    /// Tick at or below `price` (a buy that must not pay more).
+    #[inline(always)]
+    pub fn price_down(&self, price: f64) -> f64 {
+        floor_to(price, self.tick_size)
+    }
+
+    /// Tick at or above `price` (a sell that must not take less).
+    #[inline(always)]
+    pub fn price_up(&self, price: f64) -> f64 {
+        ceil_to(price, self.tick_size)
+    }
+
+    /// Nearest lot; may be 0 below half a step.
+    #[inline(always)]
+    pub fn qty(&self, qty: f64) -> f64 {
+        round_to(qty, self.qty_step)
+    }
+
+    /// Whole lots in `qty` (never more than held); 0 below one step.
+    #[inline(always)]
+    pub fn qty_down(&self, qty: f64) -> f64 {
+        floor_to(qty, self.qty_step)
+    }
+
+    /// Quote size: nearest lot, at least `min_qty`.
+    #[inline(always)]
+    pub fn clip(&self, qty: f64) -> f64 {
+        self.qty(qty).max(self.min_qty)
+    }
+
+    pub fn price_decimals(&self) -> usize {
+        step_decimals(self.tick_size)
+    }
+
+    pub fn qty_decimals(&self) -> usize {
+        step_decimals(self.qty_step)
+    }
+}
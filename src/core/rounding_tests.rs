@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::rounding::{self, Grid};
+    use crate::core::serializer::write_order_json;
+    use crate::strategy::symbols::{Category, Instrument};
+
+    // RIVERUSDT-like linear grid
+    const GRID: Grid = Grid::new(0.001, 0.1, 0.1);
+
+    #[test]
+    fn price_exactly_between_ticks_rounds_away_from_zero() {
+        // 100.05 / 0.1 is 1000.4999999999999 in floats: the naive round gives 100.0
+        let grid = Grid::new(0.1, 0.1, 0.1);
+        assert!((grid.price(100.05) - 100.1).abs() < 1e-9);
+        assert!((grid.price(100.04) - 100.0).abs() < 1e-9);
+        assert!((GRID.price(1.2345) - 1.235).abs() < 1e-12);
+        // Passive rounding never moves past the target
+        assert!((GRID.price_down(1.2349) - 1.234).abs() < 1e-12);
+        assert!((GRID.price_up(1.2341) - 1.235).abs() < 1e-12);
+        // Already on the grid stays put despite the float noise of the division
+        assert!((grid.price_up(0.3) - 0.3).abs() < 1e-12);
+        assert!((grid.price_down(0.3) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn qty_below_one_step() {
+        assert_eq!(GRID.qty_down(0.09), 0.0);
+        assert_eq!(GRID.qty(0.04), 0.0);
+        assert!((GRID.qty(0.05) - 0.1).abs() < 1e-12);
+        // A quote is never below the instrument minimum
+        assert!((GRID.clip(0.04) - 0.1).abs() < 1e-12);
+        assert!((Grid::new(0.001, 0.1, 0.5).clip(0.3) - 0.5).abs() < 1e-12);
+        // 0.1 + 0.2 is 0.30000000000000004, whole lots are still 3
+        assert!((GRID.qty_down(0.1 + 0.2) - 0.3).abs() < 1e-12);
+        assert!((GRID.qty_down(0.7 * 3.0) - 2.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn no_grid_leaves_the_value_alone() {
+        assert_eq!(rounding::round_to(1.23456, 0.0), 1.23456);
+        assert_eq!(rounding::floor_to(1.23456, 0.0), 1.23456);
+        assert!(rounding::on_grid(1.23456, 0.0));
+    }
+
+    #[test]
+    fn rounded_values_pass_the_grid_check_and_print_exactly() {
+        let grid = Grid::new(0.0001, 0.01, 0.01);
+        assert_eq!((grid.price_decimals(), grid.qty_decimals()), (4, 2));
+        assert_eq!(rounding::step_decimals(0.5), 1);
+        assert_eq!(rounding::step_decimals(10.0), 0);
+        for i in 0..1_000 {
+            let px = grid.price(2.5 + i as f64 * 0.000_173);
+            let qty = grid.qty(i as f64 * 0.037);
+            assert!(rounding::on_grid(px, grid.tick_size), "{}", px);
+            assert!(rounding::on_grid(qty, grid.qty_step), "{}", qty);
+        }
+
+        // The serializer and the strategy agree on the same ambiguous inputs
+        let mut buf = [0u8; 256];
+        let n = write_order_json(&mut buf, "linear", "RIVERUSDT", "Buy", 0.25, 1.2345, &GRID);
+        let json = std::str::from_utf8(&buf[..n]).unwrap();
+        assert!(json.contains(r#""qty":"0.3","price":"1.235""#), "{}", json);
+    }
+
+    #[test]
+    fn inverse_contracts_on_the_lot_grid() {
+        let inverse = Instrument {
+            name: "BTCUSD",
+            base: "BTC",
+            quote: "USD",
+            category: Category::Inverse,
+            tick_size: 0.5,
+            qty_step: 1.0,
+            min_qty: 1.0,
+            venue_symbols: [Some("BTCUSD"), None, None],
+        };
+        // 0.0001 BTC at 30000 is 3 contracts, below the minimum is 1
+        assert_eq!(inverse.order_qty(0.0001, 30_000.0), 3.0);
+        assert_eq!(inverse.order_qty(0.000_001, 30_000.0), 1.0);
+        // Half a contract rounds up
+        assert_eq!(inverse.order_qty(0.0025, 1_000.0), 3.0);
+        assert_eq!(inverse.grid().price(30_000.25), 30_000.5);
+    }
+}
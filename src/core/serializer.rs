@@ -1,27 +1,27 @@
 use std::io::Write;
-use ryu; // Fast float to string
+use crate::core::rounding::Grid;
 
 /// Serializes a JSON limit order into the buffer for Bybit V5.
-/// Format: {"category":"linear","symbol":"...","side":"...","orderType":"Limit","qty":"...","price":"...","timeInForce":"PostOnly"}
+/// Format: {"category":"<linear|inverse|spot|option>","symbol":"...","side":"...","orderType":"Limit","qty":"...","price":"...","timeInForce":"PostOnly"}
 /// Returns the number of bytes written.
-pub fn write_order_json(buf: &mut [u8], symbol: &str, side: &str, qty: f64, price: f64) -> usize {
+/// `qty` must already be in the category's unit (`Instrument::order_qty`); qty and price are
+/// put on the instrument's grid (`Instrument::grid`, nearest step) and printed with its decimals, so
+/// float noise like 0.30000000000000004 never reaches the exchange.
+pub fn write_order_json(buf: &mut [u8], category: &str, symbol: &str, side: &str, qty: f64, price: f64, grid: &Grid) -> usize {
     let mut cursor = std::io::Cursor::new(buf);
     
     // We construct the JSON manually to avoid allocation
-    // {"reqId":"...","category":"linear","symbol":"...","side":"...","orderType":"Limit","qty":"...","price":"...","timeInForce":"PostOnly"}
+    // {"reqId":"...","category":"...","symbol":"...","side":"...","orderType":"Limit","qty":"...","price":"...","timeInForce":"PostOnly"}
     // For HFT challenge we omit reqId for simplicity unless needed for matching
     
-    let _ = write!(cursor, r#"{{"category":"linear","symbol":"{}","side":"{}","orderType":"Limit","qty":""#, symbol, side);
+    let _ = write!(cursor, r#"{{"category":"{}","symbol":"{}","side":"{}","orderType":"Limit","qty":""#, category, symbol, side);
     
-    // Write Float Qty
-    let mut buffer = ryu::Buffer::new();
-    let _ = cursor.write_all(buffer.format(qty).as_bytes());
+    // Fixed precision from the lot / tick grid (write! into the slice, no allocation)
+    let _ = write!(cursor, "{:.*}", grid.qty_decimals(), grid.qty(qty));
     
     let _ = cursor.write_all(r#"","price":""#.as_bytes());
     
-    // Write Float Price
-    let mut buffer2 = ryu::Buffer::new();
-    let _ = cursor.write_all(buffer2.format(price).as_bytes());
+    let _ = write!(cursor, "{:.*}", grid.price_decimals(), grid.price(price));
     
     let _ = cursor.write_all(r#"","timeInForce":"PostOnly"}}"#.as_bytes());
     
@@ -0,0 +1,158 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Deserialize;
+use crate::core::clock::LiveClock;
+
+// Request timestamps. Bybit accepts a signed request when
+//   server_time - recv_window <= timestamp < server_time + 1000
+// so we stamp slightly in the past: by `synced_margin_ms` once the Trade WS `Timenow`
+// header has given us the offset, by larger fixed margins where no offset is known
+// (Trade WS before the first response, REST thread before the Hot Thread has synced).
+// Every request timestamp (Trade WS orders, REST cancel-all and queries, WS auth expiry)
+// is computed by `TimeSyncConfig::timestamp_ms` / `auth_expires_ms` with numbers from the
+// `[time]` table of the engine config.
+const DEFAULT_RECV_WINDOW_MS: u64 = 20000;
+const DEFAULT_SYNCED_MARGIN_MS: i64 = 500;
+// Local clock is ahead of Bybit on our boxes; stay in the past
+const DEFAULT_UNSYNCED_MARGIN_MS: i64 = 2000;
+const DEFAULT_REST_MARGIN_MS: i64 = 6000;
+const DEFAULT_RESYNC_THRESHOLD_MS: i64 = 1000;
+// WS auth signature lifetime
+const DEFAULT_AUTH_EXPIRY_MS: u64 = 5000;
+/// Bybit rejects timestamps further than this ahead of its clock.
+pub const MAX_FUTURE_MS: i64 = 1000;
+
+// Offset in use on the Trade WS, shared with the REST thread; i64::MIN = not synced yet
+static SYNCED_OFFSET_MS: AtomicI64 = AtomicI64::new(i64::MIN);
+
+/// Local wall clock, ms since the Unix epoch.
+pub fn wall_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Exchange minus local clock as last accepted by the Hot Thread's `TimeSync`.
+pub fn synced_offset_ms() -> Option<i64> {
+    Some(SYNCED_OFFSET_MS.load(Ordering::Relaxed)).filter(|&offset| offset != i64::MIN)
+}
+
+/// The `[time]` table of the engine config.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeSyncConfig {
+    /// X-BAPI-RECV-WINDOW on every signed request (WS and REST).
+    pub recv_window_ms: u64,
+    /// Subtracted from the estimated exchange time once the offset is known.
+    pub synced_margin_ms: i64,
+    /// Subtracted from local time on the Trade WS before the first `Timenow`.
+    pub unsynced_margin_ms: i64,
+    /// Subtracted from local time by the REST client (no offset on that thread).
+    pub rest_margin_ms: i64,
+    /// A new `Timenow` offset replaces the current one only when it moved by more than this.
+    pub resync_threshold_ms: i64,
+    /// `expires` of the private / Trade WS auth signature, ahead of the exchange clock.
+    pub auth_expiry_ms: u64,
+}
+
+impl Default for TimeSyncConfig {
+    fn default() -> Self {
+        Self {
+            recv_window_ms: DEFAULT_RECV_WINDOW_MS,
+            synced_margin_ms: DEFAULT_SYNCED_MARGIN_MS,
+            unsynced_margin_ms: DEFAULT_UNSYNCED_MARGIN_MS,
+            rest_margin_ms: DEFAULT_REST_MARGIN_MS,
+            resync_threshold_ms: DEFAULT_RESYNC_THRESHOLD_MS,
+            auth_expiry_ms: DEFAULT_AUTH_EXPIRY_MS,
+        }
+    }
+}
+
+impl TimeSyncConfig {
+    /// X-BAPI-TIMESTAMP: estimated exchange time minus `synced_margin_ms` when the offset is
+    /// known, else local time minus `unsynced_margin_ms` (Trade WS) or `rest_margin_ms` (REST).
+    #[inline(always)]
+    pub fn timestamp_ms(&self, local_ms: u64, offset_ms: Option<i64>, unsynced_margin_ms: i64) -> u64 {
+        let ts = match offset_ms {
+            Some(offset) => local_ms as i64 + offset - self.synced_margin_ms,
+            None => local_ms as i64 - unsynced_margin_ms,
+        };
+        ts.max(0) as u64
+    }
+
+    /// REST request timestamp: the Trade WS offset once the Hot Thread has synced.
+    pub fn rest_timestamp_ms(&self, local_ms: u64) -> u64 {
+        self.timestamp_ms(local_ms, synced_offset_ms(), self.rest_margin_ms)
+    }
+
+    /// `expires` of a WS auth request.
+    pub fn auth_expires_ms(&self, local_ms: u64, offset_ms: Option<i64>) -> u64 {
+        (local_ms as i64 + offset_ms.unwrap_or(0)).max(0) as u64 + self.auth_expiry_ms
+    }
+
+    /// Startup check. `skew_ms` = exchange minus local clock, measured once.
+    /// Every way we stamp requests must land inside the window the exchange accepts.
+    pub fn check_skew(&self, skew_ms: i64) -> Result<(), String> {
+        // How far behind the exchange's clock each kind of timestamp ends up
+        let lags = [
+            ("synced WS", self.synced_margin_ms),
+            ("unsynced WS", skew_ms + self.unsynced_margin_ms),
+            ("REST", skew_ms + self.rest_margin_ms),
+        ];
+        for (name, lag) in lags {
+            if lag >= self.recv_window_ms as i64 {
+                return Err(format!("{} timestamps would be {} ms behind the exchange (skew {} ms), recv_window is {} ms",
+                    name, lag, skew_ms, self.recv_window_ms));
+            }
+            if lag <= -MAX_FUTURE_MS {
+                return Err(format!("{} timestamps would be {} ms ahead of the exchange (skew {} ms), limit is {} ms",
+                    name, -lag, skew_ms, MAX_FUTURE_MS));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Trade WS clock sync, owned by the Hot Thread. Publishes the raw offset to
+/// `LiveClock` (`Clock::exchange_time_ms`) and stamps outgoing requests.
+#[derive(Debug)]
+pub struct TimeSync {
+    pub config: TimeSyncConfig,
+    offset_ms: Option<i64>,
+}
+
+impl TimeSync {
+    pub fn new(config: TimeSyncConfig) -> Self {
+        Self { config, offset_ms: None }
+    }
+
+    /// Exchange minus local clock in use for request timestamps; None until the first `Timenow`.
+    pub fn offset_ms(&self) -> Option<i64> {
+        self.offset_ms
+    }
+
+    /// `Timenow` header of a Trade WS response. Returns the new offset when it was (re)set;
+    /// small moves are ignored to avoid jitter in the timestamps.
+    pub fn on_server_time(&mut self, server_ms: u64, local_ms: u64) -> Option<i64> {
+        let drift = server_ms as i64 - local_ms as i64;
+        // Raw offset (no safety margin) for Clock::exchange_time_ms
+        LiveClock::set_exchange_offset_ms(drift);
+        match self.offset_ms {
+            Some(current) if (current - drift).abs() <= self.config.resync_threshold_ms => None,
+            _ => {
+                self.offset_ms = Some(drift);
+                SYNCED_OFFSET_MS.store(drift, Ordering::Relaxed);
+                Some(drift)
+            }
+        }
+    }
+
+    /// X-BAPI-TIMESTAMP for a Trade WS request.
+    #[inline(always)]
+    pub fn request_ts_ms(&self, local_ms: u64) -> u64 {
+        self.config.timestamp_ms(local_ms, self.offset_ms, self.config.unsynced_margin_ms)
+    }
+
+    /// `expires` of a private / Trade WS auth request.
+    pub fn auth_expires_ms(&self, local_ms: u64) -> u64 {
+        self.config.auth_expires_ms(local_ms, self.offset_ms)
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::time_sync::{TimeSync, TimeSyncConfig};
+
+    #[test]
+    fn request_timestamps_use_margin_until_synced() {
+        let mut sync = TimeSync::new(TimeSyncConfig::default());
+        assert_eq!(sync.request_ts_ms(10_000), 8_000); // unsynced: local - 2000
+
+        // Exchange 300ms behind us
+        assert_eq!(sync.on_server_time(9_700, 10_000), Some(-300));
+        assert_eq!(sync.request_ts_ms(10_000), 9_200); // 10_000 - 300 - 500
+        assert_eq!(sync.auth_expires_ms(10_000), 14_700); // exchange time + 5000
+    }
+
+    #[test]
+    fn rest_and_ws_stamps_share_one_rule() {
+        let config = TimeSyncConfig { synced_margin_ms: 200, rest_margin_ms: 3_000, auth_expiry_ms: 10_000, ..TimeSyncConfig::default() };
+        // Before any offset REST keeps its wider margin, after it both paths stamp alike
+        assert_eq!(config.timestamp_ms(10_000, None, config.rest_margin_ms), 7_000);
+        assert_eq!(config.timestamp_ms(10_000, Some(-300), config.rest_margin_ms), 9_500);
+        assert_eq!(config.timestamp_ms(1_000, None, config.rest_margin_ms), 0);
+        assert_eq!(config.auth_expires_ms(10_000, None), 20_000);
+        assert!(config.check_skew(-1_500).is_ok());
+    }
+
+    #[test]
+    fn small_drift_is_ignored_large_drift_resyncs() {
+        let mut sync = TimeSync::new(TimeSyncConfig::default());
+        sync.on_server_time(10_000, 10_000);
+        assert_eq!(sync.on_server_time(10_800, 10_000), None);
+        assert_eq!(sync.offset_ms(), Some(0));
+        assert_eq!(sync.on_server_time(11_500, 10_000), Some(1_500));
+    }
+
+    #[test]
+    fn startup_check_rejects_skew_outside_recv_window() {
+        let config = TimeSyncConfig::default();
+        assert!(config.check_skew(0).is_ok());
+        assert!(config.check_skew(-1_500).is_ok()); // local 1.5s ahead: still behind the exchange everywhere
+        // Local clock 3.5s ahead: pre-sync WS timestamps (local - 2000) would be 1.5s in the exchange's future
+        assert!(config.check_skew(-3_500).is_err());
+        // Local clock 15s behind: REST timestamps fall out of the 20s window
+        assert!(config.check_skew(15_000).is_err());
+
+        let wide = TimeSyncConfig { recv_window_ms: 30_000, ..config };
+        assert!(wide.check_skew(15_000).is_ok());
+    }
+}
@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+// Parse + book update cost per message, per symbol, against a latency budget.
+// Fixed-bucket histograms (no allocation, one add per sample) give the window's p99.
+// Cost per level is tracked next to the total: when the total p99 blows the budget
+// but the per-level p99 didn't move, the messages got deeper (market); when the
+// per-level p99 moved too, the code got slower (regression).
+// The budget matches the criterion baseline (benches/book_update.rs, < 5us per delta).
+pub const DEFAULT_BUDGET_NS: u64 = 5_000;
+/// Symbols tracked at once (the bot trades a handful at most).
+pub const MAX_SYMBOLS: usize = 4;
+const BUCKETS: usize = 64;
+// Total: 250ns buckets up to 16us. Per level: 25ns buckets up to 1.6us.
+const TOTAL_BUCKET_NS: u64 = 250;
+const PER_LEVEL_BUCKET_NS: u64 = 25;
+
+/// Linear histogram; samples beyond the last bucket land in the last one (max is kept exactly).
+#[derive(Debug, Clone, Copy)]
+pub struct Histogram {
+    bucket_ns: u64,
+    buckets: [u32; BUCKETS],
+    pub count: u64,
+    pub max_ns: u64,
+}
+
+impl Histogram {
+    pub fn new(bucket_ns: u64) -> Self {
+        Self { bucket_ns, buckets: [0; BUCKETS], count: 0, max_ns: 0 }
+    }
+
+    #[inline(always)]
+    pub fn record(&mut self, ns: u64) {
+        let idx = ((ns / self.bucket_ns) as usize).min(BUCKETS - 1);
+        self.buckets[idx] += 1;
+        self.count += 1;
+        self.max_ns = self.max_ns.max(ns);
+    }
+
+    /// Upper bound of the bucket holding the `pct` percentile (0 if empty).
+    /// Capped at `max_ns` so an overflowing tail reports the real worst case.
+    pub fn percentile(&self, pct: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((self.count as f64 * pct / 100.0).ceil() as u64).max(1);
+        let mut seen = 0u64;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n as u64;
+            if seen >= rank {
+                return ((i as u64 + 1) * self.bucket_ns).min(self.max_ns);
+            }
+        }
+        self.max_ns
+    }
+}
+
+/// One symbol's window summary (Copy, travels inside `MetricsSnapshot`).
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolLatency {
+    pub symbol: &'static str,
+    pub samples: u64,
+    pub p50_ns: u64,
+    pub p99_ns: u64,
+    pub max_ns: u64,
+    pub avg_levels: f64,
+    pub p99_ns_per_level: u64,
+    pub budget_ns: u64,
+}
+
+impl SymbolLatency {
+    pub fn over_budget(&self) -> bool {
+        self.p99_ns > self.budget_ns
+    }
+
+    pub fn print(&self) {
+        println!("BOOKLAT: {} | n {} | p50 {}ns p99 {}ns max {}ns | levels avg {:.1} | p99 {}ns/level",
+            self.symbol, self.samples, self.p50_ns, self.p99_ns, self.max_ns, self.avg_levels, self.p99_ns_per_level);
+        if self.over_budget() {
+            eprintln!("ALERT: [BOOKLAT] {} parse+update p99 {}ns over budget {}ns (avg {:.1} levels, {}ns/level)",
+                self.symbol, self.p99_ns, self.budget_ns, self.avg_levels, self.p99_ns_per_level);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    symbol: &'static str,
+    total: Histogram,
+    per_level: Histogram,
+    levels: u64,
+}
+
+impl Slot {
+    fn new(symbol: &'static str) -> Self {
+        Self {
+            symbol,
+            total: Histogram::new(TOTAL_BUCKET_NS),
+            per_level: Histogram::new(PER_LEVEL_BUCKET_NS),
+            levels: 0,
+        }
+    }
+}
+
+/// Hot Thread accumulator; summarized and reset with every metrics flush.
+pub struct BookLatencyTracker {
+    pub budget_ns: u64,
+    slots: [Option<Slot>; MAX_SYMBOLS],
+}
+
+impl BookLatencyTracker {
+    pub fn new(budget_ns: u64) -> Self {
+        Self { budget_ns, slots: [None; MAX_SYMBOLS] }
+    }
+
+    /// One applied book message. Symbols beyond `MAX_SYMBOLS` are ignored.
+    #[inline(always)]
+    pub fn record(&mut self, symbol: &'static str, elapsed: Duration, levels: u32) {
+        let ns = elapsed.as_nanos() as u64;
+        let Some(slot) = self.slot(symbol) else { return };
+        slot.total.record(ns);
+        slot.levels += levels as u64;
+        if levels > 0 {
+            slot.per_level.record(ns / levels as u64);
+        }
+    }
+
+    fn slot(&mut self, symbol: &'static str) -> Option<&mut Slot> {
+        let idx = self
+            .slots
+            .iter()
+            .position(|s| s.is_some_and(|s| s.symbol == symbol))
+            .or_else(|| self.slots.iter().position(|s| s.is_none()))?;
+        Some(self.slots[idx].get_or_insert_with(|| Slot::new(symbol)))
+    }
+
+    /// Window summaries (symbols with samples only); starts a new window.
+    pub fn flush(&mut self) -> [Option<SymbolLatency>; MAX_SYMBOLS] {
+        let mut out = [None; MAX_SYMBOLS];
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            let Some(s) = slot else { continue };
+            if s.total.count > 0 {
+                out[i] = Some(SymbolLatency {
+                    symbol: s.symbol,
+                    samples: s.total.count,
+                    p50_ns: s.total.percentile(50.0),
+                    p99_ns: s.total.percentile(99.0),
+                    max_ns: s.total.max_ns,
+                    avg_levels: s.levels as f64 / s.total.count as f64,
+                    p99_ns_per_level: s.per_level.percentile(99.0),
+                    budget_ns: self.budget_ns,
+                });
+            }
+            *s = Slot::new(s.symbol);
+        }
+        out
+    }
+}
+
+impl Default for BookLatencyTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUDGET_NS)
+    }
+}
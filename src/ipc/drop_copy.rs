@@ -0,0 +1,361 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rtrb::{Consumer, Producer};
+use crate::strategy::market_maker::ActionType;
+
+// Drop copy: every order we send and every execution the exchange reports, in one
+// normalized schema, for an external risk monitor that must not rely on the strategy's
+// own bookkeeping. Orders are recorded from the action actually handed to the wire
+// (after the OMS gate), executions straight from the exchange message fields.
+// The Hot Thread only copies fixed-size events into a dedicated ring; formatting, file
+// and socket IO happen on the DROP-COPY thread.
+pub const SCHEMA_VERSION: u32 = 1;
+const ID_LEN: usize = 40; // Bybit orderLinkId <= 36, execId is a UUID
+const DEFAULT_RECONNECT_SECS: u64 = 5;
+/// Last events kept on the Hot Thread for incident snapshots (`incident.rs`).
+pub const RECENT_EVENTS: usize = 16;
+
+/// Fixed-capacity ASCII id (orderLinkId / execId), Copy so events can travel through the ring.
+#[derive(Clone, Copy)]
+pub struct FixedId {
+    len: u8,
+    bytes: [u8; ID_LEN],
+}
+
+impl FixedId {
+    pub const EMPTY: FixedId = FixedId { len: 0, bytes: [0; ID_LEN] };
+
+    /// Longer ids are truncated (never the case for Bybit ids).
+    pub fn new(s: &str) -> Self {
+        let mut id = Self::EMPTY;
+        let n = s.len().min(ID_LEN);
+        id.bytes[..n].copy_from_slice(&s.as_bytes()[..n]);
+        id.len = n as u8;
+        id
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or("")
+    }
+}
+
+impl std::fmt::Debug for FixedId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropKind {
+    OrderNew,
+    OrderAmend,
+    OrderCancel,
+    CancelAll,
+    /// Market reduce-only close.
+    ClosePosition,
+    /// Server-side stop loss.
+    TradingStop,
+    Execution,
+}
+
+impl DropKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            DropKind::OrderNew => "order_new",
+            DropKind::OrderAmend => "order_amend",
+            DropKind::OrderCancel => "order_cancel",
+            DropKind::CancelAll => "cancel_all",
+            DropKind::ClosePosition => "close_position",
+            DropKind::TradingStop => "trading_stop",
+            DropKind::Execution => "execution",
+        }
+    }
+}
+
+/// How an outgoing request left the Hot Thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Written to the Trade WS.
+    Ws,
+    /// Parked in the request pipeline, sent when capacity frees up.
+    WsQueued,
+    /// REST fallback thread.
+    Rest,
+    /// Never sent (pipeline queue or REST ring full).
+    Dropped,
+    /// Inbound (executions).
+    Exchange,
+}
+
+impl Route {
+    pub fn name(self) -> &'static str {
+        match self {
+            Route::Ws => "ws",
+            Route::WsQueued => "ws_queued",
+            Route::Rest => "rest",
+            Route::Dropped => "dropped",
+            Route::Exchange => "exchange",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DropCopyEvent {
+    /// Hot Thread sequence number: a gap downstream means events were lost in the ring.
+    pub seq: u64,
+    pub kind: DropKind,
+    pub route: Route,
+    /// Local wall clock, unix us.
+    pub ts_us: u64,
+    /// Exchange time (execTime), unix ms; 0 for outgoing requests.
+    pub exch_ts_ms: u64,
+    pub side: Option<&'static str>,
+    pub price: f64,
+    pub qty: f64,
+    pub link_id: FixedId,
+    pub exec_id: FixedId,
+    pub fee: f64,
+    pub is_maker: Option<bool>,
+}
+
+impl DropCopyEvent {
+    fn blank(kind: DropKind, route: Route) -> Self {
+        Self {
+            seq: 0,
+            kind,
+            route,
+            ts_us: unix_us(),
+            exch_ts_ms: 0,
+            side: None,
+            price: 0.0,
+            qty: 0.0,
+            link_id: FixedId::EMPTY,
+            exec_id: FixedId::EMPTY,
+            fee: 0.0,
+            is_maker: None,
+        }
+    }
+
+    /// Outgoing request; None for `ActionType::None`. The route is filled in by `publish_order`
+    /// once the Hot Thread knows where the request went.
+    pub fn from_action(action: &ActionType) -> Option<Self> {
+        let event = match action {
+            ActionType::CreateOrder { price, qty, side, link_id } => Self {
+                side: Some(side), price: *price, qty: *qty, link_id: FixedId::new(link_id),
+                ..Self::blank(DropKind::OrderNew, Route::Ws)
+            },
+            ActionType::AmendOrder { price, qty, side, link_id } => Self {
+                side: Some(side), price: *price, qty: *qty, link_id: FixedId::new(link_id),
+                ..Self::blank(DropKind::OrderAmend, Route::Ws)
+            },
+            ActionType::AmendPrice { price, side, link_id } => Self {
+                side: Some(side), price: *price, link_id: FixedId::new(link_id),
+                ..Self::blank(DropKind::OrderAmend, Route::Ws)
+            },
+            ActionType::AmendQty { qty, side, link_id } => Self {
+                side: Some(side), qty: *qty, link_id: FixedId::new(link_id),
+                ..Self::blank(DropKind::OrderAmend, Route::Ws)
+            },
+            ActionType::CancelOrder { link_id } => Self {
+                link_id: FixedId::new(link_id),
+                ..Self::blank(DropKind::OrderCancel, Route::Ws)
+            },
+            ActionType::CancelAll => Self::blank(DropKind::CancelAll, Route::Ws),
+            ActionType::ClosePosition { qty, side } => Self {
+                side: Some(side), qty: *qty,
+                ..Self::blank(DropKind::ClosePosition, Route::Ws)
+            },
+            ActionType::SetTradingStop { price, side } => Self {
+                side: Some(side), price: *price,
+                ..Self::blank(DropKind::TradingStop, Route::Ws)
+            },
+            ActionType::None => return None,
+        };
+        Some(event)
+    }
+
+    /// Execution as reported by the exchange (fields of one `execution` topic item).
+    pub fn execution(side: &str, price: f64, qty: f64, link_id: &str, exec_id: &str, exch_ts_ms: u64) -> Self {
+        Self {
+            exch_ts_ms,
+            side: match side {
+                "Buy" => Some("Buy"),
+                "Sell" => Some("Sell"),
+                _ => None,
+            },
+            price,
+            qty,
+            link_id: FixedId::new(link_id),
+            exec_id: FixedId::new(exec_id),
+            ..Self::blank(DropKind::Execution, Route::Exchange)
+        }
+    }
+
+    pub fn with_fee(mut self, fee: f64, is_maker: bool) -> Self {
+        self.fee = fee;
+        self.is_maker = Some(is_maker);
+        self
+    }
+
+    /// One JSON line (schema v1). Ids are Bybit-generated ASCII, no escaping needed.
+    pub fn write_json<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        write!(out, r#"{{"v":{},"seq":{},"ts_us":{},"event":"{}","route":"{}""#,
+            SCHEMA_VERSION, self.seq, self.ts_us, self.kind.name(), self.route.name())?;
+        if let Some(side) = self.side {
+            write!(out, r#","side":"{}""#, side)?;
+        }
+        if self.price != 0.0 {
+            write!(out, r#","px":{}"#, self.price)?;
+        }
+        if self.qty != 0.0 {
+            write!(out, r#","qty":{}"#, self.qty)?;
+        }
+        if self.link_id.len > 0 {
+            write!(out, r#","link_id":"{}""#, self.link_id.as_str())?;
+        }
+        if self.kind == DropKind::Execution {
+            write!(out, r#","exec_id":"{}","fee":{},"maker":{},"exch_ts":{}"#,
+                self.exec_id.as_str(), self.fee, self.is_maker.unwrap_or(false), self.exch_ts_ms)?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn unix_us() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+}
+
+/// Hot Thread side: stamps sequence numbers and pushes into the drop-copy ring.
+pub struct DropCopyPublisher {
+    producer: Producer<DropCopyEvent>,
+    seq: u64,
+    // Copies of the last published events (ring), whether or not the drop-copy ring took them
+    recent: [Option<DropCopyEvent>; RECENT_EVENTS],
+    /// Events lost because the ring was full (the writer fell behind).
+    pub dropped: u64,
+}
+
+impl DropCopyPublisher {
+    pub fn new(producer: Producer<DropCopyEvent>) -> Self {
+        Self { producer, seq: 0, recent: [None; RECENT_EVENTS], dropped: 0 }
+    }
+
+    /// Last published events, oldest first.
+    pub fn recent(&self) -> impl Iterator<Item = &DropCopyEvent> {
+        let next = self.seq as usize % RECENT_EVENTS;
+        (0..RECENT_EVENTS).filter_map(move |i| self.recent[(next + i) % RECENT_EVENTS].as_ref())
+    }
+
+    pub fn publish(&mut self, mut event: DropCopyEvent) {
+        self.seq += 1;
+        event.seq = self.seq;
+        self.recent[(self.seq as usize - 1) % RECENT_EVENTS] = Some(event);
+        if self.producer.push(event).is_err() {
+            self.dropped += 1;
+        }
+    }
+
+    /// Publishes an outgoing request built by `DropCopyEvent::from_action`.
+    pub fn publish_order(&mut self, event: Option<DropCopyEvent>, route: Route) {
+        if let Some(mut event) = event {
+            event.route = route;
+            self.publish(event);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DropCopyConfig {
+    /// JSON lines, appended.
+    pub file: Option<String>,
+    /// Optional TCP sink (`host:port`), same lines. Reconnected in the background.
+    pub tcp_addr: Option<String>,
+    pub reconnect_interval: Duration,
+}
+
+impl Default for DropCopyConfig {
+    fn default() -> Self {
+        Self {
+            file: Some("dropcopy.jsonl".to_string()),
+            tcp_addr: None,
+            reconnect_interval: Duration::from_secs(DEFAULT_RECONNECT_SECS),
+        }
+    }
+}
+
+/// DROP-COPY thread side: drains the ring into the file and the socket.
+/// Blocking IO lives here only.
+pub struct DropCopyWriter {
+    config: DropCopyConfig,
+    consumer: Consumer<DropCopyEvent>,
+    file: Option<BufWriter<File>>,
+    socket: Option<TcpStream>,
+    last_connect: Option<Instant>,
+    line: Vec<u8>,
+}
+
+impl DropCopyWriter {
+    pub fn new(config: DropCopyConfig, consumer: Consumer<DropCopyEvent>) -> Self {
+        let file = config.file.as_deref().and_then(|path| {
+            match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(f) => Some(BufWriter::new(f)),
+                Err(e) => {
+                    eprintln!("DROPCOPY: Cannot open {}: {} (file sink disabled)", path, e);
+                    None
+                }
+            }
+        });
+        Self { config, consumer, file, socket: None, last_connect: None, line: Vec::with_capacity(512) }
+    }
+
+    /// Writes everything queued; returns the number of events written.
+    pub fn poll(&mut self, now: Instant) -> usize {
+        self.ensure_socket(now);
+        let mut written = 0;
+        while let Ok(event) = self.consumer.pop() {
+            self.line.clear();
+            if event.write_json(&mut self.line).is_err() {
+                continue;
+            }
+            if let Some(file) = self.file.as_mut() {
+                if let Err(e) = file.write_all(&self.line) {
+                    eprintln!("DROPCOPY: File write failed: {}", e);
+                }
+            }
+            if let Some(socket) = self.socket.as_mut() {
+                if let Err(e) = socket.write_all(&self.line) {
+                    eprintln!("DROPCOPY: Socket write failed: {} (reconnecting)", e);
+                    self.socket = None;
+                }
+            }
+            written += 1;
+        }
+        if written > 0 {
+            if let Some(file) = self.file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+        written
+    }
+
+    fn ensure_socket(&mut self, now: Instant) {
+        let Some(addr) = self.config.tcp_addr.as_deref() else { return };
+        if self.socket.is_some() {
+            return;
+        }
+        if self.last_connect.is_some_and(|t| now.saturating_duration_since(t) < self.config.reconnect_interval) {
+            return;
+        }
+        self.last_connect = Some(now);
+        match TcpStream::connect(addr) {
+            Ok(socket) => {
+                let _ = socket.set_nodelay(true);
+                println!("DROPCOPY: Connected to {}", addr);
+                self.socket = Some(socket);
+            }
+            Err(e) => eprintln!("DROPCOPY: Connect to {} failed: {}", addr, e),
+        }
+    }
+}
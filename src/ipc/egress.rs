@@ -0,0 +1,191 @@
+use std::time::{Duration, Instant};
+use crate::ipc::session::Conn;
+use crate::strategy::market_maker::ActionType;
+
+// Outbound message audit. Every message the Hot Thread commits to a WS connection is counted
+// by op (a Trade WS frame parked in the pipeline counts when parked: it leaves shortly after,
+// and a dropped one is alerted on anyway). Counts are kept per one-minute window together
+// with the busiest one-second bucket, and that peak is compared with Bybit's documented
+// per-second limit for the op: headroom = 1 - peak / limit. A tight window shows up long
+// before the first 10006, so the send gate's rate limit and the amend budget can be tuned
+// from these numbers. Bybit counts order limits per account, REST included; the REST thread's
+// requests are not in here.
+// Bybit's default order-entry limits (requests per second, non-VIP); 0 = no documented limit.
+const DEFAULT_ORDER_LIMIT: u32 = 10;
+const WINDOW: Duration = Duration::from_secs(60);
+const BUCKET: Duration = Duration::from_secs(1);
+// Headroom below this is flagged in the report
+const DEFAULT_WARN_HEADROOM: f64 = 0.2;
+
+/// Kind of an outbound WS message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EgressOp {
+    Subscribe,
+    Auth,
+    Ping,
+    Create,
+    Amend,
+    Cancel,
+    CancelAll,
+    TradingStop,
+}
+
+pub const OP_COUNT: usize = 8;
+const OPS: [EgressOp; OP_COUNT] = [
+    EgressOp::Subscribe, EgressOp::Auth, EgressOp::Ping, EgressOp::Create,
+    EgressOp::Amend, EgressOp::Cancel, EgressOp::CancelAll, EgressOp::TradingStop,
+];
+const CONNS: [Conn; 3] = [Conn::Public, Conn::Private, Conn::Trade];
+
+impl EgressOp {
+    pub fn from_name(name: &str) -> Option<Self> {
+        OPS.iter().copied().find(|op| op.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EgressOp::Subscribe => "subscribe",
+            EgressOp::Auth => "auth",
+            EgressOp::Ping => "ping",
+            EgressOp::Create => "create",
+            EgressOp::Amend => "amend",
+            EgressOp::Cancel => "cancel",
+            EgressOp::CancelAll => "cancel_all",
+            EgressOp::TradingStop => "trading_stop",
+        }
+    }
+
+    /// Trade WS op an action is sent as (a market close is an `order.create`).
+    pub fn of(action: &ActionType) -> Option<Self> {
+        match action {
+            ActionType::CreateOrder { .. } | ActionType::ClosePosition { .. } => Some(EgressOp::Create),
+            ActionType::AmendOrder { .. } | ActionType::AmendPrice { .. } | ActionType::AmendQty { .. } => Some(EgressOp::Amend),
+            ActionType::CancelOrder { .. } => Some(EgressOp::Cancel),
+            ActionType::CancelAll => Some(EgressOp::CancelAll),
+            ActionType::SetTradingStop { .. } => Some(EgressOp::TradingStop),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EgressConfig {
+    /// Documented requests per second per op (index: `EgressOp as usize`); 0 = unlimited.
+    pub limits: [u32; OP_COUNT],
+    /// Headroom under this is flagged in the report.
+    pub warn_headroom: f64,
+}
+
+impl Default for EgressConfig {
+    fn default() -> Self {
+        let mut limits = [0; OP_COUNT];
+        for op in [EgressOp::Create, EgressOp::Amend, EgressOp::Cancel, EgressOp::CancelAll, EgressOp::TradingStop] {
+            limits[op as usize] = DEFAULT_ORDER_LIMIT;
+        }
+        Self { limits, warn_headroom: DEFAULT_WARN_HEADROOM }
+    }
+}
+
+/// One closed minute (Copy, travels inside `MetricsSnapshot`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EgressReport {
+    pub window: Duration,
+    /// Messages per connection and op (index: `Conn as usize`, `EgressOp as usize`).
+    pub counts: [[u32; OP_COUNT]; 3],
+    /// Busiest one-second bucket of the window.
+    pub peak: [[u32; OP_COUNT]; 3],
+    pub config: EgressConfig,
+}
+
+impl EgressReport {
+    /// 1 - peak / limit for one connection and op; None for ops without a limit.
+    pub fn headroom(&self, conn: Conn, op: EgressOp) -> Option<f64> {
+        let limit = self.config.limits[op as usize];
+        (limit > 0).then(|| 1.0 - self.peak[conn as usize][op as usize] as f64 / limit as f64)
+    }
+
+    /// Tightest limited op of the window.
+    pub fn min_headroom(&self) -> Option<(Conn, EgressOp, f64)> {
+        CONNS.iter()
+            .flat_map(|&conn| OPS.iter().map(move |&op| (conn, op)))
+            .filter(|&(conn, op)| self.counts[conn as usize][op as usize] > 0)
+            .filter_map(|(conn, op)| self.headroom(conn, op).map(|h| (conn, op, h)))
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+    }
+
+    pub fn print(&self) {
+        for conn in CONNS {
+            let mut line = format!("EGRESS: {} {:.0}s |", conn.name(), self.window.as_secs_f64());
+            let mut sent = false;
+            for op in OPS {
+                let (count, peak) = (self.counts[conn as usize][op as usize], self.peak[conn as usize][op as usize]);
+                if count == 0 {
+                    continue;
+                }
+                sent = true;
+                line.push_str(&format!(" {} {} (peak {}/s", op.name(), count, peak));
+                match self.headroom(conn, op) {
+                    Some(h) => line.push_str(&format!(" of {}/s, headroom {:.0}%) |", self.config.limits[op as usize], h * 100.0)),
+                    None => line.push_str(") |"),
+                }
+            }
+            if sent {
+                println!("{}", line.trim_end_matches(" |"));
+            }
+        }
+        if let Some((conn, op, h)) = self.min_headroom().filter(|m| m.2 < self.config.warn_headroom) {
+            eprintln!("WARNING: [EGRESS] {} {} peaked at {:.0}% of Bybit's {}/s limit (headroom {:.0}%)",
+                conn.name(), op.name(), (1.0 - h) * 100.0, self.config.limits[op as usize], h * 100.0);
+        }
+    }
+}
+
+/// Hot Thread counters, owned by `HotMetrics`. Fixed arrays only.
+#[derive(Debug)]
+pub struct EgressAudit {
+    pub config: EgressConfig,
+    window_start: Instant,
+    bucket_start: Instant,
+    counts: [[u32; OP_COUNT]; 3],
+    bucket: [[u32; OP_COUNT]; 3],
+    peak: [[u32; OP_COUNT]; 3],
+}
+
+impl EgressAudit {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            config: EgressConfig::default(),
+            window_start: now,
+            bucket_start: now,
+            counts: [[0; OP_COUNT]; 3],
+            bucket: [[0; OP_COUNT]; 3],
+            peak: [[0; OP_COUNT]; 3],
+        }
+    }
+
+    /// One message of `op` committed to `conn`.
+    #[inline(always)]
+    pub fn record(&mut self, conn: Conn, op: EgressOp, now: Instant) {
+        if now.saturating_duration_since(self.bucket_start) >= BUCKET {
+            self.bucket = [[0; OP_COUNT]; 3];
+            self.bucket_start = now;
+        }
+        let (c, o) = (conn as usize, op as usize);
+        self.counts[c][o] += 1;
+        self.bucket[c][o] += 1;
+        self.peak[c][o] = self.peak[c][o].max(self.bucket[c][o]);
+    }
+
+    /// Returns the finished minute and starts a new one.
+    pub fn flush_due(&mut self, now: Instant) -> Option<EgressReport> {
+        let window = now.saturating_duration_since(self.window_start);
+        if window < WINDOW {
+            return None;
+        }
+        let report = EgressReport { window, counts: self.counts, peak: self.peak, config: self.config };
+        self.counts = [[0; OP_COUNT]; 3];
+        self.peak = [[0; OP_COUNT]; 3];
+        self.window_start = now;
+        Some(report)
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use crate::ipc::drop_copy::FixedId;
+
+// Exchange error-code history. Every non-zero retCode seen by the Hot Thread (private and
+// Trade WS responses) and the REST thread becomes a Copy `RetCodeEvent` on a ring to the
+// Cold Thread, which counts them per UTC day and code, with the request that drew them
+// (source + op) as context and the first message as a sample. A finished day is printed
+// as one `ERRCODES:` line and appended to a CSV, which is read back at start so the week
+// survives restarts. Every `report_days` the Cold Thread prints the report: totals per day
+// and the top codes of the window with their contexts.
+const DEFAULT_FILE: &str = "analytics/error_codes.csv";
+const DEFAULT_REPORT_DAYS: u64 = 7;
+const TOP_CODES: usize = 10;
+const TOP_CONTEXTS: usize = 3;
+const DAY_MS: u64 = 86_400_000;
+const HEADER: &str = "day,code,source,op,count,msg";
+
+/// Connection / thread a code came back on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorSource {
+    Private,
+    Trade,
+    Rest,
+}
+
+impl ErrorSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorSource::Private => "private",
+            ErrorSource::Trade => "trade",
+            ErrorSource::Rest => "rest",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "private" => Some(ErrorSource::Private),
+            "trade" => Some(ErrorSource::Trade),
+            "rest" => Some(ErrorSource::Rest),
+            _ => None,
+        }
+    }
+}
+
+// Request kinds we send; anything else is counted as "other"
+const OPS: [&str; 12] = [
+    "order.create", "order.amend", "order.cancel", "auth", "subscribe", "ping",
+    "cancel_all", "close_position", "open_orders", "statement", "trading_stop", "other",
+];
+
+/// `op` of a WS response or a REST request kind, as a static name ("other" if unknown).
+pub fn op_name(op: &str) -> &'static str {
+    OPS.iter().copied().find(|&known| known == op).unwrap_or("other")
+}
+
+/// One non-zero retCode. Copy, travels through the ring.
+#[derive(Debug, Clone, Copy)]
+pub struct RetCodeEvent {
+    /// Local wall clock, unix ms.
+    pub ts_ms: u64,
+    pub code: i64,
+    pub source: ErrorSource,
+    pub op: &'static str,
+    /// retMsg, truncated.
+    pub msg: FixedId,
+}
+
+impl RetCodeEvent {
+    pub fn new(ts_ms: u64, code: i64, source: ErrorSource, op: &str, msg: &str) -> Self {
+        Self { ts_ms, code, source, op: op_name(op), msg: FixedId::new(msg) }
+    }
+}
+
+/// Code of a `RestClient` error ("Bybit error: <code> - <msg>"); None for HTTP/IO errors.
+pub fn rest_ret_code(err: &str) -> Option<(i64, &str)> {
+    let rest = err.strip_prefix("Bybit error: ")?;
+    let (code, msg) = rest.split_once(" - ").unwrap_or((rest, ""));
+    Some((code.trim().parse().ok()?, msg))
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorStatsConfig {
+    /// Day rows, appended and read back at start. None = in memory only.
+    pub file: Option<String>,
+    /// Report period and the window it covers, days.
+    pub report_days: u64,
+}
+
+impl Default for ErrorStatsConfig {
+    fn default() -> Self {
+        Self { file: Some(DEFAULT_FILE.to_string()), report_days: DEFAULT_REPORT_DAYS }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct CodeCount {
+    count: u64,
+    contexts: HashMap<(ErrorSource, &'static str), u64>,
+    msg: String,
+}
+
+/// Owned by the Cold Thread.
+pub struct ErrorStats {
+    pub config: ErrorStatsConfig,
+    // UTC day (unix ms / DAY_MS) -> code -> counts
+    days: BTreeMap<u64, HashMap<i64, CodeCount>>,
+    // Day still being counted (not written yet)
+    open_day: u64,
+    last_report_day: u64,
+}
+
+impl ErrorStats {
+    /// Starts at `now_ms` with the window's days from `config.file`.
+    pub fn new(config: ErrorStatsConfig, now_ms: u64) -> Self {
+        let today = now_ms / DAY_MS;
+        let mut stats = Self { config, days: BTreeMap::new(), open_day: today, last_report_day: today };
+        if let Some(path) = stats.config.file.clone() {
+            if let Ok(text) = fs::read_to_string(&path) {
+                stats.load(&text, today);
+            }
+        }
+        stats
+    }
+
+    fn load(&mut self, text: &str, today: u64) {
+        let first = today.saturating_sub(self.config.report_days);
+        for line in text.lines().skip(1) {
+            let mut cols = line.splitn(6, ',');
+            let (Some(day), Some(code), Some(source), Some(op), Some(count)) =
+                (cols.next(), cols.next(), cols.next(), cols.next(), cols.next()) else { continue };
+            let (Ok(day), Ok(code), Some(source), Ok(count)) =
+                (day.parse::<u64>(), code.parse::<i64>(), ErrorSource::from_name(source), count.parse::<u64>()) else { continue };
+            // Only closed days are written; a day still open when the process stopped is lost
+            if day < first || day >= today {
+                continue;
+            }
+            let entry = self.days.entry(day).or_default().entry(code).or_default();
+            entry.count += count;
+            *entry.contexts.entry((source, op_name(op))).or_default() += count;
+            if entry.msg.is_empty() {
+                entry.msg = cols.next().unwrap_or("").to_string();
+            }
+        }
+    }
+
+    pub fn record(&mut self, event: &RetCodeEvent) {
+        let entry = self.days.entry(event.ts_ms / DAY_MS).or_default().entry(event.code).or_default();
+        entry.count += 1;
+        *entry.contexts.entry((event.source, event.op)).or_default() += 1;
+        if entry.msg.is_empty() {
+            entry.msg = event.msg.as_str().to_string();
+        }
+    }
+
+    /// Once per Cold Thread loop: closes finished days (line + CSV) and, when due, returns
+    /// the report.
+    pub fn poll(&mut self, now_ms: u64) -> Option<String> {
+        let today = now_ms / DAY_MS;
+        if today <= self.open_day {
+            return None;
+        }
+        for day in self.open_day..today {
+            if let Some(codes) = self.days.get(&day) {
+                println!("ERRCODES: {} | {}", date(day), summary(codes));
+                if let Err(e) = self.append(day, codes) {
+                    eprintln!("COLD: Error-code history not written: {}", e);
+                }
+            }
+        }
+        self.open_day = today;
+        let first = today.saturating_sub(self.config.report_days);
+        self.days.retain(|&day, _| day >= first);
+        if today - self.last_report_day >= self.config.report_days {
+            self.last_report_day = today;
+            return Some(self.report(today));
+        }
+        None
+    }
+
+    fn append(&self, day: u64, codes: &HashMap<i64, CodeCount>) -> std::io::Result<()> {
+        let Some(path) = &self.config.file else { return Ok(()) };
+        if let Some(dir) = Path::new(path).parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let new_file = !Path::new(path).exists();
+        let mut out = OpenOptions::new().create(true).append(true).open(path)?;
+        if new_file {
+            writeln!(out, "{}", HEADER)?;
+        }
+        for (code, entry) in codes {
+            for ((source, op), count) in &entry.contexts {
+                writeln!(out, "{},{},{},{},{},{}", day, code, source.name(), op, count, entry.msg.replace(['\n', ','], " "))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The report over the window ending with the open day (admin `errors`).
+    pub fn report_so_far(&self, now_ms: u64) -> String {
+        self.report(now_ms / DAY_MS + 1)
+    }
+
+    /// Days of the window up to (not including) `today`: totals per day, then the top codes
+    /// with their busiest contexts.
+    pub fn report(&self, today: u64) -> String {
+        let first = today.saturating_sub(self.config.report_days);
+        let mut out = format!("ERRCODES REPORT: {} .. {}\n", date(first), date(today.saturating_sub(1)));
+        let mut totals: HashMap<i64, CodeCount> = HashMap::new();
+        for (&day, codes) in self.days.range(first..today) {
+            out.push_str(&format!("  {} | {}\n", date(day), summary(codes)));
+            for (&code, entry) in codes {
+                let total = totals.entry(code).or_default();
+                total.count += entry.count;
+                for (&context, &count) in &entry.contexts {
+                    *total.contexts.entry(context).or_default() += count;
+                }
+                if total.msg.is_empty() {
+                    total.msg = entry.msg.clone();
+                }
+            }
+        }
+        if totals.is_empty() {
+            out.push_str("  no errors\n");
+            return out;
+        }
+        for (code, entry) in top(&totals, TOP_CODES) {
+            let mut contexts: Vec<_> = entry.contexts.iter().collect();
+            contexts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let contexts: Vec<String> = contexts.iter().take(TOP_CONTEXTS)
+                .map(|((source, op), count)| format!("{}/{} x{}", source.name(), op, count)).collect();
+            out.push_str(&format!("  {:>7} x{:<6} {} | {:?}\n", code, entry.count, contexts.join(", "), entry.msg));
+        }
+        out
+    }
+}
+
+fn top(codes: &HashMap<i64, CodeCount>, n: usize) -> Vec<(i64, &CodeCount)> {
+    let mut sorted: Vec<(i64, &CodeCount)> = codes.iter().map(|(&code, entry)| (code, entry)).collect();
+    sorted.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(&b.0)));
+    sorted.truncate(n);
+    sorted
+}
+
+fn summary(codes: &HashMap<i64, CodeCount>) -> String {
+    let total: u64 = codes.values().map(|c| c.count).sum();
+    let top: Vec<String> = top(codes, 5).iter().map(|(code, entry)| format!("{} x{}", code, entry.count)).collect();
+    format!("{} errors | {}", total, top.join(", "))
+}
+
+/// UTC day number -> "YYYY-MM-DD" (civil-from-days, proleptic Gregorian).
+fn date(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
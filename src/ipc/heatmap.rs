@@ -0,0 +1,182 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rtrb::{Consumer, Producer};
+use crate::core::orderbook::{L2OrderBook, Level};
+
+// Liquidity heatmap for research (where do walls sit on this symbol, and for how long).
+// Once per `interval` the Hot Thread sums resting size within fixed bps bands of the mid
+// on each side and notes the largest single level; the sample is a small Copy struct
+// pushed into its own ring. The DROP-COPY thread appends one CSV row per sample.
+// Bands are cumulative (the 10 bps band includes the 5 bps one). The book is 20 levels
+// deep, so `*_depth_bps` says how far a band could see: beyond it the size is unknown.
+pub const BANDS_BPS: [f64; 4] = [5.0, 10.0, 25.0, 50.0];
+const DEFAULT_FILE: &str = "analytics/liquidity.csv";
+const DEFAULT_INTERVAL_MS: u64 = 1_000;
+
+/// One side of a sample.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SideLiquidity {
+    /// Resting size within BANDS_BPS[i] of the mid.
+    pub bands: [f64; BANDS_BPS.len()],
+    /// Largest single level and its distance from the mid, bps.
+    pub max_qty: f64,
+    pub max_bps: f64,
+    /// Distance of the deepest level we hold.
+    pub depth_bps: f64,
+}
+
+impl SideLiquidity {
+    fn from_levels(levels: &[Level], mid: f64) -> Self {
+        let mut side = SideLiquidity::default();
+        for level in levels.iter().filter(|l| l.price > 0.0 && l.qty > 0.0) {
+            let bps = (level.price - mid).abs() / mid * 10_000.0;
+            for (band, limit) in side.bands.iter_mut().zip(BANDS_BPS) {
+                if bps <= limit {
+                    *band += level.qty;
+                }
+            }
+            if level.qty > side.max_qty {
+                side.max_qty = level.qty;
+                side.max_bps = bps;
+            }
+            side.depth_bps = side.depth_bps.max(bps);
+        }
+        side
+    }
+}
+
+/// One heatmap row.
+#[derive(Debug, Clone, Copy)]
+pub struct LiquiditySample {
+    /// Local wall clock, unix ms.
+    pub ts_ms: u64,
+    pub mid: f64,
+    pub bid: SideLiquidity,
+    pub ask: SideLiquidity,
+}
+
+impl LiquiditySample {
+    pub fn header() -> String {
+        let mut header = String::from("ts_ms,mid");
+        for side in ["bid", "ask"] {
+            for bps in BANDS_BPS {
+                header.push_str(&format!(",{}_{}bps", side, bps));
+            }
+            header.push_str(&format!(",{0}_max_qty,{0}_max_bps,{0}_depth_bps", side));
+        }
+        header
+    }
+
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        write!(out, "{},{}", self.ts_ms, self.mid)?;
+        for side in [&self.bid, &self.ask] {
+            for qty in side.bands {
+                write!(out, ",{}", qty)?;
+            }
+            write!(out, ",{},{:.2},{:.2}", side.max_qty, side.max_bps, side.depth_bps)?;
+        }
+        writeln!(out)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HeatmapConfig {
+    /// CSV the rows are appended to; None disables recording.
+    pub file: Option<String>,
+    pub interval: Duration,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self { file: Some(DEFAULT_FILE.to_string()), interval: Duration::from_millis(DEFAULT_INTERVAL_MS) }
+    }
+}
+
+/// Hot Thread side: samples the book at most once per interval.
+pub struct HeatmapRecorder {
+    producer: Producer<LiquiditySample>,
+    interval: Duration,
+    next_ts: Option<Instant>,
+    /// Samples lost because the ring was full.
+    pub dropped: u64,
+}
+
+impl HeatmapRecorder {
+    pub fn new(producer: Producer<LiquiditySample>, interval: Duration) -> Self {
+        Self { producer, interval, next_ts: None, dropped: 0 }
+    }
+
+    /// Call on every applied book update; cheap unless a sample is due.
+    pub fn on_book(&mut self, book: &L2OrderBook, now: Instant) {
+        if self.next_ts.is_some_and(|next| now < next) {
+            return;
+        }
+        let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+        if !book.snapshot_applied || bid <= 0.0 || ask <= 0.0 {
+            return;
+        }
+        self.next_ts = Some(now + self.interval);
+        let mid = (bid + ask) / 2.0;
+        let sample = LiquiditySample {
+            ts_ms: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+            mid,
+            bid: SideLiquidity::from_levels(&book.bids, mid),
+            ask: SideLiquidity::from_levels(&book.asks, mid),
+        };
+        if self.producer.push(sample).is_err() {
+            self.dropped += 1;
+        }
+    }
+}
+
+/// DROP-COPY thread side: appends queued samples to the CSV (header on a new file).
+pub struct HeatmapWriter {
+    file: Option<BufWriter<std::fs::File>>,
+    consumer: Consumer<LiquiditySample>,
+}
+
+impl HeatmapWriter {
+    pub fn new(config: &HeatmapConfig, consumer: Consumer<LiquiditySample>) -> Self {
+        let file = config.file.as_deref().and_then(|path| {
+            if let Some(dir) = Path::new(path).parent().filter(|d| !d.as_os_str().is_empty()) {
+                let _ = fs::create_dir_all(dir);
+            }
+            let fresh = fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true);
+            match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(f) => {
+                    let mut out = BufWriter::new(f);
+                    if fresh {
+                        let _ = writeln!(out, "{}", LiquiditySample::header());
+                    }
+                    Some(out)
+                }
+                Err(e) => {
+                    eprintln!("HEATMAP: Cannot open {}: {} (recording disabled)", path, e);
+                    None
+                }
+            }
+        });
+        Self { file, consumer }
+    }
+
+    /// Writes everything queued; returns the number of rows written.
+    pub fn poll(&mut self) -> usize {
+        let mut written = 0;
+        while let Ok(sample) = self.consumer.pop() {
+            let Some(file) = self.file.as_mut() else { continue };
+            if let Err(e) = sample.write_csv(file) {
+                eprintln!("HEATMAP: Write failed: {}", e);
+                continue;
+            }
+            written += 1;
+        }
+        if written > 0 {
+            if let Some(file) = self.file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+        written
+    }
+}
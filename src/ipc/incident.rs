@@ -0,0 +1,228 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rtrb::{Consumer, Producer};
+use crate::core::clock::Clock;
+use crate::core::orderbook::{L2OrderBook, Level};
+use crate::ipc::drop_copy::{DropCopyEvent, DropCopyPublisher, FixedId, RECENT_EVENTS};
+use crate::strategy::market_maker::MarketMaker;
+use crate::strategy::order_manager::OrderManager;
+
+// Incident snapshots for post-mortem review. When a circuit breaker trips (quoting halted:
+// kill switch, exchange incident, maintenance) or a fill lands far through the book, the
+// Hot Thread copies the book (20 levels a side), both quote records and the last drop-copy
+// events into a fixed-size snapshot and pushes it into a dedicated ring. The DROP-COPY
+// thread renders it as a price ladder with our quotes marked, one file per incident.
+const BOOK_DEPTH: usize = 20;
+const DEFAULT_DIR: &str = "incidents";
+// A fill whose price is this far through the mid (against us) when it is reported
+const DEFAULT_ADVERSE_FILL_BPS: f64 = 20.0;
+
+/// What tripped the dump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IncidentTrigger {
+    /// Quoting halted (`kill-switch`, `exchange-incident`, `maintenance`).
+    Halt { reason: &'static str },
+    /// Our fill at `px` while the mid was already `adverse_bps` beyond it.
+    AdverseFill { side: &'static str, px: f64, qty: f64, mid: f64, adverse_bps: f64 },
+}
+
+impl IncidentTrigger {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IncidentTrigger::Halt { .. } => "halt",
+            IncidentTrigger::AdverseFill { .. } => "adverse-fill",
+        }
+    }
+}
+
+/// One side's quote as the OMS saw it.
+#[derive(Debug, Clone, Copy)]
+pub struct QuoteView {
+    pub live: bool,
+    pub price: f64,
+    pub qty: f64,
+    /// In-flight amend, if any.
+    pub pending_price: Option<f64>,
+    pub link_id: FixedId,
+}
+
+/// Fixed-size copy of everything needed to review one incident. Copy, travels through the ring.
+#[derive(Debug, Clone, Copy)]
+pub struct IncidentSnapshot {
+    /// Local wall clock, unix us.
+    pub ts_us: u64,
+    pub trigger: IncidentTrigger,
+    pub bids: [Level; BOOK_DEPTH],
+    pub asks: [Level; BOOK_DEPTH],
+    pub buy: QuoteView,
+    pub sell: QuoteView,
+    pub position: f64,
+    pub entry_price: f64,
+    /// Last orders/executions, oldest first.
+    pub recent: [Option<DropCopyEvent>; RECENT_EVENTS],
+}
+
+#[derive(Debug, Clone)]
+pub struct IncidentConfig {
+    /// One file per incident lands here; None disables the dumps.
+    pub dir: Option<String>,
+    pub adverse_fill_bps: f64,
+}
+
+impl Default for IncidentConfig {
+    fn default() -> Self {
+        Self { dir: Some(DEFAULT_DIR.to_string()), adverse_fill_bps: DEFAULT_ADVERSE_FILL_BPS }
+    }
+}
+
+fn unix_us() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+}
+
+/// Hot Thread side: builds snapshots and pushes them into the incident ring.
+pub struct IncidentRecorder {
+    producer: Producer<IncidentSnapshot>,
+    pub adverse_fill_bps: f64,
+    /// Snapshots lost because the ring was full.
+    pub dropped: u64,
+}
+
+impl IncidentRecorder {
+    pub fn new(producer: Producer<IncidentSnapshot>, adverse_fill_bps: f64) -> Self {
+        Self { producer, adverse_fill_bps, dropped: 0 }
+    }
+
+    /// How far the mid already is beyond our fill price, in bps (positive = against us).
+    /// Some(trigger) when it reaches `adverse_fill_bps`.
+    pub fn adverse_fill(&self, side: &'static str, px: f64, qty: f64, book: &L2OrderBook) -> Option<IncidentTrigger> {
+        let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+        if bid <= 0.0 || ask <= 0.0 || px <= 0.0 {
+            return None;
+        }
+        let mid = (bid + ask) / 2.0;
+        let adverse_bps = if side == "Buy" { (px - mid) / px } else { (mid - px) / px } * 10_000.0;
+        (adverse_bps >= self.adverse_fill_bps).then_some(IncidentTrigger::AdverseFill { side, px, qty, mid, adverse_bps })
+    }
+
+    pub fn capture<C: Clock>(&mut self, trigger: IncidentTrigger, book: &L2OrderBook, strategy: &MarketMaker<C>,
+                             oms: &OrderManager<C>, drop_copy: &DropCopyPublisher) {
+        let quote = |side: &str, link_id: &str| {
+            let rec = oms.record(side);
+            QuoteView {
+                live: rec.live,
+                price: rec.price,
+                qty: rec.qty,
+                pending_price: rec.pending.map(|p| p.price),
+                link_id: FixedId::new(link_id),
+            }
+        };
+        let mut recent = [None; RECENT_EVENTS];
+        for (slot, event) in recent.iter_mut().zip(drop_copy.recent()) {
+            *slot = Some(*event);
+        }
+        let snapshot = IncidentSnapshot {
+            ts_us: unix_us(),
+            trigger,
+            bids: book.bids,
+            asks: book.asks,
+            buy: quote("Buy", &strategy.active_buy_link_id),
+            sell: quote("Sell", &strategy.active_sell_link_id),
+            position: strategy.position,
+            entry_price: strategy.entry_price,
+            recent,
+        };
+        if self.producer.push(snapshot).is_err() {
+            self.dropped += 1;
+        }
+        eprintln!("ALERT: [INCIDENT] {} -> book snapshot queued (lost so far: {})", trigger.name(), self.dropped);
+    }
+}
+
+/// DROP-COPY thread side: renders queued snapshots into `<dir>/incident-<unix us>-<trigger>.txt`.
+pub struct IncidentWriter {
+    dir: Option<PathBuf>,
+    consumer: Consumer<IncidentSnapshot>,
+}
+
+impl IncidentWriter {
+    pub fn new(config: &IncidentConfig, consumer: Consumer<IncidentSnapshot>) -> Self {
+        let dir = config.dir.as_deref().and_then(|dir| match fs::create_dir_all(dir) {
+            Ok(()) => Some(PathBuf::from(dir)),
+            Err(e) => {
+                eprintln!("INCIDENT: Cannot create {}: {} (snapshots disabled)", dir, e);
+                None
+            }
+        });
+        Self { dir, consumer }
+    }
+
+    /// Writes everything queued; returns the number of files written.
+    pub fn poll(&mut self) -> usize {
+        let mut written = 0;
+        while let Ok(snapshot) = self.consumer.pop() {
+            let Some(dir) = self.dir.as_ref() else { continue };
+            let path = dir.join(format!("incident-{}-{}.txt", snapshot.ts_us, snapshot.trigger.name()));
+            let result = File::create(&path).and_then(|f| {
+                let mut out = BufWriter::new(f);
+                snapshot.render(&mut out)?;
+                out.flush()
+            });
+            match result {
+                Ok(()) => {
+                    eprintln!("INCIDENT: Snapshot written to {}", path.display());
+                    written += 1;
+                }
+                Err(e) => eprintln!("INCIDENT: Write to {} failed: {}", path.display(), e),
+            }
+        }
+        written
+    }
+}
+
+impl IncidentSnapshot {
+    /// Price ladder (asks high to low, then bids), our resting quotes marked on their level.
+    pub fn render<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "INCIDENT {} at unix_us {}", self.trigger.name(), self.ts_us)?;
+        match self.trigger {
+            IncidentTrigger::Halt { reason } => writeln!(out, "Quoting halted: {}", reason)?,
+            IncidentTrigger::AdverseFill { side, px, qty, mid, adverse_bps } => writeln!(out,
+                "{} {} @ {} while mid was {} ({:.1} bps against us)", side, qty, px, mid, adverse_bps)?,
+        }
+        writeln!(out, "Position: {} | Entry: {}", self.position, self.entry_price)?;
+
+        writeln!(out, "\n--- BOOK (top {} each side) ---", BOOK_DEPTH)?;
+        writeln!(out, "{:>14} {:>14}", "price", "qty")?;
+        for level in self.asks.iter().rev().filter(|l| l.price > 0.0) {
+            writeln!(out, "{:>14} {:>14}  ASK{}", level.price, level.qty, self.marker(level.price, &self.sell, "Sell"))?;
+        }
+        let (bid, ask) = (self.bids[0].price, self.asks[0].price);
+        if bid > 0.0 && ask > 0.0 {
+            writeln!(out, "{:>14} {:>14}", "-- spread --", format!("{:.2} bps", (ask - bid) / ((ask + bid) / 2.0) * 10_000.0))?;
+        }
+        for level in self.bids.iter().filter(|l| l.price > 0.0) {
+            writeln!(out, "{:>14} {:>14}  BID{}", level.price, level.qty, self.marker(level.price, &self.buy, "Buy"))?;
+        }
+
+        writeln!(out, "\n--- QUOTES ---")?;
+        for (side, quote) in [("Buy", &self.buy), ("Sell", &self.sell)] {
+            writeln!(out, "{:<4} live={} px={} qty={} pending={:?} link={}",
+                side, quote.live, quote.price, quote.qty, quote.pending_price, quote.link_id.as_str())?;
+        }
+
+        writeln!(out, "\n--- RECENT ORDERS / EXECUTIONS (oldest first, drop-copy schema) ---")?;
+        for event in self.recent.iter().flatten() {
+            event.write_json(out)?;
+        }
+        Ok(())
+    }
+
+    fn marker(&self, price: f64, quote: &QuoteView, side: &str) -> String {
+        if quote.live && (quote.price - price).abs() < 1e-9 {
+            format!("  << our {} {}", side, quote.qty)
+        } else {
+            String::new()
+        }
+    }
+}
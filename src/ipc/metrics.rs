@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+use crate::ipc::egress::{EgressAudit, EgressOp, EgressReport};
+use crate::ipc::book_latency::{BookLatencyTracker, SymbolLatency, MAX_SYMBOLS};
+use crate::ipc::session::Conn;
+use crate::net::tls_client::TlsStats;
+
+// Hot-thread counters. Plain integers owned by the hot thread: no atomics, no sharing,
+// so adding a metric costs one add on a line that is already in L1. The cold thread
+// only ever sees a copied snapshot, pushed over the log ring every FLUSH_INTERVAL.
+const DEFAULT_FLUSH_SECS: u64 = 10;
+
+/// One flush window of hot-path counters (Copy, travels through the SPSC ring by value).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsSnapshot {
+    pub window: Duration,
+    pub book_updates: u64,
+    pub strategy_evals: u64,
+    pub actions: u64,
+    pub orders_sent: u64,
+    pub vetoes: u64,
+    pub frame_errors: u64,
+    pub tick_count: u64,
+    pub tick_sum_us: u64,
+    pub tick_max_us: u64,
+    /// Parse + book update latency per symbol (p99 vs budget).
+    pub book_latency: [Option<SymbolLatency>; MAX_SYMBOLS],
+    /// TLS write backlog per connection (index: `Conn as usize`).
+    pub tls_backlog: [TlsBacklog; 3],
+    /// Outbound messages per connection and op, once a minute (`egress.rs`).
+    pub egress: Option<EgressReport>,
+}
+
+/// Time one connection spent with ciphertext the socket would not take (congestion).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TlsBacklog {
+    pub pending: Duration,
+    pub partial_writes: u64,
+    /// Estimated ciphertext still waiting at the end of the window.
+    pub pending_bytes: u64,
+}
+
+impl MetricsSnapshot {
+    pub fn print(&self) {
+        let secs = self.window.as_secs_f64().max(1e-9);
+        let avg_us = self.tick_sum_us.checked_div(self.tick_count).unwrap_or(0);
+        println!("METRICS: {:.0}s | book {} ({:.0}/s) | evals {} | actions {} | sent {} | vetoes {} | frame errs {} | tick avg {}us max {}us",
+            self.window.as_secs_f64(), self.book_updates, self.book_updates as f64 / secs, self.strategy_evals,
+            self.actions, self.orders_sent, self.vetoes, self.frame_errors, avg_us, self.tick_max_us);
+        for lat in self.book_latency.iter().flatten() {
+            lat.print();
+        }
+        // Quiet unless a socket pushed back
+        if self.tls_backlog.iter().any(|b| b.partial_writes > 0 || b.pending_bytes > 0) {
+            let mut line = String::from("TLSQ:");
+            for conn in [Conn::Public, Conn::Private, Conn::Trade] {
+                let b = self.tls_backlog[conn as usize];
+                line.push_str(&format!(" {} {:.1}ms/{} partial/{} B |", conn.name(), b.pending.as_secs_f64() * 1000.0, b.partial_writes, b.pending_bytes));
+            }
+            println!("{}", line.trim_end_matches(" |"));
+        }
+        if let Some(egress) = &self.egress {
+            egress.print();
+        }
+    }
+}
+
+/// Per-thread accumulator. Aligned to its own cache line so it never false-shares
+/// with whatever the allocator puts next to it on the hot thread's stack/heap.
+#[repr(align(64))]
+pub struct HotMetrics {
+    counters: MetricsSnapshot,
+    window_start: Instant,
+    pub flush_interval: Duration,
+    pub book_latency: BookLatencyTracker,
+    // Cumulative TlsStats at the last flush, per connection
+    tls_seen: [TlsBacklog; 3],
+    pub egress: EgressAudit,
+}
+
+impl HotMetrics {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            counters: MetricsSnapshot::default(),
+            window_start: now,
+            flush_interval: Duration::from_secs(DEFAULT_FLUSH_SECS),
+            book_latency: BookLatencyTracker::default(),
+            tls_seen: [TlsBacklog::default(); 3],
+            egress: EgressAudit::new(now),
+        }
+    }
+
+    #[inline(always)]
+    pub fn book_update(&mut self) {
+        self.counters.book_updates += 1;
+    }
+
+    /// Time spent parsing one book message and applying it to `symbol`'s book.
+    #[inline(always)]
+    pub fn book_parsed(&mut self, symbol: &'static str, elapsed: Duration, levels: u32) {
+        self.book_latency.record(symbol, elapsed, levels);
+    }
+
+    #[inline(always)]
+    pub fn strategy_eval(&mut self, actions: usize) {
+        self.counters.strategy_evals += 1;
+        self.counters.actions += actions as u64;
+    }
+
+    /// Frames written to the Trade WS (direct sends and pipeline releases).
+    #[inline(always)]
+    pub fn orders_sent(&mut self, n: usize) {
+        self.counters.orders_sent += n as u64;
+    }
+
+    /// One message committed to `conn` (sent, or parked in the Trade WS pipeline).
+    #[inline(always)]
+    pub fn egress(&mut self, conn: Conn, op: EgressOp, now: Instant) {
+        self.egress.record(conn, op, now);
+    }
+
+    #[inline(always)]
+    pub fn veto(&mut self) {
+        self.counters.vetoes += 1;
+    }
+
+    #[inline(always)]
+    pub fn frame_error(&mut self) {
+        self.counters.frame_errors += 1;
+    }
+
+    /// Read-to-done time of one readable event on the public socket.
+    #[inline(always)]
+    pub fn tick(&mut self, elapsed: Duration) {
+        let us = elapsed.as_micros() as u64;
+        self.counters.tick_count += 1;
+        self.counters.tick_sum_us += us;
+        self.counters.tick_max_us = self.counters.tick_max_us.max(us);
+    }
+
+    /// Returns the finished window and starts a new one once `flush_interval` has passed.
+    pub fn flush_due(&mut self, now: Instant) -> Option<MetricsSnapshot> {
+        let window = now.saturating_duration_since(self.window_start);
+        if window < self.flush_interval {
+            return None;
+        }
+        let snapshot = MetricsSnapshot { window, book_latency: self.book_latency.flush(), egress: self.egress.flush_due(now), ..self.counters };
+        self.counters = MetricsSnapshot::default();
+        self.window_start = now;
+        Some(snapshot)
+    }
+
+    /// Window share of each connection's cumulative write backlog (public, private, trade).
+    /// Call with a snapshot from `flush_due`, at the same `now`.
+    pub fn tls_backlog(&mut self, stats: [&TlsStats; 3], now: Instant) -> [TlsBacklog; 3] {
+        let mut window = [TlsBacklog::default(); 3];
+        for ((out, seen), stats) in window.iter_mut().zip(self.tls_seen.iter_mut()).zip(stats) {
+            let total = TlsBacklog { pending: stats.pending_time(now), partial_writes: stats.partial_writes, pending_bytes: stats.pending_bytes };
+            *out = TlsBacklog {
+                pending: total.pending.saturating_sub(seen.pending),
+                partial_writes: total.partial_writes.saturating_sub(seen.partial_writes),
+                pending_bytes: total.pending_bytes,
+            };
+            *seen = total;
+        }
+        window
+    }
+}
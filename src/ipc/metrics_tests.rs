@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::ipc::egress::EgressOp;
+    use crate::ipc::metrics::{HotMetrics, TlsBacklog};
+    use crate::ipc::session::Conn;
+    use crate::net::tls_client::TlsStats;
+
+    fn tls(pending_total_ms: u64, partial_writes: u64, pending_bytes: u64, now: Instant) -> TlsStats {
+        TlsStats {
+            created_ts: now,
+            handshake_done_ts: None,
+            tls_bytes_in: 0,
+            tls_bytes_out: 0,
+            plaintext_in: 0,
+            plaintext_out: 0,
+            last_flush_ts: None,
+            pending_bytes,
+            pending_since: None,
+            pending_total: Duration::from_millis(pending_total_ms),
+            partial_writes,
+            alerts_received: 0,
+            tls_errors: 0,
+            peer_closed: false,
+        }
+    }
+
+    #[test]
+    fn flush_hands_over_the_window_and_starts_a_new_one() {
+        let t0 = Instant::now();
+        let mut metrics = HotMetrics::new(t0);
+        let interval = metrics.flush_interval;
+        metrics.book_update();
+        metrics.book_update();
+        metrics.strategy_eval(3);
+        metrics.orders_sent(2);
+        metrics.veto();
+        metrics.frame_error();
+        metrics.tick(Duration::from_micros(10));
+        metrics.tick(Duration::from_micros(30));
+
+        assert!(metrics.flush_due(t0 + interval - Duration::from_millis(1)).is_none());
+        let late = t0 + interval + Duration::from_millis(5);
+        let snap = metrics.flush_due(late).expect("interval passed");
+        assert_eq!(snap.window, interval + Duration::from_millis(5));
+        assert_eq!((snap.book_updates, snap.strategy_evals, snap.actions, snap.orders_sent), (2, 1, 3, 2));
+        assert_eq!((snap.vetoes, snap.frame_errors), (1, 1));
+        assert_eq!((snap.tick_count, snap.tick_sum_us, snap.tick_max_us), (2, 40, 30));
+
+        // Counters and the window start over at the flush
+        assert!(metrics.flush_due(late + interval - Duration::from_millis(1)).is_none());
+        let next = metrics.flush_due(late + interval).unwrap();
+        assert_eq!((next.book_updates, next.tick_count, next.tick_max_us), (0, 0, 0));
+        assert_eq!(next.window, interval);
+    }
+
+    #[test]
+    fn flush_carries_book_latency_per_symbol() {
+        let t0 = Instant::now();
+        let mut metrics = HotMetrics::new(t0);
+        let interval = metrics.flush_interval;
+        metrics.book_parsed("RIVER-PERP", Duration::from_micros(4), 8);
+        metrics.book_parsed("RIVER-PERP", Duration::from_micros(6), 2);
+
+        let snap = metrics.flush_due(t0 + interval).unwrap();
+        let lat: Vec<_> = snap.book_latency.iter().flatten().collect();
+        assert_eq!(lat.len(), 1);
+        assert_eq!((lat[0].symbol, lat[0].samples, lat[0].avg_levels), ("RIVER-PERP", 2, 5.0));
+        // A quiet window carries no symbol
+        let next = metrics.flush_due(t0 + interval * 2).unwrap();
+        assert!(next.book_latency.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn tls_backlog_reports_the_window_share_of_cumulative_stats() {
+        let t0 = Instant::now();
+        let mut metrics = HotMetrics::new(t0);
+        let quiet = tls(0, 0, 0, t0);
+
+        let first = metrics.tls_backlog([&quiet, &quiet, &tls(40, 3, 512, t0)], t0);
+        assert_eq!(first[Conn::Public as usize], TlsBacklog::default());
+        assert_eq!(first[Conn::Trade as usize], TlsBacklog { pending: Duration::from_millis(40), partial_writes: 3, pending_bytes: 512 });
+
+        // Counts are deltas since the last flush; pending bytes are a level
+        let mut trade = tls(50, 4, 0, t0);
+        let second = metrics.tls_backlog([&quiet, &quiet, &trade], t0);
+        assert_eq!(second[Conn::Trade as usize], TlsBacklog { pending: Duration::from_millis(10), partial_writes: 1, pending_bytes: 0 });
+
+        // A backlog still open at the flush counts up to `now`
+        trade.pending_since = Some(t0);
+        let now = t0 + Duration::from_millis(7);
+        let third = metrics.tls_backlog([&quiet, &quiet, &trade], now);
+        assert_eq!(third[Conn::Trade as usize].pending, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn egress_report_rides_the_flush_once_a_minute() {
+        let t0 = Instant::now();
+        let mut metrics = HotMetrics::new(t0);
+        let interval = metrics.flush_interval;
+        metrics.egress(Conn::Trade, EgressOp::Create, t0);
+
+        assert!(metrics.flush_due(t0 + interval).unwrap().egress.is_none());
+        assert!(metrics.flush_due(t0 + Duration::from_secs(60)).unwrap().egress.is_some());
+    }
+}
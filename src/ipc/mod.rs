@@ -1,3 +1,17 @@
+//! Payloads that cross thread boundaries through the SPSC rings, and their writers.
+
 pub mod ring_buffer; 
+pub mod metrics;
+pub mod session;
+pub mod drop_copy;
+pub mod book_latency;
+pub mod incident;
+pub mod heatmap;
+pub mod watchdog;
+pub mod error_stats;
+pub mod egress;
 // Placeholder for custom ring buffer wrappers if needed, 
 // though we use rtrb directly in main for now.
+
+#[cfg(test)]
+mod metrics_tests;
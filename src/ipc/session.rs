@@ -0,0 +1,218 @@
+use std::time::{Duration, Instant};
+
+// Session / SLA accounting: how long each connection has been up, how often it dropped,
+// and how much of the session the bot was actually quoting versus sidelined (and why).
+// Owned by the Hot Thread (plain fields, no atomics); the Cold Thread gets Copy snapshots
+// through the log ring, periodically and on the admin `stats` command.
+const DEFAULT_REPORT_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conn {
+    Public,
+    Private,
+    Trade,
+}
+
+const CONNS: [Conn; 3] = [Conn::Public, Conn::Private, Conn::Trade];
+
+impl Conn {
+    pub fn name(self) -> &'static str {
+        match self {
+            Conn::Public => "public",
+            Conn::Private => "private",
+            Conn::Trade => "trade",
+        }
+    }
+}
+
+/// Why the strategy is not in the market. Checked in this order, first match wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    /// Trade WS not authenticated yet, or down.
+    TradeDown,
+    /// No snapshot / too shallow / resync after a corrupt book.
+    BookNotReady,
+    /// Exchange maintenance window.
+    Maintenance,
+    /// Unplanned exchange incident (error spike / all connections reset or stale).
+    ExchangeIncident,
+    /// Reduce-only mode (env or admin).
+    ReduceOnly,
+    /// Holding a position: the scalper manages the exit and posts no new quotes.
+    InPosition,
+}
+
+const REASONS: [PauseReason; 6] = [
+    PauseReason::TradeDown,
+    PauseReason::BookNotReady,
+    PauseReason::Maintenance,
+    PauseReason::ExchangeIncident,
+    PauseReason::ReduceOnly,
+    PauseReason::InPosition,
+];
+
+impl PauseReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            PauseReason::TradeDown => "trade-down",
+            PauseReason::BookNotReady => "book-not-ready",
+            PauseReason::Maintenance => "maintenance",
+            PauseReason::ExchangeIncident => "exchange-incident",
+            PauseReason::ReduceOnly => "reduce-only",
+            PauseReason::InPosition => "in-position",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteState {
+    Quoting,
+    Paused(PauseReason),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConnStats {
+    pub connected_since: Option<Instant>,
+    /// Closed up-periods; the running one is added in `snapshot`.
+    pub uptime: Duration,
+    pub connects: u32,
+    pub messages: u64,
+}
+
+impl ConnStats {
+    /// Connects after the first one.
+    pub fn reconnects(&self) -> u32 {
+        self.connects.saturating_sub(1)
+    }
+}
+
+/// Point-in-time copy of the session counters (travels through the log ring).
+#[derive(Debug, Clone, Copy)]
+pub struct SessionSnapshot {
+    pub elapsed: Duration,
+    pub conns: [ConnStats; 3],
+    pub state: QuoteState,
+    pub quoting: Duration,
+    pub paused: [Duration; 6],
+    pub pauses: [u32; 6],
+}
+
+impl SessionSnapshot {
+    pub fn print(&self) {
+        let total = self.elapsed.as_secs_f64().max(1e-9);
+        let pct = |d: Duration| d.as_secs_f64() / total * 100.0;
+        println!("SESSION: up {:.1}h | quoting {:.1}% | now {:?}", total / 3600.0, pct(self.quoting), self.state);
+        for (conn, stats) in CONNS.iter().zip(self.conns.iter()) {
+            println!("SESSION:   {:<7} up {:.1}% | reconnects {} | msgs {} | connected {}",
+                conn.name(), pct(stats.uptime), stats.reconnects(), stats.messages, stats.connected_since.is_some());
+        }
+        for (i, reason) in REASONS.iter().enumerate() {
+            if self.pauses[i] > 0 {
+                println!("SESSION:   paused {:<14} {:.1}% ({} times)", reason.name(), pct(self.paused[i]), self.pauses[i]);
+            }
+        }
+    }
+}
+
+pub struct SessionStats {
+    pub report_interval: Duration,
+    start: Instant,
+    conns: [ConnStats; 3],
+    state: QuoteState,
+    state_since: Instant,
+    quoting: Duration,
+    paused: [Duration; 6],
+    pauses: [u32; 6],
+    last_report: Instant,
+}
+
+impl SessionStats {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            report_interval: Duration::from_secs(DEFAULT_REPORT_SECS),
+            start: now,
+            conns: [ConnStats::default(); 3],
+            state: QuoteState::Paused(PauseReason::TradeDown),
+            state_since: now,
+            quoting: Duration::ZERO,
+            paused: [Duration::ZERO; 6],
+            pauses: [0; 6],
+            last_report: now,
+        }
+    }
+
+    /// Connection reached its working state (subscribed / authenticated).
+    pub fn on_connect(&mut self, conn: Conn, now: Instant) {
+        let stats = &mut self.conns[conn as usize];
+        if stats.connected_since.is_none() {
+            stats.connected_since = Some(now);
+            stats.connects += 1;
+        }
+    }
+
+    /// Connection lost (EOF / IO error). Repeated calls for the same outage are ignored.
+    pub fn on_disconnect(&mut self, conn: Conn, now: Instant) {
+        let stats = &mut self.conns[conn as usize];
+        if let Some(since) = stats.connected_since.take() {
+            stats.uptime += now.saturating_duration_since(since);
+        }
+    }
+
+    #[inline(always)]
+    pub fn on_message(&mut self, conn: Conn) {
+        self.conns[conn as usize].messages += 1;
+    }
+
+    /// Called every loop iteration with the current state; only transitions do any work.
+    pub fn set_state(&mut self, state: QuoteState, now: Instant) {
+        if state == self.state {
+            return;
+        }
+        self.close_state(now);
+        if let QuoteState::Paused(reason) = state {
+            self.pauses[reason as usize] += 1;
+        }
+        self.state = state;
+        self.state_since = now;
+    }
+
+    fn close_state(&mut self, now: Instant) {
+        let spent = now.saturating_duration_since(self.state_since);
+        match self.state {
+            QuoteState::Quoting => self.quoting += spent,
+            QuoteState::Paused(reason) => self.paused[reason as usize] += spent,
+        }
+        self.state_since = now;
+    }
+
+    pub fn snapshot(&self, now: Instant) -> SessionSnapshot {
+        let mut snap = SessionSnapshot {
+            elapsed: now.saturating_duration_since(self.start),
+            conns: self.conns,
+            state: self.state,
+            quoting: self.quoting,
+            paused: self.paused,
+            pauses: self.pauses,
+        };
+        let running = now.saturating_duration_since(self.state_since);
+        match self.state {
+            QuoteState::Quoting => snap.quoting += running,
+            QuoteState::Paused(reason) => snap.paused[reason as usize] += running,
+        }
+        for conn in snap.conns.iter_mut() {
+            if let Some(since) = conn.connected_since {
+                conn.uptime += now.saturating_duration_since(since);
+            }
+        }
+        snap
+    }
+
+    /// Snapshot every `report_interval`, or right away when `requested` (admin `stats`).
+    pub fn report_due(&mut self, now: Instant, requested: bool) -> Option<SessionSnapshot> {
+        if !requested && now.saturating_duration_since(self.last_report) < self.report_interval {
+            return None;
+        }
+        self.last_report = now;
+        Some(self.snapshot(now))
+    }
+}
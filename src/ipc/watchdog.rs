@@ -0,0 +1,110 @@
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Hot Thread watchdog. The Hot Thread stores a wall-clock timestamp into a shared atomic
+// on every pass of its loop (one relaxed store, nothing else on the hot path); the Cold
+// Thread polls it. A beat older than `stall` means the loop is stuck (deadlock, endless
+// loop, blocking call) even though the process is alive: the Cold Thread alerts once and,
+// if `cancel_all` is set, pulls every resting order over REST, since nothing else will.
+// A deliberate pause (rate-limit back-off) is announced with `pause`, which moves the beat
+// into the future. Optionally the last beat is mirrored to a file for an external
+// supervisor; the file stops changing when the Hot Thread does.
+const DEFAULT_STALL_MS: u64 = 1_000;
+const DEFAULT_FILE_INTERVAL_MS: u64 = 1_000;
+
+pub fn now_us() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64
+}
+
+/// Hot Thread: one loop pass done.
+#[inline(always)]
+pub fn beat(heartbeat: &AtomicU64) {
+    heartbeat.store(now_us(), Ordering::Relaxed);
+}
+
+/// Hot Thread: about to block on purpose for `pause`; not a stall.
+pub fn pause(heartbeat: &AtomicU64, pause: Duration) {
+    heartbeat.store(now_us() + pause.as_micros() as u64, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    /// No beat for this long: the Hot Thread is stalled.
+    pub stall: Duration,
+    /// REST cancel-all on a stall.
+    pub cancel_all: bool,
+    /// Heartbeat file (last beat, unix µs); None = off.
+    pub file: Option<String>,
+    pub file_interval: Duration,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stall: Duration::from_millis(DEFAULT_STALL_MS),
+            cancel_all: true,
+            file: None,
+            file_interval: Duration::from_millis(DEFAULT_FILE_INTERVAL_MS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogEvent {
+    /// First poll past the stall threshold; `silent` since the last beat.
+    Stalled { silent: Duration },
+    /// Beats resumed after a stall that lasted at least `silent`.
+    Recovered { silent: Duration },
+}
+
+/// Cold Thread side: stall detection and the heartbeat file.
+pub struct HotWatchdog {
+    pub config: WatchdogConfig,
+    // Beat value the current stall was raised on
+    stalled_on: Option<u64>,
+    last_written: u64,
+    next_write: Option<Instant>,
+}
+
+impl HotWatchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self { config, stalled_on: None, last_written: 0, next_write: None }
+    }
+
+    /// `beat_us`: last value the Hot Thread stored (0 = not running yet, never a stall).
+    pub fn poll(&mut self, beat_us: u64, now_us: u64) -> Option<WatchdogEvent> {
+        if !self.config.enabled || beat_us == 0 {
+            return None;
+        }
+        let silent = Duration::from_micros(now_us.saturating_sub(beat_us));
+        match self.stalled_on {
+            Some(stalled_on) if beat_us != stalled_on => {
+                self.stalled_on = None;
+                Some(WatchdogEvent::Recovered { silent: Duration::from_micros(beat_us.saturating_sub(stalled_on)) })
+            }
+            None if silent >= self.config.stall => {
+                self.stalled_on = Some(beat_us);
+                Some(WatchdogEvent::Stalled { silent })
+            }
+            _ => None,
+        }
+    }
+
+    /// Rewrites the heartbeat file every `file_interval` while the beat moves.
+    pub fn write_file(&mut self, beat_us: u64, now: Instant) {
+        let Some(path) = self.config.file.as_deref() else { return };
+        if beat_us == self.last_written || self.next_write.is_some_and(|at| now < at) {
+            return;
+        }
+        self.next_write = Some(now + self.config.file_interval);
+        self.last_written = beat_us;
+        // Write then rename: a reader never sees a half-written value
+        let tmp = format!("{}.tmp", path);
+        if let Err(e) = fs::write(&tmp, format!("{}\n", beat_us)).and_then(|_| fs::rename(&tmp, path)) {
+            eprintln!("WATCHDOG: Cannot write heartbeat file {}: {}", path, e);
+        }
+    }
+}
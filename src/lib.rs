@@ -0,0 +1,29 @@
+//! Bybit V5 market-making engine as a library.
+//!
+//! The `hft_rust` binary (`main.rs`) is a thin shell over these modules: it pins the threads,
+//! wires the rings and runs the mio event loop. Anything else that needs the engine
+//! (backtester CLI, research tools) links the library and drives the same types directly.
+//!
+//! * [`core`]     - order book, parser/serializer, clocks, batching, logging. No IO.
+//! * [`net`]      - TLS/WebSocket/REST clients, request pipeline, outage and network monitors.
+//! * [`strategy`] - market maker, OMS, risk, send gate and the quoting controllers.
+//! * [`ipc`]      - ring payloads between threads: metrics, session stats, drop copy, incidents.
+//! * [`auth`]     - request signing: Bybit and Binance (HMAC), Coinbase (ES256).
+//! * [`config`]   - engine parameters from a TOML file (`EngineConfig`).
+//! * [`cli`]      - command line of the `hft_rust` binary.
+//!
+//! Time goes through [`core::clock::Clock`]: [`core::clock::LiveClock`] in production,
+//! [`core::clock::SimClock`] for replay and tests, so the strategy runs unchanged offline.
+
+pub mod core;
+pub mod net;
+pub mod strategy;
+pub mod ipc;
+pub mod auth;
+pub mod config;
+pub mod cli;
+
+#[cfg(test)]
+mod cli_tests;
+#[cfg(test)]
+mod config_tests;
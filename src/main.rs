@@ -2,38 +2,87 @@ use core_affinity;
 use rtrb::RingBuffer;
 use std::thread;
 use std::time::{Duration, Instant};
-use std::net::ToSocketAddrs;
-use std::sync::Arc;
-use std::io::Write; // Import Write for flush
-use rustls::{ClientConfig, RootCertStore};
 
 use mio::{Events, Poll, Token};
 
-// Modules
-mod core;
-mod net;
-mod strategy;
-mod ipc;
-mod auth;
-
-use net::ws_client::WsClient;
-use net::framing; 
-use core::orderbook::L2OrderBook;
-use strategy::market_maker::{MarketMaker, ActionType};
+// Engine library (src/lib.rs)
+use hft_rust::{auth, core, ipc, net, strategy};
+
+use net::ws_client::WsClientBuilder;
+use net::adapter::{AdapterEvent, ConnectionState, ExchangeAdapter};
+use net::bybit::{BybitAuth, BybitPrivate, BybitPublic, BybitTrade};
+use net::resolver::{EndpointCache, EndpointUpdate, DnsRefresher, ResolverConfig};
+use net::framing;
+use net::pipeline::{RequestPipeline, PipelineConfig, FRAME_SLOT_SIZE};
+use net::rest_client::{self, RestClient, RestRequest, RestReply, CancelTarget, LinkId};
+use net::outage::{OutageDetector, OutageEvent, SERVER_ERROR_CODES};
+use net::feed_watch::FeedWatchdog;
+use net::feed_arbiter::{Feed, FeedArbiter, Verdict};
+use net::tls_client;
+use net::okx::{self, OkxEvent, OkxPublic};
+use net::binance::{self, BinanceBookTicker, BinanceMarketEvent, BinanceClient, BinanceCredentials, BinanceUserData, BinanceUserEvent, HedgeBook, HedgeRequest, LISTEN_KEY_KEEPALIVE};
+use net::netstat::{NetStatsMonitor, NetStatsConfig};
+use net::maintenance::{MaintenanceMonitor, MaintenanceConfig};
+use net::startup::{ConnectStage, StartupTimeline};
+use core::orderbook::Side;
+use strategy::market_maker::{ActionType, CloseOrderType, MarketMaker};
+use core::time_sync::{wall_ms, TimeSync};
+use core::parser::MarketEvent;
+use core::logging::{self, info, debug};
 use strategy::risk::RiskEngine;
-use auth::signer::Signer;
+use strategy::order_manager::{self, OrderManager, TraceStage};
+use strategy::router::{Venue, HEDGE_UNWIND_TAG};
+use strategy::book_manager::{BookManager, SymbolSlot};
+use strategy::private_stream;
+use strategy::margin::MarginTracker;
+use strategy::symbols::SymbolRegistry;
+use hft_rust::cli::{self, CliArgs};
+use hft_rust::config::{self, EngineConfig, Environment, PinningConfig};
+use strategy::fees::{FeeTracker, FeeConfig, FEE_TIERS};
+use strategy::experiment::{Experiment, ExperimentConfig};
+use strategy::fill::{Fill, FillStats};
+use strategy::pnl_audit::PnlAudit;
+use strategy::send_gate::{Committed, SendGate};
+use strategy::rest_quoting::RestQuoting;
+use strategy::shadow::ShadowStrategy;
+use strategy::paper::PaperConfig;
+use strategy::strategy_log::StrategyEvent;
+use ipc::metrics::{HotMetrics, MetricsSnapshot};
+use ipc::egress::EgressOp;
+use ipc::session::{SessionStats, SessionSnapshot, Conn, QuoteState, PauseReason};
+use ipc::drop_copy::{DropCopyEvent, DropCopyPublisher, DropCopyWriter, Route};
+use ipc::incident::{IncidentRecorder, IncidentSnapshot, IncidentTrigger, IncidentWriter};
+use ipc::heatmap::{HeatmapRecorder, HeatmapWriter, LiquiditySample};
+use ipc::watchdog::{self, HotWatchdog, WatchdogEvent};
+use ipc::error_stats::{self, ErrorSource, ErrorStats, RetCodeEvent};
+use auth::secrets;
 use simd_json; 
 use simd_json::prelude::*;
 
-static MINIMAL_LOGS: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// Strategy stays off until the book has a snapshot and this many levels per side
+const MIN_BOOK_LEVELS: usize = 5;
+// Application-level keepalive for the idle Bybit streams (Bybit recommends 20s)
+const BYBIT_PING_INTERVAL: Duration = Duration::from_secs(20);
+const BYBIT_PING_MSG: &str = r#"{"op":"ping"}"#;
+
+// Safe mode: every order reduce-only, no position-increasing quotes.
+// Set from [oms] reduce_only (HFT_REDUCE_ONLY) at startup, toggled at runtime from the admin console.
+static REDUCE_ONLY: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// Exchange maintenance window ahead/active (published by the Cold Thread).
+static MAINTENANCE_HALT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// NIC drops / TCP retransmit alert on our side (Cold Thread): resets and silence in the
+// next minute are blamed on the local network, not the exchange. Cleared by the Hot Thread.
+static LOCAL_NET_ALERT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// Admin `kill on` / `kill off`: no new or moved quotes leave the send gate; the strategy
+// flattens and pulls its quotes as in a maintenance halt.
+static KILL_SWITCH: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// Admin `stats`: the Hot Thread pushes a session snapshot to the Cold Thread and clears it.
+static STATS_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// Admin `errors`: the Cold Thread prints the error-code report now.
+static ERRORS_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// Hot Thread loop heartbeat (unix µs, 0 until the loop runs), watched by the Cold Thread.
+static HOT_HEARTBEAT_US: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
-macro_rules! info {
-    ($($arg:tt)*) => {
-        if !crate::MINIMAL_LOGS.load(std::sync::atomic::Ordering::Relaxed) {
-             println!($($arg)*);
-        }
-    }
-}
 
 #[derive(Debug, Clone, Copy)]
 struct LogMessage {
@@ -44,78 +93,77 @@ struct LogMessage {
     binance_bid: f64,
     binance_ask: f64,
     latency: u64,
+    trace_id: u64,
+    strategy_event: Option<StrategyEvent>, // msg_type 30
+    metrics: Option<MetricsSnapshot>, // msg_type 40
+    session: Option<SessionSnapshot>, // msg_type 41
 }
 
-#[derive(PartialEq, Debug)]
-enum ConnectionState {
-    HandshakeSending,
-    HandshakeWaiting,
-    Subscribing,
-    Authenticating,
-    Active,
+// Pins the calling thread to `core` ([pinning] map, already checked against the host).
+fn pin_current(thread: &str, core: Option<usize>) {
+    match core {
+        Some(id) if core_affinity::set_for_current(core_affinity::CoreId { id }) => println!("{} Thread pinned to Core ID: {}", thread, id),
+        Some(id) => eprintln!("WARNING: Failed to pin {} thread to core {} (Windows scheduler restriction?)", thread, id),
+        None => println!("{} Thread not pinned", thread),
+    }
 }
 
-// HTTP REST function to cancel all orders on startup
-fn cancel_all_orders_http(api_key: &str, api_secret: &str) -> Result<(), String> {
-    info!("========================================");
-    info!(">>> Canceling ALL orders via HTTP REST...");
-    
-    let url = "https://api.bybit.com/v5/order/cancel-all";
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64;
-    
-    // Subtract 6 seconds to account for clock skew (local clock is ahead)
-    let timestamp = timestamp.saturating_sub(6000);
-    
-    let recv_window = 20000u64;
-    
-    // POST body as JSON
-    let body = r#"{"category":"linear","symbol":"RIVERUSDT"}"#;
-    
-    // Signature string for POST: timestamp + api_key + recv_window + body
-    let sign_str = format!("{}{}{}{}", timestamp, api_key, recv_window, body);
-    
-    // HMAC SHA256
-    use ring::hmac;
-    let key = hmac::Key::new(hmac::HMAC_SHA256, api_secret.as_bytes());
-    let signature = hmac::sign(&key, sign_str.as_bytes());
-    let sig_hex = hex::encode(signature.as_ref());
-    
-    // Make HTTP POST request with JSON body
-    let response = ureq::post(url)
-        .set("Content-Type", "application/json")
-        .set("X-BAPI-API-KEY", api_key)
-        .set("X-BAPI-TIMESTAMP", &timestamp.to_string())
-        .set("X-BAPI-SIGN", &sig_hex)
-        .set("X-BAPI-RECV-WINDOW", &recv_window.to_string())
-        .send_string(body);
-    
-    match response {
-        Ok(resp) => {
-            let body = resp.into_string().map_err(|e| format!("Failed to read response: {}", e))?;
-            info!(">>> CancelAll HTTP Response: {}", body);
-            
-            // Parse retCode
-            if let Ok(json) = simd_json::to_borrowed_value(body.as_bytes().to_vec().as_mut_slice()) {
-                let ret_code = json.get("retCode").and_then(|v| v.as_i64()).unwrap_or(-1);
-                if ret_code == 0 {
-                    info!(">>> CancelAll HTTP SUCCESS!");
-                    info!("========================================");
-                    Ok(())
-                } else {
-                    let ret_msg = json.get("retMsg").and_then(|v| v.as_str()).unwrap_or("unknown");
-                    eprintln!("!!! CancelAll HTTP FAILED: {} - {}", ret_code, ret_msg);
-                    Err(format!("Bybit error: {} - {}", ret_code, ret_msg))
-                }
-            } else {
-                Err("Failed to parse response JSON".to_string())
+// REST thread: retCode of a failed request into the error-code history (HTTP errors have none).
+fn push_rest_code(producer: &mut rtrb::Producer<RetCodeEvent>, op: &str, err: &str) {
+    if let Some((code, msg)) = error_stats::rest_ret_code(err) {
+        let _ = producer.push(RetCodeEvent::new(watchdog::now_us() / 1000, code, ErrorSource::Rest, op, msg));
+    }
+}
+
+// An order that left the send gate, until the exchange has it: its symbol slot, what the
+// gate committed, the REST form of a risk pull and its drop-copy record.
+#[derive(Debug, Clone, Copy)]
+struct Outgoing {
+    slot: usize,
+    committed: Committed,
+    retry: Option<RestRequest>,
+    dc_order: Option<DropCopyEvent>,
+}
+
+// An order lost between the send gate and the exchange (queue full, send error, expired in
+// the queue, REST ring full). A quote is rolled back in the OMS, the margin and the strategy.
+// A risk pull must not vanish: it goes to REST, and when that is not possible the kill switch
+// trips so the strategy flattens and pulls everything. Returns the drop-copy route.
+fn on_order_dropped(order: Outgoing, strategy: &mut MarketMaker, order_manager: &mut OrderManager, send_gate: &mut SendGate,
+    margin: &mut MarginTracker, rest_producer: &mut rtrb::Producer<(usize, RestRequest)>) -> Route {
+    match (order.committed, send_gate.on_dropped(order.committed, order_manager, margin)) {
+        (Committed::Create { side, .. }, _) => strategy.on_order_cancel(side),
+        (Committed::Amend { side, .. }, Some(live)) => strategy.on_amend_deferred(side, live, true),
+        (Committed::RiskPull, _) => match order.retry {
+            Some(req) if rest_producer.push((order.slot, req)).is_ok() => {
+                eprintln!("HOT: {:?} never reached the Trade WS, retried via REST", req);
+                return Route::Rest;
+            }
+            _ => {
+                KILL_SWITCH.store(true, std::sync::atomic::Ordering::Relaxed);
+                eprintln!("CRITICAL: risk pull LOST (symbol slot {}), KILL SWITCH ENGAGED ('kill off' to resume)", order.slot);
             }
         },
+        _ => {}
+    }
+    Route::Dropped
+}
+
+// HTTP REST bulk cancel: startup clean slate. Blocking, so it runs wherever a RestClient
+// lives off the Hot Thread (startup, REST thread, shutdown/watchdog paths).
+fn cancel_all_orders_http(rest: &RestClient, targets: &[CancelTarget]) -> Result<(), String> {
+    info!(orders, "========================================");
+    info!(orders, ">>> Canceling ALL orders via HTTP REST ({:?})...", targets);
+    
+    match rest.cancel_targets(targets) {
+        Ok(()) => {
+            info!(orders, ">>> CancelAll HTTP SUCCESS!");
+            info!(orders, "========================================");
+            Ok(())
+        },
         Err(e) => {
-            eprintln!("!!! HTTP Request FAILED: {}", e);
-            Err(format!("HTTP error: {}", e))
+            eprintln!("!!! CancelAll HTTP FAILED: {}", e);
+            Err(e)
         }
     }
 }
@@ -123,53 +171,398 @@ fn cancel_all_orders_http(api_key: &str, api_secret: &str) -> Result<(), String>
 
 
 fn main() {
+    // Command line wins over HFT_* env, which wins over the config file
+    let cli = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(cli) if cli.help => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // --seal-secrets: one-off, nothing else starts
+    if let Some(path) = cli.seal_secrets.as_deref() {
+        let sealed = secrets::prompt("API key: ")
+            .and_then(|key| Ok((key, secrets::prompt("API secret: ")?)))
+            .and_then(|(key, secret)| secrets::Credentials::new(&key, &secret))
+            .and_then(|credentials| {
+                // Other venues' keys by variable name (BINANCE_API_KEY=...), an empty line ends
+                let mut venue_keys = Vec::new();
+                loop {
+                    let line = secrets::prompt("Venue key (NAME=value, empty to finish): ")?;
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    // Never echo the line back: the value is a secret
+                    let (name, value) = line.split_once('=').ok_or("venue key line without '=' (NAME=value)")?;
+                    venue_keys.push((name.trim().to_string(), value.trim().to_string()));
+                }
+                secrets::seal_to_file(path, &credentials, &venue_keys, &secrets::passphrase()?)
+            });
+        match sealed {
+            Ok(()) => println!("Sealed credentials written to {} (run with HFT_SECRETS=file:{})", path, path),
+            Err(e) => {
+                eprintln!("FATAL: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Log levels per subsystem: HFT_LOG_MODE=minimal is shorthand for all=error, HFT_LOG refines it,
+    // --log-mode (minimal or a spec) goes last
     if std::env::var("HFT_LOG_MODE").unwrap_or_default() == "minimal" {
-         MINIMAL_LOGS.store(true, std::sync::atomic::Ordering::Relaxed);
+         let _ = logging::apply_spec("all=error");
+    }
+    if let Ok(spec) = std::env::var("HFT_LOG") {
+         if let Err(e) = logging::apply_spec(&spec) {
+             eprintln!("WARNING: {}", e);
+         }
+    }
+    if let Some(mode) = cli.log_mode.as_deref() {
+         if let Err(e) = logging::apply_spec(if mode == "minimal" { "all=error" } else { mode }) {
+             eprintln!("--log-mode: {}\n\n{}", e, cli::USAGE);
+             std::process::exit(2);
+         }
     }
     
     println!("Initializing HFT Engine"); 
-    info!("Mode: Generic. Logs will be verbose unless minimal mode is active.");
+    println!("Log levels: {}", logging::summary());
 
     let _ = rustls::crypto::ring::default_provider().install_default();
     
     // Load Env
     dotenv::dotenv().ok();
+
+    // Engine parameters: --config or HFT_CONFIG, else ./config.toml if present, else built-in
+    // defaults. HFT_* overrides go on top and are checked like the file (EngineConfig::apply_env).
+    let config_path = cli.config.clone().or_else(|| std::env::var("HFT_CONFIG").ok());
+    let mut engine_config = match EngineConfig::locate(config_path.as_deref()) {
+        Ok((config, path)) => {
+            println!("Config: {}", path.as_deref().unwrap_or("built-in defaults"));
+            config
+        }
+        Err(e) => {
+            eprintln!("FATAL: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = engine_config.apply_env(|name| std::env::var(name).ok()) {
+        eprintln!("FATAL: {}", e);
+        std::process::exit(1);
+    }
+    if engine_config.oms.reduce_only {
+        println!("Reduce-only: ON from the start ([oms] reduce_only / HFT_REDUCE_ONLY)");
+        REDUCE_ONLY.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+    // --symbol: ticker only, tick/lot sizes stay those of [instrument]
+    if let Some(symbol) = cli.symbol.as_deref() {
+        engine_config.instrument.name = symbol.to_string();
+        engine_config.instrument.bybit_symbol = symbol.to_string();
+        engine_config.instrument.binance_symbol = None;
+        engine_config.instrument.okx_symbol = None;
+    }
+    // Strategy preset: --preset, else HFT_PRESET, else the config's `preset`
+    if let Some(preset) = cli.preset.or(engine_config.preset) {
+        engine_config.apply_preset(preset);
+        let params = preset.params();
+        println!("Preset: {} (spread {}..{}, size x{}, TP {}, hold {}/{} ms)", preset.name(), params.min_spread,
+            params.max_spread, params.size_factor, params.take_profit_pct, params.join_after_ms, params.cross_after_ms);
+    }
+    // Bybit deployment (mainnet | testnet | demo, HFT_ENVIRONMENT): every WS and REST host follows it
+    println!("Environment: {} (ws {} / {}, rest {})", engine_config.endpoints.environment.name(),
+        engine_config.endpoints.public_host(), engine_config.endpoints.private_host(), engine_config.endpoints.rest_url());
+    // Certificate pins of the WS hosts ([tls.pins]); HFT_TLS_PINS=off ignores them (an
+    // exchange rotated to a key nobody pinned yet)
+    if std::env::var("HFT_TLS_PINS").is_ok() {
+        eprintln!("WARNING: HFT_TLS_PINS=off, certificate pins ignored");
+    }
+    let cert_pins = engine_config.tls.cert_pins().expect("pins validated by EngineConfig::apply_env");
+    if !cert_pins.is_empty() {
+        println!("TLS: certificate pins for {}", cert_pins.hosts().collect::<Vec<_>>().join(", "));
+    }
+
+    // --paper: public stream + paper exchange on the tokio runner, no keys needed
+    if cli.paper {
+        #[cfg(feature = "tokio-runtime")]
+        {
+            let mut paper = hft_rust::strategy::paper::PaperConfig::from_env();
+            if let Some(model) = cli.fill_model {
+                paper.model = model;
+            }
+            if let Err(e) = net::async_ws::run_paper(&engine_config, paper) {
+                eprintln!("FATAL: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "tokio-runtime"))]
+        {
+            eprintln!("FATAL: --paper needs a build with --features tokio-runtime");
+            std::process::exit(2);
+        }
+    }
     
     // Cancel all orders via HTTP BEFORE starting (Clean Start)
-    let api_key = std::env::var("BYBIT_API_KEY").expect("BYBIT_API_KEY not set");
-    let api_secret = std::env::var("BYBIT_SECRET_KEY").expect("BYBIT_SECRET_KEY not set");
+    // API keys from HFT_SECRETS: env (.env, default) | keyring[:SERVICE] | file:PATH | stdin
+    let secrets_spec = std::env::var("HFT_SECRETS").unwrap_or_default();
+    // The same provider holds the other venues' keys (Binance hedge / user data)
+    let secret_provider = match secrets::provider_from_spec(&secrets_spec) {
+        Ok(provider) => provider,
+        Err(e) => {
+            eprintln!("FATAL: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let credentials = match secret_provider.load() {
+        Ok(credentials) => {
+            println!("Credentials: {}", secret_provider.name());
+            credentials
+        }
+        Err(e) => {
+            eprintln!("FATAL: Credentials ({}): {}", secret_provider.name(), e);
+            std::process::exit(1);
+        }
+    };
+
+    // Venue tickers come from the symbol registry, never hardcoded; the traded instrument from the
+    // config (HFT_CATEGORY: linear|inverse|spot|option, request shape and qty unit)
+    let mut primary_instrument = match engine_config.instrument.to_instrument() {
+        Ok(instrument) => instrument,
+        Err(e) => {
+            eprintln!("FATAL: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // [[symbols]]: more contracts on the same connections. One public stream per category,
+    // so they must share the primary's; a partial [quote] override starts from the defaults.
+    let mut quoted = vec![(primary_instrument, engine_config.quote)];
+    for extra in &engine_config.symbols {
+        let instrument = match extra.instrument.to_instrument() {
+            Ok(instrument) => instrument,
+            Err(e) => {
+                eprintln!("FATAL: symbols: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if instrument.category != primary_instrument.category {
+            eprintln!("FATAL: symbols: {} is {}, the primary instrument {} (one public stream per category)",
+                instrument.name, instrument.category.as_str(), primary_instrument.category.as_str());
+            std::process::exit(1);
+        }
+        quoted.push((instrument, extra.quote.unwrap_or(engine_config.quote)));
+    }
+    // Tick, lot and minimum from the exchange (instruments-info); the config's grid is only a fallback
+    for (instrument, _) in quoted.iter_mut() {
+        let Some(symbol) = instrument.venue_symbol(Venue::Bybit) else { continue };
+        match rest_client::fetch_instrument_spec(engine_config.endpoints.rest_url(), instrument.category, symbol) {
+            Ok(spec) => spec.apply(instrument),
+            Err(e) => eprintln!("WARNING: No instruments-info for {} ({}), keeping the configured tick {} / lot {}",
+                symbol, e, instrument.tick_size, instrument.qty_step),
+        }
+    }
+    primary_instrument = quoted[0].0;
+    let mut symbols = SymbolRegistry::new();
+    for (instrument, _) in &quoted {
+        symbols.register(*instrument);
+    }
+    let bybit_symbol = symbols.venue_symbol(primary_instrument.name, Venue::Bybit)
+        .expect("Primary instrument has no Bybit symbol");
+    for (instrument, _) in &quoted {
+        println!("Instrument: {} ({} {}) tick {} lot {} min {}", instrument.name, instrument.venue_symbol(Venue::Bybit).unwrap_or("-"),
+            instrument.category.as_str(), instrument.tick_size, instrument.qty_step, instrument.min_qty);
+    }
     
-        // Bypass HTTP Cancel for now if it fails
-    // if let Err(e) = cancel_all_orders_http(&api_key, &api_secret) {
-    //     eprintln!("WARNING: Failed to cancel orders on startup: {}", e);
-    //     eprintln!("Continuing anyway...");
-    // }
+    // Request timestamps: recv_window (HFT_RECV_WINDOW_MS on top) and safety margins from [time].
+    // Refuse to start if the measured clock skew would push any of them outside recv_window.
+    let time_config = engine_config.time;
+    let local_ms = || wall_ms() as i64;
+    let sent_ms = local_ms();
+    let startup_rest = RestClient::new(&credentials.api_key, &credentials.api_secret, bybit_symbol).with_instrument(&primary_instrument).with_time_sync(time_config)
+        .with_base_url(engine_config.endpoints.rest_url());
+    match startup_rest.server_time_ms() {
+         Ok(server_ms) => {
+             let skew = server_ms as i64 - (sent_ms + local_ms()) / 2;
+             if let Err(e) = time_config.check_skew(skew) {
+                 eprintln!("FATAL: Clock check failed: {}", e);
+                 std::process::exit(1);
+             }
+             info!(net, "Clock skew vs Bybit: {} ms (recv_window {} ms)", skew, time_config.recv_window_ms);
+         }
+         Err(e) => eprintln!("WARNING: Could not measure clock skew ({}), continuing with recv_window {} ms", e, time_config.recv_window_ms),
+    }
+
+    // Cancel leftovers on every configured symbol ([startup] cancel_scope = "settle" or
+    // HFT_CANCEL_SCOPE=settle widens it to the whole settle coin). A failure doesn't stop the start.
+    let cancel_scope = engine_config.startup.cancel_scope().expect("cancel scope validated by EngineConfig::apply_env");
+    let cancel_targets = symbols.cancel_targets(cancel_scope);
+    if let Err(e) = cancel_all_orders_http(&startup_rest, &cancel_targets) {
+        eprintln!("WARNING: Failed to cancel orders on startup: {}", e);
+        eprintln!("Continuing anyway...");
+    }
+    // Available balance for the pre-trade margin check until the first wallet push
+    let margin_coin = primary_instrument.settle_coin();
+    let startup_balance = match startup_rest.available_balance(margin_coin) {
+        Ok(available) => {
+            println!("Available balance: {} {}", available, margin_coin);
+            Some(available)
+        }
+        Err(e) => {
+            eprintln!("WARNING: Could not read the wallet balance ({}), margin check waits for the wallet topic", e);
+            None
+        }
+    };
+
+    // Connection declarations. Private/Trade are idle between orders, so they get
+    // Bybit's recommended 20s application ping; Public never goes quiet.
+    // Public streams are split per category (linear / inverse / spot / option)
+    let endpoints_config = &engine_config.endpoints;
+    let bybit_public = WsClientBuilder::new(endpoints_config.public_host())
+        .path(&endpoints_config.public_path(primary_instrument.category));
+    let bybit_private = WsClientBuilder::new(endpoints_config.private_host())
+        .path(&endpoints_config.private_path) // V5 Private Endpoint: /v5/private
+        .auto_ping(BYBIT_PING_INTERVAL, Some(BYBIT_PING_MSG));
+    let bybit_trade = WsClientBuilder::new(endpoints_config.private_host())
+        .path(&endpoints_config.trade_path)
+        .auto_ping(BYBIT_PING_INTERVAL, Some(BYBIT_PING_MSG));
+    // Second public connection ([endpoints] backup_public_host): same topics over other IPs,
+    // the first copy of each message wins (net::feed_arbiter)
+    let bybit_backup = endpoints_config.backup_public_host.as_deref()
+        .map(|host| WsClientBuilder::new(host).path(&endpoints_config.public_path(primary_instrument.category)));
+    // --- BINANCE: bookTicker reference quote for every instrument with a binance_symbol ([venues] binance_book, HFT_BINANCE_BOOK) ---
+    let binance_testnet = engine_config.endpoints.environment != Environment::Mainnet;
+    let binance_book = binance::book_ticker_builder(binance_testnet);
+    let binance_symbols: Vec<&'static str> = quoted.iter().filter_map(|(instrument, _)| instrument.venue_symbol(Venue::Binance)).collect();
+    let binance_book_enabled = !binance_symbols.is_empty() && engine_config.venues.binance_book;
+    // --- BINANCE: user data stream, fills and account updates ([venues] binance_user, HFT_BINANCE_USER) ---
+    let binance_user_enabled = engine_config.venues.binance_user;
+    // --- OKX: reference books5 for every instrument with an okx_symbol ([venues] okx, HFT_OKX=off disables) ---
+    let okx_public = okx::public_builder();
+    let okx_ids: Vec<&'static str> = quoted.iter().filter_map(|(instrument, _)| instrument.venue_symbol(Venue::Okx)).collect();
+    let okx_enabled = !okx_ids.is_empty() && engine_config.venues.okx;
+
+    // DNS: resolve every endpoint here, before the Hot Thread exists. The Hot Thread
+    // connects/reconnects from its cached copy; the Cold Thread re-resolves and pushes changes.
+    let mut endpoints = EndpointCache::new();
+    let [public_endpoint, private_endpoint, trade_endpoint] =
+        [&bybit_public, &bybit_private, &bybit_trade].map(|builder| builder.register_endpoint(&mut endpoints));
+    let backup_endpoint = bybit_backup.as_ref().map(|builder| builder.register_endpoint(&mut endpoints));
+    if okx_enabled {
+        okx_public.register_endpoint(&mut endpoints);
+    }
+    // The user data stream dials the same host
+    if binance_book_enabled || binance_user_enabled {
+        binance_book.register_endpoint(&mut endpoints);
+    }
+    endpoints.resolve_all();
+    if let Some(id) = backup_endpoint {
+        let shared: Vec<_> = endpoints.addrs(id).iter().filter(|addr| endpoints.addrs(public_endpoint).contains(addr)).collect();
+        if !shared.is_empty() {
+            eprintln!("WARNING: backup_public_host shares addresses {:?} with the public host: both feeds may take one route", shared);
+        }
+    }
+    // Pools ranked by TCP connect time ([endpoints.race]); the Hot Thread's copy starts on the
+    // fastest gateway of each
+    let race_config = engine_config.endpoints.race.race_config();
+    if race_config.enabled {
+        endpoints.race(&race_config);
+    }
+    // Startup report: DNS .. first snapshot per connection, alert above [startup] budget_ms
+    let startup_config = engine_config.startup.startup();
+    let mut hot_endpoints = endpoints.clone();
+    let (mut dns_producer, mut dns_consumer) = RingBuffer::<EndpointUpdate>::new(16);
 
     // 1. Setup IPC
     let (mut producer, mut consumer) = RingBuffer::<LogMessage>::new(4096);
+    // Non-zero retCodes for the error-code history, one ring per sending thread
+    let (mut err_producer, mut err_consumer) = RingBuffer::<RetCodeEvent>::new(256);
+    let (mut rest_err_producer, mut rest_err_consumer) = RingBuffer::<RetCodeEvent>::new(64);
+    let error_stats_config = engine_config.records.error_stats();
 
-    let core_ids = core_affinity::get_core_ids().expect("Failed to retrieve core IDs");
-    
-    // On average laptop we might have many cores, but let's stick to 0 and 1.
-    // Ensure we don't crash if only 1 core.
-    let cold_core = if core_ids.len() > 1 { core_ids[1] } else { core_ids[0] };
+    // Thread -> core map: [pinning] (hot 0, cold 1 by default) with HFT_PINNING, checked
+    // against the cores this host has. --core-pinning off: leave placement to the OS
+    // scheduler (shared hosts, containers)
+    let pinning = if cli.core_pinning.unwrap_or(true) {
+        let pinning = &engine_config.pinning;
+        let available: Vec<usize> = core_affinity::get_core_ids().unwrap_or_default().iter().map(|c| c.id).collect();
+        let (plan, warnings) = pinning.resolve(&available, &config::isolated_cores());
+        for warning in warnings {
+            eprintln!("WARNING: Pinning: {}", warning);
+        }
+        plan
+    } else {
+        PinningConfig::unpinned()
+    };
+    let cold_core = pinning.cold;
+
+    // Hot Thread watchdog ([watchdog]): stall_ms (0 disables), cancel_all = false (alert only),
+    // file (heartbeat file for an external supervisor).
+    let watchdog_config = engine_config.watchdog.watchdog();
     
+    let status_url = engine_config.endpoints.environment.status_url();
     // COLD THREAD (Logger)
     let cold_handle = thread::spawn(move || {
-        // Safe Pinning
-        if core_affinity::set_for_current(cold_core) {
-            info!("COLD Thread pinned to Core ID: {:?}", cold_core);
-        } else {
-            eprintln!("WARNING: Failed to pin COLD thread (Windows scheduler restriction?)");
-        }
+        pin_current("COLD", cold_core);
 
-        info!("COLD Thread running.");
+        println!("COLD Thread running.");
+        let mut net_monitor = NetStatsMonitor::new(NetStatsConfig::default());
+        let mut maint_monitor = MaintenanceMonitor::new(MaintenanceConfig { status_url, ..MaintenanceConfig::default() });
+        let mut dns_refresher = DnsRefresher::new(endpoints, ResolverConfig::default(), Instant::now());
+        let mut watchdog = HotWatchdog::new(watchdog_config);
+        let mut error_stats = ErrorStats::new(error_stats_config, watchdog::now_us() / 1000);
         loop {
+             // Hot Thread stuck: nobody else will pull our quotes
+             let beat = HOT_HEARTBEAT_US.load(std::sync::atomic::Ordering::Relaxed);
+             match watchdog.poll(beat, watchdog::now_us()) {
+                 Some(WatchdogEvent::Stalled { silent }) => {
+                     eprintln!("ALERT: [WATCHDOG] Hot Thread silent for {:?} (deadlock / stuck loop?)", silent);
+                     if watchdog.config.cancel_all {
+                         KILL_SWITCH.store(true, std::sync::atomic::Ordering::Relaxed);
+                         match cancel_all_orders_http(&startup_rest, &cancel_targets) {
+                             Ok(()) => eprintln!("ALERT: [WATCHDOG] Orders cancelled, kill switch engaged ('kill off' to resume)"),
+                             Err(e) => eprintln!("ALERT: [WATCHDOG] Cancel-all FAILED: {}", e),
+                         }
+                     }
+                 }
+                 Some(WatchdogEvent::Recovered { silent }) => eprintln!("WATCHDOG: Hot Thread beating again after {:?}", silent),
+                 None => {}
+             }
+             watchdog.write_file(beat, Instant::now());
+             // NIC drops / TCP retransmits (procfs, Linux only)
+             if net_monitor.poll(Instant::now()) {
+                 LOCAL_NET_ALERT.store(true, std::sync::atomic::Ordering::Relaxed);
+             }
+             // Bybit scheduled maintenance (REST, once a minute)
+             let halt = maint_monitor.poll(Instant::now());
+             MAINTENANCE_HALT.store(halt, std::sync::atomic::Ordering::Relaxed);
+             // Endpoint re-resolution (blocking DNS), changes go to the Hot Thread's cache
+             dns_refresher.poll(Instant::now(), |update| {
+                 if dns_producer.push(update).is_err() {
+                     eprintln!("COLD: DNS ring full, update for endpoint {} dropped", update.id);
+                 }
+             });
+
+             // retCode history: count, close finished days, weekly report (or admin `errors`)
+             while let Ok(event) = err_consumer.pop().or_else(|_| rest_err_consumer.pop()) {
+                 error_stats.record(&event);
+             }
+             let now_ms = watchdog::now_us() / 1000;
+             if let Some(report) = error_stats.poll(now_ms) {
+                 print!("{}", report);
+             }
+             if ERRORS_REQUESTED.swap(false, std::sync::atomic::Ordering::Relaxed) {
+                 print!("{}", error_stats.report_so_far(now_ms));
+             }
+
              while let Ok(msg) = consumer.pop() {
                  if msg.msg_type == 1 || msg.msg_type == 20 { // Status Update OR Quote Adjustment
                      // DISABLED: Too verbose, hiding order logs
-                     // But enable for minimal mode if type 20
-                     if crate::MINIMAL_LOGS.load(std::sync::atomic::Ordering::Relaxed) {
+                     // Per-quote latency only when order entry is being debugged
+                     if logging::enabled(logging::Subsystem::Orders, logging::LogLevel::Debug) {
                          if msg.msg_type == 20 {
                              println!("Lat: {}us", msg.latency);
                          }
@@ -185,93 +578,494 @@ fn main() {
                          // );
                          // let _ = std::io::stdout().flush();
                      }
+                 } else if msg.msg_type == 30 { // Sampled strategy diagnostics
+                     if let Some(event) = msg.strategy_event {
+                         event.print();
+                     }
+                 } else if msg.msg_type == 40 { // Hot-thread metrics window
+                     if let Some(metrics) = msg.metrics {
+                         metrics.print();
+                     }
+                 } else if msg.msg_type == 41 { // Session / SLA stats
+                     if let Some(session) = msg.session {
+                         session.print();
+                     }
                  } else if msg.msg_type == 10 { // Buy Signal
-                     info!("\n[SIMULATION] !!! SIGNAL TRIGGERED: BUY (Skewed Quote) !!!");
+                     info!(strategy, "\n[SIMULATION] !!! SIGNAL TRIGGERED: BUY (Skewed Quote) !!!");
                  } else if msg.msg_type == 11 { // Sell Signal
-                     info!("\n[SIMULATION] !!! SIGNAL TRIGGERED: SELL (Skewed Quote) !!!");
+                     info!(strategy, "\n[SIMULATION] !!! SIGNAL TRIGGERED: SELL (Skewed Quote) !!!");
                  }
              }
              thread::sleep(Duration::from_millis(1));
         }
     });
 
+    // REST THREAD (Fallback order entry)
+    // Risk-reducing actions land here when the Trade WS is down/degraded.
+    // Unpinned by default ([pinning] rest): it spends its life blocked on HTTP.
+    // Also answers heartbeat reconcile queries (open orders) through the reply ring.
+    // Both rings carry the symbol slot (BookManager index) a request / reply belongs to.
+    let (mut rest_producer, mut rest_consumer) = RingBuffer::<(usize, RestRequest)>::new(64);
+    let (mut reply_producer, mut reply_consumer) = RingBuffer::<(usize, RestReply)>::new(16);
+    let rest_clients: Vec<RestClient> = quoted.iter().map(|(instrument, _)| {
+        RestClient::new(&credentials.api_key, &credentials.api_secret, instrument.venue_symbol(Venue::Bybit).unwrap_or(bybit_symbol))
+            .with_instrument(instrument).with_time_sync(time_config)
+            .with_base_url(engine_config.endpoints.rest_url())
+    }).collect();
+    let rest_core = pinning.rest;
+    let _rest_handle = thread::spawn(move || {
+        pin_current("REST", rest_core);
+        println!("REST Thread running.");
+        loop {
+            while let Ok((slot, req)) = rest_consumer.pop() {
+                let Some(rest_client) = rest_clients.get(slot) else {
+                    eprintln!("REST: {:?} for unknown symbol slot {} dropped", req, slot);
+                    continue;
+                };
+                if let RestRequest::QueryOpenOrders = req {
+                    let reply = match rest_client.open_orders() {
+                        Ok(orders) => RestReply::OpenOrders(orders),
+                        Err(e) => {
+                            eprintln!("REST: Open orders query FAILED: {}", e);
+                            push_rest_code(&mut rest_err_producer, "open_orders", &e);
+                            if rest_client::is_server_error(&e) {
+                                let _ = reply_producer.push((slot, RestReply::ServerError));
+                            }
+                            RestReply::QueryFailed
+                        }
+                    };
+                    let _ = reply_producer.push((slot, reply));
+                    continue;
+                }
+                if let RestRequest::PollOpenOrders { sent } = req {
+                    match rest_client.open_orders() {
+                        Ok(orders) => {
+                            let _ = reply_producer.push((slot, RestReply::PolledOrders { orders, sent }));
+                        }
+                        Err(e) => {
+                            eprintln!("REST: Open orders poll FAILED: {}", e);
+                            push_rest_code(&mut rest_err_producer, "open_orders", &e);
+                            if rest_client::is_server_error(&e) {
+                                let _ = reply_producer.push((slot, RestReply::ServerError));
+                            }
+                        }
+                    }
+                    continue;
+                }
+                if let Some((op, link_id)) = req.order_op() {
+                    // REST quoting: every outcome goes back, the Hot Thread settles it like a Trade WS response
+                    let code = match rest_client.execute(req) {
+                        Ok(()) => 0,
+                        Err(e) => {
+                            eprintln!("REST: {} {:?} FAILED: {}", op, link_id, e);
+                            push_rest_code(&mut rest_err_producer, op, &e);
+                            if rest_client::is_server_error(&e) {
+                                let _ = reply_producer.push((slot, RestReply::ServerError));
+                            }
+                            error_stats::rest_ret_code(&e).map_or(-1, |(code, _)| code)
+                        }
+                    };
+                    let _ = reply_producer.push((slot, RestReply::Order { op, link_id, code }));
+                    continue;
+                }
+                if let RestRequest::QueryStatement { start_ms, end_ms } = req {
+                    let reply = match rest_client.statement(start_ms, end_ms) {
+                        Ok(statement) => RestReply::Statement(statement),
+                        Err(e) => {
+                            eprintln!("REST: Transaction log query FAILED: {}", e);
+                            push_rest_code(&mut rest_err_producer, "statement", &e);
+                            if rest_client::is_server_error(&e) {
+                                let _ = reply_producer.push((slot, RestReply::ServerError));
+                            }
+                            RestReply::StatementFailed
+                        }
+                    };
+                    let _ = reply_producer.push((slot, reply));
+                    continue;
+                }
+                info!(orders, "REST: Executing fallback {:?} on {}", req, rest_client.symbol());
+                if let Err(e) = rest_client.execute(req) {
+                    eprintln!("REST: Fallback {:?} FAILED: {}", req, e);
+                    let op = match req {
+                        RestRequest::ClosePosition { .. } => "close_position",
+                        RestRequest::HedgeUnwind { .. } => "hedge_unwind",
+                        _ => "cancel_all",
+                    };
+                    push_rest_code(&mut rest_err_producer, op, &e);
+                    if rest_client::is_server_error(&e) {
+                        let _ = reply_producer.push((slot, RestReply::ServerError));
+                    }
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    // HEDGE THREAD (Binance USDⓈ-M order entry)
+    // Every Bybit execution is hedged by an opposite market order on the symbol's
+    // binance_symbol, scaled by [venues] hedge_ratio (e.g. 1.0; 0 = no hedging).
+    // Keys: BINANCE_API_KEY / BINANCE_SECRET_KEY from HFT_SECRETS. Fills below Binance's lot step net
+    // up in a HedgeBook until a whole step can go. Client-side rate limiting keeps
+    // hedge_headroom (default 0.2) of every Binance limit free.
+    // Shares the REST core ([pinning] rest): it spends its life blocked on HTTP.
+    let hedge_ratio = Some(engine_config.venues.hedge_ratio).filter(|ratio| *ratio > 0.0);
+    let hedge_headroom = engine_config.venues.hedge_headroom;
+    let hedge_client = hedge_ratio.and_then(|_| match BinanceCredentials::load(&*secret_provider) {
+        Ok(binance_credentials) => Some(BinanceClient::new(binance_credentials, hedge_headroom)
+            .with_base_url(engine_config.endpoints.environment.binance_fapi_url()).with_time_sync(time_config)),
+        Err(e) => {
+            eprintln!("WARNING: Binance hedging disabled: {}", e);
+            None
+        }
+    });
+    let hedge_ratio = hedge_client.as_ref().and(hedge_ratio);
+    let hedge_symbols: Vec<Option<&'static str>> = quoted.iter().map(|(instrument, _)| instrument.venue_symbol(Venue::Binance)).collect();
+    let (mut hedge_producer, mut hedge_consumer) = RingBuffer::<(usize, HedgeRequest)>::new(64);
+    if let (Some(mut binance), Some(ratio)) = (hedge_client, hedge_ratio) {
+        thread::spawn(move || {
+            pin_current("HEDGE", rest_core);
+            // Lot grid per symbol slot; a symbol without binance_symbol (or unlisted) is not hedged
+            let mut hedge_books: Vec<Option<HedgeBook>> = hedge_symbols.iter().map(|symbol| {
+                let symbol = (*symbol)?;
+                match binance.market_lot(symbol) {
+                    Ok((step, min_qty)) => Some(HedgeBook::new(step, min_qty)),
+                    Err(e) => {
+                        eprintln!("HEDGE: {} lot size unavailable, not hedged: {}", symbol, e);
+                        None
+                    }
+                }
+            }).collect();
+            println!("HEDGE Thread running (Binance, ratio {}).", ratio);
+            let mut hedge_seq: u64 = 0;
+            loop {
+                while let Ok((slot, req)) = hedge_consumer.pop() {
+                    let (Some(symbol), Some(book)) = (hedge_symbols.get(slot).copied().flatten(), hedge_books.get_mut(slot).and_then(Option::as_mut)) else {
+                        continue;
+                    };
+                    let Some((side, qty)) = book.add(&req) else { continue };
+                    hedge_seq += 1;
+                    let client_id = format!("hg-{}-{}", wall_ms(), hedge_seq);
+                    match binance.market_order(symbol, side, qty, book.step(), false, &client_id) {
+                        Ok(fill) => info!(orders, "HEDGE: {} {} {} filled {} @ {} (for {:?}, unhedged {:.6})",
+                            side, qty, symbol, fill.executed_qty, fill.avg_price, req.source, book.pending()),
+                        Err(e) => {
+                            eprintln!("HEDGE: {} {} {} FAILED: {} (owed again)", side, qty, symbol, e);
+                            book.restore(side, qty);
+                        }
+                    }
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+    }
+
+    // BINANCE-KEY THREAD (user data stream listenKey)
+    // HFT_BINANCE_USER=on: ORDER_TRADE_UPDATE / ACCOUNT_UPDATE of the Binance account on the
+    // Hot Thread, hedge fills included. The listenKey comes from REST, so it is kept here:
+    // created at startup, kept alive every 30 min, created again when the keepalive fails or
+    // the Hot Thread reports the stream down or expired (Binance hands back the same key while
+    // it is valid). Every key goes to the Hot Thread, which connects `/ws/<listenKey>` with it.
+    let (mut listen_key_producer, mut listen_key_consumer) = RingBuffer::<String>::new(4);
+    let (mut key_renew_producer, mut key_renew_consumer) = RingBuffer::<()>::new(4);
+    let user_client = binance_user_enabled.then(|| BinanceCredentials::load(&*secret_provider)).and_then(|credentials| match credentials {
+        Ok(credentials) => Some(BinanceClient::new(credentials, hedge_headroom)
+            .with_base_url(engine_config.endpoints.environment.binance_fapi_url()).with_time_sync(time_config)),
+        Err(e) => {
+            eprintln!("WARNING: Binance user data stream disabled: {}", e);
+            None
+        }
+    });
+    if let Some(mut binance) = user_client {
+        thread::spawn(move || {
+            pin_current("BINANCE-KEY", rest_core);
+            let mut kept_alive = Instant::now();
+            let mut renew = true;
+            loop {
+                while key_renew_consumer.pop().is_ok() {
+                    renew = true;
+                }
+                if !renew && kept_alive.elapsed() >= LISTEN_KEY_KEEPALIVE {
+                    kept_alive = Instant::now();
+                    if let Err(e) = binance.keepalive_listen_key() {
+                        eprintln!("BINANCE-KEY: keepalive failed ({}), creating a new key", e);
+                        renew = true;
+                    }
+                }
+                if renew {
+                    kept_alive = Instant::now();
+                    match binance.create_listen_key() {
+                        Ok(key) => {
+                            info!(net, "BINANCE-KEY: listenKey ready, user data stream (re)connecting");
+                            renew = false;
+                            if listen_key_producer.push(key).is_err() {
+                                eprintln!("BINANCE-KEY: key ring full, key LOST");
+                            }
+                        }
+                        Err(e) => eprintln!("BINANCE-KEY: listenKey creation failed, retrying: {}", e),
+                    }
+                    // Paces a connect that keeps failing
+                    thread::sleep(Duration::from_secs(5));
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+    }
+
+    // DROP-COPY THREAD (external risk feed)
+    // Every order leaving the Hot Thread and every execution, as JSON lines to a file
+    // ([records] drop_copy_file, empty disables) and optionally a TCP sink (drop_copy_addr).
+    // Unpinned by default ([pinning] drop_copy): file/socket IO only.
+    let (dc_producer, dc_consumer) = RingBuffer::<DropCopyEvent>::new(1024);
+    let dc_config = engine_config.records.drop_copy();
+    // Incident snapshots (halt trips, adverse fills) ride along: rare, file IO only.
+    // [records] incident_dir (empty disables), adverse_fill_bps.
+    let (incident_producer, incident_consumer) = RingBuffer::<IncidentSnapshot>::new(8);
+    let incident_config = engine_config.records.incidents();
+    let adverse_fill_bps = incident_config.adverse_fill_bps;
+    // Liquidity heatmap (size per bps band of mid, once a second) for wall research.
+    // [records] heatmap_file (empty disables), heatmap_interval_ms.
+    let (heatmap_producer, heatmap_consumer) = RingBuffer::<LiquiditySample>::new(64);
+    let heatmap_config = engine_config.records.heatmap();
+    let heatmap_interval = heatmap_config.interval;
+    let drop_copy_core = pinning.drop_copy;
+    let _drop_copy_handle = thread::spawn(move || {
+        pin_current("DROP-COPY", drop_copy_core);
+        info!(orders, "DROP-COPY Thread running ({:?}, {:?}, incidents {:?}, heatmap {:?}).", dc_config.file, dc_config.tcp_addr, incident_config.dir, heatmap_config.file);
+        let mut writer = DropCopyWriter::new(dc_config, dc_consumer);
+        let mut incident_writer = IncidentWriter::new(&incident_config, incident_consumer);
+        let mut heatmap_writer = HeatmapWriter::new(&heatmap_config, heatmap_consumer);
+        loop {
+            writer.poll(Instant::now());
+            incident_writer.poll();
+            heatmap_writer.poll();
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    // ADMIN THREAD (stdin console)
+    // "reduce-only on" / "reduce-only off", "kill on" / "kill off", "log [net=debug,...]", "stats". Exits quietly if stdin is closed (daemon).
+    let _admin_handle = thread::spawn(|| {
+        let stdin = std::io::stdin();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match stdin.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            match line.trim() {
+                "reduce-only on" => {
+                    REDUCE_ONLY.store(true, std::sync::atomic::Ordering::Relaxed);
+                    println!("ADMIN: Reduce-only mode ON");
+                }
+                "reduce-only off" => {
+                    REDUCE_ONLY.store(false, std::sync::atomic::Ordering::Relaxed);
+                    println!("ADMIN: Reduce-only mode OFF");
+                }
+                "kill on" => {
+                    KILL_SWITCH.store(true, std::sync::atomic::Ordering::Relaxed);
+                    eprintln!("ADMIN: KILL SWITCH ENGAGED");
+                }
+                "kill off" => {
+                    KILL_SWITCH.store(false, std::sync::atomic::Ordering::Relaxed);
+                    println!("ADMIN: Kill switch released");
+                }
+                "stats" => STATS_REQUESTED.store(true, std::sync::atomic::Ordering::Relaxed),
+                "errors" => ERRORS_REQUESTED.store(true, std::sync::atomic::Ordering::Relaxed),
+                "log" => println!("ADMIN: Log levels {}", logging::summary()),
+                cmd if cmd.starts_with("log ") => match logging::apply_spec(&cmd[4..]) {
+                    Ok(()) => println!("ADMIN: Log levels {}", logging::summary()),
+                    Err(e) => eprintln!("ADMIN: {}", e),
+                },
+                "" => {}
+                other => eprintln!("ADMIN: Unknown command '{}'", other),
+            }
+        }
+    });
+
     // HOT THREAD (Strategy)
-    let hot_core = core_ids[0]; 
+    let hot_core = pinning.hot;
+    let risk_config = engine_config.risk;
+    let regime_config = engine_config.regimes;
+    let oms_config = engine_config.oms.order_manager();
+    let preset = engine_config.preset;
+    let gate_config = engine_config.send_gate.send_gate();
+    let rest_quoting_config = engine_config.rest_quoting.rest_quoting();
+    let margin_config = engine_config.margin.margin();
+    let obligation_config = engine_config.obligation.obligation();
+    let strategy_config = engine_config.strategy.clone();
+    let experiment_config = engine_config.experiment.clone();
+    let shadow_config = engine_config.shadow.clone();
+    let pnl_audit_config = engine_config.pnl_audit.pnl_audit();
+    let metrics_config = engine_config.metrics.clone();
+    let feed_watch_config = engine_config.feed_watch.feed_watch();
     
     let hot_handle = thread::spawn(move || {
-        if core_affinity::set_for_current(hot_core) {
-            info!("HOT Thread pinned to Core ID: {:?}", hot_core);
-        } else {
-            eprintln!("WARNING: Failed to pin HOT thread (Windows scheduler restriction?)");
-        }
+        pin_current("HOT", hot_core);
         
-        info!("HOT Thread running.");
+        println!("HOT Thread running.");
 
         // --- INIT ---
-        let mut book = L2OrderBook::new();
-        // Strategy is now mutable
-        let mut strategy = MarketMaker::new(0.01); 
+        // Per-symbol book, strategy, OMS and send gate; slot 0 is the primary instrument.
+        // [send_gate]: order rate and the per-side create/reject loop breaker
+        // REST QUOTING without the Trade WS ([rest_quoting]): off (default) | fallback | always
+        let mut rest_quoting = RestQuoting::new(rest_quoting_config);
+        // PRE-TRADE MARGIN: account-wide balance, checked by every slot's send gate
+        let mut margin = MarginTracker::new(margin_config, margin_coin);
+        if let Some(available) = startup_balance {
+            margin.on_balance(available);
+        }
+        let mut books = BookManager::new();
+        for (instrument, params) in quoted {
+            let slot = match books.add(instrument, gate_config, oms_config) {
+                Ok(slot) => slot,
+                Err(e) => {
+                    eprintln!("CRITICAL ERROR: Symbol {}: {}", instrument.name, e);
+                    return;
+                }
+            };
+            books.slots[slot].strategy.params = params; // [quote] or the symbol's own
+            books.slots[slot].strategy.regime.config = regime_config; // [regimes]
+            books.slots[slot].obligation.config = obligation_config;
+            // ROUTER: Binance takes hedges only when the HEDGE thread trades the symbol there
+            books.slots[slot].router.set_tradable(Venue::Binance, hedge_ratio.is_some() && instrument.venue_symbol(Venue::Binance).is_some());
+        }
+        if obligation_config.enabled {
+            info!(strategy, "HOT: MM obligation tracking ON (spread <= {} bps, qty >= {}, uptime >= {:.0}%)",
+                obligation_config.max_spread_bps, obligation_config.min_qty, obligation_config.min_uptime * 100.0);
+        }
+        // [strategy], validated at load: every symbol's controllers
+        let log_sampling = strategy_config.log_sampling().expect("log sampling validated by EngineConfig::apply_env");
+        let close_mode = strategy_config.close_mode().expect("close mode validated by EngineConfig::apply_env");
+        let batch_policy = strategy_config.batch_policy().expect("batch policy validated by EngineConfig::apply_env");
+        for SymbolSlot { strategy, .. } in books.slots.iter_mut() {
+            strategy.log.sampling = log_sampling;
+            if strategy_config.jitter {
+                strategy.jitter.config.enabled = true;
+                info!(strategy, "HOT: Quote jitter ON (delay <= {:?}, +-{} tick)", strategy.jitter.config.max_delay, strategy.jitter.config.max_ticks);
+            }
+            strategy.jitter.config.size_band = strategy_config.size_band;
+            if strategy_config.size_band > 0.0 {
+                info!(strategy, "HOT: Quote size band +-{:.0}%", strategy_config.size_band * 100.0);
+            }
+            if let Some(preset) = preset {
+                strategy.aging = preset.aging(); // hold times; exit_join_ms / exit_cross_ms below still win
+            }
+            strategy.last_price.config.enabled = strategy_config.last_price_band > 0.0; // 0 = off
+            strategy.last_price.config.max_deviation = strategy_config.last_price_band; // max |touch / last trade - 1| the strategy acts on
+            if let Some(ms) = strategy_config.exit_join_ms {
+                strategy.aging.join_after = Duration::from_millis(ms); // oldest lot age -> exit joins the touch
+            }
+            if let Some(ms) = strategy_config.exit_cross_ms {
+                strategy.aging.cross_after = Duration::from_millis(ms); // lots older than this -> market close
+            }
+            strategy.close.order_type = close_mode;
+            strategy.close.limit_timeout = Duration::from_millis(strategy_config.close_limit_ms); // limit-first: rest at the touch this long, then market
+            if strategy.close.order_type == CloseOrderType::LimitFirst {
+                info!(strategy, "HOT: Limit-first closes ({:?} at the touch, then market)", strategy.close.limit_timeout);
+            }
+            if !strategy_config.fade {
+                strategy.fade.config.enabled = false;
+                info!(strategy, "HOT: Quote fade OFF (static TPS spread and size)");
+            }
+            strategy.fade.config.cooldown = Duration::from_millis(strategy_config.fade_cooldown_ms); // how long a fade holds after the last hot fill
+            if !strategy_config.warmup {
+                strategy.warmup.config.enabled = false;
+                info!(strategy, "HOT: Quote warmup OFF (full size from the first quote)");
+            }
+            strategy.warmup.config.duration = Duration::from_millis(strategy_config.warmup_ms); // healthy time to full size
+            strategy.warmup.config.start_fraction = strategy_config.warmup_fraction; // size right after start/reconnect/resync
+            strategy.warmup.restart();
+            strategy.requote.config.max_age = Duration::from_millis(strategy_config.requote_max_age_ms); // oldest a resting quote may get
+            strategy.requote.config.decay = strategy_config.requote_decay; // drift, as a fraction of the quoted spread, that refreshes a fresh quote
+            if let Some(policy) = batch_policy {
+                strategy.batch.policy = policy;
+                info!(strategy, "HOT: Batch detection policy {:?}", policy);
+            }
+        }
+        // A/B EXPERIMENT ([experiment]): variant A = current params, B = A with the overrides of variant_b
+        // Runs on the primary symbol only
+        let mut experiment = Experiment::new(ExperimentConfig { slice: experiment_config.slice(), ..ExperimentConfig::default() }, Instant::now());
+        if let Some(spec) = experiment_config.variant_b.as_deref() {
+            let strategy = &mut books.slots[0].strategy;
+            let mut variant_b = strategy.params;
+            variant_b.apply_spec(spec).expect("variant_b validated by EngineConfig::apply_env");
+            experiment.config.enabled = true;
+            experiment.config.variants = [strategy.params, variant_b];
+            info!(strategy, "HOT: A/B experiment ON ({:?} slices) A={:?} B={:?}", experiment.config.slice, strategy.params, variant_b);
+        }
+        // SHADOW ([shadow]): live params with the overrides of `quote`, evaluated on paper (primary symbol)
+        let mut shadow = shadow_config.quote.as_deref().map(|spec| {
+            let live = &books.slots[0];
+            let mut candidate = MarketMaker::new(0.01);
+            candidate.set_instrument(&live.instrument);
+            candidate.params = live.strategy.params;
+            candidate.params.apply_spec(spec).expect("shadow quote validated by EngineConfig::apply_env");
+            candidate.regime.config = live.strategy.regime.config;
+            candidate.aging = live.strategy.aging;
+            candidate.last_price.config = live.strategy.last_price.config;
+            let mut shadow = ShadowStrategy::new(candidate, oms_config, PaperConfig::from_env(), Instant::now());
+            shadow.report_every = shadow_config.report_every();
+            info!(strategy, "HOT: Shadow strategy ON (fill model {}, report every {:?}) live={:?} shadow={:?}",
+                shadow.paper.config.model.name(), shadow.report_every, live.strategy.params, shadow.strategy.params);
+            shadow
+        });
         let mut risk = RiskEngine::new();
+        risk.config = risk_config;
+        // Request shape per Bybit category (shared by every symbol): one-way positionIdx (futures
+        // only), market-close fields. Qty/price precision is per symbol; strategy quantities are
+        // base coin, converted by `order_qty`.
+        let category = books.slots[0].instrument.category;
+        let position_idx = if category.is_futures() { r#","positionIdx":0"# } else { "" };
+        let close_args = rest_client::close_args(category);
+        let mut trade_pipeline = RequestPipeline::new(PipelineConfig::default());
+        let mut fee_tracker = FeeTracker::new(FeeConfig::default());
+        let mut fill_stats = FillStats::default();
+        let mut binance_fills = FillStats::default(); // User data stream executions (hedges), reported apart
+        // Daily reconcile of execution-stream fees/PnL against the exchange transaction log ([pnl_audit])
+        let mut pnl_audit = PnlAudit::new(pnl_audit_config, wall_ms());
         let mut last_latency = 0; // Track last execution latency
         
-        let api_key_env = std::env::var("BYBIT_API_KEY").expect("BYBIT_API_KEY not found in .env");
-        let secret_key_env = std::env::var("BYBIT_SECRET_KEY").expect("BYBIT_SECRET_KEY not found in .env");
-        
-        let api_key = api_key_env.as_str(); // Keep as reference if needed or clone
-        let signer = Signer::new(&secret_key_env);
-
         // --- NETWORK SETUP ---
-        info!("HOT: Loading TLS...");
-        let mut root_store = RootCertStore::empty();
-        root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
-        
-        let config = Arc::new(ClientConfig::builder()
-            .with_root_certificates(root_store)
-            .with_no_client_auth());
+        info!(net, "HOT: Loading TLS...");
+        let config = match tls_client::client_config(cert_pins) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("CRITICAL ERROR: {}", e);
+                return;
+            }
+        };
 
-        let host = "stream.bybit.com";
-        let path = "/v5/public/linear";
-        
-        // FORCE IPv4 RESOLUTION
-        // We use ToSocketAddrs with a hint or filter for IPv4
-        let addrs: Vec<_> = format!("{}:443", host).to_socket_addrs().unwrap().collect();
-        let addr = addrs.iter().find(|a| a.is_ipv4()).unwrap_or(&addrs[0]).clone(); // Prefer IPv4
-        info!("HOT: Resolved Public IP: {}", addr);
-        
         let mut poll = Poll::new().unwrap();
         let mut events = Events::with_capacity(128); 
         
-        let mut ws_client = match WsClient::connect(addr, host, config.clone()) {
-            Ok(client) => client,
+        // One ExchangeAdapter per connection: handshake, auth/subscribe, framing and reconnect
+        // live in net::adapter; the blocks below only handle the messages.
+        let symbols: Vec<&'static str> = books.slots.iter().map(|slot| slot.symbol).collect();
+        let mut public = match bybit_public.connect_cached(&mut hot_endpoints, config.clone()) {
+            Ok(client) => BybitPublic::new(client, &symbols),
             Err(e) => {
                 eprintln!("CRITICAL ERROR: Failed to connect to Bybit: {}", e);
                 return;
             }
         };
+        info!(net, "HOT: Resolved Public IP: {}", public.connection().client.addr);
 
-        // --- BINANCE SETUP (DISABLED) ---
-        let bin_host = "fstream.binance.com";
-        let bin_path = "/ws/btcusdt@bookTicker"; 
-        /*
-        let bin_addr = format!("{}:443", bin_host).to_socket_addrs().unwrap().next().unwrap();
-        
-        let mut ws_binance = match WsClient::connect(bin_addr, bin_host, config.clone()) {
-            Ok(client) => client,
+        // --- BACKUP PUBLIC (same topics, other IPs; FeedArbiter keeps the first copy) ---
+        let mut backup = bybit_backup.as_ref().and_then(|builder| match builder.connect_cached(&mut hot_endpoints, config.clone()) {
+            Ok(client) => {
+                info!(net, "HOT: Resolved Backup Public IP: {}", client.addr);
+                Some(BybitPublic::backup(client, &symbols))
+            }
             Err(e) => {
-                eprintln!("CRITICAL ERROR: Failed to connect to Binance: {}", e);
-                return;
+                eprintln!("WARNING: Backup public feed disabled, connect failed: {}", e);
+                None
             }
-        };
-        */
+        });
         
         // --- PRIVATE BYBIT SETUP ---
-        let priv_host = "stream.bybit.com";
-        let priv_host = "stream.bybit.com";
-        let priv_path = "/v5/private"; // CORRECT V5 Private Endpoint
-        let priv_addr = format!("{}:443", priv_host).to_socket_addrs().unwrap().next().unwrap();
-        
-        let mut ws_private = match WsClient::connect(priv_addr, priv_host, config.clone()) {
-            Ok(client) => client,
+        let mut private = match bybit_private.connect_cached(&mut hot_endpoints, config.clone()) {
+            Ok(client) => BybitPrivate::new(client, BybitAuth::new(&credentials.api_key, &credentials.api_secret, time_config)),
             Err(e) => {
                 eprintln!("CRITICAL ERROR: Failed to connect to Bybit Private Stream: {}", e);
                 return;
@@ -279,688 +1073,1363 @@ fn main() {
         };
 
         // --- TRADE BYBIT SETUP (Orders) ---
-        let trade_host = "stream.bybit.com";
-        let trade_path = "/v5/trade"; 
-        let trade_addr = format!("{}:443", trade_host).to_socket_addrs().unwrap().next().unwrap();
-        
-        let mut ws_trade = match WsClient::connect(trade_addr, trade_host, config.clone()) {
-            Ok(client) => client,
+        let mut trade = match bybit_trade.connect_cached(&mut hot_endpoints, config.clone()) {
+            Ok(client) => BybitTrade::new(client, BybitAuth::new(&credentials.api_key, &credentials.api_secret, time_config)),
             Err(e) => {
                 eprintln!("CRITICAL ERROR: Failed to connect to Bybit Trade: {}", e);
                 return;
             }
         };
 
+        // --- OKX PUBLIC (reference only, no order entry) ---
+        let mut okx = if okx_enabled {
+            match okx_public.connect_cached(&mut hot_endpoints, config.clone()) {
+                Ok(client) => Some(OkxPublic::new(client, &okx_ids)),
+                Err(e) => {
+                    eprintln!("WARNING: OKX reference feed disabled, connect failed: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // --- BINANCE bookTicker (reference only; hedge orders go over REST) ---
+        let mut binance = if binance_book_enabled {
+            match binance_book.connect_cached(&mut hot_endpoints, config.clone()) {
+                Ok(client) => Some(BinanceBookTicker::new(client, &binance_symbols)),
+                Err(e) => {
+                    eprintln!("WARNING: Binance reference feed disabled, connect failed: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // --- BINANCE user data: connected once the BINANCE-KEY thread hands over a listenKey ---
+        let mut binance_user: Option<BinanceUserData> = None;
+        let mut binance_user_key = String::new();
+
         // Tokens
         const BYBIT_TOKEN: Token = Token(0);
         const BINANCE_TOKEN: Token = Token(1);
         const BYBIT_PRIVATE_TOKEN: Token = Token(2);
         const BYBIT_TRADE_TOKEN: Token = Token(3);
+        const OKX_TOKEN: Token = Token(4);
+        const BYBIT_BACKUP_TOKEN: Token = Token(5);
+        const BINANCE_USER_TOKEN: Token = Token(6);
+
+        let mut public_startup = StartupTimeline::new("public", ConnectStage::Snapshot, public.connection().client.tls.stats.created_ts,
+            hot_endpoints.resolve_time(public_endpoint), startup_config);
+        let mut private_startup = StartupTimeline::new("private", ConnectStage::Subscribe, private.connection().client.tls.stats.created_ts,
+            hot_endpoints.resolve_time(private_endpoint), startup_config);
+        let mut trade_startup = StartupTimeline::new("trade", ConnectStage::Auth, trade.connection().client.tls.stats.created_ts,
+            hot_endpoints.resolve_time(trade_endpoint), startup_config);
     
         // Register All
-        ws_client.register(poll.registry(), BYBIT_TOKEN).expect("Failed to register Bybit Public");
-        // ws_binance.register(poll.registry(), BINANCE_TOKEN).expect("Failed to register Binance");
-        ws_private.register(poll.registry(), BYBIT_PRIVATE_TOKEN).expect("Failed to register Bybit Private");
-        ws_trade.register(poll.registry(), BYBIT_TRADE_TOKEN).expect("Failed to register Bybit Trade");
-    
-        // Buffers for Binance
-        let mut bin_buf = [0u8; 65536];
-        let mut bin_offset = 0;
-        let mut bin_handshake_done = false;
-    
-        // Buffers for Bybit
-        let mut buf = [0u8; 65536];
-        let mut offset = 0; 
-    
-        // Reuse other buffers
-        let mut write_buf = [0u8; 1024]; 
-        let mut frame_buf = [0u8; 512]; // Increased for larger order JSON with headers 
-        let mut signature_hex = [0u8; 64];
+        public.connection_mut().register(poll.registry(), BYBIT_TOKEN).expect("Failed to register Bybit Public");
+        private.connection_mut().register(poll.registry(), BYBIT_PRIVATE_TOKEN).expect("Failed to register Bybit Private");
+        trade.connection_mut().register(poll.registry(), BYBIT_TRADE_TOKEN).expect("Failed to register Bybit Trade");
+        if let Some(backup) = backup.as_mut() {
+            backup.connection_mut().register(poll.registry(), BYBIT_BACKUP_TOKEN).expect("Failed to register Bybit Backup Public");
+        }
+        if let Some(okx) = okx.as_mut() {
+            okx.connection_mut().register(poll.registry(), OKX_TOKEN).expect("Failed to register OKX Public");
+        }
+        if let Some(binance) = binance.as_mut() {
+            binance.connection_mut().register(poll.registry(), BINANCE_TOKEN).expect("Failed to register Binance");
+        }
     
-        // Buffers for Private
-        let mut priv_buf = [0u8; 65536];
-        let mut priv_offset = 0;
-        
-        let mut trade_buf = [0u8; 65536];
-        let mut trade_offset = 0;
-
+        // Order frames: one pipeline slot, so anything that encodes can also be parked
+        let mut frame_buf = [0u8; FRAME_SLOT_SIZE];
         let mut tick_count: u64 = 0;
-        let mut state = ConnectionState::HandshakeSending;
-        let mut priv_state = ConnectionState::HandshakeSending;
-        let mut trade_state = ConnectionState::HandshakeSending;
-        
+        let mut metrics = HotMetrics::new(Instant::now()); // Plain counters, flushed to the Cold Thread
+        metrics.book_latency.budget_ns = metrics_config.book_budget_ns; // p99 parse+update alert threshold
+        metrics.egress.config = metrics_config.egress(); // per-op req/s the headroom is measured against
+        let mut session = SessionStats::new(Instant::now()); // Uptime / time-in-market accounting
+        let mut outage = OutageDetector::default(); // Exchange incident (not local network) -> halt
+        // Silent connection -> pull quotes + reconnect ([feed_watch])
+        let mut feed_watch = FeedWatchdog::default();
+        feed_watch.config = feed_watch_config;
+        for conn in [Conn::Public, Conn::Private, Conn::Trade] {
+            feed_watch.arm(conn, Instant::now());
+        }
+        // Dual public feed: dedup by sequence; one silent feed is reconnected alone, quotes stay
+        let mut arbiter = backup.as_ref().map(|_| FeedArbiter::new(feed_watch.config.silence[Conn::Public as usize]));
+        for arbiter in arbiter.iter_mut() {
+            arbiter.arm(Feed::Primary, Instant::now());
+            arbiter.arm(Feed::Backup, Instant::now());
+        }
+        let mut drop_copy = DropCopyPublisher::new(dc_producer);
+        let mut incidents = IncidentRecorder::new(incident_producer, adverse_fill_bps); // Book snapshots on halt trips / adverse fills
+        let mut heatmap = HeatmapRecorder::new(heatmap_producer, heatmap_interval); // Liquidity by band of mid, research only
+        let mut next_trace_id: u64 = 0; // One per ingested book message, follows its actions to ack/fill
+        let mut hedge_unwind_seq: u64 = 0; // Suffix of the router's Bybit hedge unwinds (orderLinkId)
         let mut priv_authenticated = false; 
         let mut trade_authenticated = false;
-        let mut request_priv_sub = false;        
-        let mut bin_active = false;
+        let mut trade_ws_down = false; // Set on Trade WS IO error/EOF/stale -> risk pulls go via REST until re-auth
+        let mut last_tick_latency = Duration::ZERO; // Previous public tick, gates the warmup ramp
+        let mut request_priv_sub = false; // Auth acked -> subscribe once the private events are handled
         
-        // Dynamic Time Sync
-        // Offset = ServerTime - LocalTime
-        let mut time_offset: i64 = 0; 
-        let mut offset_initialized = false;
+        // Dynamic Time Sync (Trade WS `Timenow`), stamps every request header
+        let mut time_sync = TimeSync::new(time_config);
+        let recv_window = time_sync.config.recv_window_ms;
         
         // Auto-Liquidation State
         let mut last_fill_ts: Option<Instant> = None;
     
-        info!("HOT: Entering Main Loop (Dual Exchange Mode)...");
+        info!(net, "HOT: Entering Main Loop (Dual Exchange Mode)...");
         
         loop {
+        watchdog::beat(&HOT_HEARTBEAT_US);
         if let Err(e) = poll.poll(&mut events, Some(Duration::from_millis(1))) {
             eprintln!("Poll error: {}", e);
         }
 
         for event in &events {
             match event.token() {
-                BYBIT_TOKEN => {
+                BYBIT_TOKEN | BYBIT_BACKUP_TOKEN => {
+                    let feed = if event.token() == BYBIT_TOKEN { Feed::Primary } else { Feed::Backup };
+                    let Some(feed_conn) = (match feed {
+                        Feed::Primary => Some(&mut public),
+                        Feed::Backup => backup.as_mut(),
+                    }) else { continue };
                     if event.is_writable() {
-                        ws_client.is_connected = true; 
-                        
-                        match state {
-                            ConnectionState::HandshakeSending => {
-                                info!("HOT: Sending Bybit Handshake...");
-                                if let Err(e) = ws_client.send_handshake(host, path) {
-                                     eprintln!("Bybit Handshake send error: {}", e);
-                                }
-                                state = ConnectionState::HandshakeWaiting;
+                        feed_conn.on_writable();
+                    }
+                    let start_tick = Instant::now();
+                    let read = if event.is_readable() {
+                        risk.update_packet_time();
+                        feed_conn.on_readable()
+                    } else {
+                        0
+                    };
+                    let tls_done = feed_conn.connection().client.tls.stats.handshake_done_ts;
+                    let mut resync = Vec::new(); // Books to re-snapshot once the batch is handled
+                    feed_conn.next_events(&mut |event| match event {
+                        AdapterEvent::HandshakeSent if feed == Feed::Primary => {
+                            public_startup.mark(ConnectStage::Tcp, Instant::now());
+                        }
+                        AdapterEvent::Upgraded if feed == Feed::Primary => {
+                            if let Some(ts) = tls_done {
+                                public_startup.mark(ConnectStage::Tls, ts);
                             }
-                            ConnectionState::Subscribing => {
-                                let sub_msg = r#"{"op": "subscribe", "args": ["orderbook.50.RIVERUSDT"]}"#;
-                                info!("HOT: Sending Bybit Subscription: {}", sub_msg);
-                                
-                                let frame_len = framing::encode_text_frame(sub_msg.as_bytes(), &mut frame_buf);
-                                
-                                if frame_len > 0 {
-                                    if let Err(e) = ws_client.tls.write_plaintext(&frame_buf[..frame_len]) {
-                                        eprintln!("Subscription send error: {}", e);
-                                    }
-                                }
-                                state = ConnectionState::Active;
+                            public_startup.mark(ConnectStage::Upgrade, Instant::now());
+                        }
+                        AdapterEvent::Subscribed => {
+                            metrics.egress(Conn::Public, EgressOp::Subscribe, Instant::now());
+                            if feed == Feed::Primary {
+                                public_startup.mark(ConnectStage::Subscribe, Instant::now());
+                            }
+                            // With a backup feed, the stream is up once either feed is
+                            if arbiter.as_mut().is_none_or(|arbiter| arbiter.on_connect(feed)) {
+                                session.on_connect(Conn::Public, Instant::now());
+                                books.restart_warmup();
+                                outage.on_connect(Conn::Public, Instant::now());
+                            }
+                        }
+                        AdapterEvent::HandshakeSent | AdapterEvent::Upgraded | AdapterEvent::AuthSent => {}
+                        AdapterEvent::FrameError(_) => metrics.frame_error(),
+                        AdapterEvent::Down(_) => {
+                            // ... and down once both are
+                            if arbiter.as_mut().is_none_or(|arbiter| arbiter.on_down(feed)) {
+                                session.on_disconnect(Conn::Public, Instant::now());
+                                outage.on_disconnect(Conn::Public);
                             }
-                            _ => {}
                         }
+                        AdapterEvent::Message(payload) => {
+                            session.on_message(Conn::Public);
+                            outage.on_message(Conn::Public);
+                            feed_watch.on_message(Conn::Public);
+                            // The other feed delivered this one already
+                            if arbiter.as_mut().is_some_and(|arbiter| arbiter.on_message(feed, payload, Instant::now()) == Verdict::Stale) {
+                                return;
+                            }
+                             // Symbol from the raw topic (before the in-place parse); no topic -> primary
+                             let slot = core::parser::topic_symbol(payload).and_then(|sym| books.slot_of_bytes(sym)).unwrap_or(0);
+                             let (instrument, bybit_symbol) = (books.slots[slot].instrument, books.slots[slot].symbol);
+                             // Everything printed into a request is put on the instrument's grid first (core::rounding)
+                             let grid = instrument.grid();
+                             let (qty_dp, px_dp) = (grid.qty_decimals(), grid.price_decimals());
+                             let SymbolSlot { book, book_monitor, strategy, order_manager, send_gate, markout, router, .. } = &mut books.slots[slot];
+                             // Tickers: mark price is the OMS price-band reference (independent of our book)
+                             // ... and its bid1/ask1 (Bybit's own touch) the trade-through check for our book.
+                             // publicTrade: the newest price is the strategy's last-price guard
+                             let mut mark_price = None;
+                             let mut exchange_top = None;
+                             let mut last_trade = None;
+                             let is_ticker = !matches!(core::parser::parse_market_event(payload, |ev| match ev {
+                                 MarketEvent::Ticker(t) => {
+                                     mark_price = t.mark_price;
+                                     exchange_top = Some((t.bid1_price, t.ask1_price));
+                                 }
+                                 MarketEvent::Trade(t) => {
+                                     last_trade = Some(t.price);
+                                     if let Some(shadow) = shadow.as_mut().filter(|_| slot == 0) {
+                                         shadow.on_trade(&t, Instant::now());
+                                     }
+                                 }
+                                 _ => {}
+                             }), Ok(0));
+                             if let Some(mark) = mark_price {
+                                 order_manager.set_reference_price(mark, Instant::now());
+                             }
+                             if let Some(price) = last_trade {
+                                 strategy.last_price.on_trade(price, Instant::now());
+                             }
+                             let ticker_fault = exchange_top.and_then(|(bid1, ask1)| book_monitor.on_ticker(book, bid1, ask1));
+                             // Parse Bybit. A ticker fault runs the book path as an internal update (ts 0) to pull and resync.
+                             let book_update = if is_ticker {
+                                 ticker_fault.map(|_| core::parser::BookUpdate { ts: 0, update_id: 0, snapshot: false, levels: 0 })
+                             } else {
+                                 let parse_start = Instant::now();
+                                 match core::parser::parse_book_update(payload, book) {
+                                     Ok(update) => {
+                                         metrics.book_parsed(bybit_symbol, parse_start.elapsed(), update.levels);
+                                         Some(update)
+                                     }
+                                     Err(e) => {
+                                         debug!(parser, "HOT: Public message not applied to book: {:?}", e);
+                                         None
+                                     }
+                                 }
+                             };
+                             if let Some(update) = book_update {
+                                 metrics.book_update();
+                                 if update.snapshot {
+                                     public_startup.mark(ConnectStage::Snapshot, Instant::now());
+                                 }
+                                 // BOOK SANITY: crossed/locked/broken levels -> pull quotes, drop the book, resubscribe
+                                 let book_fault = ticker_fault.or_else(|| book_monitor.on_update(book, &update));
+                                 if let Some(fault) = book_fault {
+                                     eprintln!("HOT: [BOOK] Corrupt book ({:?}), pulling quotes and resyncing (faults: {})", fault, book_monitor.faults);
+                                     strategy.warmup.restart();
+                                 }
+                                 next_trace_id += 1;
+                                 let trace_id = next_trace_id;
+                                 order_manager.on_book_update(book);
+                                 router.update_quote(Venue::Bybit, book.bids[0].price, book.asks[0].price);
+                                 router.update_quote(Venue::Binance, strategy.binance_bid, strategy.binance_ask);
+                                 if slot == 0 && book_fault.is_none() {
+                                     heatmap.on_book(book, Instant::now()); // research: primary only
+                                 }
+                                 if markout.on_mid((book.bids[0].price + book.asks[0].price) / 2.0, Instant::now()) {
+                                     markout.report();
+                                 }
+                                 if order_manager.refresh_due(Instant::now()) {
+                                     strategy.requote_pending = true;
+                                 }
+                                     // Trigger Strategy, but only send if authenticated
+                                 let order_entry = trade_authenticated || rest_quoting.active();
+                                 let mut shadow_tick = None; // (health, live action count) of this tick
+                                 if order_entry && !book.is_ready(MIN_BOOK_LEVELS) {
+                                     info!(strategy, "HOT: Book not ready (snapshot: {}, depth {}/{}), strategy waiting",
+                                         book.snapshot_applied, book.depth(Side::Buy), book.depth(Side::Sell));
+                                 } else if order_entry {
+                                 let reduce_only = REDUCE_ONLY.load(std::sync::atomic::Ordering::Relaxed);
+                                 strategy.reduce_only = reduce_only;
+                                 let kill = KILL_SWITCH.load(std::sync::atomic::Ordering::Relaxed);
+                                 send_gate.kill_switch = kill;
+                                 let maintenance = MAINTENANCE_HALT.load(std::sync::atomic::Ordering::Relaxed);
+                                 let halted = maintenance || outage.halted() || kill;
+                                 if halted && !strategy.halted {
+                                     // Circuit breaker tripped: snapshot the book as it was
+                                     let reason = if kill { "kill-switch" } else if outage.halted() { "exchange-incident" } else { "maintenance" };
+                                     incidents.capture(IncidentTrigger::Halt { reason }, book, strategy, order_manager, &drop_copy);
+                                 }
+                                 strategy.halted = halted;
+                                 order_manager.set_reduce_only(reduce_only);
+                                 if let Some(variant) = experiment.poll(Instant::now()).filter(|_| slot == 0) {
+                                     strategy.params = experiment.params(variant);
+                                     strategy.requote_pending = true;
+                                     info!(strategy, "HOT: [EXPERIMENT] New slice -> variant {}", variant.name());
+                                     experiment.report((book.bids[0].price + book.asks[0].price) / 2.0, Instant::now());
+                                 }
+                                 // WARMUP: size ramps up only while book, Trade WS and tick latency are healthy
+                                 let healthy = book_fault.is_none() && !trade_ws_down && !trade_pipeline.degraded(Instant::now())
+                                     && last_tick_latency <= strategy.warmup.config.max_tick_latency;
+                                 strategy.on_health(healthy);
+                                 let strat_start = Instant::now();
+                                 let tick_actions = if book_fault.is_some() { strategy.pull_quotes() } else { strategy.on_tick(book, &update) };
+                                 shadow_tick = Some((healthy, tick_actions.as_ref().map_or(0, Vec::len)));
+                                 if let Some(mut actions) = tick_actions {
+                                     metrics.strategy_eval(actions.len());
+                                     order_manager.set_position(strategy.positions.size());
+                                     // SELF-MATCH PREVENTION: pull our own resting quote before anything crosses it
+                                     let stp = order_manager.prevent_self_match(&mut actions, [&strategy.active_buy_link_id, &strategy.active_sell_link_id]);
+                                     for side in stp.into_iter().flatten() {
+                                         strategy.on_order_cancel(side);
+                                     }
+                                     // PRIORITY: risk-reducing actions leave first, new quotes last (stable: order kept within a class)
+                                     actions.sort_by_key(|a| order_manager.egress_priority(&a.action_type));
+                                     let decided_ts = Instant::now();
+                                     let strat_cost = decided_ts.duration_since(strat_start).as_micros();
+                                     // Loop through actions
+                                     for mut action in actions {
+                                         // Only the fields that change go out (Bybit rejects no-op amends as "not modified")
+                                         if !order_manager.narrow_amend(&mut action.action_type) {
+                                             debug!(orders, "HOT: Amend would not change the order, skipped: {:?}", action.action_type);
+                                             continue;
+                                         }
+                                         // REST QUOTING: one create/amend per side per interval, deferred like a veto
+                                         let via_rest = rest_quoting.active();
+                                         if via_rest && !rest_quoting.ready(&action.action_type, Instant::now()) {
+                                             debug!(orders, "HOT: [REST-QUOTING] Paced, NOT sent: {:?}", action.action_type);
+                                             match action.action_type {
+                                                 ActionType::AmendOrder { side, .. } | ActionType::AmendPrice { side, .. } | ActionType::AmendQty { side, .. } => {
+                                                     strategy.on_amend_deferred(side, order_manager.record(side).price, true);
+                                                 }
+                                                 ActionType::CreateOrder { side, .. } => strategy.on_order_cancel(side),
+                                                 _ => {}
+                                             }
+                                             continue;
+                                         }
+                                         // Generate timestamp for header
+                                         // DYNAMIC TIME SYNC: synced offset minus margin, or a wider fixed margin before the first response
+                                         let ts_ms = time_sync.request_ts_ms(wall_ms());
+
+                                         // SEND GATE phase 1: kill switch -> validator -> risk (OMS) -> margin -> rate limiter
+                                         let oms_now = Instant::now();
+                                         if let Err(veto) = send_gate.pre_send(&action.action_type, order_manager, &margin, oms_now) {
+                                             metrics.veto();
+                                             let stage = veto.stage();
+                                             if veto.loud() {
+                                                 eprintln!("HOT: [GATE] {} veto, NOT sent: {:?} ({:?})", stage.name(), veto, action.action_type);
+                                             }
+                                             info!(orders, "HOT: [GATE] Deferred by {} (#{}): {:?}", stage.name(), send_gate.vetoes(stage), veto);
+                                             match action.action_type {
+                                                 ActionType::AmendOrder { side, .. } | ActionType::AmendPrice { side, .. } | ActionType::AmendQty { side, .. } => {
+                                                     strategy.on_amend_deferred(side, order_manager.record(side).price, veto.retry());
+                                                 }
+                                                 // Never left the building: strategy must not think it's resting
+                                                 ActionType::CreateOrder { side, .. } => strategy.on_order_cancel(side),
+                                                 _ => {}
+                                             }
+                                             continue;
+                                         }
+                                         order_manager.begin_trace(&action.action_type, trace_id, start_tick, decided_ts);
+                                         // DROP COPY: snapshot of the request as it leaves the gate, route filled in below
+                                         let dc_order = DropCopyEvent::from_action(&action.action_type);
+                                         // SEND GATE phase 2: committed to a route below (WS, queue or REST)
+                                         let committed = send_gate.post_send(&action.action_type, order_manager, &mut margin, oms_now);
+                                         if let Some(trip) = send_gate.throttle.take_trip() {
+                                             eprintln!("HOT: [THROTTLE] {} {} create loop, side benched for {:?} (level {}): {:?}", bybit_symbol, trip.side, trip.cooldown, trip.level, trip.kind);
+                                         }
+                                         let trace_side = order_manager::action_side(&action.action_type);
+                                         let priority = order_manager.egress_priority(&action.action_type);
+                                         let egress_op = EgressOp::of(&action.action_type);
+
+                                         // EXIT COST: the strategy's close is on the Bybit position, so it goes to Bybit;
+                                         // the fee-adjusted gap to where the router would send a reducing order is logged
+                                         if let ActionType::ClosePosition { side, .. } = action.action_type {
+                                             if let (Some(bybit_px), Some(route)) = (router.effective_px(Venue::Bybit, side), router.route(side)) {
+                                                 info!(orders, "HOT: [ROUTER] Close {} on Bybit (effective {:.4}), route {:?} {:.4}", side, bybit_px, route.venue, route.effective_px);
+                                             }
+                                         }
+
+                                         // REST FALLBACK: risk-reducing actions must not depend on a sick Trade WS
+                                         // (in REST quoting mode the quotes themselves go there too)
+                                         let rest_req = match action.action_type {
+                                             ActionType::CancelAll => Some(RestRequest::CancelAll),
+                                             ActionType::ClosePosition { qty, side } => Some(RestRequest::ClosePosition {
+                                                 qty: instrument.order_qty(qty, (book.bids[0].price + book.asks[0].price) / 2.0),
+                                                 side,
+                                             }),
+                                             _ if !via_rest => None,
+                                             ActionType::CreateOrder { price, qty, side, ref link_id } => Some(RestRequest::CreateOrder {
+                                                 side,
+                                                 price,
+                                                 qty: instrument.order_qty(qty, price),
+                                                 reduce_only: order_manager.reduce_only() || order_manager.reduces_position(side),
+                                                 link_id: LinkId::new(link_id),
+                                             }),
+                                             ActionType::AmendOrder { price, qty, ref link_id, .. } => Some(RestRequest::AmendOrder {
+                                                 price: Some(price),
+                                                 qty: Some(instrument.order_qty(qty, price)),
+                                                 link_id: LinkId::new(link_id),
+                                             }),
+                                             ActionType::AmendPrice { price, ref link_id, .. } => Some(RestRequest::AmendOrder { price: Some(price), qty: None, link_id: LinkId::new(link_id) }),
+                                             ActionType::AmendQty { qty, side, ref link_id } => Some(RestRequest::AmendOrder {
+                                                 price: None,
+                                                 qty: Some(instrument.order_qty(qty, order_manager.latest(side).0)),
+                                                 link_id: LinkId::new(link_id),
+                                             }),
+                                             ActionType::CancelOrder { ref link_id } => Some(RestRequest::CancelOrder { link_id: LinkId::new(link_id) }),
+                                             _ => None,
+                                         };
+                                         // A risk pull lost on the Trade WS is retried over REST (on_order_dropped)
+                                         let retry = match action.action_type {
+                                             _ if committed != Committed::RiskPull => None,
+                                             ActionType::CancelOrder { ref link_id } => Some(RestRequest::CancelOrder { link_id: LinkId::new(link_id) }),
+                                             _ => rest_req,
+                                         };
+                                         let outgoing = Outgoing { slot, committed, retry, dc_order };
+                                         if let Some(req) = rest_req {
+                                             if via_rest || trade_ws_down || trade_pipeline.degraded(oms_now) {
+                                                 if rest_producer.push((slot, req)).is_err() {
+                                                     eprintln!("HOT: REST fallback ring full, {:?} LOST", req);
+                                                     let outgoing = Outgoing { retry: None, ..outgoing };
+                                                     let route = on_order_dropped(outgoing, strategy, order_manager, send_gate, &mut margin, &mut rest_producer);
+                                                     drop_copy.publish_order(dc_order, route);
+                                                 } else {
+                                                     info!(orders, "HOT: Trade WS unhealthy -> {:?} routed via REST", req);
+                                                     drop_copy.publish_order(dc_order, Route::Rest);
+                                                     if via_rest {
+                                                         rest_quoting.on_sent(&action.action_type, oms_now);
+                                                         if let Some(side) = trace_side {
+                                                             order_manager.mark(side, TraceStage::Sent, oms_now);
+                                                         }
+                                                     }
+                                                 }
+                                                 continue;
+                                             }
+                                         }
+
+                                         // Send to TRADE WS
+                                         let req_json = match action.action_type {
+                                             ActionType::CreateOrder { price, qty, side, link_id } => {
+                                                  info!(orders, "HOT: [PERF] #{} CreateOrder {} @ {} generated in {}us", trace_id, side, price, strat_cost);
+                                                  // Exit orders (aged inventory) must never flip the position
+                                                  let reduces = order_manager.reduce_only() || order_manager.reduces_position(side);
+                                                  let reduce_flag = if reduces && category.has_positions() { r#","reduceOnly":true"# } else { "" };
+                                                  format!(r#"{{"reqId":"{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.create","args":[{{"category":"{}","symbol":"{}","side":"{}"{},"orderType":"Limit","qty":"{:.*}","price":"{:.*}","timeInForce":"PostOnly","orderLinkId":"{}"{}}}]}}"#,
+                                                      link_id, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol, side, position_idx,
+                                                      qty_dp, grid.qty(instrument.order_qty(qty, price)), px_dp, grid.price(price), link_id, reduce_flag)
+                                              },
+                                             ActionType::AmendOrder { price, qty, side: _, link_id } => {
+                                                 info!(orders, "HOT: [PERF] #{} AmendOrder to {} generated in {}us", trace_id, price, strat_cost);
+                                                 format!(r#"{{"reqId":"amend-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.amend","args":[{{"category":"{}","symbol":"{}","qty":"{:.*}","price":"{:.*}","orderLinkId":"{}"}}]}}"#,
+                                                     link_id, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol,
+                                                     qty_dp, grid.qty(instrument.order_qty(qty, price)), px_dp, grid.price(price), link_id)
+                                             },
+                                             ActionType::AmendPrice { price, side: _, link_id } => {
+                                                 info!(orders, "HOT: [PERF] #{} AmendPrice to {} generated in {}us", trace_id, price, strat_cost);
+                                                 format!(r#"{{"reqId":"amend-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.amend","args":[{{"category":"{}","symbol":"{}","price":"{:.*}","orderLinkId":"{}"}}]}}"#,
+                                                     link_id, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol, px_dp, grid.price(price), link_id)
+                                             },
+                                             ActionType::AmendQty { qty, side, link_id } => {
+                                                 info!(orders, "HOT: [PERF] #{} AmendQty to {} generated in {}us", trace_id, qty, strat_cost);
+                                                 // Inverse qty is converted at the price the order keeps
+                                                 let keep_px = order_manager.latest(side).0;
+                                                 format!(r#"{{"reqId":"amend-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.amend","args":[{{"category":"{}","symbol":"{}","qty":"{:.*}","orderLinkId":"{}"}}]}}"#,
+                                                     link_id, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol, qty_dp, grid.qty(instrument.order_qty(qty, keep_px)), link_id)
+                                             },
+                                             ActionType::CancelOrder { link_id } => {
+                                                 info!(orders, "HOT: [PERF] #{} CancelOrder generated in {}us", trace_id, strat_cost);
+                                                 format!(r#"{{"reqId":"cancel-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.cancel","args":[{{"category":"{}","symbol":"{}","orderLinkId":"{}"}}]}}"#,
+                                                     link_id, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol, link_id)
+                                             },
+                                             ActionType::ClosePosition { qty, side } => {
+                                                 // Market Order to Close
+                                                 // Use ReduceOnly to prevent flipping position (close_args: per category)
+                                                 info!(orders, "HOT: Strategy requested ClosePosition: Side={}, Qty={} (Calc: {}us)", side, qty, strat_cost);
+                                                 let close_qty = grid.qty(instrument.order_qty(qty, (book.bids[0].price + book.asks[0].price) / 2.0));
+                                                 format!(r#"{{"reqId":"close-{}{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.create","args":[{{"category":"{}","symbol":"{}","side":"{}","orderType":"Market","qty":"{:.*}","timeInForce":"GTC","smpType":"CancelMaker","orderLinkId":"close-{}{}-{}"{}}}]}}"#,
+                                                     strategy.link_tag, side, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol, side, qty_dp, close_qty, strategy.link_tag, side, ts_ms, close_args)
+                                             },
+                                             ActionType::SetTradingStop { price, .. } if !category.is_futures() => {
+                                                info!(orders, "HOT: SetTradingStop @ {} skipped: no trading stop for {}", price, category.as_str());
+                                                String::new()
+                                             },
+                                             ActionType::SetTradingStop { price, side } => {
+                                                info!(orders, "HOT: Strategy requested SetTradingStop (SL) @ {}", price);
+                                                // Requires positionIdx=0 for One-Way Mode
+                                                format!(r#"{{"reqId":"sl-{}{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"position.trading-stop","args":[{{"category":"{}","symbol":"{}","stopLoss":"{:.*}","positionIdx":0}}]}}"#,
+                                                    strategy.link_tag, side, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol, px_dp, grid.price(price))
+                                             },
+                                             ActionType::CancelAll => {
+                                                 info!(orders, "HOT: Strategy requested CancelAll (Clean Sweep)");
+                                                 format!(r#"{{"reqId":"cancel-all-{}{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"{}"}},"op":"order.cancel-all","args":[{{"category":"{}","symbol":"{}"}}]}}"#,
+                                                     strategy.link_tag, ts_ms, ts_ms, recv_window, category.as_str(), bybit_symbol)
+                                             },
+                                             _ => String::new()
+                                         };
+
+                                         let latency = start_tick.elapsed();
+                                         let lat_u64 = latency.as_micros() as u64;
+
+                                         if !req_json.is_empty() {
+                                             // info!(">>> ORDER OUT: {}", req_json);
+                                             let frame_len = framing::encode_text_frame(req_json.as_bytes(), &mut frame_buf);
+                                             if let Some(side) = trace_side {
+                                                 order_manager.mark(side, TraceStage::Serialized, Instant::now());
+                                             }
+                                             // Write to TRADE WS
+                                             // We assume Trade WS is ready. Ideally check state.
+                                             if frame_len > 0 {
+                                                 if trade_pipeline.can_send() {
+                                                     if let Err(e) = trade.send_order(&frame_buf[..frame_len]) {
+                                                         eprintln!("Order Send Error: {}", e);
+                                                         trade_ws_down = true;
+                                                         let route = on_order_dropped(outgoing, strategy, order_manager, send_gate, &mut margin, &mut rest_producer);
+                                                         drop_copy.publish_order(dc_order, route);
+                                                     } else {
+                                                         let sent_ts = Instant::now();
+                                                         metrics.orders_sent(1);
+                                                         if let Some(op) = egress_op {
+                                                             metrics.egress(Conn::Trade, op, sent_ts);
+                                                         }
+                                                         drop_copy.publish_order(dc_order, Route::Ws);
+                                                         trade_pipeline.on_sent(sent_ts);
+                                                         if let Some(side) = trace_side {
+                                                             order_manager.mark(side, TraceStage::Sent, sent_ts);
+                                                         }
+                                                         // Egress: bytes on the socket now, or on the next writable event
+                                                         if let Some(flushed) = trade.connection().client.tls.flushed_at() {
+                                                             order_manager.mark_egress(flushed);
+                                                         }
+                                                     }
+                                                 } else if trade_pipeline.enqueue(&frame_buf[..frame_len], priority as u8, outgoing, Instant::now()) {
+                                                     info!(orders, "HOT: [PIPELINE] {} in flight, order queued ({} waiting)", trade_pipeline.in_flight(), trade_pipeline.queued());
+                                                     if let Some(op) = egress_op {
+                                                         metrics.egress(Conn::Trade, op, Instant::now());
+                                                     }
+                                                     drop_copy.publish_order(dc_order, Route::WsQueued);
+                                                 } else {
+                                                     eprintln!("HOT: [PIPELINE] Queue full, order DROPPED (total drops: {})", trade_pipeline.dropped);
+                                                     let route = on_order_dropped(outgoing, strategy, order_manager, send_gate, &mut margin, &mut rest_producer);
+                                                     drop_copy.publish_order(dc_order, route);
+                                                 }
+                                             } else {
+                                                 eprintln!("HOT: Order frame over {} bytes, DROPPED: {:?}", FRAME_SLOT_SIZE, egress_op);
+                                                 let route = on_order_dropped(outgoing, strategy, order_manager, send_gate, &mut margin, &mut rest_producer);
+                                                 drop_copy.publish_order(dc_order, route);
+                                             }
+                                         }
 
-                        if let Err(e) = ws_client.write_tls() {
-                            eprintln!("Bybit TLS Write Error: {}", e);
+                                         // Push Log
+                                         let _ = producer.push(LogMessage {
+                                             timestamp: tick_count,
+                                             msg_type: 20,
+                                             bybit_bid: book.bids[0].price,
+                                             bybit_ask: book.asks[0].price,
+                                             binance_bid: strategy.binance_bid,
+                                             binance_ask: strategy.binance_ask,
+                                             latency: lat_u64,
+                                             trace_id,
+                                             strategy_event: None,
+                                             metrics: None,
+                                             session: None,
+                                         });
+                                     }
+                                 }
+                                 } // end priv_authenticated check
+                                 // SHADOW: the same tick for the candidate, once the live actions are out
+                                 if let (Some(shadow), Some((healthy, live_actions))) = (shadow.as_mut().filter(|_| slot == 0), shadow_tick) {
+                                     shadow.strategy.halted = strategy.halted;
+                                     shadow.strategy.reduce_only = strategy.reduce_only;
+                                     shadow.on_live_actions(live_actions);
+                                     shadow.on_book(book, &update, healthy, book_fault.is_some());
+                                     shadow.report_due((book.bids[0].price + book.asks[0].price) / 2.0, Instant::now());
+                                 }
+                                 if book_fault.is_some() {
+                                     // Fresh snapshot: unsubscribe + subscribe makes Bybit resend the full book.
+                                     // Until it lands the book is not ready, so the strategy stays out.
+                                     book.clear();
+                                     resync.push(bybit_symbol);
+                                 }
+                             }
+
+                            // Throttled Status Update (every 100 ticks, primary symbol)
+                            if tick_count.is_multiple_of(100) {
+                                 let primary = &books.slots[0];
+                                 let _ = producer.push(LogMessage {
+                                     timestamp: tick_count,
+                                     msg_type: 1, // Status
+                                     bybit_bid: primary.book.bids[0].price,
+                                     bybit_ask: primary.book.asks[0].price,
+                                     binance_bid: primary.strategy.binance_bid,
+                                     binance_ask: primary.strategy.binance_ask,
+                                     latency: last_latency as u64,
+                                     trace_id: next_trace_id,
+                                     strategy_event: None,
+                                     metrics: None,
+                                     session: None,
+                                 });
+                            }
+                        }
+                    });
+                    for symbol in resync {
+                        if let Some(arbiter) = arbiter.as_mut() {
+                            arbiter.resync(symbol.as_bytes()); // the other feed's deltas wait for this snapshot
+                        }
+                        match feed_conn.resync_book(symbol) {
+                            Ok(()) => {
+                                // unsubscribe + subscribe
+                                metrics.egress(Conn::Public, EgressOp::Subscribe, Instant::now());
+                                metrics.egress(Conn::Public, EgressOp::Subscribe, Instant::now());
+                            }
+                            Err(e) => eprintln!("HOT: [BOOK] Resync send error: {}", e),
                         }
                     }
-
+                    if read > 0 {
+                        risk.check_internal_latency(start_tick);
+                        last_tick_latency = start_tick.elapsed();
+                        metrics.tick(last_tick_latency);
+                    }
+                }
+                
+                // OKX public: books5 top of book -> each slot's router (exit-cost reference)
+                OKX_TOKEN => {
+                    let Some(okx) = okx.as_mut() else { continue };
+                    if event.is_writable() {
+                        okx.on_writable();
+                    }
                     if event.is_readable() {
-                        risk.update_packet_time();
-                        let start_tick = Instant::now();
-                        
-                        // BYBIT READ logic
-                        if offset >= buf.len() {
-                             offset = 0; // Reset on overflow
-                        }
-                        
-                        match ws_client.read(&mut buf[offset..]) {
-                            Ok(n) if n > 0 => {
-                                let end = offset + n;
-                                match state {
-                                    ConnectionState::HandshakeWaiting => {
-                                        if let Ok(s) = std::str::from_utf8(&buf[..end]) {
-                                            if s.contains("101 Switching Protocols") {
-                                                info!("HOT: Bybit Upgraded! Ready to Subscribe.");
-                                                state = ConnectionState::Subscribing;
-                                                offset = 0; 
-                                            } else {
-                                                offset = end; 
-                                            }
-                                        }
-                                    }
-                                    ConnectionState::Active => {
-                                        // Frame Decoding Loop
-                                        let mut current_pos = 0;
-                                        loop {
-                                            let slice = &mut buf[current_pos..end];
-                                            match framing::decode_frame(slice) {
-                                                Ok(Some((consumed, payload))) => {
-                                                    if !payload.is_empty() {
-                                                         // Parse Bybit
-                                                         if let Ok(ts) = core::parser::parse_and_update(payload, &mut book) {
-                                                                 // Trigger Strategy, but only send if authenticated
-                                                             if trade_authenticated {
-                                                             let strat_start = Instant::now();
-                                                             if let Some(actions) = strategy.on_tick(&book, ts) {
-                                                                 let strat_cost = strat_start.elapsed().as_micros();
-                                                                 // Loop through actions
-                                                                 for action in actions {
-                                                                     // Generate timestamp for header
-                                                                     // DYNAMIC TIME SYNC
-                                                                     let local_now = std::time::SystemTime::now()
-                                                                         .duration_since(std::time::UNIX_EPOCH)
-                                                                         .unwrap_or_default()
-                                                                         .as_millis() as u64;
-                                                                     
-                                                                     // Apply offset. If offset is negative (Local > Server), we subtract difference.
-                                                                     // If not initialized, we try a safe fallback or sending naive time.
-                                                                     let ts_ms = if offset_initialized {
-                                                                         ((local_now as i64) + time_offset) as u64
-                                                                     } else {
-                                                                         // Fallback if no response yet: Subtract 2s to be safe
-                                                                         local_now.saturating_sub(2000) 
-                                                                     };
-                                                                     
-                                                                     // Send to TRADE WS
-                                                                     let req_json = match action.action_type {
-                                                                         ActionType::CreateOrder { price, qty, side, link_id } => {
-                                                                              info!("HOT: [PERF] CreateOrder {} @ {} generated in {}us", side, price, strat_cost);
-                                                                              format!(r#"{{"reqId":"{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"20000"}},"op":"order.create","args":[{{"category":"linear","symbol":"RIVERUSDT","side":"{}","positionIdx":0,"orderType":"Limit","qty":"{:.1}","price":"{:.3}","timeInForce":"PostOnly","orderLinkId":"{}"}}]}}"#, 
-                                                                                  link_id, ts_ms, ts_ms, side, qty, price, link_id)
-                                                                          },
-                                                                         ActionType::AmendOrder { price, qty, side: _, link_id } => {
-                                                                             info!("HOT: [PERF] AmendOrder to {} generated in {}us", price, strat_cost);
-                                                                             format!(r#"{{"reqId":"amend-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"20000"}},"op":"order.amend","args":[{{"category":"linear","symbol":"RIVERUSDT","qty":"{:.1}","price":"{:.3}","orderLinkId":"{}"}}]}}"#, 
-                                                                                 link_id, ts_ms, ts_ms, qty, price, link_id)
-                                                                         },
-                                                                         ActionType::CancelOrder { link_id } => {
-                                                                             info!("HOT: [PERF] CancelOrder generated in {}us", strat_cost);
-                                                                             format!(r#"{{"reqId":"cancel-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"20000"}},"op":"order.cancel","args":[{{"category":"linear","symbol":"RIVERUSDT","orderLinkId":"{}"}}]}}"#, 
-                                                                                 link_id, ts_ms, ts_ms, link_id)
-                                                                         },
-                                                                         ActionType::ClosePosition { qty, side } => {
-                                                                             // Market Order to Close
-                                                                             // Use ReduceOnly to prevent flipping position
-                                                                             info!("HOT: Strategy requested ClosePosition: Side={}, Qty={} (Calc: {}us)", side, qty, strat_cost);
-                                                                             format!(r#"{{"reqId":"close-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"20000"}},"op":"order.create","args":[{{"category":"linear","symbol":"RIVERUSDT","side":"{}","positionIdx":0,"orderType":"Market","qty":"{:.1}","timeInForce":"GTC","reduceOnly":true,"orderLinkId":"close-{}-{}"}}]}}"#, 
-                                                                                 side, ts_ms, ts_ms, side, qty, side, ts_ms)
-                                                                         },
-                                                                         ActionType::SetTradingStop { price, side } => {
-                                                                            info!("HOT: Strategy requested SetTradingStop (SL) @ {}", price);
-                                                                            // Requires positionIdx=0 for One-Way Mode
-                                                                            format!(r#"{{"reqId":"sl-{}-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"20000"}},"op":"position.trading-stop","args":[{{"category":"linear","symbol":"RIVERUSDT","stopLoss":"{:.3}","positionIdx":0}}]}}"#, 
-                                                                                side, ts_ms, ts_ms, price)
-                                                                         },
-                                                                         ActionType::CancelAll => {
-                                                                             info!("HOT: Strategy requested CancelAll (Clean Sweep)");
-                                                                             format!(r#"{{"reqId":"cancel-all-{}","header":{{"X-BAPI-TIMESTAMP":"{}","X-BAPI-RECV-WINDOW":"20000"}},"op":"order.cancel-all","args":[{{"category":"linear","symbol":"RIVERUSDT"}}]}}"#, 
-                                                                                 ts_ms, ts_ms)
-                                                                         },
-                                                                         _ => String::new()
-                                                                     };
-                                                                     
-                                                                     let latency = start_tick.elapsed();
-                                                                     let lat_u64 = latency.as_micros() as u64;
-
-                                                                     if !req_json.is_empty() {
-                                                                         // info!(">>> ORDER OUT: {}", req_json);
-                                                                         let frame_len = framing::encode_text_frame(req_json.as_bytes(), &mut frame_buf);
-                                                                         // Write to TRADE WS
-                                                                         // We assume Trade WS is ready. Ideally check state.
-                                                                         if frame_len > 0 {
-                                                                             if let Err(e) = ws_trade.tls.write_plaintext(&frame_buf[..frame_len]) { 
-                                                                                 eprintln!("Order Send Error: {}", e);
-                                                                             } else {
-                                                                                 // Hack: Force queue write
-                                                                                 let _ = ws_trade.write_tls();
-                                                                             }
-                                                                         }
-                                                                     }
-                                                                     
-                                                                     // Push Log
-                                                                     let _ = producer.push(LogMessage {
-                                                                         timestamp: tick_count,
-                                                                         msg_type: 20, 
-                                                                         bybit_bid: book.bids[0].price,
-                                                                         bybit_ask: book.asks[0].price,
-                                                                         binance_bid: strategy.binance_bid,
-                                                                         binance_ask: strategy.binance_ask,
-                                                                         latency: lat_u64,
-                                                                     });
-                                                                 }
-                                                             }
-                                                             } // end priv_authenticated check
-                                                         }
-                                                    }
-
-                                                    // Throttled Status Update (every 100 ticks)
-                                                    if tick_count % 100 == 0 {
-                                                         let _ = producer.push(LogMessage {
-                                                             timestamp: tick_count,
-                                                             msg_type: 1, // Status
-                                                             bybit_bid: book.bids[0].price,
-                                                             bybit_ask: book.asks[0].price,
-                                                             binance_bid: strategy.binance_bid,
-                                                             binance_ask: strategy.binance_ask,
-                                                             latency: last_latency as u64,
-                                                         });
-                                                    }
-                                                    current_pos += consumed;
-                                                },
-                                                Ok(None) => break,
-                                                Err(_) => break, // Drop invalid
-                                            }
-                                        }
-                                        if current_pos < end {
-                                            buf.copy_within(current_pos..end, 0);
-                                            offset = end - current_pos;
-                                        } else {
-                                            offset = 0;
-                                        }
+                        okx.on_readable();
+                    }
+                    okx.next_events(&mut |event| match event {
+                        AdapterEvent::Message(message) => {
+                            let parsed = okx::parse_message(message, |event| match event {
+                                OkxEvent::Book { inst_id, book } => {
+                                    if let Some(slot) = books.slots.iter_mut().find(|s| s.instrument.venue_symbol(Venue::Okx) == Some(inst_id)) {
+                                        slot.router.update_quote(Venue::Okx, book.best_bid(), book.best_ask());
                                     }
-                                    _ => { offset = 0; }
                                 }
-                                risk.check_internal_latency(start_tick);
+                                OkxEvent::Error { code, msg } => eprintln!("HOT: OKX error {}: {}", code, msg),
+                                _ => {}
+                            });
+                            if let Err(e) = parsed {
+                                debug!(net, "HOT: OKX parse error: {:?}", e);
                             }
-                            Ok(_) => {},
-                            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {},
-                            Err(e) => eprintln!("HOT: Bybit IO Error: {}", e),
                         }
-                    }
+                        AdapterEvent::Down(_) => eprintln!("HOT: OKX public WS down, reference quotes stop"),
+                        _ => {}
+                    });
                 }
-                
-                // BINANCE_TOKEN => { DISABLED }
 
-                BYBIT_PRIVATE_TOKEN => {
+                // Binance bookTicker: each slot's strategy reference and router (exit-cost reference)
+                BINANCE_TOKEN => {
+                    let Some(binance) = binance.as_mut() else { continue };
                     if event.is_writable() {
-                         match priv_state {
-                            ConnectionState::HandshakeSending => {
-                                info!("HOT: Sending Private Handshake...");
-                                if let Err(e) = ws_private.send_handshake(priv_host, priv_path) {
-                                     eprintln!("Private Handshake send error: {}", e);
+                        binance.on_writable();
+                    }
+                    if event.is_readable() {
+                        binance.on_readable();
+                    }
+                    binance.next_events(&mut |event| match event {
+                        AdapterEvent::Message(message) => {
+                            let parsed = binance::parse_market_message(message, |event| match event {
+                                BinanceMarketEvent::BookTicker { symbol, bid, ask, .. } => {
+                                    if let Some(slot) = books.slots.iter_mut().find(|s| s.instrument.venue_symbol(Venue::Binance) == Some(symbol)) {
+                                        slot.strategy.binance_bid = bid;
+                                        slot.strategy.binance_ask = ask;
+                                        slot.router.update_quote(Venue::Binance, bid, ask);
+                                    }
                                 }
-                                priv_state = ConnectionState::HandshakeWaiting;
-                            }
-                            ConnectionState::Authenticating => {
-                                // Auth
-                                let expires = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() + 5000;
-                                let sign_payload = format!("GET/realtime{}", expires);
-                                signer.sign_message(sign_payload.as_bytes(), &mut signature_hex);
-                                let sig_str = std::str::from_utf8(&signature_hex[..64]).unwrap_or(""); 
-                                
-                                let auth_msg = format!(r#"{{"op":"auth","args":["{}","{}","{}"]}}"#, api_key, expires, sig_str);
-                                info!("HOT: Authenticating Private WS...");
-                                let auth_len = framing::encode_text_frame(auth_msg.as_bytes(), &mut frame_buf);
-                                let _ = ws_private.tls.write_plaintext(&frame_buf[..auth_len]);
-                                
-                                priv_state = ConnectionState::Active; 
+                                BinanceMarketEvent::Error { code, msg } => eprintln!("HOT: Binance error {}: {}", code, msg),
+                                BinanceMarketEvent::Ack { .. } => {}
+                            });
+                            if let Err(e) = parsed {
+                                debug!(net, "HOT: Binance parse error: {:?}", e);
                             }
-                            _ => {}
                         }
-                        let _ = ws_private.write_tls();
-                    }
+                        AdapterEvent::Down(_) => eprintln!("HOT: Binance WS down, reference quotes stop"),
+                        _ => {}
+                    });
+                }
 
+                // Binance user data: the account's executions (hedges) and balances; not the strategy's fills
+                BINANCE_USER_TOKEN => {
+                    let Some(user) = binance_user.as_mut() else { continue };
+                    if event.is_writable() {
+                        user.on_writable();
+                    }
                     if event.is_readable() {
-                        if priv_offset >= priv_buf.len() { priv_offset = 0; }
-                        match ws_private.read(&mut priv_buf[priv_offset..]) {
-                            Ok(n) if n > 0 => {
-                                info!("HOT: Private WS Read {} bytes, state={:?}", n, priv_state);
-                                let end = priv_offset + n;
-                                match priv_state {
-                                    ConnectionState::HandshakeWaiting => {
-                                        if let Ok(s) = std::str::from_utf8(&priv_buf[..end]) {
-                                            if s.contains("101 Switching Protocols") {
-                                                info!("HOT: Private Switch Proto!");
-                                                priv_state = ConnectionState::Authenticating; 
-                                                priv_offset = 0;
-                                            } else { priv_offset = end; }
-                                        }
-                                    }
-                                    ConnectionState::Active => {
-                                        // Parse Executions
-                                        // DEBUG: Show raw buffer (hex if not UTF-8)
-                                        // info!("HOT: Private Active entered, end={}", end);
-                                        let _ = std::io::stdout().flush();
-                                        let mut current_pos = 0;
-                                        loop {
-                                            let slice = &mut priv_buf[current_pos..end];
-                                            let decode_result = framing::decode_frame(slice);
-                                            // info!("HOT: decode_frame result: {:?}", decode_result.as_ref().map(|r| r.as_ref().map(|(c, p)| (*c, p.len()))));
-                                            match decode_result {
-                                                Ok(Some((consumed, payload))) => {
-                                                    // info!("HOT: Decoded frame, consumed={}, payload_len={}", consumed, payload.len());
-                                                    if !payload.is_empty() {
-                                                        // LOG ALL PRIVATE RESPONSES
-                                                        match std::str::from_utf8(payload) {
-                                                            Ok(s) => {}, // info!("HOT: Private RAW: {}", s),
-                                                            Err(_) => {}, // info!("HOT: Private RAW (hex): {:02x?}", &payload[..payload.len().min(50)]),
-                                                        }
-                                                        if let Ok(json) = simd_json::to_borrowed_value(payload) {                                                             // Check for Error (retCode != 0)
-                                                             if let Some(ret_code) = json.get("retCode").and_then(|v| v.as_i64()) {
-                                                                 if ret_code != 0 {
-                                                                     eprintln!("CRITICAL BYBIT ERROR: {:?}", json);
-                                                                     
-                                                                     // RECOVERY LOGIC
-                                                                     let op = json.get("op").and_then(|v| v.as_str()).unwrap_or("");
-                                                                     let ret_msg = json.get("retMsg").and_then(|v| v.as_str()).unwrap_or("");
-                                                                     
-                                                                     let is_create_fail = op == "order.create";
-                                                                     let is_gone = ret_code == 110001; // Order not exists
-                                                                     let is_mode_mismatch = ret_code == 10001; // Position mode mismatch OR Params error
-                                                                     let is_duplicate = ret_code == 110072; // Duplicate ClOrdID
-                                                                     
-                                                                     let is_not_modified = ret_msg.contains("not modified");
-                                                                     
-                                                                     // RECOVERY 1: Reset state on failure (excluding benign "not modified")
-                                                                     // We treat DUPLICATE (110072) as a failure that requires RESET (to generate new ID), not restore.
-                                                                     if (is_create_fail || is_gone || is_mode_mismatch || is_duplicate) && !is_not_modified {
-                                                                         if let Some(req_id) = json.get("reqId").and_then(|v| v.as_str()) {
-                                                                              let side_to_reset = if req_id.contains("bot-buy") || req_id.contains("-b-") || req_id.starts_with("b-") { Some("Buy") } 
-                                                                                         else if req_id.contains("bot-sell") || req_id.contains("-s-") || req_id.starts_with("s-") { Some("Sell") }
-                                                                                         else { None };
-                                                                              
-                                                                              if let Some(s) = side_to_reset {
-                                                                                  eprintln!("HOT: RECOVERY -> Resetting {} state (Code: {})", s, ret_code);
-                                                                                  strategy.reset_order(s);
-                                                                              }
-                                                                         }
-                                                                      }
-                                                                      
-                                                                      // CRITICAL ERROR HANDLING for POSITION LOOP
-                                                                      // 110017: ReduceOnly failed because pos is 0
-                                                                      // 10404: Params error (often related to invalid qty/price on close)
-                                                                      // 10006: Rate Limit (STOP EVERYTHING)
-                                                                      if ret_code == 110017 || ret_code == 10404 {
-                                                                          eprintln!("STRATEGY: >>> CRITICAL POSITION SYNC ERROR (Code: {}). Forcing Position = 0.", ret_code);
-                                                                          strategy.sync_position(0.0, 0.0);
-                                                                          strategy.has_active_buy = false;
-                                                                          strategy.has_active_sell = false;
-                                                                      }
-                                                                      if ret_code == 10006 {
-                                                                           eprintln!("STRATEGY: >>> API RATE LIMIT EXCEEDED! SLEEPING 10s...");
-                                                                           thread::sleep(Duration::from_secs(10));
-                                                                      }
-                                                                 }
-                                                             }
-                                                             // Check for Execution
-                                                             if let Some(topic) = json.get("topic").and_then(|v| v.as_str()) {
-                                                                  if topic == "execution" {
-                                                                      // Filled?
-                                                                      last_fill_ts = Some(Instant::now());
-                                                                      
-                                                                      // Parse Execution Data
-                                                                      if let Some(data_arr) = json.get("data").and_then(|v| v.as_array()) {
-                                                                          for item in data_arr {
-                                                                              // Check for Exec Type
-                                                                              let exec_type = item.get("execType").and_then(|v| v.as_str()).unwrap_or("");
-                                                                              let order_status = item.get("orderStatus").and_then(|v| v.as_str()).unwrap_or("");
-                                                                              let side = item.get("side").and_then(|v| v.as_str()).unwrap_or("");
-                                                                              
-                                                                              if exec_type == "Trade" {
-                                                                                   let qty = item.get("execQty").and_then(|v| v.as_str()).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-                                                                                   let px = item.get("execPrice").and_then(|v| v.as_str()).unwrap_or("0").parse::<f64>().unwrap_or(0.0);
-                                                                                   println!("\n[EXECUTION] Trade Filled!"); // Always print executions
-                                                                                   strategy.on_fill(side, qty, px);
-                                                                                   // Use Auto-Liquidation State
-                                                                                   last_fill_ts = Some(Instant::now());
-                                                                              } else if order_status == "Cancelled" || order_status == "Rejected" || order_status == "Deactivated" {
-                                                                                   println!("\n[EXECUTION] Order Cancelled/Rejected! Side: {}", side); // Always print cancellations
-                                                                                   strategy.on_order_cancel(side);
-                                                                              }
-                                                                          }
-                                                                      }
-                                                                  }
-                                                             }
-                                                             
-                                                             // Check for Position Update (Sync State)
-                                                             if let Some(topic) = json.get("topic").and_then(|v| v.as_str()) {
-                                                                 if topic == "position" {
-                                                                     if let Some(data_arr) = json.get("data").and_then(|v| v.as_array()) {
-                                                                         info!("HOT: Received Position Update! Count: {}", data_arr.len());
-                                                                         for pos in data_arr {
-                                                                             let symbol = pos.get("symbol").and_then(|v| v.as_str()).unwrap_or("UNKNOWN");
-                                                                             let side_str = pos.get("side").and_then(|v| v.as_str()).unwrap_or("None");
-                                                                             let size_str = pos.get("size").and_then(|v| v.as_str()).unwrap_or("0");
-                                                                             let idx = pos.get("positionIdx").and_then(|v| v.as_u64()).unwrap_or(0);
-                                                                             
-                                                                             // info!("HOT: Pos Item -> Sym: {}, Side: {}, Size: {}, Idx: {}", symbol, side_str, size_str, idx);
-
-                                                                             if symbol == "RIVERUSDT" {
-                                                                                 let entry_price_str = pos.get("avgPrice").and_then(|v| v.as_str()).unwrap_or("0");
-                                                                                 let size = size_str.parse::<f64>().unwrap_or(0.0);
-                                                                                 let entry_price = entry_price_str.parse::<f64>().unwrap_or(0.0);
-                                                                                 
-                                                                                 let signed_qty = if side_str == "Buy" { size } else if side_str == "Sell" { -size } else { 0.0 };
-                                                                                 
-                                                                                 strategy.sync_position(signed_qty, entry_price);
-                                                                             }
-                                                                         }
-                                                                     }
-                                                                 }
-                                                             }
-                                                             // Check for auth success
-                                                             if let Some(op) = json.get("op").and_then(|v| v.as_str()) {
-                                                                 if op == "auth" {
-                                                                     let is_success = json.get("success").and_then(|v| v.as_bool()).unwrap_or(false) 
-                                                                         || json.get("retCode").and_then(|v| v.as_i64()) == Some(0);
-                                                                     
-                                                                     if is_success {
-                                                                         priv_authenticated = true;
-                                                                         request_priv_sub = true;
-                                                                         info!("HOT: Private WS AUTHENTICATED!");
-                                                                     }
-                                                                 }
-                                                             }
-                                                        }
-                                                    }
-                                                    current_pos += consumed;
-                                                },
-                                                Ok(None) => break,
-                                                Err(_) => break,
-                                            }
-                                        }
-                                        if current_pos < end {
-                                             priv_buf.copy_within(current_pos..end, 0);
-                                             priv_offset = end - current_pos;
-                                        } else { priv_offset = 0; }
+                        user.on_readable();
+                    }
+                    user.next_events(&mut |event| match event {
+                        AdapterEvent::Message(message) => {
+                            let parsed = binance::parse_user_message(message, |event| match event {
+                                BinanceUserEvent::Order { symbol, client_id, trade_id, fill: Some(fill), .. } => {
+                                    info!(orders, "HOT: Binance execution {} {} {} @ {} (client id {}, fee {}, maker {})",
+                                        symbol, fill.side, fill.qty, fill.px, client_id, fill.fee, fill.is_maker);
+                                    drop_copy.publish(DropCopyEvent::execution(
+                                        fill.side, fill.px, fill.qty, client_id, &trade_id.to_string(), fill.exec_time_ms,
+                                    ).with_fee(fill.fee, fill.is_maker));
+                                    binance_fills.on_fill(&fill);
+                                    binance_fills.report_as("BINANCE FILLS");
+                                }
+                                BinanceUserEvent::Order { symbol, client_id, exec_type, status, .. } => {
+                                    debug!(orders, "HOT: Binance order {} {} {} -> {}", symbol, client_id, exec_type, status);
+                                }
+                                BinanceUserEvent::Balance { asset, wallet, cross_wallet } => {
+                                    info!(risk, "HOT: Binance balance {} wallet {} (cross {})", asset, wallet, cross_wallet);
+                                }
+                                BinanceUserEvent::Position { symbol, position_side, amount, entry_price, unrealized_pnl } => {
+                                    info!(risk, "HOT: Binance position {} {} {} @ {} (uPnL {})", symbol, position_side, amount, entry_price, unrealized_pnl);
+                                }
+                                BinanceUserEvent::ListenKeyExpired => {
+                                    eprintln!("HOT: Binance listenKey expired, renewing");
+                                    if key_renew_producer.push(()).is_err() {
+                                        eprintln!("HOT: listenKey renew ring full, request LOST");
                                     }
-                                    _ => { priv_offset = 0; }
                                 }
+                            });
+                            if let Err(e) = parsed {
+                                debug!(net, "HOT: Binance user data parse error: {:?}", e);
+                            }
+                        }
+                        AdapterEvent::Down(reason) => {
+                            eprintln!("HOT: Binance user data WS down ({:?}), renewing the listenKey", reason);
+                            if key_renew_producer.push(()).is_err() {
+                                eprintln!("HOT: listenKey renew ring full, request LOST");
                             }
-                            Ok(_) => {},
-                            Err(_) => {},
                         }
+                        _ => {}
+                    });
+                }
 
-                        if request_priv_sub {
-                            request_priv_sub = false;
-                            request_priv_sub = false;
-                            let exec_sub = r#"{"op": "subscribe", "args": ["execution","position"]}"#;
-                            info!("HOT: Sending Private Subscription: {}", exec_sub);
-                            let sub_len = framing::encode_text_frame(exec_sub.as_bytes(), &mut frame_buf);
-                            let _ = ws_private.tls.write_plaintext(&frame_buf[..sub_len]);
+                BYBIT_PRIVATE_TOKEN => {
+                    if event.is_writable() {
+                        private.on_writable();
+                    }
+                    if event.is_readable() {
+                        let n = private.on_readable();
+                        debug!(net, "HOT: Private WS Read {} bytes, state={:?}", n, private.state());
+                    }
+                    let tls_done = private.connection().client.tls.stats.handshake_done_ts;
+                    private.next_events(&mut |event| match event {
+                        AdapterEvent::HandshakeSent => {
+                            private_startup.mark(ConnectStage::Tcp, Instant::now());
+                        }
+                        AdapterEvent::Upgraded => {
+                            if let Some(ts) = tls_done {
+                                private_startup.mark(ConnectStage::Tls, ts);
+                            }
+                            private_startup.mark(ConnectStage::Upgrade, Instant::now());
+                        }
+                        AdapterEvent::AuthSent => metrics.egress(Conn::Private, EgressOp::Auth, Instant::now()),
+                        AdapterEvent::Subscribed | AdapterEvent::FrameError(_) => {}
+                        AdapterEvent::Down(_) => {
+                            session.on_disconnect(Conn::Private, Instant::now());
+                            outage.on_disconnect(Conn::Private);
+                        }
+                        AdapterEvent::Message(payload) => {
+                            session.on_message(Conn::Private);
+                            outage.on_message(Conn::Private);
+                            feed_watch.on_message(Conn::Private);
+                            // LOG ALL PRIVATE RESPONSES
+                            match std::str::from_utf8(payload) {
+                                Ok(s) => debug!(parser, "HOT: Private RAW: {}", s),
+                                Err(_) => debug!(parser, "HOT: Private RAW (hex): {:02x?}", &payload[..payload.len().min(50)]),
+                            }
+                            if let Ok(json) = simd_json::to_borrowed_value(payload) {                                                             // Check for Error (retCode != 0)
+                                 let response = private_stream::Response::of(&json);
+                                 if let Some(ret_code) = response.error() {
+                                     eprintln!("CRITICAL BYBIT ERROR: {:?}", json);
+                                     let _ = err_producer.push(RetCodeEvent::new(watchdog::now_us() / 1000, ret_code, ErrorSource::Private, response.op, response.ret_msg));
+                                     // RECOVERY: failed orders get a fresh id, "no position" closes flatten,
+                                     // a rate limit stops everything for 10s
+                                     let recovery = private_stream::on_private_error(&mut books, &response);
+                                     if let Some((slot, side)) = recovery.reset {
+                                         eprintln!("HOT: RECOVERY -> Resetting {} {} state (Code: {})", books.slots[slot].symbol, side, ret_code);
+                                     }
+                                     if let Some(slot) = recovery.flattened {
+                                         eprintln!("STRATEGY: >>> CRITICAL POSITION SYNC ERROR on {} (Code: {}). Forcing Position = 0.", books.slots[slot].symbol, ret_code);
+                                     }
+                                     if let Some((slot, trip)) = private_stream::on_reject(&mut books, &response, Instant::now()) {
+                                         eprintln!("HOT: [THROTTLE] {} {} reject loop, side benched for {:?} (level {}): {:?}", books.slots[slot].symbol, trip.side, trip.cooldown, trip.level, trip.kind);
+                                     }
+                                     if recovery.rate_limited {
+                                         eprintln!("STRATEGY: >>> API RATE LIMIT EXCEEDED! SLEEPING 10s...");
+                                         watchdog::pause(&HOT_HEARTBEAT_US, Duration::from_secs(10));
+                                         thread::sleep(Duration::from_secs(10));
+                                     }
+                                 }
+                                 // Check for Execution
+                                 if let Some(topic) = json.get("topic").and_then(|v| v.as_str()) {
+                                      if topic == "execution" {
+                                          // Filled?
+                                          last_fill_ts = Some(Instant::now());
+
+                                          // Parse Execution Data
+                                          if let Some(data_arr) = json.get("data").and_then(|v| v.as_array()) {
+                                              let mut fee_tier_changed = false;
+                                              for item in data_arr {
+                                                  // Executions on a symbol we don't quote (manual trades) stay out of every strategy
+                                                  let exec_symbol = item.get("symbol").and_then(|v| v.as_str()).unwrap_or("");
+                                                  let Some(slot) = books.slot_of(exec_symbol) else {
+                                                      info!(orders, "HOT: Execution on {} ignored (not quoted)", exec_symbol);
+                                                      continue;
+                                                  };
+                                                  let instrument = books.slots[slot].instrument;
+                                                  let slot_state = &mut books.slots[slot];
+                                                  // Check for Exec Type
+                                                  let exec_type = item.get("execType").and_then(|v| v.as_str()).unwrap_or("");
+                                                  let order_status = item.get("orderStatus").and_then(|v| v.as_str()).unwrap_or("");
+                                                  let side = item.get("side").and_then(|v| v.as_str()).unwrap_or("");
+
+                                                  if exec_type == "Trade" {
+                                                       let Some(mut fill) = Fill::from_execution(item) else {
+                                                           eprintln!("HOT: Execution with unknown side ignored: {:?}", item);
+                                                           continue;
+                                                       };
+                                                       let (qty, px) = (fill.qty, fill.px);
+                                                       println!("\n[EXECUTION] Trade Filled!"); // Always print executions
+                                                       // DROP COPY: exchange's fields only, before the strategy touches anything
+                                                       drop_copy.publish(DropCopyEvent::execution(
+                                                           side, px, qty,
+                                                           item.get("orderLinkId").and_then(|v| v.as_str()).unwrap_or(""),
+                                                           item.get("execId").and_then(|v| v.as_str()).unwrap_or(""),
+                                                           fill.exec_time_ms,
+                                                       ).with_fee(fill.fee, fill.is_maker));
+                                                       // Strategy, PnL and fee volume count base coin (inverse execQty is USD contracts)
+                                                       fill.qty = instrument.base_qty(fill.qty, fill.px);
+                                                       let qty = fill.qty;
+                                                       if let Some(trigger) = incidents.adverse_fill(fill.side, px, qty, &slot_state.book) {
+                                                           incidents.capture(trigger, &slot_state.book, &slot_state.strategy, &slot_state.order_manager, &drop_copy);
+                                                       }
+                                                       let link_id = item.get("orderLinkId").and_then(|v| v.as_str()).unwrap_or("");
+                                                       // Position before the fill: flat or on the fill's side means it opens
+                                                       let position = slot_state.strategy.position;
+                                                       let opening = position == 0.0 || (position > 0.0) == (fill.side == "Buy");
+                                                       private_stream::on_fill(slot_state, &fill, link_id, order_status, Instant::now());
+                                                       // HEDGE: the opposite side on the venue the router picks, off the Hot Thread;
+                                                       // the router's own Bybit unwinds are not hedged again
+                                                       if let Some(ratio) = hedge_ratio.filter(|_| !link_id.starts_with(HEDGE_UNWIND_TAG)) {
+                                                           let plan = slot_state.router.plan_hedge(fill.side, qty, ratio, opening);
+                                                           if plan.binance > 0.0 {
+                                                               let hedge = HedgeRequest { side: plan.side, qty: plan.binance, source: LinkId::new(link_id) };
+                                                               if hedge_producer.push((slot, hedge)).is_err() {
+                                                                   eprintln!("HOT: Hedge ring full, {:?} LOST", hedge);
+                                                               }
+                                                           }
+                                                           if plan.bybit > 0.0 {
+                                                               hedge_unwind_seq += 1;
+                                                               let unwind = RestRequest::HedgeUnwind {
+                                                                   qty: instrument.order_qty(plan.bybit, px),
+                                                                   side: plan.side,
+                                                                   link_id: LinkId::new(&format!("{}{}-{}", HEDGE_UNWIND_TAG, wall_ms(), hedge_unwind_seq)),
+                                                               };
+                                                               info!(orders, "HOT: [ROUTER] Hedge of {} {} {} stays on Bybit: {:?}", fill.side, qty, link_id, unwind);
+                                                               if rest_producer.push((slot, unwind)).is_err() {
+                                                                   eprintln!("HOT: REST ring full, {:?} LOST", unwind);
+                                                               }
+                                                           }
+                                                       }
+                                                       fill_stats.on_fill(&fill);
+                                                       fill_stats.report();
+                                                       // Experiment and statement audit cover the primary symbol only
+                                                       if slot == 0 {
+                                                           experiment.on_fill(&fill);
+                                                           pnl_audit.on_fill(&fill);
+                                                           if let Some(shadow) = shadow.as_mut() {
+                                                               shadow.on_live_fill(&fill);
+                                                           }
+                                                       }
+                                                       if fill.is_maker {
+                                                           // Tag by how the quote was priced (touch vs wall front-run) for markouts
+                                                           slot_state.markout.on_fill(side, px, slot_state.strategy.quote_origin(side), Instant::now());
+                                                           let now_ms = std::time::SystemTime::now()
+                                                               .duration_since(std::time::UNIX_EPOCH)
+                                                               .map(|d| d.as_millis() as u64)
+                                                               .unwrap_or(0);
+                                                           if let Some(tier) = fee_tracker.on_maker_fill(qty, px, now_ms) {
+                                                               println!("FEES: Maker fee tier changed -> {}", FEE_TIERS[tier].name);
+                                                               fee_tier_changed = true;
+                                                           }
+                                                           fee_tracker.report();
+                                                       }
+                                                       // Use Auto-Liquidation State
+                                                       last_fill_ts = Some(Instant::now());
+                                                  } else if private_stream::on_order_status(slot_state, side, order_status) {
+                                                       println!("\n[EXECUTION] Order Cancelled/Rejected! Side: {}", side); // Always print cancellations
+                                                  }
+                                              }
+                                              // Fee tier is account-wide: every symbol's spread floor follows it
+                                              if fee_tier_changed {
+                                                  for slot in books.slots.iter_mut() {
+                                                      slot.strategy.spread_floor_discount = fee_tracker.spread_floor_discount();
+                                                  }
+                                              }
+                                          }
+                                      }
+                                 }
+
+                                 // Wallet: available balance for the pre-trade margin check
+                                 if json.get("topic").and_then(|v| v.as_str()) == Some("wallet") {
+                                     let data = json.get("data").and_then(|v| v.as_array());
+                                     if let Some(available) = data.into_iter().flatten().find_map(|account| rest_client::coin_available(account, margin.coin)) {
+                                         debug!(risk, "HOT: Available balance {} {}", available, margin.coin);
+                                         margin.on_balance(available);
+                                     }
+                                 }
+                                 // Check for Position Update (Sync State)
+                                 if let Some(topic) = json.get("topic").and_then(|v| v.as_str()) {
+                                     if topic == "position" {
+                                         if let Some(data_arr) = json.get("data").and_then(|v| v.as_array()) {
+                                             info!(risk, "HOT: Received Position Update! Count: {}", data_arr.len());
+                                             for pos in data_arr {
+                                                 // Exchange size -> strategy position, leverage -> margin estimate
+                                                 private_stream::on_position(&mut books, pos);
+                                             }
+                                         }
+                                     }
+                                 }
+                                 // Check for auth success
+                                 if response.is_auth_ok() {
+                                     priv_authenticated = true;
+                                     private_startup.mark(ConnectStage::Auth, Instant::now());
+                                     session.on_connect(Conn::Private, Instant::now());
+                                     books.restart_warmup();
+                                     outage.on_connect(Conn::Private, Instant::now());
+                                     request_priv_sub = true;
+                                     info!(net, "HOT: Private WS AUTHENTICATED!");
+                                 } else if response.op == "subscribe" && response.success {
+                                     private_startup.mark(ConnectStage::Subscribe, Instant::now());
+                                 }
+                            }
+                        }
+                    });
+                    if request_priv_sub {
+                        request_priv_sub = false;
+                        if private.subscribe().is_ok() {
+                            metrics.egress(Conn::Private, EgressOp::Subscribe, Instant::now());
                         }
                     }
-                    
                 }
                 
                 BYBIT_TRADE_TOKEN => {
-                     if event.is_writable() {
-                         match trade_state {
-                            ConnectionState::HandshakeSending => {
-                                info!("HOT: Sending Trade Handshake...");
-                                if let Err(e) = ws_trade.send_handshake(trade_host, trade_path) {
-                                     eprintln!("Trade Handshake send error: {}", e);
-                                }
-                                trade_state = ConnectionState::HandshakeWaiting;
-                            }
-                            ConnectionState::Authenticating => {
-                                // Auth for Trade
-                                let expires = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() + 5000;
-                                let sign_payload = format!("GET/realtime{}", expires);
-                                signer.sign_message(sign_payload.as_bytes(), &mut signature_hex);
-                                let sig_str = std::str::from_utf8(&signature_hex[..64]).unwrap_or(""); 
-                                
-                                let auth_msg = format!(r#"{{"op":"auth","args":["{}","{}","{}"]}}"#, api_key, expires, sig_str);
-                                info!("HOT: Authenticating Trade WS...");
-                                let auth_len = framing::encode_text_frame(auth_msg.as_bytes(), &mut frame_buf);
-                                let _ = ws_trade.tls.write_plaintext(&frame_buf[..auth_len]);
-                                
-                                trade_state = ConnectionState::Active; 
+                    if event.is_writable() {
+                        trade.on_writable();
+                        if let Some(flushed) = trade.connection().client.tls.flushed_at() {
+                            for slot in books.slots.iter_mut() {
+                                slot.order_manager.mark_egress(flushed); // frames that hit a full socket buffer
                             }
-                            _ => {}
                         }
-                        let _ = ws_trade.write_tls();
                     }
+                    let read = if event.is_readable() { trade.on_readable() } else { 0 };
+                    let tls_done = trade.connection().client.tls.stats.handshake_done_ts;
+                    trade.next_events(&mut |event| match event {
+                        AdapterEvent::HandshakeSent => {
+                            trade_startup.mark(ConnectStage::Tcp, Instant::now());
+                        }
+                        AdapterEvent::Upgraded => {
+                            if let Some(ts) = tls_done {
+                                trade_startup.mark(ConnectStage::Tls, ts);
+                            }
+                            trade_startup.mark(ConnectStage::Upgrade, Instant::now());
+                        }
+                        AdapterEvent::AuthSent => metrics.egress(Conn::Trade, EgressOp::Auth, Instant::now()),
+                        AdapterEvent::Subscribed | AdapterEvent::FrameError(_) => {}
+                        AdapterEvent::Down(_) => {
+                            if !trade_ws_down {
+                                eprintln!("HOT: Trade WS down. Risk pulls switch to REST.");
+                            }
+                            trade_ws_down = true;
+                            session.on_disconnect(Conn::Trade, Instant::now());
+                            outage.on_disconnect(Conn::Trade);
+                        }
+                        AdapterEvent::Message(payload) => {
+                            session.on_message(Conn::Trade);
+                            outage.on_message(Conn::Trade);
+                            feed_watch.on_message(Conn::Trade);
+                            // Clone string BEFORE mutable borrow by simd_json
+                            let payload_str = std::str::from_utf8(payload).unwrap_or("invalid utf8").to_string();
+                            debug!(parser, "HOT: Trade RAW: {}", payload_str);
 
-                    if event.is_readable() {
-                        if trade_offset >= trade_buf.len() { trade_offset = 0; }
-                        match ws_trade.read(&mut trade_buf[trade_offset..]) {
-                            Ok(n) if n > 0 => {
-                                let end = trade_offset + n;
-                                match trade_state {
-                                    ConnectionState::HandshakeWaiting => {
-                                        if let Ok(s) = std::str::from_utf8(&trade_buf[..end]) {
-                                            if s.contains("101 Switching Protocols") {
-                                                info!("HOT: Trade Switch Proto!");
-                                                trade_state = ConnectionState::Authenticating; 
-                                                trade_offset = 0;
-                                            } else { trade_offset = end; }
-                                        }
-                                    }
-                                    ConnectionState::Active => {
-                                        let mut current_pos = 0;
-                                        loop {
-                                            let slice = &mut trade_buf[current_pos..end];
-                                            let decode_result = framing::decode_frame(slice);
-                                            match decode_result {
-                                                Ok(Some((consumed, payload))) => {
-                                                    if !payload.is_empty() {
-                                                        // Clone string BEFORE mutable borrow by simd_json
-                                                        let payload_str = std::str::from_utf8(payload).unwrap_or("invalid utf8").to_string();
-                                                        // info!("HOT: Trade RAW: {}", payload_str);
-                                                        
-                                                         if let Ok(json) = simd_json::to_borrowed_value(payload) {
-                                                             
-                                                             // 0. Update Time Offset from Header
-                                                             if let Some(header) = json.get("header").and_then(|v| v.as_object()) {
-                                                                 if let Some(timenow_val) = header.get("Timenow") {
-                                                                      // Timenow can be string or int? Docs show string "167..."
-                                                                      let server_time_opt = if let Some(s) = timenow_val.as_str() {
-                                                                          s.parse::<u64>().ok()
-                                                                      } else {
-                                                                           timenow_val.as_u64()
-                                                                      };
-
-                                                                      if let Some(server_time) = server_time_opt {
-                                                                          let local = std::time::SystemTime::now()
-                                                                                .duration_since(std::time::UNIX_EPOCH)
-                                                                                .unwrap_or_default()
-                                                                                .as_millis() as i64;
-                                                                          
-                                                                          // Calculate drift
-                                                                          // If Server=100, Local=105, Offset = -5.
-                                                                          let drift = (server_time as i64) - local;
-                                                                          
-                                                                          // Smooth update or first set? Let's just set it for now.
-                                                                          // But maybe keep the MOST negative drift (furthest back) to be safe?
-                                                                          // Actually, simple setting is usually fine for <1 sec latency.
-                                                                          // To be safer, we can subtract an extra 500ms from the offset to be "slightly in past"
-                                                                          if !offset_initialized {
-                                                                               time_offset = drift - 500; 
-                                                                               offset_initialized = true;
-                                                                               info!("HOT: Time Sync Initialized! Offset: {} ms", time_offset);
-                                                                          } else {
-                                                                               // Slowly adjust? Or ignore?
-                                                                               // Let's ignore subsequent updates to avoid jitter unless huge deviation
-                                                                               if (time_offset - drift).abs() > 1000 {
-                                                                                    info!("HOT: Time Drift Detected! Old: {}, New: {}. Resyncing.", time_offset, drift);
-                                                                                    time_offset = drift - 500;
-                                                                               }
-                                                                          }
-                                                                      }
-                                                                 }
-                                                             }
-
-                                                             // 1. Check for Auth
-                                                             if let Some(op) = json.get("op").and_then(|v| v.as_str()) {
-                                                                 if op == "auth" {
-                                                                     let is_success = json.get("success").and_then(|v| v.as_bool()).unwrap_or(false) 
-                                                                         || json.get("retCode").and_then(|v| v.as_i64()) == Some(0);
-                                                                     if is_success {
-                                                                         trade_authenticated = true;
-                                                                         info!("========================================");
-                                                                         info!("HOT: Trade WS AUTHENTICATED!");
-                                                                         info!("========================================");
-                                                                     }
-                                                                 }
-                                                             }
-
-                                                             // 2. Check for Trade Errors
-                                                             if let Some(ret_code) = json.get("retCode").and_then(|v| v.as_i64()) {
-                                                                 if ret_code != 0 {
-                                                                      let ret_msg = json.get("retMsg").and_then(|v| v.as_str()).unwrap_or("");
-                                                                      println!("HOT: Trade Error Code: {}, Msg: {}", ret_code, ret_msg);
-                                                                      
-                                                                      // A. Position is Zero (110017) -> Stop Closing Loop
-                                                                      if ret_code == 110017 {
-                                                                          info!("HOT: Trade -> Position already closed (110017). Syncing to 0.");
-                                                                          strategy.sync_position(0.0, 0.0);
-                                                                          // Also reset flags just in case
-                                                                          strategy.has_active_buy = false;
-                                                                          strategy.has_active_sell = false;
-                                                                      }
-                                                                      // B. Order Not Found (110001) -> Reset Order State
-                                                                      else if ret_code == 110001 {
-                                                                           if let Some(req_id) = json.get("reqId").and_then(|v| v.as_str()) {
-                                                                               let side_to_reset = if req_id.contains("bot-buy") || req_id.contains("-b-") || req_id.starts_with("b-") { Some("Buy") } 
-                                                                                          else if req_id.contains("bot-sell") || req_id.contains("-s-") || req_id.starts_with("s-") { Some("Sell") }
-                                                                                          else { None };
-                                                                               
-                                                                               if let Some(s) = side_to_reset {
-                                                                                   info!("HOT: Trade -> Order Lost/Late (110001). Resetting {} state.", s);
-                                                                                   strategy.reset_order(s);
-                                                                               }
-                                                                           }
-                                                                      }
-                                                                 }
-                                                             }
-                                                        }
-                                                    }
-                                                    current_pos += consumed;
-                                                },
-                                                Ok(None) => break,
-                                                Err(_) => break,
-                                            }
-                                        }
-                                        if current_pos < end {
-                                             trade_buf.copy_within(current_pos..end, 0);
-                                             trade_offset = end - current_pos;
-                                        } else { trade_offset = 0; }
-                                    }
-                                    _ => { trade_offset = 0; }
-                                }
+                             if let Ok(json) = simd_json::to_borrowed_value(payload) {
+                                 // Every response to one of our requests carries its reqId
+                                 let response = private_stream::Response::of(&json);
+                                 if json.get("reqId").is_some() {
+                                     trade_pipeline.on_ack();
+                                     // PENDING AMEND: confirmed on success, rolled back on a reject
+                                     // (110001 "gone" is reset further down)
+                                     private_stream::on_trade_response(&mut books, &response, Instant::now());
+                                 }
+
+                                 // 0. Update Time Offset from Header
+                                 if let Some(header) = json.get("header").and_then(|v| v.as_object()) {
+                                     if let Some(timenow_val) = header.get("Timenow") {
+                                          // Timenow can be string or int? Docs show string "167..."
+                                          let server_time_opt = if let Some(s) = timenow_val.as_str() {
+                                              s.parse::<u64>().ok()
+                                          } else {
+                                               timenow_val.as_u64()
+                                          };
+
+                                          if let Some(server_time) = server_time_opt {
+                                              let local = wall_ms();
+
+                                              // Offset = Server - Local (Server=100, Local=105 -> -5); small jitter is ignored
+                                              let previous = time_sync.offset_ms();
+                                              if let Some(offset) = time_sync.on_server_time(server_time, local) {
+                                                  match previous {
+                                                      None => info!(net, "HOT: Time Sync Initialized! Offset: {} ms (margin {} ms)", offset, time_sync.config.synced_margin_ms),
+                                                      Some(old) => info!(net, "HOT: Time Drift Detected! Old: {}, New: {}. Resyncing.", old, offset),
+                                                  }
+                                              }
+                                          }
+                                     }
+                                 }
+
+                                 // 1. Check for Auth
+                                 if response.is_auth_ok() {
+                                     trade_authenticated = true;
+                                     trade_ws_down = false; // (re)connected: orders back on the WS
+                                     trade_startup.mark(ConnectStage::Auth, Instant::now());
+                                     session.on_connect(Conn::Trade, Instant::now());
+                                     books.restart_warmup();
+                                     outage.on_connect(Conn::Trade, Instant::now());
+                                     info!(net, "========================================");
+                                     info!(net, "HOT: Trade WS AUTHENTICATED!");
+                                     info!(net, "========================================");
+                                 }
+
+                                 // 2. Check for Trade Errors
+                                 if let Some(ret_code) = response.error() {
+                                     println!("HOT: Trade Error Code: {}, Msg: {}", ret_code, response.ret_msg);
+                                     let _ = err_producer.push(RetCodeEvent::new(watchdog::now_us() / 1000, ret_code, ErrorSource::Trade, response.op, response.ret_msg));
+                                     if SERVER_ERROR_CODES.contains(&ret_code) {
+                                         outage.on_server_error();
+                                     }
+                                     // 110017: position already closed -> stop the closing loop; 110001: order lost/late
+                                     let recovery = private_stream::on_trade_error(&mut books, &response);
+                                     if let Some(slot) = recovery.flattened {
+                                         info!(orders, "HOT: Trade -> {} position already closed (110017). Syncing to 0.", books.slots[slot].symbol);
+                                     }
+                                     if let Some((slot, side)) = recovery.reset {
+                                         info!(orders, "HOT: Trade -> Order Lost/Late (110001). Resetting {} {} state.", books.slots[slot].symbol, side);
+                                     }
+                                     if let Some((slot, trip)) = private_stream::on_reject(&mut books, &response, Instant::now()) {
+                                         eprintln!("HOT: [THROTTLE] {} {} reject loop, side benched for {:?} (level {}): {:?}", books.slots[slot].symbol, trip.side, trip.cooldown, trip.level, trip.kind);
+                                     }
+                                 }
                             }
-                            Ok(_) => {},
-                            Err(_) => {},
                         }
+                    });
+                    if read > 0 && trade.state() == ConnectionState::Active {
+                        // Responses freed capacity -> release parked orders
+                        let released = trade_pipeline.drain(Instant::now(), |frame| trade.send_order(frame));
+                        metrics.orders_sent(released);
                     }
                 }
                 _ => {}
             }
         }
         
-        // Reregister Both (every loop might be heavy, but needed for TLS wants_write state?)
-        // Only if state changes ideally.
-        // For MVP, keep it simple.
-        let bybit_interest = if ws_client.tls.wants_write() || state == ConnectionState::HandshakeSending || state == ConnectionState::Subscribing {
-            mio::Interest::READABLE | mio::Interest::WRITABLE
-        } else {
-            mio::Interest::READABLE
-        };
-        poll.registry().reregister(ws_client.tls.socket(), BYBIT_TOKEN, bybit_interest).unwrap();
+        // Strategy diagnostics -> Cold Thread (already sampled by StrategyLog)
+        for slot in books.slots.iter_mut() {
+            slot.strategy.log.drain(|event| {
+                let _ = producer.push(LogMessage {
+                    timestamp: tick_count,
+                    msg_type: 30,
+                    bybit_bid: 0.0,
+                    bybit_ask: 0.0,
+                    binance_bid: 0.0,
+                    binance_ask: 0.0,
+                    latency: 0,
+                    trace_id: 0,
+                    strategy_event: Some(event),
+                    metrics: None,
+                    session: None,
+                });
+            });
+        }
 
-        /*
-        let bin_interest = if ws_binance.tls.wants_write() || !bin_handshake_done {
-            mio::Interest::READABLE | mio::Interest::WRITABLE
+        // Session accounting: in the market or sidelined (first matching reason wins)
+        let session_now = Instant::now();
+        // EXCHANGE OUTAGE: error spike / all connections reset or stale -> halt like maintenance
+        if LOCAL_NET_ALERT.load(std::sync::atomic::Ordering::Relaxed)
+            && LOCAL_NET_ALERT.swap(false, std::sync::atomic::Ordering::Relaxed) {
+            outage.on_local_network_alert();
+        }
+        match outage.poll(session_now) {
+            Some(OutageEvent::Halt(signal)) => eprintln!("ALERT: [OUTAGE] Exchange incident ({:?}) -> halting: flatten, no quotes (incidents: {})", signal, outage.incidents),
+            Some(OutageEvent::Resume) => eprintln!("ALERT: [OUTAGE] Exchange incident cleared after {:?} quiet -> resuming", outage.config.recovery),
+            None => {}
+        }
+        // BINANCE USER DATA: a listenKey from the BINANCE-KEY thread -> connect with it, unless
+        // the live connection already uses it
+        while let Ok(key) = listen_key_consumer.pop() {
+            let live = binance_user.as_ref().is_some_and(|user| user.state() != ConnectionState::Closed);
+            if live && key == binance_user_key {
+                continue;
+            }
+            let connected = binance::user_data_builder(binance_testnet, &key).connect_cached(&mut hot_endpoints, config.clone())
+                .map(BinanceUserData::new)
+                .and_then(|mut user| user.connection_mut().register(poll.registry(), BINANCE_USER_TOKEN).map(|()| user));
+            match connected {
+                Ok(user) => {
+                    binance_user = Some(user);
+                    binance_user_key = key;
+                }
+                Err(e) => {
+                    eprintln!("HOT: Binance user data connect failed: {}", e);
+                    if key_renew_producer.push(()).is_err() {
+                        eprintln!("HOT: listenKey renew ring full, request LOST");
+                    }
+                }
+            }
+        }
+        // DUAL FEED: one public connection silent while the other delivers -> reconnect only that
+        // one; the books keep moving on the other, so no quote pull and no book clear
+        let tripped = arbiter.as_mut().and_then(|arbiter| arbiter.poll(session_now).map(|trip| (arbiter, trip)));
+        if let Some((arbiter, (feed, silent))) = tripped {
+            eprintln!("ALERT: [FEED-STALE] {} public feed silent for {:?}, the other delivers -> reconnecting it (wins {:?}, duplicates {})",
+                feed.name(), silent, arbiter.wins, arbiter.duplicates);
+            let feed_conn = match feed {
+                Feed::Primary => Some(&mut public),
+                Feed::Backup => backup.as_mut(),
+            };
+            if let Some(feed_conn) = feed_conn {
+                if let Err(e) = feed_conn.connection_mut().reconnect(&mut hot_endpoints, poll.registry()) {
+                    eprintln!("HOT: {} WS reconnect failed: {}", feed_conn.connection().name(), e);
+                }
+            }
+            arbiter.on_down(feed);
+            arbiter.arm(feed, session_now); // a failed reconnect is retried after the same silence
+        }
+        // STALE FEED: pull quotes over REST (the silent WS may be Trade) and reconnect that connection
+        if let Some((conn, silent)) = feed_watch.poll(session_now) {
+            eprintln!("ALERT: [FEED-STALE] {} WS silent for {:?} -> pulling quotes, reconnecting (trips: {})",
+                conn.name(), silent, feed_watch.trips[conn as usize]);
+            for (i, slot) in books.slots.iter_mut().enumerate() {
+                if slot.strategy.pull_quotes().is_some() && rest_producer.push((i, RestRequest::CancelAll)).is_err() {
+                    eprintln!("HOT: REST fallback ring full, stale-feed CancelAll LOST");
+                }
+            }
+            session.on_disconnect(conn, session_now);
+            outage.on_disconnect(conn);
+            feed_watch.arm(conn, session_now); // a failed reconnect is retried after the same silence
+            match conn {
+                Conn::Public => {
+                    match public.connection_mut().reconnect(&mut hot_endpoints, poll.registry()) {
+                        Ok(()) => {
+                            // No quoting on the old book until the new snapshot lands
+                            for slot in &mut books.slots {
+                                slot.book.clear();
+                            }
+                        }
+                        Err(e) => eprintln!("HOT: Public WS reconnect failed: {}", e),
+                    }
+                    // Both feeds were silent: both start over
+                    if let (Some(backup), Some(arbiter)) = (backup.as_mut(), arbiter.as_mut()) {
+                        if let Err(e) = backup.connection_mut().reconnect(&mut hot_endpoints, poll.registry()) {
+                            eprintln!("HOT: Backup public WS reconnect failed: {}", e);
+                        }
+                        for feed in [Feed::Primary, Feed::Backup] {
+                            arbiter.on_down(feed);
+                            arbiter.arm(feed, session_now);
+                        }
+                        arbiter.reset();
+                    }
+                }
+                Conn::Private => match private.connection_mut().reconnect(&mut hot_endpoints, poll.registry()) {
+                    Ok(()) => request_priv_sub = false,
+                    Err(e) => eprintln!("HOT: Private WS reconnect failed: {}", e),
+                },
+                Conn::Trade => match trade.connection_mut().reconnect(&mut hot_endpoints, poll.registry()) {
+                    Ok(()) => {
+                        trade_authenticated = false;
+                        trade_ws_down = true;
+                    }
+                    Err(e) => eprintln!("HOT: Trade WS reconnect failed: {}", e),
+                },
+            }
+        }
+        match rest_quoting.update(trade_ws_down) {
+            Some(true) => eprintln!("ALERT: [REST-QUOTING] Quotes over REST ({:?}): one create/amend per side per {:?}, open orders polled every {:?}",
+                rest_quoting.config.mode, rest_quoting.config.interval, rest_quoting.config.poll),
+            Some(false) => eprintln!("ALERT: [REST-QUOTING] Trade WS back -> quotes over WS again ({} REST requests, {} paced)", rest_quoting.sent, rest_quoting.paced),
+            None => {}
+        }
+        let quote_state = if (!trade_authenticated || trade_ws_down) && !rest_quoting.active() {
+            QuoteState::Paused(PauseReason::TradeDown)
+        } else if !books.all_ready(MIN_BOOK_LEVELS) {
+            QuoteState::Paused(PauseReason::BookNotReady)
+        } else if MAINTENANCE_HALT.load(std::sync::atomic::Ordering::Relaxed) {
+            QuoteState::Paused(PauseReason::Maintenance)
+        } else if outage.halted() {
+            QuoteState::Paused(PauseReason::ExchangeIncident)
+        } else if REDUCE_ONLY.load(std::sync::atomic::Ordering::Relaxed) {
+            QuoteState::Paused(PauseReason::ReduceOnly)
+        } else if books.in_position() {
+            QuoteState::Paused(PauseReason::InPosition)
         } else {
-            mio::Interest::READABLE
+            QuoteState::Quoting
         };
-        poll.registry().reregister(ws_binance.tls.socket(), BINANCE_TOKEN, bin_interest).unwrap();
-        */
+        session.set_state(quote_state, session_now);
+        // Plain load first: the swap (a locked RMW) only runs when the admin asked
+        let stats_requested = STATS_REQUESTED.load(std::sync::atomic::Ordering::Relaxed)
+            && STATS_REQUESTED.swap(false, std::sync::atomic::Ordering::Relaxed);
+        if let Some(snapshot) = session.report_due(session_now, stats_requested) {
+            let _ = producer.push(LogMessage {
+                timestamp: tick_count,
+                msg_type: 41,
+                bybit_bid: 0.0,
+                bybit_ask: 0.0,
+                binance_bid: 0.0,
+                binance_ask: 0.0,
+                latency: 0,
+                trace_id: 0,
+                strategy_event: None,
+                metrics: None,
+                session: Some(snapshot),
+            });
+        }
 
-        let priv_interest = if ws_private.tls.wants_write() || priv_state != ConnectionState::Active {
-             mio::Interest::READABLE | mio::Interest::WRITABLE
-        } else {
-             mio::Interest::READABLE
-        };
-        poll.registry().reregister(ws_private.tls.socket(), BYBIT_PRIVATE_TOKEN, priv_interest).unwrap();
+        // Hot-thread counters -> Cold Thread (one snapshot per flush window)
+        let metrics_now = Instant::now();
+        if let Some(mut snapshot) = metrics.flush_due(metrics_now) {
+            snapshot.tls_backlog = metrics.tls_backlog([&public.connection().client.tls.stats, &private.connection().client.tls.stats, &trade.connection().client.tls.stats], metrics_now);
+            let _ = producer.push(LogMessage {
+                timestamp: tick_count,
+                msg_type: 40,
+                bybit_bid: 0.0,
+                bybit_ask: 0.0,
+                binance_bid: 0.0,
+                binance_ask: 0.0,
+                latency: 0,
+                trace_id: 0,
+                strategy_event: None,
+                metrics: Some(snapshot),
+                session: None,
+            });
+        }
 
-        let trade_interest = if ws_trade.tls.wants_write() || trade_state != ConnectionState::Active {
-             mio::Interest::READABLE | mio::Interest::WRITABLE
-        } else {
-             mio::Interest::READABLE
-        };
-        poll.registry().reregister(ws_trade.tls.socket(), BYBIT_TRADE_TOKEN, trade_interest).unwrap();
+        // Heartbeat invariant check: strategy flags vs OrderManager records.
+        // Divergence -> REST snapshot (REST thread), repaired when the reply lands.
+        let reconcile_now = Instant::now();
+        for (slot, SymbolSlot { strategy, order_manager, .. }) in books.slots.iter_mut().enumerate() {
+            if order_manager.reconcile_due(strategy.has_active_buy, strategy.has_active_sell, reconcile_now)
+                && rest_producer.push((slot, RestRequest::QueryOpenOrders)).is_err() {
+                eprintln!("HOT: REST ring full, reconcile query dropped");
+                order_manager.on_reconcile_failed();
+            }
+        }
+        // MM OBLIGATION: two-sided presence inside the program's band, per UTC hour / day
+        let uptime_ms = wall_ms();
+        for SymbolSlot { symbol, book, order_manager, obligation, .. } in books.slots.iter_mut() {
+            if !obligation.config.enabled {
+                continue;
+            }
+            let mid = (book.bids[0].price + book.asks[0].price) / 2.0;
+            let compliant = obligation.compliant(order_manager.record("Buy"), order_manager.record("Sell"), mid);
+            obligation.on_sample(compliant, uptime_ms);
+            while let Some(report) = obligation.take_report() {
+                println!("OBLIGATION {} {} @{}: uptime {:.2}%", symbol, report.period.name(), report.start_ms, report.uptime * 100.0);
+                if report.below {
+                    eprintln!("ALERT: [MM-UPTIME] {} {} uptime {:.2}% below the program's {:.0}%",
+                        symbol, report.period.name(), report.uptime * 100.0, obligation.config.min_uptime * 100.0);
+                }
+            }
+        }
+        // REST QUOTING: open orders polled for every symbol (no Trade WS acks to lean on)
+        if rest_quoting.poll_due(reconcile_now) {
+            for slot in 0..books.slots.len() {
+                if rest_producer.push((slot, RestRequest::PollOpenOrders { sent: reconcile_now })).is_err() {
+                    eprintln!("HOT: REST ring full, open-order poll dropped");
+                }
+            }
+        }
+        // Statement pull for the last settled audit window (or a retry), primary symbol
+        if let Some((start_ms, end_ms)) = pnl_audit.poll(wall_ms()) {
+            if rest_producer.push((0, RestRequest::QueryStatement { start_ms, end_ms })).is_err() {
+                eprintln!("HOT: REST ring full, statement query dropped");
+                pnl_audit.on_failed(wall_ms());
+            }
+        }
+        // Re-resolved endpoint addresses (Cold Thread) -> cache used by connect/reconnect
+        while let Ok(update) = dns_consumer.pop() {
+            hot_endpoints.apply(update);
+        }
+        while let Ok((slot, reply)) = reply_consumer.pop() {
+            if let RestReply::Order { op, link_id, code } = reply {
+                let (recovery, trip) = private_stream::on_rest_order(&mut books, op, link_id.as_str(), code, Instant::now());
+                if let Some((slot, side)) = recovery.reset {
+                    eprintln!("HOT: RECOVERY -> Resetting {} {} state after REST {} (Code: {})", books.slots[slot].symbol, side, op, code);
+                }
+                if let Some(slot) = recovery.flattened {
+                    eprintln!("STRATEGY: >>> CRITICAL POSITION SYNC ERROR on {} (REST code: {}). Forcing Position = 0.", books.slots[slot].symbol, code);
+                }
+                if let Some((slot, trip)) = trip {
+                    eprintln!("HOT: [THROTTLE] {} {} reject loop, side benched for {:?} (level {}): {:?}", books.slots[slot].symbol, trip.side, trip.cooldown, trip.level, trip.kind);
+                }
+                continue;
+            }
+            let Some(SymbolSlot { strategy, order_manager, .. }) = books.slots.get_mut(slot) else { continue };
+            match reply {
+                RestReply::OpenOrders(orders) => {
+                    let stale = order_manager.on_open_orders(&orders, strategy.has_active_buy, strategy.has_active_sell, reconcile_now);
+                    for side in stale.into_iter().flatten() {
+                        strategy.reset_order(side);
+                    }
+                }
+                RestReply::PolledOrders { orders, sent } => {
+                    let stale = order_manager.on_polled_orders(&orders, strategy.has_active_buy, strategy.has_active_sell, sent, reconcile_now);
+                    for side in stale.into_iter().flatten() {
+                        strategy.reset_order(side);
+                    }
+                }
+                RestReply::QueryFailed => order_manager.on_reconcile_failed(),
+                RestReply::Statement(statement) => {
+                    pnl_audit.on_statement(&statement);
+                }
+                RestReply::StatementFailed => pnl_audit.on_failed(wall_ms()),
+                RestReply::ServerError => outage.on_server_error(),
+                RestReply::Order { .. } => {}
+            }
+        }
+
+        // Keepalives (Active connections only; no-op for builders without auto_ping)
+        let ping_now = Instant::now();
+        if let Some(sent) = private.connection_mut().keepalive(ping_now) {
+            match sent {
+                Ok(()) => metrics.egress(Conn::Private, EgressOp::Ping, ping_now),
+                Err(e) => eprintln!("HOT: Private ping failed: {}", e),
+            }
+        }
+        if let Some(sent) = (!trade_ws_down).then(|| trade.connection_mut().keepalive(ping_now)).flatten() {
+            match sent {
+                Ok(()) => metrics.egress(Conn::Trade, EgressOp::Ping, ping_now),
+                Err(e) => eprintln!("HOT: Trade ping failed: {}", e),
+            }
+        }
+        // OKX closes a connection that sent nothing for 30s, however busy the books are
+        if let Some(Err(e)) = okx.as_mut().and_then(|okx| okx.connection_mut().keepalive(ping_now)) {
+            eprintln!("HOT: OKX ping failed: {}", e);
+        }
+
+        // Setup steps re-arm WRITABLE every pass; once Active, WRITABLE is held only while
+        // ciphertext is pending and reregistered on a change (no syscall per loop).
+        public.connection_mut().update_interest(poll.registry()).unwrap();
+        if let Some(backup) = backup.as_mut() {
+            backup.connection_mut().update_interest(poll.registry()).unwrap();
+        }
+        private.connection_mut().update_interest(poll.registry()).unwrap();
+        trade.connection_mut().update_interest(poll.registry()).unwrap();
+        if let Some(okx) = okx.as_mut() {
+            okx.connection_mut().update_interest(poll.registry()).unwrap();
+        }
+        if let Some(binance) = binance.as_mut() {
+            binance.connection_mut().update_interest(poll.registry()).unwrap();
+        }
+        if let Some(user) = binance_user.as_mut() {
+            user.connection_mut().update_interest(poll.registry()).unwrap();
+        }
+
+        // Lost responses must not stall the trade pipeline
+        if trade_pipeline.in_flight() > 0 || trade_pipeline.queued() > 0 {
+            let now = Instant::now();
+            trade_pipeline.expire(now);
+            // Parked too long: stale timestamp and price, rolled back instead of sent
+            while let Some(order) = trade_pipeline.pop_expired(now) {
+                eprintln!("HOT: [PIPELINE] Parked order expired, NOT sent: {:?} (total expired: {})", order.committed, trade_pipeline.expired);
+                let SymbolSlot { strategy, order_manager, send_gate, .. } = &mut books.slots[order.slot];
+                let route = on_order_dropped(order, strategy, order_manager, send_gate, &mut margin, &mut rest_producer);
+                drop_copy.publish_order(order.dc_order, route);
+            }
+            // Parked frames wait out a Trade WS reconnect (they would land in its handshake)
+            let released = if trade_authenticated {
+                trade_pipeline.drain(now, |frame| trade.send_order(frame))
+            } else {
+                0
+            };
+            metrics.orders_sent(released);
+        }
 
         tick_count = tick_count.wrapping_add(1);
     }
@@ -0,0 +1,382 @@
+use std::io;
+use std::time::Instant;
+use mio::{Registry, Token};
+use crate::core::logging::info;
+use crate::net::framing::{self, FrameAssembler, Opcode};
+use crate::net::resolver::EndpointCache;
+use crate::net::ws_client::WsClient;
+
+// One exchange WS connection as the Hot Thread's poll loop sees it. Every venue runs the same
+// steps: upgrade request on the first writable event, 101 on a readable one, then auth and/or
+// subscribe, then whole messages. `WsConnection` owns those steps (state, read buffer, frame
+// decoding, fragment assembly, server close / ping, reconnect); an `ExchangeAdapter` adds
+// what differs per venue: the auth / subscribe messages and its own request methods. The
+// loop only sees `on_writable` / `on_readable` for its token and the events they produce:
+//
+//     adapter.on_readable();
+//     adapter.next_events(&mut |event| match event { AdapterEvent::Message(payload) => ..., _ => ... });
+//
+// What a message *means* (book, execution, order ack) stays with the caller: the adapters
+// don't parse payloads, so the hot path is the same parse-in-place as before.
+
+/// Setup progress of one connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    HandshakeSending,
+    HandshakeWaiting,
+    Subscribing,
+    Authenticating,
+    Active,
+    /// Closed by the server (close frame answered), EOF, IO error or a rejected upgrade;
+    /// nothing more is read until `reconnect`.
+    Closed,
+}
+
+impl ConnectionState {
+    /// A setup step is driven from the next writable event.
+    pub fn wants_write(self) -> bool {
+        matches!(self, ConnectionState::HandshakeSending | ConnectionState::Subscribing | ConnectionState::Authenticating)
+    }
+}
+
+/// Why a connection went `Closed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disconnect {
+    /// Server close frame, with its status code.
+    ServerClose(Option<u16>),
+    Eof,
+    Io(io::ErrorKind),
+    UpgradeRejected,
+}
+
+/// What one connection did since the last `next_events`, in order.
+#[derive(Debug)]
+pub enum AdapterEvent<'a> {
+    /// TCP is up, upgrade request written.
+    HandshakeSent,
+    /// 101 accepted (the TLS handshake time is in the client's stats).
+    Upgraded,
+    /// Auth request written.
+    AuthSent,
+    /// Subscribe request written; the connection is `Active`.
+    Subscribed,
+    /// One whole text / binary message (fragments already joined), parsed in place by the caller.
+    Message(&'a mut [u8]),
+    /// Undecodable frame or fragment, already logged; the bytes are dropped.
+    FrameError(&'static str),
+    Down(Disconnect),
+}
+
+/// Setup notice queued by `on_writable` / `on_readable` until the next `next_events`.
+#[derive(Debug, Clone, Copy)]
+enum Notice {
+    HandshakeSent,
+    Upgraded,
+    AuthSent,
+    Subscribed,
+    Down(Disconnect),
+}
+
+impl Notice {
+    fn event(self) -> AdapterEvent<'static> {
+        match self {
+            Notice::HandshakeSent => AdapterEvent::HandshakeSent,
+            Notice::Upgraded => AdapterEvent::Upgraded,
+            Notice::AuthSent => AdapterEvent::AuthSent,
+            Notice::Subscribed => AdapterEvent::Subscribed,
+            Notice::Down(reason) => AdapterEvent::Down(reason),
+        }
+    }
+}
+
+/// The venue-independent part of a WS connection.
+pub struct WsConnection {
+    pub client: WsClient,
+    pub state: ConnectionState,
+    name: &'static str,
+    /// First step after the 101: `Authenticating` (private) or `Subscribing` (public).
+    after_upgrade: ConnectionState,
+    token: Token,
+    buf: Vec<u8>,
+    offset: usize,
+    /// Largest payload a single frame may declare and still fit `buf`.
+    max_payload: usize,
+    assembler: FrameAssembler,
+    notices: Vec<Notice>,
+}
+
+impl WsConnection {
+    /// `name` labels logs and TLS summaries ("public", "trade", "okx", ...).
+    pub fn new(client: WsClient, name: &'static str, after_upgrade: ConnectionState) -> Self {
+        let buf = vec![0u8; client.read_buffer_size()];
+        Self {
+            client,
+            state: ConnectionState::HandshakeSending,
+            name,
+            after_upgrade,
+            token: Token(usize::MAX),
+            max_payload: buf.len().saturating_sub(framing::MAX_HEADER_LEN),
+            buf,
+            offset: 0,
+            assembler: FrameAssembler::new(framing::DEFAULT_MAX_MESSAGE),
+            notices: Vec::with_capacity(4),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn register(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
+        self.token = token;
+        self.client.register(registry, token)
+    }
+
+    /// WRITABLE re-armed while a setup step waits for it, otherwise only while ciphertext is
+    /// pending (see `TlsClient::update_interest`).
+    pub fn update_interest(&mut self, registry: &Registry) -> io::Result<()> {
+        self.client.tls.update_interest(registry, self.token, self.state.wants_write())
+    }
+
+    /// New TCP/TLS connection to the endpoint's cached addresses, registered under the same
+    /// token; setup starts over. The old connection is dropped only once the new one exists.
+    /// One that never got through the upgrade moves the endpoint to its next address first.
+    pub fn reconnect(&mut self, cache: &mut EndpointCache, registry: &Registry) -> io::Result<()> {
+        if !self.client.handshake_complete {
+            self.client.connect_failed(cache);
+        }
+        let mut client = self.client.reconnect(cache)?;
+        client.register(registry, self.token)?;
+        self.client = client;
+        self.state = ConnectionState::HandshakeSending;
+        self.offset = 0;
+        self.assembler = FrameAssembler::new(framing::DEFAULT_MAX_MESSAGE);
+        self.notices.clear();
+        Ok(())
+    }
+
+    /// Masks and writes one text message (setup and control requests; orders go through
+    /// `write_frame` with a frame the caller already encoded).
+    pub fn send_text(&mut self, msg: &str) -> io::Result<()> {
+        let mut frame = vec![0u8; msg.len() + framing::MAX_HEADER_LEN + 4];
+        let len = framing::encode_text_frame(msg.as_bytes(), &mut frame);
+        self.client.tls.write_plaintext(&frame[..len])?;
+        self.client.write_tls()
+    }
+
+    /// Writes an encoded frame and flushes what the socket takes; the rest goes out on the
+    /// next writable event.
+    pub fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.client.tls.write_plaintext(frame)?;
+        self.client.write_tls()
+    }
+
+    /// Sends the builder's keepalive when the connection is `Active` and the interval has
+    /// passed. None when nothing was due.
+    pub fn keepalive(&mut self, now: Instant) -> Option<io::Result<()>> {
+        (self.state == ConnectionState::Active && self.client.ping_due(now)).then(|| self.client.send_ping(now))
+    }
+
+    fn close(&mut self, reason: Disconnect) {
+        if self.state != ConnectionState::Closed {
+            self.state = ConnectionState::Closed;
+            self.notices.push(Notice::Down(reason));
+        }
+        self.offset = 0;
+    }
+
+    /// Upgrade request, or the venue's setup message (None = nothing to send).
+    fn on_writable(&mut self, setup: Option<String>) {
+        self.client.is_connected = true;
+        match self.state {
+            ConnectionState::HandshakeSending => {
+                info!(net, "HOT: Sending {} Handshake...", self.name);
+                if let Err(e) = self.client.send_handshake() {
+                    eprintln!("{} Handshake send error: {}", self.name, e);
+                    self.close(Disconnect::Io(e.kind()));
+                    return;
+                }
+                self.state = ConnectionState::HandshakeWaiting;
+                self.notices.push(Notice::HandshakeSent);
+            }
+            step @ (ConnectionState::Authenticating | ConnectionState::Subscribing) => {
+                if let Some(msg) = setup {
+                    match self.send_text(&msg) {
+                        Ok(()) if step == ConnectionState::Authenticating => self.notices.push(Notice::AuthSent),
+                        Ok(()) => self.notices.push(Notice::Subscribed),
+                        Err(e) => {
+                            // Never Active without its auth / subscribe: down, the owner reconnects
+                            eprintln!("{} setup send error: {}", self.name, e);
+                            self.close(Disconnect::Io(e.kind()));
+                            return;
+                        }
+                    }
+                }
+                self.state = ConnectionState::Active;
+            }
+            _ => {}
+        }
+        if let Err(e) = self.client.write_tls() {
+            eprintln!("{} TLS Write Error: {}", self.name, e);
+        }
+    }
+
+    /// One read into the buffer; completes the upgrade when the 101 is in. Returns the
+    /// bytes read (0: nothing, EOF or an error, the last two queued as `Down`).
+    pub fn on_readable(&mut self) -> usize {
+        if self.offset >= self.buf.len() {
+            self.offset = 0; // Reset on overflow
+        }
+        let n = match self.client.read(&mut self.buf[self.offset..]) {
+            Ok(n) if n > 0 => n,
+            Ok(_) => {
+                if self.state != ConnectionState::Closed {
+                    eprintln!("HOT: {} WS closed by peer", self.name);
+                    self.client.tls.stats.summary(self.name);
+                }
+                self.close(Disconnect::Eof);
+                return 0;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return 0,
+            Err(e) => {
+                if self.state != ConnectionState::Closed {
+                    eprintln!("HOT: {} WS IO Error: {}", self.name, e);
+                    self.client.tls.stats.summary(self.name);
+                }
+                self.close(Disconnect::Io(e.kind()));
+                return 0;
+            }
+        };
+        let end = self.offset + n;
+        match self.state {
+            ConnectionState::HandshakeWaiting => match self.client.accept_upgrade(&self.buf[..end]) {
+                Ok(Some(head)) => {
+                    info!(net, "HOT: {} Upgraded!", self.name);
+                    self.client.tls.stats.summary(self.name);
+                    self.state = self.after_upgrade;
+                    self.notices.push(Notice::Upgraded);
+                    // Frames that came in the same read wait for the decoder
+                    self.buf.copy_within(head..end, 0);
+                    self.offset = end - head;
+                }
+                Ok(None) => self.offset = end,
+                Err(e) => {
+                    eprintln!("HOT: {} WS upgrade rejected: {}", self.name, e);
+                    self.close(Disconnect::UpgradeRejected);
+                }
+            },
+            // Early frames are kept until the connection is Active
+            ConnectionState::Subscribing | ConnectionState::Authenticating | ConnectionState::Active => self.offset = end,
+            _ => self.offset = 0,
+        }
+        n
+    }
+
+    /// Queued setup notices, then every whole message buffered (`Active` only). Server pings
+    /// are answered here; a server close is answered and ends the stream.
+    pub fn next_events(&mut self, on_event: &mut dyn FnMut(AdapterEvent<'_>)) {
+        let mut notices = std::mem::take(&mut self.notices);
+        for notice in notices.drain(..) {
+            on_event(notice.event());
+        }
+        self.notices = notices;
+        if self.state != ConnectionState::Active || self.offset == 0 {
+            return;
+        }
+        let end = self.offset;
+        let mut current_pos = 0;
+        let mut closed = None;
+        loop {
+            match framing::decode_frame_limited(&mut self.buf[current_pos..end], self.max_payload) {
+                Ok(Some((_, Opcode::Close, _, payload))) => {
+                    // Server close: answer it and treat the stream like an EOF
+                    let code = framing::close_code(payload);
+                    eprintln!("HOT: {} WS closed by server (code {:?})", self.name, code);
+                    closed = Some(code);
+                    current_pos = end;
+                    break;
+                }
+                Ok(Some((consumed, Opcode::Ping, _, payload))) => {
+                    let mut pong = [0u8; 136];
+                    let len = framing::encode_pong_frame(payload, &mut pong);
+                    if let Err(e) = self.client.tls.write_plaintext(&pong[..len]).and_then(|_| self.client.write_tls()) {
+                        eprintln!("HOT: {} pong failed: {}", self.name, e);
+                    }
+                    current_pos += consumed;
+                }
+                Ok(Some((consumed, opcode, fin, frame))) => {
+                    // Only whole messages reach the caller; fragments wait in the assembler
+                    match self.assembler.push(opcode, fin, frame) {
+                        Ok(Some(message)) => on_event(AdapterEvent::Message(message)),
+                        Ok(None) => {}
+                        Err(e) => {
+                            eprintln!("HOT: {} fragment error: {}", self.name, e);
+                            on_event(AdapterEvent::FrameError(e));
+                        }
+                    }
+                    current_pos += consumed;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    // Stream framing is lost: drop everything buffered instead of re-decoding the same bad header forever
+                    eprintln!("HOT: {} frame error: {}, dropping {} buffered bytes", self.name, e, end - current_pos);
+                    on_event(AdapterEvent::FrameError(e));
+                    current_pos = end;
+                    break;
+                }
+            }
+        }
+        if current_pos < end {
+            self.buf.copy_within(current_pos..end, 0);
+            self.offset = end - current_pos;
+        } else {
+            self.offset = 0;
+        }
+        if let Some(code) = closed {
+            let _ = self.client.send_close(code);
+            self.client.tls.stats.summary(self.name);
+            self.state = ConnectionState::Closed;
+            self.offset = 0;
+            on_event(AdapterEvent::Down(Disconnect::ServerClose(code)));
+        }
+    }
+}
+
+/// One venue's WS connection in the poll loop. Implementations supply the connection and
+/// their setup messages; the IO steps are shared.
+pub trait ExchangeAdapter {
+    fn connection(&self) -> &WsConnection;
+
+    fn connection_mut(&mut self) -> &mut WsConnection;
+
+    /// Message for the setup step `step` (`Authenticating` or `Subscribing`); None sends
+    /// nothing and the connection goes `Active` all the same. A message that fails to go out
+    /// takes the connection `Closed` (`Down`) instead.
+    fn setup_message(&mut self, step: ConnectionState) -> Option<String>;
+
+    fn state(&self) -> ConnectionState {
+        self.connection().state
+    }
+
+    /// Writable event: upgrade request or the next setup message.
+    fn on_writable(&mut self) {
+        let step = self.state();
+        let msg = if matches!(step, ConnectionState::Authenticating | ConnectionState::Subscribing) { self.setup_message(step) } else { None };
+        self.connection_mut().on_writable(msg);
+    }
+
+    /// Readable event: see `WsConnection::on_readable`.
+    fn on_readable(&mut self) -> usize {
+        self.connection_mut().on_readable()
+    }
+
+    /// Events since the last call: setup notices, then whole messages in arrival order.
+    fn next_events(&mut self, on_event: &mut dyn FnMut(AdapterEvent<'_>)) {
+        self.connection_mut().next_events(on_event)
+    }
+
+    /// Writes one encoded order / request frame.
+    fn send_order(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.connection_mut().write_frame(frame)
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use std::net::{Shutdown, TcpListener};
+    use crate::auth::signer::Signer;
+    use crate::core::time_sync::TimeSyncConfig;
+    use crate::net::adapter::{AdapterEvent, ConnectionState, Disconnect, ExchangeAdapter};
+    use crate::net::bybit::BybitAuth;
+    use crate::net::framing;
+    use crate::net::kraken::{self, KrakenBook};
+    use crate::net::tls_client::{self, CertPins};
+
+    // A connection to a local listener whose write side is shut: every send fails with EPIPE
+    fn broken_adapter() -> (KrakenBook, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = tls_client::client_config(CertPins::default()).unwrap();
+        let client = kraken::builder(false).connect_to(listener.local_addr().unwrap(), config).unwrap();
+        let _peer = listener.accept().unwrap();
+        client.tls.socket.shutdown(Shutdown::Write).unwrap();
+        (KrakenBook::new(client, &["PF_XBTUSD"]), listener)
+    }
+
+    fn downs(adapter: &mut KrakenBook) -> Vec<Disconnect> {
+        let mut downs = Vec::new();
+        adapter.next_events(&mut |event| {
+            if let AdapterEvent::Down(reason) = event {
+                downs.push(reason);
+            }
+        });
+        downs
+    }
+
+    #[test]
+    fn setup_steps_want_write() {
+        let setup = [ConnectionState::HandshakeSending, ConnectionState::Subscribing, ConnectionState::Authenticating];
+        assert!(setup.iter().all(|state| state.wants_write()));
+        // Waiting on the 101, or nothing left to send
+        for state in [ConnectionState::HandshakeWaiting, ConnectionState::Active, ConnectionState::Closed] {
+            assert!(!state.wants_write(), "{:?}", state);
+        }
+    }
+
+    #[test]
+    fn a_setup_message_that_cannot_go_out_takes_the_connection_down() {
+        let (mut adapter, _listener) = broken_adapter();
+        adapter.connection_mut().state = ConnectionState::Subscribing;
+        adapter.on_writable();
+        // Not Active without its subscription
+        assert_eq!(adapter.state(), ConnectionState::Closed);
+        assert_eq!(downs(&mut adapter), vec![Disconnect::Io(std::io::ErrorKind::BrokenPipe)]);
+
+        let (mut adapter, _listener) = broken_adapter();
+        adapter.on_writable();
+        assert_eq!(adapter.state(), ConnectionState::Closed);
+        assert_eq!(downs(&mut adapter).len(), 1);
+    }
+
+    #[test]
+    fn bybit_auth_signs_expires() {
+        let auth = BybitAuth::new("key", "bybit-test-secret", TimeSyncConfig::default());
+        let msg = auth.auth_message(1_700_000_000_000);
+        let args: Vec<&str> = msg
+            .strip_prefix(r#"{"op":"auth","args":[""#)
+            .and_then(|rest| rest.strip_suffix(r#""]}"#))
+            .unwrap()
+            .split(r#"",""#)
+            .collect();
+        assert_eq!(args[0], "key");
+        // 5s expiry on top of whatever offset the Trade WS has synced
+        let expires: u64 = args[1].parse().unwrap();
+        assert!(expires > 1_700_000_000_000 - 60_000 && expires < 1_700_000_000_000 + 60_000, "{}", expires);
+        let mut expected = [0u8; 64];
+        Signer::new("bybit-test-secret").sign_message(format!("GET/realtime{}", expires).as_bytes(), &mut expected);
+        assert_eq!(args[2].as_bytes(), &expected[..]);
+        assert!(!format!("{:?}", auth).contains("bybit-test-secret"));
+    }
+
+    #[test]
+    fn pong_echoes_the_ping_payload_masked() {
+        let mut out = [0u8; 32];
+        let n = framing::encode_pong_frame(b"hb", &mut out);
+        assert_eq!((n, out[0], out[1]), (2 + 4 + 2, 0x8A, 0x80 | 2));
+        let mask = [out[2], out[3], out[4], out[5]];
+        assert_eq!([out[6] ^ mask[0], out[7] ^ mask[1]], *b"hb");
+        assert_eq!(framing::encode_pong_frame(b"hb", &mut out[..7]), 0);
+    }
+}
@@ -0,0 +1,390 @@
+use std::convert::TryFrom;
+use std::io::{self, ErrorKind};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rustls::{ClientConfig, pki_types::ServerName};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::{client::TlsStream, TlsConnector};
+use crate::config::EngineConfig;
+use crate::core::book_check::BookSanityMonitor;
+use crate::core::clock::Clock;
+use crate::core::logging::{debug, info};
+use crate::core::orderbook::L2OrderBook;
+use crate::core::parser::{self, BookUpdate, MarketEvent};
+use crate::net::framing::{self, Assembled, FrameAssembler, Opcode};
+use crate::net::rest_client;
+use crate::net::tls_client;
+use crate::net::ws_client::{self, ReconnectPolicy, WsClientBuilder};
+use crate::strategy::fill::Fill;
+use crate::strategy::market_maker::{Action, MarketMaker};
+use crate::strategy::order_manager::OrderManager;
+use crate::strategy::paper::{self, PaperConfig, PaperExchange};
+use crate::strategy::router::Venue;
+
+// Tokio connection layer (feature `tokio-runtime`). Same builder options, framing, parser,
+// strategy and OMS as the mio Hot Thread; only the IO is async. Meant for deployments where
+// latency is not the point (paper trading, monitoring) and for integration tests that want
+// `#[tokio::test]` instead of a hand-rolled poll loop. Not for the latency-critical path:
+// the mio loop stays the production runtime.
+const OUT_FRAME_LEN: usize = 65536;
+
+/// One WebSocket over tokio + tokio-rustls. Owns its receive buffer; payloads are handed
+/// out as slices into it, so nothing is copied between the socket and the parser (only the
+/// pieces of a fragmented message are, into `assembler`).
+pub struct AsyncWsClient {
+    stream: TlsStream<TcpStream>,
+    buf: Vec<u8>,
+    // Unparsed bytes are buf[start..end]
+    start: usize,
+    end: usize,
+    // Frame handed out by the last `next_payload`, dropped on the next call
+    consumed: usize,
+    assembler: FrameAssembler,
+    out: Vec<u8>,
+    ping_interval: Option<Duration>,
+    ping_message: Option<&'static str>,
+    last_ping_ts: Instant,
+}
+
+impl AsyncWsClient {
+    /// TCP connect (async DNS), TLS handshake and WS upgrade; returns once the server sent 101.
+    pub async fn connect(options: &WsClientBuilder, config: Arc<ClientConfig>) -> io::Result<Self> {
+        let (host, port, server_name) = options.target();
+        let tcp = TcpStream::connect((host, port)).await?;
+        tcp.set_nodelay(true)?;
+        let server_name = ServerName::try_from(server_name)
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Invalid DNS name"))?
+            .to_owned();
+        let stream = TlsConnector::from(config).connect(server_name, tcp).await?;
+
+        let (ping_interval, ping_message) = options.ping_settings();
+        let mut client = Self {
+            stream,
+            buf: vec![0u8; options.buffer_len()],
+            start: 0,
+            end: 0,
+            consumed: 0,
+            assembler: FrameAssembler::new(framing::DEFAULT_MAX_MESSAGE),
+            out: vec![0u8; OUT_FRAME_LEN],
+            ping_interval,
+            ping_message,
+            last_ping_ts: Instant::now(),
+        };
+        let key = ws_client::new_key();
+        client.stream.write_all(options.handshake_request(&key).as_bytes()).await?;
+        client.stream.flush().await?;
+        client.read_upgrade(&key).await?;
+        info!(net, "ASYNC: WS connected to {}:{}", host, port);
+        Ok(client)
+    }
+
+    // Reads up to the end of the HTTP response; bytes after it are the first frames.
+    async fn read_upgrade(&mut self, key: &str) -> io::Result<()> {
+        loop {
+            match ws_client::parse_upgrade(&self.buf[..self.end], key) {
+                Ok(Some(len)) => {
+                    self.start = len;
+                    return Ok(());
+                }
+                Ok(None) if self.end < self.buf.len() => self.fill().await?,
+                Ok(None) => return Err(io::Error::new(ErrorKind::InvalidData, "WS upgrade response too large")),
+                Err(e) => return Err(io::Error::new(ErrorKind::ConnectionRefused, format!("WS upgrade rejected: {}", e))),
+            }
+        }
+    }
+
+    async fn fill(&mut self) -> io::Result<()> {
+        let n = self.stream.read(&mut self.buf[self.end..]).await?;
+        if n == 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "WS closed by peer"));
+        }
+        self.end += n;
+        Ok(())
+    }
+
+    /// Next message payload; control frames are skipped, fragments are reassembled. A server close is answered with
+    /// a close frame and ends the stream with `ConnectionAborted` (the caller reconnects).
+    /// The slice stays valid until the next call.
+    pub async fn next_payload(&mut self) -> io::Result<&mut [u8]> {
+        self.start += self.consumed;
+        self.consumed = 0;
+        loop {
+            // Frame boundaries first, so the borrow of `buf` ends before any read
+            let max_payload = self.buf.len() - framing::MAX_HEADER_LEN;
+            let frame = match framing::decode_frame_limited(&mut self.buf[self.start..self.end], max_payload) {
+                Ok(Some((len, Opcode::Close, _, payload))) => {
+                    let code = framing::close_code(payload);
+                    return self.on_close(len, code).await;
+                }
+                Ok(Some((len, opcode, fin, payload))) => Some((len, len - payload.len(), opcode, fin)),
+                Ok(None) => None,
+                Err(e) => return Err(io::Error::new(ErrorKind::InvalidData, e)),
+            };
+            if let Some((len, header, opcode, fin)) = frame {
+                let (from, to) = (self.start + header, self.start + len);
+                match self.assembler.feed(opcode, fin, &self.buf[from..to]) {
+                    Ok(Assembled::Whole) => {
+                        self.consumed = len;
+                        return Ok(&mut self.buf[from..to]);
+                    }
+                    Ok(Assembled::Message) => {
+                        self.start = to;
+                        return Ok(self.assembler.message());
+                    }
+                    Ok(Assembled::Fragment | Assembled::Control) => {
+                        self.start = to;
+                        continue;
+                    }
+                    Err(e) => return Err(io::Error::new(ErrorKind::InvalidData, e)),
+                }
+            }
+            // Partial frame: move it to the front when the tail is full
+            if self.end == self.buf.len() {
+                self.buf.copy_within(self.start..self.end, 0);
+                self.end -= self.start;
+                self.start = 0;
+            }
+            self.fill().await?;
+        }
+    }
+
+    // Echoes the server's close code; the stream is done either way
+    async fn on_close(&mut self, len: usize, code: Option<u16>) -> io::Result<&mut [u8]> {
+        self.start += len;
+        info!(net, "ASYNC: WS closed by server (code {:?})", code);
+        let n = framing::encode_close_frame(code.unwrap_or(framing::CLOSE_NORMAL), &mut self.out);
+        self.stream.write_all(&self.out[..n]).await?;
+        self.stream.flush().await?;
+        Err(io::Error::new(ErrorKind::ConnectionAborted, format!("WS closed by server (code {:?})", code)))
+    }
+
+    /// One masked text frame, any size (the out buffer grows past `OUT_FRAME_LEN` if needed).
+    pub async fn send_text(&mut self, payload: &[u8]) -> io::Result<()> {
+        let needed = framing::client_header_len(payload.len()) + payload.len();
+        if self.out.len() < needed {
+            self.out.resize(needed, 0);
+        }
+        let len = framing::encode_text_frame(payload, &mut self.out);
+        if len == 0 {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "Frame too large"));
+        }
+        self.stream.write_all(&self.out[..len]).await?;
+        self.stream.flush().await
+    }
+
+    /// Time left until the configured auto-ping; None if auto-ping is off.
+    pub fn ping_in(&self, now: Instant) -> Option<Duration> {
+        self.ping_interval.map(|interval| interval.saturating_sub(now.saturating_duration_since(self.last_ping_ts)))
+    }
+
+    pub async fn send_ping(&mut self, now: Instant) -> io::Result<()> {
+        self.last_ping_ts = now;
+        match self.ping_message {
+            Some(msg) => self.send_text(msg.as_bytes()).await,
+            None => {
+                let mut frame = [0u8; 6];
+                let len = framing::encode_ping_frame(&mut frame);
+                self.stream.write_all(&frame[..len]).await?;
+                self.stream.flush().await
+            }
+        }
+    }
+}
+
+/// Public stream state for the async runtime: the same book, sanity monitor, strategy
+/// and OMS the Hot Thread drives. With `paper` set, strategy actions rest on the paper
+/// exchange and its fills go back to the strategy as executions would.
+pub struct PublicFeed<C: Clock> {
+    pub book: L2OrderBook,
+    pub monitor: BookSanityMonitor,
+    pub strategy: MarketMaker<C>,
+    pub oms: OrderManager<C>,
+    pub paper: Option<PaperExchange>,
+    // Exchange ts of the last applied update, stamped on paper taker fills
+    book_ts: u64,
+}
+
+// `paper::settle`, then the session line
+fn settle<C: Clock>(strategy: &mut MarketMaker<C>, oms: &mut OrderManager<C>, paper: &PaperExchange, fill: &Fill) {
+    paper::settle(strategy, oms, paper, fill);
+    paper.report();
+}
+
+impl<C: Clock> PublicFeed<C> {
+    pub fn new(strategy: MarketMaker<C>, oms: OrderManager<C>) -> Self {
+        Self { book: L2OrderBook::new(), monitor: BookSanityMonitor::new(), strategy, oms, paper: None, book_ts: 0 }
+    }
+
+    /// Strategy actions onto the paper exchange (no-op without one).
+    pub fn apply_paper(&mut self, actions: &[Action]) {
+        let Some(paper) = self.paper.as_mut() else { return };
+        let ts_ms = self.book_ts;
+        for action in actions {
+            if let Some(fill) = paper.on_action(&action.action_type, &self.book, ts_ms) {
+                settle(&mut self.strategy, &mut self.oms, paper, &fill);
+            }
+        }
+    }
+
+    /// One public payload, in the Hot Thread order: ticker -> OMS reference price and
+    /// trade-through check; book delta -> book and sanity check; then the strategy
+    /// (quotes pulled on a book fault). Returns the strategy actions and whether the
+    /// book needs a fresh snapshot (the caller resubscribes).
+    pub fn on_payload(&mut self, payload: &mut [u8]) -> (Option<Vec<Action>>, bool) {
+        let mut mark_price = None;
+        let mut exchange_top = None;
+        let (strategy, oms, paper) = (&mut self.strategy, &mut self.oms, &mut self.paper);
+        let is_ticker = !matches!(parser::parse_market_event(payload, |ev| match ev {
+            MarketEvent::Ticker(t) => {
+                mark_price = t.mark_price;
+                exchange_top = Some((t.bid1_price, t.ask1_price));
+            }
+            MarketEvent::Trade(t) => {
+                strategy.last_price.on_trade(t.price, Instant::now());
+                if let Some(paper) = paper.as_mut() {
+                    if let Some(fill) = paper.on_trade(&t) {
+                        settle(strategy, oms, paper, &fill);
+                    }
+                }
+            }
+            _ => {}
+        }), Ok(0));
+        if let Some(mark) = mark_price {
+            self.oms.set_reference_price(mark, Instant::now());
+        }
+        let ticker_fault = exchange_top.and_then(|(bid1, ask1)| self.monitor.on_ticker(&self.book, bid1, ask1));
+        let update = if is_ticker {
+            ticker_fault.map(|_| BookUpdate { ts: 0, update_id: 0, snapshot: false, levels: 0 })
+        } else {
+            match parser::parse_book_update(payload, &mut self.book) {
+                Ok(update) => Some(update),
+                Err(e) => {
+                    debug!(parser, "ASYNC: Public message not applied to book: {:?}", e);
+                    None
+                }
+            }
+        };
+        let Some(update) = update else { return (None, false) };
+
+        let fault = ticker_fault.or_else(|| self.monitor.on_update(&self.book, &update));
+        if let Some(fault) = fault {
+            eprintln!("ASYNC: [BOOK] Corrupt book ({:?}), pulling quotes and resyncing (faults: {})", fault, self.monitor.faults);
+            self.strategy.warmup.restart();
+            let actions = self.strategy.pull_quotes();
+            if let Some(actions) = actions.as_deref() {
+                self.apply_paper(actions);
+            }
+            self.book.clear();
+            return (actions, true);
+        }
+        self.oms.on_book_update(&self.book);
+        self.book_ts = self.book_ts.max(update.ts);
+        if let Some(paper) = self.paper.as_mut() {
+            for fill in paper.on_book(&self.book, update.ts).into_iter().flatten() {
+                settle(&mut self.strategy, &mut self.oms, paper, &fill);
+            }
+        }
+        self.strategy.on_health(true);
+        let actions = self.strategy.on_tick(&self.book, &update);
+        if let Some(actions) = actions.as_deref() {
+            self.oms.set_position(self.strategy.positions.size());
+            self.apply_paper(actions);
+        }
+        (actions, false)
+    }
+}
+
+/// Runs a public stream until it fails: subscribes, feeds every payload through `feed`
+/// and hands the strategy actions to `on_actions` (the caller routes them: Trade WS, REST
+/// or a paper book). Resubscribes `resync` on a book fault and keeps the auto-ping going.
+pub async fn run_public<C: Clock, F: FnMut(Vec<Action>)>(ws: &mut AsyncWsClient, subscribe: &str, resync: &[String],
+                                                         feed: &mut PublicFeed<C>, mut on_actions: F) -> io::Result<()> {
+    ws.send_text(subscribe.as_bytes()).await?;
+    loop {
+        let payload = match ws.ping_in(Instant::now()) {
+            Some(wait) => match tokio::time::timeout(wait, ws.next_payload()).await {
+                Ok(payload) => payload?,
+                Err(_) => {
+                    ws.send_ping(Instant::now()).await?;
+                    continue;
+                }
+            },
+            None => ws.next_payload().await?,
+        };
+        if payload.is_empty() {
+            continue;
+        }
+        let (actions, resubscribe) = feed.on_payload(payload);
+        if let Some(actions) = actions {
+            on_actions(actions);
+        }
+        if resubscribe {
+            for msg in resync {
+                ws.send_text(msg.as_bytes()).await?;
+            }
+        }
+    }
+}
+
+/// Paper trading on the public Bybit stream (`hft_async`, `hft_rust --paper`): book, strategy
+/// and OMS as in production, actions rest on a `PaperExchange` filled by `paper.model`.
+/// No keys, no orders. Blocks on its own current-thread runtime; reconnects with backoff.
+pub fn run_paper(engine_config: &EngineConfig, paper: PaperConfig) -> Result<(), String> {
+    let mut instrument = engine_config.instrument.to_instrument()?;
+    let bybit_symbol = instrument.venue_symbol(Venue::Bybit).ok_or("Primary instrument has no Bybit symbol")?;
+    match rest_client::fetch_instrument_spec(engine_config.endpoints.rest_url(), instrument.category, bybit_symbol) {
+        Ok(spec) => spec.apply(&mut instrument),
+        Err(e) => eprintln!("WARNING: No instruments-info for {} ({}), keeping the configured grid", bybit_symbol, e),
+    }
+    let bybit_public = WsClientBuilder::new(engine_config.endpoints.public_host())
+        .path(&engine_config.endpoints.public_path(instrument.category));
+    let subscribe = format!(r#"{{"op": "subscribe", "args": ["orderbook.50.{}","tickers.{}","publicTrade.{}"]}}"#,
+        bybit_symbol, bybit_symbol, bybit_symbol);
+    let resync = [
+        format!(r#"{{"op": "unsubscribe", "args": ["orderbook.50.{}"]}}"#, bybit_symbol),
+        format!(r#"{{"op": "subscribe", "args": ["orderbook.50.{}"]}}"#, bybit_symbol),
+    ];
+    let reconnect = ReconnectPolicy { max_attempts: 0, initial_backoff: Duration::from_millis(500), max_backoff: Duration::from_secs(30) };
+
+    let config = tls_client::client_config(engine_config.tls.cert_pins()?)?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("tokio runtime: {}", e))?;
+    println!("PAPER: {} ({}), fill model {}", instrument.name, bybit_symbol, paper.model.name());
+    let mut strategy = MarketMaker::new(0.01);
+    strategy.params = engine_config.quote;
+    if let Some(preset) = engine_config.preset {
+        strategy.aging = preset.aging();
+    }
+    strategy.set_instrument(&instrument);
+    let mut feed = PublicFeed::new(strategy, OrderManager::new(engine_config.oms.order_manager()));
+    feed.paper = Some(PaperExchange::new(paper));
+    runtime.block_on(async {
+        let mut attempt = 0;
+        loop {
+            let result = match AsyncWsClient::connect(&bybit_public, config.clone()).await {
+                Ok(mut ws) => {
+                    attempt = 0;
+                    feed.strategy.warmup.restart();
+                    run_public(&mut ws, &subscribe, &resync, &mut feed, |actions| {
+                        for action in actions {
+                            info!(orders, "PAPER: {:?}", action.action_type);
+                        }
+                    }).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("ASYNC: Public stream down: {}", e);
+            }
+            attempt += 1;
+            match reconnect.backoff(attempt) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err("Public stream: reconnect attempts exhausted".to_string()),
+            }
+        }
+    })
+}
@@ -0,0 +1,645 @@
+use std::time::{Duration, Instant};
+use simd_json::prelude::*;
+use crate::auth::secrets::{lookup_all, redacted_debug, SecretProvider};
+use crate::auth::signer::BinanceSigner;
+use crate::core::logging::debug;
+use crate::core::parser::{num_field, u64_field, ParseError};
+use crate::core::rounding;
+use crate::core::time_sync::{self, TimeSyncConfig};
+use crate::net::adapter::{ConnectionState, ExchangeAdapter, WsConnection};
+use crate::net::rest_client::LinkId;
+use crate::net::ws_client::{WsClient, WsClientBuilder};
+use crate::strategy::fill::Fill;
+
+// Binance USDⓈ-M futures order entry, for hedging Bybit fills. REST only (blocking ureq, its
+// own Cold-side thread): a hedge is one taker order per fill batch, well below the rate where
+// a WS API session would pay off. Compared with Bybit:
+//   * the signature is hex(HMAC-SHA256(secret, query)) over the parameters as sent, timestamp
+//     and recvWindow included; the key goes in `X-MBX-APIKEY` (`auth::signer::BinanceSigner`);
+//   * parameters travel form-encoded (query string or body), not as JSON; errors come back as
+//     `{"code":-2019,"msg":"..."}` with an HTTP 4xx status;
+//   * `quantity` is base coin on the symbol's LOT_SIZE / MARKET_LOT_SIZE step;
+//   * limits are per account for orders (300 / 10s, 1200 / min) and per IP for request weight
+//     (2400 / min). Every response reports the used counts in `X-MBX-*` headers; a 429 means
+//     back off for `Retry-After`, ignoring it escalates to a 418 IP ban.
+// Market data is the `bookTicker` stream (`BinanceBookTicker`), a reference quote for the
+// router; the server pings every few minutes and drops a connection that does not pong.
+// Account events come over the user data stream (`BinanceUserData`): a listenKey from REST
+// (valid 60 min, extended by a keepalive PUT) names the connection, `/ws/<listenKey>`, and
+// the server pushes `ORDER_TRADE_UPDATE` / `ACCOUNT_UPDATE` without any subscribe.
+pub const BINANCE_FAPI_URL: &str = "https://fapi.binance.com";
+pub const BINANCE_FAPI_TESTNET_URL: &str = "https://testnet.binancefuture.com";
+pub const BINANCE_FSTREAM_HOST: &str = "fstream.binance.com";
+pub const BINANCE_FSTREAM_TESTNET_HOST: &str = "stream.binancefuture.com";
+pub const KEY_VAR: &str = "BINANCE_API_KEY";
+pub const SECRET_VAR: &str = "BINANCE_SECRET_KEY";
+// Documented limits (USDⓈ-M, default tier)
+const ORDER_LIMIT_10S: u32 = 300;
+const ORDER_LIMIT_1M: u32 = 1200;
+const WEIGHT_LIMIT_1M: u32 = 2400;
+// Request weights (USDⓈ-M docs): order, cancel-all and exchangeInfo
+const ORDER_WEIGHT: u32 = 1;
+const CANCEL_ALL_WEIGHT: u32 = 1;
+const EXCHANGE_INFO_WEIGHT: u32 = 1;
+const LISTEN_KEY_WEIGHT: u32 = 1;
+/// A listenKey lapses 60 min after its creation or last keepalive.
+pub const LISTEN_KEY_KEEPALIVE: Duration = Duration::from_secs(30 * 60);
+// Back-off when a 429 / 418 carries no Retry-After
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Binance API key and the signer of its secret.
+pub struct BinanceCredentials {
+    pub api_key: String,
+    signer: BinanceSigner,
+}
+
+redacted_debug!(BinanceCredentials { api_key });
+
+impl BinanceCredentials {
+    pub fn new(api_key: &str, secret: &str) -> Result<Self, String> {
+        let (api_key, secret) = (api_key.trim(), secret.trim());
+        if api_key.is_empty() || secret.is_empty() {
+            return Err("empty Binance API key or secret".to_string());
+        }
+        Ok(Self { api_key: api_key.to_string(), signer: BinanceSigner::new(secret) })
+    }
+
+    pub fn load(provider: &dyn SecretProvider) -> Result<Self, String> {
+        let [key, secret] = lookup_all(provider, [KEY_VAR, SECRET_VAR])?;
+        Self::new(&key, &secret)
+    }
+
+    /// `query` + `&signature=...`.
+    pub fn sign(&self, query: &str) -> String {
+        self.signer.signed_query(query)
+    }
+}
+
+/// One hedge: a reduce-or-open market order on Binance, opposite to the Bybit fill it covers.
+/// `Copy` so it travels through the rtrb ring without allocation.
+#[derive(Debug, Clone, Copy)]
+pub struct HedgeRequest {
+    /// "Buy" | "Sell" like everywhere else in the bot.
+    pub side: &'static str,
+    /// Base coin, not yet on Binance's lot grid (`HedgeBook` takes care of it).
+    pub qty: f64,
+    /// orderLinkId of the Bybit order that filled (logs only).
+    pub source: LinkId,
+}
+
+fn binance_side(side: &str) -> &'static str {
+    if side == "Buy" { "BUY" } else { "SELL" }
+}
+
+fn opposite(side: &str) -> &'static str {
+    if side == "Buy" { "Sell" } else { "Buy" }
+}
+
+impl HedgeRequest {
+    /// The hedge of a Bybit fill of `qty` base coin on `fill_side`, scaled by `ratio`.
+    pub fn for_fill(fill_side: &str, qty: f64, ratio: f64, source: LinkId) -> Self {
+        Self { side: opposite(fill_side), qty: qty * ratio, source }
+    }
+}
+
+/// Net hedge still owed per symbol. Fills smaller than Binance's lot step add up here until
+/// a whole step can be sent; opposite fills net out before anything is sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HedgeBook {
+    /// Signed base coin: positive = to buy on Binance.
+    pending: f64,
+    step: f64,
+    min_qty: f64,
+}
+
+impl HedgeBook {
+    pub fn new(step: f64, min_qty: f64) -> Self {
+        Self { pending: 0.0, step, min_qty: min_qty.max(step) }
+    }
+
+    /// Adds a hedge to the net and returns the order to send now, (side, qty on the lot
+    /// grid), if the net reached the minimum order size.
+    pub fn add(&mut self, req: &HedgeRequest) -> Option<(&'static str, f64)> {
+        self.pending += if req.side == "Buy" { req.qty } else { -req.qty };
+        let qty = rounding::floor_to(self.pending.abs(), self.step);
+        if qty + self.step * 1e-9 < self.min_qty {
+            return None;
+        }
+        let side = if self.pending > 0.0 { "Buy" } else { "Sell" };
+        self.pending -= if side == "Buy" { qty } else { -qty };
+        Some((side, qty))
+    }
+
+    /// An order that failed goes back on the net, to be retried with the next hedge.
+    pub fn restore(&mut self, side: &str, qty: f64) {
+        self.pending += if side == "Buy" { qty } else { -qty };
+    }
+
+    /// Lot step the orders are sent on.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Signed base coin not hedged yet.
+    pub fn pending(&self) -> f64 {
+        self.pending
+    }
+}
+
+/// Used counts as the last response reported them (`X-MBX-*` headers).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsedLimits {
+    pub weight_1m: Option<u32>,
+    pub orders_10s: Option<u32>,
+    pub orders_1m: Option<u32>,
+}
+
+/// Fixed window counter, reset when `len` has passed since it opened. Binance counts per
+/// calendar interval; a window opened at our first request can only end later, never
+/// earlier, so it errs on the safe side.
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    len: Duration,
+    limit: u32,
+    opened: Option<Instant>,
+    used: u32,
+}
+
+impl Window {
+    fn new(len: Duration, limit: u32) -> Self {
+        Self { len, limit, opened: None, used: 0 }
+    }
+
+    fn roll(&mut self, now: Instant) {
+        if self.opened.is_none_or(|t| now.duration_since(t) >= self.len) {
+            self.opened = Some(now);
+            self.used = 0;
+        }
+    }
+
+    /// Time until `n` more fit, zero if they fit now.
+    fn wait(&mut self, now: Instant, n: u32) -> Duration {
+        self.roll(now);
+        if self.used + n <= self.limit {
+            return Duration::ZERO;
+        }
+        self.opened.map_or(Duration::ZERO, |t| (t + self.len).saturating_duration_since(now))
+    }
+}
+
+/// Client-side limiter for the Binance order path: order count per 10s and per minute
+/// (account), request weight per minute (IP), each kept below `1 - headroom` of the documented
+/// limit, plus the back-off of the last 429 / 418. Counts follow the exchange's headers when
+/// those are higher (another process on the same key or IP).
+#[derive(Debug, Clone, Copy)]
+pub struct BinanceRateLimiter {
+    orders_10s: Window,
+    orders_1m: Window,
+    weight_1m: Window,
+    banned_until: Option<Instant>,
+}
+
+impl BinanceRateLimiter {
+    /// `headroom` in [0, 1): share of each limit left unused.
+    pub fn new(headroom: f64) -> Self {
+        let cap = |limit: u32| ((limit as f64 * (1.0 - headroom.clamp(0.0, 0.99))) as u32).max(1);
+        Self {
+            orders_10s: Window::new(Duration::from_secs(10), cap(ORDER_LIMIT_10S)),
+            orders_1m: Window::new(Duration::from_secs(60), cap(ORDER_LIMIT_1M)),
+            weight_1m: Window::new(Duration::from_secs(60), cap(WEIGHT_LIMIT_1M)),
+            banned_until: None,
+        }
+    }
+
+    /// Time to wait before a request of `weight` (and `orders` new orders) may go, zero if
+    /// now. Nothing is counted until `record`.
+    pub fn wait(&mut self, now: Instant, weight: u32, orders: u32) -> Duration {
+        let ban = self.banned_until.map_or(Duration::ZERO, |t| t.saturating_duration_since(now));
+        let mut wait = ban.max(self.weight_1m.wait(now, weight));
+        if orders > 0 {
+            wait = wait.max(self.orders_10s.wait(now, orders)).max(self.orders_1m.wait(now, orders));
+        }
+        wait
+    }
+
+    /// Counts a request that was sent.
+    pub fn record(&mut self, now: Instant, weight: u32, orders: u32) {
+        for (window, n) in [(&mut self.weight_1m, weight), (&mut self.orders_10s, orders), (&mut self.orders_1m, orders)] {
+            window.roll(now);
+            window.used += n;
+        }
+    }
+
+    /// Raises the local counts to what the exchange reported.
+    pub fn observe(&mut self, now: Instant, used: UsedLimits) {
+        for (window, seen) in [(&mut self.weight_1m, used.weight_1m), (&mut self.orders_10s, used.orders_10s), (&mut self.orders_1m, used.orders_1m)] {
+            if let Some(seen) = seen {
+                window.roll(now);
+                window.used = window.used.max(seen);
+            }
+        }
+    }
+
+    /// 429 (rate limited) or 418 (IP banned): nothing goes out for `retry_after`.
+    pub fn back_off(&mut self, now: Instant, retry_after: Option<Duration>) {
+        let until = now + retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+        self.banned_until = Some(self.banned_until.map_or(until, |t| t.max(until)));
+    }
+}
+
+/// Fill summary of a market order (`newOrderRespType=RESULT`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HedgeFill {
+    pub order_id: u64,
+    pub executed_qty: f64,
+    pub avg_price: f64,
+}
+
+/// `X-MBX-USED-WEIGHT-1M` / `X-MBX-ORDER-COUNT-10S` / `X-MBX-ORDER-COUNT-1M`.
+fn used_limits(response: &ureq::Response) -> UsedLimits {
+    let header = |name: &str| response.header(name).and_then(|v| v.trim().parse().ok());
+    UsedLimits {
+        weight_1m: header("X-MBX-USED-WEIGHT-1M"),
+        orders_10s: header("X-MBX-ORDER-COUNT-10S"),
+        orders_1m: header("X-MBX-ORDER-COUNT-1M"),
+    }
+}
+
+/// Query of a market hedge order, keys in the order they are signed and sent.
+pub fn market_order_query(symbol: &str, side: &str, qty: f64, qty_decimals: usize, reduce_only: bool, client_id: &str) -> String {
+    let mut query = format!("symbol={}&side={}&type=MARKET&quantity={:.*}", symbol, binance_side(side), qty_decimals, qty);
+    if reduce_only {
+        query.push_str("&reduceOnly=true");
+    }
+    if !client_id.is_empty() {
+        query.push_str("&newClientOrderId=");
+        query.push_str(client_id);
+    }
+    query.push_str("&newOrderRespType=RESULT");
+    query
+}
+
+/// Error of a Binance response body (`{"code":-2019,"msg":"Margin is insufficient."}`), if
+/// it is one.
+fn api_error(json: &simd_json::BorrowedValue) -> Option<String> {
+    let code = json.get("code").and_then(|v| v.as_i64()).filter(|c| *c < 0)?;
+    let msg = json.get("msg").and_then(|v| v.as_str()).unwrap_or("unknown");
+    Some(format!("Binance error: {} - {}", code, msg))
+}
+
+/// Parses a POST /fapi/v1/order response.
+pub fn parse_order_response(resp: &mut [u8]) -> Result<HedgeFill, String> {
+    let json = simd_json::to_borrowed_value(resp).map_err(|_| "Failed to parse response JSON".to_string())?;
+    if let Some(err) = api_error(&json) {
+        return Err(err);
+    }
+    let num = |key: &str| json.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+    Ok(HedgeFill {
+        order_id: json.get("orderId").and_then(|v| v.as_u64()).ok_or_else(|| "No orderId in response".to_string())?,
+        executed_qty: num("executedQty"),
+        avg_price: num("avgPrice"),
+    })
+}
+
+/// Key of a POST /fapi/v1/listenKey response (`{"listenKey":"..."}`).
+pub fn parse_listen_key(resp: &mut [u8]) -> Result<String, String> {
+    let json = simd_json::to_borrowed_value(resp).map_err(|_| "Failed to parse response JSON".to_string())?;
+    if let Some(err) = api_error(&json) {
+        return Err(err);
+    }
+    json.get("listenKey").and_then(|v| v.as_str()).filter(|k| !k.is_empty()).map(str::to_string)
+        .ok_or_else(|| "No listenKey in response".to_string())
+}
+
+/// (step, min qty) of market orders on `symbol` from a GET /fapi/v1/exchangeInfo response:
+/// MARKET_LOT_SIZE, else LOT_SIZE.
+pub fn parse_market_lot(resp: &mut [u8], symbol: &str) -> Result<(f64, f64), String> {
+    let json = simd_json::to_borrowed_value(resp).map_err(|_| "Failed to parse response JSON".to_string())?;
+    if let Some(err) = api_error(&json) {
+        return Err(err);
+    }
+    let item = json.get("symbols").and_then(|l| l.as_array()).into_iter().flatten()
+        .find(|s| s.get("symbol").and_then(|v| v.as_str()) == Some(symbol))
+        .ok_or_else(|| format!("{} not listed", symbol))?;
+    let filters = item.get("filters").and_then(|f| f.as_array()).map(|a| a.as_slice()).unwrap_or(&[]);
+    let lot = |kind: &str| filters.iter().find(|f| f.get("filterType").and_then(|v| v.as_str()) == Some(kind)).and_then(|f| {
+        let num = |key: &str| f.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).filter(|v| *v > 0.0);
+        Some((num("stepSize")?, num("minQty")?))
+    });
+    lot("MARKET_LOT_SIZE").or_else(|| lot("LOT_SIZE")).ok_or_else(|| format!("No lot size filter for {}", symbol))
+}
+
+/// Blocking Binance USDⓈ-M REST client (ureq) for hedge orders. Cold side only: every call
+/// waits on the rate limiter and on HTTP.
+pub struct BinanceClient {
+    credentials: BinanceCredentials,
+    base_url: String,
+    time: TimeSyncConfig,
+    limiter: BinanceRateLimiter,
+}
+
+impl BinanceClient {
+    pub fn new(credentials: BinanceCredentials, headroom: f64) -> Self {
+        Self {
+            credentials,
+            base_url: BINANCE_FAPI_URL.to_string(),
+            time: TimeSyncConfig::default(),
+            limiter: BinanceRateLimiter::new(headroom),
+        }
+    }
+
+    /// REST host other than fapi.binance.com (testnet), no trailing slash.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// recv_window and timestamp margin (shared with the Bybit clients).
+    pub fn with_time_sync(mut self, time: TimeSyncConfig) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// (step, min qty) of market orders on `symbol` (unsigned).
+    pub fn market_lot(&mut self, symbol: &str) -> Result<(f64, f64), String> {
+        let url = format!("{}/fapi/v1/exchangeInfo?symbol={}", self.base_url, symbol);
+        let body = self.send(ureq::get(&url), None, EXCHANGE_INFO_WEIGHT, 0)?;
+        parse_market_lot(&mut body.into_bytes(), symbol)
+    }
+
+    /// Market order of `qty` base coin (already on the lot grid).
+    pub fn market_order(&mut self, symbol: &str, side: &str, qty: f64, step: f64, reduce_only: bool, client_id: &str) -> Result<HedgeFill, String> {
+        let query = market_order_query(symbol, side, qty, rounding::step_decimals(step), reduce_only, client_id);
+        let body = self.signed("POST", "/fapi/v1/order", &query, ORDER_WEIGHT, 1)?;
+        parse_order_response(&mut body.into_bytes())
+    }
+
+    /// DELETE /fapi/v1/allOpenOrders: hedges are market orders, this only sweeps leftovers.
+    pub fn cancel_all(&mut self, symbol: &str) -> Result<(), String> {
+        let body = self.signed("DELETE", "/fapi/v1/allOpenOrders", &format!("symbol={}", symbol), CANCEL_ALL_WEIGHT, 0)?;
+        let mut resp_bytes = body.into_bytes();
+        let json = simd_json::to_borrowed_value(&mut resp_bytes).map_err(|_| "Failed to parse response JSON".to_string())?;
+        // Success is {"code":200,"msg":"..."}: only negative codes are errors
+        api_error(&json).map_or(Ok(()), Err)
+    }
+
+    /// POST /fapi/v1/listenKey: key of the user data stream. While one is valid Binance
+    /// returns it again (and extends it), so this is also the way back after a disconnect.
+    pub fn create_listen_key(&mut self) -> Result<String, String> {
+        let body = self.keyed("POST", "/fapi/v1/listenKey")?;
+        parse_listen_key(&mut body.into_bytes())
+    }
+
+    /// PUT /fapi/v1/listenKey: another 60 min for the current key. Err (-1125) once it has
+    /// lapsed: create a new one.
+    pub fn keepalive_listen_key(&mut self) -> Result<(), String> {
+        self.keyed("PUT", "/fapi/v1/listenKey").map(|_| ())
+    }
+
+    /// API key only, no signature (the listenKey endpoints).
+    fn keyed(&mut self, method: &str, path: &str) -> Result<String, String> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = ureq::request(method, &url).set("X-MBX-APIKEY", &self.credentials.api_key);
+        self.send(request, Some(""), LISTEN_KEY_WEIGHT, 0)
+    }
+
+    /// Signs `query` (+ recvWindow, timestamp) and sends it as the body of `method`.
+    fn signed(&mut self, method: &str, path: &str, query: &str, weight: u32, orders: u32) -> Result<String, String> {
+        let timestamp = self.time.rest_timestamp_ms(time_sync::wall_ms());
+        let query = format!("{}&recvWindow={}&timestamp={}", query, self.time.recv_window_ms, timestamp);
+        let signed = self.credentials.sign(&query);
+        let url = format!("{}{}", self.base_url, path);
+        let request = ureq::request(method, &url)
+            .set("X-MBX-APIKEY", &self.credentials.api_key)
+            .set("Content-Type", "application/x-www-form-urlencoded");
+        self.send(request, Some(&signed), weight, orders)
+    }
+
+    /// Waits for the limiter, sends, feeds the limiter back from the headers. Err on a
+    /// transport error, a non-2xx status or a Binance error body.
+    fn send(&mut self, request: ureq::Request, body: Option<&str>, weight: u32, orders: u32) -> Result<String, String> {
+        let wait = self.limiter.wait(Instant::now(), weight, orders);
+        if !wait.is_zero() {
+            debug!(orders, "BINANCE: rate limit, waiting {:?}", wait);
+            std::thread::sleep(wait);
+        }
+        self.limiter.record(Instant::now(), weight, orders);
+        let result = match body {
+            Some(body) => request.send_string(body),
+            None => request.call(),
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, response)) => {
+                let now = Instant::now();
+                self.limiter.observe(now, used_limits(&response));
+                if status == 429 || status == 418 {
+                    let retry_after = response.header("Retry-After").and_then(|v| v.trim().parse().ok()).map(Duration::from_secs);
+                    self.limiter.back_off(now, retry_after);
+                }
+                let body = response.into_string().unwrap_or_default();
+                let detail = simd_json::to_borrowed_value(&mut body.clone().into_bytes()).ok().and_then(|json| api_error(&json));
+                return Err(format!("HTTP error: status code {}: {}", status, detail.unwrap_or(body)));
+            }
+            Err(e) => return Err(format!("HTTP error: {}", e)),
+        };
+        self.limiter.observe(Instant::now(), used_limits(&response));
+        let body = response.into_string().map_err(|e| format!("Failed to read response: {}", e))?;
+        debug!(orders, "BINANCE: -> {}", body);
+        Ok(body)
+    }
+}
+
+/// Market data connection (raw streams, subscribed after the upgrade).
+pub fn book_ticker_builder(testnet: bool) -> WsClientBuilder {
+    WsClientBuilder::new(if testnet { BINANCE_FSTREAM_TESTNET_HOST } else { BINANCE_FSTREAM_HOST }).path("/ws")
+}
+
+/// `SUBSCRIBE` of the `bookTicker` streams of `symbols` (any case; stream names are lower case).
+pub fn subscribe_book_ticker(symbols: &[&str]) -> String {
+    let streams: Vec<String> = symbols.iter().map(|s| format!(r#""{}@bookTicker""#, s.to_ascii_lowercase())).collect();
+    format!(r#"{{"method":"SUBSCRIBE","params":[{}],"id":1}}"#, streams.join(","))
+}
+
+/// One `bookTicker` push or a reply to our request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinanceMarketEvent<'a> {
+    BookTicker { symbol: &'a str, bid: f64, bid_qty: f64, ask: f64, ask_qty: f64, update_id: u64 },
+    /// `{"result":null,"id":1}`
+    Ack { id: u64 },
+    Error { code: i64, msg: &'a str },
+}
+
+/// Calls `on_event` for the event in one market data message; other streams yield `Ok(0)`.
+pub fn parse_market_message<F: FnMut(BinanceMarketEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    if let Some(error) = tape.get("error") {
+        let msg = error.get("msg").and_then(|v| v.as_str()).unwrap_or("");
+        on_event(BinanceMarketEvent::Error { code: error.get("code").and_then(|v| v.as_i64()).unwrap_or(0), msg });
+        return Ok(1);
+    }
+    if tape.get("result").is_some() {
+        on_event(BinanceMarketEvent::Ack { id: tape.get("id").and_then(|v| v.as_u64()).unwrap_or(0) });
+        return Ok(1);
+    }
+    if tape.get("e").and_then(|v| v.as_str()) != Some("bookTicker") {
+        return Ok(0);
+    }
+    let field = |key| num_field(&tape, key).ok_or(ParseError::Schema("binance: bookTicker field missing"));
+    on_event(BinanceMarketEvent::BookTicker {
+        symbol: tape.get("s").and_then(|v| v.as_str()).unwrap_or(""),
+        bid: field("b")?,
+        bid_qty: field("B")?,
+        ask: field("a")?,
+        ask_qty: field("A")?,
+        update_id: tape.get("u").and_then(|v| v.as_u64()).unwrap_or(0),
+    });
+    Ok(1)
+}
+
+/// `bookTicker` connection as an `ExchangeAdapter`.
+pub struct BinanceBookTicker {
+    conn: WsConnection,
+    subscribe: String,
+}
+
+impl BinanceBookTicker {
+    pub fn new(client: WsClient, symbols: &[&str]) -> Self {
+        Self { conn: WsConnection::new(client, "binance", ConnectionState::Subscribing), subscribe: subscribe_book_ticker(symbols) }
+    }
+}
+
+impl ExchangeAdapter for BinanceBookTicker {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        Some(self.subscribe.clone())
+    }
+}
+
+/// User data connection of `listen_key` (same host as the market data).
+pub fn user_data_builder(testnet: bool, listen_key: &str) -> WsClientBuilder {
+    book_ticker_builder(testnet).path(&format!("/ws/{}", listen_key))
+}
+
+/// One account event of the user data stream.
+#[derive(Debug, Clone, Copy)]
+pub enum BinanceUserEvent<'a> {
+    /// `ORDER_TRADE_UPDATE`. `fill` only on a `TRADE` execution (`x`), with the exchange's
+    /// commission and realized PnL.
+    Order { symbol: &'a str, client_id: &'a str, order_id: u64, exec_type: &'a str, status: &'a str, trade_id: u64, fill: Option<Fill> },
+    /// `ACCOUNT_UPDATE`, one per changed asset.
+    Balance { asset: &'a str, wallet: f64, cross_wallet: f64 },
+    /// `ACCOUNT_UPDATE`, one per changed position; `position_side` is `BOTH` in one-way mode.
+    Position { symbol: &'a str, position_side: &'a str, amount: f64, entry_price: f64, unrealized_pnl: f64 },
+    /// The key lapsed: nothing more arrives on this connection.
+    ListenKeyExpired,
+}
+
+fn str_of<'v>(obj: &'v simd_json::BorrowedValue<'v>, key: &str) -> &'v str {
+    obj.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// The order's last execution (`l` @ `L`). Commission `n` is positive when paid, like
+/// Bybit's execFee; the rate is only meaningful when it is charged in the quote coin (`N`).
+fn fill_of(order: &simd_json::BorrowedValue) -> Option<Fill> {
+    let side = match str_of(order, "S") {
+        "BUY" => "Buy",
+        "SELL" => "Sell",
+        _ => return None,
+    };
+    let (qty, px) = (num_field(order, "l").unwrap_or(0.0), num_field(order, "L").unwrap_or(0.0));
+    let fee = num_field(order, "n").unwrap_or(0.0);
+    Some(Fill {
+        side,
+        qty,
+        px,
+        fee,
+        fee_rate: if qty > 0.0 && px > 0.0 { fee / (qty * px) } else { 0.0 },
+        is_maker: order.get("m").and_then(|v| v.as_bool()).unwrap_or(false),
+        closed_pnl: num_field(order, "rp").unwrap_or(0.0),
+        exec_time_ms: u64_field(order, "T").unwrap_or(0),
+        seq: 0,
+    })
+}
+
+/// Calls `on_event` for each event in one user data message. Returns the number of events;
+/// other event types yield `Ok(0)`.
+pub fn parse_user_message<F: FnMut(BinanceUserEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    match str_of(&tape, "e") {
+        "ORDER_TRADE_UPDATE" => {
+            let order = tape.get("o").ok_or(ParseError::Schema("binance: ORDER_TRADE_UPDATE without o"))?;
+            let exec_type = str_of(order, "x");
+            on_event(BinanceUserEvent::Order {
+                symbol: str_of(order, "s"),
+                client_id: str_of(order, "c"),
+                order_id: u64_field(order, "i").unwrap_or(0),
+                exec_type,
+                status: str_of(order, "X"),
+                trade_id: u64_field(order, "t").unwrap_or(0),
+                fill: if exec_type == "TRADE" { fill_of(order) } else { None },
+            });
+            Ok(1)
+        }
+        "ACCOUNT_UPDATE" => {
+            let account = tape.get("a").ok_or(ParseError::Schema("binance: ACCOUNT_UPDATE without a"))?;
+            let list = |key| account.get(key).and_then(|v| v.as_array()).map(|a| a.as_slice()).unwrap_or(&[]);
+            let (balances, positions) = (list("B"), list("P"));
+            for balance in balances {
+                on_event(BinanceUserEvent::Balance {
+                    asset: str_of(balance, "a"),
+                    wallet: num_field(balance, "wb").unwrap_or(0.0),
+                    cross_wallet: num_field(balance, "cw").unwrap_or(0.0),
+                });
+            }
+            for position in positions {
+                on_event(BinanceUserEvent::Position {
+                    symbol: str_of(position, "s"),
+                    position_side: str_of(position, "ps"),
+                    amount: num_field(position, "pa").unwrap_or(0.0),
+                    entry_price: num_field(position, "ep").unwrap_or(0.0),
+                    unrealized_pnl: num_field(position, "up").unwrap_or(0.0),
+                });
+            }
+            Ok(balances.len() + positions.len())
+        }
+        "listenKeyExpired" => {
+            on_event(BinanceUserEvent::ListenKeyExpired);
+            Ok(1)
+        }
+        _ => Ok(0),
+    }
+}
+
+/// User data connection as an `ExchangeAdapter`. The listenKey in the path is the whole
+/// setup: `Active` right after the upgrade.
+pub struct BinanceUserData {
+    conn: WsConnection,
+}
+
+impl BinanceUserData {
+    pub fn new(client: WsClient) -> Self {
+        Self { conn: WsConnection::new(client, "binance user", ConnectionState::Subscribing) }
+    }
+}
+
+impl ExchangeAdapter for BinanceUserData {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::net::binance::{self, BinanceCredentials, BinanceMarketEvent, BinanceRateLimiter, BinanceUserEvent, HedgeBook, HedgeRequest, UsedLimits};
+    use crate::net::rest_client::LinkId;
+
+    #[test]
+    fn signs_query_and_formats_hedge_order() {
+        // Example of the Binance API docs (SIGNED endpoint security)
+        let creds = BinanceCredentials::new("key", "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j").unwrap();
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(creds.sign(query), format!("{}&signature=c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71", query));
+        assert!(!format!("{:?}", creds).contains("NhqPt"));
+
+        assert_eq!(binance::market_order_query("BTCUSDT", "Sell", 0.012, 3, false, "h-mm0b1"),
+            "symbol=BTCUSDT&side=SELL&type=MARKET&quantity=0.012&newClientOrderId=h-mm0b1&newOrderRespType=RESULT");
+        let mut resp = br#"{"orderId":22542179,"symbol":"BTCUSDT","status":"FILLED","executedQty":"0.012","avgPrice":"64000.10"}"#.to_vec();
+        let fill = binance::parse_order_response(&mut resp).unwrap();
+        assert_eq!((fill.order_id, fill.executed_qty, fill.avg_price), (22542179, 0.012, 64000.1));
+        let mut resp = br#"{"code":-2019,"msg":"Margin is insufficient."}"#.to_vec();
+        assert_eq!(binance::parse_order_response(&mut resp).unwrap_err(), "Binance error: -2019 - Margin is insufficient.");
+
+        let mut info = br#"{"symbols":[{"symbol":"BTCUSDT","filters":[{"filterType":"LOT_SIZE","stepSize":"0.001","minQty":"0.001"},{"filterType":"MARKET_LOT_SIZE","stepSize":"0.001","minQty":"0.002"}]}]}"#.to_vec();
+        assert_eq!(binance::parse_market_lot(&mut info, "BTCUSDT").unwrap(), (0.001, 0.002));
+    }
+
+    #[test]
+    fn hedge_book_nets_fills_up_to_the_lot() {
+        let link = LinkId::new("mm0b1");
+        let mut book = HedgeBook::new(0.01, 0.01);
+        // Bybit maker buy of 0.004 -> sell 0.004 on Binance: below one step, kept
+        assert_eq!(book.add(&HedgeRequest::for_fill("Buy", 0.004, 1.0, link)), None);
+        assert_eq!(book.add(&HedgeRequest::for_fill("Buy", 0.009, 1.0, link)), Some(("Sell", 0.01)));
+        assert!((book.pending() + 0.003).abs() < 1e-9);
+        // An opposite fill nets out first
+        assert_eq!(book.add(&HedgeRequest::for_fill("Sell", 0.005, 1.0, link)), None);
+        assert!((book.pending() - 0.002).abs() < 1e-9);
+        // A failed order is owed again
+        book.restore("Sell", 0.01);
+        assert_eq!(book.add(&HedgeRequest::for_fill("Buy", 0.0, 1.0, link)), None);
+        assert!((book.pending() + 0.008).abs() < 1e-9);
+        // Half hedge
+        assert_eq!(book.add(&HedgeRequest::for_fill("Buy", 0.04, 0.5, link)), Some(("Sell", 0.02)));
+    }
+
+    #[test]
+    fn rate_limiter_windows_headers_and_back_off() {
+        let t0 = Instant::now();
+        // 50% headroom: 150 orders per 10s
+        let mut limiter = BinanceRateLimiter::new(0.5);
+        for _ in 0..150 {
+            assert_eq!(limiter.wait(t0, 1, 1), Duration::ZERO);
+            limiter.record(t0, 1, 1);
+        }
+        assert_eq!(limiter.wait(t0 + Duration::from_secs(4), 1, 1), Duration::from_secs(6));
+        // Weight-only requests are not held by the order windows
+        assert_eq!(limiter.wait(t0 + Duration::from_secs(4), 1, 0), Duration::ZERO);
+        assert_eq!(limiter.wait(t0 + Duration::from_secs(10), 1, 1), Duration::ZERO);
+
+        // Another process used most of the IP weight
+        let t1 = t0 + Duration::from_secs(70);
+        limiter.observe(t1, UsedLimits { weight_1m: Some(1200), ..UsedLimits::default() });
+        assert_eq!(limiter.wait(t1, 1, 0), Duration::from_secs(60));
+
+        let mut limiter = BinanceRateLimiter::new(0.0);
+        limiter.back_off(t0, Some(Duration::from_secs(30)));
+        assert_eq!(limiter.wait(t0 + Duration::from_secs(10), 1, 1), Duration::from_secs(20));
+        assert_eq!(limiter.wait(t0 + Duration::from_secs(30), 1, 1), Duration::ZERO);
+    }
+
+    #[test]
+    fn book_ticker_subscribes_and_parses() {
+        assert_eq!(binance::subscribe_book_ticker(&["BTCUSDT", "ETHUSDT"]), r#"{"method":"SUBSCRIBE","params":["btcusdt@bookTicker","ethusdt@bookTicker"],"id":1}"#);
+        let mut msgs = [
+            br#"{"result":null,"id":1}"#.to_vec(),
+            br#"{"e":"bookTicker","u":400900217,"E":1568014460893,"T":1568014460891,"s":"BTCUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}"#.to_vec(),
+            br#"{"error":{"code":2,"msg":"Invalid request"},"id":1}"#.to_vec(),
+            br#"{"e":"aggTrade","s":"BTCUSDT"}"#.to_vec(),
+        ];
+        let mut seen = Vec::new();
+        for msg in msgs.iter_mut() {
+            binance::parse_market_message(msg, |event| seen.push(format!("{:?}", event))).unwrap();
+        }
+        assert_eq!(seen, [
+            "Ack { id: 1 }".to_string(),
+            format!("{:?}", BinanceMarketEvent::BookTicker { symbol: "BTCUSDT", bid: 25.3519, bid_qty: 31.21, ask: 25.3652, ask_qty: 40.66, update_id: 400900217 }),
+            r#"Error { code: 2, msg: "Invalid request" }"#.to_string(),
+        ]);
+    }
+
+    #[test]
+    fn user_data_stream_parses_fills_and_account_updates() {
+        let mut resp = br#"{"listenKey":"pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"}"#.to_vec();
+        assert_eq!(binance::parse_listen_key(&mut resp).unwrap(), "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1");
+        assert!(binance::parse_listen_key(&mut br#"{"code":-1125,"msg":"This listenKey does not exist."}"#.to_vec()).unwrap_err().contains("-1125"));
+
+        let mut fill = br#"{"e":"ORDER_TRADE_UPDATE","E":1568879465651,"T":1568879465650,"o":{"s":"BTCUSDT","c":"hg-1-7","S":"SELL","o":"MARKET","f":"GTC","q":"0.010","p":"0","ap":"64000.10","sp":"0","x":"TRADE","X":"FILLED","i":8886774,"l":"0.010","z":"0.010","L":"64000.10","N":"USDT","n":"0.25600040","T":1568879465650,"t":512,"b":"0","a":"0","m":false,"R":false,"wt":"CONTRACT_PRICE","ot":"MARKET","ps":"BOTH","cp":false,"rp":"1.5","pP":false,"si":0,"ss":0}}"#.to_vec();
+        let mut fills = Vec::new();
+        assert_eq!(binance::parse_user_message(&mut fill, |event| {
+            let BinanceUserEvent::Order { symbol, client_id, order_id, exec_type, status, trade_id, fill: Some(fill) } = event else { panic!("{:?}", event) };
+            assert_eq!((symbol, client_id, order_id, exec_type, status, trade_id), ("BTCUSDT", "hg-1-7", 8886774, "TRADE", "FILLED", 512));
+            fills.push(fill);
+        }).unwrap(), 1);
+        let fill = fills[0];
+        assert_eq!((fill.side, fill.qty, fill.px, fill.fee, fill.is_maker, fill.closed_pnl, fill.exec_time_ms), ("Sell", 0.01, 64000.1, 0.2560004, false, 1.5, 1568879465650));
+        assert!((fill.fee_rate - 0.0004).abs() < 1e-12, "{}", fill.fee_rate);
+
+        // A new order is no execution
+        let mut new = br#"{"e":"ORDER_TRADE_UPDATE","E":1,"T":1,"o":{"s":"BTCUSDT","c":"x","S":"BUY","x":"NEW","X":"NEW","i":1,"l":"0","L":"0","n":"0","T":1,"t":0,"m":false,"rp":"0"}}"#.to_vec();
+        let mut fills = 0;
+        binance::parse_user_message(&mut new, |event| fills += matches!(event, BinanceUserEvent::Order { fill: Some(_), .. }) as u32).unwrap();
+        assert_eq!(fills, 0);
+
+        let mut account = br#"{"e":"ACCOUNT_UPDATE","E":1564745798939,"T":1564745798938,"a":{"m":"ORDER","B":[{"a":"USDT","wb":"122624.12345678","cw":"100.12345678","bc":"50.12345678"}],"P":[{"s":"BTCUSDT","pa":"-0.010","ep":"64000.10","bep":"0","cr":"200","up":"-0.5","mt":"cross","iw":"0","ps":"BOTH"}]}}"#.to_vec();
+        let mut seen = Vec::new();
+        assert_eq!(binance::parse_user_message(&mut account, |event| seen.push(format!("{:?}", event))).unwrap(), 2);
+        assert_eq!(seen, [
+            format!("{:?}", BinanceUserEvent::Balance { asset: "USDT", wallet: 122624.12345678, cross_wallet: 100.12345678 }),
+            format!("{:?}", BinanceUserEvent::Position { symbol: "BTCUSDT", position_side: "BOTH", amount: -0.01, entry_price: 64000.1, unrealized_pnl: -0.5 }),
+        ]);
+
+        let mut expired = br#"{"e":"listenKeyExpired","E":1576653824250,"listenKey":"pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"}"#.to_vec();
+        let mut seen = Vec::new();
+        binance::parse_user_message(&mut expired, |event| seen.push(format!("{:?}", event))).unwrap();
+        assert_eq!(seen, ["ListenKeyExpired"]);
+    }
+}
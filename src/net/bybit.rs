@@ -0,0 +1,149 @@
+use std::io;
+use crate::auth::secrets::redacted_debug;
+use crate::auth::signer::Signer;
+use crate::core::logging::info;
+use crate::core::time_sync::{self, TimeSyncConfig};
+use crate::net::adapter::{ConnectionState, ExchangeAdapter, WsConnection};
+use crate::net::ws_client::WsClient;
+
+// Bybit V5 WS connections as `ExchangeAdapter`s: public (books, tickers, trades of every
+// quoted symbol), private (executions, positions, wallet) and trade (order entry). The Hot
+// Thread still reads the payloads itself; these own the setup: the public subscription, the
+// `auth` op of the private / trade sockets and the private subscription once auth is acked.
+
+/// `auth` op of the private and trade sockets: hex(HMAC-SHA256(secret, "GET/realtime" + expires)).
+pub struct BybitAuth {
+    api_key: String,
+    signer: Signer,
+    time: TimeSyncConfig,
+}
+
+redacted_debug!(BybitAuth { api_key });
+
+impl BybitAuth {
+    pub fn new(api_key: &str, api_secret: &str, time: TimeSyncConfig) -> Self {
+        Self { api_key: api_key.to_string(), signer: Signer::new(api_secret), time }
+    }
+
+    /// `expires` from the Trade WS clock offset once synced (`time_sync::synced_offset_ms`).
+    pub fn auth_message(&self, local_ms: u64) -> String {
+        let expires = self.time.auth_expires_ms(local_ms, time_sync::synced_offset_ms());
+        let mut signature_hex = [0u8; 64];
+        self.signer.sign_message(format!("GET/realtime{}", expires).as_bytes(), &mut signature_hex);
+        let sig_str = std::str::from_utf8(&signature_hex).unwrap_or("");
+        format!(r#"{{"op":"auth","args":["{}","{}","{}"]}}"#, self.api_key, expires, sig_str)
+    }
+}
+
+/// Topics of one symbol on the public stream.
+fn public_topics(symbol: &str) -> String {
+    format!(r#""orderbook.50.{0}","tickers.{0}","publicTrade.{0}""#, symbol)
+}
+
+/// Public stream of every quoted symbol (one category per connection).
+pub struct BybitPublic {
+    conn: WsConnection,
+    subscribe: String,
+}
+
+impl BybitPublic {
+    pub fn new(client: WsClient, symbols: &[&str]) -> Self {
+        Self::named(client, symbols, "public")
+    }
+
+    /// Second connection with the same topics (`net::feed_arbiter`).
+    pub fn backup(client: WsClient, symbols: &[&str]) -> Self {
+        Self::named(client, symbols, "backup public")
+    }
+
+    fn named(client: WsClient, symbols: &[&str], name: &'static str) -> Self {
+        let topics: Vec<String> = symbols.iter().map(|symbol| public_topics(symbol)).collect();
+        let subscribe = format!(r#"{{"op": "subscribe", "args": [{}]}}"#, topics.join(","));
+        Self { conn: WsConnection::new(client, name, ConnectionState::Subscribing), subscribe }
+    }
+
+    /// Fresh snapshot of one book: unsubscribe + subscribe makes Bybit resend it.
+    pub fn resync_book(&mut self, symbol: &str) -> io::Result<()> {
+        for op in ["unsubscribe", "subscribe"] {
+            self.conn.send_text(&format!(r#"{{"op": "{}", "args": ["orderbook.50.{}"]}}"#, op, symbol))?;
+        }
+        Ok(())
+    }
+}
+
+impl ExchangeAdapter for BybitPublic {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        info!(net, "HOT: Sending Bybit Subscription: {}", self.subscribe);
+        Some(self.subscribe.clone())
+    }
+}
+
+/// Private stream: auth on connect, `execution` / `position` / `wallet` once auth is acked.
+pub struct BybitPrivate {
+    conn: WsConnection,
+    auth: BybitAuth,
+}
+
+pub const PRIVATE_SUBSCRIBE: &str = r#"{"op": "subscribe", "args": ["execution","position","wallet"]}"#;
+
+impl BybitPrivate {
+    pub fn new(client: WsClient, auth: BybitAuth) -> Self {
+        Self { conn: WsConnection::new(client, "private", ConnectionState::Authenticating), auth }
+    }
+
+    /// After the auth ack (subscribing earlier gets the request refused).
+    pub fn subscribe(&mut self) -> io::Result<()> {
+        info!(net, "HOT: Sending Private Subscription: {}", PRIVATE_SUBSCRIBE);
+        self.conn.send_text(PRIVATE_SUBSCRIBE)
+    }
+}
+
+impl ExchangeAdapter for BybitPrivate {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        info!(net, "HOT: Authenticating Private WS...");
+        Some(self.auth.auth_message(time_sync::wall_ms()))
+    }
+}
+
+/// Trade stream: auth on connect, then order ops (frames encoded by the caller, `send_order`).
+pub struct BybitTrade {
+    conn: WsConnection,
+    auth: BybitAuth,
+}
+
+impl BybitTrade {
+    pub fn new(client: WsClient, auth: BybitAuth) -> Self {
+        Self { conn: WsConnection::new(client, "trade", ConnectionState::Authenticating), auth }
+    }
+}
+
+impl ExchangeAdapter for BybitTrade {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        info!(net, "HOT: Authenticating Trade WS...");
+        Some(self.auth.auth_message(time_sync::wall_ms()))
+    }
+}
@@ -0,0 +1,346 @@
+use std::time::Duration;
+use ring::hmac;
+use simd_json::prelude::*;
+use simd_json::BorrowedValue;
+use crate::auth::ec::EcSigner;
+use crate::auth::secrets::{lookup_all, redacted_debug, SecretProvider};
+use crate::core::base64;
+use crate::core::logging::debug;
+use crate::core::orderbook::{L2OrderBook, Side};
+use crate::core::parser::{list, str_field, u64_field, ParseError};
+use crate::core::time_sync;
+use crate::net::adapter::{ConnectionState, ExchangeAdapter, WsConnection};
+use crate::net::ws_client::{WsClient, WsClientBuilder};
+
+// Coinbase International Exchange (INTX) perpetuals. Market data over the INTX WS (`LEVEL2`
+// channel), order entry over REST. Two keys, two schemes:
+//   * the WS subscribe is signed with the INTX HMAC key: base64(HMAC-SHA256(base64-decoded
+//     secret, time + key + "CBINTLMD" + passphrase)), time in seconds;
+//   * REST orders carry an ES256 JWT of a CDP key (`auth::ec::EcSigner`) bound to the method,
+//     host and path; nothing secret travels.
+// Otherwise unlike Bybit:
+//   * LEVEL2 is a snapshot, then updates `[side, px, qty]` (qty 0 = level gone), numbered by
+//     a per-product `sequence` that must grow by one (`Level2Sequencer`);
+//   * sides are "BUY" / "SELL", sizes in base coin as decimal strings, every order names the
+//     portfolio it trades for;
+//   * orders are addressed by the exchange id or our `client_order_id` (up to 128 chars).
+// The market data connection is an `ExchangeAdapter` (`IntxMarketData`); REST order entry
+// stays a blocking client for the cold side.
+pub const INTX_WS_HOST: &str = "ws-md.international.coinbase.com";
+pub const INTX_SANDBOX_WS_HOST: &str = "ws-md.n5e2.coinbase.com";
+pub const INTX_REST_HOST: &str = "api.international.coinbase.com";
+pub const INTX_SANDBOX_REST_HOST: &str = "api-n5e1.coinbase.com";
+pub const INTX_PING_INTERVAL: Duration = Duration::from_secs(20);
+pub const KEY_VAR: &str = "INTX_API_KEY";
+pub const SECRET_VAR: &str = "INTX_SECRET_KEY";
+pub const PASSPHRASE_VAR: &str = "INTX_PASSPHRASE";
+pub const CDP_KEY_NAME_VAR: &str = "INTX_CDP_KEY_NAME";
+pub const CDP_PRIVATE_KEY_VAR: &str = "INTX_CDP_PRIVATE_KEY";
+const LEVEL2_CHANNEL: &str = "LEVEL2";
+const SUBSCRIBE_SUFFIX: &str = "CBINTLMD";
+
+/// Market data connection (WS ping frames keep it open).
+pub fn market_data_builder(sandbox: bool) -> WsClientBuilder {
+    let host = if sandbox { INTX_SANDBOX_WS_HOST } else { INTX_WS_HOST };
+    WsClientBuilder::new(host).path("/").auto_ping(INTX_PING_INTERVAL, None)
+}
+
+/// Market data connection as an `ExchangeAdapter`: `LEVEL2` of `products` once upgraded,
+/// signed with the wall clock of each (re)connect.
+pub struct IntxMarketData {
+    conn: WsConnection,
+    credentials: IntxCredentials,
+    products: Vec<String>,
+}
+
+impl IntxMarketData {
+    pub fn new(client: WsClient, credentials: IntxCredentials, products: &[&str]) -> Self {
+        Self {
+            conn: WsConnection::new(client, "intx", ConnectionState::Subscribing),
+            credentials,
+            products: products.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// The `SUBSCRIBE` sent at `ts_secs`.
+    pub fn subscribe(&self, ts_secs: u64) -> String {
+        let products: Vec<&str> = self.products.iter().map(String::as_str).collect();
+        self.credentials.subscribe_level2(&products, ts_secs)
+    }
+}
+
+impl ExchangeAdapter for IntxMarketData {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        debug!(net, "HOT: Sending INTX LEVEL2 subscription for {:?}", self.products);
+        Some(self.subscribe(time_sync::wall_ms() / 1000))
+    }
+}
+
+/// INTX API key, passphrase and the HMAC key of the (base64) secret.
+#[derive(Clone)]
+pub struct IntxCredentials {
+    pub api_key: String,
+    pub passphrase: String,
+    key: hmac::Key,
+}
+
+redacted_debug!(IntxCredentials { api_key });
+
+impl IntxCredentials {
+    pub fn new(api_key: &str, secret: &str, passphrase: &str) -> Result<Self, String> {
+        let (api_key, secret, passphrase) = (api_key.trim(), secret.trim(), passphrase.trim());
+        if api_key.is_empty() || secret.is_empty() || passphrase.is_empty() {
+            return Err("empty INTX API key, secret or passphrase".to_string());
+        }
+        let secret = base64::decode(secret).ok_or_else(|| "INTX secret is not base64".to_string())?;
+        Ok(Self { api_key: api_key.to_string(), passphrase: passphrase.to_string(), key: hmac::Key::new(hmac::HMAC_SHA256, &secret) })
+    }
+
+    pub fn load(provider: &dyn SecretProvider) -> Result<Self, String> {
+        let [key, secret, passphrase] = lookup_all(provider, [KEY_VAR, SECRET_VAR, PASSPHRASE_VAR])?;
+        Self::new(&key, &secret, &passphrase)
+    }
+
+    /// base64(HMAC-SHA256(secret, prehash)).
+    pub fn sign(&self, prehash: &str) -> String {
+        base64::encode(hmac::sign(&self.key, prehash.as_bytes()).as_ref())
+    }
+
+    /// Signed `SUBSCRIBE` of `channels` for every product; `ts_secs` is Unix seconds.
+    pub fn subscribe_message(&self, products: &[&str], channels: &[&str], ts_secs: u64) -> String {
+        let list = |items: &[&str]| items.iter().map(|i| format!(r#""{}""#, i)).collect::<Vec<_>>().join(",");
+        let sign = self.sign(&format!("{}{}{}{}", ts_secs, self.api_key, SUBSCRIBE_SUFFIX, self.passphrase));
+        format!(r#"{{"type":"SUBSCRIBE","product_ids":[{}],"channels":[{}],"time":"{}","key":"{}","passphrase":"{}","signature":"{}"}}"#,
+            list(products), list(channels), ts_secs, self.api_key, self.passphrase, sign)
+    }
+
+    /// `LEVEL2` for every product.
+    pub fn subscribe_level2(&self, products: &[&str], ts_secs: u64) -> String {
+        self.subscribe_message(products, &[LEVEL2_CHANNEL], ts_secs)
+    }
+}
+
+/// One LEVEL2 message. Levels are kept as they came (`[px, qty]` in a snapshot,
+/// `[side, px, qty]` in an update) and applied by `apply`.
+#[derive(Debug, Clone, Copy)]
+pub struct Level2Update<'a> {
+    pub snapshot: bool,
+    pub sequence: u64,
+    bids: &'a [BorrowedValue<'a>],
+    asks: &'a [BorrowedValue<'a>],
+    changes: &'a [BorrowedValue<'a>],
+}
+
+fn level_num(level: &[BorrowedValue], i: usize) -> Option<f64> {
+    level.get(i).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok())
+}
+
+impl Level2Update<'_> {
+    /// Applies the message to `book` (cleared first on a snapshot). Check the sequence with a
+    /// `Level2Sequencer` before.
+    pub fn apply(&self, book: &mut L2OrderBook) {
+        if self.snapshot {
+            book.clear();
+            for (side, levels) in [(Side::Buy, self.bids), (Side::Sell, self.asks)] {
+                for level in levels.iter().filter_map(|l| l.as_array()) {
+                    if let (Some(px), Some(qty)) = (level_num(level, 0), level_num(level, 1)) {
+                        book.update(side, px, qty);
+                    }
+                }
+            }
+            book.snapshot_applied = true;
+            return;
+        }
+        for change in self.changes.iter().filter_map(|l| l.as_array()) {
+            let side = match change.first().and_then(|v| v.as_str()) {
+                Some("BUY") => Side::Buy,
+                Some("SELL") => Side::Sell,
+                _ => continue,
+            };
+            if let (Some(px), Some(qty)) = (level_num(change, 1), level_num(change, 2)) {
+                book.update(side, px, qty);
+            }
+        }
+    }
+}
+
+/// An update whose sequence is not the last one + 1: messages were lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub expected: u64,
+    pub got: u64,
+}
+
+/// Sequence of one product's LEVEL2 messages. Updates before the first snapshot and after a
+/// gap are refused until the next snapshot (resubscribe to get one).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Level2Sequencer {
+    last: Option<u64>,
+}
+
+impl Level2Sequencer {
+    /// Ok if `update` may be applied.
+    pub fn check(&mut self, update: &Level2Update) -> Result<(), SequenceGap> {
+        if update.snapshot {
+            self.last = Some(update.sequence);
+            return Ok(());
+        }
+        match self.last {
+            Some(last) if update.sequence == last + 1 => {
+                self.last = Some(update.sequence);
+                Ok(())
+            }
+            last => {
+                self.last = None;
+                Err(SequenceGap { expected: last.map_or(0, |l| l + 1), got: update.sequence })
+            }
+        }
+    }
+}
+
+/// Normalized INTX market data message.
+#[derive(Debug, Clone, Copy)]
+pub enum IntxEvent<'a> {
+    Subscribed,
+    /// `REJECT` of a subscribe (bad signature, unknown product, ...).
+    Rejected { reason: &'a str },
+    Level2 { product: &'a str, update: Level2Update<'a> },
+}
+
+/// Calls `on_event` for the event in one INTX market data message. Returns the number of
+/// events; other channels yield `Ok(0)`.
+pub fn parse_message<F: FnMut(IntxEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    let kind = str_field(&tape, "type");
+    match kind {
+        "SUBSCRIBED" => on_event(IntxEvent::Subscribed),
+        "REJECT" => on_event(IntxEvent::Rejected { reason: Some(str_field(&tape, "reason")).filter(|r| !r.is_empty()).unwrap_or(str_field(&tape, "message")) }),
+        "SNAPSHOT" | "UPDATE" if str_field(&tape, "channel") == LEVEL2_CHANNEL => on_event(IntxEvent::Level2 {
+            product: str_field(&tape, "product_id"),
+            update: Level2Update {
+                snapshot: kind == "SNAPSHOT",
+                sequence: u64_field(&tape, "sequence").ok_or(ParseError::Schema("intx: no sequence"))?,
+                bids: list(&tape, "bids"),
+                asks: list(&tape, "asks"),
+                changes: list(&tape, "changes"),
+            },
+        }),
+        _ => return Ok(0),
+    }
+    Ok(1)
+}
+
+/// PostOnly GTC limit order. `side` is "Buy" | "Sell" like everywhere else in the bot.
+#[derive(Debug, Clone, Copy)]
+pub struct IntxOrder<'a> {
+    pub instrument: &'a str,
+    pub client_order_id: &'a str,
+    pub side: &'a str,
+    pub price: f64,
+    /// Base coin.
+    pub size: f64,
+    pub price_dp: usize,
+    pub size_dp: usize,
+}
+
+/// Body of POST /api/v1/orders.
+pub fn order_body(portfolio: &str, order: &IntxOrder) -> String {
+    let side = if order.side == "Buy" { "BUY" } else { "SELL" };
+    format!(r#"{{"client_order_id":"{}","side":"{}","size":"{:.*}","tif":"GTC","instrument":"{}","type":"LIMIT","price":"{:.*}","portfolio":"{}","post_only":true}}"#,
+        order.client_order_id, side, order.size_dp, order.size, order.instrument, order.price_dp, order.price, portfolio)
+}
+
+/// Body of PUT /api/v1/orders/{id}: only the fields that change, as (value, decimals).
+pub fn modify_body(portfolio: &str, price: Option<(f64, usize)>, size: Option<(f64, usize)>) -> String {
+    let mut body = format!(r#"{{"portfolio":"{}""#, portfolio);
+    if let Some((px, dp)) = price {
+        body.push_str(&format!(r#","price":"{:.*}""#, dp, px));
+    }
+    if let Some((size, dp)) = size {
+        body.push_str(&format!(r#","size":"{:.*}""#, dp, size));
+    }
+    body.push('}');
+    body
+}
+
+/// Exchange id of an order response, or the error INTX answered with.
+pub fn parse_order_response(resp: &mut [u8]) -> Result<String, String> {
+    let json = simd_json::to_borrowed_value(resp).map_err(|_| "Failed to parse response JSON".to_string())?;
+    match json.get("order_id").and_then(|v| v.as_str().map(str::to_string).or_else(|| v.as_u64().map(|id| id.to_string()))) {
+        Some(id) => Ok(id),
+        None => {
+            let title = Some(str_field(&json, "title")).filter(|t| !t.is_empty()).unwrap_or(str_field(&json, "message"));
+            Err(format!("INTX error: {}", if title.is_empty() { "no order_id in response" } else { title }))
+        }
+    }
+}
+
+/// Blocking INTX REST order entry (ureq). Cold side only.
+pub struct IntxRestClient {
+    signer: EcSigner,
+    host: String,
+    portfolio: String,
+}
+
+impl IntxRestClient {
+    pub fn new(signer: EcSigner, portfolio: &str, sandbox: bool) -> Self {
+        let host = if sandbox { INTX_SANDBOX_REST_HOST } else { INTX_REST_HOST };
+        Self { signer, host: host.to_string(), portfolio: portfolio.to_string() }
+    }
+
+    /// CDP key from INTX_CDP_KEY_NAME / INTX_CDP_PRIVATE_KEY (PEM) of `provider`.
+    pub fn load_signer(provider: &dyn SecretProvider) -> Result<EcSigner, String> {
+        let [name, pem] = lookup_all(provider, [CDP_KEY_NAME_VAR, CDP_PRIVATE_KEY_VAR])?;
+        EcSigner::from_pem(&name, &pem)
+    }
+
+    /// New PostOnly order; Ok(exchange order id).
+    pub fn create_order(&self, order: &IntxOrder) -> Result<String, String> {
+        self.send("POST", "/api/v1/orders", None, Some(&order_body(&self.portfolio, order)))
+    }
+
+    /// Price and/or size of order `id` (exchange id or client_order_id).
+    pub fn modify_order(&self, id: &str, price: Option<(f64, usize)>, size: Option<(f64, usize)>) -> Result<String, String> {
+        self.send("PUT", &format!("/api/v1/orders/{}", id), None, Some(&modify_body(&self.portfolio, price, size)))
+    }
+
+    pub fn cancel_order(&self, id: &str) -> Result<String, String> {
+        self.send("DELETE", &format!("/api/v1/orders/{}", id), Some(&format!("portfolio={}", self.portfolio)), None)
+    }
+
+    /// Signs a fresh JWT for method + host + path (the query is not part of it) and sends.
+    fn send(&self, method: &str, path: &str, query: Option<&str>, body: Option<&str>) -> Result<String, String> {
+        let jwt = self.signer.jwt(&format!("{} {}{}", method, self.host, path), time_sync::wall_ms() / 1000)?;
+        let url = match query {
+            Some(query) => format!("https://{}{}?{}", self.host, path, query),
+            None => format!("https://{}{}", self.host, path),
+        };
+        let request = ureq::request(method, &url).set("Authorization", &format!("Bearer {}", jwt)).set("Content-Type", "application/json");
+        let result = match body {
+            Some(body) => request.send_string(body),
+            None => request.call(),
+        };
+        let text = match result {
+            Ok(response) => response.into_string().map_err(|e| format!("Failed to read response: {}", e))?,
+            // 4xx / 5xx still carry the error body
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                return Err(match parse_order_response(&mut body.clone().into_bytes()) {
+                    Err(e) => format!("HTTP error: status code {}: {}", status, e),
+                    Ok(_) => format!("HTTP error: status code {}", status),
+                });
+            }
+            Err(e) => return Err(format!("HTTP error: {}", e)),
+        };
+        debug!(orders, "INTX: {} {} -> {}", method, path, text);
+        parse_order_response(&mut text.into_bytes())
+    }
+}
@@ -0,0 +1,431 @@
+use std::io;
+use std::time::Duration;
+use ring::hmac;
+use simd_json::prelude::*;
+use simd_json::BorrowedValue;
+use crate::auth::secrets::{lookup_all, redacted_debug, SecretProvider};
+use crate::core::logging::info;
+use crate::core::orderbook::{L2OrderBook, Side};
+use crate::core::parser::{num_field, str_field, u64_field, ParseError};
+use crate::core::time_sync;
+use crate::net::adapter::{ConnectionState, ExchangeAdapter, WsConnection};
+use crate::net::ws_client::{WsClient, WsClientBuilder};
+use crate::strategy::fill::Fill;
+
+// Deribit V2 connector: JSON-RPC 2.0 over one WebSocket (market data, login and order entry
+// share the connection), on the same WsClient (handshake, TLS, framing, auto-ping) as Bybit.
+// Every request carries a numeric `id` echoed by its response; pushes arrive as
+// `method: "subscription"` notifications. Where it departs from Bybit:
+//   * login is `public/auth` with `grant_type: client_signature`: hex HMAC-SHA256 of
+//     `timestamp + "\n" + nonce + "\n" + data` (data empty), timestamp in ms. The secret
+//     never goes over the wire;
+//   * `book.{instrument}.100ms` is incremental: a snapshot, then changes chained by
+//     `prev_change_id` -> `change_id`. A broken chain means the book is wrong until the
+//     channel is resubscribed (`BookSequencer`);
+//   * keepalive is `public/set_heartbeat`: the server sends `test_request` heartbeats that
+//     must be answered with `public/test`, or it closes the connection;
+//   * `amount` is USD for inverse perpetuals and futures (multiples of the contract size),
+//     base coin for options and linear contracts; fees are in the fee currency, positive
+//     when charged, negative for a rebate (same sign as Bybit);
+//   * orders are addressed by `label` (our client id, up to 64 chars): `edit_by_label`,
+//     `cancel_by_label`.
+// In the poll loop the connection is an `ExchangeAdapter` (`DeribitConnection`).
+pub const DERIBIT_HOST: &str = "www.deribit.com";
+pub const DERIBIT_TEST_HOST: &str = "test.deribit.com";
+pub const DERIBIT_PATH: &str = "/ws/api/v2";
+pub const DERIBIT_PING_INTERVAL: Duration = Duration::from_secs(25);
+// Keepalive of the auto-ping; its response (id 0) is ignored
+pub const DERIBIT_PING_MSG: &str = r#"{"jsonrpc":"2.0","id":0,"method":"public/test","params":{}}"#;
+pub const HEARTBEAT_INTERVAL_SECS: u32 = 30;
+pub const CLIENT_ID_VAR: &str = "DERIBIT_CLIENT_ID";
+pub const CLIENT_SECRET_VAR: &str = "DERIBIT_CLIENT_SECRET";
+// Deribit error code of post_only orders that would cross (reject_post_only: true)
+pub const POST_ONLY_REJECT: i64 = 11054;
+
+/// The single connection (mainnet, or test.deribit.com with `testnet`).
+pub fn builder(testnet: bool) -> WsClientBuilder {
+    let host = if testnet { DERIBIT_TEST_HOST } else { DERIBIT_HOST };
+    WsClientBuilder::new(host).path(DERIBIT_PATH).auto_ping(DERIBIT_PING_INTERVAL, Some(DERIBIT_PING_MSG))
+}
+
+/// Deribit API client id and the HMAC key of its secret.
+#[derive(Clone)]
+pub struct DeribitCredentials {
+    pub client_id: String,
+    key: hmac::Key,
+}
+
+redacted_debug!(DeribitCredentials { client_id });
+
+impl DeribitCredentials {
+    pub fn new(client_id: &str, secret: &str) -> Result<Self, String> {
+        let (client_id, secret) = (client_id.trim(), secret.trim());
+        if client_id.is_empty() || secret.is_empty() {
+            return Err("empty Deribit client id or secret".to_string());
+        }
+        Ok(Self { client_id: client_id.to_string(), key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()) })
+    }
+
+    pub fn load(provider: &dyn SecretProvider) -> Result<Self, String> {
+        let [id, secret] = lookup_all(provider, [CLIENT_ID_VAR, CLIENT_SECRET_VAR])?;
+        Self::new(&id, &secret)
+    }
+
+    /// hex(HMAC-SHA256(secret, "{ts_ms}\n{nonce}\n{data}")).
+    pub fn sign(&self, ts_ms: u64, nonce: &str, data: &str) -> String {
+        hex::encode(hmac::sign(&self.key, format!("{}\n{}\n{}", ts_ms, nonce, data).as_bytes()).as_ref())
+    }
+
+    /// `public/auth` with a client signature; `nonce` is any fresh string (alphanumeric).
+    pub fn auth_message(&self, id: u64, ts_ms: u64, nonce: &str) -> String {
+        format!(r#"{{"jsonrpc":"2.0","id":{},"method":"public/auth","params":{{"grant_type":"client_signature","client_id":"{}","timestamp":{},"nonce":"{}","data":"","signature":"{}"}}}}"#,
+            id, self.client_id, ts_ms, nonce, self.sign(ts_ms, nonce, ""))
+    }
+}
+
+fn channels(list: impl Iterator<Item = String>) -> String {
+    list.map(|c| format!(r#""{}""#, c)).collect::<Vec<_>>().join(",")
+}
+
+/// `book.{instrument}.100ms` for every instrument, one request.
+pub fn subscribe_books(id: u64, instruments: &[&str]) -> String {
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"public/subscribe","params":{{"channels":[{}]}}}}"#,
+        id, channels(instruments.iter().map(|i| format!("book.{}.100ms", i))))
+}
+
+/// `user.orders` and `user.trades` of every instrument (raw: every change), after `public/auth`.
+pub fn subscribe_private(id: u64, instruments: &[&str]) -> String {
+    let list = instruments.iter().flat_map(|i| [format!("user.orders.{}.raw", i), format!("user.trades.{}.raw", i)]);
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"private/subscribe","params":{{"channels":[{}]}}}}"#, id, channels(list))
+}
+
+/// Asks the server for `test_request` heartbeats every `interval_secs` (10 at least).
+pub fn set_heartbeat_message(id: u64, interval_secs: u32) -> String {
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"public/set_heartbeat","params":{{"interval":{}}}}}"#, id, interval_secs.max(10))
+}
+
+/// Answer to a `test_request` heartbeat.
+pub fn test_message(id: u64) -> String {
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"public/test","params":{{}}}}"#, id)
+}
+
+/// The single connection as an `ExchangeAdapter`: `public/auth` once upgraded, then, on its
+/// answer, `public/set_heartbeat` and the book + private subscriptions. Every parsed event
+/// goes through `on_event`, which also answers the server's `test_request` heartbeats.
+pub struct DeribitConnection {
+    conn: WsConnection,
+    credentials: DeribitCredentials,
+    instruments: Vec<String>,
+    next_id: u64,
+    /// `id` of the last `public/auth` sent; 0 once it was answered.
+    auth_id: u64,
+}
+
+impl DeribitConnection {
+    pub fn new(client: WsClient, credentials: DeribitCredentials, instruments: &[&str]) -> Self {
+        Self {
+            conn: WsConnection::new(client, "deribit", ConnectionState::Authenticating),
+            credentials,
+            instruments: instruments.iter().map(|i| i.to_string()).collect(),
+            // 0 is the auto-ping's
+            next_id: 1,
+            auth_id: 0,
+        }
+    }
+
+    /// Fresh request id for an order / edit / cancel on this connection.
+    pub fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Logged in on the current connection (auth answered, subscriptions sent).
+    pub fn authenticated(&self) -> bool {
+        self.auth_id == 0 && self.conn.state == ConnectionState::Active
+    }
+
+    /// Feed every parsed event here. The answer to our `public/auth` sends `set_heartbeat` and
+    /// the subscriptions; a `test_request` gets its `public/test`. A refused login is an
+    /// error: the owner reconnects.
+    pub fn on_event(&mut self, event: &DeribitEvent) -> io::Result<()> {
+        match *event {
+            DeribitEvent::Response { id, code, msg, .. } if self.auth_id != 0 && id == self.auth_id => {
+                if code != 0 {
+                    return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("deribit auth refused ({}): {}", code, msg)));
+                }
+                self.auth_id = 0;
+                let ids = [self.next_id(), self.next_id(), self.next_id()];
+                let instruments: Vec<&str> = self.instruments.iter().map(String::as_str).collect();
+                let messages = [
+                    set_heartbeat_message(ids[0], HEARTBEAT_INTERVAL_SECS),
+                    subscribe_books(ids[1], &instruments),
+                    subscribe_private(ids[2], &instruments),
+                ];
+                info!(net, "HOT: Deribit authenticated, subscribing {:?}", self.instruments);
+                messages.iter().try_for_each(|msg| self.conn.send_text(msg))
+            }
+            DeribitEvent::TestRequest => {
+                let id = self.next_id();
+                self.conn.send_text(&test_message(id))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ExchangeAdapter for DeribitConnection {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        info!(net, "HOT: Authenticating Deribit WS...");
+        self.auth_id = self.next_id();
+        let ts_ms = time_sync::wall_ms();
+        Some(self.credentials.auth_message(self.auth_id, ts_ms, &format!("{:x}{}", ts_ms, self.auth_id)))
+    }
+}
+
+/// PostOnly limit order (rejected, not repriced, if it would cross). `side` is "Buy" |
+/// "Sell" like everywhere else in the bot.
+#[derive(Debug, Clone, Copy)]
+pub struct DeribitOrder<'a> {
+    pub instrument: &'a str,
+    pub label: &'a str,
+    pub side: &'a str,
+    pub price: f64,
+    /// USD for inverse contracts, base coin for options / linear.
+    pub amount: f64,
+    /// Decimals of the price / amount grid.
+    pub price_dp: usize,
+    pub amount_dp: usize,
+    pub reduce_only: bool,
+}
+
+/// `private/buy` | `private/sell`; the response comes back with the same `id`.
+pub fn order_message(id: u64, order: &DeribitOrder) -> String {
+    let method = if order.side == "Buy" { "private/buy" } else { "private/sell" };
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{{"instrument_name":"{}","amount":{:.*},"type":"limit","price":{:.*},"post_only":true,"reject_post_only":true,"reduce_only":{},"label":"{}"}}}}"#,
+        id, method, order.instrument, order.amount_dp, order.amount, order.price_dp, order.price, order.reduce_only, order.label)
+}
+
+/// `private/edit_by_label`: Deribit wants the amount on every edit, the price only if it changes
+/// (as (value, decimals)).
+pub fn edit_message(id: u64, instrument: &str, label: &str, amount: (f64, usize), price: Option<(f64, usize)>) -> String {
+    let price = price.map(|(px, dp)| format!(r#","price":{:.*}"#, dp, px)).unwrap_or_default();
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"private/edit_by_label","params":{{"instrument_name":"{}","label":"{}","amount":{:.*}{},"post_only":true,"reject_post_only":true}}}}"#,
+        id, instrument, label, amount.1, amount.0, price)
+}
+
+/// `private/cancel_by_label` (every open order of ours with that label on the instrument).
+pub fn cancel_message(id: u64, instrument: &str, label: &str) -> String {
+    format!(r#"{{"jsonrpc":"2.0","id":{},"method":"private/cancel_by_label","params":{{"label":"{}","instrument_name":"{}"}}}}"#, id, label, instrument)
+}
+
+/// One `book.{instrument}.100ms` push. Levels are `[action, price, amount]` with action
+/// `new` | `change` | `delete`; a snapshot has only `new`.
+#[derive(Debug, Clone, Copy)]
+pub struct DeribitBookUpdate<'a> {
+    pub snapshot: bool,
+    pub change_id: u64,
+    /// 0 on a snapshot.
+    pub prev_change_id: u64,
+    pub ts: u64,
+    bids: &'a [BorrowedValue<'a>],
+    asks: &'a [BorrowedValue<'a>],
+}
+
+impl DeribitBookUpdate<'_> {
+    /// Applies the levels to `book` (cleared first on a snapshot). Check the chain with a
+    /// `BookSequencer` before.
+    pub fn apply(&self, book: &mut L2OrderBook) {
+        if self.snapshot {
+            book.clear();
+        }
+        for (side, levels) in [(Side::Buy, self.bids), (Side::Sell, self.asks)] {
+            for level in levels.iter().filter_map(|l| l.as_array()) {
+                let action = level.first().and_then(|v| v.as_str()).unwrap_or("");
+                let (Some(px), Some(amount)) = (level.get(1).and_then(|v| v.cast_f64()), level.get(2).and_then(|v| v.cast_f64())) else { continue };
+                book.update(side, px, if action == "delete" { 0.0 } else { amount });
+            }
+        }
+        if self.snapshot {
+            book.snapshot_applied = true;
+        }
+    }
+}
+
+/// A change whose `prev_change_id` is not the last `change_id` seen: updates were lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookGap {
+    pub expected: u64,
+    pub got: u64,
+}
+
+/// Change-id chain of one instrument's book. Changes before the first snapshot and after a
+/// gap are refused until the next snapshot (resubscribe to get one).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BookSequencer {
+    last: Option<u64>,
+}
+
+impl BookSequencer {
+    /// Ok if `update` may be applied.
+    pub fn check(&mut self, update: &DeribitBookUpdate) -> Result<(), BookGap> {
+        if update.snapshot {
+            self.last = Some(update.change_id);
+            return Ok(());
+        }
+        match self.last {
+            Some(last) if last == update.prev_change_id => {
+                self.last = Some(update.change_id);
+                Ok(())
+            }
+            last => {
+                self.last = None;
+                Err(BookGap { expected: last.unwrap_or(0), got: update.prev_change_id })
+            }
+        }
+    }
+}
+
+/// One order of `user.orders.{instrument}.raw`.
+#[derive(Debug, Clone, Copy)]
+pub struct DeribitOrderUpdate<'a> {
+    pub instrument: &'a str,
+    pub order_id: &'a str,
+    pub label: &'a str,
+    pub side: &'static str,
+    pub price: f64,
+    pub amount: f64,
+    pub filled_amount: f64,
+    /// open | filled | rejected | cancelled | untriggered
+    pub state: &'a str,
+}
+
+/// Normalized Deribit message.
+#[derive(Debug, Clone, Copy)]
+pub enum DeribitEvent<'a> {
+    /// Answer to request `id`: `code` 0 = success, else the JSON-RPC error code (`msg` its
+    /// message). `label` is the order's label for order methods, "" otherwise.
+    Response { id: u64, code: i64, msg: &'a str, label: &'a str },
+    /// The server wants a `public/test` now.
+    TestRequest,
+    Heartbeat,
+    Book { instrument: &'a str, update: DeribitBookUpdate<'a> },
+    Order(DeribitOrderUpdate<'a>),
+    /// One of our executions (fee in the fee currency, Bybit's sign).
+    Trade { instrument: &'a str, label: &'a str, fill: Fill },
+}
+
+fn side_of(direction: &str) -> Option<&'static str> {
+    match direction {
+        "buy" => Some("Buy"),
+        "sell" => Some("Sell"),
+        _ => None,
+    }
+}
+
+fn order_update<'v>(item: &'v BorrowedValue<'v>) -> Option<DeribitOrderUpdate<'v>> {
+    Some(DeribitOrderUpdate {
+        instrument: str_field(item, "instrument_name"),
+        order_id: str_field(item, "order_id"),
+        label: str_field(item, "label"),
+        side: side_of(str_field(item, "direction"))?,
+        price: num_field(item, "price").unwrap_or(0.0),
+        amount: num_field(item, "amount").unwrap_or(0.0),
+        filled_amount: num_field(item, "filled_amount").unwrap_or(0.0),
+        state: str_field(item, "order_state"),
+    })
+}
+
+fn trade_fill(item: &BorrowedValue) -> Option<Fill> {
+    Some(Fill {
+        side: side_of(str_field(item, "direction"))?,
+        qty: num_field(item, "amount").unwrap_or(0.0),
+        px: num_field(item, "price").unwrap_or(0.0),
+        fee: num_field(item, "fee").unwrap_or(0.0),
+        // Not reported per trade; the fee currency differs by contract
+        fee_rate: 0.0,
+        is_maker: str_field(item, "liquidity") == "M",
+        closed_pnl: 0.0,
+        exec_time_ms: u64_field(item, "timestamp").unwrap_or(0),
+        seq: u64_field(item, "trade_seq").unwrap_or(0),
+    })
+}
+
+fn level_list<'v>(data: &'v BorrowedValue<'v>, key: &str) -> &'v [BorrowedValue<'v>] {
+    data.get(key).and_then(|v| v.as_array()).map(|a| a.as_slice()).unwrap_or(&[])
+}
+
+/// Items of a push: `data` is an object on `raw` order channels, an array elsewhere.
+fn items<'v>(data: &'v BorrowedValue<'v>) -> &'v [BorrowedValue<'v>] {
+    match data.as_array() {
+        Some(list) => list.as_slice(),
+        None => std::slice::from_ref(data),
+    }
+}
+
+/// Calls `on_event` for each event in one Deribit text message. Returns the number of
+/// events; notifications of other channels yield `Ok(0)`.
+pub fn parse_message<F: FnMut(DeribitEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    if let Some(id) = tape.get("id").and_then(|v| v.as_u64()) {
+        let (code, msg) = match tape.get("error") {
+            Some(error) => (error.get("code").and_then(|v| v.as_i64()).unwrap_or(-1), str_field(error, "message")),
+            None => (0, ""),
+        };
+        let label = tape.get("result").and_then(|r| r.get("order")).map(|o| str_field(o, "label")).unwrap_or("");
+        on_event(DeribitEvent::Response { id, code, msg, label });
+        return Ok(1);
+    }
+    let params = tape.get("params").ok_or(ParseError::Schema("deribit: no params"))?;
+    match str_field(&tape, "method") {
+        "heartbeat" => {
+            on_event(if str_field(params, "type") == "test_request" { DeribitEvent::TestRequest } else { DeribitEvent::Heartbeat });
+            return Ok(1);
+        }
+        "subscription" => {}
+        _ => return Ok(0),
+    }
+    let channel = str_field(params, "channel");
+    let data = params.get("data").ok_or(ParseError::Schema("deribit: no data"))?;
+    // book.{instrument}.100ms | user.orders.{instrument}.raw | user.trades.{instrument}.raw
+    let mut parts = channel.split('.');
+    let mut n = 0;
+    match (parts.next().unwrap_or(""), parts.next().unwrap_or("")) {
+        ("book", _) => {
+            let update = DeribitBookUpdate {
+                snapshot: str_field(data, "type") == "snapshot",
+                change_id: u64_field(data, "change_id").unwrap_or(0),
+                prev_change_id: u64_field(data, "prev_change_id").unwrap_or(0),
+                ts: u64_field(data, "timestamp").unwrap_or(0),
+                bids: level_list(data, "bids"),
+                asks: level_list(data, "asks"),
+            };
+            on_event(DeribitEvent::Book { instrument: str_field(data, "instrument_name"), update });
+            n += 1;
+        }
+        ("user", "orders") => {
+            for update in items(data).iter().filter_map(order_update) {
+                on_event(DeribitEvent::Order(update));
+                n += 1;
+            }
+        }
+        ("user", "trades") => {
+            for item in items(data) {
+                if let Some(fill) = trade_fill(item) {
+                    on_event(DeribitEvent::Trade { instrument: str_field(item, "instrument_name"), label: str_field(item, "label"), fill });
+                    n += 1;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(n)
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use crate::core::orderbook::L2OrderBook;
+    use crate::net::adapter::{ConnectionState, ExchangeAdapter};
+    use crate::net::deribit::{self, BookGap, BookSequencer, DeribitConnection, DeribitCredentials, DeribitEvent, DeribitOrder};
+    use crate::net::tls_client::{self, CertPins};
+
+    #[test]
+    fn auth_and_order_messages() {
+        let creds = DeribitCredentials::new("AMANDA", "AMANDASECRECT").unwrap();
+        // Example of the Deribit API docs (client_signature)
+        assert_eq!(creds.sign(1576074319000, "1iqt2wls", ""), "56590594f97921b09b18f166befe0d1319b198bbcdad7ca73382de2f88fe9aa1");
+        assert!(creds.auth_message(1, 1576074319000, "1iqt2wls").contains(r#""client_id":"AMANDA","timestamp":1576074319000,"nonce":"1iqt2wls","data":"","signature":"56590594"#));
+        assert!(!format!("{:?}", creds).contains("SECRECT"));
+
+        let order = DeribitOrder { instrument: "BTC-PERPETUAL", label: "mm0b1", side: "Sell", price: 64000.5, amount: 100.0, price_dp: 1, amount_dp: 0, reduce_only: false };
+        assert_eq!(deribit::order_message(7, &order),
+            r#"{"jsonrpc":"2.0","id":7,"method":"private/sell","params":{"instrument_name":"BTC-PERPETUAL","amount":100,"type":"limit","price":64000.5,"post_only":true,"reject_post_only":true,"reduce_only":false,"label":"mm0b1"}}"#);
+        assert!(deribit::edit_message(8, "BTC-PERPETUAL", "mm0b1", (100.0, 0), None).contains(r#""label":"mm0b1","amount":100,"post_only""#));
+        assert!(deribit::subscribe_private(9, &["BTC-PERPETUAL"]).contains(r#"["user.orders.BTC-PERPETUAL.raw","user.trades.BTC-PERPETUAL.raw"]"#));
+    }
+
+    #[test]
+    fn book_chain_is_checked_and_applied() {
+        let mut book = L2OrderBook::new();
+        let mut sequencer = BookSequencer::default();
+        let mut msgs = [
+            br#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"book.BTC-PERPETUAL.100ms","data":{"type":"snapshot","timestamp":1700000000000,"instrument_name":"BTC-PERPETUAL","change_id":100,"bids":[["new",64000.0,5000.0],["new",63999.5,1000.0]],"asks":[["new",64000.5,2000.0]]}}}"#.to_vec(),
+            br#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"book.BTC-PERPETUAL.100ms","data":{"type":"change","timestamp":1700000000100,"instrument_name":"BTC-PERPETUAL","prev_change_id":100,"change_id":101,"bids":[["delete",64000.0,0.0]],"asks":[["change",64000.5,300.0]]}}}"#.to_vec(),
+            // 102 lost
+            br#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"book.BTC-PERPETUAL.100ms","data":{"type":"change","timestamp":1700000000300,"instrument_name":"BTC-PERPETUAL","prev_change_id":102,"change_id":103,"bids":[],"asks":[]}}}"#.to_vec(),
+        ];
+        let mut results = Vec::new();
+        for msg in msgs.iter_mut() {
+            deribit::parse_message(msg, |event| if let DeribitEvent::Book { instrument, update } = event {
+                assert_eq!(instrument, "BTC-PERPETUAL");
+                let checked = sequencer.check(&update);
+                if checked.is_ok() {
+                    update.apply(&mut book);
+                }
+                results.push(checked);
+            }).unwrap();
+        }
+        assert_eq!(results, [Ok(()), Ok(()), Err(BookGap { expected: 101, got: 102 })]);
+        assert!(book.snapshot_applied);
+        assert_eq!((book.bids[0].price, book.asks[0].price, book.asks[0].qty), (63999.5, 64000.5, 300.0));
+    }
+
+    #[test]
+    fn parses_private_pushes_responses_and_heartbeats() {
+        let mut events = Vec::new();
+        let mut msg = br#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"user.trades.BTC-PERPETUAL.raw","data":[{"trade_id":"1","trade_seq":42,"instrument_name":"BTC-PERPETUAL","order_id":"9","label":"mm0b1","direction":"buy","price":64000.0,"amount":100.0,"fee":-0.00000039,"fee_currency":"BTC","liquidity":"M","timestamp":1700000000200}]}}"#.to_vec();
+        deribit::parse_message(&mut msg, |event| if let DeribitEvent::Trade { instrument, label, fill } = event {
+            assert_eq!((instrument, label, fill.side, fill.qty, fill.is_maker, fill.seq), ("BTC-PERPETUAL", "mm0b1", "Buy", 100.0, true, 42));
+            assert!(fill.fee < 0.0);
+            events.push("trade");
+        }).unwrap();
+        let mut msg = br#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"user.orders.BTC-PERPETUAL.raw","data":{"order_id":"9","label":"mm0b1","instrument_name":"BTC-PERPETUAL","direction":"buy","price":64000.0,"amount":200.0,"filled_amount":100.0,"order_state":"open"}}}"#.to_vec();
+        deribit::parse_message(&mut msg, |event| if let DeribitEvent::Order(update) = event {
+            assert_eq!((update.label, update.side, update.filled_amount, update.state), ("mm0b1", "Buy", 100.0, "open"));
+            events.push("order");
+        }).unwrap();
+        let mut msg = br#"{"jsonrpc":"2.0","id":7,"error":{"message":"post_only_reject","code":11054}}"#.to_vec();
+        deribit::parse_message(&mut msg, |event| if let DeribitEvent::Response { id: 7, code: deribit::POST_ONLY_REJECT, .. } = event { events.push("reject") }).unwrap();
+        let mut msg = br#"{"jsonrpc":"2.0","id":8,"result":{"order":{"label":"mm0b1","order_state":"open"},"trades":[]}}"#.to_vec();
+        deribit::parse_message(&mut msg, |event| if let DeribitEvent::Response { id: 8, code: 0, label: "mm0b1", .. } = event { events.push("ack") }).unwrap();
+        let mut msg = br#"{"jsonrpc":"2.0","method":"heartbeat","params":{"type":"test_request"}}"#.to_vec();
+        deribit::parse_message(&mut msg, |event| if let DeribitEvent::TestRequest = event { events.push("test") }).unwrap();
+        assert_eq!(events, ["trade", "order", "reject", "ack", "test"]);
+    }
+
+    #[test]
+    fn connection_logs_in_then_subscribes_and_answers_test_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = tls_client::client_config(CertPins::default()).unwrap();
+        let client = deribit::builder(true).connect_to(listener.local_addr().unwrap(), config).unwrap();
+        let _peer = listener.accept().unwrap();
+        let creds = DeribitCredentials::new("AMANDA", "AMANDASECRECT").unwrap();
+        let mut conn = DeribitConnection::new(client, creds, &["BTC-PERPETUAL"]);
+        assert_eq!(conn.state(), ConnectionState::HandshakeSending);
+
+        let auth = conn.setup_message(ConnectionState::Authenticating).unwrap();
+        assert!(auth.starts_with(r#"{"jsonrpc":"2.0","id":1,"method":"public/auth","params":{"grant_type":"client_signature","client_id":"AMANDA""#), "{}", auth);
+        conn.connection_mut().state = ConnectionState::Active;
+        assert!(!conn.authenticated());
+
+        // Someone else's answer, then a heartbeat: no login yet, the test request is answered
+        conn.on_event(&DeribitEvent::Response { id: 7, code: 0, msg: "", label: "" }).unwrap();
+        conn.on_event(&DeribitEvent::TestRequest).unwrap();
+        assert!(!conn.authenticated());
+        assert_eq!(conn.next_id(), 3);
+
+        // The auth answer: set_heartbeat, book and private subscriptions take ids 4..=6
+        conn.on_event(&DeribitEvent::Response { id: 1, code: 0, msg: "", label: "" }).unwrap();
+        assert!(conn.authenticated());
+        assert_eq!(conn.next_id(), 7);
+
+        // A reconnect logs in again; a refused login is an error for the owner
+        let auth = conn.setup_message(ConnectionState::Authenticating).unwrap();
+        assert!(auth.contains(r#""id":8,"#), "{}", auth);
+        assert!(!conn.authenticated());
+        let refused = conn.on_event(&DeribitEvent::Response { id: 8, code: 13004, msg: "invalid_credentials", label: "" }).unwrap_err();
+        assert!(refused.to_string().contains("invalid_credentials"), "{}", refused);
+    }
+}
@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+// Two Bybit public connections (different hosts, so different IPs and routes) subscribed to
+// the same topics. Every push carries a per-topic sequence number that both connections
+// share, so the first copy of each number is applied and the second is dropped: the faster
+// packet wins, and when one connection stalls or drops the other already delivered the same
+// data, so the book sees no gap. Each connection is TCP-ordered, so "higher than the last
+// applied" is enough; a connection can't skip a number the other delivered late.
+// Sequence per topic: `orderbook.*` — `data.u`, `tickers.*` — `cs`, `publicTrade.*` — `seq`
+// of the trades (`ts` on pushes without it). Messages without a topic (acks, pongs) pass.
+// Owned by the Hot Thread: reads the raw bytes before the in-place parse, no allocation
+// after the first message of each topic.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feed {
+    Primary,
+    Backup,
+}
+
+const FEEDS: [Feed; 2] = [Feed::Primary, Feed::Backup];
+
+impl Feed {
+    pub fn name(self) -> &'static str {
+        match self {
+            Feed::Primary => "primary",
+            Feed::Backup => "backup",
+        }
+    }
+}
+
+/// What to do with one public message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// First copy: apply.
+    Fresh,
+    /// The other feed delivered it already, or the book waits for a snapshot: drop.
+    Stale,
+    /// No topic / sequence (subscribe ack, pong): not deduplicated.
+    Unsequenced,
+}
+
+#[derive(Debug)]
+struct Topic {
+    name: Box<[u8]>,
+    // None: resyncing, only a snapshot is applied
+    last: Option<u64>,
+}
+
+pub struct FeedArbiter {
+    /// A feed quiet this long while the other delivers is reconnected.
+    pub silence: Duration,
+    /// Messages applied from each feed (index: `Feed as usize`): who is faster.
+    pub wins: [u64; 2],
+    /// Copies dropped.
+    pub duplicates: u64,
+    topics: Vec<Topic>,
+    up: [bool; 2],
+    // None: not watched (before `arm`, or tripped and not yet re-armed)
+    last_message: [Option<Instant>; 2],
+}
+
+impl FeedArbiter {
+    pub fn new(silence: Duration) -> Self {
+        Self { silence, wins: [0; 2], duplicates: 0, topics: Vec::new(), up: [false; 2], last_message: [None; 2] }
+    }
+
+    /// Subscribed. True when it is the first live feed (the public stream as a whole is up).
+    pub fn on_connect(&mut self, feed: Feed) -> bool {
+        let first = !self.up.iter().any(|&up| up);
+        self.up[feed as usize] = true;
+        first
+    }
+
+    /// Connection closed. True when no feed is left (the public stream as a whole is down).
+    /// Still watched: it goes silent and `poll` reports it for a reconnect.
+    pub fn on_down(&mut self, feed: Feed) -> bool {
+        self.up[feed as usize] = false;
+        !self.up.iter().any(|&up| up)
+    }
+
+    /// Connection opened (startup or reconnect): silence is counted from `now`.
+    pub fn arm(&mut self, feed: Feed, now: Instant) {
+        self.last_message[feed as usize] = Some(now);
+    }
+
+    pub fn is_up(&self, feed: Feed) -> bool {
+        self.up[feed as usize]
+    }
+
+    /// Before the in-place parse of a message from `feed`.
+    pub fn on_message(&mut self, feed: Feed, payload: &[u8], now: Instant) -> Verdict {
+        if self.last_message[feed as usize].is_some() {
+            self.last_message[feed as usize] = Some(now);
+        }
+        let Some((topic, seq)) = sequence(payload) else { return Verdict::Unsequenced };
+        let snapshot = field(payload, b"type") == Some(b"snapshot");
+        let index = match self.topics.iter().position(|t| &*t.name == topic) {
+            Some(index) => index,
+            None => {
+                self.topics.push(Topic { name: topic.into(), last: None });
+                self.topics.len() - 1
+            }
+        };
+        let last = &mut self.topics[index].last;
+        let fresh = match *last {
+            // Unknown topic or resync: a book needs its snapshot first
+            None => snapshot || !topic.starts_with(b"orderbook."),
+            // u=1 snapshot: Bybit restarted the book service and the numbering with it
+            Some(_) if snapshot && seq == 1 => true,
+            Some(prev) => seq > prev,
+        };
+        if fresh {
+            *last = Some(seq);
+            self.wins[feed as usize] += 1;
+            Verdict::Fresh
+        } else {
+            self.duplicates += 1;
+            Verdict::Stale
+        }
+    }
+
+    /// Book of `symbol` cleared for a resync: its deltas are dropped until a snapshot lands
+    /// on either feed.
+    pub fn resync(&mut self, symbol: &[u8]) {
+        let of_symbol = |name: &[u8]| name.starts_with(b"orderbook.") && name.strip_suffix(symbol).is_some_and(|head| head.ends_with(b"."));
+        for topic in self.topics.iter_mut().filter(|t| of_symbol(&t.name)) {
+            topic.last = None;
+        }
+    }
+
+    /// Every book cleared (both feeds reconnected): start over.
+    pub fn reset(&mut self) {
+        self.topics.clear();
+    }
+
+    /// Once per loop iteration. Returns a feed silent past `silence` while the other one
+    /// delivers (both silent is FeedWatchdog's call) and stops watching it until it is re-armed.
+    pub fn poll(&mut self, now: Instant) -> Option<(Feed, Duration)> {
+        let quiet = |last: Option<Instant>| last.map(|t| now.saturating_duration_since(t));
+        for feed in FEEDS {
+            let other = FEEDS[1 - feed as usize];
+            let Some(silent) = quiet(self.last_message[feed as usize]) else { continue };
+            let other_live = quiet(self.last_message[other as usize]).is_some_and(|s| s < self.silence);
+            if silent >= self.silence && other_live {
+                self.last_message[feed as usize] = None;
+                return Some((feed, silent));
+            }
+        }
+        None
+    }
+}
+
+/// Topic and sequence number of a public push, from the raw bytes.
+pub fn sequence(payload: &[u8]) -> Option<(&[u8], u64)> {
+    let topic = field(payload, b"topic")?;
+    let seq = if topic.starts_with(b"orderbook.") {
+        field(payload, b"u")
+    } else if topic.starts_with(b"tickers.") {
+        field(payload, b"cs")
+    } else if topic.starts_with(b"publicTrade.") {
+        field(payload, b"seq").or_else(|| field(payload, b"ts"))
+    } else {
+        None
+    }?;
+    Some((topic, std::str::from_utf8(seq).ok()?.parse().ok()?))
+}
+
+/// Value of the first `"key":` in `payload`, without quotes. Enough for Bybit's flat
+/// envelopes; not a JSON parser.
+fn field<'a>(payload: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let mut from = 0;
+    while let Some(at) = payload[from..].windows(key.len() + 2).position(|w| w[0] == b'"' && &w[1..=key.len()] == key && w[key.len() + 1] == b'"') {
+        from += at + key.len() + 2;
+        // A value equal to the key ("S":"u") is not the key
+        let Some(rest) = payload[from..].strip_prefix(b":") else { continue };
+        if let Some(quoted) = rest.strip_prefix(b"\"") {
+            return quoted.iter().position(|&b| b == b'"').map(|end| &quoted[..end]);
+        }
+        let end = rest.iter().position(|&b| matches!(b, b',' | b'}' | b']')).unwrap_or(rest.len());
+        return Some(&rest[..end]);
+    }
+    None
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::net::feed_arbiter::{self, Feed, FeedArbiter, Verdict};
+
+    fn book(kind: &str, u: u64) -> String {
+        format!(r#"{{"topic":"orderbook.50.BTCUSDT","type":"{}","ts":1,"data":{{"s":"BTCUSDT","b":[],"a":[],"u":{},"seq":9}},"cts":1}}"#, kind, u)
+    }
+
+    #[test]
+    fn first_copy_wins() {
+        let t0 = Instant::now();
+        let mut arbiter = FeedArbiter::new(Duration::from_secs(5));
+        let seen: Vec<Verdict> = [
+            (Feed::Primary, book("snapshot", 100)),
+            (Feed::Backup, book("snapshot", 100)),
+            (Feed::Backup, book("delta", 101)),
+            (Feed::Primary, book("delta", 101)),
+            // Primary stalls: Backup carries on, Primary's late copies are dropped
+            (Feed::Backup, book("delta", 102)),
+            (Feed::Backup, book("delta", 103)),
+            (Feed::Primary, book("delta", 102)),
+            (Feed::Primary, book("delta", 104)),
+            (Feed::Backup, r#"{"success":true,"ret_msg":"","op":"subscribe"}"#.to_string()),
+        ].iter().map(|(feed, msg)| arbiter.on_message(*feed, msg.as_bytes(), t0)).collect();
+        use Verdict::*;
+        assert_eq!(seen, [Fresh, Stale, Fresh, Stale, Fresh, Fresh, Stale, Fresh, Unsequenced]);
+        assert_eq!((arbiter.wins, arbiter.duplicates), ([2, 3], 3));
+
+        // Tickers and trades have their own numbering
+        let ticker = |cs: u64| format!(r#"{{"topic":"tickers.BTCUSDT","type":"delta","data":{{"symbol":"BTCUSDT","markPrice":"1"}},"cs":{},"ts":1}}"#, cs);
+        assert_eq!(arbiter.on_message(Feed::Backup, ticker(7).as_bytes(), t0), Fresh);
+        assert_eq!(arbiter.on_message(Feed::Primary, ticker(7).as_bytes(), t0), Stale);
+        let trade = r#"{"topic":"publicTrade.BTCUSDT","type":"snapshot","ts":1672304486868,"data":[{"T":1672304486865,"s":"BTCUSDT","S":"Buy","v":"0.001","p":"16578.50","i":"20f43950","BT":false,"seq":1783284617}]}"#;
+        assert_eq!(feed_arbiter::sequence(trade.as_bytes()), Some((&b"publicTrade.BTCUSDT"[..], 1783284617)));
+        assert_eq!(feed_arbiter::sequence(br#"{"topic":"publicTrade.BTCUSDT","ts":5,"data":[{"S":"u"}]}"#), Some((&b"publicTrade.BTCUSDT"[..], 5)));
+    }
+
+    #[test]
+    fn resync_waits_for_a_snapshot() {
+        let t0 = Instant::now();
+        let mut arbiter = FeedArbiter::new(Duration::from_secs(5));
+        assert_eq!(arbiter.on_message(Feed::Primary, book("snapshot", 100).as_bytes(), t0), Verdict::Fresh);
+        arbiter.resync(b"BTCUSDT");
+        // The other feed's deltas would land on a cleared book
+        assert_eq!(arbiter.on_message(Feed::Backup, book("delta", 101).as_bytes(), t0), Verdict::Stale);
+        assert_eq!(arbiter.on_message(Feed::Primary, book("snapshot", 101).as_bytes(), t0), Verdict::Fresh);
+        assert_eq!(arbiter.on_message(Feed::Backup, book("delta", 102).as_bytes(), t0), Verdict::Fresh);
+        // Bybit restarted its book service: numbering starts over at a u=1 snapshot
+        assert_eq!(arbiter.on_message(Feed::Backup, book("snapshot", 1).as_bytes(), t0), Verdict::Fresh);
+        assert_eq!(arbiter.on_message(Feed::Primary, book("delta", 2).as_bytes(), t0), Verdict::Fresh);
+    }
+
+    #[test]
+    fn silent_feed_is_reported_while_the_other_delivers() {
+        let t0 = Instant::now();
+        let mut arbiter = FeedArbiter::new(Duration::from_secs(5));
+        for feed in [Feed::Primary, Feed::Backup] {
+            arbiter.arm(feed, t0);
+        }
+        assert!(arbiter.on_connect(Feed::Primary));
+        assert!(!arbiter.on_connect(Feed::Backup));
+        arbiter.on_message(Feed::Backup, book("snapshot", 1).as_bytes(), t0 + Duration::from_secs(4));
+        assert_eq!(arbiter.poll(t0 + Duration::from_secs(4)), None);
+        assert_eq!(arbiter.poll(t0 + Duration::from_secs(6)), Some((Feed::Primary, Duration::from_secs(6))));
+        assert_eq!(arbiter.poll(t0 + Duration::from_secs(7)), None);
+        // Both silent: FeedWatchdog reconnects the stream, not the arbiter
+        arbiter.arm(Feed::Primary, t0 + Duration::from_secs(7));
+        assert_eq!(arbiter.poll(t0 + Duration::from_secs(20)), None);
+
+        assert!(!arbiter.on_down(Feed::Primary));
+        assert!(arbiter.is_up(Feed::Backup));
+        assert!(arbiter.on_down(Feed::Backup));
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+use crate::ipc::session::Conn;
+
+// Per-connection feed staleness. A half-open connection (peer gone, no FIN/RST reaching us)
+// reads as WouldBlock forever: no EOF, no error, the book just stops moving and quotes rest
+// at old prices. Every connection must deliver a message within its silence limit, counted
+// from the (re)connect; past it the Hot Thread pulls quotes and reconnects that connection
+// from scratch. A connection that dropped (EOF, server close) or never got through its
+// handshake goes silent too and is reconnected the same way, so the limit is also the retry
+// interval. OutageDetector still decides whether the exchange as a whole is down.
+// Owned by the Hot Thread: plain counters, Instants only in `poll`.
+// Public carries book deltas many times a second on any listed symbol.
+const DEFAULT_PUBLIC_SILENCE_MS: u64 = 5_000;
+// Private/Trade only carry pongs when idle (20s ping): two missed pongs.
+const DEFAULT_PRIVATE_SILENCE_MS: u64 = 45_000;
+const CONNS: [Conn; 3] = [Conn::Public, Conn::Private, Conn::Trade];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedWatchConfig {
+    pub enabled: bool,
+    /// Longest silence per connection (index: `Conn as usize`).
+    pub silence: [Duration; 3],
+}
+
+impl Default for FeedWatchConfig {
+    fn default() -> Self {
+        let private = Duration::from_millis(DEFAULT_PRIVATE_SILENCE_MS);
+        Self { enabled: true, silence: [Duration::from_millis(DEFAULT_PUBLIC_SILENCE_MS), private, private] }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Feed {
+    messages: u64,
+    seen_messages: u64,
+    // None: not watched (before `arm`, or tripped and not yet re-armed)
+    last_activity: Option<Instant>,
+}
+
+pub struct FeedWatchdog {
+    pub config: FeedWatchConfig,
+    /// Stale trips per connection.
+    pub trips: [u64; 3],
+    feeds: [Feed; 3],
+}
+
+impl FeedWatchdog {
+    pub fn new(config: FeedWatchConfig) -> Self {
+        Self { config, trips: [0; 3], feeds: [Feed::default(); 3] }
+    }
+
+    #[inline(always)]
+    pub fn on_message(&mut self, conn: Conn) {
+        self.feeds[conn as usize].messages += 1;
+    }
+
+    /// Connection opened (startup or reconnect): silence is counted from `now`.
+    pub fn arm(&mut self, conn: Conn, now: Instant) {
+        self.feeds[conn as usize].last_activity = Some(now);
+    }
+
+    /// Once per loop iteration. Returns a connection silent past its limit (one per call) and
+    /// stops watching it until it is re-armed.
+    pub fn poll(&mut self, now: Instant) -> Option<(Conn, Duration)> {
+        if !self.config.enabled {
+            return None;
+        }
+        for conn in CONNS {
+            let feed = &mut self.feeds[conn as usize];
+            if feed.messages != feed.seen_messages {
+                feed.seen_messages = feed.messages;
+                if feed.last_activity.is_some() {
+                    feed.last_activity = Some(now);
+                }
+            }
+            let Some(last) = feed.last_activity else { continue };
+            let silent = now.saturating_duration_since(last);
+            if silent >= self.config.silence[conn as usize] {
+                feed.last_activity = None;
+                self.trips[conn as usize] += 1;
+                return Some((conn, silent));
+            }
+        }
+        None
+    }
+}
+
+impl Default for FeedWatchdog {
+    fn default() -> Self {
+        Self::new(FeedWatchConfig::default())
+    }
+}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::ipc::session::Conn;
+    use crate::net::feed_watch::FeedWatchdog;
+
+    #[test]
+    fn silent_feed_trips_once_until_rearmed() {
+        let t0 = Instant::now();
+        let mut watch = FeedWatchdog::default();
+        for conn in [Conn::Public, Conn::Private, Conn::Trade] {
+            watch.arm(conn, t0);
+        }
+        // Private/Trade quiet for 30s is normal (20s pings); the public book must keep moving
+        watch.on_message(Conn::Public);
+        assert_eq!(watch.poll(t0 + Duration::from_secs(4)), None);
+        assert_eq!(watch.poll(t0 + Duration::from_secs(30)), Some((Conn::Public, Duration::from_secs(26))));
+        assert_eq!(watch.poll(t0 + Duration::from_secs(31)), None);
+        assert_eq!(watch.trips, [1, 0, 0]);
+
+        // Reconnected: watched again from the new connect
+        watch.arm(Conn::Public, t0 + Duration::from_secs(31));
+        assert_eq!(watch.poll(t0 + Duration::from_secs(40)), Some((Conn::Public, Duration::from_secs(9))));
+        assert_eq!(watch.poll(t0 + Duration::from_secs(45)), Some((Conn::Private, Duration::from_secs(45))));
+    }
+}
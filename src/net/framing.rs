@@ -1,9 +1,108 @@
 
+use std::cell::Cell;
+use ring::rand::{SecureRandom, SystemRandom};
+
+// Largest server frame header: 2 bytes + 8-byte extended length (servers never mask).
+pub const MAX_HEADER_LEN: usize = 10;
+// Our receive buffers are 64KB; a frame that can't fit will never complete.
+pub const DEFAULT_MAX_PAYLOAD: usize = 65536 - MAX_HEADER_LEN;
+
+// Normal closure status, echoed when the server closes without a code of its own.
+pub const CLOSE_NORMAL: u16 = 1000;
+// Control frames (close/ping/pong) carry at most 125 bytes and are never fragmented.
+const MAX_CONTROL_PAYLOAD: usize = 125;
+// Largest message reassembled from fragments (a deep orderbook snapshot split by the server).
+pub const DEFAULT_MAX_MESSAGE: usize = 4 * 65536;
+
+// Client mask keys (RFC 6455 5.3): a fresh, unpredictable key per frame, so nothing on the
+// path sees chosen bytes and frames are not fingerprintable by a constant mask. The key is
+// not a secret beyond that: xorshift64* per thread, seeded once from the system RNG
+// (the clock if that fails). No allocation, a few ALU ops per frame.
+thread_local! {
+    static MASK_STATE: Cell<u64> = Cell::new(mask_seed());
+}
+
+fn mask_seed() -> u64 {
+    let mut seed = [0u8; 8];
+    if SystemRandom::new().fill(&mut seed).is_err() {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos();
+        seed = (nanos as u64).to_le_bytes();
+    }
+    u64::from_le_bytes(seed) | 1 // xorshift state must not be 0
+}
+
+/// Next masking key for a client frame.
+#[inline]
+pub fn next_mask() -> [u8; 4] {
+    MASK_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        ((x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 32) as u32).to_le_bytes()
+    })
+}
+
+/// Frame opcode (low nibble of the first header byte).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    /// None for the reserved opcodes (0x3-0x7, 0xB-0xF).
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0x0F {
+            0x0 => Some(Self::Continuation),
+            0x1 => Some(Self::Text),
+            0x2 => Some(Self::Binary),
+            0x8 => Some(Self::Close),
+            0x9 => Some(Self::Ping),
+            0xA => Some(Self::Pong),
+            _ => None,
+        }
+    }
+
+    pub fn bits(self) -> u8 {
+        match self {
+            Self::Continuation => 0x0,
+            Self::Text => 0x1,
+            Self::Binary => 0x2,
+            Self::Close => 0x8,
+            Self::Ping => 0x9,
+            Self::Pong => 0xA,
+        }
+    }
+
+    pub fn is_control(self) -> bool {
+        matches!(self, Self::Close | Self::Ping | Self::Pong)
+    }
+}
+
+/// A complete frame: bytes consumed, opcode, FIN and the payload slice in the read buffer.
+pub type Decoded<'a> = (usize, Opcode, bool, &'a mut [u8]);
+
+/// Decodes a WebSocket frame from the given buffer with the default payload limit.
+/// See `decode_frame_limited`.
+pub fn decode_frame(buf: &mut [u8]) -> Result<Option<Decoded<'_>>, &'static str> {
+    decode_frame_limited(buf, DEFAULT_MAX_PAYLOAD)
+}
+
 /// Decodes a WebSocket frame from the given buffer.
-/// Returns Ok(Some((bytes_consumed, payload_slice))) if a full frame is available.
+/// Returns Ok(Some((bytes_consumed, opcode, fin, payload_slice))) if a full frame is available.
+/// A fragment (FIN unset, or a continuation) is one piece of a message: see `FrameAssembler`.
 /// Returns Ok(None) if more data is needed (Incomplete).
-/// Returns Err if the frame is invalid or unexpected (e.g. masked from server).
-pub fn decode_frame(buf: &mut [u8]) -> Result<Option<(usize, &mut [u8])>, &'static str> {
+/// Returns Err if the frame is invalid or unexpected (masked from server, reserved opcode,
+/// fragmented or oversized control frame), or if the
+/// declared payload length exceeds `max_payload` — checked as soon as the length is known,
+/// so a corrupt header can't leave the caller waiting for data that will never fit.
+pub fn decode_frame_limited(buf: &mut [u8], max_payload: usize) -> Result<Option<Decoded<'_>>, &'static str> {
     if buf.len() < 2 {
         return Ok(None);
     }
@@ -11,8 +110,8 @@ pub fn decode_frame(buf: &mut [u8]) -> Result<Option<(usize, &mut [u8])>, &'stat
     let first_byte = buf[0];
     let second_byte = buf[1];
 
-    // let fin = (first_byte & 0x80) != 0;
-    // let opcode = first_byte & 0x0F;
+    let fin = (first_byte & 0x80) != 0;
+    let opcode = Opcode::from_bits(first_byte).ok_or("Reserved opcode")?;
 
     // Check if masked (Server should NOT mask)
     let masked = (second_byte & 0x80) != 0;
@@ -23,6 +122,10 @@ pub fn decode_frame(buf: &mut [u8]) -> Result<Option<(usize, &mut [u8])>, &'stat
     let mut payload_len = (second_byte & 0x7F) as usize;
     let mut header_len = 2;
 
+    if opcode.is_control() && (!fin || payload_len > MAX_CONTROL_PAYLOAD) {
+        return Err("Fragmented or oversized control frame");
+    }
+
     if payload_len == 126 {
         if buf.len() < 4 {
             return Ok(None);
@@ -38,12 +141,19 @@ pub fn decode_frame(buf: &mut [u8]) -> Result<Option<(usize, &mut [u8])>, &'stat
         // Big-endian u64
         let mut len_bytes = [0u8; 8];
         len_bytes.copy_from_slice(&buf[2..10]);
-        // Safety check for 32-bit systems or insane sizes 
-        // (though we probably run on 64-bit)
-        payload_len = u64::from_be_bytes(len_bytes) as usize;
+        // Compare as u64 before casting: the declared length may not fit usize
+        let declared = u64::from_be_bytes(len_bytes);
+        if declared > max_payload as u64 {
+            return Err("Declared frame length exceeds max frame size");
+        }
+        payload_len = declared as usize;
         header_len += 8;
     }
 
+    if payload_len > max_payload {
+        return Err("Declared frame length exceeds max frame size");
+    }
+
     let total_len = header_len + payload_len;
     if buf.len() < total_len {
         return Ok(None);
@@ -63,47 +173,166 @@ pub fn decode_frame(buf: &mut [u8]) -> Result<Option<(usize, &mut [u8])>, &'stat
     let (_, remaining) = buf.split_at_mut(header_len);
     let (payload, _) = remaining.split_at_mut(payload_len);
     
-    Ok(Some((total_len, payload)))
+    Ok(Some((total_len, opcode, fin, payload)))
+}
+
+/// What `FrameAssembler::feed` made of a data frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assembled {
+    /// Unfragmented message: the frame payload is the message, nothing was copied.
+    Whole,
+    /// A piece of a message, buffered; nothing to hand out yet.
+    Fragment,
+    /// The last piece arrived: `message()` holds the whole message.
+    Message,
+    /// Close/ping/pong: not part of any message, left alone.
+    Control,
+}
+
+/// Per-connection reassembly of fragmented messages (first frame with FIN unset, then
+/// continuation frames up to one with FIN set). Control frames may arrive between fragments
+/// and pass through untouched. The buffer is allocated once.
+#[derive(Debug)]
+pub struct FrameAssembler {
+    buf: Vec<u8>,
+    // Opcode of the message being collected
+    pending: Option<Opcode>,
 }
 
-/// Encodes a text frame (opcode 0x1) with masking (client -> server requirement).
-/// Writes directly to dst_buf to avoid allocation.
-/// Returns the number of bytes written.
+impl FrameAssembler {
+    pub fn new(max_message: usize) -> Self {
+        Self { buf: Vec::with_capacity(max_message), pending: None }
+    }
+
+    /// A message is partly collected.
+    pub fn in_progress(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Takes one data frame. Fragments are copied into the buffer; an unfragmented frame is
+    /// left where it is. A continuation with nothing to continue, a new message before the
+    /// last one finished or a message past the buffer size drops the partial message and
+    /// returns Err.
+    pub fn feed(&mut self, opcode: Opcode, fin: bool, payload: &[u8]) -> Result<Assembled, &'static str> {
+        match (opcode, self.pending) {
+            (op, _) if op.is_control() => Ok(Assembled::Control),
+            (Opcode::Continuation, None) => Err("Continuation frame without a fragmented message"),
+            (Opcode::Continuation, Some(_)) => {
+                self.append(payload)?;
+                if fin {
+                    self.pending = None;
+                    Ok(Assembled::Message)
+                } else {
+                    Ok(Assembled::Fragment)
+                }
+            }
+            (_, Some(_)) => {
+                self.pending = None;
+                Err("New message before the fragmented one finished")
+            }
+            (_, None) if fin => Ok(Assembled::Whole),
+            (_, None) => {
+                self.buf.clear();
+                self.append(payload)?;
+                self.pending = Some(opcode);
+                Ok(Assembled::Fragment)
+            }
+        }
+    }
+
+    /// The message completed by the last `feed` (valid until the next fragmented one starts).
+    pub fn message(&mut self) -> &mut [u8] {
+        &mut self.buf
+    }
+
+    /// `feed` for callers that hold the frame in the read buffer: the whole message once it
+    /// is complete (the frame itself when unfragmented), None while fragments are pending and
+    /// for control frames.
+    pub fn push<'a>(&'a mut self, opcode: Opcode, fin: bool, payload: &'a mut [u8]) -> Result<Option<&'a mut [u8]>, &'static str> {
+        Ok(match self.feed(opcode, fin, payload)? {
+            Assembled::Whole => Some(payload),
+            Assembled::Fragment | Assembled::Control => None,
+            Assembled::Message => Some(self.message()),
+        })
+    }
+
+    fn append(&mut self, payload: &[u8]) -> Result<(), &'static str> {
+        if self.buf.len() + payload.len() > self.buf.capacity() {
+            self.pending = None;
+            self.buf.clear();
+            return Err("Fragmented message exceeds max message size");
+        }
+        self.buf.extend_from_slice(payload);
+        Ok(())
+    }
+}
+
+/// Status code of a close frame payload (first 2 bytes, big-endian); None when the server
+/// sent no code. The rest of the payload is a UTF-8 reason.
+pub fn close_code(payload: &[u8]) -> Option<u16> {
+    match payload {
+        [hi, lo, ..] => Some(u16::from_be_bytes([*hi, *lo])),
+        _ => None,
+    }
+}
+
+/// Encodes a masked close frame (opcode 0x8) carrying `code`; the reply to a server close
+/// echoes the server's code. Returns the number of bytes written.
+pub fn encode_close_frame(code: u16, dst_buf: &mut [u8]) -> usize {
+    encode_frame(Opcode::Close, &code.to_be_bytes(), dst_buf)
+}
+
+/// Encodes a masked, empty ping frame (opcode 0x9). Returns the number of bytes written (6).
+pub fn encode_ping_frame(dst_buf: &mut [u8]) -> usize {
+    encode_frame(Opcode::Ping, &[], dst_buf)
+}
+
+/// Encodes a masked pong frame (opcode 0xA) echoing a server ping's payload (at most 125
+/// bytes). Returns the number of bytes written, 0 if dst_buf can't hold it.
+pub fn encode_pong_frame(payload: &[u8], dst_buf: &mut [u8]) -> usize {
+    encode_frame(Opcode::Pong, payload, dst_buf)
+}
+
+/// Encodes a text frame (opcode 0x1) with masking (client -> server requirement), any
+/// payload size (7-bit, 16-bit or 64-bit length). Writes directly to dst_buf to avoid
+/// allocation. Returns the number of bytes written, 0 if dst_buf can't hold the frame.
 pub fn encode_text_frame(src_payload: &[u8], dst_buf: &mut [u8]) -> usize {
+    encode_frame(Opcode::Text, src_payload, dst_buf)
+}
+
+fn encode_frame(opcode: Opcode, src_payload: &[u8], dst_buf: &mut [u8]) -> usize {
     let payload_len = src_payload.len();
+    let header_len = client_header_len(payload_len);
+    if dst_buf.len() < header_len + payload_len {
+        eprintln!("Frame too large for encoder buffer: {} bytes payload, {} bytes buffer", payload_len, dst_buf.len());
+        return 0;
+    }
     let mut offset = 0;
 
-    // 1. Byte 0: FIN (0x80) | Opcode (0x1 = Text)
-    dst_buf[offset] = 0x81;
+    // 1. Byte 0: FIN (0x80) | Opcode
+    dst_buf[offset] = 0x80 | opcode.bits();
     offset += 1;
 
     // 2. Byte 1: Mask (0x80) | Length
-    // Extended length for payloads >= 126 bytes
+    // 7-bit length up to 125, then 126 + u16 or 127 + u64 (big-endian)
     if payload_len < 126 {
         dst_buf[offset] = 0x80 | (payload_len as u8);
         offset += 1;
-    } else if payload_len <= 65535 {
-        // Extended payload length (2 bytes, big-endian)
+    } else if payload_len <= u16::MAX as usize {
         dst_buf[offset] = 0x80 | 126;
         offset += 1;
-        let len_bytes = (payload_len as u16).to_be_bytes();
-        dst_buf[offset] = len_bytes[0];
-        dst_buf[offset + 1] = len_bytes[1];
+        dst_buf[offset..offset + 2].copy_from_slice(&(payload_len as u16).to_be_bytes());
         offset += 2;
     } else {
-        // Payload too large for our use case
-        eprintln!("Frame too large for encoder: {} bytes", payload_len);
-        return 0;
+        dst_buf[offset] = 0x80 | 127;
+        offset += 1;
+        dst_buf[offset..offset + 8].copy_from_slice(&(payload_len as u64).to_be_bytes());
+        offset += 8;
     }
 
-    // 3. Mask Key (4 bytes)
-    // Simple rotating mask: [1, 2, 3, 4]
-    // In prod use random: rand::random::<[u8; 4]>()
-    let mask_key = [1u8, 2, 3, 4];
-    dst_buf[offset] = mask_key[0];
-    dst_buf[offset+1] = mask_key[1];
-    dst_buf[offset+2] = mask_key[2];
-    dst_buf[offset+3] = mask_key[3];
+    // 3. Mask Key (4 bytes), fresh per frame
+    let mask_key = next_mask();
+    dst_buf[offset..offset + 4].copy_from_slice(&mask_key);
     offset += 4;
 
     // 4. Payload (Masked)
@@ -114,3 +343,13 @@ pub fn encode_text_frame(src_payload: &[u8], dst_buf: &mut [u8]) -> usize {
 
     offset
 }
+
+/// Header bytes of a masked client frame carrying `payload_len` bytes: 2 + 4-byte mask, plus
+/// 2 or 8 bytes of extended length. A frame needs `client_header_len(n) + n` bytes of buffer.
+pub fn client_header_len(payload_len: usize) -> usize {
+    match payload_len {
+        0..=125 => 2 + 4,
+        126..=65535 => 2 + 2 + 4,
+        _ => 2 + 8 + 4,
+    }
+}
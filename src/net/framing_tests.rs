@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+    use crate::net::framing::{close_code, decode_frame, decode_frame_limited, encode_close_frame, encode_ping_frame, encode_text_frame, Assembled, FrameAssembler, Opcode, DEFAULT_MAX_PAYLOAD};
+
+    fn server_frame(payload: &[u8]) -> Vec<u8> {
+        let mut f = vec![0x81];
+        if payload.len() < 126 {
+            f.push(payload.len() as u8);
+        } else if payload.len() <= 65535 {
+            f.push(126);
+            f.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        } else {
+            f.push(127);
+            f.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        }
+        f.extend_from_slice(payload);
+        f
+    }
+
+    #[test]
+    fn decodes_short_and_extended_lengths() {
+        let mut short = server_frame(b"hello");
+        let (consumed, opcode, _, payload) = decode_frame(&mut short).unwrap().unwrap();
+        assert_eq!((consumed, opcode, &payload[..]), (7, Opcode::Text, &b"hello"[..]));
+
+        let body = vec![b'x'; 300];
+        let mut ext = server_frame(&body);
+        let (consumed, _, _, payload) = decode_frame(&mut ext).unwrap().unwrap();
+        assert_eq!(consumed, 304);
+        assert_eq!(payload.len(), 300);
+    }
+
+    #[test]
+    fn truncated_headers_need_more_data() {
+        let body = vec![b'x'; 300];
+        let full = server_frame(&body);
+        // 1 byte, mid 16-bit length, header complete but payload short
+        for cut in [1, 3, 10] {
+            let mut part = full[..cut].to_vec();
+            assert!(decode_frame(&mut part).unwrap().is_none(), "cut at {}", cut);
+        }
+
+        let mut mid_u64 = vec![0x81, 127, 0, 0, 0];
+        assert!(decode_frame(&mut mid_u64).unwrap().is_none());
+    }
+
+    #[test]
+    fn oversized_declared_length_is_rejected_immediately() {
+        // Header only: 64-bit length claiming 1 TB
+        let mut huge = vec![0x81, 127];
+        huge.extend_from_slice(&(1u64 << 40).to_be_bytes());
+        assert!(decode_frame(&mut huge).is_err());
+
+        // u64::MAX must not overflow header_len + payload_len
+        let mut max = vec![0x81, 127];
+        max.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert!(decode_frame(&mut max).is_err());
+
+        // Just over the default limit
+        let mut over = vec![0x81, 127];
+        over.extend_from_slice(&((DEFAULT_MAX_PAYLOAD + 1) as u64).to_be_bytes());
+        assert!(decode_frame(&mut over).is_err());
+
+        // Configurable limit applies to 16-bit lengths too
+        let mut ext = server_frame(&vec![b'x'; 300]);
+        assert!(decode_frame_limited(&mut ext, 200).is_err());
+        assert!(decode_frame_limited(&mut ext, 300).unwrap().is_some());
+    }
+
+    #[test]
+    fn masked_server_frame_is_rejected() {
+        let mut buf = vec![0x81, 0x80 | 5, 1, 2, 3, 4, b'h', b'e', b'l', b'l', b'o'];
+        assert!(decode_frame(&mut buf).is_err());
+    }
+
+    #[test]
+    fn server_close_is_parsed_and_echoed() {
+        // Close 1001 "going away", then a text frame that must not be mistaken for payload
+        let mut buf = vec![0x88, 7, 0x03, 0xE9, b'b', b'y', b'e', b'!', b'!'];
+        buf.extend_from_slice(&server_frame(b"{}"));
+        let (consumed, opcode, _, payload) = decode_frame(&mut buf).unwrap().unwrap();
+        assert_eq!((consumed, opcode), (9, Opcode::Close));
+        assert_eq!(close_code(payload), Some(1001));
+        assert_eq!(close_code(&[]), None);
+        let (_, opcode, _, _) = decode_frame(&mut buf[9..]).unwrap().unwrap();
+        assert_eq!(opcode, Opcode::Text);
+
+        // Our answer: masked close carrying the same code
+        let mut out = [0u8; 16];
+        let n = encode_close_frame(1001, &mut out);
+        assert_eq!((n, out[0], out[1]), (2 + 4 + 2, 0x88, 0x80 | 2));
+        let mask = [out[2], out[3], out[4], out[5]];
+        assert_eq!([out[6] ^ mask[0], out[7] ^ mask[1]], 1001u16.to_be_bytes());
+
+        // Reserved opcodes, fragmented or oversized control frames are protocol errors
+        assert!(decode_frame(&mut [0x83, 0]).is_err());
+        assert!(decode_frame(&mut [0x08, 0]).is_err());
+        let mut long_ping = vec![0x89, 126, 0, 126];
+        long_ping.extend_from_slice(&[0u8; 126]);
+        assert!(decode_frame(&mut long_ping).is_err());
+    }
+
+    #[test]
+    fn fragmented_message_is_delivered_whole() {
+        // {"a":1} split over Text (FIN=0), a ping, Continuation (FIN=0), Continuation (FIN=1)
+        let mut wire = vec![0x01, 3, b'{', b'"', b'a'];
+        wire.extend_from_slice(&[0x89, 0]);
+        wire.extend_from_slice(&[0x00, 2, b'"', b':']);
+        wire.extend_from_slice(&[0x80, 2, b'1', b'}']);
+        wire.extend_from_slice(&server_frame(b"next"));
+
+        let mut assembler = FrameAssembler::new(64);
+        let mut pos = 0;
+        let mut messages = Vec::new();
+        while let Some((consumed, opcode, fin, payload)) = decode_frame(&mut wire[pos..]).unwrap() {
+            if let Some(message) = assembler.push(opcode, fin, payload).unwrap() {
+                messages.push(message.to_vec());
+            }
+            pos += consumed;
+        }
+        assert_eq!(messages, vec![br#"{"a":1}"#.to_vec(), b"next".to_vec()]);
+        assert!(!assembler.in_progress());
+
+        // Out-of-order pieces drop the partial message
+        assert!(assembler.feed(Opcode::Continuation, true, b"x").is_err());
+        assert_eq!(assembler.feed(Opcode::Text, false, b"ab"), Ok(Assembled::Fragment));
+        assert!(assembler.feed(Opcode::Text, true, b"cd").is_err());
+        assert!(!assembler.in_progress());
+        // So does one past the buffer
+        assert_eq!(assembler.feed(Opcode::Text, false, &[0u8; 60]), Ok(Assembled::Fragment));
+        assert!(assembler.feed(Opcode::Continuation, true, &[0u8; 8]).is_err());
+        assert_eq!(assembler.feed(Opcode::Text, true, b"ok"), Ok(Assembled::Whole));
+    }
+
+    #[test]
+    fn extended_lengths_encode_and_mask() {
+        let unmask = |frame: &[u8], header: usize| -> Vec<u8> {
+            let mask = &frame[header - 4..header];
+            frame[header..].iter().enumerate().map(|(i, b)| b ^ mask[i % 4]).collect()
+        };
+        for (len, header) in [(125, 6), (126, 8), (65535, 8), (65536, 14), (70_000, 14)] {
+            let payload: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let mut out = vec![0u8; header + len];
+            let n = encode_text_frame(&payload, &mut out);
+            assert_eq!(n, header + len, "len {}", len);
+            assert_eq!(out[0], 0x81);
+            let declared = match out[1] & 0x7F {
+                126 => u16::from_be_bytes([out[2], out[3]]) as usize,
+                127 => u64::from_be_bytes(out[2..10].try_into().unwrap()) as usize,
+                short => short as usize,
+            };
+            assert_eq!((out[1] & 0x80, declared), (0x80, len));
+            assert_eq!(unmask(&out[..n], header), payload);
+        }
+        // A buffer one byte short is refused, not overrun
+        let mut short = vec![0u8; 8 + 300 - 1];
+        assert_eq!(encode_text_frame(&[b'x'; 300], &mut short), 0);
+    }
+
+    #[test]
+    fn encode_roundtrip_unmasks() {
+        let mut out = [0u8; 512];
+        let n = encode_text_frame(b"ping", &mut out);
+        assert_eq!(n, 2 + 4 + 4);
+        let mask = [out[2], out[3], out[4], out[5]];
+        let decoded: Vec<u8> = out[6..n].iter().enumerate().map(|(i, b)| b ^ mask[i % 4]).collect();
+        assert_eq!(decoded, b"ping");
+
+        // Every frame gets its own key, pings included
+        let masks: Vec<[u8; 4]> = (0..8).map(|_| {
+            let n = encode_ping_frame(&mut out);
+            assert_eq!((n, out[0], out[1]), (6, 0x89, 0x80));
+            [out[2], out[3], out[4], out[5]]
+        }).collect();
+        assert!(masks.windows(2).all(|w| w[0] != w[1]), "{:?}", masks);
+    }
+}
@@ -0,0 +1,431 @@
+use std::time::Duration;
+use ring::{digest, hmac};
+use simd_json::prelude::*;
+use simd_json::BorrowedValue;
+use crate::auth::secrets::{lookup_all, redacted_debug, SecretProvider};
+use crate::core::base64;
+use crate::core::logging::debug;
+use crate::core::orderbook::{L2OrderBook, Side};
+use crate::core::parser::{list, num_field, str_field, u64_field, ParseError};
+use crate::core::time_sync;
+use crate::net::adapter::{ConnectionState, ExchangeAdapter, WsConnection};
+use crate::net::ws_client::{WsClient, WsClientBuilder};
+use crate::strategy::fill::Fill;
+
+// Kraken Futures connector: public `book` feed and private `fills` over the v1 WS, order entry
+// over REST v3. The book is the part that needs care: `book_snapshot`, then one `book`
+// message per changed level, all numbered by a per-product `seq`. A skipped `seq` or a
+// checksum that does not match our copy means the book is wrong; `BookChecker` refuses
+// updates until the next snapshot (resubscribe to get one). Other departures from Bybit:
+//   * the checksum is CRC32 (IEEE) over the top 10 asks (best first), then the top 10 bids,
+//     each level as price then qty with the decimal point and leading zeros removed;
+//   * private feeds need a challenge: send the key, get a UUID back, subscribe with
+//     base64(HMAC-SHA512(base64-decoded secret, SHA256(challenge)));
+//   * REST `Authent` is base64(HMAC-SHA512(secret, SHA256(postData + nonce + path))), path
+//     without the `/derivatives` prefix; parameters are form-encoded;
+//   * `qty` / `size` is contracts (USD for inverse PI_ products, base coin for PF_ linear);
+//     `fee_paid` is positive when charged, like Bybit.
+pub const KRAKEN_WS_HOST: &str = "futures.kraken.com";
+pub const KRAKEN_DEMO_WS_HOST: &str = "demo-futures.kraken.com";
+pub const KRAKEN_WS_PATH: &str = "/ws/v1";
+pub const KRAKEN_REST_URL: &str = "https://futures.kraken.com/derivatives";
+pub const KRAKEN_DEMO_REST_URL: &str = "https://demo-futures.kraken.com/derivatives";
+// Kraken drops a connection that sent nothing for 60s
+pub const KRAKEN_PING_INTERVAL: Duration = Duration::from_secs(30);
+pub const KEY_VAR: &str = "KRAKEN_FUTURES_API_KEY";
+pub const SECRET_VAR: &str = "KRAKEN_FUTURES_SECRET";
+pub const CHECKSUM_DEPTH: usize = 10;
+const REST_PATH_PREFIX: &str = "/derivatives";
+
+/// The WS connection (book, and fills after the challenge), WS ping frames as keepalive.
+pub fn builder(demo: bool) -> WsClientBuilder {
+    let host = if demo { KRAKEN_DEMO_WS_HOST } else { KRAKEN_WS_HOST };
+    WsClientBuilder::new(host).path(KRAKEN_WS_PATH).auto_ping(KRAKEN_PING_INTERVAL, None)
+}
+
+/// CRC32 (IEEE 802.3, reflected), bitwise: a few hundred bytes per book message.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { crc >> 1 ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// One number of the checksum string: printed on its grid, no point, no leading zeros.
+fn checksum_digits(value: f64, decimals: usize, out: &mut String) {
+    let text = format!("{:.*}", decimals, value);
+    out.extend(text.chars().filter(|c| *c != '.').skip_while(|c| *c == '0'));
+}
+
+/// Kraken's checksum of `book`: top 10 asks, then top 10 bids, prices and qtys on their grid
+/// (`price_dp` / `qty_dp` decimals).
+pub fn book_checksum(book: &L2OrderBook, price_dp: usize, qty_dp: usize) -> u32 {
+    let mut text = String::with_capacity(CHECKSUM_DEPTH * 2 * 24);
+    for levels in [&book.asks, &book.bids] {
+        for level in levels.iter().take(CHECKSUM_DEPTH).filter(|l| l.price > 0.0) {
+            checksum_digits(level.price, price_dp, &mut text);
+            checksum_digits(level.qty, qty_dp, &mut text);
+        }
+    }
+    crc32(text.as_bytes())
+}
+
+/// Kraken Futures API key and the HMAC-SHA512 key of its (base64) secret.
+#[derive(Clone)]
+pub struct KrakenCredentials {
+    pub api_key: String,
+    key: hmac::Key,
+}
+
+redacted_debug!(KrakenCredentials { api_key });
+
+impl KrakenCredentials {
+    pub fn new(api_key: &str, secret: &str) -> Result<Self, String> {
+        let (api_key, secret) = (api_key.trim(), secret.trim());
+        if api_key.is_empty() || secret.is_empty() {
+            return Err("empty Kraken Futures API key or secret".to_string());
+        }
+        let secret = base64::decode(secret).ok_or_else(|| "Kraken Futures secret is not base64".to_string())?;
+        Ok(Self { api_key: api_key.to_string(), key: hmac::Key::new(hmac::HMAC_SHA512, &secret) })
+    }
+
+    pub fn load(provider: &dyn SecretProvider) -> Result<Self, String> {
+        let [key, secret] = lookup_all(provider, [KEY_VAR, SECRET_VAR])?;
+        Self::new(&key, &secret)
+    }
+
+    /// base64(HMAC-SHA512(secret, SHA256(input))), the shape of both Kraken signatures.
+    fn sign(&self, input: &str) -> String {
+        let hash = digest::digest(&digest::SHA256, input.as_bytes());
+        base64::encode(hmac::sign(&self.key, hash.as_ref()).as_ref())
+    }
+
+    /// Signed challenge for the private WS feeds.
+    pub fn sign_challenge(&self, challenge: &str) -> String {
+        self.sign(challenge)
+    }
+
+    /// `Authent` header of a REST call; `path` with or without the `/derivatives` prefix.
+    pub fn authent(&self, post_data: &str, nonce: &str, path: &str) -> String {
+        let path = path.strip_prefix(REST_PATH_PREFIX).unwrap_or(path);
+        self.sign(&format!("{}{}{}", post_data, nonce, path))
+    }
+
+    /// First step of the private login: the server answers with the challenge.
+    pub fn challenge_request(&self) -> String {
+        format!(r#"{{"event":"challenge","api_key":"{}"}}"#, self.api_key)
+    }
+
+    /// Private `fills` subscription with the answered challenge.
+    pub fn subscribe_fills(&self, challenge: &str) -> String {
+        format!(r#"{{"event":"subscribe","feed":"fills","api_key":"{}","original_challenge":"{}","signed_challenge":"{}"}}"#,
+            self.api_key, challenge, self.sign_challenge(challenge))
+    }
+}
+
+/// Public `book` connection as an `ExchangeAdapter`. The private `fills` feed needs the
+/// challenge answered first: send `challenge_request` / `subscribe_fills` on the same
+/// connection (`connection_mut().send_text`) once it is `Active`.
+pub struct KrakenBook {
+    conn: WsConnection,
+    subscribe: String,
+}
+
+impl KrakenBook {
+    pub fn new(client: WsClient, products: &[&str]) -> Self {
+        Self { conn: WsConnection::new(client, "kraken", ConnectionState::Subscribing), subscribe: subscribe_book(products) }
+    }
+}
+
+impl ExchangeAdapter for KrakenBook {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        debug!(net, "HOT: Sending Kraken Subscription: {}", self.subscribe);
+        Some(self.subscribe.clone())
+    }
+}
+
+/// Public `book` of every product, one message.
+pub fn subscribe_book(products: &[&str]) -> String {
+    let list = products.iter().map(|p| format!(r#""{}""#, p)).collect::<Vec<_>>().join(",");
+    format!(r#"{{"event":"subscribe","feed":"book","product_ids":[{}]}}"#, list)
+}
+
+/// One message of the `book` feed.
+#[derive(Debug, Clone, Copy)]
+pub enum KrakenBookUpdate<'a> {
+    /// Levels as `{"price", "qty"}` objects.
+    Snapshot { seq: u64, bids: &'a [BorrowedValue<'a>], asks: &'a [BorrowedValue<'a>], checksum: Option<u32> },
+    /// One level; qty 0 removes it.
+    Delta { seq: u64, side: Side, price: f64, qty: f64, checksum: Option<u32> },
+}
+
+impl KrakenBookUpdate<'_> {
+    pub fn seq(&self) -> u64 {
+        match *self {
+            KrakenBookUpdate::Snapshot { seq, .. } | KrakenBookUpdate::Delta { seq, .. } => seq,
+        }
+    }
+
+    pub fn checksum(&self) -> Option<u32> {
+        match *self {
+            KrakenBookUpdate::Snapshot { checksum, .. } | KrakenBookUpdate::Delta { checksum, .. } => checksum,
+        }
+    }
+
+    /// Applies the message to `book` (replaced on a snapshot), unchecked.
+    pub fn apply(&self, book: &mut L2OrderBook) {
+        match *self {
+            KrakenBookUpdate::Snapshot { bids, asks, .. } => {
+                book.clear();
+                for (side, levels) in [(Side::Buy, bids), (Side::Sell, asks)] {
+                    for level in levels {
+                        if let (Some(px), Some(qty)) = (num_field(level, "price"), num_field(level, "qty")) {
+                            book.update(side, px, qty);
+                        }
+                    }
+                }
+                book.snapshot_applied = true;
+            }
+            KrakenBookUpdate::Delta { side, price, qty, .. } => book.update(side, price, qty),
+        }
+    }
+}
+
+/// Why a product's book can no longer be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookError {
+    /// A message before the first snapshot, or after an earlier error.
+    NotSynced,
+    Gap { expected: u64, got: u64 },
+    Checksum { expected: u32, got: u32 },
+}
+
+/// Sequence and checksum guard of one product's book.
+#[derive(Debug, Clone, Copy)]
+pub struct BookChecker {
+    last_seq: Option<u64>,
+    price_dp: usize,
+    qty_dp: usize,
+    pub checksum_failures: u64,
+    pub gaps: u64,
+}
+
+impl BookChecker {
+    /// `price_dp` / `qty_dp`: decimals of the product's tick and contract size (checksum text).
+    pub fn new(price_dp: usize, qty_dp: usize) -> Self {
+        Self { last_seq: None, price_dp, qty_dp, checksum_failures: 0, gaps: 0 }
+    }
+
+    /// Applies `update` to `book` if it continues the sequence, then verifies the checksum the
+    /// message carries. On Err the book is stale until the next snapshot.
+    pub fn apply(&mut self, update: &KrakenBookUpdate, book: &mut L2OrderBook) -> Result<(), BookError> {
+        let seq = update.seq();
+        match (update, self.last_seq) {
+            (KrakenBookUpdate::Snapshot { .. }, _) => {}
+            (_, Some(last)) if seq == last + 1 => {}
+            (_, Some(last)) => {
+                self.last_seq = None;
+                self.gaps += 1;
+                return Err(BookError::Gap { expected: last + 1, got: seq });
+            }
+            (_, None) => return Err(BookError::NotSynced),
+        }
+        update.apply(book);
+        self.last_seq = Some(seq);
+        if let Some(expected) = update.checksum() {
+            let got = book_checksum(book, self.price_dp, self.qty_dp);
+            if got != expected {
+                self.last_seq = None;
+                self.checksum_failures += 1;
+                return Err(BookError::Checksum { expected, got });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Normalized Kraken Futures WS message.
+#[derive(Debug, Clone, Copy)]
+pub enum KrakenEvent<'a> {
+    Info,
+    Subscribed { feed: &'a str },
+    /// UUID to sign for the private feeds.
+    Challenge { message: &'a str },
+    /// `error` / `alert` events.
+    Error { message: &'a str },
+    Heartbeat,
+    Book { product: &'a str, update: KrakenBookUpdate<'a> },
+    /// One of our executions (`fills` feed, not the history snapshot).
+    Fill { product: &'a str, cli_ord_id: &'a str, fill: Fill },
+}
+
+fn checksum_field(obj: &BorrowedValue) -> Option<u32> {
+    u64_field(obj, "checksum").map(|c| c as u32)
+}
+
+fn fill_of(item: &BorrowedValue) -> Fill {
+    let (qty, px) = (num_field(item, "qty").unwrap_or(0.0), num_field(item, "price").unwrap_or(0.0));
+    let fee = num_field(item, "fee_paid").unwrap_or(0.0);
+    Fill {
+        side: if item.get("buy").and_then(|v| v.as_bool()).unwrap_or(false) { "Buy" } else { "Sell" },
+        qty,
+        px,
+        fee,
+        // Only meaningful for linear contracts (fee in the quote coin)
+        fee_rate: if qty > 0.0 && px > 0.0 { fee / (qty * px) } else { 0.0 },
+        is_maker: str_field(item, "fill_type") == "maker",
+        closed_pnl: 0.0,
+        exec_time_ms: u64_field(item, "time").unwrap_or(0),
+        seq: u64_field(item, "seq").unwrap_or(0),
+    }
+}
+
+/// Calls `on_event` for each event in one Kraken Futures WS message. Returns the number of
+/// events; other feeds yield `Ok(0)`.
+pub fn parse_message<F: FnMut(KrakenEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    let tape = simd_json::to_borrowed_value(data)?;
+    if let Some(event) = tape.get("event").and_then(|v| v.as_str()) {
+        on_event(match event {
+            "info" => KrakenEvent::Info,
+            "subscribed" => KrakenEvent::Subscribed { feed: str_field(&tape, "feed") },
+            "challenge" => KrakenEvent::Challenge { message: str_field(&tape, "message") },
+            "error" | "alert" => KrakenEvent::Error { message: str_field(&tape, "message") },
+            _ => return Ok(0),
+        });
+        return Ok(1);
+    }
+    let product = str_field(&tape, "product_id");
+    match str_field(&tape, "feed") {
+        "heartbeat" => on_event(KrakenEvent::Heartbeat),
+        "book_snapshot" => on_event(KrakenEvent::Book {
+            product,
+            update: KrakenBookUpdate::Snapshot {
+                seq: u64_field(&tape, "seq").ok_or(ParseError::Schema("kraken: no seq"))?,
+                bids: list(&tape, "bids"),
+                asks: list(&tape, "asks"),
+                checksum: checksum_field(&tape),
+            },
+        }),
+        "book" => on_event(KrakenEvent::Book {
+            product,
+            update: KrakenBookUpdate::Delta {
+                seq: u64_field(&tape, "seq").ok_or(ParseError::Schema("kraken: no seq"))?,
+                side: if str_field(&tape, "side") == "buy" { Side::Buy } else { Side::Sell },
+                price: num_field(&tape, "price").ok_or(ParseError::Schema("kraken: no price"))?,
+                qty: num_field(&tape, "qty").unwrap_or(0.0),
+                checksum: checksum_field(&tape),
+            },
+        }),
+        "fills" => {
+            let fills = list(&tape, "fills");
+            for item in fills {
+                on_event(KrakenEvent::Fill { product: str_field(item, "instrument"), cli_ord_id: str_field(item, "cli_ord_id"), fill: fill_of(item) });
+            }
+            return Ok(fills.len());
+        }
+        _ => return Ok(0),
+    }
+    Ok(1)
+}
+
+/// Post-only limit order. `side` is "Buy" | "Sell" like everywhere else in the bot.
+#[derive(Debug, Clone, Copy)]
+pub struct KrakenOrder<'a> {
+    pub symbol: &'a str,
+    pub cli_ord_id: &'a str,
+    pub side: &'a str,
+    pub price: f64,
+    /// Contracts.
+    pub size: f64,
+    pub price_dp: usize,
+    pub size_dp: usize,
+    pub reduce_only: bool,
+}
+
+/// Form body of POST /api/v3/sendorder.
+pub fn send_order_data(order: &KrakenOrder) -> String {
+    let side = if order.side == "Buy" { "buy" } else { "sell" };
+    format!("orderType=post&symbol={}&side={}&size={:.*}&limitPrice={:.*}&cliOrdId={}&reduceOnly={}",
+        order.symbol, side, order.size_dp, order.size, order.price_dp, order.price, order.cli_ord_id, order.reduce_only)
+}
+
+/// Order id of a sendorder / editorder / cancelorder response, or the reason it failed.
+/// Kraken answers HTTP 200 with `result: error`, or `result: success` with a status that is
+/// not one of the accepted ones (`placed`, `edited`, `cancelled`).
+pub fn parse_order_response(resp: &mut [u8]) -> Result<String, String> {
+    let json = simd_json::to_borrowed_value(resp).map_err(|_| "Failed to parse response JSON".to_string())?;
+    if str_field(&json, "result") != "success" {
+        return Err(format!("Kraken error: {}", str_field(&json, "error")));
+    }
+    let status = ["sendStatus", "editStatus", "cancelStatus"].iter().find_map(|key| json.get(*key))
+        .ok_or_else(|| "No order status in response".to_string())?;
+    match str_field(status, "status") {
+        "placed" | "edited" | "cancelled" => Ok(Some(str_field(status, "order_id")).filter(|id| !id.is_empty())
+            .unwrap_or(str_field(status, "orderId")).to_string()),
+        other => Err(format!("Kraken error: {}", other)),
+    }
+}
+
+/// Blocking Kraken Futures REST order entry (ureq). Cold side only.
+pub struct KrakenRestClient {
+    credentials: KrakenCredentials,
+    base_url: String,
+    nonce_seq: std::cell::Cell<u64>,
+}
+
+impl KrakenRestClient {
+    pub fn new(credentials: KrakenCredentials, demo: bool) -> Self {
+        let base_url = if demo { KRAKEN_DEMO_REST_URL } else { KRAKEN_REST_URL };
+        Self { credentials, base_url: base_url.to_string(), nonce_seq: std::cell::Cell::new(0) }
+    }
+
+    pub fn send_order(&self, order: &KrakenOrder) -> Result<String, String> {
+        self.post("/api/v3/sendorder", &send_order_data(order))
+    }
+
+    /// New price and/or size of our order `cli_ord_id`, as (value, decimals).
+    pub fn edit_order(&self, cli_ord_id: &str, price: Option<(f64, usize)>, size: Option<(f64, usize)>) -> Result<String, String> {
+        let mut data = format!("cliOrdId={}", cli_ord_id);
+        if let Some((px, dp)) = price {
+            data.push_str(&format!("&limitPrice={:.*}", dp, px));
+        }
+        if let Some((size, dp)) = size {
+            data.push_str(&format!("&size={:.*}", dp, size));
+        }
+        self.post("/api/v3/editorder", &data)
+    }
+
+    pub fn cancel_order(&self, cli_ord_id: &str) -> Result<String, String> {
+        self.post("/api/v3/cancelorder", &format!("cliOrdId={}", cli_ord_id))
+    }
+
+    /// Signs (APIKey, Nonce, Authent) and sends a form POST.
+    fn post(&self, path: &str, data: &str) -> Result<String, String> {
+        // Strictly increasing: wall ms plus a counter for calls within the same ms
+        self.nonce_seq.set(self.nonce_seq.get() + 1);
+        let nonce = format!("{}{:04}", time_sync::wall_ms(), self.nonce_seq.get() % 10_000);
+        let authent = self.credentials.authent(data, &nonce, path);
+        let response = ureq::post(&format!("{}{}", self.base_url, path))
+            .set("Content-Type", "application/x-www-form-urlencoded")
+            .set("APIKey", &self.credentials.api_key)
+            .set("Nonce", &nonce)
+            .set("Authent", &authent)
+            .send_string(data)
+            .map_err(|e| format!("HTTP error: {}", e))?;
+        let body = response.into_string().map_err(|e| format!("Failed to read response: {}", e))?;
+        debug!(orders, "KRAKEN: {} -> {}", path, body);
+        parse_order_response(&mut body.into_bytes())
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::orderbook::L2OrderBook;
+    use crate::net::kraken::{self, BookChecker, BookError, KrakenCredentials, KrakenEvent, KrakenOrder};
+
+    const SECRET: &str = "a3Jha2VuLWZ1dHVyZXMtdGVzdC1zZWNyZXQtMDEyMzQ1Njc4OQ==";
+
+    #[test]
+    fn challenge_and_rest_signatures() {
+        let creds = KrakenCredentials::new("pubkey", SECRET).unwrap();
+        // Reference values from Python hmac/hashlib/base64
+        let challenge = "c100b894-1729-464d-ace1-52dbce11db42";
+        assert_eq!(creds.sign_challenge(challenge), "OZ6gdbt7uPnjIlFwPDyjLltfo/6+SbFmSfTAMuG5EO0OG4LcrReoNpx2qepzG/5hTGOsVDZ1lsZiqkxxakLGBg==");
+        assert!(creds.subscribe_fills(challenge).contains(r#""original_challenge":"c100b894-1729-464d-ace1-52dbce11db42","signed_challenge":"OZ6gdbt7"#));
+        assert!(!format!("{:?}", creds).contains(SECRET));
+
+        let order = KrakenOrder { symbol: "PF_XBTUSD", cli_ord_id: "mm0b1", side: "Buy", price: 64000.5, size: 0.001, price_dp: 1, size_dp: 4, reduce_only: false };
+        let data = kraken::send_order_data(&order);
+        assert_eq!(data, "orderType=post&symbol=PF_XBTUSD&side=buy&size=0.0010&limitPrice=64000.5&cliOrdId=mm0b1&reduceOnly=false");
+        let expected = "fmqaf6shuVra1ncDHZ5ppTFD0Tvvv5FB8yzDx4yXJqU1zElYHqkejXUgB06yG6qjMX5e+vwTBaNx4S077MsvIA==";
+        assert_eq!(creds.authent(&data, "1700000000000", "/api/v3/sendorder"), expected);
+        assert_eq!(creds.authent(&data, "1700000000000", "/derivatives/api/v3/sendorder"), expected);
+        assert!(KrakenCredentials::new("pubkey", "not base64!").is_err());
+
+        let mut placed = br#"{"result":"success","sendStatus":{"order_id":"179f9af8-e45e-469d-b3e9-2fd4675cb7d0","status":"placed"}}"#.to_vec();
+        assert_eq!(kraken::parse_order_response(&mut placed).unwrap(), "179f9af8-e45e-469d-b3e9-2fd4675cb7d0");
+        let mut crossed = br#"{"result":"success","sendStatus":{"status":"postWouldExecute"}}"#.to_vec();
+        assert_eq!(kraken::parse_order_response(&mut crossed).unwrap_err(), "Kraken error: postWouldExecute");
+    }
+
+    #[test]
+    fn book_is_sequenced_and_checksummed() {
+        assert_eq!(kraken::crc32(b"123456789"), 0xcbf4_3926);
+        let mut book = L2OrderBook::new();
+        let mut checker = BookChecker::new(1, 4);
+        let mut msgs = [
+            br#"{"feed":"book_snapshot","product_id":"PF_XBTUSD","timestamp":1700000000000,"seq":10,"bids":[{"price":64000.0,"qty":2.0},{"price":63999.5,"qty":0.001}],"asks":[{"price":64000.5,"qty":0.5},{"price":64001.0,"qty":1.25}],"checksum":2231343450}"#.to_vec(),
+            br#"{"feed":"book","product_id":"PF_XBTUSD","side":"buy","seq":11,"price":64000.0,"qty":3.0,"timestamp":1700000000100,"checksum":1500070111}"#.to_vec(),
+            // Our copy no longer matches
+            br#"{"feed":"book","product_id":"PF_XBTUSD","side":"sell","seq":12,"price":64000.5,"qty":0.0,"timestamp":1700000000200,"checksum":1}"#.to_vec(),
+            br#"{"feed":"book","product_id":"PF_XBTUSD","side":"sell","seq":13,"price":64001.0,"qty":0.0,"timestamp":1700000000300}"#.to_vec(),
+        ];
+        let mut results = Vec::new();
+        for msg in msgs.iter_mut() {
+            kraken::parse_message(msg, |event| {
+                if let KrakenEvent::Book { product, update } = event {
+                    assert_eq!(product, "PF_XBTUSD");
+                    results.push(checker.apply(&update, &mut book));
+                }
+            }).unwrap();
+            if results.len() == 2 {
+                assert_eq!((book.bids[0].price, book.bids[0].qty, book.asks[1].qty), (64000.0, 3.0, 1.25));
+            }
+        }
+        assert_eq!(results[..2], [Ok(()), Ok(())]);
+        assert!(matches!(results[2], Err(BookError::Checksum { expected: 1, .. })));
+        assert_eq!(results[3], Err(BookError::NotSynced));
+        assert_eq!(checker.checksum_failures, 1);
+
+        // A fresh snapshot resyncs; then a skipped seq is a gap
+        let mut resync = msgs[0].clone();
+        let mut skip = br#"{"feed":"book","product_id":"PF_XBTUSD","side":"buy","seq":12,"price":63999.5,"qty":0.0}"#.to_vec();
+        for msg in [&mut resync, &mut skip] {
+            kraken::parse_message(msg, |event| {
+                if let KrakenEvent::Book { update, .. } = event {
+                    results.push(checker.apply(&update, &mut book));
+                }
+            }).unwrap();
+        }
+        assert_eq!(results[4..], [Ok(()), Err(BookError::Gap { expected: 11, got: 12 })]);
+        assert_eq!(checker.gaps, 1);
+    }
+
+    #[test]
+    fn events_and_fills_parse() {
+        let mut seen = Vec::new();
+        let mut msgs = [
+            br#"{"event":"info","version":1}"#.to_vec(),
+            br#"{"event":"challenge","message":"c100b894-1729-464d-ace1-52dbce11db42"}"#.to_vec(),
+            br#"{"event":"subscribed","feed":"fills","api_key":"pubkey"}"#.to_vec(),
+            br#"{"event":"error","message":"Invalid product id"}"#.to_vec(),
+            br#"{"feed":"heartbeat","time":1700000000000}"#.to_vec(),
+        ];
+        for msg in msgs.iter_mut() {
+            kraken::parse_message(msg, |event| seen.push(format!("{:?}", event))).unwrap();
+        }
+        assert_eq!(seen, [
+            "Info",
+            r#"Challenge { message: "c100b894-1729-464d-ace1-52dbce11db42" }"#,
+            r#"Subscribed { feed: "fills" }"#,
+            r#"Error { message: "Invalid product id" }"#,
+            "Heartbeat",
+        ]);
+
+        let mut fills = br#"{"feed":"fills","username":"u","fills":[{"instrument":"PF_XBTUSD","time":1700000000500,"price":64000.0,"seq":100,"buy":true,"qty":0.01,"order_id":"o1","cli_ord_id":"mm0b1","fill_id":"f1","fill_type":"maker","fee_paid":0.128,"fee_currency":"USD"}]}"#.to_vec();
+        let mut got = None;
+        assert_eq!(kraken::parse_message(&mut fills, |event| {
+            if let KrakenEvent::Fill { product, cli_ord_id, fill } = event {
+                got = Some((product.to_string(), cli_ord_id.to_string(), fill));
+            }
+        }).unwrap(), 1);
+        let (product, cli_ord_id, fill) = got.unwrap();
+        assert_eq!((product.as_str(), cli_ord_id.as_str()), ("PF_XBTUSD", "mm0b1"));
+        assert_eq!((fill.side, fill.qty, fill.px, fill.is_maker, fill.seq), ("Buy", 0.01, 64000.0, true, 100));
+        assert!((fill.fee_rate - 0.0002).abs() < 1e-12);
+
+        let mut history = br#"{"feed":"fills_snapshot","fills":[]}"#.to_vec();
+        assert_eq!(kraken::parse_message(&mut history, |_| panic!("no events")).unwrap(), 0);
+    }
+}
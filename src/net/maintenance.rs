@@ -0,0 +1,148 @@
+use simd_json::prelude::*;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::core::logging::info;
+
+const BYBIT_STATUS_URL: &str = "https://api.bybit.com/v5/system/status";
+
+// Bybit "product" code for USDT/USDC perpetuals & futures (our "linear" category).
+const PRODUCT_FUTURES: u64 = 1;
+
+const DEFAULT_POLL_INTERVAL_MS: u64 = 60_000;
+const DEFAULT_HALT_LEAD_MS: u64 = 10 * 60_000;   // flatten 10 min before the window
+const DEFAULT_RESUME_GRACE_MS: u64 = 2 * 60_000; // books are thin right after restart
+const HTTP_TIMEOUT_MS: u64 = 3_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MaintenanceConfig {
+    /// Status endpoint of the environment we trade on (`config::Environment::status_url`).
+    pub status_url: &'static str,
+    pub poll_interval: Duration,
+    /// How long before a window starts we stop quoting and flatten.
+    pub halt_lead: Duration,
+    /// How long after a window ends we stay halted.
+    pub resume_grace: Duration,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            status_url: BYBIT_STATUS_URL,
+            poll_interval: Duration::from_millis(DEFAULT_POLL_INTERVAL_MS),
+            halt_lead: Duration::from_millis(DEFAULT_HALT_LEAD_MS),
+            resume_grace: Duration::from_millis(DEFAULT_RESUME_GRACE_MS),
+        }
+    }
+}
+
+/// One scheduled or ongoing maintenance affecting our category. Times in unix ms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaintenanceWindow {
+    pub begin_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Parses a /v5/system/status response into windows that touch linear contracts.
+/// Completed/cancelled entries are dropped; an empty `product` list means "all products".
+pub fn parse_system_status(body: &mut [u8]) -> Result<Vec<MaintenanceWindow>, String> {
+    let json = simd_json::to_borrowed_value(body).map_err(|_| "Failed to parse status JSON".to_string())?;
+    let ret_code = json.get("retCode").and_then(|v| v.as_i64()).unwrap_or(-1);
+    if ret_code != 0 {
+        return Err(format!("Bybit status error: retCode {}", ret_code));
+    }
+
+    let mut windows = Vec::new();
+    let list = match json.get("result").and_then(|r| r.get("list")).and_then(|l| l.as_array()) {
+        Some(list) => list,
+        None => return Ok(windows),
+    };
+    for item in list {
+        match item.get("state").and_then(|v| v.as_str()) {
+            Some("scheduled") | Some("ongoing") => {}
+            _ => continue,
+        }
+        let affects_linear = match item.get("product").and_then(|v| v.as_array()) {
+            Some(products) => products.is_empty() || products.iter().any(|p| p.as_u64() == Some(PRODUCT_FUTURES)),
+            None => true,
+        };
+        if !affects_linear {
+            continue;
+        }
+        // Bybit sends times as strings of unix ms
+        let begin = item.get("begin").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok());
+        let end = item.get("end").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok());
+        if let (Some(begin_ms), Some(end_ms)) = (begin, end) {
+            windows.push(MaintenanceWindow { begin_ms, end_ms });
+        }
+    }
+    Ok(windows)
+}
+
+/// Polls Bybit's system status and decides whether trading should be halted.
+/// COLD THREAD ONLY (blocking HTTP). The result is published to the Hot Thread via an atomic.
+pub struct MaintenanceMonitor {
+    config: MaintenanceConfig,
+    last_poll: Option<Instant>,
+    windows: Vec<MaintenanceWindow>,
+    halted: bool,
+}
+
+impl MaintenanceMonitor {
+    pub fn new(config: MaintenanceConfig) -> Self {
+        Self {
+            config,
+            last_poll: None,
+            windows: Vec::new(),
+            halted: false,
+        }
+    }
+
+    /// Cheap to call every cold-loop iteration; fetches only once per interval.
+    /// Returns whether trading should be halted right now.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let due = match self.last_poll {
+            Some(last) => now.saturating_duration_since(last) >= self.config.poll_interval,
+            None => true,
+        };
+        if due {
+            self.last_poll = Some(now);
+            match fetch_status(self.config.status_url) {
+                // Keep the last known schedule if the endpoint is unreachable
+                Ok(windows) => self.windows = windows,
+                Err(e) => eprintln!("MAINT: Status poll failed: {}", e),
+            }
+        }
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let halted = self.should_halt(now_ms);
+        if halted != self.halted {
+            if halted {
+                eprintln!("MAINT: Exchange maintenance window ahead/active -> HALT (flatten, no quotes)");
+            } else {
+                info!(net, "MAINT: Maintenance window passed -> RESUME");
+            }
+            self.halted = halted;
+        }
+        halted
+    }
+
+    fn should_halt(&self, now_ms: u64) -> bool {
+        let lead = self.config.halt_lead.as_millis() as u64;
+        let grace = self.config.resume_grace.as_millis() as u64;
+        self.windows
+            .iter()
+            .any(|w| now_ms + lead >= w.begin_ms && now_ms < w.end_ms + grace)
+    }
+}
+
+fn fetch_status(url: &str) -> Result<Vec<MaintenanceWindow>, String> {
+    let response = ureq::get(url)
+        .timeout(Duration::from_millis(HTTP_TIMEOUT_MS))
+        .call()
+        .map_err(|e| format!("HTTP error: {}", e))?;
+    let body = response.into_string().map_err(|e| format!("Failed to read response: {}", e))?;
+    let mut bytes = body.into_bytes();
+    parse_system_status(&mut bytes)
+}
@@ -1,4 +1,69 @@
+//! Network layer: TLS (optional certificate pinning)/WebSocket/REST clients, exchange WS adapters (Bybit; OKX, Deribit, Coinbase INTX and Kraken Futures connectors; Binance book ticker, user data stream and hedge orders), request pipeline, multi-address DNS cache, outage, feed-staleness and network monitors, dual public feed arbitration.
+
 pub mod ws_client;
+pub mod adapter;
+pub mod bybit;
 pub mod tls_client;
 pub mod tcp_opt;
 pub mod framing;
+pub mod pipeline;
+pub mod rest_client;
+pub mod netstat;
+pub mod maintenance;
+pub mod resolver;
+pub mod outage;
+pub mod feed_watch;
+pub mod feed_arbiter;
+pub mod okx;
+pub mod binance;
+pub mod deribit;
+pub mod coinbase;
+pub mod kraken;
+pub mod startup;
+#[cfg(feature = "tokio-runtime")]
+pub mod async_ws;
+
+#[cfg(test)]
+mod adapter_tests;
+
+#[cfg(test)]
+mod binance_tests;
+
+#[cfg(test)]
+mod coinbase_tests;
+
+#[cfg(test)]
+mod deribit_tests;
+
+#[cfg(test)]
+mod feed_arbiter_tests;
+
+#[cfg(test)]
+mod feed_watch_tests;
+
+#[cfg(test)]
+mod framing_tests;
+
+#[cfg(test)]
+mod kraken_tests;
+
+#[cfg(test)]
+mod okx_tests;
+
+#[cfg(test)]
+mod outage_tests;
+
+#[cfg(test)]
+mod pipeline_tests;
+
+#[cfg(test)]
+mod resolver_tests;
+
+#[cfg(test)]
+mod startup_tests;
+
+#[cfg(test)]
+mod tls_client_tests;
+
+#[cfg(test)]
+mod ws_client_tests;
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+// Sampling cadence and alert thresholds.
+// TCP retransmits are the usual cause of "mystery" 100-200ms outliers
+// (RTO min is 200ms on Linux), so we watch the ratio, not just the count.
+const DEFAULT_SAMPLE_INTERVAL_MS: u64 = 5_000;
+const DEFAULT_RETRANS_RATIO_ALERT: f64 = 0.01; // 1% of outgoing segments
+const DEFAULT_MIN_RETRANS_ALERT: u64 = 5;      // ignore single stray retransmits
+
+#[derive(Debug, Clone, Copy)]
+pub struct NetStatsConfig {
+    pub sample_interval: Duration,
+    pub retrans_ratio_alert: f64,
+    pub min_retrans_alert: u64,
+}
+
+impl Default for NetStatsConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_millis(DEFAULT_SAMPLE_INTERVAL_MS),
+            retrans_ratio_alert: DEFAULT_RETRANS_RATIO_ALERT,
+            min_retrans_alert: DEFAULT_MIN_RETRANS_ALERT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IfaceCounters {
+    pub rx_errs: u64,
+    pub rx_drops: u64,
+    pub tx_errs: u64,
+    pub tx_drops: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TcpCounters {
+    pub out_segs: u64,
+    pub retrans_segs: u64,
+}
+
+/// Parses /proc/net/dev into (interface, counters), skipping loopback.
+/// Row layout after "iface:": rx bytes packets errs drop fifo frame compressed multicast,
+/// then tx bytes packets errs drop ...
+pub fn parse_proc_net_dev(text: &str) -> Vec<(String, IfaceCounters)> {
+    let mut out = Vec::new();
+    for line in text.lines().skip(2) {
+        let (name, rest) = match line.split_once(':') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let name = name.trim();
+        if name == "lo" {
+            continue;
+        }
+        let mut fields = [0u64; 16];
+        for (i, f) in rest.split_whitespace().take(16).enumerate() {
+            fields[i] = f.parse().unwrap_or(0);
+        }
+        out.push((name.to_string(), IfaceCounters {
+            rx_errs: fields[2],
+            rx_drops: fields[3],
+            tx_errs: fields[10],
+            tx_drops: fields[11],
+        }));
+    }
+    out
+}
+
+/// Parses the "Tcp:" header/value line pair of /proc/net/snmp.
+pub fn parse_proc_net_snmp(text: &str) -> Option<TcpCounters> {
+    let mut tcp_lines = text.lines().filter(|l| l.starts_with("Tcp:"));
+    let header = tcp_lines.next()?;
+    let values = tcp_lines.next()?;
+
+    let mut counters = TcpCounters::default();
+    for (name, value) in header.split_whitespace().zip(values.split_whitespace()).skip(1) {
+        match name {
+            "OutSegs" => counters.out_segs = value.parse().ok()?,
+            "RetransSegs" => counters.retrans_segs = value.parse().ok()?,
+            _ => {}
+        }
+    }
+    Some(counters)
+}
+
+/// Periodic interface/TCP health sampler. COLD THREAD ONLY (reads procfs, allocates).
+/// On non-Linux targets `poll` is a no-op.
+pub struct NetStatsMonitor {
+    config: NetStatsConfig,
+    last_sample: Option<Instant>,
+    prev_ifaces: Vec<(String, IfaceCounters)>,
+    prev_tcp: Option<TcpCounters>,
+    pub retrans_alerts: u64,
+    pub drop_alerts: u64,
+}
+
+impl NetStatsMonitor {
+    pub fn new(config: NetStatsConfig) -> Self {
+        Self {
+            config,
+            last_sample: None,
+            prev_ifaces: Vec::new(),
+            prev_tcp: None,
+            retrans_alerts: 0,
+            drop_alerts: 0,
+        }
+    }
+
+    /// Cheap to call every cold-loop iteration; samples only once per interval.
+    /// Returns true when this sample raised an alert (local network trouble).
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if let Some(last) = self.last_sample {
+            if now.saturating_duration_since(last) < self.config.sample_interval {
+                return false;
+            }
+        }
+        self.last_sample = Some(now);
+
+        #[cfg(target_os = "linux")]
+        let alerted = {
+            let ifaces = std::fs::read_to_string("/proc/net/dev")
+                .map(|t| parse_proc_net_dev(&t))
+                .unwrap_or_default();
+            let tcp = std::fs::read_to_string("/proc/net/snmp")
+                .ok()
+                .and_then(|t| parse_proc_net_snmp(&t));
+            self.evaluate(ifaces, tcp)
+        };
+        #[cfg(not(target_os = "linux"))]
+        let alerted = false;
+        alerted
+    }
+
+    /// Compares a fresh sample with the previous one and alerts on spikes.
+    fn evaluate(&mut self, ifaces: Vec<(String, IfaceCounters)>, tcp: Option<TcpCounters>) -> bool {
+        let alerts_before = self.drop_alerts + self.retrans_alerts;
+        for (name, cur) in &ifaces {
+            if let Some((_, prev)) = self.prev_ifaces.iter().find(|(n, _)| n == name) {
+                let rx_drops = cur.rx_drops.saturating_sub(prev.rx_drops);
+                let tx_drops = cur.tx_drops.saturating_sub(prev.tx_drops);
+                let errs = cur.rx_errs.saturating_sub(prev.rx_errs) + cur.tx_errs.saturating_sub(prev.tx_errs);
+                if rx_drops + tx_drops + errs > 0 {
+                    self.drop_alerts += 1;
+                    eprintln!("NET ALERT: {} drops rx={} tx={} errs={} in last {:?}",
+                        name, rx_drops, tx_drops, errs, self.config.sample_interval);
+                }
+            }
+        }
+
+        if let (Some(cur), Some(prev)) = (tcp, self.prev_tcp) {
+            let retrans = cur.retrans_segs.saturating_sub(prev.retrans_segs);
+            let out = cur.out_segs.saturating_sub(prev.out_segs).max(1);
+            let ratio = retrans as f64 / out as f64;
+            if retrans >= self.config.min_retrans_alert && ratio > self.config.retrans_ratio_alert {
+                self.retrans_alerts += 1;
+                eprintln!("NET ALERT: TCP retransmit spike {} / {} segs ({:.2}%) in last {:?}",
+                    retrans, out, ratio * 100.0, self.config.sample_interval);
+            }
+        }
+
+        self.prev_ifaces = ifaces;
+        if tcp.is_some() {
+            self.prev_tcp = tcp;
+        }
+        self.drop_alerts + self.retrans_alerts > alerts_before
+    }
+}
@@ -0,0 +1,329 @@
+use std::time::Duration;
+use ring::hmac;
+use simd_json::prelude::*;
+use simd_json::BorrowedValue;
+use crate::auth::secrets::{lookup_all, redacted_debug, SecretProvider};
+use crate::core::base64;
+use crate::core::logging::info;
+use crate::core::orderbook::{L2OrderBook, Side};
+use crate::core::parser::{num_field, str_field, u64_field, ParseError};
+use crate::net::adapter::{ConnectionState, ExchangeAdapter, WsConnection};
+use crate::net::ws_client::{WsClient, WsClientBuilder};
+use crate::strategy::fill::Fill;
+
+// OKX V5 connector: the same WsClient (handshake, TLS, framing, auto-ping) as Bybit, with
+// OKX's endpoints, login, channel names and message shapes. Public `books5` (top 5 levels,
+// full snapshot every push, no checksum state to keep) is the reference book; the private
+// `orders` channel carries order state and fills. Order entry goes over the same private
+// connection (`order` / `amend-order` / `cancel-order` ops, answered by `id`).
+// Unlike Bybit:
+//   * login signs `timestamp + "GET/users/self/verify"` (timestamp in seconds), base64, and
+//     needs the API passphrase besides key and secret;
+//   * keepalive is the bare text `ping`, answered with a bare `pong` (not JSON); OKX drops a
+//     connection that sent nothing for 30s;
+//   * `sz` is in contracts (instrument `ctVal`), not base coin; `clOrdId` and `id` are
+//     alphanumeric, up to 32 chars;
+//   * fees come negative when charged, positive for a rebate (Bybit: the other way round).
+pub const OKX_HOST: &str = "ws.okx.com";
+pub const OKX_PORT: u16 = 8443;
+pub const OKX_PUBLIC_PATH: &str = "/ws/v5/public";
+pub const OKX_PRIVATE_PATH: &str = "/ws/v5/private";
+pub const OKX_PING_INTERVAL: Duration = Duration::from_secs(25);
+pub const OKX_PING_MSG: &str = "ping";
+pub const KEY_VAR: &str = "OKX_API_KEY";
+pub const SECRET_VAR: &str = "OKX_SECRET_KEY";
+pub const PASSPHRASE_VAR: &str = "OKX_PASSPHRASE";
+pub const BOOK_DEPTH: usize = 5;
+const BOOKS_CHANNEL: &str = "books5";
+const ORDERS_CHANNEL: &str = "orders";
+const LOGIN_PATH: &str = "GET/users/self/verify";
+
+/// Public connection (books).
+pub fn public_builder() -> WsClientBuilder {
+    WsClientBuilder::new(OKX_HOST).port(OKX_PORT).path(OKX_PUBLIC_PATH).auto_ping(OKX_PING_INTERVAL, Some(OKX_PING_MSG))
+}
+
+/// Private connection (login, orders channel, order entry).
+pub fn private_builder() -> WsClientBuilder {
+    WsClientBuilder::new(OKX_HOST).port(OKX_PORT).path(OKX_PRIVATE_PATH).auto_ping(OKX_PING_INTERVAL, Some(OKX_PING_MSG))
+}
+
+/// Public connection as an `ExchangeAdapter`: `books5` of `inst_ids` once upgraded.
+pub struct OkxPublic {
+    conn: WsConnection,
+    subscribe: String,
+}
+
+impl OkxPublic {
+    pub fn new(client: WsClient, inst_ids: &[&str]) -> Self {
+        Self { conn: WsConnection::new(client, "okx", ConnectionState::Subscribing), subscribe: subscribe_books(inst_ids) }
+    }
+}
+
+impl ExchangeAdapter for OkxPublic {
+    fn connection(&self) -> &WsConnection {
+        &self.conn
+    }
+
+    fn connection_mut(&mut self) -> &mut WsConnection {
+        &mut self.conn
+    }
+
+    fn setup_message(&mut self, _step: ConnectionState) -> Option<String> {
+        info!(net, "HOT: Sending OKX Subscription: {}", self.subscribe);
+        Some(self.subscribe.clone())
+    }
+}
+
+/// OKX API key, passphrase and the HMAC key of the secret.
+#[derive(Clone)]
+pub struct OkxCredentials {
+    pub api_key: String,
+    pub passphrase: String,
+    key: hmac::Key,
+}
+
+redacted_debug!(OkxCredentials { api_key });
+
+impl OkxCredentials {
+    pub fn new(api_key: &str, secret: &str, passphrase: &str) -> Result<Self, String> {
+        let (api_key, secret, passphrase) = (api_key.trim(), secret.trim(), passphrase.trim());
+        if api_key.is_empty() || secret.is_empty() || passphrase.is_empty() {
+            return Err("empty OKX API key, secret or passphrase".to_string());
+        }
+        Ok(Self { api_key: api_key.to_string(), passphrase: passphrase.to_string(), key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()) })
+    }
+
+    pub fn load(provider: &dyn SecretProvider) -> Result<Self, String> {
+        let [key, secret, passphrase] = lookup_all(provider, [KEY_VAR, SECRET_VAR, PASSPHRASE_VAR])?;
+        Self::new(&key, &secret, &passphrase)
+    }
+
+    /// base64(HMAC-SHA256(secret, prehash)).
+    pub fn sign(&self, prehash: &str) -> String {
+        base64::encode(hmac::sign(&self.key, prehash.as_bytes()).as_ref())
+    }
+
+    /// `login` op for the private connection; `ts_secs` is Unix seconds (OKX allows 30s of skew).
+    pub fn login_message(&self, ts_secs: u64) -> String {
+        let sign = self.sign(&format!("{}{}", ts_secs, LOGIN_PATH));
+        format!(r#"{{"op":"login","args":[{{"apiKey":"{}","passphrase":"{}","timestamp":"{}","sign":"{}"}}]}}"#,
+            self.api_key, self.passphrase, ts_secs, sign)
+    }
+}
+
+/// `books5` for every instId, one message.
+pub fn subscribe_books(inst_ids: &[&str]) -> String {
+    let args: Vec<String> = inst_ids.iter().map(|id| format!(r#"{{"channel":"{}","instId":"{}"}}"#, BOOKS_CHANNEL, id)).collect();
+    format!(r#"{{"op":"subscribe","args":[{}]}}"#, args.join(","))
+}
+
+/// `orders` for an instrument type (`SWAP`, `FUTURES`, `SPOT`, `ANY`), after login.
+pub fn subscribe_orders(inst_type: &str) -> String {
+    format!(r#"{{"op":"subscribe","args":[{{"channel":"{}","instType":"{}"}}]}}"#, ORDERS_CHANNEL, inst_type)
+}
+
+fn okx_side(side: &str) -> &'static str {
+    if side == "Buy" { "buy" } else { "sell" }
+}
+
+/// PostOnly limit order (cross margin). `side` is "Buy" | "Sell" like everywhere else in the bot.
+#[derive(Debug, Clone, Copy)]
+pub struct OkxOrder<'a> {
+    pub inst_id: &'a str,
+    pub cl_ord_id: &'a str,
+    pub side: &'a str,
+    pub px: f64,
+    /// Contracts.
+    pub sz: f64,
+    /// Decimals of the price / size grid.
+    pub px_dp: usize,
+    pub sz_dp: usize,
+    pub reduce_only: bool,
+}
+
+/// `order` op; the response comes back with the same `id`.
+pub fn order_message(id: &str, order: &OkxOrder) -> String {
+    format!(r#"{{"id":"{}","op":"order","args":[{{"instId":"{}","tdMode":"cross","clOrdId":"{}","side":"{}","ordType":"post_only","px":"{:.*}","sz":"{:.*}","reduceOnly":{}}}]}}"#,
+        id, order.inst_id, order.cl_ord_id, okx_side(order.side), order.px_dp, order.px, order.sz_dp, order.sz, order.reduce_only)
+}
+
+/// `amend-order` op: only the fields that change, as (value, decimals).
+pub fn amend_message(id: &str, inst_id: &str, cl_ord_id: &str, px: Option<(f64, usize)>, sz: Option<(f64, usize)>) -> String {
+    let mut fields = String::new();
+    if let Some((px, dp)) = px {
+        fields.push_str(&format!(r#","newPx":"{:.*}""#, dp, px));
+    }
+    if let Some((sz, dp)) = sz {
+        fields.push_str(&format!(r#","newSz":"{:.*}""#, dp, sz));
+    }
+    format!(r#"{{"id":"{}","op":"amend-order","args":[{{"instId":"{}","clOrdId":"{}"{}}}]}}"#, id, inst_id, cl_ord_id, fields)
+}
+
+/// `cancel-order` op.
+pub fn cancel_message(id: &str, inst_id: &str, cl_ord_id: &str) -> String {
+    format!(r#"{{"id":"{}","op":"cancel-order","args":[{{"instId":"{}","clOrdId":"{}"}}]}}"#, id, inst_id, cl_ord_id)
+}
+
+/// One `books5` push: the whole top of book. Unused levels are (0, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OkxBook {
+    pub ts: u64,
+    /// (price, contracts), best first.
+    pub bids: [(f64, f64); BOOK_DEPTH],
+    pub asks: [(f64, f64); BOOK_DEPTH],
+}
+
+impl OkxBook {
+    pub fn best_bid(&self) -> f64 {
+        self.bids[0].0
+    }
+
+    pub fn best_ask(&self) -> f64 {
+        self.asks[0].0
+    }
+
+    /// Replaces `book` with these levels (each push is a snapshot).
+    pub fn apply(&self, book: &mut L2OrderBook) {
+        book.clear();
+        for &(px, sz) in self.bids.iter().filter(|l| l.0 > 0.0) {
+            book.update(Side::Buy, px, sz);
+        }
+        for &(px, sz) in self.asks.iter().filter(|l| l.0 > 0.0) {
+            book.update(Side::Sell, px, sz);
+        }
+        book.snapshot_applied = true;
+    }
+}
+
+/// One item of the `orders` channel.
+#[derive(Debug, Clone, Copy)]
+pub struct OkxOrderUpdate<'a> {
+    pub inst_id: &'a str,
+    pub ord_id: &'a str,
+    pub cl_ord_id: &'a str,
+    pub side: &'static str,
+    pub px: f64,
+    pub sz: f64,
+    /// live | partially_filled | filled | canceled | mmp_canceled
+    pub state: &'a str,
+    /// The execution this push reports, if any (fee sign already flipped to Bybit's).
+    pub fill: Option<Fill>,
+}
+
+/// Normalized OKX message.
+#[derive(Debug, Clone, Copy)]
+pub enum OkxEvent<'a> {
+    Pong,
+    Login { ok: bool, code: &'a str, msg: &'a str },
+    Subscribed { channel: &'a str },
+    /// `event: error` (bad login, bad subscribe, ...).
+    Error { code: &'a str, msg: &'a str },
+    Book { inst_id: &'a str, book: OkxBook },
+    Order(OkxOrderUpdate<'a>),
+    /// Answer to an `order` / `amend-order` / `cancel-order`, one per order; `code` "0" = accepted.
+    OrderAck { id: &'a str, op: &'a str, cl_ord_id: &'a str, code: &'a str, msg: &'a str },
+}
+
+fn levels(item: &BorrowedValue, key: &str) -> [(f64, f64); BOOK_DEPTH] {
+    let mut out = [(0.0, 0.0); BOOK_DEPTH];
+    let Some(arr) = item.get(key).and_then(|v| v.as_array()) else { return out };
+    for (slot, level) in out.iter_mut().zip(arr.iter().filter_map(|l| l.as_array())) {
+        // [px, sz, "0" (deprecated), order count]
+        let field = |i: usize| level.get(i).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+        *slot = (field(0), field(1));
+    }
+    out
+}
+
+fn order_update<'v>(item: &'v BorrowedValue<'v>) -> Option<OkxOrderUpdate<'v>> {
+    let side = match str_field(item, "side") {
+        "buy" => "Buy",
+        "sell" => "Sell",
+        _ => return None,
+    };
+    let fill_sz = num_field(item, "fillSz").unwrap_or(0.0);
+    let fill = (fill_sz > 0.0 && !str_field(item, "tradeId").is_empty()).then(|| {
+        let px = num_field(item, "fillPx").unwrap_or(0.0);
+        let fee = -num_field(item, "fillFee").unwrap_or(0.0);
+        Fill {
+            side,
+            qty: fill_sz,
+            px,
+            fee,
+            fee_rate: if px > 0.0 { fee / (fill_sz * px) } else { 0.0 },
+            is_maker: str_field(item, "execType") == "M",
+            closed_pnl: num_field(item, "fillPnl").unwrap_or(0.0),
+            exec_time_ms: u64_field(item, "fillTime").unwrap_or(0),
+            seq: 0,
+        }
+    });
+    Some(OkxOrderUpdate {
+        inst_id: str_field(item, "instId"),
+        ord_id: str_field(item, "ordId"),
+        cl_ord_id: str_field(item, "clOrdId"),
+        side,
+        px: num_field(item, "px").unwrap_or(0.0),
+        sz: num_field(item, "sz").unwrap_or(0.0),
+        state: str_field(item, "state"),
+        fill,
+    })
+}
+
+/// Calls `on_event` for each event in one OKX text message (public or private). Returns the
+/// number of events; messages of other channels yield `Ok(0)`.
+pub fn parse_message<F: FnMut(OkxEvent)>(data: &mut [u8], mut on_event: F) -> Result<usize, ParseError> {
+    if data.trim_ascii() == b"pong" {
+        on_event(OkxEvent::Pong);
+        return Ok(1);
+    }
+    let tape = simd_json::to_borrowed_value(data)?;
+    if let Some(event) = tape.get("event").and_then(|v| v.as_str()) {
+        let (code, msg) = (str_field(&tape, "code"), str_field(&tape, "msg"));
+        on_event(match event {
+            "login" => OkxEvent::Login { ok: code == "0" || code.is_empty(), code, msg },
+            "subscribe" => OkxEvent::Subscribed { channel: tape.get("arg").map(|arg| str_field(arg, "channel")).unwrap_or("") },
+            "error" => OkxEvent::Error { code, msg },
+            _ => return Ok(0),
+        });
+        return Ok(1);
+    }
+    if let Some(op) = tape.get("op").and_then(|v| v.as_str()) {
+        let (id, code, msg) = (str_field(&tape, "id"), str_field(&tape, "code"), str_field(&tape, "msg"));
+        let items = tape.get("data").and_then(|v| v.as_array()).map(|a| a.as_slice()).unwrap_or(&[]);
+        if items.is_empty() {
+            on_event(OkxEvent::OrderAck { id, op, cl_ord_id: "", code, msg });
+            return Ok(1);
+        }
+        // Per-order result wins: a batch can be "1" (partial) with some orders accepted
+        for item in items {
+            let s_code = str_field(item, "sCode");
+            let (code, msg) = if s_code.is_empty() { (code, msg) } else { (s_code, str_field(item, "sMsg")) };
+            on_event(OkxEvent::OrderAck { id, op, cl_ord_id: str_field(item, "clOrdId"), code, msg });
+        }
+        return Ok(items.len());
+    }
+    let channel = tape.get("arg").map(|arg| str_field(arg, "channel")).unwrap_or("");
+    let items = tape.get("data").and_then(|v| v.as_array()).ok_or(ParseError::Schema("okx: data is not an array"))?;
+    let mut n = 0;
+    match channel {
+        BOOKS_CHANNEL => {
+            let inst_id = tape.get("arg").map(|arg| str_field(arg, "instId")).unwrap_or("");
+            for item in items {
+                on_event(OkxEvent::Book {
+                    inst_id,
+                    book: OkxBook { ts: u64_field(item, "ts").unwrap_or(0), bids: levels(item, "bids"), asks: levels(item, "asks") },
+                });
+                n += 1;
+            }
+        }
+        ORDERS_CHANNEL => {
+            for update in items.iter().filter_map(order_update) {
+                on_event(OkxEvent::Order(update));
+                n += 1;
+            }
+        }
+        _ => {}
+    }
+    Ok(n)
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::core::orderbook::L2OrderBook;
+    use crate::net::okx::{self, OkxCredentials, OkxEvent, OkxOrder};
+
+    #[test]
+    fn login_and_order_messages() {
+        let creds = OkxCredentials::new("key", "22582BD0CFF14C41EDBF1AB98506286D", "pass").unwrap();
+        // base64(HMAC-SHA256(secret, "1538054050GET/users/self/verify"))
+        assert_eq!(creds.sign("1538054050GET/users/self/verify"), "+LdIr8lkkvhr5hoA3g9TMC0+uQJ849ftAcocA/ouu4M=");
+        assert!(creds.login_message(1538054050).contains(r#""passphrase":"pass","timestamp":"1538054050","sign":"+LdIr8lkkvhr5hoA3g9TMC0+uQJ849ftAcocA/ouu4M=""#));
+        assert!(!format!("{:?}", creds).contains("pass\""));
+
+        let order = OkxOrder { inst_id: "BTC-USDT-SWAP", cl_ord_id: "mm0b1", side: "Buy", px: 64000.1, sz: 2.0, px_dp: 1, sz_dp: 0, reduce_only: false };
+        assert_eq!(okx::order_message("7", &order),
+            r#"{"id":"7","op":"order","args":[{"instId":"BTC-USDT-SWAP","tdMode":"cross","clOrdId":"mm0b1","side":"buy","ordType":"post_only","px":"64000.1","sz":"2","reduceOnly":false}]}"#);
+        assert_eq!(okx::amend_message("8", "BTC-USDT-SWAP", "mm0b1", Some((64001.0, 1)), None),
+            r#"{"id":"8","op":"amend-order","args":[{"instId":"BTC-USDT-SWAP","clOrdId":"mm0b1","newPx":"64001.0"}]}"#);
+    }
+
+    #[test]
+    fn parses_books_orders_and_acks() {
+        let mut events = Vec::new();
+        let mut msg = br#"{"arg":{"channel":"books5","instId":"BTC-USDT-SWAP"},"data":[{"asks":[["64001.2","30","0","3"],["64001.3","5","0","1"]],"bids":[["64001.1","12","0","2"]],"instId":"BTC-USDT-SWAP","ts":"1700000000123"}]}"#.to_vec();
+        let mut book = L2OrderBook::new();
+        okx::parse_message(&mut msg, |event| if let OkxEvent::Book { inst_id, book: top } = event {
+            assert_eq!(inst_id, "BTC-USDT-SWAP");
+            assert_eq!((top.ts, top.best_bid(), top.best_ask(), top.asks[1]), (1_700_000_000_123, 64001.1, 64001.2, (64001.3, 5.0)));
+            top.apply(&mut book);
+            events.push("book");
+        }).unwrap();
+        assert!(book.snapshot_applied && book.is_ready(1));
+
+        // Maker fill with a rebate: OKX reports it positive, Fill keeps Bybit's sign (negative = rebate)
+        let mut msg = br#"{"arg":{"channel":"orders","instType":"SWAP"},"data":[{"instId":"BTC-USDT-SWAP","ordId":"1","clOrdId":"mm0b1","side":"buy","px":"64000.1","sz":"2","state":"partially_filled","fillPx":"64000.1","fillSz":"1","fillFee":"0.0128","execType":"M","tradeId":"99","fillTime":"1700000000200","fillPnl":"0"}]}"#.to_vec();
+        okx::parse_message(&mut msg, |event| if let OkxEvent::Order(update) = event {
+            let fill = update.fill.unwrap();
+            assert_eq!((update.cl_ord_id, update.state, fill.side, fill.qty, fill.is_maker), ("mm0b1", "partially_filled", "Buy", 1.0, true));
+            assert!(fill.fee < 0.0 && fill.exec_time_ms == 1_700_000_000_200);
+            events.push("order");
+        }).unwrap();
+
+        let mut msg = br#"{"id":"7","op":"order","code":"1","msg":"","data":[{"clOrdId":"mm0b1","ordId":"","sCode":"51008","sMsg":"Insufficient balance"}]}"#.to_vec();
+        okx::parse_message(&mut msg, |event| if let OkxEvent::OrderAck { id, cl_ord_id, code, .. } = event {
+            assert_eq!((id, cl_ord_id, code), ("7", "mm0b1", "51008"));
+            events.push("ack");
+        }).unwrap();
+
+        let mut msg = b"pong".to_vec();
+        okx::parse_message(&mut msg, |event| if let OkxEvent::Pong = event { events.push("pong") }).unwrap();
+        let mut msg = br#"{"event":"login","code":"0","msg":""}"#.to_vec();
+        okx::parse_message(&mut msg, |event| if let OkxEvent::Login { ok: true, .. } = event { events.push("login") }).unwrap();
+        assert_eq!(events, ["book", "order", "ack", "pong", "login"]);
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::{Duration, Instant};
+use crate::ipc::session::Conn;
+
+// Exchange incident vs. our own network. The exchange is the problem when it answers
+// with server errors (REST 5xx / retCode 10016), or when every connection resets or
+// goes silent together while the local network looks healthy (no NIC drop / TCP
+// retransmit alert from NetStatsMonitor). One flaky connection, or trouble right
+// after a local network alert, is left to the normal reconnect path.
+// Owned by the Hot Thread: plain counters, Instants only in `poll`.
+const DEFAULT_WINDOW_SECS: u64 = 30;
+const DEFAULT_SERVER_ERRORS: u32 = 5;
+const DEFAULT_RESET_CONNS: u32 = 2;
+// Private/Trade only carry pongs when idle (20s ping), so silence below that is normal.
+const DEFAULT_STALE_SECS: u64 = 25;
+const DEFAULT_LOCAL_GRACE_SECS: u64 = 60;
+const DEFAULT_RECOVERY_SECS: u64 = 60;
+
+/// Bybit retCodes that mean the exchange itself failed the request.
+pub const SERVER_ERROR_CODES: [i64; 2] = [10016, 10019];
+
+#[derive(Debug, Clone, Copy)]
+pub struct OutageConfig {
+    /// Counting window for server errors and resets.
+    pub window: Duration,
+    /// Server errors within `window` that make an incident.
+    pub server_errors: u32,
+    /// Distinct connections reset within `window` that make an incident.
+    pub reset_conns: u32,
+    /// Every connected connection silent this long = incident.
+    pub stale_after: Duration,
+    /// After a local network alert, resets and silence are blamed on us, not the exchange.
+    pub local_grace: Duration,
+    /// Quiet time (no new signal) before trading resumes.
+    pub recovery: Duration,
+}
+
+impl Default for OutageConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(DEFAULT_WINDOW_SECS),
+            server_errors: DEFAULT_SERVER_ERRORS,
+            reset_conns: DEFAULT_RESET_CONNS,
+            stale_after: Duration::from_secs(DEFAULT_STALE_SECS),
+            local_grace: Duration::from_secs(DEFAULT_LOCAL_GRACE_SECS),
+            recovery: Duration::from_secs(DEFAULT_RECOVERY_SECS),
+        }
+    }
+}
+
+/// What tripped the detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutageSignal {
+    ServerErrors { count: u32 },
+    ConnectionResets { conns: u32 },
+    AllStale { silent_secs: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutageEvent {
+    /// Incident detected: stop quoting, flatten.
+    Halt(OutageSignal),
+    /// No signal for `recovery`: trading may resume.
+    Resume,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ConnState {
+    connected: bool,
+    messages: u64,
+    seen_messages: u64,
+    last_activity: Option<Instant>,
+    reset_in_window: bool,
+}
+
+pub struct OutageDetector {
+    pub config: OutageConfig,
+    pub incidents: u64,
+    conns: [ConnState; 3],
+    window_start: Option<Instant>,
+    server_errors: u32,
+    pending_resets: u32,
+    local_alert: bool,
+    local_alert_ts: Option<Instant>,
+    halted: Option<OutageSignal>,
+    last_signal: Option<Instant>,
+}
+
+impl OutageDetector {
+    pub fn new(config: OutageConfig) -> Self {
+        Self {
+            config,
+            incidents: 0,
+            conns: [ConnState::default(); 3],
+            window_start: None,
+            server_errors: 0,
+            pending_resets: 0,
+            local_alert: false,
+            local_alert_ts: None,
+            halted: None,
+            last_signal: None,
+        }
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted.is_some()
+    }
+
+    /// REST 5xx / retCode 10016, or a server error code on a WS response.
+    #[inline(always)]
+    pub fn on_server_error(&mut self) {
+        self.server_errors += 1;
+    }
+
+    #[inline(always)]
+    pub fn on_message(&mut self, conn: Conn) {
+        self.conns[conn as usize].messages += 1;
+    }
+
+    /// Connection lost (EOF / IO error). Repeated calls for the same outage count once.
+    pub fn on_disconnect(&mut self, conn: Conn) {
+        let state = &mut self.conns[conn as usize];
+        if state.connected {
+            state.connected = false;
+            if !state.reset_in_window {
+                state.reset_in_window = true;
+                self.pending_resets += 1;
+            }
+        }
+    }
+
+    pub fn on_connect(&mut self, conn: Conn, now: Instant) {
+        let state = &mut self.conns[conn as usize];
+        state.connected = true;
+        state.last_activity = Some(now);
+    }
+
+    /// NetStatsMonitor saw drops / a retransmit spike on our side.
+    pub fn on_local_network_alert(&mut self) {
+        self.local_alert = true;
+    }
+
+    /// Once per loop iteration. Returns a transition when the state changes.
+    pub fn poll(&mut self, now: Instant) -> Option<OutageEvent> {
+        if self.local_alert {
+            self.local_alert = false;
+            self.local_alert_ts = Some(now);
+        }
+        let local_suspect = self.local_alert_ts.is_some_and(|t| now.saturating_duration_since(t) < self.config.local_grace);
+
+        for state in self.conns.iter_mut() {
+            if state.messages != state.seen_messages {
+                state.seen_messages = state.messages;
+                state.last_activity = Some(now);
+            }
+        }
+
+        let signal = self.evaluate(now, local_suspect);
+        let window_start = *self.window_start.get_or_insert(now);
+        if now.saturating_duration_since(window_start) >= self.config.window {
+            self.window_start = Some(now);
+            self.server_errors = 0;
+            self.pending_resets = 0;
+            for state in self.conns.iter_mut() {
+                state.reset_in_window = false;
+            }
+        }
+
+        if let Some(signal) = signal {
+            self.last_signal = Some(now);
+            if self.halted.is_none() {
+                self.halted = Some(signal);
+                self.incidents += 1;
+                return Some(OutageEvent::Halt(signal));
+            }
+            return None;
+        }
+        let quiet = self.last_signal.is_none_or(|t| now.saturating_duration_since(t) >= self.config.recovery);
+        if self.halted.is_some() && quiet {
+            self.halted = None;
+            return Some(OutageEvent::Resume);
+        }
+        None
+    }
+
+    fn evaluate(&self, now: Instant, local_suspect: bool) -> Option<OutageSignal> {
+        // The exchange answered, so the network got there: never blamed on us
+        if self.server_errors >= self.config.server_errors {
+            return Some(OutageSignal::ServerErrors { count: self.server_errors });
+        }
+        if local_suspect {
+            return None;
+        }
+        if self.pending_resets >= self.config.reset_conns {
+            return Some(OutageSignal::ConnectionResets { conns: self.pending_resets });
+        }
+        if self.conns.iter().any(|c| !c.connected) {
+            return None; // something is still connecting: the reset path handles it
+        }
+        let silent = self
+            .conns
+            .iter()
+            .filter_map(|c| c.last_activity.map(|t| now.saturating_duration_since(t)))
+            .min()?;
+        if silent >= self.config.stale_after {
+            return Some(OutageSignal::AllStale { silent_secs: silent.as_secs() });
+        }
+        None
+    }
+}
+
+impl Default for OutageDetector {
+    fn default() -> Self {
+        Self::new(OutageConfig::default())
+    }
+}
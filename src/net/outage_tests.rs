@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::ipc::session::Conn;
+    use crate::net::outage::{OutageDetector, OutageEvent, OutageSignal};
+
+    fn connected(now: Instant) -> OutageDetector {
+        let mut det = OutageDetector::default();
+        for conn in [Conn::Public, Conn::Private, Conn::Trade] {
+            det.on_connect(conn, now);
+        }
+        det
+    }
+
+    #[test]
+    fn server_error_spike_halts_then_recovers() {
+        let t0 = Instant::now();
+        let mut det = connected(t0);
+        for _ in 0..4 {
+            det.on_server_error();
+        }
+        assert_eq!(det.poll(t0), None);
+        det.on_server_error();
+        assert_eq!(det.poll(t0), Some(OutageEvent::Halt(OutageSignal::ServerErrors { count: 5 })));
+        assert!(det.halted());
+
+        // The spike counts until its window closes (t0+30s); then `recovery` (60s) of quiet resumes
+        for secs in [30, 60, 89] {
+            for conn in [Conn::Public, Conn::Private, Conn::Trade] {
+                det.on_message(conn);
+            }
+            assert_eq!(det.poll(t0 + Duration::from_secs(secs)), None);
+        }
+        for conn in [Conn::Public, Conn::Private, Conn::Trade] {
+            det.on_message(conn);
+        }
+        assert_eq!(det.poll(t0 + Duration::from_secs(90)), Some(OutageEvent::Resume));
+        assert!(!det.halted());
+    }
+
+    #[test]
+    fn resets_across_connections_are_an_incident_unless_local_network_alerted() {
+        let t0 = Instant::now();
+        let mut det = connected(t0);
+        det.on_disconnect(Conn::Public);
+        assert_eq!(det.poll(t0), None); // one connection: normal reconnect path
+        det.on_disconnect(Conn::Trade);
+        assert_eq!(det.poll(t0), Some(OutageEvent::Halt(OutageSignal::ConnectionResets { conns: 2 })));
+
+        let mut local = connected(t0);
+        local.on_local_network_alert();
+        local.on_disconnect(Conn::Public);
+        local.on_disconnect(Conn::Trade);
+        assert_eq!(local.poll(t0), None);
+    }
+
+    #[test]
+    fn all_connections_silent_is_stale_one_busy_is_not() {
+        let t0 = Instant::now();
+        let mut det = connected(t0);
+        det.on_message(Conn::Public);
+        assert_eq!(det.poll(t0 + Duration::from_secs(10)), None);
+        assert_eq!(det.poll(t0 + Duration::from_secs(30)), None); // public spoke at t0+10s
+        assert_eq!(det.poll(t0 + Duration::from_secs(36)), Some(OutageEvent::Halt(OutageSignal::AllStale { silent_secs: 26 })));
+    }
+}
@@ -0,0 +1,217 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+// Queue geometry. Frames are copied into fixed slots so queuing never allocates.
+// main.rs encodes order frames into a buffer of exactly FRAME_SLOT_SIZE bytes, so a frame
+// that could be built always fits a slot (the largest order frame is under 500 bytes).
+pub const FRAME_SLOT_SIZE: usize = 1024;
+const QUEUE_CAPACITY: usize = 32;
+const MAX_IN_FLIGHT_CAP: usize = 32;
+
+const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+const DEFAULT_ACK_TIMEOUT_MS: u64 = 2_000;
+// A parked frame carries the X-BAPI-TIMESTAMP and the price of the tick that built it; past
+// this age it is expired instead of sent (the quote no longer reflects the book).
+const DEFAULT_MAX_QUEUE_AGE_MS: u64 = 1_000;
+// How long a missing response keeps the connection flagged as degraded
+const DEGRADED_HOLD_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineConfig {
+    /// Requests allowed on the wire without a response (clamped to MAX_IN_FLIGHT_CAP).
+    pub max_in_flight: usize,
+    /// A request without a response after this long stops counting as in-flight.
+    pub ack_timeout: Duration,
+    /// A parked frame older than this is never sent (`pop_expired` hands it back).
+    pub max_queue_age: Duration,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            ack_timeout: Duration::from_millis(DEFAULT_ACK_TIMEOUT_MS),
+            max_queue_age: Duration::from_millis(DEFAULT_MAX_QUEUE_AGE_MS),
+        }
+    }
+}
+
+/// Per-connection request pipeline for the Trade WS.
+///
+/// Bybit accepts several requests in flight on one session, but bursts (CancelAll +
+/// Close + two quotes after a shock) can exceed what the session tolerates.
+/// Frames beyond `max_in_flight` are parked in fixed slots and released as
+/// responses (anything carrying `reqId`) come back: lowest priority value first,
+/// FIFO within a priority, so a parked cancel overtakes parked quotes. Each parked frame
+/// carries a `T` from the caller (what to undo if it expires before it leaves).
+pub struct RequestPipeline<T: Copy = ()> {
+    config: PipelineConfig,
+
+    // In-flight send times, FIFO. Responses are matched oldest-first.
+    sent_ts: [Option<Instant>; MAX_IN_FLIGHT_CAP],
+    sent_head: usize,
+    in_flight: usize,
+
+    // Parked frames (len 0 = free slot), released by (priority, arrival).
+    slots: [[u8; FRAME_SLOT_SIZE]; QUEUE_CAPACITY],
+    slot_lens: [usize; QUEUE_CAPACITY],
+    slot_prio: [u8; QUEUE_CAPACITY],
+    slot_seq: [u64; QUEUE_CAPACITY],
+    slot_meta: [Option<(Instant, T)>; QUEUE_CAPACITY],
+    next_seq: u64,
+    queued: usize,
+
+    pub dropped: u64,
+    pub expired: u64,
+    pub timed_out: u64,
+    last_timeout_ts: Option<Instant>,
+}
+
+impl<T: Copy> RequestPipeline<T> {
+    pub fn new(config: PipelineConfig) -> Self {
+        let mut config = config;
+        config.max_in_flight = config.max_in_flight.clamp(1, MAX_IN_FLIGHT_CAP);
+        Self {
+            config,
+            sent_ts: [None; MAX_IN_FLIGHT_CAP],
+            sent_head: 0,
+            in_flight: 0,
+            slots: [[0u8; FRAME_SLOT_SIZE]; QUEUE_CAPACITY],
+            slot_lens: [0; QUEUE_CAPACITY],
+            slot_prio: [0; QUEUE_CAPACITY],
+            slot_seq: [0; QUEUE_CAPACITY],
+            slot_meta: [None; QUEUE_CAPACITY],
+            next_seq: 0,
+            queued: 0,
+            dropped: 0,
+            expired: 0,
+            timed_out: 0,
+            last_timeout_ts: None,
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queued
+    }
+
+    /// True if a new frame may go straight to the socket (nothing parked ahead of it).
+    pub fn can_send(&self) -> bool {
+        self.queued == 0 && self.in_flight < self.config.max_in_flight
+    }
+
+    /// Connection is unfit for urgent traffic: frames are parked behind the
+    /// in-flight limit, or a response went missing recently.
+    pub fn degraded(&self, now: Instant) -> bool {
+        if self.queued > 0 {
+            return true;
+        }
+        match self.last_timeout_ts {
+            Some(ts) => now.saturating_duration_since(ts) < Duration::from_millis(DEGRADED_HOLD_MS),
+            None => false,
+        }
+    }
+
+    /// Marks one request as written to the connection.
+    pub fn on_sent(&mut self, now: Instant) {
+        let idx = (self.sent_head + self.in_flight) % MAX_IN_FLIGHT_CAP;
+        self.sent_ts[idx] = Some(now);
+        self.in_flight += 1;
+    }
+
+    /// A response for one request arrived.
+    pub fn on_ack(&mut self) {
+        if self.in_flight == 0 {
+            return;
+        }
+        self.sent_ts[self.sent_head] = None;
+        self.sent_head = (self.sent_head + 1) % MAX_IN_FLIGHT_CAP;
+        self.in_flight -= 1;
+    }
+
+    /// Releases in-flight slots whose response never came (lost or unparseable reply),
+    /// so a single missing ack can't stall the pipeline forever.
+    pub fn expire(&mut self, now: Instant) {
+        while self.in_flight > 0 {
+            match self.sent_ts[self.sent_head] {
+                Some(ts) if now.saturating_duration_since(ts) >= self.config.ack_timeout => {
+                    self.on_ack();
+                    self.timed_out += 1;
+                    self.last_timeout_ts = Some(now);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Parks a frame until the connection has capacity; lower `priority` leaves first.
+    /// Returns false (and counts a drop) if the frame is empty, too large or the queue is full.
+    pub fn enqueue(&mut self, frame: &[u8], priority: u8, meta: T, now: Instant) -> bool {
+        let free = self.slot_lens.iter().position(|&len| len == 0);
+        let idx = match free {
+            Some(idx) if !frame.is_empty() && frame.len() <= FRAME_SLOT_SIZE => idx,
+            _ => {
+                self.dropped += 1;
+                return false;
+            }
+        };
+        self.slots[idx][..frame.len()].copy_from_slice(frame);
+        self.slot_lens[idx] = frame.len();
+        self.slot_prio[idx] = priority;
+        self.slot_seq[idx] = self.next_seq;
+        self.slot_meta[idx] = Some((now, meta));
+        self.next_seq += 1;
+        self.queued += 1;
+        true
+    }
+
+    fn stale(&self, idx: usize, now: Instant) -> bool {
+        self.slot_meta[idx].is_some_and(|(ts, _)| now.saturating_duration_since(ts) >= self.config.max_queue_age)
+    }
+
+    fn free(&mut self, idx: usize) -> Option<T> {
+        self.slot_lens[idx] = 0;
+        self.queued -= 1;
+        self.slot_meta[idx].take().map(|(_, meta)| meta)
+    }
+
+    // Parked slot that leaves next: lowest priority value, then oldest. Stale frames wait
+    // for `pop_expired`.
+    fn next_slot(&self, now: Instant) -> Option<usize> {
+        (0..QUEUE_CAPACITY)
+            .filter(|&idx| self.slot_lens[idx] > 0 && !self.stale(idx, now))
+            .min_by_key(|&idx| (self.slot_prio[idx], self.slot_seq[idx]))
+    }
+
+    /// Removes one parked frame older than `max_queue_age` and returns its `meta`; call until
+    /// None. Its timestamp and price are out of date, so it is not sent: the caller undoes
+    /// what it committed for it or rebuilds it.
+    pub fn pop_expired(&mut self, now: Instant) -> Option<T> {
+        let idx = (0..QUEUE_CAPACITY).find(|&idx| self.slot_lens[idx] > 0 && self.stale(idx, now))?;
+        self.expired += 1;
+        self.free(idx)
+    }
+
+    /// Sends parked frames while capacity allows, stale ones excepted. `send` writes one frame
+    /// to the connection. Returns the number of frames sent.
+    pub fn drain<F>(&mut self, now: Instant, mut send: F) -> usize
+    where
+        F: FnMut(&[u8]) -> io::Result<()>,
+    {
+        let mut sent = 0;
+        while self.in_flight < self.config.max_in_flight {
+            let Some(idx) = self.next_slot(now) else { break };
+            if let Err(e) = send(&self.slots[idx][..self.slot_lens[idx]]) {
+                eprintln!("Pipeline send error: {}", e);
+                break;
+            }
+            self.free(idx);
+            self.on_sent(now);
+            sent += 1;
+        }
+        sent
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::net::pipeline::{PipelineConfig, RequestPipeline, FRAME_SLOT_SIZE};
+
+    #[test]
+    fn parked_frames_leave_by_priority_then_fifo() {
+        let now = Instant::now();
+        let mut pipe = RequestPipeline::new(PipelineConfig { max_in_flight: 1, ..PipelineConfig::default() });
+        pipe.on_sent(now);
+        assert!(!pipe.can_send());
+
+        // Burst after a shock: quotes parked first, then the cancel and the close
+        for (frame, priority) in [(&b"quote-1"[..], 2), (b"amend", 1), (b"quote-2", 2), (b"cancel", 0), (b"close", 0)] {
+            assert!(pipe.enqueue(frame, priority, (), now));
+        }
+        let mut order = Vec::new();
+        for _ in 0..5 {
+            pipe.on_ack();
+            pipe.drain(now, |frame| {
+                order.push(String::from_utf8_lossy(frame).into_owned());
+                Ok(())
+            });
+        }
+        assert_eq!(order, ["cancel", "close", "amend", "quote-1", "quote-2"]);
+        assert_eq!(pipe.queued(), 0);
+    }
+
+    #[test]
+    fn full_queue_drops() {
+        let now = Instant::now();
+        let mut pipe = RequestPipeline::new(PipelineConfig::default());
+        for _ in 0..32 {
+            assert!(pipe.enqueue(b"quote", 2, (), now));
+        }
+        assert!(!pipe.enqueue(b"cancel", 0, (), now));
+        assert_eq!(pipe.dropped, 1);
+    }
+
+    #[test]
+    fn a_frame_that_fits_the_encoder_buffer_fits_a_slot() {
+        let now = Instant::now();
+        let mut pipe = RequestPipeline::new(PipelineConfig::default());
+        assert!(pipe.enqueue(&[b'x'; FRAME_SLOT_SIZE], 2, (), now));
+        assert!(!pipe.enqueue(&[b'x'; FRAME_SLOT_SIZE + 1], 2, (), now));
+        assert!(!pipe.enqueue(b"", 2, (), now));
+        assert_eq!(pipe.dropped, 2);
+    }
+
+    #[test]
+    fn stale_frames_are_handed_back_not_sent() {
+        let start = Instant::now();
+        let config = PipelineConfig { max_in_flight: 1, max_queue_age: Duration::from_millis(500), ..PipelineConfig::default() };
+        let mut pipe = RequestPipeline::new(config);
+        pipe.on_sent(start);
+        assert!(pipe.enqueue(b"quote-1", 2, 1, start));
+        assert!(pipe.enqueue(b"cancel", 0, 2, start + Duration::from_millis(300)));
+        assert_eq!(pipe.pop_expired(start + Duration::from_millis(499)), None);
+
+        // Capacity frees up after the quote went stale: only the cancel leaves
+        let later = start + Duration::from_millis(600);
+        pipe.on_ack();
+        let mut sent = Vec::new();
+        assert_eq!(pipe.drain(later, |frame| {
+            sent.push(frame.to_vec());
+            Ok(())
+        }), 1);
+        assert_eq!(sent, [b"cancel".to_vec()]);
+        assert!(pipe.degraded(later));
+        assert_eq!(pipe.pop_expired(later), Some(1));
+        assert_eq!(pipe.pop_expired(later), None);
+        assert_eq!((pipe.queued(), pipe.expired), (0, 1));
+    }
+}
@@ -0,0 +1,325 @@
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use crate::core::logging::info;
+
+// getaddrinfo blocks for as long as the DNS server takes (seconds on a bad day).
+// Endpoints are resolved once before the Hot Thread starts and re-resolved on the
+// Cold Thread; the Hot Thread only ever reads cached addresses.
+// Every address of a host is kept: a connect that fails before the WS upgrade moves the
+// endpoint to its next address (`rotate`), so one dead or unreachable IP of a DNS pool
+// doesn't hold a connection down until the next refresh.
+// The pool is kept in preference order: the startup race ranks it by connect time, and a
+// refresh keeps that ranking for the addresses that survive it (newcomers go last), so losing
+// the address in use falls back to the best surviving one. No re-race on refresh: the Cold
+// Thread that refreshes also runs the Hot Thread watchdog and must not block for seconds.
+const DEFAULT_REFRESH_SECS: u64 = 60;
+// Startup race: connects per address, the fastest one counts
+const DEFAULT_RACE_SAMPLES: u32 = 3;
+const DEFAULT_RACE_TIMEOUT_MS: u64 = 1_000;
+/// Addresses kept per endpoint (exchange pools answer with 2-8 A records).
+pub const MAX_ADDRS: usize = 8;
+
+/// Blocking resolution. NEVER call from the Hot Thread.
+/// With `prefer_ipv4` only IPv4 addresses are kept (the sockets are IPv4), unless there are
+/// none. Sorted, so a round-robin answer of the same pool compares equal.
+pub fn resolve_addrs(host: &str, port: u16, prefer_ipv4: bool) -> io::Result<Vec<SocketAddr>> {
+    let all: Vec<SocketAddr> = (host, port).to_socket_addrs()?.collect();
+    let mut addrs: Vec<SocketAddr> = if prefer_ipv4 && all.iter().any(|a| a.is_ipv4()) {
+        all.into_iter().filter(|a| a.is_ipv4()).collect()
+    } else {
+        all
+    };
+    addrs.sort();
+    addrs.dedup();
+    addrs.truncate(MAX_ADDRS);
+    if addrs.is_empty() {
+        return Err(io::Error::new(ErrorKind::NotFound, format!("No address for {}", host)));
+    }
+    Ok(addrs)
+}
+
+/// First address of `resolve_addrs`. Blocking, NEVER call from the Hot Thread.
+pub fn resolve(host: &str, port: u16, prefer_ipv4: bool) -> io::Result<SocketAddr> {
+    resolve_addrs(host, port, prefer_ipv4).map(|addrs| addrs[0])
+}
+
+#[derive(Debug, Clone)]
+struct CachedEndpoint {
+    host: String,
+    port: u16,
+    prefer_ipv4: bool,
+    // Preference order (race ranking), capacity MAX_ADDRS, so replacing the set never allocates
+    addrs: Vec<SocketAddr>,
+    // Index in `addrs` of the address connects go to
+    current: usize,
+    // Duration of the last successful lookup (startup report)
+    resolve_time: Option<Duration>,
+}
+
+impl CachedEndpoint {
+    fn addr(&self) -> Option<SocketAddr> {
+        self.addrs.get(self.current).copied()
+    }
+
+    /// Same addresses as `addrs`, in any order.
+    fn same_pool(&self, addrs: &[SocketAddr]) -> bool {
+        self.addrs.len() == addrs.len() && addrs.iter().all(|a| self.addrs.contains(a))
+    }
+
+    /// New address set. Addresses still listed keep their rank, new ones follow in the order
+    /// given. The address in use stays current if it is still listed, otherwise the best
+    /// surviving one (the first) takes over.
+    fn replace(&mut self, addrs: impl Iterator<Item = SocketAddr>) {
+        let mut fresh = [None; MAX_ADDRS];
+        for (slot, addr) in fresh.iter_mut().zip(addrs) {
+            *slot = Some(addr);
+        }
+        let fresh = fresh.iter().map_while(|addr| *addr);
+        let in_use = self.addr();
+        self.addrs.retain(|addr| fresh.clone().any(|a| a == *addr));
+        for addr in fresh {
+            if !self.addrs.contains(&addr) {
+                self.addrs.push(addr);
+            }
+        }
+        self.current = in_use.and_then(|addr| self.addrs.iter().position(|&a| a == addr)).unwrap_or(0);
+    }
+
+    /// Ranks the pool by `probes` (fastest first, silent addresses last in their order) and
+    /// makes the fastest current.
+    fn rank(&mut self, probes: &[Probe]) {
+        let rtt = |addr: &SocketAddr| probes.iter().find(|p| p.addr == *addr).and_then(|p| p.rtt);
+        // Stable: ties and unanswered addresses keep their order
+        self.addrs.sort_by_key(|addr| (rtt(addr).is_none(), rtt(addr)));
+        self.current = 0;
+    }
+}
+
+/// Fresh address set for endpoint `id` (index in the `EndpointCache`). Copy, travels over a ring.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EndpointUpdate {
+    pub id: usize,
+    addrs: [Option<SocketAddr>; MAX_ADDRS],
+}
+
+impl EndpointUpdate {
+    pub fn new(id: usize, addrs: &[SocketAddr]) -> Self {
+        let mut fixed = [None; MAX_ADDRS];
+        for (slot, addr) in fixed.iter_mut().zip(addrs) {
+            *slot = Some(*addr);
+        }
+        Self { id, addrs: fixed }
+    }
+
+    pub fn addrs(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.addrs.iter().map_while(|addr| *addr)
+    }
+}
+
+/// Last known addresses of every endpoint we dial. Each thread owns its own copy;
+/// the refresher's copy is the source, the Hot Thread's copy is fed by `apply`.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointCache {
+    entries: Vec<CachedEndpoint>,
+}
+
+impl EndpointCache {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Registers an endpoint (deduplicated by host and port) and returns its id.
+    pub fn add(&mut self, host: &str, port: u16, prefer_ipv4: bool) -> usize {
+        if let Some(id) = self.id(host, port) {
+            return id;
+        }
+        self.entries.push(CachedEndpoint {
+            host: host.to_string(),
+            port,
+            prefer_ipv4,
+            addrs: Vec::with_capacity(MAX_ADDRS),
+            current: 0,
+            resolve_time: None,
+        });
+        self.entries.len() - 1
+    }
+
+    pub fn id(&self, host: &str, port: u16) -> Option<usize> {
+        self.entries.iter().position(|e| e.host == host && e.port == port)
+    }
+
+    /// Current address, never resolves.
+    pub fn lookup(&self, host: &str, port: u16) -> Option<SocketAddr> {
+        self.id(host, port).and_then(|id| self.entries[id].addr())
+    }
+
+    /// Current address of endpoint `id`.
+    pub fn addr(&self, id: usize) -> Option<SocketAddr> {
+        self.entries.get(id).and_then(|e| e.addr())
+    }
+
+    /// Every cached address of endpoint `id`.
+    pub fn addrs(&self, id: usize) -> &[SocketAddr] {
+        self.entries.get(id).map_or(&[], |e| &e.addrs)
+    }
+
+    /// A connect to `failed` didn't get through: the endpoint moves on to its next address
+    /// (wrapping). No-op when the endpoint already moved off `failed` (DNS refresh, another
+    /// connection's failure). Returns the address the next connect goes to. No allocation.
+    pub fn rotate(&mut self, host: &str, port: u16, failed: SocketAddr) -> Option<SocketAddr> {
+        let id = self.id(host, port)?;
+        let entry = &mut self.entries[id];
+        if entry.addr() == Some(failed) && entry.addrs.len() > 1 {
+            entry.current = (entry.current + 1) % entry.addrs.len();
+            info!(net, "DNS: {} at {} failed, next {} ({}/{})", entry.host, failed, entry.addrs[entry.current], entry.current + 1, entry.addrs.len());
+        }
+        entry.addr()
+    }
+
+    /// Makes `addr` endpoint `id`'s current address, if it is in the pool.
+    pub fn prefer(&mut self, id: usize, addr: SocketAddr) {
+        if let Some(entry) = self.entries.get_mut(id) {
+            if let Some(index) = entry.addrs.iter().position(|&a| a == addr) {
+                entry.current = index;
+            }
+        }
+    }
+
+    /// Blocking: TCP-connects to every address of every endpoint, ranks each pool by connect
+    /// time and makes the fastest one current (`probe_addrs`). Startup only, after
+    /// `resolve_all`. Returns each endpoint's probes (index: endpoint id).
+    pub fn race(&mut self, config: &RaceConfig) -> Vec<Vec<Probe>> {
+        let mut results = Vec::with_capacity(self.entries.len());
+        for id in 0..self.entries.len() {
+            let probes = probe_addrs(self.addrs(id), config);
+            if let Some(best) = fastest(&probes) {
+                self.entries[id].rank(&probes);
+                let report: Vec<String> = probes.iter().map(|p| match p.rtt {
+                    Some(rtt) => format!("{} {:.1}ms", p.addr, rtt.as_secs_f64() * 1e3),
+                    None => format!("{} failed", p.addr),
+                }).collect();
+                info!(net, "DNS: {} race -> {} ({})", self.entries[id].host, best.addr, report.join(", "));
+            } else if !probes.is_empty() {
+                eprintln!("DNS: {} race: no address answered within {:?}, keeping {:?}", self.entries[id].host, config.timeout, self.addr(id));
+            }
+            results.push(probes);
+        }
+        results
+    }
+
+    /// How long the last successful lookup of endpoint `id` took.
+    pub fn resolve_time(&self, id: usize) -> Option<Duration> {
+        self.entries.get(id).and_then(|e| e.resolve_time)
+    }
+
+    /// Blocking: resolves every endpoint, keeping the old addresses on failure.
+    /// Returns the updates (address sets that are new or changed). Startup / Cold Thread only.
+    pub fn resolve_all(&mut self) -> Vec<EndpointUpdate> {
+        let mut updates = Vec::new();
+        for (id, entry) in self.entries.iter_mut().enumerate() {
+            let started = Instant::now();
+            match resolve_addrs(&entry.host, entry.port, entry.prefer_ipv4) {
+                Ok(addrs) => {
+                    entry.resolve_time = Some(started.elapsed());
+                    if !entry.same_pool(&addrs) {
+                        if !entry.addrs.is_empty() {
+                            info!(net, "DNS: {} moved {:?} -> {:?}", entry.host, entry.addrs, addrs);
+                        }
+                        entry.replace(addrs.iter().copied());
+                        updates.push(EndpointUpdate::new(id, &entry.addrs));
+                    }
+                }
+                Err(e) => eprintln!("DNS: Failed to resolve {}:{}: {} (keeping {:?})", entry.host, entry.port, e, entry.addrs),
+            }
+        }
+        updates
+    }
+
+    /// Hot Thread side: takes an update produced by the refresher. No allocation.
+    pub fn apply(&mut self, update: EndpointUpdate) {
+        if let Some(entry) = self.entries.get_mut(update.id) {
+            entry.replace(update.addrs());
+        }
+    }
+}
+
+/// Startup endpoint race: `samples` connects per address, each bounded by `timeout`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaceConfig {
+    pub enabled: bool,
+    pub samples: u32,
+    pub timeout: Duration,
+}
+
+impl Default for RaceConfig {
+    fn default() -> Self {
+        Self { enabled: true, samples: DEFAULT_RACE_SAMPLES, timeout: Duration::from_millis(DEFAULT_RACE_TIMEOUT_MS) }
+    }
+}
+
+/// TCP connect time of one address: the best of the race's samples, None if none connected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Probe {
+    pub addr: SocketAddr,
+    pub rtt: Option<Duration>,
+}
+
+/// Blocking: `samples` TCP connects to each address (addresses in parallel, one thread each),
+/// closed as soon as they complete. A connect is one SYN / SYN-ACK round trip, which is what
+/// separates the gateways of one pool. NEVER call from the Hot Thread.
+pub fn probe_addrs(addrs: &[SocketAddr], config: &RaceConfig) -> Vec<Probe> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = addrs.iter().map(|&addr| scope.spawn(move || {
+            let rtt = (0..config.samples)
+                .filter_map(|_| {
+                    let started = Instant::now();
+                    TcpStream::connect_timeout(&addr, config.timeout).ok().map(|_| started.elapsed())
+                })
+                .min();
+            Probe { addr, rtt }
+        })).collect();
+        addrs.iter().zip(handles).map(|(&addr, h)| h.join().unwrap_or(Probe { addr, rtt: None })).collect()
+    })
+}
+
+/// Lowest RTT; ties go to the earlier address.
+pub fn fastest(probes: &[Probe]) -> Option<Probe> {
+    probes.iter().filter(|p| p.rtt.is_some()).min_by_key(|p| p.rtt).copied()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResolverConfig {
+    pub refresh_interval: Duration,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self { refresh_interval: Duration::from_secs(DEFAULT_REFRESH_SECS) }
+    }
+}
+
+/// Periodic re-resolution. COLD THREAD ONLY (blocking DNS).
+/// Changed addresses are handed to `publish` (the Hot Thread's ring).
+pub struct DnsRefresher {
+    pub config: ResolverConfig,
+    cache: EndpointCache,
+    last_refresh: Instant,
+}
+
+impl DnsRefresher {
+    /// `cache` is the startup-resolved cache, so the first refresh only happens after one interval.
+    pub fn new(cache: EndpointCache, config: ResolverConfig, now: Instant) -> Self {
+        Self { config, cache, last_refresh: now }
+    }
+
+    pub fn poll<F: FnMut(EndpointUpdate)>(&mut self, now: Instant, mut publish: F) {
+        if now.saturating_duration_since(self.last_refresh) < self.config.refresh_interval {
+            return;
+        }
+        self.last_refresh = now;
+        for update in self.cache.resolve_all() {
+            publish(update);
+        }
+    }
+}
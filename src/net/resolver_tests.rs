@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Duration;
+    use crate::net::resolver::{self, EndpointCache, EndpointUpdate, Probe, RaceConfig, MAX_ADDRS};
+
+    fn ip(last: u8) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, last], 443))
+    }
+
+    #[test]
+    fn rotates_through_the_pool_on_failed_connects() {
+        let mut cache = EndpointCache::new();
+        let id = cache.add("stream.bybit.com", 443, true);
+        assert_eq!(cache.lookup("stream.bybit.com", 443), None);
+        cache.apply(EndpointUpdate::new(id, &[ip(1), ip(2), ip(3)]));
+        assert_eq!(cache.lookup("stream.bybit.com", 443), Some(ip(1)));
+
+        assert_eq!(cache.rotate("stream.bybit.com", 443, ip(1)), Some(ip(2)));
+        // Another connection failed on the old address too: already moved on
+        assert_eq!(cache.rotate("stream.bybit.com", 443, ip(1)), Some(ip(2)));
+        assert_eq!(cache.rotate("stream.bybit.com", 443, ip(2)), Some(ip(3)));
+        assert_eq!(cache.rotate("stream.bybit.com", 443, ip(3)), Some(ip(1)));
+        assert_eq!(cache.rotate("unknown.host", 443, ip(1)), None);
+    }
+
+    #[test]
+    fn refresh_keeps_the_address_in_use() {
+        let mut cache = EndpointCache::new();
+        let id = cache.add("stream.bybit.com", 443, true);
+        cache.apply(EndpointUpdate::new(id, &[ip(1), ip(2)]));
+        cache.rotate("stream.bybit.com", 443, ip(1));
+        // Still listed: stays current, and keeps its rank ahead of the newcomers
+        cache.apply(EndpointUpdate::new(id, &[ip(0), ip(2), ip(9)]));
+        assert_eq!((cache.addr(id), cache.addrs(id)), (Some(ip(2)), &[ip(2), ip(0), ip(9)][..]));
+        // Gone from the pool: first of the new set
+        cache.apply(EndpointUpdate::new(id, &[ip(5), ip(6)]));
+        assert_eq!(cache.addr(id), Some(ip(5)));
+
+        let pool: Vec<SocketAddr> = (0..12).map(ip).collect();
+        let update = EndpointUpdate::new(id, &pool);
+        assert_eq!(update.addrs().count(), MAX_ADDRS);
+        cache.apply(update);
+        assert_eq!(cache.addrs(id), &[ip(5), ip(6), ip(0), ip(1), ip(2), ip(3), ip(4), ip(7)]);
+    }
+
+    #[test]
+    fn literal_addresses_resolve_without_dns() {
+        assert_eq!(resolver::resolve_addrs("10.0.0.7", 443, true).unwrap(), [ip(7)]);
+        assert_eq!(resolver::resolve("10.0.0.7", 443, true).unwrap(), ip(7));
+    }
+
+    #[test]
+    fn race_picks_the_fastest_reachable_address() {
+        let probe = |last: u8, ms: Option<u64>| Probe { addr: ip(last), rtt: ms.map(Duration::from_millis) };
+        assert_eq!(resolver::fastest(&[probe(1, Some(9)), probe(2, None), probe(3, Some(2)), probe(4, Some(2))]), Some(probe(3, Some(2))));
+        assert_eq!(resolver::fastest(&[probe(1, None)]), None);
+
+        // A local listener answers; nothing listens on the other port
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let config = RaceConfig { samples: 2, timeout: Duration::from_millis(200), ..RaceConfig::default() };
+        let probes = resolver::probe_addrs(&[closed, open], &config);
+        assert_eq!((probes[0].addr, probes[0].rtt, probes[1].addr), (closed, None, open));
+        assert!(probes[1].rtt.is_some());
+
+        let mut cache = EndpointCache::new();
+        let id = cache.add("127.0.0.1", open.port(), true);
+        cache.apply(EndpointUpdate::new(id, &[closed, open]));
+        cache.race(&config);
+        assert_eq!((cache.addr(id), cache.addrs(id)), (Some(open), &[open, closed][..]));
+    }
+
+    #[test]
+    fn a_refresh_falls_back_to_the_best_surviving_address() {
+        let listeners: Vec<TcpListener> = (0..2).map(|_| TcpListener::bind("127.0.0.1:0").unwrap()).collect();
+        let (first, second) = (listeners[0].local_addr().unwrap(), listeners[1].local_addr().unwrap());
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut cache = EndpointCache::new();
+        let id = cache.add("127.0.0.1", first.port(), true);
+        cache.apply(EndpointUpdate::new(id, &[closed, first, second]));
+        cache.race(&RaceConfig { samples: 2, timeout: Duration::from_millis(200), ..RaceConfig::default() });
+        assert_eq!(cache.addrs(id)[2], closed);
+        let (winner, runner_up) = (cache.addrs(id)[0], cache.addrs(id)[1]);
+        assert_eq!(cache.addr(id), Some(winner));
+
+        // The winner left the pool: the runner-up takes over, not the first address listed
+        cache.apply(EndpointUpdate::new(id, &[closed, runner_up, ip(9)]));
+        assert_eq!((cache.addr(id), cache.addrs(id)), (Some(runner_up), &[runner_up, closed, ip(9)][..]));
+    }
+}
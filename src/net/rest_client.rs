@@ -0,0 +1,507 @@
+use std::time::Instant;
+use ring::hmac;
+use simd_json::prelude::*;
+use crate::core::logging::debug;
+use crate::core::rounding::Grid;
+use crate::core::time_sync::{self, TimeSyncConfig};
+use crate::net::outage::SERVER_ERROR_CODES;
+use crate::strategy::symbols::{Category, Instrument};
+
+const BYBIT_REST_URL: &str = "https://api.bybit.com";
+// Transaction log paging: 50 rows a page, a day of market making fits well inside the cap
+const STATEMENT_PAGE_LIMIT: usize = 50;
+const STATEMENT_MAX_PAGES: usize = 100;
+// Bybit caps orderLinkId at 36 characters
+const MAX_LINK_ID: usize = 36;
+
+/// orderLinkId inline, so quote requests stay `Copy` on the way through the ring.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct LinkId {
+    bytes: [u8; MAX_LINK_ID],
+    len: u8,
+}
+
+impl LinkId {
+    /// Ids longer than Bybit accepts are cut at 36 bytes (on a char boundary).
+    pub fn new(id: &str) -> Self {
+        let mut len = id.len().min(MAX_LINK_ID);
+        while !id.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut bytes = [0u8; MAX_LINK_ID];
+        bytes[..len].copy_from_slice(&id.as_bytes()[..len]);
+        Self { bytes, len: len as u8 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or("")
+    }
+}
+
+impl std::fmt::Debug for LinkId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+/// Risk-reducing requests the Hot Thread hands to the REST thread when the Trade WS
+/// can't be trusted, and quotes in REST quoting mode (`strategy::rest_quoting`). `Copy` so it
+/// travels through the rtrb ring without allocation. Quantities are already in the
+/// exchange's unit (`Instrument::order_qty`).
+#[derive(Debug, Clone, Copy)]
+pub enum RestRequest {
+    CancelAll,
+    ClosePosition { qty: f64, side: &'static str },
+    /// Market reduce-only order that takes a hedge the router kept on Bybit (`ExecutionRouter::plan_hedge`).
+    HedgeUnwind { qty: f64, side: &'static str, link_id: LinkId },
+    /// PostOnly limit quote; answered with `RestReply::Order`, like the two below.
+    CreateOrder { side: &'static str, price: f64, qty: f64, reduce_only: bool, link_id: LinkId },
+    /// Only the fields that change are set.
+    AmendOrder { price: Option<f64>, qty: Option<f64>, link_id: LinkId },
+    CancelOrder { link_id: LinkId },
+    /// Open-order snapshot for reconciliation; answered with `RestReply::OpenOrders`.
+    QueryOpenOrders,
+    /// Open-order poll of REST quoting, queued at `sent`; answered with `RestReply::PolledOrders`.
+    PollOpenOrders { sent: Instant },
+    /// Exchange statement for [start_ms, end_ms) on our symbol; answered with `RestReply::Statement`.
+    QueryStatement { start_ms: u64, end_ms: u64 },
+}
+
+/// One bulk cancel (POST /v5/order/cancel-all). Bybit wants the category plus either a
+/// symbol or, for linear/inverse, a settle coin (every symbol settled in it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelTarget {
+    Symbol { category: Category, symbol: &'static str },
+    SettleCoin { category: Category, coin: &'static str },
+}
+
+/// One resting order as the exchange sees it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenOrder {
+    pub price: f64,
+    pub qty: f64,
+}
+
+/// Our symbol's lines of the account transaction log (GET /v5/account/transaction-log)
+/// over one window, summed. Values in the settle coin, signed as Bybit reports them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Statement {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// TRADE rows (one per execution).
+    pub trades: u32,
+    /// Trading fees of TRADE rows: positive = paid, negative = rebate.
+    pub fees: f64,
+    /// cashFlow of TRADE rows: realized PnL of the closing executions.
+    pub realized: f64,
+    /// Funding of SETTLEMENT rows.
+    pub funding: f64,
+    /// Net wallet change of all our rows (realized, fees, funding).
+    pub change: f64,
+}
+
+/// Our open orders per side (the market maker keeps at most one per side).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OpenOrders {
+    pub buy: Option<OpenOrder>,
+    pub sell: Option<OpenOrder>,
+}
+
+/// Trading grid of one symbol from GET /v5/market/instruments-info, in the exchange's qty
+/// unit (USD contracts for inverse, base coin for spot).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstrumentSpec {
+    pub tick_size: f64,
+    pub qty_step: f64,
+    pub min_qty: f64,
+}
+
+impl InstrumentSpec {
+    /// Overrides the configured grid of `instrument` with the exchange's.
+    pub fn apply(&self, instrument: &mut Instrument) {
+        instrument.tick_size = self.tick_size;
+        instrument.qty_step = self.qty_step;
+        instrument.min_qty = self.min_qty;
+    }
+}
+
+/// Answers the REST thread sends back to the Hot Thread (rtrb ring, `Copy`).
+#[derive(Debug, Clone, Copy)]
+pub enum RestReply {
+    OpenOrders(OpenOrders),
+    QueryFailed,
+    PolledOrders { orders: OpenOrders, sent: Instant },
+    Statement(Statement),
+    StatementFailed,
+    /// A request failed on the exchange side (HTTP 5xx / retCode 10016): feeds the outage detector.
+    ServerError,
+    /// Outcome of a REST quote request: `op` as the Trade WS would name it ("order.create",
+    /// "order.amend", "order.cancel"), retCode 0 on success, -1 when there was no Bybit answer.
+    Order { op: &'static str, link_id: LinkId, code: i64 },
+}
+
+impl RestRequest {
+    /// Trade WS op name of a quote request, None for the rest.
+    pub fn order_op(&self) -> Option<(&'static str, LinkId)> {
+        match *self {
+            RestRequest::CreateOrder { link_id, .. } => Some(("order.create", link_id)),
+            RestRequest::AmendOrder { link_id, .. } => Some(("order.amend", link_id)),
+            RestRequest::CancelOrder { link_id } => Some(("order.cancel", link_id)),
+            _ => None,
+        }
+    }
+}
+
+/// True when a `RestClient` error came from the exchange failing, not from us or the network.
+pub fn is_server_error(err: &str) -> bool {
+    err.contains("status code 5") || SERVER_ERROR_CODES.iter().any(|code| err.contains(&format!("Bybit error: {} ", code)))
+}
+
+/// Grid of `symbol` (GET /v5/market/instruments-info, unsigned). Called once per quoted
+/// symbol at startup, before any client exists.
+pub fn fetch_instrument_spec(base_url: &str, category: Category, symbol: &str) -> Result<InstrumentSpec, String> {
+    let url = format!("{}/v5/market/instruments-info?category={}&symbol={}",
+        base_url.trim_end_matches('/'), category.as_str(), symbol);
+    let response = ureq::get(&url).call().map_err(|e| format!("HTTP error: {}", e))?;
+    let mut resp_bytes = response.into_string().map_err(|e| format!("Failed to read response: {}", e))?.into_bytes();
+    parse_instrument_spec(&mut resp_bytes, category)
+}
+
+/// Blocking Bybit V5 REST client (ureq). Cold side only: never call from the Hot Thread.
+pub struct RestClient {
+    api_key: String,
+    key: hmac::Key,
+    symbol: String,
+    category: Category,
+    // Grid the quote and close prices / qtys are printed on
+    grid: Grid,
+    // recv_window and timestamp margin (core::time_sync)
+    time: TimeSyncConfig,
+    base_url: String,
+}
+
+impl RestClient {
+    pub fn new(api_key: &str, api_secret: &str, symbol: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            key: hmac::Key::new(hmac::HMAC_SHA256, api_secret.as_bytes()),
+            symbol: symbol.to_string(),
+            category: Category::Linear,
+            grid: Grid::new(0.001, 0.1, 0.1),
+            time: TimeSyncConfig::default(),
+            base_url: BYBIT_REST_URL.to_string(),
+        }
+    }
+
+    /// REST host other than api.bybit.com (testnet, regional domain), no trailing slash.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Category and grid of the traded instrument (default: linear, 0.1 lots).
+    pub fn with_instrument(mut self, instrument: &Instrument) -> Self {
+        self.category = instrument.category;
+        self.grid = instrument.grid();
+        self
+    }
+
+    /// Ticker every symbol-scoped request goes to.
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn with_time_sync(mut self, time: TimeSyncConfig) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn execute(&self, req: RestRequest) -> Result<(), String> {
+        match req {
+            RestRequest::CancelAll => self.cancel_all(),
+            RestRequest::ClosePosition { qty, side } => self.close_position(side, qty),
+            RestRequest::HedgeUnwind { qty, side, link_id } => self.market_close(side, qty, Some(link_id.as_str())),
+            RestRequest::CreateOrder { side, price, qty, reduce_only, link_id } => self.create_order(side, price, qty, reduce_only, link_id.as_str()),
+            RestRequest::AmendOrder { price, qty, link_id } => self.amend_order(price, qty, link_id.as_str()),
+            RestRequest::CancelOrder { link_id } => self.cancel_order(link_id.as_str()),
+            RestRequest::QueryOpenOrders | RestRequest::PollOpenOrders { .. } => self.open_orders().map(|_| ()),
+            RestRequest::QueryStatement { start_ms, end_ms } => self.statement(start_ms, end_ms).map(|_| ()),
+        }
+    }
+
+    pub fn cancel_all(&self) -> Result<(), String> {
+        let body = format!(r#"{{"category":"{}","symbol":"{}"}}"#, self.category.as_str(), self.symbol);
+        self.post("/v5/order/cancel-all", &body)
+    }
+
+    pub fn cancel_target(&self, target: CancelTarget) -> Result<(), String> {
+        let body = match target {
+            CancelTarget::Symbol { category, symbol } => format!(r#"{{"category":"{}","symbol":"{}"}}"#, category.as_str(), symbol),
+            CancelTarget::SettleCoin { category, coin } => format!(r#"{{"category":"{}","settleCoin":"{}"}}"#, category.as_str(), coin),
+        };
+        self.post("/v5/order/cancel-all", &body)
+    }
+
+    /// Bulk cancel across `targets`. A failed target doesn't stop the rest;
+    /// Err lists every failure.
+    pub fn cancel_targets(&self, targets: &[CancelTarget]) -> Result<(), String> {
+        let failures: Vec<String> = targets
+            .iter()
+            .filter_map(|&t| self.cancel_target(t).err().map(|e| format!("{:?}: {}", t, e)))
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures.join("; "))
+        }
+    }
+
+    /// Market reduce-only close, mirrors the WS ClosePosition request.
+    pub fn close_position(&self, side: &str, qty: f64) -> Result<(), String> {
+        self.market_close(side, qty, None)
+    }
+
+    /// Market reduce-only order; `link_id` lets its execution be told apart (hedge unwinds).
+    pub fn market_close(&self, side: &str, qty: f64, link_id: Option<&str>) -> Result<(), String> {
+        let link = link_id.map(|id| format!(r#","orderLinkId":"{}""#, id)).unwrap_or_default();
+        let body = format!(
+            r#"{{"category":"{}","symbol":"{}","side":"{}","orderType":"Market","qty":"{:.*}","timeInForce":"GTC","smpType":"CancelMaker"{}{}}}"#,
+            self.category.as_str(), self.symbol, side, self.grid.qty_decimals(), self.grid.qty(qty), link, close_args(self.category)
+        );
+        self.post("/v5/order/create", &body)
+    }
+
+    /// PostOnly limit quote, mirrors the WS order.create.
+    pub fn create_order(&self, side: &str, price: f64, qty: f64, reduce_only: bool, link_id: &str) -> Result<(), String> {
+        let position_idx = if self.category.is_futures() { r#","positionIdx":0"# } else { "" };
+        let reduce_flag = if reduce_only && self.category.has_positions() { r#","reduceOnly":true"# } else { "" };
+        let body = format!(
+            r#"{{"category":"{}","symbol":"{}","side":"{}"{},"orderType":"Limit","qty":"{:.*}","price":"{:.*}","timeInForce":"PostOnly","orderLinkId":"{}"{}}}"#,
+            self.category.as_str(), self.symbol, side, position_idx, self.grid.qty_decimals(), self.grid.qty(qty),
+            self.grid.price_decimals(), self.grid.price(price), link_id, reduce_flag
+        );
+        self.post("/v5/order/create", &body)
+    }
+
+    /// Amends price and/or qty of the order `link_id`; a None field is left out.
+    pub fn amend_order(&self, price: Option<f64>, qty: Option<f64>, link_id: &str) -> Result<(), String> {
+        let mut body = format!(r#"{{"category":"{}","symbol":"{}","orderLinkId":"{}""#, self.category.as_str(), self.symbol, link_id);
+        if let Some(qty) = qty {
+            body.push_str(&format!(r#","qty":"{:.*}""#, self.grid.qty_decimals(), self.grid.qty(qty)));
+        }
+        if let Some(price) = price {
+            body.push_str(&format!(r#","price":"{:.*}""#, self.grid.price_decimals(), self.grid.price(price)));
+        }
+        body.push('}');
+        self.post("/v5/order/amend", &body)
+    }
+
+    pub fn cancel_order(&self, link_id: &str) -> Result<(), String> {
+        let body = format!(r#"{{"category":"{}","symbol":"{}","orderLinkId":"{}"}}"#, self.category.as_str(), self.symbol, link_id);
+        self.post("/v5/order/cancel", &body)
+    }
+
+    /// Our live orders on the symbol (GET /v5/order/realtime).
+    pub fn open_orders(&self) -> Result<OpenOrders, String> {
+        let query = format!("category={}&symbol={}", self.category.as_str(), self.symbol);
+        let mut resp_bytes = self.get("/v5/order/realtime", &query)?.into_bytes();
+        parse_open_orders(&mut resp_bytes)
+    }
+
+    /// Transaction log of [start_ms, end_ms) summed for our symbol, all pages
+    /// (Bybit allows a window of up to 7 days).
+    pub fn statement(&self, start_ms: u64, end_ms: u64) -> Result<Statement, String> {
+        let mut statement = Statement { start_ms, end_ms, ..Statement::default() };
+        let mut cursor = String::new();
+        for _ in 0..STATEMENT_MAX_PAGES {
+            // Query keys in alphabetical order; the signature covers the string as sent
+            let mut query = format!("accountType=UNIFIED&category={}", self.category.as_str());
+            if !cursor.is_empty() {
+                query.push_str("&cursor=");
+                query.push_str(&cursor);
+            }
+            query.push_str(&format!("&endTime={}&limit={}&startTime={}", end_ms.saturating_sub(1), STATEMENT_PAGE_LIMIT, start_ms));
+            let mut resp_bytes = self.get("/v5/account/transaction-log", &query)?.into_bytes();
+            match parse_transaction_log(&mut resp_bytes, &self.symbol, &mut statement)? {
+                Some(next) => cursor = next,
+                None => return Ok(statement),
+            }
+        }
+        Err(format!("Transaction log longer than {} pages", STATEMENT_MAX_PAGES))
+    }
+
+    /// Available balance of `coin` in the unified account (GET /v5/account/wallet-balance).
+    /// Seeds the pre-trade margin check before the first `wallet` push.
+    pub fn available_balance(&self, coin: &str) -> Result<f64, String> {
+        let query = format!("accountType=UNIFIED&coin={}", coin);
+        let resp = self.get("/v5/account/wallet-balance", &query)?;
+        let mut resp_bytes = resp.into_bytes();
+        let json = simd_json::to_borrowed_value(&mut resp_bytes)
+            .map_err(|_| "Failed to parse response JSON".to_string())?;
+        check_ret_code(&json)?;
+        let list = json.get("result").and_then(|r| r.get("list")).and_then(|l| l.as_array());
+        list.into_iter().flatten().find_map(|account| coin_available(account, coin))
+            .ok_or_else(|| format!("No {} in wallet", coin))
+    }
+
+    /// Exchange clock, ms (GET /v5/market/time, unsigned). Used for the startup skew check.
+    pub fn server_time_ms(&self) -> Result<u64, String> {
+        let url = format!("{}/v5/market/time", self.base_url);
+        let response = ureq::get(&url).call().map_err(|e| format!("HTTP error: {}", e))?;
+        let mut resp_bytes = response.into_string().map_err(|e| format!("Failed to read response: {}", e))?.into_bytes();
+        let json = simd_json::to_borrowed_value(&mut resp_bytes)
+            .map_err(|_| "Failed to parse response JSON".to_string())?;
+        check_ret_code(&json)?;
+        json.get("time").and_then(|v| v.as_u64()).ok_or_else(|| "No time in response".to_string())
+    }
+
+    /// (timestamp, hex signature) for `payload` (POST body or GET query string).
+    fn sign(&self, payload: &str) -> Result<(u64, String), String> {
+        let timestamp = self.time.rest_timestamp_ms(time_sync::wall_ms());
+
+        // Signature string: timestamp + api_key + recv_window + body/query
+        let sign_str = format!("{}{}{}{}", timestamp, self.api_key, self.time.recv_window_ms, payload);
+        let signature = hmac::sign(&self.key, sign_str.as_bytes());
+        Ok((timestamp, hex::encode(signature.as_ref())))
+    }
+
+    /// Signs and sends a POST. Ok only if Bybit answered retCode 0.
+    fn post(&self, path: &str, body: &str) -> Result<(), String> {
+        let (timestamp, sig_hex) = self.sign(body)?;
+
+        let url = format!("{}{}", self.base_url, path);
+        let response = ureq::post(&url)
+            .set("Content-Type", "application/json")
+            .set("X-BAPI-API-KEY", &self.api_key)
+            .set("X-BAPI-TIMESTAMP", &timestamp.to_string())
+            .set("X-BAPI-SIGN", &sig_hex)
+            .set("X-BAPI-RECV-WINDOW", &self.time.recv_window_ms.to_string())
+            .send_string(body)
+            .map_err(|e| format!("HTTP error: {}", e))?;
+
+        let resp_body = response.into_string().map_err(|e| format!("Failed to read response: {}", e))?;
+        debug!(orders, "REST: {} -> {}", path, resp_body);
+
+        let mut resp_bytes = resp_body.into_bytes();
+        let json = simd_json::to_borrowed_value(&mut resp_bytes)
+            .map_err(|_| "Failed to parse response JSON".to_string())?;
+        check_ret_code(&json)
+    }
+
+    /// Signs and sends a GET, returns the raw body (retCode checked by the caller's parser).
+    fn get(&self, path: &str, query: &str) -> Result<String, String> {
+        let (timestamp, sig_hex) = self.sign(query)?;
+
+        let url = format!("{}{}?{}", self.base_url, path, query);
+        let response = ureq::get(&url)
+            .set("X-BAPI-API-KEY", &self.api_key)
+            .set("X-BAPI-TIMESTAMP", &timestamp.to_string())
+            .set("X-BAPI-SIGN", &sig_hex)
+            .set("X-BAPI-RECV-WINDOW", &self.time.recv_window_ms.to_string())
+            .call()
+            .map_err(|e| format!("HTTP error: {}", e))?;
+
+        response.into_string().map_err(|e| format!("Failed to read response: {}", e))
+    }
+}
+
+/// Category-specific fields of a market close (WS and REST send the same).
+/// Futures: one-way position + reduce-only. Options: reduce-only. Spot has no position
+/// to reduce, and a market Buy would take qty in quote coin unless told otherwise.
+pub fn close_args(category: Category) -> &'static str {
+    match category {
+        Category::Linear | Category::Inverse => r#","positionIdx":0,"reduceOnly":true"#,
+        Category::Option => r#","reduceOnly":true"#,
+        Category::Spot => r#","marketUnit":"baseCoin""#,
+    }
+}
+
+fn check_ret_code(json: &simd_json::BorrowedValue) -> Result<(), String> {
+    let ret_code = json.get("retCode").and_then(|v| v.as_i64()).unwrap_or(-1);
+    if ret_code == 0 {
+        Ok(())
+    } else {
+        let ret_msg = json.get("retMsg").and_then(|v| v.as_str()).unwrap_or("unknown");
+        Err(format!("Bybit error: {} - {}", ret_code, ret_msg))
+    }
+}
+
+/// Parses a /v5/order/realtime response. If several orders share a side, the first wins.
+pub fn parse_open_orders(resp: &mut [u8]) -> Result<OpenOrders, String> {
+    let json = simd_json::to_borrowed_value(resp)
+        .map_err(|_| "Failed to parse response JSON".to_string())?;
+    check_ret_code(&json)?;
+
+    let mut orders = OpenOrders::default();
+    let list = json.get("result").and_then(|r| r.get("list")).and_then(|l| l.as_array());
+    for item in list.into_iter().flatten() {
+        let num = |key: &str| item.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+        let order = OpenOrder { price: num("price"), qty: num("qty") };
+        match item.get("side").and_then(|v| v.as_str()) {
+            Some("Buy") if orders.buy.is_none() => orders.buy = Some(order),
+            Some("Sell") if orders.sell.is_none() => orders.sell = Some(order),
+            _ => {}
+        }
+    }
+    Ok(orders)
+}
+
+/// Parses an instruments-info response (first list item). Spot has no `qtyStep`: its lot
+/// is `basePrecision`.
+pub fn parse_instrument_spec(resp: &mut [u8], category: Category) -> Result<InstrumentSpec, String> {
+    let json = simd_json::to_borrowed_value(resp)
+        .map_err(|_| "Failed to parse response JSON".to_string())?;
+    check_ret_code(&json)?;
+
+    let item = json.get("result").and_then(|r| r.get("list")).and_then(|l| l.as_array()).and_then(|l| l.first())
+        .ok_or_else(|| "Symbol not listed".to_string())?;
+    let num = |filter: &str, key: &str| item.get(filter).and_then(|f| f.get(key)).and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<f64>().ok()).filter(|v| *v > 0.0)
+        .ok_or_else(|| format!("No {}.{} in response", filter, key));
+    let step_key = if category == Category::Spot { "basePrecision" } else { "qtyStep" };
+    Ok(InstrumentSpec {
+        tick_size: num("priceFilter", "tickSize")?,
+        qty_step: num("lotSizeFilter", step_key)?,
+        min_qty: num("lotSizeFilter", "minOrderQty")?,
+    })
+}
+
+/// Balance of `coin` free for new orders in one wallet account item (REST wallet-balance list
+/// or private `wallet` topic data, same shape): walletBalance minus position IM, order IM and
+/// locked (spot orders).
+pub fn coin_available(account: &simd_json::BorrowedValue, coin: &str) -> Option<f64> {
+    let coins = account.get("coin").and_then(|c| c.as_array())?;
+    let item = coins.iter().find(|c| c.get("coin").and_then(|v| v.as_str()) == Some(coin))?;
+    let num = |key: &str| item.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+    let wallet = item.get("walletBalance").and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok())?;
+    Some(wallet - num("totalPositionIM") - num("totalOrderIM") - num("locked"))
+}
+
+/// Adds one /v5/account/transaction-log page to `statement` (rows of other symbols are
+/// skipped). Returns the cursor of the next page, None on the last one.
+pub fn parse_transaction_log(resp: &mut [u8], symbol: &str, statement: &mut Statement) -> Result<Option<String>, String> {
+    let json = simd_json::to_borrowed_value(resp)
+        .map_err(|_| "Failed to parse response JSON".to_string())?;
+    check_ret_code(&json)?;
+
+    let result = json.get("result");
+    let list = result.and_then(|r| r.get("list")).and_then(|l| l.as_array());
+    for item in list.into_iter().flatten() {
+        if item.get("symbol").and_then(|v| v.as_str()) != Some(symbol) {
+            continue;
+        }
+        let num = |key: &str| item.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+        match item.get("type").and_then(|v| v.as_str()) {
+            Some("TRADE") => {
+                statement.trades += 1;
+                statement.fees += num("fee");
+                statement.realized += num("cashFlow");
+            }
+            Some("SETTLEMENT") => statement.funding += num("funding"),
+            _ => {}
+        }
+        statement.change += num("change");
+    }
+    let cursor = result.and_then(|r| r.get("nextPageCursor")).and_then(|v| v.as_str()).unwrap_or("");
+    Ok((!cursor.is_empty()).then(|| cursor.to_string()))
+}
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+use crate::core::logging::info;
+
+// Startup latency per connection: how long each step from DNS to the first useful
+// message took. Printed once per connection when its last stage lands, so a deploy
+// that routes us through a slower region shows up on the first lines of the log
+// instead of in the PnL. Stages are measured back to back from the previous one
+// that happened; DNS is timed separately (resolved for every endpoint before the
+// Hot Thread starts) and added to the total.
+const DEFAULT_BUDGET_MS: u64 = 3_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStage {
+    Dns,
+    /// Non-blocking connect finished (first writable event).
+    Tcp,
+    Tls,
+    /// HTTP 101 received.
+    Upgrade,
+    /// Auth acknowledged (private and trade streams).
+    Auth,
+    /// Subscribe acknowledged, or sent where the stream has no ack we wait for.
+    Subscribe,
+    /// First full order book (public stream).
+    Snapshot,
+}
+
+const STAGES: [ConnectStage; 7] = [
+    ConnectStage::Dns, ConnectStage::Tcp, ConnectStage::Tls, ConnectStage::Upgrade,
+    ConnectStage::Auth, ConnectStage::Subscribe, ConnectStage::Snapshot,
+];
+
+impl ConnectStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectStage::Dns => "dns",
+            ConnectStage::Tcp => "tcp",
+            ConnectStage::Tls => "tls",
+            ConnectStage::Upgrade => "upgrade",
+            ConnectStage::Auth => "auth",
+            ConnectStage::Subscribe => "subscribe",
+            ConnectStage::Snapshot => "snapshot",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StartupConfig {
+    /// DNS to last stage; above it the report is an alert.
+    pub budget: Duration,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self { budget: Duration::from_millis(DEFAULT_BUDGET_MS) }
+    }
+}
+
+/// Stage timestamps of one connection. Hot Thread; only the one-off report allocates.
+pub struct StartupTimeline {
+    label: &'static str,
+    /// Stage that completes the report.
+    last: ConnectStage,
+    started: Instant,
+    marks: [Option<Instant>; STAGES.len()],
+    dns: Option<Duration>,
+    budget: Duration,
+    pub reported: bool,
+}
+
+impl StartupTimeline {
+    /// `started`: when the TCP connect was issued (`TlsStats::created_ts`).
+    pub fn new(label: &'static str, last: ConnectStage, started: Instant, dns: Option<Duration>, config: StartupConfig) -> Self {
+        Self { label, last, started, marks: [None; STAGES.len()], dns, budget: config.budget, reported: false }
+    }
+
+    /// Records `stage` at `at` (first time only). Reaching the last stage prints the report;
+    /// returns true if that report went over budget.
+    pub fn mark(&mut self, stage: ConnectStage, at: Instant) -> bool {
+        let slot = &mut self.marks[stage as usize];
+        if slot.is_some() || self.reported {
+            return false;
+        }
+        *slot = Some(at);
+        if stage != self.last {
+            return false;
+        }
+        self.reported = true;
+        self.report()
+    }
+
+    /// Time spent in `stage`: since the previous stage that happened (or the connect).
+    pub fn stage(&self, stage: ConnectStage) -> Option<Duration> {
+        if stage == ConnectStage::Dns {
+            return self.dns;
+        }
+        let at = self.marks[stage as usize]?;
+        let prev = self.marks[..stage as usize].iter().rev().flatten().next().copied().unwrap_or(self.started);
+        Some(at.saturating_duration_since(prev))
+    }
+
+    /// DNS plus connect to the last stage recorded so far.
+    pub fn total(&self) -> Duration {
+        let end = self.marks.iter().rev().flatten().next().copied().unwrap_or(self.started);
+        self.dns.unwrap_or(Duration::ZERO) + end.saturating_duration_since(self.started)
+    }
+
+    fn report(&self) -> bool {
+        let mut line = format!("STARTUP [{}]:", self.label);
+        for stage in STAGES.iter().filter(|s| **s as usize <= self.last as usize) {
+            // Stages this stream skips (no auth on public) are left out
+            if let Some(d) = self.stage(*stage) {
+                line.push_str(&format!(" {} {:.1}ms |", stage.name(), d.as_secs_f64() * 1000.0));
+            }
+        }
+        let total = self.total();
+        info!(net, "{} total {:.1}ms", line, total.as_secs_f64() * 1000.0);
+        let over = total > self.budget;
+        if over {
+            eprintln!("ALERT: [STARTUP] {} connection took {:.1}ms to {} (budget {}ms): check routing/region",
+                self.label, total.as_secs_f64() * 1000.0, self.last.name(), self.budget.as_millis());
+        }
+        over
+    }
+}
@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::net::startup::{ConnectStage, StartupConfig, StartupTimeline};
+
+    #[test]
+    fn stages_measured_from_the_previous_one() {
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+        let config = StartupConfig { budget: ms(100) };
+        let mut timeline = StartupTimeline::new("trade", ConnectStage::Auth, t0, Some(ms(5)), config);
+        assert!(!timeline.mark(ConnectStage::Tcp, t0 + ms(10)));
+        assert!(!timeline.mark(ConnectStage::Tls, t0 + ms(30)));
+        assert!(!timeline.mark(ConnectStage::Upgrade, t0 + ms(40)));
+        // Later marks of the same stage are ignored
+        assert!(!timeline.mark(ConnectStage::Tcp, t0 + ms(35)));
+
+        assert_eq!(timeline.stage(ConnectStage::Dns), Some(ms(5)));
+        assert_eq!(timeline.stage(ConnectStage::Tls), Some(ms(20)));
+        assert_eq!(timeline.stage(ConnectStage::Upgrade), Some(ms(10)));
+        assert!(!timeline.reported);
+
+        // Auth lands at 120ms: DNS 5 + 120 is over the 100ms budget
+        assert!(timeline.mark(ConnectStage::Auth, t0 + ms(120)));
+        assert!(timeline.reported);
+        assert_eq!(timeline.stage(ConnectStage::Auth), Some(ms(80)));
+        assert_eq!(timeline.total(), ms(125));
+        assert_eq!(timeline.stage(ConnectStage::Subscribe), None);
+    }
+}
@@ -20,10 +20,30 @@ pub fn apply_optimizations(stream: &TcpStream) -> io::Result<()> {
     Ok(())
 }
 
+/// Per-connection socket options (see `WsClientBuilder`).
+#[derive(Debug, Clone, Copy)]
+pub struct TcpOptions {
+    pub nodelay: bool,
+    /// SO_RCVBUF / SO_SNDBUF. None = kernel default.
+    pub recv_buffer: Option<usize>,
+    pub send_buffer: Option<usize>,
+}
+
+impl Default for TcpOptions {
+    fn default() -> Self {
+        Self { nodelay: true, recv_buffer: None, send_buffer: None }
+    }
+}
+
 /// Creates a properly configured socket for outbound connection.
 /// 
 /// Returns a `socket2::Socket` which can be converted to `std::net::TcpStream`.
 pub fn create_socket() -> io::Result<Socket> {
+    create_socket_with(&TcpOptions::default())
+}
+
+/// `create_socket` with explicit options.
+pub fn create_socket_with(opts: &TcpOptions) -> io::Result<Socket> {
     let socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
     
     // Set non-blocking before connect to allow async connect behavior
@@ -31,7 +51,14 @@ pub fn create_socket() -> io::Result<Socket> {
     
     // Nodelay might need to be set after connect on some platforms, 
     // but setting it here is good practice if supported.
-    socket.set_nodelay(true)?;
+    socket.set_nodelay(opts.nodelay)?;
+
+    if let Some(size) = opts.recv_buffer {
+        socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = opts.send_buffer {
+        socket.set_send_buffer_size(size)?;
+    }
     
     // Optimizations for Linux HFT
     #[cfg(target_os = "linux")]
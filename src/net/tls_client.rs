@@ -3,14 +3,288 @@ use std::sync::Arc;
 use mio::{Events, Interest, Poll, Token, Registry};
 use mio::net::TcpStream;
 use rustls::{ClientConnection, ClientConfig, RootCertStore, pki_types::ServerName};
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, TrustAnchor, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use ring::digest;
+use webpki::{EndEntityCert, KeyUsage, VerifiedPath};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
+use std::time::{Duration, Instant};
 use crate::net::tcp_opt;
+use crate::core::base64;
+use crate::core::logging::info;
+
+// rustls does not say how much ciphertext it holds, so the write backlog is estimated from
+// what we hand it: plaintext plus the TLS 1.3 record overhead (5 header + 16 tag + 1 type)
+// per started 16 KiB record. Handshake records are not counted; the estimate only ever
+// errs low and is reset whenever the buffer drains.
+const RECORD_PAYLOAD: usize = 16 * 1024;
+const RECORD_OVERHEAD: usize = 22;
+
+/// Per-connection TLS counters. Plain integers updated inline: no atomics, no locks
+/// (the connection is owned by one thread). Read them from the owner or copy out.
+#[derive(Debug, Clone, Copy)]
+pub struct TlsStats {
+    pub created_ts: Instant,
+    /// Set the first time rustls reports the handshake finished.
+    pub handshake_done_ts: Option<Instant>,
+    pub tls_bytes_in: u64,       // ciphertext read from the socket
+    pub tls_bytes_out: u64,      // ciphertext flushed to the socket
+    pub plaintext_in: u64,       // decrypted bytes handed to the caller
+    pub plaintext_out: u64,      // bytes the caller asked us to encrypt
+    /// Last time the TLS send buffer drained completely to the socket (egress timestamp).
+    pub last_flush_ts: Option<Instant>,
+    /// Estimated ciphertext still waiting for the socket.
+    pub pending_bytes: u64,
+    /// Since when the socket has been refusing our ciphertext (None = nothing stuck).
+    pub pending_since: Option<Instant>,
+    /// Total time with ciphertext stuck, closed intervals only (see `pending_time`).
+    pub pending_total: Duration,
+    /// write_tls calls that left ciphertext behind (socket would block).
+    pub partial_writes: u64,
+    /// Fatal alerts from the peer. rustls doesn't do renegotiation: a server asking
+    /// for it ends up here or in `tls_errors`.
+    pub alerts_received: u64,
+    pub tls_errors: u64,
+    pub peer_closed: bool,       // close_notify received
+}
+
+impl TlsStats {
+    fn new() -> Self {
+        Self {
+            created_ts: Instant::now(),
+            handshake_done_ts: None,
+            tls_bytes_in: 0,
+            tls_bytes_out: 0,
+            plaintext_in: 0,
+            plaintext_out: 0,
+            last_flush_ts: None,
+            pending_bytes: 0,
+            pending_since: None,
+            pending_total: Duration::ZERO,
+            partial_writes: 0,
+            alerts_received: 0,
+            tls_errors: 0,
+            peer_closed: false,
+        }
+    }
+
+    /// Time from connection object creation (TCP connect started) to handshake completion.
+    pub fn handshake_duration(&self) -> Option<Duration> {
+        self.handshake_done_ts.map(|ts| ts.saturating_duration_since(self.created_ts))
+    }
+
+    /// Total time with ciphertext stuck, including a backlog still open at `now`.
+    pub fn pending_time(&self, now: Instant) -> Duration {
+        self.pending_total + self.pending_since.map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+    }
+
+    pub fn summary(&self, label: &str) {
+        let hs_us = self.handshake_duration().map(|d| d.as_micros() as i64).unwrap_or(-1);
+        info!(net, "TLS [{}]: handshake {}us | tls in/out {}/{} B | plain in/out {}/{} B | alerts {} | errors {} | closed {}",
+            label, hs_us, self.tls_bytes_in, self.tls_bytes_out, self.plaintext_in, self.plaintext_out,
+            self.alerts_received, self.tls_errors, self.peer_closed);
+    }
+}
+
+// Certificate pinning: a host listed in `[tls.pins]` must chain to one of its pins. The pin is
+// looked for on the path webpki verified (leaf, intermediates, root), not among whatever the
+// server sent: a pinned certificate appended to an otherwise foreign chain never satisfies it.
+// The CA check still runs first: a pin narrows webpki's verdict, it never replaces it. A CA that
+// issued a rogue certificate, or a corporate proxy with its own root installed, passes webpki
+// but not the pin, and the handshake fails with a "pin mismatch" error that names the keys of
+// the verified path. Hosts without pins are verified as before.
+
+/// Base64 SHA-256 SPKI hashes per host (`sha256/...` or bare base64, as `openssl dgst | base64`
+/// prints them). Empty: no pinning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertPins {
+    hosts: BTreeMap<String, Vec<[u8; 32]>>,
+}
+
+impl CertPins {
+    /// Parses `[tls.pins]`. Err on a host without pins or a pin that is not 32 bytes of base64.
+    pub fn new(pins: &BTreeMap<String, Vec<String>>) -> Result<Self, String> {
+        let mut hosts = BTreeMap::new();
+        for (host, list) in pins {
+            if list.is_empty() {
+                return Err(format!("{}: empty pin list (remove the host to disable pinning)", host));
+            }
+            let hashes = list.iter().map(|pin| {
+                let text = pin.trim();
+                base64::decode(text.strip_prefix("sha256/").unwrap_or(text))
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .ok_or_else(|| format!("{}: bad pin {:?} (base64 of a SHA-256 hash)", host, pin))
+            }).collect::<Result<Vec<_>, _>>()?;
+            hosts.insert(host.to_ascii_lowercase(), hashes);
+        }
+        Ok(Self { hosts })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    pub fn hosts(&self) -> impl Iterator<Item = &str> {
+        self.hosts.keys().map(String::as_str)
+    }
+
+    /// Ok if `host` is not pinned, or a path from `end_entity` through `intermediates` to one of
+    /// `roots` verifies and carries a pinned key (leaf, intermediate or root). Certificates the
+    /// path does not use are ignored.
+    pub fn verify(&self, host: &str, roots: &[TrustAnchor<'_>], end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>], now: UnixTime) -> Result<(), String> {
+        let Some(pins) = self.hosts.get(&host.to_ascii_lowercase()) else { return Ok(()) };
+        let cert = EndEntityCert::try_from(end_entity).map_err(|e| format!("{}: bad certificate: {:?}", host, e))?;
+        // Verified paths without a pin; the callback's Err makes webpki try the next candidate
+        let unpinned = RefCell::new(Vec::new());
+        let pinned = |path: &VerifiedPath<'_>| {
+            let mut hashes: Vec<[u8; 32]> = std::iter::once(path.end_entity().der())
+                .chain(path.intermediate_certificates().map(|cert| cert.der()))
+                .filter_map(|der| spki_sha256(&der))
+                .collect();
+            hashes.push(anchor_sha256(path.anchor()));
+            if hashes.iter().any(|hash| pins.contains(hash)) {
+                return Ok(());
+            }
+            let keys = hashes.iter().map(|hash| format!("sha256/{}", base64::encode(hash))).collect::<Vec<_>>();
+            unpinned.borrow_mut().push(keys.join(" > "));
+            Err(webpki::Error::UnknownIssuer)
+        };
+        let verified = cert.verify_for_usage(webpki::ALL_VERIFICATION_ALGS, roots, intermediates, now,
+            KeyUsage::server_auth(), None, Some(&pinned));
+        match verified {
+            Ok(_) => Ok(()),
+            Err(_) if !unpinned.borrow().is_empty() => Err(format!(
+                "certificate pin mismatch for {}: verified path [{}], none pinned (TLS interception or a key rotation)",
+                host, unpinned.borrow().join("; "))),
+            Err(e) => Err(format!("{}: chain does not verify: {:?}", host, e)),
+        }
+    }
+}
+
+/// One DER element: tag, whole encoding, contents, and the bytes that follow it.
+struct DerElement<'a> {
+    tag: u8,
+    whole: &'a [u8],
+    contents: &'a [u8],
+    rest: &'a [u8],
+}
+
+fn der_element(der: &[u8]) -> Option<DerElement<'_>> {
+    let (&tag, rest) = der.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > 4 || rest.len() < n {
+            return None;
+        }
+        (rest[..n].iter().fold(0usize, |len, &b| len << 8 | b as usize), &rest[n..])
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let header = der.len() - rest.len();
+    Some(DerElement { tag, whole: &der[..header + len], contents: &rest[..len], rest: &rest[len..] })
+}
+
+/// DER SubjectPublicKeyInfo of an X.509 certificate: the seventh field of tbsCertificate,
+/// counting the optional `[0]` version.
+pub fn spki_der(cert: &[u8]) -> Option<&[u8]> {
+    const SEQUENCE: u8 = 0x30;
+    let sequence = |der| der_element(der).filter(|e| e.tag == SEQUENCE);
+    let mut tbs = sequence(sequence(cert)?.contents)?.contents;
+    if tbs.first() == Some(&0xa0) {
+        tbs = der_element(tbs)?.rest;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for _ in 0..5 {
+        tbs = der_element(tbs)?.rest;
+    }
+    Some(sequence(tbs)?.whole)
+}
+
+/// The pin of a certificate: SHA-256 of its DER SubjectPublicKeyInfo.
+pub fn spki_sha256(cert: &[u8]) -> Option<[u8; 32]> {
+    let hash = digest::digest(&digest::SHA256, spki_der(cert)?);
+    hash.as_ref().try_into().ok()
+}
+
+/// The pin of a root. A trust anchor keeps only the contents of its SubjectPublicKeyInfo:
+/// the SEQUENCE header goes back on before hashing, so the value matches `spki_sha256`.
+pub fn anchor_sha256(anchor: &TrustAnchor<'_>) -> [u8; 32] {
+    let contents = anchor.subject_public_key_info.as_ref();
+    let len = contents.len().to_be_bytes();
+    let significant = &len[len.iter().take_while(|&&b| b == 0).count()..];
+    let mut hash = digest::Context::new(&digest::SHA256);
+    hash.update(&[0x30]);
+    if contents.len() >= 0x80 {
+        hash.update(&[0x80 | significant.len() as u8]);
+    }
+    hash.update(significant);
+    hash.update(contents);
+    hash.finish().as_ref().try_into().expect("SHA-256 is 32 bytes")
+}
+
+/// webpki, then the pins of the host on the path it verified.
+#[derive(Debug)]
+struct PinnedVerifier {
+    webpki: Arc<WebPkiServerVerifier>,
+    pins: CertPins,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, intermediates: &[CertificateDer<'_>], server_name: &ServerName<'_>,
+        ocsp_response: &[u8], now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.webpki.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        let ServerName::DnsName(host) = server_name else { return Ok(verified) };
+        let roots = webpki_roots::TLS_SERVER_ROOTS;
+        self.pins.verify(host.as_ref(), roots, end_entity, intermediates, now).map(|()| verified).map_err(|e| {
+            eprintln!("CRITICAL: TLS {}", e);
+            rustls::Error::General(e)
+        })
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.webpki.supported_verify_schemes()
+    }
+}
+
+/// Client config of the WS connections: webpki roots, and `pins` on top when there are any.
+pub fn client_config(pins: CertPins) -> Result<Arc<ClientConfig>, String> {
+    let mut root_store = RootCertStore::empty();
+    root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if pins.is_empty() {
+        return Ok(Arc::new(ClientConfig::builder().with_root_certificates(root_store).with_no_client_auth()));
+    }
+    let webpki = WebPkiServerVerifier::builder(Arc::new(root_store)).build().map_err(|e| format!("TLS verifier: {}", e))?;
+    Ok(Arc::new(ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedVerifier { webpki, pins }))
+        .with_no_client_auth()))
+}
 
 /// A non-blocking TLS wrapper around mio::net::TcpStream.
 /// Designed for HFT: No internal Mutex/Locks. State is owned by the struct.
 pub struct TlsClient {
     pub socket: TcpStream,
     pub tls_conn: ClientConnection,
+    pub stats: TlsStats,
+    // Interest currently registered with mio (reregister only on change)
+    interest: Option<Interest>,
 }
 
 impl TlsClient {
@@ -25,11 +299,34 @@ impl TlsClient {
         Ok(Self {
             socket,
             tls_conn,
+            stats: TlsStats::new(),
+            interest: None,
         })
     }
 
     pub fn register(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
-        registry.register(&mut self.socket, token, Interest::READABLE | Interest::WRITABLE)
+        let interest = Interest::READABLE | Interest::WRITABLE;
+        registry.register(&mut self.socket, token, interest)?;
+        self.interest = Some(interest);
+        Ok(())
+    }
+
+    /// `rearm`: the caller drives a setup step (handshake, auth, subscribe) from the next
+    /// writable event and needs it re-armed on every pass (mio is edge-triggered). Otherwise
+    /// WRITABLE is held only while ciphertext is pending and reregistered only on a change:
+    /// the socket's writable edge after a WouldBlock is what flushes the rest.
+    pub fn update_interest(&mut self, registry: &Registry, token: Token, rearm: bool) -> io::Result<()> {
+        let interest = if rearm || self.tls_conn.wants_write() {
+            Interest::READABLE | Interest::WRITABLE
+        } else {
+            Interest::READABLE
+        };
+        if !rearm && self.interest == Some(interest) {
+            return Ok(());
+        }
+        registry.reregister(&mut self.socket, token, interest)?;
+        self.interest = Some(interest);
+        Ok(())
     }
 
     pub fn socket(&mut self) -> &mut TcpStream {
@@ -40,13 +337,41 @@ impl TlsClient {
         self.tls_conn.wants_write()
     }
 
+    /// When everything written so far actually left for the socket; None while ciphertext
+    /// is still buffered (socket would block), i.e. the last frame's egress is pending.
+    pub fn flushed_at(&self) -> Option<Instant> {
+        if self.tls_conn.wants_write() { None } else { self.stats.last_flush_ts }
+    }
+
+    /// Decrypts buffered records, updating handshake/alert/close counters.
+    fn process_packets(&mut self, kind: io::ErrorKind) -> io::Result<rustls::IoState> {
+        match self.tls_conn.process_new_packets() {
+            Ok(state) => {
+                if state.peer_has_closed() {
+                    self.stats.peer_closed = true;
+                }
+                if self.stats.handshake_done_ts.is_none() && !self.tls_conn.is_handshaking() {
+                    self.stats.handshake_done_ts = Some(Instant::now());
+                }
+                Ok(state)
+            }
+            Err(e) => {
+                if let rustls::Error::AlertReceived(_) = e {
+                    self.stats.alerts_received += 1;
+                }
+                self.stats.tls_errors += 1;
+                Err(io::Error::new(kind, e.to_string()))
+            }
+        }
+    }
+
     /// Pulls encrypted data from socket -> TLS Engine.
     /// Returns true if data was read.
     pub fn read_tls(&mut self) -> io::Result<bool> {
         match self.tls_conn.read_tls(&mut self.socket) {
             Ok(n) => {
-                 let state = self.tls_conn.process_new_packets()
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+                 self.stats.tls_bytes_in += n as u64;
+                 let state = self.process_packets(io::ErrorKind::Other)?;
                  
                  // FIX: use state directly, it IS IoState
                  Ok(n > 0 || state.plaintext_bytes_to_read() > 0)
@@ -56,21 +381,36 @@ impl TlsClient {
         }
     }
 
-    /// Pushes encrypted data from TLS Engine -> Socket.
+    /// Pushes encrypted data from TLS Engine -> Socket, until drained or the socket would block.
+    /// A full drain stamps `last_flush_ts` and closes the backlog interval; a partial one opens
+    /// it. Either way the caller keeps WRITABLE interest while `wants_write()` (`update_interest`).
     pub fn write_tls(&mut self) -> io::Result<()> {
-        if self.tls_conn.wants_write() {
+        while self.tls_conn.wants_write() {
              match self.tls_conn.write_tls(&mut self.socket) {
+                 Ok(0) => break,
                  Ok(n) => {
-                     // DEBUG:
-                     // println!("DEBUG: write_tls flushed {} bytes. Wants write: {}", n, self.tls_conn.wants_write());
-                     Ok(())
+                     self.stats.tls_bytes_out += n as u64;
+                     self.stats.pending_bytes = self.stats.pending_bytes.saturating_sub(n as u64);
+                     if !self.tls_conn.wants_write() {
+                         let now = Instant::now();
+                         self.stats.last_flush_ts = Some(now);
+                         self.stats.pending_bytes = 0;
+                         if let Some(since) = self.stats.pending_since.take() {
+                             self.stats.pending_total += now.saturating_duration_since(since);
+                         }
+                     }
                  },
-                 Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
-                 Err(e) => Err(e),
+                 Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                 Err(e) => return Err(e),
              }
-        } else {
-            Ok(())
         }
+        if self.tls_conn.wants_write() {
+            self.stats.partial_writes += 1;
+            if self.stats.pending_since.is_none() {
+                self.stats.pending_since = Some(Instant::now());
+            }
+        }
+        Ok(())
     }
 
     /// Reads PLAINTEXT from the internal TLS buffer into `buf`.
@@ -83,8 +423,9 @@ impl TlsClient {
              Ok(0) => {
                  socket_eof = true;
              },
-             Ok(_) => {
+             Ok(n) => {
                  // Got data, proceeding
+                 self.stats.tls_bytes_in += n as u64;
              },
              Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
                  // Continue to check if we have decrypted data available
@@ -95,8 +436,7 @@ impl TlsClient {
         // 2. Process any new packets (decrypt)
         // We do this even if read_tls was WouldBlock, in case there's processed data pending? 
         // Or strictly as requested: "Call self.conn.process_new_packets()"
-        let _state = self.tls_conn.process_new_packets()
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let _state = self.process_packets(io::ErrorKind::InvalidData)?;
 
         // 3. Read decrypted data
         match self.tls_conn.reader().read(buf) {
@@ -113,7 +453,10 @@ impl TlsClient {
                     Err(io::Error::from(io::ErrorKind::WouldBlock))
                 }
             },
-            Ok(n) => Ok(n),
+            Ok(n) => {
+                self.stats.plaintext_in += n as u64;
+                Ok(n)
+            },
             Err(e) => Err(e),
         }
     }
@@ -121,7 +464,11 @@ impl TlsClient {
     /// Writes PLAINTEXT into the internal TLS buffer.
     pub fn write_plaintext(&mut self, buf: &[u8]) -> io::Result<usize> {
         match self.tls_conn.writer().write(buf) {
-             Ok(n) => Ok(n),
+             Ok(n) => {
+                 self.stats.plaintext_out += n as u64;
+                 self.stats.pending_bytes += (n + n.div_ceil(RECORD_PAYLOAD) * RECORD_OVERHEAD) as u64;
+                 Ok(n)
+             },
              Err(e) => Err(e),
         }
     }
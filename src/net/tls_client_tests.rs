@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::net::tls_client::{self, CertPins};
+    use rustls::pki_types::{CertificateDer, UnixTime};
+    use crate::core::base64;
+
+    // Let's Encrypt root, pinned with the value `openssl x509 -pubkey | openssl pkey -pubin
+    // -outform der | openssl dgst -sha256 -binary | base64` prints for it
+    const ISRG_ROOT_X1: &str = include_str!("../../tests/fixtures/tls/isrg_root_x1.pem");
+    const ISRG_ROOT_X1_PIN: &str = "sha256/C5+lpZ7tcVwmwQIMcRtPbsQtWLABXhQzejna0wHFr8M=";
+    // Self-made root of an intercepting proxy and a stream.bybit.com leaf it signed (EC P-256)
+    const INTERCEPT_ROOT: &str = include_str!("../../tests/fixtures/tls/intercept_root.pem");
+    const INTERCEPT_LEAF: &str = include_str!("../../tests/fixtures/tls/intercept_leaf.pem");
+
+    fn der(pem: &str) -> Vec<u8> {
+        base64::decode(&pem.lines().filter(|l| !l.starts_with("-----")).collect::<String>()).unwrap()
+    }
+
+    fn pins(host: &str, list: &[&str]) -> Result<CertPins, String> {
+        CertPins::new(&BTreeMap::from([(host.to_string(), list.iter().map(|p| p.to_string()).collect())]))
+    }
+
+    #[test]
+    fn spki_hash_matches_openssl() {
+        let cert = der(ISRG_ROOT_X1);
+        let spki = tls_client::spki_der(&cert).unwrap();
+        // RSA-4096 key: SEQUENCE of 0x222 bytes
+        assert_eq!(&spki[..4], &[0x30, 0x82, 0x02, 0x22]);
+        assert_eq!(tls_client::spki_sha256(&cert).map(|hash| hash.to_vec()), base64::decode(&ISRG_ROOT_X1_PIN[7..]));
+        // The same key seen as a trust anchor (SPKI contents only)
+        let cert = CertificateDer::from(cert);
+        let anchor = webpki::anchor_from_trusted_cert(&cert).unwrap();
+        assert_eq!(tls_client::anchor_sha256(&anchor).to_vec(), base64::decode(&ISRG_ROOT_X1_PIN[7..]).unwrap());
+        assert_eq!(tls_client::spki_der(&cert[..100]), None);
+    }
+
+    #[test]
+    fn pins_are_matched_on_the_verified_path_only() {
+        // A proxy root and the stream.bybit.com leaf it issued
+        let root = CertificateDer::from(der(INTERCEPT_ROOT));
+        let leaf = CertificateDer::from(der(INTERCEPT_LEAF));
+        let pinned = CertificateDer::from(der(ISRG_ROOT_X1));
+        let roots = [webpki::anchor_from_trusted_cert(&root).unwrap()];
+        let pin = |cert: &CertificateDer<'_>| format!("sha256/{}", base64::encode(&tls_client::spki_sha256(cert).unwrap()));
+        let now = UnixTime::now();
+
+        // The pinned certificate is appended to the handshake but the path runs leaf > proxy root
+        let pins = pins("Stream.Bybit.com", &[ISRG_ROOT_X1_PIN]).unwrap();
+        assert_eq!(pins.hosts().collect::<Vec<_>>(), ["stream.bybit.com"]);
+        let err = pins.verify("STREAM.bybit.com", &roots, &leaf, std::slice::from_ref(&pinned), now).unwrap_err();
+        assert!(err.contains("pin mismatch for STREAM.bybit.com"), "{}", err);
+        assert!(err.contains(&format!("[{} > {}]", pin(&leaf), pin(&root))), "{}", err);
+        assert!(!err.contains(ISRG_ROOT_X1_PIN), "{}", err);
+        assert!(pins.verify("fstream.binance.com", &roots, &leaf, std::slice::from_ref(&pinned), now).is_ok());
+
+        // Leaf or root of the verified path, either form of the pin
+        for key in [pin(&leaf), pin(&root)[7..].to_string()] {
+            let pins = self::pins("stream.bybit.com", &[ISRG_ROOT_X1_PIN, &key]).unwrap();
+            assert!(pins.verify("stream.bybit.com", &roots, &leaf, std::slice::from_ref(&pinned), now).is_ok());
+            // No path to the public roots: the pin is never consulted
+            let err = pins.verify("stream.bybit.com", webpki_roots::TLS_SERVER_ROOTS, &leaf, &[], now).unwrap_err();
+            assert!(err.contains("chain does not verify"), "{}", err);
+        }
+
+        assert!(self::pins("stream.bybit.com", &[]).unwrap_err().contains("empty pin list"));
+        assert!(self::pins("stream.bybit.com", &["sha256/AAAA"]).unwrap_err().contains("bad pin"));
+        assert!(self::pins("stream.bybit.com", &["not base64!"]).is_err());
+        assert!(CertPins::default().is_empty());
+        assert!(tls_client::client_config(pins).is_ok());
+    }
+}
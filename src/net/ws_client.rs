@@ -1,26 +1,305 @@
-use mio::{Events, Interest, Poll, Token, Registry};
-use std::io::{self, Read, Write, ErrorKind};
+use mio::{Token, Registry};
+use std::fmt;
+use std::io::{self, ErrorKind};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
 use rustls::ClientConfig;
-use crate::net::tcp_opt;
+use crate::core::base64;
+use crate::net::framing;
+use crate::net::resolver::{self, EndpointCache};
+use crate::net::tcp_opt::{self, TcpOptions};
 use crate::net::tls_client::TlsClient;
 
-// Token for our socket in the MIO poll
-const WS_TOKEN: Token = Token(0);
+const DEFAULT_PORT: u16 = 443;
+const DEFAULT_READ_BUFFER: usize = 65536;
+// RFC 6455: the server proves it read our key by hashing it with this GUID
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Upgrade response headers larger than this are refused (Bybit's are ~300 bytes).
+pub const MAX_UPGRADE_RESPONSE: usize = 8192;
 
-pub struct WsClient {
-    pub tls: TlsClient,
-    pub is_connected: bool,
-    pub handshake_complete: bool,
+/// Why the HTTP upgrade response was not accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeError {
+    /// Status other than 101 (403 geo-block, 429, 5xx during maintenance...).
+    Status(u16),
+    /// Required header absent or with the wrong value.
+    Header(&'static str),
+    /// `Sec-WebSocket-Accept` does not match our key.
+    Accept,
+    /// Not an HTTP/1.1 response.
+    Malformed,
+    /// No end of headers within `MAX_UPGRADE_RESPONSE`.
+    TooLarge,
 }
 
-impl WsClient {
-    // UPDATED: Now takes Arc<ClientConfig>
-    pub fn connect(addr: SocketAddr, server_name: &str, config: Arc<ClientConfig>) -> io::Result<Self> {
+impl fmt::Display for UpgradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradeError::Status(code) => write!(f, "HTTP {} instead of 101", code),
+            UpgradeError::Header(name) => write!(f, "bad or missing {} header", name),
+            UpgradeError::Accept => write!(f, "Sec-WebSocket-Accept does not match the key"),
+            UpgradeError::Malformed => write!(f, "malformed HTTP response"),
+            UpgradeError::TooLarge => write!(f, "response headers over {} bytes", MAX_UPGRADE_RESPONSE),
+        }
+    }
+}
+
+/// Fresh `Sec-WebSocket-Key`: 16 random bytes, base64.
+pub fn new_key() -> String {
+    let mut nonce = [0u8; 16];
+    if SystemRandom::new().fill(&mut nonce).is_err() {
+        // Only the server's echo depends on it; the key is not a secret
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos();
+        nonce = nanos.to_le_bytes();
+    }
+    base64::encode(&nonce)
+}
+
+/// `Sec-WebSocket-Accept` a server must answer `key` with.
+pub fn accept_key(key: &str) -> String {
+    let mut input = String::with_capacity(key.len() + WS_GUID.len());
+    input.push_str(key);
+    input.push_str(WS_GUID);
+    base64::encode(digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, input.as_bytes()).as_ref())
+}
+
+/// Parses the upgrade response at the start of `buf` (everything read so far).
+/// `Ok(None)`: headers not complete yet, read more. `Ok(Some(len))`: a valid 101 for `key`
+/// whose headers end at `len`; any bytes after it are the first WS frames.
+pub fn parse_upgrade(buf: &[u8], key: &str) -> Result<Option<usize>, UpgradeError> {
+    let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return if buf.len() > MAX_UPGRADE_RESPONSE { Err(UpgradeError::TooLarge) } else { Ok(None) };
+    };
+    let head = std::str::from_utf8(&buf[..pos]).map_err(|_| UpgradeError::Malformed)?;
+    let mut lines = head.split("\r\n");
+    let mut status = lines.next().unwrap_or("").split(' ');
+    if !status.next().is_some_and(|v| v.starts_with("HTTP/1.")) {
+        return Err(UpgradeError::Malformed);
+    }
+    let code = status.next().and_then(|c| c.parse::<u16>().ok()).ok_or(UpgradeError::Malformed)?;
+    if code != 101 {
+        return Err(UpgradeError::Status(code));
+    }
+    let (mut upgrade, mut connection, mut accept) = (false, false, None);
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(UpgradeError::Malformed)?;
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("upgrade") {
+            upgrade = value.eq_ignore_ascii_case("websocket");
+        } else if name.eq_ignore_ascii_case("connection") {
+            connection = value.split(',').any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+        } else if name.eq_ignore_ascii_case("sec-websocket-accept") {
+            accept = Some(value);
+        }
+    }
+    if !upgrade {
+        return Err(UpgradeError::Header("Upgrade"));
+    }
+    if !connection {
+        return Err(UpgradeError::Header("Connection"));
+    }
+    if accept.ok_or(UpgradeError::Header("Sec-WebSocket-Accept"))? != accept_key(key) {
+        return Err(UpgradeError::Accept);
+    }
+    Ok(Some(pos + 4))
+}
+
+/// What to do when the connection drops. Exponential backoff from `initial_backoff`
+/// up to `max_backoff`; `max_attempts == 0` means retry forever.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// No reconnects: a dropped connection stays dropped (the historical behavior).
+    pub fn never() -> Self {
+        Self { max_attempts: 1, initial_backoff: Duration::ZERO, max_backoff: Duration::ZERO }
+    }
+
+    /// Delay before reconnect attempt `attempt` (1-based), None when attempts are exhausted.
+    pub fn backoff(&self, attempt: u32) -> Option<Duration> {
+        if attempt == 0 || (self.max_attempts != 0 && attempt >= self.max_attempts) {
+            return None;
+        }
+        let factor = 1u32 << (attempt - 1).min(16);
+        Some((self.initial_backoff * factor).min(self.max_backoff))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+/// Declarative description of one WebSocket connection.
+/// Cloned into the `WsClient` it builds, so the same connection can be re-established.
+#[derive(Clone)]
+pub struct WsClientBuilder {
+    host: String,
+    port: u16,
+    path: String,
+    server_name: Option<String>,
+    headers: Vec<(String, String)>,
+    tcp: TcpOptions,
+    prefer_ipv4: bool,
+    read_buffer_size: usize,
+    ping_interval: Option<Duration>,
+    ping_message: Option<&'static str>,
+    reconnect: ReconnectPolicy,
+}
+
+impl WsClientBuilder {
+    pub fn new(host: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            port: DEFAULT_PORT,
+            path: "/".to_string(),
+            server_name: None,
+            headers: Vec::new(),
+            tcp: TcpOptions::default(),
+            prefer_ipv4: true,
+            read_buffer_size: DEFAULT_READ_BUFFER,
+            ping_interval: None,
+            ping_message: None,
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = path.to_string();
+        self
+    }
+
+    /// TLS SNI / certificate name, if different from the host we dial.
+    pub fn server_name(mut self, name: &str) -> Self {
+        self.server_name = Some(name.to_string());
+        self
+    }
+
+    /// Extra header for the upgrade request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn tcp_options(mut self, tcp: TcpOptions) -> Self {
+        self.tcp = tcp;
+        self
+    }
+
+    /// Our socket is IPv4; resolution falls back to the first address only if no IPv4 exists.
+    pub fn prefer_ipv4(mut self, prefer: bool) -> Self {
+        self.prefer_ipv4 = prefer;
+        self
+    }
+
+    /// Size of the receive buffer the owner should allocate (see `WsClient::read_buffer_size`).
+    pub fn read_buffer_size(mut self, size: usize) -> Self {
+        self.read_buffer_size = size;
+        self
+    }
+
+    /// Send a keepalive every `interval`: a WS ping frame, or `message` as a text
+    /// frame when the venue wants an application-level ping (Bybit: `{"op":"ping"}`).
+    pub fn auto_ping(mut self, interval: Duration, message: Option<&'static str>) -> Self {
+        self.ping_interval = Some(interval);
+        self.ping_message = message;
+        self
+    }
+
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Adds this connection's host/port to `cache` (to be pre-resolved) and returns its id.
+    pub fn register_endpoint(&self, cache: &mut EndpointCache) -> usize {
+        cache.add(&self.host, self.port, self.prefer_ipv4)
+    }
+
+    /// Resolves (blocking DNS), then `connect_to`. Not for the Hot Thread.
+    pub fn connect(&self, config: Arc<ClientConfig>) -> io::Result<WsClient> {
+        let addr = resolver::resolve(&self.host, self.port, self.prefer_ipv4)?;
+        self.connect_to(addr, config)
+    }
+
+    /// Connects to the endpoint's current address in `cache`, never touches DNS (Hot Thread).
+    /// An address the connect fails on at once (no route, unreachable) is rotated past and
+    /// the next one is tried, up to every cached address.
+    pub fn connect_cached(&self, cache: &mut EndpointCache, config: Arc<ClientConfig>) -> io::Result<WsClient> {
+        let not_cached = || io::Error::new(ErrorKind::NotFound, format!("No cached address for {}", self.host));
+        let id = cache.id(&self.host, self.port).ok_or_else(not_cached)?;
+        let mut last_err = None;
+        for _ in 0..cache.addrs(id).len() {
+            let addr = cache.addr(id).ok_or_else(not_cached)?;
+            match self.connect_to(addr, config.clone()) {
+                Ok(client) => return Ok(client),
+                Err(e) => {
+                    eprintln!("WS: connect to {} ({}) failed: {}", self.host, addr, e);
+                    cache.rotate(&self.host, self.port, addr);
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(not_cached))
+    }
+
+    /// Host, port and TLS server name to dial (shared with the async client).
+    #[cfg(feature = "tokio-runtime")]
+    pub(crate) fn target(&self) -> (&str, u16, &str) {
+        (&self.host, self.port, self.server_name.as_deref().unwrap_or(&self.host))
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    pub(crate) fn buffer_len(&self) -> usize {
+        self.read_buffer_size
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    pub(crate) fn ping_settings(&self) -> (Option<Duration>, Option<&'static str>) {
+        (self.ping_interval, self.ping_message)
+    }
+
+    /// HTTP/1.1 upgrade request for `key` with the configured extra headers.
+    pub(crate) fn handshake_request(&self, key: &str) -> String {
+        let mut extra = String::new();
+        for (name, value) in &self.headers {
+            extra.push_str(name);
+            extra.push_str(": ");
+            extra.push_str(value);
+            extra.push_str("\r\n");
+        }
+        format!(
+            "GET {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Upgrade: websocket\r\n\
+             Connection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\n\
+             Sec-WebSocket-Version: 13\r\n\
+             {}\
+             \r\n",
+            self.path, self.host, key, extra
+        )
+    }
+
+    /// Starts a non-blocking TCP connect to `addr` and wraps it in TLS.
+    /// The WS upgrade is sent later with `send_handshake` once the socket is writable.
+    pub fn connect_to(&self, addr: SocketAddr, config: Arc<ClientConfig>) -> io::Result<WsClient> {
         // 1. Create optimized raw socket
-        let raw_socket = tcp_opt::create_socket()?;
-        
+        let raw_socket = tcp_opt::create_socket_with(&self.tcp)?;
+
         // 2. Initiate connection
         match raw_socket.connect(&addr.into()) {
             Ok(_) => {}
@@ -30,42 +309,121 @@ impl WsClient {
         }
 
         let mio_stream: mio::net::TcpStream = mio::net::TcpStream::from_std(raw_socket.into());
-        
+
         // 3. Wrap in TLS
-        let tls_client = TlsClient::new(mio_stream, server_name, config)?;
+        let server_name = self.server_name.as_deref().unwrap_or(&self.host);
+        let tls_client = TlsClient::new(mio_stream, server_name, config.clone())?;
 
-        Ok(Self {
+        Ok(WsClient {
             tls: tls_client,
             is_connected: false,
             handshake_complete: false,
+            key: new_key(),
+            addr,
+            options: self.clone(),
+            config,
+            last_ping_ts: Instant::now(),
         })
     }
+}
+
+pub struct WsClient {
+    pub tls: TlsClient,
+    pub is_connected: bool,
+    pub handshake_complete: bool,
+    pub addr: SocketAddr,
+    // Sec-WebSocket-Key of this connection's upgrade
+    key: String,
+    options: WsClientBuilder,
+    config: Arc<ClientConfig>,
+    last_ping_ts: Instant,
+}
+
+impl WsClient {
+    pub fn builder(host: &str) -> WsClientBuilder {
+        WsClientBuilder::new(host)
+    }
+
+    /// Fresh connection with the same options (for the reconnect path).
+    /// Uses the cached address (refreshed by the Cold Thread), never blocks on DNS.
+    pub fn reconnect(&self, cache: &mut EndpointCache) -> io::Result<WsClient> {
+        self.options.connect_cached(cache, self.config.clone())
+    }
+
+    /// This connection never got its 101 (refused, reset, timed out, rejected): the next
+    /// `reconnect` goes to the endpoint's next cached address.
+    pub fn connect_failed(&self, cache: &mut EndpointCache) {
+        cache.rotate(&self.options.host, self.options.port, self.addr);
+    }
+
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        self.options.reconnect
+    }
+
+    pub fn read_buffer_size(&self) -> usize {
+        self.options.read_buffer_size
+    }
+
+    pub fn host(&self) -> &str {
+        &self.options.host
+    }
+
+    pub fn path(&self) -> &str {
+        &self.options.path
+    }
 
     pub fn register(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
          self.tls.register(registry, token)
     }
 
-    pub fn send_handshake(&mut self, host: &str, path: &str) -> io::Result<()> {
-        let request = format!(
-            "GET {} HTTP/1.1\r\n\
-             Host: {}\r\n\
-             Upgrade: websocket\r\n\
-             Connection: Upgrade\r\n\
-             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
-             Sec-WebSocket-Version: 13\r\n\
-             \r\n",
-            path, host
-        );
-
+    pub fn send_handshake(&mut self) -> io::Result<()> {
+        let request = self.options.handshake_request(&self.key);
         self.tls.write_plaintext(request.as_bytes())?;
         self.tls.write_tls()?;
         Ok(())
     }
 
+    /// Checks the upgrade response read so far into `buf` (see `parse_upgrade`).
+    /// `Ok(Some(len))` completes the handshake; `buf[len..]` holds the first frames.
+    pub fn accept_upgrade(&mut self, buf: &[u8]) -> Result<Option<usize>, UpgradeError> {
+        let head = parse_upgrade(buf, &self.key)?;
+        self.handshake_complete = head.is_some();
+        Ok(head)
+    }
+
+    /// Auto-ping configured and the interval has passed since the last ping.
+    pub fn ping_due(&self, now: Instant) -> bool {
+        match self.options.ping_interval {
+            Some(interval) => now.saturating_duration_since(self.last_ping_ts) >= interval,
+            None => false,
+        }
+    }
+
+    pub fn send_ping(&mut self, now: Instant) -> io::Result<()> {
+        self.last_ping_ts = now;
+        let mut frame = [0u8; 128];
+        let len = match self.options.ping_message {
+            Some(msg) => framing::encode_text_frame(msg.as_bytes(), &mut frame),
+            None => framing::encode_ping_frame(&mut frame),
+        };
+        self.tls.write_plaintext(&frame[..len])?;
+        self.tls.write_tls()
+    }
+
+    /// Answers a server close frame: echoes its status code (`CLOSE_NORMAL` if it sent none)
+    /// and marks the connection down. The server then drops TCP; nothing more is read.
+    pub fn send_close(&mut self, code: Option<u16>) -> io::Result<()> {
+        self.is_connected = false;
+        let mut frame = [0u8; 8];
+        let len = framing::encode_close_frame(code.unwrap_or(framing::CLOSE_NORMAL), &mut frame);
+        self.tls.write_plaintext(&frame[..len])?;
+        self.tls.write_tls()
+    }
+
     pub fn read<'a>(&mut self, buf: &'a mut [u8]) -> io::Result<usize> {
         self.tls.read(buf)
     }
-    
+
     pub fn write_tls(&mut self) -> io::Result<()> {
         self.tls.write_tls()
     }
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use crate::net::ws_client::{accept_key, new_key, parse_upgrade, UpgradeError};
+
+    // RFC 6455 section 1.3 example key
+    const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+
+    fn response(status: &str, accept: &str) -> Vec<u8> {
+        format!("HTTP/1.1 {}\r\nupgrade: WebSocket\r\nConnection: keep-alive, Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", status, accept).into_bytes()
+    }
+
+    #[test]
+    fn upgrade_split_across_reads_leaves_trailing_frames() {
+        assert_eq!(accept_key(KEY), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        let head = response("101 Switching Protocols", &accept_key(KEY));
+        let mut read = head.clone();
+        read.extend_from_slice(&[0x81, 0x02, b'{', b'}']); // first frame in the same packet
+
+        // Partial headers: wait, even past the status line
+        assert_eq!(parse_upgrade(&read[..20], KEY), Ok(None));
+        assert_eq!(parse_upgrade(&read[..head.len() - 2], KEY), Ok(None));
+        assert_eq!(parse_upgrade(&read, KEY), Ok(Some(head.len())));
+
+        // Fresh keys are random and the right size
+        let (a, b) = (new_key(), new_key());
+        assert_eq!(a.len(), 24);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn bad_upgrade_responses_are_refused() {
+        let ok = accept_key(KEY);
+        assert_eq!(parse_upgrade(&response("403 Forbidden", &ok), KEY), Err(UpgradeError::Status(403)));
+        assert_eq!(parse_upgrade(&response("101 Switching Protocols", "AAAA"), KEY), Err(UpgradeError::Accept));
+        assert_eq!(parse_upgrade(&response("101 Switching Protocols", &ok), "b3RoZXIga2V5IDEyMzQ1Ng=="), Err(UpgradeError::Accept));
+        let no_upgrade = format!("HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", ok);
+        assert_eq!(parse_upgrade(no_upgrade.as_bytes(), KEY), Err(UpgradeError::Header("Upgrade")));
+        assert_eq!(parse_upgrade(b"SSH-2.0-OpenSSH\r\n\r\n", KEY), Err(UpgradeError::Malformed));
+        assert_eq!(parse_upgrade(&[b'x'; 9000], KEY), Err(UpgradeError::TooLarge));
+    }
+}
@@ -1,2 +1,113 @@
-// Placeholder for BookManager
-pub struct BookManager;
+use crate::core::book_check::BookSanityMonitor;
+use crate::core::orderbook::L2OrderBook;
+use crate::strategy::market_maker::MarketMaker;
+use crate::strategy::markout::MarkoutTracker;
+use crate::strategy::obligation::QuoteUptime;
+use crate::strategy::order_manager::{self, OrderManager, OrderManagerConfig};
+use crate::strategy::router::{ExecutionRouter, Venue};
+use crate::strategy::send_gate::{SendGate, SendGateConfig};
+use crate::strategy::symbols::Instrument;
+
+// Per-symbol state of the Hot Thread. One slot per quoted contract, all fed by the same
+// public / private / trade connections: public messages are routed by the symbol in their
+// topic, private items by their `symbol` field, Trade WS responses by the slot tag in the
+// reqId (`order_manager::slot_tag`). Slot 0 is the primary instrument and keeps untagged
+// ids, so a single-symbol run looks exactly as before. Account-wide state (risk, fees,
+// session, outage, the trade pipeline) stays outside.
+
+/// One subscribe request carries both topics of every symbol and Bybit takes at most 10
+/// args per request; it also has to fit the Hot Thread's frame buffer.
+pub const MAX_SYMBOLS: usize = 5;
+
+pub struct SymbolSlot {
+    pub instrument: Instrument,
+    /// Bybit ticker (topics, order requests, position updates).
+    pub symbol: &'static str,
+    pub book: L2OrderBook,
+    pub book_monitor: BookSanityMonitor,
+    pub strategy: MarketMaker,
+    pub order_manager: OrderManager,
+    pub send_gate: SendGate,
+    pub markout: MarkoutTracker,
+    /// MM-program quote uptime (off unless HFT_MM_OBLIGATION is set).
+    pub obligation: QuoteUptime,
+    pub router: ExecutionRouter,
+}
+
+pub struct BookManager {
+    pub slots: Vec<SymbolSlot>,
+}
+
+impl BookManager {
+    pub fn new() -> Self {
+        Self { slots: Vec::new() }
+    }
+
+    /// Adds a slot for `instrument` and returns its index. Err if it has no Bybit ticker,
+    /// is already quoted, or the slots are full.
+    pub fn add(&mut self, instrument: Instrument, gate_config: SendGateConfig, oms_config: OrderManagerConfig) -> Result<usize, String> {
+        let symbol = instrument.venue_symbol(Venue::Bybit)
+            .ok_or_else(|| format!("{} has no Bybit symbol", instrument.name))?;
+        if self.slot_of(symbol).is_some() {
+            return Err(format!("{} is listed twice", symbol));
+        }
+        if self.slots.len() >= MAX_SYMBOLS {
+            return Err(format!("At most {} symbols per process", MAX_SYMBOLS));
+        }
+        let slot = self.slots.len();
+        let mut strategy = MarketMaker::new(0.01);
+        strategy.set_slot(slot);
+        strategy.set_instrument(&instrument);
+        self.slots.push(SymbolSlot {
+            instrument,
+            symbol,
+            book: L2OrderBook::new(),
+            book_monitor: BookSanityMonitor::new(),
+            strategy,
+            order_manager: OrderManager::new(oms_config),
+            send_gate: SendGate::new(gate_config, instrument),
+            markout: MarkoutTracker::new(),
+            obligation: QuoteUptime::default(),
+            router: ExecutionRouter::new(),
+        });
+        Ok(slot)
+    }
+
+    /// Slot quoting the Bybit ticker `symbol`.
+    pub fn slot_of(&self, symbol: &str) -> Option<usize> {
+        self.slots.iter().position(|s| s.symbol == symbol)
+    }
+
+    /// `slot_of` for a ticker still in raw message bytes (`parser::topic_symbol`).
+    pub fn slot_of_bytes(&self, symbol: &[u8]) -> Option<usize> {
+        self.slots.iter().position(|s| s.symbol.as_bytes() == symbol)
+    }
+
+    /// Slot an orderLinkId / reqId belongs to (its `slot_tag`).
+    pub fn by_link(&mut self, id: &str) -> Option<&mut SymbolSlot> {
+        self.slots.get_mut(order_manager::slot_from_link(id))
+    }
+
+    /// Start/reconnect/resync: every strategy goes back to warmup size.
+    pub fn restart_warmup(&mut self) {
+        for slot in &mut self.slots {
+            slot.strategy.warmup.restart();
+        }
+    }
+
+    /// Every book has its snapshot and `min_levels` per side.
+    pub fn all_ready(&self, min_levels: usize) -> bool {
+        self.slots.iter().all(|s| s.book.is_ready(min_levels))
+    }
+
+    /// Some slot holds a position.
+    pub fn in_position(&self) -> bool {
+        self.slots.iter().any(|s| s.strategy.position.abs() > 0.0001)
+    }
+}
+
+impl Default for BookManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
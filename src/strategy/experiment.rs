@@ -0,0 +1,150 @@
+use std::time::{Duration, Instant};
+use crate::strategy::market_maker::QuoteParams;
+use crate::strategy::fill::Fill;
+
+// Live A/B test: two parameter sets take turns on fixed time slices (A, B, A, B, ...),
+// so both see the same market regimes over a session. Fills are tagged with the variant
+// active when they arrive; a quote placed just before a switch can fill in the next
+// slice and is counted there.
+const DEFAULT_SLICE_SECS: u64 = 15 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    A,
+    B,
+}
+
+impl Variant {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::A => "A",
+            Variant::B => "B",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExperimentConfig {
+    pub enabled: bool,
+    pub slice: Duration,
+    /// Parameter sets, indexed by `Variant`.
+    pub variants: [QuoteParams; 2],
+}
+
+impl Default for ExperimentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            slice: Duration::from_secs(DEFAULT_SLICE_SECS),
+            variants: [QuoteParams::default(); 2],
+        }
+    }
+}
+
+/// Per-variant results. PnL is marked to the mid at report time: cash from all fills
+/// (maker and taker exits, net of fees) plus the variant's share of the position.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VariantStats {
+    pub slices: u32,
+    pub time_active: Duration,
+    pub maker_fills: u64,
+    pub taker_fills: u64,
+    pub volume: f64,
+    /// Net exchange fees (paid minus rebates), already in `pnl`.
+    pub fees: f64,
+    cash: f64,
+    position: f64,
+}
+
+impl VariantStats {
+    pub fn pnl(&self, mid: f64) -> f64 {
+        self.cash + self.position * mid
+    }
+}
+
+pub struct Experiment {
+    pub config: ExperimentConfig,
+    start: Option<Instant>,
+    slice_start: Instant,
+    current: Variant,
+    stats: [VariantStats; 2],
+}
+
+impl Experiment {
+    pub fn new(config: ExperimentConfig, now: Instant) -> Self {
+        Self { config, start: None, slice_start: now, current: Variant::A, stats: [VariantStats::default(); 2] }
+    }
+
+    pub fn current(&self) -> Variant {
+        self.current
+    }
+
+    pub fn params(&self, variant: Variant) -> QuoteParams {
+        self.config.variants[variant.index()]
+    }
+
+    /// Advances the schedule. Returns the variant to switch to when a new slice begins
+    /// (including the very first call); None while the current slice runs or when disabled.
+    pub fn poll(&mut self, now: Instant) -> Option<Variant> {
+        if !self.config.enabled || self.config.slice.is_zero() {
+            return None;
+        }
+        let start = *self.start.get_or_insert(now);
+        let slice_no = now.saturating_duration_since(start).as_nanos() / self.config.slice.as_nanos();
+        let variant = if slice_no.is_multiple_of(2) { Variant::A } else { Variant::B };
+        let first = self.stats.iter().all(|s| s.slices == 0);
+        if !first && variant == self.current {
+            return None;
+        }
+        if !first {
+            self.stats[self.current.index()].time_active += now.saturating_duration_since(self.slice_start);
+        }
+        self.current = variant;
+        self.slice_start = now;
+        self.stats[variant.index()].slices += 1;
+        Some(variant)
+    }
+
+    /// Tags a fill with the active variant. The exchange-reported fee (rebates are negative)
+    /// comes out of the variant's cash.
+    pub fn on_fill(&mut self, fill: &Fill) {
+        if !self.config.enabled {
+            return;
+        }
+        let stats = &mut self.stats[self.current.index()];
+        let sign = if fill.side == "Buy" { 1.0 } else { -1.0 };
+        stats.position += sign * fill.qty;
+        stats.cash -= sign * fill.notional() + fill.fee;
+        stats.volume += fill.notional();
+        stats.fees += fill.fee;
+        if fill.is_maker {
+            stats.maker_fills += 1;
+        } else {
+            stats.taker_fills += 1;
+        }
+    }
+
+    /// Results so far, counting the running slice up to `now`.
+    pub fn stats(&self, variant: Variant, now: Instant) -> VariantStats {
+        let mut stats = self.stats[variant.index()];
+        if variant == self.current && self.start.is_some() {
+            stats.time_active += now.saturating_duration_since(self.slice_start);
+        }
+        stats
+    }
+
+    /// Side-by-side summary, PnL marked to `mid`; fills and PnL also per hour active.
+    pub fn report(&self, mid: f64, now: Instant) {
+        for variant in [Variant::A, Variant::B] {
+            let s = self.stats(variant, now);
+            let hours = (s.time_active.as_secs_f64() / 3600.0).max(1e-9);
+            println!("EXPERIMENT: {} {:?} | slices {} ({:.1}h) | fills {} maker / {} taker ({:.1}/h) | vol ${:.0} | fees {:+.4} | PnL {:+.4} ({:+.4}/h)",
+                variant.name(), self.params(variant), s.slices, s.time_active.as_secs_f64() / 3600.0,
+                s.maker_fills, s.taker_fills, s.maker_fills as f64 / hours, s.volume, s.fees, s.pnl(mid), s.pnl(mid) / hours);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::strategy::experiment::{Experiment, ExperimentConfig, Variant};
+    use crate::strategy::fill::Fill;
+    use crate::strategy::market_maker::QuoteParams;
+
+    fn experiment(now: Instant) -> Experiment {
+        let b = QuoteParams { min_spread: QuoteParams::default().min_spread * 2.0, ..QuoteParams::default() };
+        let config = ExperimentConfig { enabled: true, slice: Duration::from_secs(10), variants: [QuoteParams::default(), b] };
+        Experiment::new(config, now)
+    }
+
+    fn fill(side: &'static str, px: f64, fee: f64, is_maker: bool) -> Fill {
+        Fill { side, qty: 1.0, px, fee, fee_rate: 0.0, is_maker, closed_pnl: 0.0, exec_time_ms: 0, seq: 0 }
+    }
+
+    #[test]
+    fn slices_alternate_from_the_first_poll() {
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let mut exp = experiment(t0);
+
+        assert_eq!(exp.poll(at(0)), Some(Variant::A));
+        assert_eq!(exp.poll(at(5)), None);
+        assert_eq!(exp.poll(at(10)), Some(Variant::B));
+        assert_eq!(exp.current(), Variant::B);
+        assert_ne!(exp.params(Variant::B), exp.params(Variant::A));
+        // A poll that lands two slices later sees the same variant: no switch
+        assert_eq!(exp.poll(at(31)), None);
+        assert_eq!(exp.poll(at(40)), Some(Variant::A));
+
+        let (a, b) = (exp.stats(Variant::A, at(45)), exp.stats(Variant::B, at(45)));
+        assert_eq!((a.slices, b.slices), (2, 1));
+        // Time is booked between switches, the running slice up to `now`
+        assert_eq!(a.time_active, Duration::from_secs(15));
+        assert_eq!(b.time_active, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn fills_count_for_the_active_variant() {
+        let t0 = Instant::now();
+        let mut exp = experiment(t0);
+        exp.poll(t0);
+        exp.on_fill(&fill("Buy", 100.0, -0.01, true));
+        exp.poll(t0 + Duration::from_secs(10));
+        exp.on_fill(&fill("Sell", 101.0, 0.05, false));
+
+        let now = t0 + Duration::from_secs(12);
+        let (a, b) = (exp.stats(Variant::A, now), exp.stats(Variant::B, now));
+        assert_eq!((a.maker_fills, a.taker_fills, b.maker_fills, b.taker_fills), (1, 0, 0, 1));
+        assert_eq!((a.volume, b.volume), (100.0, 101.0));
+        // Long 1 from 100 with a rebate, marked at 100.5; short 1 from 101 paying the fee
+        assert!((a.pnl(100.5) - 0.51).abs() < 1e-9);
+        assert!((b.pnl(100.5) - 0.45).abs() < 1e-9);
+        assert_eq!((a.fees, b.fees), (-0.01, 0.05));
+    }
+
+    #[test]
+    fn a_disabled_experiment_does_nothing() {
+        let t0 = Instant::now();
+        let mut exp = Experiment::new(ExperimentConfig::default(), t0);
+        assert_eq!(exp.poll(t0), None);
+        exp.on_fill(&fill("Buy", 100.0, 0.0, true));
+        let stats = exp.stats(Variant::A, t0 + Duration::from_secs(60));
+        assert_eq!((stats.slices, stats.maker_fills, stats.time_active), (0, 0, Duration::ZERO));
+        assert_eq!(exp.current(), Variant::A);
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::{Duration, Instant};
+use crate::core::rounding;
+
+// Quote fading on our own fill rate. The TPS spread and the configured size are static
+// bounds; this controller moves the quote inside/around them from how we are being hit:
+//   Fade    - both sides filled `hot_fills` times inside `window` (being run over, realized
+//             volatility is high): spread x `widen`, size x `shrink`, held for `cooldown`
+//   Tighten - no fill for `quiet_after`: the spread moves `tighten` of the way to the minimum
+//   Normal  - everything else, the TPS spread as is
+// Fill times are kept in a fixed ring; fills are rare, so this is off the tick path.
+const FILL_SLOTS: usize = 16;
+const DEFAULT_WINDOW_MS: u64 = 10_000;
+const DEFAULT_HOT_FILLS: usize = 4;
+const DEFAULT_WIDEN: f64 = 1.5;
+const DEFAULT_SHRINK: f64 = 0.5;
+const DEFAULT_COOLDOWN_MS: u64 = 30_000;
+const DEFAULT_QUIET_AFTER_MS: u64 = 60_000;
+const DEFAULT_TIGHTEN: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeMode {
+    Normal,
+    Fade,
+    Tighten,
+}
+
+impl FadeMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            FadeMode::Normal => "normal",
+            FadeMode::Fade => "fade",
+            FadeMode::Tighten => "tighten",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FadeConfig {
+    pub enabled: bool,
+    /// Rolling window the fill rate is measured over.
+    pub window: Duration,
+    /// Fills inside the window (with both sides hit) that trigger the fade; clamped to FILL_SLOTS.
+    pub hot_fills: usize,
+    /// Spread multiplier while fading.
+    pub widen: f64,
+    /// Size multiplier while fading.
+    pub shrink: f64,
+    /// How long the fade holds after the last triggering fill.
+    pub cooldown: Duration,
+    /// No fill for this long: tighten.
+    pub quiet_after: Duration,
+    /// Fraction of the way from the TPS spread to the minimum spread when tightening.
+    pub tighten: f64,
+}
+
+impl Default for FadeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: Duration::from_millis(DEFAULT_WINDOW_MS),
+            hot_fills: DEFAULT_HOT_FILLS,
+            widen: DEFAULT_WIDEN,
+            shrink: DEFAULT_SHRINK,
+            cooldown: Duration::from_millis(DEFAULT_COOLDOWN_MS),
+            quiet_after: Duration::from_millis(DEFAULT_QUIET_AFTER_MS),
+            tighten: DEFAULT_TIGHTEN,
+        }
+    }
+}
+
+/// Fill-rate feedback on the quote. Owned by the strategy. Fixed arrays only.
+pub struct QuoteFade {
+    pub config: FadeConfig,
+    // Side (true = Buy) and time of the last fills (ring)
+    fills: [Option<(bool, Instant)>; FILL_SLOTS],
+    next_slot: usize,
+    fade_until: Option<Instant>,
+    // Quiet timer starts at construction, so a fresh start does not tighten right away
+    last_fill: Instant,
+    mode: FadeMode,
+}
+
+impl QuoteFade {
+    pub fn new(config: FadeConfig, now: Instant) -> Self {
+        Self { config, fills: [None; FILL_SLOTS], next_slot: 0, fade_until: None, last_fill: now, mode: FadeMode::Normal }
+    }
+
+    /// One execution. Arms (or extends) the cooldown when the window is hot on both sides.
+    pub fn on_fill(&mut self, side: &str, now: Instant) {
+        self.fills[self.next_slot] = Some((side == "Buy", now));
+        self.next_slot = (self.next_slot + 1) % FILL_SLOTS;
+        self.last_fill = now;
+        let (buys, sells) = self.recent(now);
+        if buys > 0 && sells > 0 && buys + sells >= self.config.hot_fills.min(FILL_SLOTS) {
+            self.fade_until = Some(now + self.config.cooldown);
+        }
+    }
+
+    /// Fills (buys, sells) inside the window.
+    pub fn recent(&self, now: Instant) -> (usize, usize) {
+        self.fills
+            .iter()
+            .flatten()
+            .filter(|(_, ts)| now.saturating_duration_since(*ts) < self.config.window)
+            .fold((0, 0), |(b, s), (buy, _)| if *buy { (b + 1, s) } else { (b, s + 1) })
+    }
+
+    /// Re-evaluates the mode; Some(new mode) when it changed.
+    pub fn update(&mut self, now: Instant) -> Option<FadeMode> {
+        let mode = if !self.config.enabled {
+            FadeMode::Normal
+        } else if self.fade_until.is_some_and(|until| now < until) {
+            FadeMode::Fade
+        } else if now.saturating_duration_since(self.last_fill) >= self.config.quiet_after {
+            FadeMode::Tighten
+        } else {
+            FadeMode::Normal
+        };
+        if mode == self.mode {
+            return None;
+        }
+        self.mode = mode;
+        Some(mode)
+    }
+
+    /// Spread for the current mode, from the TPS spread and the (static) minimum.
+    pub fn spread(&self, tps_spread: f64, min_spread: f64) -> f64 {
+        match self.mode {
+            FadeMode::Normal => tps_spread,
+            FadeMode::Fade => tps_spread * self.config.widen,
+            FadeMode::Tighten => tps_spread - self.config.tighten.clamp(0.0, 1.0) * (tps_spread - min_spread).max(0.0),
+        }
+    }
+
+    /// Size for the current mode, on the lot grid and never below `min_qty`.
+    pub fn size(&self, qty: f64, qty_step: f64, min_qty: f64) -> f64 {
+        if self.mode != FadeMode::Fade || qty_step <= 0.0 {
+            return qty;
+        }
+        rounding::floor_to(qty * self.config.shrink, qty_step).max(min_qty)
+    }
+}
@@ -0,0 +1,113 @@
+// Bybit derivatives VIP tiers: 30-day volume (USD) -> maker/taker fee.
+// Bybit qualifies on total volume; we count maker volume only, so the tier
+// we report is a lower bound of what the exchange will grant.
+pub struct FeeTier {
+    pub name: &'static str,
+    pub min_volume_30d: f64,
+    pub maker_fee: f64,
+    pub taker_fee: f64,
+}
+
+pub const FEE_TIERS: [FeeTier; 6] = [
+    FeeTier { name: "VIP0", min_volume_30d: 0.0, maker_fee: 0.00020, taker_fee: 0.00055 },
+    FeeTier { name: "VIP1", min_volume_30d: 10_000_000.0, maker_fee: 0.00018, taker_fee: 0.00040 },
+    FeeTier { name: "VIP2", min_volume_30d: 25_000_000.0, maker_fee: 0.00016, taker_fee: 0.000375 },
+    FeeTier { name: "VIP3", min_volume_30d: 50_000_000.0, maker_fee: 0.00014, taker_fee: 0.00035 },
+    FeeTier { name: "VIP4", min_volume_30d: 100_000_000.0, maker_fee: 0.00012, taker_fee: 0.00032 },
+    FeeTier { name: "VIP5", min_volume_30d: 250_000_000.0, maker_fee: 0.00010, taker_fee: 0.00032 },
+];
+
+const WINDOW_DAYS: usize = 30;
+const RUN_RATE_DAYS: u64 = 7; // projection uses the last week's pace
+const MS_PER_DAY: u64 = 86_400_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeeConfig {
+    /// Lower the strategy's minimum spread by the round-trip maker fee saved vs VIP0.
+    pub auto_spread_floor: bool,
+}
+
+pub fn tier_for_volume(volume_30d: f64) -> usize {
+    FEE_TIERS.iter().rposition(|t| volume_30d >= t.min_volume_30d).unwrap_or(0)
+}
+
+/// Rolling 30-day maker volume in daily buckets (fixed array, no allocation).
+/// Fed from execution reports on the Hot Thread; fills are rare, so this is off the tick path.
+pub struct FeeTracker {
+    config: FeeConfig,
+    buckets: [f64; WINDOW_DAYS],
+    current_day: u64,
+    tier: usize,
+}
+
+impl FeeTracker {
+    pub fn new(config: FeeConfig) -> Self {
+        Self {
+            config,
+            buckets: [0.0; WINDOW_DAYS],
+            current_day: 0,
+            tier: 0,
+        }
+    }
+
+    /// Advances the window to the day of `now_ms`, clearing buckets of days that rolled off.
+    fn roll(&mut self, now_ms: u64) {
+        let day = now_ms / MS_PER_DAY;
+        if self.current_day == 0 {
+            self.current_day = day;
+            return;
+        }
+        let elapsed = day.saturating_sub(self.current_day);
+        for d in 1..=elapsed.min(WINDOW_DAYS as u64) {
+            self.buckets[((self.current_day + d) % WINDOW_DAYS as u64) as usize] = 0.0;
+        }
+        self.current_day = self.current_day.max(day);
+    }
+
+    /// Records a maker fill. Returns the new tier index if the fill moved us into a different tier.
+    pub fn on_maker_fill(&mut self, qty: f64, px: f64, now_ms: u64) -> Option<usize> {
+        self.roll(now_ms);
+        self.buckets[(self.current_day % WINDOW_DAYS as u64) as usize] += qty * px;
+        let tier = tier_for_volume(self.volume_30d());
+        if tier != self.tier {
+            self.tier = tier;
+            return Some(tier);
+        }
+        None
+    }
+
+    pub fn volume_30d(&self) -> f64 {
+        self.buckets.iter().sum()
+    }
+
+    pub fn tier(&self) -> &'static FeeTier {
+        &FEE_TIERS[self.tier]
+    }
+
+    /// Tier we'd reach if the last week's pace held for the whole window.
+    pub fn projected_tier(&self) -> &'static FeeTier {
+        let mut last_week = 0.0;
+        for d in 0..RUN_RATE_DAYS {
+            let day = self.current_day.saturating_sub(d);
+            last_week += self.buckets[(day % WINDOW_DAYS as u64) as usize];
+        }
+        let projected = self.volume_30d().max(last_week * WINDOW_DAYS as f64 / RUN_RATE_DAYS as f64);
+        &FEE_TIERS[tier_for_volume(projected)]
+    }
+
+    /// Spread-floor reduction for the strategy (0 unless `auto_spread_floor`).
+    /// Both legs of a round trip are maker, so the saving is twice the per-fill fee cut.
+    pub fn spread_floor_discount(&self) -> f64 {
+        if !self.config.auto_spread_floor {
+            return 0.0;
+        }
+        2.0 * (FEE_TIERS[0].maker_fee - self.tier().maker_fee)
+    }
+
+    /// One-line metrics summary.
+    pub fn report(&self) {
+        let tier = self.tier();
+        println!("FEES: 30d maker volume ${:.0} | tier {} (maker {:.4}%) | projected {}",
+            self.volume_30d(), tier.name, tier.maker_fee * 100.0, self.projected_tier().name);
+    }
+}
@@ -0,0 +1,116 @@
+use simd_json::prelude::*;
+
+// One `Trade` item of the private `execution` topic, normalized once and handed to
+// everything that reacts to fills (strategy, experiment, fee and PnL stats), so the
+// fee and the maker/taker flag come from the exchange instead of being assumed.
+// Bybit sends numbers as strings; a missing or malformed field reads as 0 / false.
+
+/// Normalized execution. `fee` is signed as Bybit reports it: positive = paid, negative = rebate.
+#[derive(Debug, Clone, Copy)]
+pub struct Fill {
+    pub side: &'static str,
+    pub qty: f64,
+    pub px: f64,
+    /// execFee, in the settle coin.
+    pub fee: f64,
+    /// feeRate actually applied (e.g. 0.0002).
+    pub fee_rate: f64,
+    pub is_maker: bool,
+    /// closedPnl: realized on the part of the position this execution closed (0 when opening).
+    pub closed_pnl: f64,
+    /// execTime, exchange ms.
+    pub exec_time_ms: u64,
+    /// Cross sequence shared with the `position` topic (0 when absent); orders the two streams.
+    pub seq: u64,
+}
+
+fn num(item: &simd_json::BorrowedValue, key: &str) -> f64 {
+    item.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0)
+}
+
+impl Fill {
+    /// Parses one `execution` data item. None when the side is neither Buy nor Sell.
+    pub fn from_execution(item: &simd_json::BorrowedValue) -> Option<Self> {
+        let side = match item.get("side").and_then(|v| v.as_str()) {
+            Some("Buy") => "Buy",
+            Some("Sell") => "Sell",
+            _ => return None,
+        };
+        Some(Self {
+            side,
+            qty: num(item, "execQty"),
+            px: num(item, "execPrice"),
+            fee: num(item, "execFee"),
+            fee_rate: num(item, "feeRate"),
+            is_maker: item.get("isMaker").and_then(|v| v.as_bool()).unwrap_or(false),
+            closed_pnl: num(item, "closedPnl"),
+            exec_time_ms: item.get("execTime").and_then(|v| v.as_str()).and_then(|t| t.parse::<u64>().ok()).unwrap_or(0),
+            seq: item.get("seq").and_then(|v| v.as_u64()).unwrap_or(0),
+        })
+    }
+
+    pub fn notional(&self) -> f64 {
+        self.qty * self.px
+    }
+}
+
+/// Session totals of executions: maker/taker mix, fees paid vs rebated, realized PnL.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FillStats {
+    pub maker_fills: u64,
+    pub taker_fills: u64,
+    pub maker_volume: f64,
+    pub taker_volume: f64,
+    /// Sum of positive execFee.
+    pub fees_paid: f64,
+    /// Sum of negative execFee, as a positive number.
+    pub rebates: f64,
+    /// Sum of closedPnl.
+    pub closed_pnl: f64,
+}
+
+impl FillStats {
+    pub fn on_fill(&mut self, fill: &Fill) {
+        if fill.is_maker {
+            self.maker_fills += 1;
+            self.maker_volume += fill.notional();
+        } else {
+            self.taker_fills += 1;
+            self.taker_volume += fill.notional();
+        }
+        if fill.fee >= 0.0 {
+            self.fees_paid += fill.fee;
+        } else {
+            self.rebates -= fill.fee;
+        }
+        self.closed_pnl += fill.closed_pnl;
+    }
+
+    /// Share of volume executed as maker (0 with no fills).
+    pub fn maker_share(&self) -> f64 {
+        let total = self.maker_volume + self.taker_volume;
+        if total > 0.0 { self.maker_volume / total } else { 0.0 }
+    }
+
+    pub fn net_fees(&self) -> f64 {
+        self.fees_paid - self.rebates
+    }
+
+    /// Net fees per traded notional, in bps.
+    pub fn fee_drag_bps(&self) -> f64 {
+        let total = self.maker_volume + self.taker_volume;
+        if total > 0.0 { self.net_fees() / total * 10_000.0 } else { 0.0 }
+    }
+
+    /// One-line metrics summary.
+    pub fn report(&self) {
+        self.report_as("FILLS");
+    }
+
+    /// `report` under another label (another venue's fills).
+    pub fn report_as(&self, label: &str) {
+        println!("{}: {} maker / {} taker | maker share {:.1}% | fees {:.4} paid, {:.4} rebated ({:+.2} bps drag) | closed PnL {:+.4}",
+            label, self.maker_fills, self.taker_fills, self.maker_share() * 100.0, self.fees_paid, self.rebates,
+            self.fee_drag_bps(), self.closed_pnl);
+    }
+}
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+// Inventory aging. Every fill that opens or adds to the position becomes a lot stamped
+// with its fill time; reducing fills consume lots oldest-first (FIFO), so the age of
+// the oldest lot is how long we have been stuck with the oldest unit of inventory.
+// The exit gets more aggressive as that age grows:
+//   Passive - rest the exit at the take-profit price (maker, waiting for the move)
+//   Join    - rest the exit at our side's touch (maker, accepting whatever PnL)
+//   Cross   - take liquidity for the lots past `cross_after` (market, reduce-only)
+// Fixed-size ring, no allocation; fills are rare, so this is off the tick path.
+const MAX_LOTS: usize = 16;
+const QTY_EPS: f64 = 0.0001;
+const DEFAULT_JOIN_AFTER_MS: u64 = 3000;
+const DEFAULT_CROSS_AFTER_MS: u64 = 8000;
+// Market closes in flight are not resent on every tick while the fill is on its way
+const DEFAULT_CROSS_RETRY_MS: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStage {
+    Passive,
+    Join,
+    Cross,
+}
+
+impl ExitStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitStage::Passive => "passive",
+            ExitStage::Join => "join",
+            ExitStage::Cross => "cross",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AgingConfig {
+    /// Oldest lot older than this: exit joins the touch.
+    pub join_after: Duration,
+    /// Lots older than this are closed at market.
+    pub cross_after: Duration,
+    /// Minimum gap between two market closes.
+    pub cross_retry: Duration,
+}
+
+impl Default for AgingConfig {
+    fn default() -> Self {
+        Self {
+            join_after: Duration::from_millis(DEFAULT_JOIN_AFTER_MS),
+            cross_after: Duration::from_millis(DEFAULT_CROSS_AFTER_MS),
+            cross_retry: Duration::from_millis(DEFAULT_CROSS_RETRY_MS),
+        }
+    }
+}
+
+impl AgingConfig {
+    pub fn stage(&self, age: Duration) -> ExitStage {
+        if age >= self.cross_after {
+            ExitStage::Cross
+        } else if age >= self.join_after {
+            ExitStage::Join
+        } else {
+            ExitStage::Passive
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Lot {
+    qty: f64,
+    opened: Instant,
+}
+
+/// FIFO lots of the current position (all on one side: long or short).
+#[derive(Debug)]
+pub struct InventoryLots {
+    lots: [Option<Lot>; MAX_LOTS],
+    head: usize,
+    len: usize,
+    long: bool,
+}
+
+impl InventoryLots {
+    pub fn new() -> Self {
+        Self { lots: [None; MAX_LOTS], head: 0, len: 0, long: true }
+    }
+
+    /// Signed position held in lots.
+    pub fn position(&self) -> f64 {
+        let qty: f64 = self.iter().map(|l| l.qty).sum();
+        if self.long { qty } else { -qty }
+    }
+
+    /// One fill, `signed_qty` > 0 for Buy. Adds a lot or consumes lots oldest-first;
+    /// a fill that flips the position opens a lot on the new side with the remainder.
+    pub fn on_fill(&mut self, signed_qty: f64, now: Instant) {
+        if signed_qty.abs() < QTY_EPS {
+            return;
+        }
+        if self.len == 0 {
+            self.long = signed_qty > 0.0;
+        }
+        if (signed_qty > 0.0) == self.long {
+            self.push(signed_qty.abs(), now);
+            return;
+        }
+        let mut left = signed_qty.abs();
+        while left > QTY_EPS && self.len > 0 {
+            let Some(lot) = &mut self.lots[self.head] else { break };
+            if lot.qty > left + QTY_EPS {
+                lot.qty -= left;
+                left = 0.0;
+            } else {
+                left -= lot.qty;
+                self.lots[self.head] = None;
+                self.head = (self.head + 1) % MAX_LOTS;
+                self.len -= 1;
+            }
+        }
+        if left > QTY_EPS {
+            self.long = signed_qty > 0.0;
+            self.push(left, now);
+        }
+    }
+
+    /// Exchange position (sync): the difference to what the lots hold is applied as a fill
+    /// at `now`, so lots that were already there keep their age.
+    pub fn sync(&mut self, position: f64, now: Instant) {
+        self.on_fill(position - self.position(), now);
+    }
+
+    /// Age of the oldest lot; None when flat.
+    pub fn oldest_age(&self, now: Instant) -> Option<Duration> {
+        self.iter().next().map(|l| now.saturating_duration_since(l.opened))
+    }
+
+    /// Quantity held longer than `age`.
+    pub fn qty_older_than(&self, age: Duration, now: Instant) -> f64 {
+        self.iter()
+            .take_while(|l| now.saturating_duration_since(l.opened) >= age)
+            .map(|l| l.qty)
+            .sum()
+    }
+
+    fn push(&mut self, qty: f64, now: Instant) {
+        if self.len == MAX_LOTS {
+            // Ring full: fold into the newest lot (it keeps its older stamp, so it ages early, not late)
+            let newest = (self.head + self.len - 1) % MAX_LOTS;
+            if let Some(lot) = &mut self.lots[newest] {
+                lot.qty += qty;
+            }
+            return;
+        }
+        self.lots[(self.head + self.len) % MAX_LOTS] = Some(Lot { qty, opened: now });
+        self.len += 1;
+    }
+
+    /// Lots oldest-first.
+    fn iter(&self) -> impl Iterator<Item = &Lot> {
+        (0..self.len).filter_map(move |i| self.lots[(self.head + i) % MAX_LOTS].as_ref())
+    }
+}
+
+impl Default for InventoryLots {
+    fn default() -> Self {
+        Self::new()
+    }
+}
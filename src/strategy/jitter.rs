@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+use crate::core::rounding;
+
+// Randomized requote timing/price/size so our refresh pattern (round timestamps, exact
+// offsets from the touch, one signature clip size) is harder for adversarial takers to learn.
+const DEFAULT_MAX_DELAY_US: u64 = 2_000;
+const DEFAULT_MAX_TICKS: i64 = 1;
+const DEFAULT_SIZE_BAND: f64 = 0.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct JitterConfig {
+    pub enabled: bool,
+    /// Requotes are held back by a uniform delay in [0, max_delay].
+    pub max_delay: Duration,
+    /// Quote prices are moved by a uniform whole number of ticks in [-max_ticks, max_ticks].
+    pub max_ticks: i64,
+    /// Quote size is drawn from base * [1 - size_band, 1 + size_band], on the lot grid.
+    /// Independent of `enabled`; 0.0 = constant size.
+    pub size_band: f64,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_delay: Duration::from_micros(DEFAULT_MAX_DELAY_US),
+            max_ticks: DEFAULT_MAX_TICKS,
+            size_band: DEFAULT_SIZE_BAND,
+        }
+    }
+}
+
+/// Requote jitter state. xorshift64* PRNG: no crate, no allocation, not cryptographic
+/// (only has to be unpredictable from the outside, not secure).
+pub struct QuoteJitter {
+    pub config: JitterConfig,
+    rng: u64,
+    deadline: Option<Instant>,
+}
+
+impl QuoteJitter {
+    pub fn new(config: JitterConfig, seed: u64) -> Self {
+        Self { config, rng: seed | 1, deadline: None }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    /// Whether a triggered requote may go out now. The first call arms a random
+    /// deadline; the requote goes out on the first tick at or after it. A deadline
+    /// left over from a trigger that went away (older than `max_delay`) is re-armed.
+    pub fn ready(&mut self, now: Instant) -> bool {
+        if !self.config.enabled {
+            return true;
+        }
+        match self.deadline {
+            Some(deadline) if now >= deadline && now - deadline <= self.config.max_delay => {
+                self.deadline = None;
+                true
+            }
+            Some(deadline) if now < deadline => false,
+            _ => {
+                let max_us = self.config.max_delay.as_micros() as u64;
+                let delay = Duration::from_micros(if max_us == 0 { 0 } else { self.next() % (max_us + 1) });
+                if delay.is_zero() {
+                    return true;
+                }
+                self.deadline = Some(now + delay);
+                false
+            }
+        }
+    }
+
+    /// Random price offset in whole ticks (0.0 when disabled).
+    pub fn price_offset(&mut self, tick_size: f64) -> f64 {
+        if !self.config.enabled || self.config.max_ticks <= 0 {
+            return 0.0;
+        }
+        let span = (2 * self.config.max_ticks + 1) as u64;
+        let ticks = (self.next() % span) as i64 - self.config.max_ticks;
+        ticks as f64 * tick_size
+    }
+
+    /// Random quote size around `base`, uniform over the lot steps inside the band and
+    /// never below `min_qty`. Returns `base` unchanged when the band is off.
+    pub fn size(&mut self, base: f64, qty_step: f64, min_qty: f64) -> f64 {
+        let band = self.config.size_band;
+        if band <= 0.0 || qty_step <= 0.0 {
+            return base;
+        }
+        let lo = rounding::ceil_steps((base * (1.0 - band)).max(min_qty), qty_step).max(0.0) as u64;
+        let hi = rounding::floor_steps(base * (1.0 + band), qty_step).max(0.0) as u64;
+        let steps = if hi <= lo { lo } else { lo + self.next() % (hi - lo + 1) };
+        steps as f64 * qty_step
+    }
+}
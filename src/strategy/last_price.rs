@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+// Last traded price guard. The book we quote off can show a level that never trades: a
+// momentary glitch, a stale delta, a fat-fingered order on an illiquid symbol. The last
+// price from publicTrade is an independent check: while our touch is more than
+// `max_deviation` away from it the strategy neither quotes nor exits on that book (a
+// maintenance halt still flattens). A last price older than `max_age` is not trusted and
+// the guard stays open, so a symbol that simply stopped trading keeps quoting.
+const DEFAULT_MAX_DEVIATION: f64 = 0.02;
+const DEFAULT_MAX_AGE_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LastPriceConfig {
+    pub enabled: bool,
+    /// Max |touch / last trade - 1| the strategy still acts on.
+    pub max_deviation: f64,
+    pub max_age: Duration,
+}
+
+impl Default for LastPriceConfig {
+    fn default() -> Self {
+        Self { enabled: true, max_deviation: DEFAULT_MAX_DEVIATION, max_age: Duration::from_millis(DEFAULT_MAX_AGE_MS) }
+    }
+}
+
+/// Owned by the strategy; fed from publicTrade by the caller.
+#[derive(Debug)]
+pub struct LastPriceGuard {
+    pub config: LastPriceConfig,
+    last_px: f64,
+    last_ts: Option<Instant>,
+    // The last check found the book off the last trade
+    blocked: bool,
+    /// Book updates the guard refused to act on.
+    pub rejected: u64,
+}
+
+impl LastPriceGuard {
+    pub fn new(config: LastPriceConfig) -> Self {
+        Self { config, last_px: 0.0, last_ts: None, blocked: false, rejected: 0 }
+    }
+
+    /// Price of the newest trade in a publicTrade message.
+    pub fn on_trade(&mut self, price: f64, now: Instant) {
+        if price.is_finite() && price > 0.0 {
+            self.last_px = price;
+            self.last_ts = Some(now);
+        }
+    }
+
+    /// Last trade price if it is fresh enough to judge the book by.
+    pub fn last_price(&self, now: Instant) -> Option<f64> {
+        let ts = self.last_ts?;
+        (now.saturating_duration_since(ts) <= self.config.max_age).then_some(self.last_px)
+    }
+
+    pub fn blocked(&self) -> bool {
+        self.blocked
+    }
+
+    /// Touch (bid, ask) against the last trade: the larger deviation, Some when it is outside
+    /// the band. Also records whether the book is blocked.
+    pub fn check(&mut self, bid: f64, ask: f64, now: Instant) -> Option<f64> {
+        let deviation = match self.last_price(now) {
+            Some(last) if self.config.enabled && self.config.max_deviation > 0.0 => {
+                // A NaN touch is as untrustworthy as a far one
+                let worst = if bid.is_finite() && ask.is_finite() {
+                    (bid / last - 1.0).abs().max((ask / last - 1.0).abs())
+                } else {
+                    f64::INFINITY
+                };
+                (worst > self.config.max_deviation).then_some(worst)
+            }
+            _ => None,
+        };
+        self.blocked = deviation.is_some();
+        if self.blocked {
+            self.rejected += 1;
+        }
+        deviation
+    }
+}
@@ -0,0 +1,90 @@
+use crate::strategy::symbols::{Category, Instrument};
+
+// Pre-trade margin estimate. Bybit answers an order the account cannot fund with an
+// insufficient-balance reject (110007 / 110004 / 110012) only after the round trip, and a
+// burst of quotes right after a fill draws a whole row of them. Every position-increasing
+// create/amend is priced here (notional / leverage, in the settle coin) against the available
+// balance of the private `wallet` topic (seeded by REST at startup), minus what the orders sent
+// since that update have already taken. Until a balance is known nothing is blocked.
+
+// Bybit's default leverage; the position topic reports the real one per symbol
+const DEFAULT_LEVERAGE: f64 = 10.0;
+// Part of the available balance kept free (fees, mark moves between wallet updates)
+const DEFAULT_BUFFER: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MarginConfig {
+    /// Used until the position topic reports a symbol's leverage.
+    pub leverage: f64,
+    /// Fraction of the available balance never committed to new orders.
+    pub buffer: f64,
+}
+
+impl Default for MarginConfig {
+    fn default() -> Self {
+        Self { leverage: DEFAULT_LEVERAGE, buffer: DEFAULT_BUFFER }
+    }
+}
+
+/// Initial margin of `qty` (base coin) at `price`, in the instrument's settle coin.
+/// Spot has no leverage: the whole notional.
+pub fn initial_margin(instrument: &Instrument, qty: f64, price: f64, leverage: f64) -> f64 {
+    let leverage = if instrument.category == Category::Spot { 1.0 } else { leverage.max(1.0) };
+    let notional = match instrument.category {
+        // Inverse notional is USD, its margin is in the base coin: exactly the base qty
+        Category::Inverse => qty,
+        _ => qty * price,
+    };
+    notional.abs() / leverage
+}
+
+/// Account-wide: owned by the Hot Thread, shared by every symbol slot's send gate.
+pub struct MarginTracker {
+    pub config: MarginConfig,
+    /// Coin the balance is tracked in (settle coin of the primary instrument).
+    pub coin: &'static str,
+    available: Option<f64>,
+    // Margin of orders sent since the last balance update
+    reserved: f64,
+}
+
+impl MarginTracker {
+    pub fn new(config: MarginConfig, coin: &'static str) -> Self {
+        Self { config, coin, available: None, reserved: 0.0 }
+    }
+
+    /// New available balance (wallet topic or REST). It already counts the margin of every
+    /// order the exchange has, so the local reservations start over.
+    pub fn on_balance(&mut self, available: f64) {
+        self.available = Some(available);
+        self.reserved = 0.0;
+    }
+
+    pub fn available(&self) -> Option<f64> {
+        self.available
+    }
+
+    /// What new orders may still take. None = no balance yet.
+    pub fn headroom(&self) -> Option<f64> {
+        self.available.map(|a| a * (1.0 - self.config.buffer) - self.reserved)
+    }
+
+    /// Err(headroom) when `required` does not fit.
+    pub fn check(&self, required: f64) -> Result<(), f64> {
+        match self.headroom() {
+            Some(headroom) if required > headroom => Err(headroom),
+            _ => Ok(()),
+        }
+    }
+
+    /// An order taking `required` left the gate.
+    pub fn reserve(&mut self, required: f64) {
+        self.reserved += required;
+    }
+
+    /// A reserved order never reached the exchange. A balance update since then has already
+    /// started the reservations over, hence the floor.
+    pub fn release(&mut self, required: f64) {
+        self.reserved = (self.reserved - required).max(0.0);
+    }
+}
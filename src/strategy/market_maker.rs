@@ -1,10 +1,61 @@
-use crate::core::orderbook::L2OrderBook;
-use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
+use crate::core::orderbook::{L2OrderBook, Side};
+use crate::core::batch::BatchDetector;
+use crate::core::parser::BookUpdate;
+use crate::core::rounding::{self, Grid};
+use crate::strategy::strategy_log::{LogSampling, StrategyEvent, StrategyLog};
+use crate::strategy::jitter::{JitterConfig, QuoteJitter};
+use crate::strategy::markout::QuoteOrigin;
+use crate::strategy::fill::Fill;
+use crate::strategy::position::PositionTracker;
+use crate::strategy::inventory::{AgingConfig, ExitStage, InventoryLots};
+use crate::strategy::fade::{FadeConfig, QuoteFade};
+use crate::strategy::warmup::{QuoteWarmup, WarmupConfig, WarmupPhase};
+use crate::strategy::requote::{EdgeDecay, RequoteConfig};
+use crate::strategy::regime::{RegimeClassifier, RegimeConfig, RegimeInputs};
+use crate::strategy::walls::{WallConfig, WallTracker};
+use crate::strategy::last_price::{LastPriceConfig, LastPriceGuard};
+use crate::strategy::order_manager;
+use crate::strategy::symbols::{Category, Instrument};
+use std::time::{Instant, Duration};
+use crate::core::clock::{Clock, LiveClock};
+use serde::Deserialize;
+
+// Default price grid and clip size. The grids are the defaults of `MarketMaker::tick_size` /
+// `qty_step` / `min_qty`; the Hot Thread sets each symbol's own (instruments-info).
+const TICK_SIZE: f64 = 0.01;
+const QUOTE_QTY: f64 = 0.8;
+const QTY_STEP: f64 = 0.1;             // RIVER lot grid (symbols::Instrument)
+const MIN_QTY: f64 = 0.1;
+
+// Take profit (default of `QuoteParams::take_profit_pct`). The base target is pulled in when a
+// wall rests just in front of it: price tends to stall on the wall, so we exit one tick ahead of it instead.
+const TAKE_PROFIT_PCT: f64 = 0.005;
+const TP_WALL_WINDOW_PCT: f64 = 0.002; // how far in front of the TP a wall still counts
+const TP_MIN_PCT: f64 = 0.001;         // never pull the target below fees + a bit
+
+// Requote buffer (Rule 1) skewed by inventory: the side that reduces the position
+// requotes on smaller moves, the side that would add to it on larger ones.
+const REQUOTE_BUFFER_PCT: f64 = 0.001;
+const INVENTORY_CLIPS: f64 = 3.0;      // |position| at which the skew is maxed, in clips at mid
+const INVENTORY_SKEW: f64 = 0.5;       // threshold range: buffer * (1 -/+ 0.5)
+
+// Spread band: the regime's spread (regime.rs) is kept inside it.
+const MIN_SPREAD: f64 = 0.004;
+const MAX_SPREAD: f64 = 0.010;
+
+// Limit-first close: how long the reduce-only limit rests at the touch before the market fallback.
+const CLOSE_LIMIT_MS: u64 = 1500;
+
+// OrderManager state invariant check interval (quiet-market requotes: requote.rs).
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ActionType {
     CreateOrder { price: f64, qty: f64, side: &'static str, link_id: String },
     AmendOrder { price: f64, qty: f64, side: &'static str, link_id: String },
+    // Narrowed by the OMS (`narrow_amend`) so unchanged fields never reach Bybit ("not modified")
+    AmendPrice { price: f64, side: &'static str, link_id: String },
+    AmendQty { qty: f64, side: &'static str, link_id: String }, // Keeps queue priority
     CancelOrder { link_id: String },
     ClosePosition { qty: f64, side: &'static str },
     SetTradingStop { price: f64, side: &'static str }, // Server-side SL
@@ -17,7 +68,103 @@ pub struct Action {
     pub action_type: ActionType,
 }
 
-pub struct MarketMaker {
+/// Quoting parameters that can change at runtime (A/B experiments swap whole sets).
+/// The `[quote]` table of the engine config.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuoteParams {
+    pub min_spread: f64,
+    pub max_spread: f64,
+    /// Requote buffer before inventory skew (Rule 1).
+    pub requote_buffer_pct: f64,
+    /// Base clip size (before size jitter).
+    pub quote_qty: f64,
+    /// Clip as quote-coin notional (e.g. USDT), converted at mid onto the lot grid; replaces
+    /// `quote_qty` so one value gives comparable risk on symbols with very different prices.
+    pub quote_notional: Option<f64>,
+    /// Base take-profit target, PnL fraction (pulled in by a wall, never pushed out).
+    pub take_profit_pct: f64,
+}
+
+impl Default for QuoteParams {
+    fn default() -> Self {
+        Self {
+            min_spread: MIN_SPREAD,
+            max_spread: MAX_SPREAD,
+            requote_buffer_pct: REQUOTE_BUFFER_PCT,
+            quote_qty: QUOTE_QTY,
+            quote_notional: None,
+            take_profit_pct: TAKE_PROFIT_PCT,
+        }
+    }
+}
+
+/// How a close signal (TP, trailing stop, maintenance halt) is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseOrderType {
+    /// Market order right away.
+    Market,
+    /// Reduce-only limit at our side's touch first, market after `limit_timeout`.
+    /// Maintenance halts still close at market.
+    LimitFirst,
+}
+
+impl CloseOrderType {
+    /// `market` | `limit-first` (HFT_CLOSE_MODE).
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec.trim() {
+            "market" => Some(CloseOrderType::Market),
+            "limit-first" => Some(CloseOrderType::LimitFirst),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CloseConfig {
+    pub order_type: CloseOrderType,
+    pub limit_timeout: Duration,
+}
+
+impl Default for CloseConfig {
+    fn default() -> Self {
+        Self { order_type: CloseOrderType::Market, limit_timeout: Duration::from_millis(CLOSE_LIMIT_MS) }
+    }
+}
+
+impl QuoteParams {
+    /// Base clip at `mid`: `quote_notional / mid` rounded to `qty_step` (at least `min_qty`),
+    /// or `quote_qty` without a notional or before the first mid.
+    pub fn clip_qty(&self, mid: f64, qty_step: f64, min_qty: f64) -> f64 {
+        match self.quote_notional {
+            Some(notional) if notional > 0.0 && mid > 0.0 && qty_step > 0.0 => {
+                rounding::round_to(notional / mid, qty_step).max(min_qty)
+            }
+            _ => self.quote_qty,
+        }
+    }
+
+    /// Overrides from a spec like `"min_spread=0.003,requote=0.0015,qty=0.6,tp=0.004"`
+    /// (`notional=50` sizes in quote coin). Err on an unknown name or a bad number; the
+    /// params are then partly applied and should be dropped.
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let parsed = item.split_once('=').and_then(|(name, v)| Some((name.trim(), v.trim().parse::<f64>().ok()?)));
+            match parsed {
+                Some(("min_spread", v)) => self.min_spread = v,
+                Some(("max_spread", v)) => self.max_spread = v,
+                Some(("requote", v)) => self.requote_buffer_pct = v,
+                Some(("qty", v)) => self.quote_qty = v,
+                Some(("notional", v)) => self.quote_notional = Some(v),
+                Some(("tp", v)) => self.take_profit_pct = v,
+                _ => return Err(format!("bad quote parameter '{}' (min_spread, max_spread, requote, qty, notional, tp)", item)),
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct MarketMaker<C: Clock = LiveClock> {
     target_spread: f64, // Not used for signal now, but maybe for check?
     tick_counter: u64,
     pub binance_bid: f64,
@@ -29,51 +176,181 @@ pub struct MarketMaker {
     pub has_active_sell: bool,
     pub active_buy_link_id: String,
     pub active_sell_link_id: String,
+    // Symbol slot prefix of our link ids (`order_manager::slot_tag`), empty for the primary.
+    pub link_tag: String,
+    // Copy of `positions` (size, entry), refreshed whenever it changes
     pub position: f64,
     pub entry_price: f64,
+    // Fills vs position snapshots: the one authoritative size (`position.rs`)
+    pub positions: PositionTracker,
+    // Session totals from execution reports (execFee signed as Bybit sends it, closedPnl)
+    pub fees_paid: f64,
+    pub realized_pnl: f64,
     pub last_trade_ts: Option<Instant>,
+    // Optimistic: price of the last quote sent per side, pending amends included. Only used
+    // to suppress duplicate amends; the confirmed price lives in the OrderManager's OrderRecord.
     pub active_buy_price: f64,
     pub active_sell_price: f64,
+    // How the price of the last quote sent per side was chosen (fill markout tagging).
+    pub active_buy_origin: QuoteOrigin,
+    pub active_sell_origin: QuoteOrigin,
     pub last_update_mid: f64,
     
     // Risk / Exit Logic
     pub highest_pnl_pct: f64,
     pub server_sl_set: bool, // Track if we've sent the request to Bybit
+    // FIFO lots of the position; the oldest lot's age picks the exit stage (see inventory.rs)
+    pub inventory: InventoryLots,
+    pub aging: AgingConfig,
+    exit_stage: Option<ExitStage>,
+    last_cross_ts: Option<Instant>,
+    // Market vs limit-first closes; the deadline is set while a limit close rests
+    pub close: CloseConfig,
+    close_deadline: Option<Instant>,
 
     // Velocity Logic
     pub last_tick_arrival_ts: Instant,
     pub tick_interval_ema: f64,
-    pub last_exch_ts: u64, // For batch detection
+    pub batch: BatchDetector, // Burst detection (same exchange ts), see core::batch
+
+    // Set when the OrderManager held back one of our amends (or asks for a stale-quote
+    // refresh); forces a requote on the next tick regardless of the price trigger.
+    pub requote_pending: bool,
+
+    // Safe mode (wind-down / exchange incident): exits still run, no new quotes.
+    pub reduce_only: bool,
+    // Exchange maintenance ahead/ongoing: flatten and stay out until it passes.
+    pub halted: bool,
+    // Subtracted from the minimum spread when a cheaper maker fee tier is reached (FeeTracker).
+    pub spread_floor_discount: f64,
+    // Sampled diagnostics, drained by the Hot Thread into the log ring (no println on the hot path).
+    pub log: StrategyLog,
+    // Optional randomized requote delay / price offset (off by default).
+    pub jitter: QuoteJitter,
+    // Wall candidates per price level; only persistent ones are front-run or used for TP.
+    pub walls: WallTracker,
+    // Spread / requote buffer / size; swapped by the A/B experiment between time slices.
+    pub params: QuoteParams,
+    // Price grid of the quoted symbol: every quote and exit price is rounded to it.
+    pub tick_size: f64,
+    // Lot grid of the quoted symbol (base coin): clip rounding, aged exits, closes.
+    pub qty_step: f64,
+    pub min_qty: f64,
+    // Widens/shrinks the quote while we are being run over, tightens it when fills dry up.
+    pub fade: QuoteFade,
+    // Reduced size after start/reconnect/resync, ramped up while the Hot Thread reports healthy ticks.
+    pub warmup: QuoteWarmup,
+    // Refreshes a resting quote once the mid drift has eaten its edge (replaces the fixed heartbeat).
+    pub requote: EdgeDecay,
+    // Calm / normal / volatile / extreme from volatility, TPS and toxicity; picks spread, buffers, size.
+    pub regime: RegimeClassifier,
+    // Last publicTrade price; a book too far from it is not acted on (glitch on an illiquid symbol).
+    pub last_price: LastPriceGuard,
+    // All time reads go through here (simulated in the backtester).
+    clock: C,
 }
 
 impl MarketMaker {
-    pub fn new(_target_spread: f64) -> Self {
+    pub fn new(target_spread: f64) -> Self {
+        Self::with_clock(target_spread, LiveClock)
+    }
+}
+
+impl<C: Clock> MarketMaker<C> {
+    pub fn with_clock(_target_spread: f64, clock: C) -> Self {
+        let now = clock.monotonic_now();
+        let now_ms = clock.now_us() / 1000;
         Self { 
             target_spread: 0.01,
             tick_counter: 0,
             binance_bid: 0.0,
             binance_ask: 0.0,
-            last_update_ts: Instant::now(),
+            last_update_ts: now,
             has_active_buy: false,
             has_active_sell: false,
 
-            active_buy_link_id: format!("b-{}", SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis()),
-            active_sell_link_id: format!("s-{}", SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis()),
+            active_buy_link_id: format!("b-{}", now_ms),
+            active_sell_link_id: format!("s-{}", now_ms),
+            link_tag: String::new(),
             position: 0.0,
             entry_price: 0.0,
+            positions: PositionTracker::default(),
+            fees_paid: 0.0,
+            realized_pnl: 0.0,
             last_trade_ts: None,
             active_buy_price: 0.0,
             active_sell_price: 0.0,
+            active_buy_origin: QuoteOrigin::Touch,
+            active_sell_origin: QuoteOrigin::Touch,
 
             last_update_mid: 0.0,
             
             highest_pnl_pct: -1.0, // Start low
             server_sl_set: false,
+            inventory: InventoryLots::new(),
+            aging: AgingConfig::default(),
+            exit_stage: None,
+            last_cross_ts: None,
+            close: CloseConfig::default(),
+            close_deadline: None,
 
              // Init with simulated 100ms interval (10 TPS) to start safe? Or slow (1s = 1 TPS)
-            last_tick_arrival_ts: Instant::now(),
+            last_tick_arrival_ts: now,
             tick_interval_ema: 1_000_000.0, // Start slow (1 TPS)
-            last_exch_ts: 0,
+            batch: BatchDetector::default(),
+            requote_pending: false,
+            reduce_only: false,
+            halted: false,
+            spread_floor_discount: 0.0,
+            log: StrategyLog::new(LogSampling::default()),
+            jitter: QuoteJitter::new(JitterConfig::default(), clock.now_us()),
+            walls: WallTracker::new(WallConfig::default()),
+            params: QuoteParams::default(),
+            tick_size: TICK_SIZE,
+            qty_step: QTY_STEP,
+            min_qty: MIN_QTY,
+            fade: QuoteFade::new(FadeConfig::default(), now),
+            warmup: QuoteWarmup::new(WarmupConfig::default()),
+            requote: EdgeDecay::new(RequoteConfig::default(), now),
+            regime: RegimeClassifier::new(RegimeConfig::default()),
+            last_price: LastPriceGuard::new(LastPriceConfig::default()),
+            clock,
+        }
+    }
+
+    /// Symbol slot this strategy quotes for; link ids carry it so fills and acks route back.
+    pub fn set_slot(&mut self, slot: usize) {
+        self.link_tag = order_manager::slot_tag(slot);
+        let ms = self.clock.now_us() / 1000;
+        self.active_buy_link_id = format!("{}b-{}", self.link_tag, ms);
+        self.active_sell_link_id = format!("{}s-{}", self.link_tag, ms);
+    }
+
+    /// Price and lot grid of the quoted instrument. Strategy sizes are base coin; an inverse lot
+    /// is USD contracts (`Instrument::order_qty` rounds those), so inverse keeps the default lot.
+    pub fn set_instrument(&mut self, instrument: &Instrument) {
+        self.tick_size = instrument.tick_size;
+        if instrument.category != Category::Inverse {
+            self.qty_step = instrument.qty_step;
+            self.min_qty = instrument.min_qty;
+        }
+    }
+
+    /// Tick and lot grid the strategy rounds to (base-coin lots, see `set_instrument`).
+    #[inline(always)]
+    pub fn grid(&self) -> Grid {
+        Grid::new(self.tick_size, self.qty_step, self.min_qty)
+    }
+
+    /// Tick health from the Hot Thread (book, Trade WS, tick latency); drives the warmup ramp.
+    pub fn on_health(&mut self, healthy: bool) {
+        let now = self.clock.monotonic_now();
+        if let Some(phase) = self.warmup.on_health(healthy, now) {
+            self.log.record(StrategyEvent::Warmup { phase: phase.name(), factor: self.warmup.factor(now) });
+            // Full size goes out right away instead of waiting for the next requote
+            if phase == WarmupPhase::Done {
+                self.requote_pending = true;
+            }
         }
     }
 
@@ -82,10 +359,16 @@ impl MarketMaker {
         self.binance_ask = ask;
     }
     
-    pub fn on_fill(&mut self, side: &str, qty: f64, px: f64) {
-        // Weighted Average Entry Price
+    pub fn on_fill(&mut self, fill: &Fill) {
+        let (side, qty, px) = (fill.side, fill.qty, fill.px);
+        self.fees_paid += fill.fee;
+        self.realized_pnl += fill.closed_pnl;
+        // A position snapshot that overtook this fill already has it: fees and PnL count, size doesn't
+        let Some(signed_qty) = self.positions.on_fill(fill) else {
+            self.log.record(StrategyEvent::FillCovered { side, qty, position: self.position });
+            return;
+        };
         if self.position == 0.0 {
-            self.entry_price = px;
             // NEW POSITION: Reset Risk State
             self.highest_pnl_pct = -1.0; 
             self.server_sl_set = false;
@@ -94,10 +377,6 @@ impl MarketMaker {
              let is_long = self.position > 0.0;
              let is_buy = side == "Buy";
              if (is_long && is_buy) || (!is_long && !is_buy) {
-                 let total_val = (self.position.abs() * self.entry_price) + (qty * px);
-                 let new_qty = self.position.abs() + qty;
-                 self.entry_price = total_val / new_qty;
-                 
                  // Adding to position might worsen PnL pct, reset logic slightly? 
                  // Or keep highest_pnl if we are still profitable? 
                  // For safety, let's keep highest_pnl but knowing entry_price changed, calc might change.
@@ -111,20 +390,43 @@ impl MarketMaker {
              // If reducing, entry price stays same, realized PnL happens.
         }
 
-        if side == "Buy" {
-            self.position += qty;
-        } else {
-            self.position -= qty;
-        }
+        // Weighted average entry (a flip re-enters at the fill price): from the tracker
+        self.position = self.positions.size();
+        self.entry_price = self.positions.entry_price();
+        let now = self.clock.monotonic_now();
+        self.inventory.on_fill(signed_qty, now);
+        self.fade.on_fill(side, now);
         
         if self.position.abs() < 0.0001 {
              self.entry_price = 0.0;
              self.highest_pnl_pct = -1.0;
              self.server_sl_set = false;
+             self.exit_stage = None;
+             self.last_cross_ts = None;
+             self.close_deadline = None;
         }
 
-        self.last_trade_ts = Some(Instant::now());
-        println!("STRATEGY: Fill detected! Side: {}, Qty: {}, Px: {}, New Pos: {}, AvgEntry: {}", side, qty, px, self.position, self.entry_price);
+        self.last_trade_ts = Some(now);
+        self.log.record(StrategyEvent::Fill {
+            side, qty, px, position: self.position, entry: self.entry_price,
+            fee: fill.fee, fee_rate: fill.fee_rate, maker: fill.is_maker,
+        });
+    }
+
+    /// Origin of the quote currently resting on `side` (markout tagging of maker fills).
+    pub fn quote_origin(&self, side: &str) -> QuoteOrigin {
+        if side == "Buy" { self.active_buy_origin } else { self.active_sell_origin }
+    }
+
+    /// The book can't be trusted (crossed/corrupt, resync pending): pull whatever is resting.
+    /// Position exits wait for a sane book; pricing them off this one is the risk we avoid.
+    pub fn pull_quotes(&mut self) -> Option<Vec<Action>> {
+        if !self.has_active_buy && !self.has_active_sell {
+            return None;
+        }
+        self.on_order_cancel("Buy");
+        self.on_order_cancel("Sell");
+        Some(vec![Action { action_type: ActionType::CancelAll }])
     }
 
     pub fn on_order_cancel(&mut self, side: &str) {
@@ -137,17 +439,51 @@ impl MarketMaker {
         }
     }
     
+    /// Called when an amend was vetoed by the OrderManager, rejected by the exchange or never
+    /// sent: the order is still resting at `live_price` (the confirmed price).
+    /// `retry` forces a requote on the next tick (the veto is temporary).
+    pub fn on_amend_deferred(&mut self, side: &str, live_price: f64, retry: bool) {
+        if side == "Buy" {
+            self.active_buy_price = live_price;
+        } else if side == "Sell" {
+            self.active_sell_price = live_price;
+        }
+        if retry {
+            self.requote_pending = true;
+        }
+    }
+
+    /// Exchange `position` snapshot as of `seq` / `updated_ms`; skipped (false) when fills newer
+    /// than it are already booked (`PositionTracker::on_snapshot`).
+    pub fn on_position_snapshot(&mut self, size: f64, avg_price: f64, seq: u64, updated_ms: u64) -> bool {
+        if !self.positions.on_snapshot(size, avg_price, seq, updated_ms) {
+            self.log.record(StrategyEvent::StaleSnapshot { position: self.position, snapshot: size });
+            return false;
+        }
+        self.adopt_position();
+        true
+    }
+
+    /// Local override, no ordering (forced flatten after a reject, tests).
     pub fn sync_position(&mut self, user_position: f64, avg_price: f64) {
+        self.positions.set(user_position, avg_price);
+        self.adopt_position();
+    }
+
+    // The tracker's position becomes the strategy's
+    fn adopt_position(&mut self) {
+        let (user_position, avg_price) = (self.positions.size(), self.positions.entry_price());
         // Only update if significantly different to avoid fighting with on_fill
         if (self.position - user_position).abs() > 0.0001 {
-            println!("STRATEGY: Syncing Position State! Old: {}, New: {}", self.position, user_position);
+            self.log.record(StrategyEvent::PositionSync { old: self.position, new: user_position });
             self.position = user_position;
             self.entry_price = avg_price;
+            self.inventory.sync(user_position, self.clock.monotonic_now());
             
             // If we suddenly have a position and didn't before, start the timer?
             // Or if we are just syncing, maybe we shouldn't reset timer if it's already running?
             if self.position.abs() > 0.0001 && self.last_trade_ts.is_none() {
-                self.last_trade_ts = Some(Instant::now());
+                self.last_trade_ts = Some(self.clock.monotonic_now());
             }
             // If position closed externally
             if self.position.abs() < 0.0001 {
@@ -155,26 +491,44 @@ impl MarketMaker {
                 self.entry_price = 0.0;
                 self.highest_pnl_pct = -1.0;
                 self.server_sl_set = false;
+                self.exit_stage = None;
+                self.last_cross_ts = None;
+                self.close_deadline = None;
                 // DO NOT cancel orders here aggressively, on_tick will handle cancellations if needed
             }
         }
     }
 
-    pub fn on_tick(&mut self, book: &L2OrderBook, exch_ts: u64) -> Option<Vec<Action>> {
+    pub fn on_tick(&mut self, book: &L2OrderBook, update: &BookUpdate) -> Option<Vec<Action>> {
         self.tick_counter += 1;
-        // if self.tick_counter % 100 == 0 { println!("DEBUG: on_tick called with TS: {}", exch_ts); }
+        // Every book message counts towards wall persistence, batched or not
+        self.walls.on_book_update(book, self.clock.monotonic_now());
+        // if self.tick_counter % 100 == 0 { println!("DEBUG: on_tick called with TS: {}", update.ts); }
         
         // BATCH DETECTION:
-        // If the exchange timestamp is identical to the last one, it means we are processing 
-        // a "burst" or "sweep" event that came in multiple packets but represents one moment.
-        // We should WAIT until we see the final state (new TS) before reacting.
-        // Identify "0" as no-timestamp passed (e.g. internal calls).
-        if exch_ts > 0 && exch_ts == self.last_exch_ts {
-             println!("STRATEGY: Skipping Batch Update (TS: {})", exch_ts);
+        // Deltas with the same exchange timestamp are one "burst" or "sweep" that came in
+        // multiple packets. The policy (default: coalesce contiguous same-ts updates
+        // arriving within 500us) decides whether this one is worth reacting to.
+        // ts "0" = no-timestamp (e.g. internal calls), always evaluated.
+        if !self.batch.admit(update, self.clock.monotonic_now()) {
+             self.log.record(StrategyEvent::SkippedBatch { exch_ts: update.ts });
              return None;
         }
-        if exch_ts > 0 {
-            self.last_exch_ts = exch_ts;
+
+        // LAST PRICE GUARD: a touch far off the last trade is a glitch until proven otherwise.
+        // Neither quotes nor exits act on it; a halt still flattens (market close, no price).
+        if !self.halted {
+            let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+            let was_blocked = self.last_price.blocked();
+            let now = self.clock.monotonic_now();
+            let deviation = self.last_price.check(bid, ask, now);
+            if deviation.is_some() || was_blocked {
+                let last = self.last_price.last_price(now).unwrap_or(0.0);
+                self.log.record(StrategyEvent::PriceGuard { blocked: deviation.is_some(), bid, ask, last, deviation: deviation.unwrap_or(0.0) });
+            }
+            if deviation.is_some() {
+                return None;
+            }
         }
 
         let mut actions = Vec::new(); // Support multiple actions (Buy + Sell sides)
@@ -201,11 +555,12 @@ impl MarketMaker {
 
              // ----------------- EXIT LOGIC -----------------
 
-             // 1. HARD TAKE PROFIT (+0.5%) - Primary Goal
-             if unrealized_pnl_pct >= 0.005 {
+             // 1. TAKE PROFIT (params.take_profit_pct, earlier if a wall sits in front of it) - Primary Goal
+             let tp_pct = self.take_profit_target(book);
+             if unrealized_pnl_pct >= tp_pct {
                  // Close IMMEDIATELY
                  close_signal = true;
-                 reason = "Hard TP (+0.5%)";
+                 reason = if tp_pct < self.params.take_profit_pct { "Wall TP (early exit)" } else { "Hard TP" };
              }
 
              // 2. SERVER-SIDE BREAKEVEN STOP (+0.05% Trigger)
@@ -213,7 +568,7 @@ impl MarketMaker {
              // We use 0.05% buffer to cover fees (approx 0.02% taker or 0.05%)
              if !self.server_sl_set && unrealized_pnl_pct >= 0.0005 {
                  // Push Action to set SL at Entry Price
-                 println!("STRATEGY: Setting Server-Side Breakeven Stop! PnL: {:.4}%", unrealized_pnl_pct * 100.0);
+                 self.log.record(StrategyEvent::BreakevenStop { pnl: unrealized_pnl_pct });
                  actions.push(Action {
                      action_type: ActionType::SetTradingStop {
                          price: self.entry_price, // Breakeven
@@ -251,19 +606,39 @@ impl MarketMaker {
                   }
              }
 
-             // 4. TIME-BASED EXIT (Panic)
-             // If we are losing money and time is up -> Close.
-             if !close_signal && unrealized_pnl_pct <= 0.0 {
-                 if let Some(ts) = self.last_trade_ts {
-                     if ts.elapsed() > Duration::from_secs(3) {
-                         close_signal = true;
-                         reason = "Time Limit (3s) & Loss";
-                     }
-                 }
+             // 4. EXCHANGE MAINTENANCE: flatten ahead of the window, whatever the PnL
+             if !close_signal && self.halted {
+                 close_signal = true;
+                 reason = "Maintenance Halt";
              }
              
+             // A limit close in progress runs to its deadline even if its trigger went away
+             if !close_signal && self.close_deadline.is_some() {
+                 close_signal = true;
+                 reason = "Limit close pending";
+             }
+
              if close_signal {
-                 println!("STRATEGY: Closing Position! Reason: {} | Pos: {} | Entry: {}", reason, self.position, self.entry_price);
+                 // LIMIT FIRST: rest at the touch (maker fee) before paying the taker fee
+                 if self.close.order_type == CloseOrderType::LimitFirst && !self.halted {
+                     let now = self.clock.monotonic_now();
+                     let deadline = match self.close_deadline {
+                         Some(deadline) => deadline,
+                         None => {
+                             self.log.record(StrategyEvent::ClosePosition { reason, position: self.position, entry: self.entry_price });
+                             *self.close_deadline.insert(now + self.close.limit_timeout)
+                         }
+                     };
+                     if now < deadline {
+                         let touch = if self.position > 0.0 { current_ask } else { current_bid };
+                         if touch > 0.0 {
+                             self.rest_exit(self.grid().price(touch), &mut actions);
+                         }
+                         return if actions.is_empty() { None } else { Some(actions) };
+                     }
+                     reason = "Limit close timeout";
+                 }
+                 self.log.record(StrategyEvent::ClosePosition { reason, position: self.position, entry: self.entry_price });
                  
                  // 1. Cancel Active Orders first to free up margin/inventory
                  // Use CancelAll for safety to ensure NO phantom orders remain
@@ -276,7 +651,7 @@ impl MarketMaker {
                  let close_side = if self.position > 0.0 { "Sell" } else { "Buy" };
                  actions.push(Action {
                      action_type: ActionType::ClosePosition {
-                         qty: self.position.abs(),
+                         qty: self.grid().qty(self.position.abs()),
                          side: close_side,
                      }
                  });
@@ -291,13 +666,25 @@ impl MarketMaker {
                  // self.last_trade_ts = None; // REMOVED to allow retry spam (with reduceOnly)
                  return Some(actions);
              } else {
-                 // HOLDING: Do not quote new orders while holding (for SAFETY)
-                 // But if we are holding and NOT closing (e.g. just gathering profit), we strictly wait.
-                 // Once close signal triggers, we enter the block above.
-                 return None;
+                 // 5. INVENTORY AGING: no new quotes while holding, only the exit order,
+                 // more aggressive as the oldest lot ages (passive -> join -> cross)
+                 return self.aged_exit(book, tp_pct, actions);
              }
         }
         
+        // SAFE MODE: flat and reduce-only (or halted) -> nothing may increase the position.
+        // Pull whatever is still resting and stay out.
+        if self.reduce_only || self.halted {
+            if self.has_active_buy || self.has_active_sell {
+                self.log.record(StrategyEvent::QuotePull { halted: self.halted });
+                actions.push(Action { action_type: ActionType::CancelAll });
+                self.has_active_buy = false;
+                self.has_active_sell = false;
+                return Some(actions);
+            }
+            return None;
+        }
+
         let bybit_bid = book.bids[0];
         let bybit_ask = book.asks[0];
 
@@ -309,7 +696,7 @@ impl MarketMaker {
         let mid_price = (bybit_bid.price + bybit_ask.price) / 2.0;
 
         // --- TICK VELOCITY CALCULATION ---
-        let now = Instant::now();
+        let now = self.clock.monotonic_now();
         let delta_us = now.duration_since(self.last_tick_arrival_ts).as_micros() as f64;
         self.last_tick_arrival_ts = now;
         
@@ -319,42 +706,58 @@ impl MarketMaker {
         // TPS (Ticks Per Second)
         let tps = 1_000_000.0 / self.tick_interval_ema.max(1.0); // Avoid div by zero
         
+        // --- REGIME: volatility, TPS and our fill rate pick a named parameter set ---
+        self.regime.on_mid(mid_price);
+        let (buys, sells) = self.fade.recent(now);
+        let inputs = RegimeInputs { volatility_bps: self.regime.volatility_bps(), tps, toxicity: (buys + sells) as f64 };
+        if let Some((from, to)) = self.regime.update(&inputs, now) {
+            self.log.record(StrategyEvent::Regime { from: from.name(), to: to.name(), volatility_bps: inputs.volatility_bps, tps, toxicity: inputs.toxicity });
+            self.requote_pending = true;
+        }
+        let regime = self.regime.params();
+
         // --- SPREAD CALCULATION ---
-        // 1. TPS Component (Dynamic 0.4% - 1.0%)
-        let min_tps = 20.0;
-        let max_tps = 100.0;
-        let min_spread = 0.004; 
-        let max_spread = 0.010;
-        
-        let spread_ratio = ((tps - min_tps) / (max_tps - min_tps)).clamp(0.0, 1.0);
-        let tps_spread = min_spread + spread_ratio * (max_spread - min_spread);
+        // The regime's spread, inside the [quote] band (floor lowered by a cheaper fee tier)
+        let min_spread = self.params.min_spread - self.spread_floor_discount;
+        let max_spread = self.params.max_spread.max(min_spread);
+        let regime_spread = regime.spread.clamp(min_spread, max_spread);
+
+        // FADE: our own fill rate moves the quote; a mode change requotes at once
+        if let Some(mode) = self.fade.update(now) {
+            self.log.record(StrategyEvent::Fade { mode: mode.name(), buys, sells });
+            self.requote_pending = true;
+        }
+
+        // Final Spread = regime spread, faded by our own fill rate
+        let final_spread = self.fade.spread(regime_spread, min_spread);
 
         // --- REQUOTE TRIGGER LOGIC (STRICT) ---
-        let elapsed = self.last_update_ts.elapsed();
+        let elapsed = now.saturating_duration_since(self.last_update_ts);
         let change_pct = if self.last_update_mid > 0.0 {
             (mid_price - self.last_update_mid).abs() / self.last_update_mid
         } else { 0.0 };
         
-        let heartbeat = elapsed > Duration::from_secs(30);
+        // Edge decay: slow drift / regime change under a resting quote, max-age backstop
+        let refresh = self.requote.due(mid_price, final_spread, now);
+        let edge_decayed = refresh.is_some();
 
-        // Rule 3: Atomic Impulse Threshold
-        // Low Volatility (TPS < 100) -> 0.3%
-        // High Volatility (TPS >= 100) -> 0.5%
-        let impulse_threshold = if tps < 100.0 { 0.003 } else { 0.005 };
+        // Rule 3: Atomic Impulse Threshold, per regime (0.3% calm/normal, 0.5% volatile/extreme)
+        let impulse_threshold = regime.impulse_threshold;
         let is_impulse = change_pct > impulse_threshold;
         
         if is_impulse {
-             println!("DEBUG: IMPULSE DETECTED! Change: {:.4}% > Threshold: {:.4}% (TPS: {:.1})", 
-                 change_pct * 100.0, impulse_threshold * 100.0, tps);
+             self.log.record(StrategyEvent::Impulse { change: change_pct, threshold: impulse_threshold, tps });
         }
         
-        // Rule 1: Normal Buffer 0.1%
-        let is_normal_move = change_pct > 0.001;
+        // Rule 1: Normal Buffer 0.1%, skewed per side by inventory
+        let buy_threshold = self.requote_threshold("Buy", mid_price);
+        let sell_threshold = self.requote_threshold("Sell", mid_price);
+        let is_normal_move = change_pct > buy_threshold.min(sell_threshold);
 
         // RATE LIMIT / INTERVAL
         // Impulse -> 0ms (Instant)
         // Normal Move -> 100ms (Buffered)
-        // Heartbeat -> 0ms (Instant)
+        // Edge decay -> 0ms (Instant)
         let min_interval = if is_impulse {
             Duration::from_millis(0)
         } else {
@@ -362,41 +765,45 @@ impl MarketMaker {
         };
 
         // CHECK CONDITIONS
-        if is_impulse {
-            // PASS: Instant Trigger
+        if is_impulse || self.requote_pending {
+            // PASS: Instant Trigger (or retry of an amend deferred by the OrderManager)
         } else if is_normal_move {
              // Check Rate Limit
              if elapsed < min_interval { return None; }
-        } else if heartbeat {
-             // PASS: Timer
+        } else if edge_decayed {
+             // PASS: Quote edge decayed
         } else {
              // NO MOVEMENT (< 0.1%) -> HOLD
              return None;
         }
 
+        // JITTER: hold the triggered requote for a random 0-2ms (no-op when disabled)
+        if !self.jitter.ready(now) {
+            return None;
+        }
+
         // --- SHOCK SPREAD REMOVED (User Request) ---
         // We rely on "Atomic Impulse" logic to find the safe entry moment (end of batch).
-        // Once found, we quote with standard TPS spread to catch the reversal (sniping).
+        // Once found, we quote with the regime's spread to catch the reversal (sniping).
         // let shock_spread = if is_impulse { ... }; 
-        
-        // Final Spread = TPS Logic Only
-        let final_spread = tps_spread;
 
-        println!("STRATEGY: >>> REQUOTE (Reason: {}) | TPS: {:.1} | Spread: {:.2}% (TPS only) | Change: {:.4}%", 
-            if is_impulse { "IMPULSE" } else if is_normal_move { "Normal >0.1%" } else { "Heartbeat" },
+        self.log.record(StrategyEvent::Requote {
+            reason: if is_impulse { "IMPULSE" } else if is_normal_move { "Normal >0.1%" } else { refresh.map_or("Pending", |r| r.name()) },
             tps,
-            final_spread * 100.0,
-            change_pct * 100.0
-        );
+            spread: final_spread,
+            change: change_pct,
+            regime: self.regime.regime().name(),
+        });
 
-        let mut target_buy_price = (bybit_bid.price * (1.0 - final_spread) * 100.0).round() / 100.0;
-        let mut target_sell_price = (bybit_ask.price * (1.0 + final_spread) * 100.0).round() / 100.0;
+        let mut target_buy_price = self.grid().price(bybit_bid.price * (1.0 - final_spread));
+        let mut target_sell_price = self.grid().price(bybit_ask.price * (1.0 + final_spread));
+        let mut buy_origin = QuoteOrigin::Touch;
+        let mut sell_origin = QuoteOrigin::Touch;
 
         // --- WALL DETECTION (Liquidity Walls) ---
-        // Look for volume > 1000.0 within top 20 levels.
+        // Look for volume > 1000.0 within top 20 levels that has persisted (WallTracker).
         // If found, place order 1 tick in front of it.
-        let wall_threshold = 1000.0; 
-        let tick_size = 0.01;
+        let tick_size = self.tick_size;
 
         // 1. Scan Bids (Support) - Find wall BELOW our target or SLIGHTLY ABOVE (to tighten spread securely)
         // We only care if the wall is somewhat close to spread.
@@ -404,8 +811,8 @@ impl MarketMaker {
             let lvl = book.bids[i];
             if lvl.price == 0.0 { break; }
             
-            // Wall Logic: Huge volume
-            if lvl.qty >= wall_threshold {
+            // Wall Logic: Huge volume that has been there for a while (not a flashed spoof)
+            if self.walls.is_wall(Side::Buy, lvl.price) {
                 // Determine Front-Run Price
                 let front_run = lvl.price + tick_size;
                 
@@ -420,12 +827,12 @@ impl MarketMaker {
                 if book.bids[0].price == lvl.price {
                      // Don't log every tick if it's top of book, reducing spam
                 } else {
-                     println!("STRATEGY: [WALL SCAN] BUY | WallPx: {} | Dist: {:.2}% | Safe: {} | USE: {}", 
-                        lvl.price, dist_pct * 100.0, is_safe_dist, is_useful);
+                     self.log.record(StrategyEvent::WallScan { side: "BUY", wall_px: lvl.price, dist: dist_pct, safe: is_safe_dist, used: is_useful });
                 }
                 
                 if is_useful {
                      target_buy_price = front_run;
+                     buy_origin = QuoteOrigin::WallFrontRun;
                      break; // Found best wall
                 }
             }
@@ -436,7 +843,7 @@ impl MarketMaker {
             let lvl = book.asks[i];
             if lvl.price == 0.0 { break; }
             
-            if lvl.qty >= wall_threshold {
+            if self.walls.is_wall(Side::Sell, lvl.price) {
                 let front_run = lvl.price - tick_size;
                 
                 let dist_pct = (front_run - mid_price).abs() / mid_price;
@@ -449,22 +856,29 @@ impl MarketMaker {
                 if book.asks[0].price == lvl.price {
                     // Reduce spam
                 } else {
-                    println!("STRATEGY: [WALL SCAN] SELL | WallPx: {} | Dist: {:.2}% | Safe: {} | USE: {}", 
-                        lvl.price, dist_pct * 100.0, is_safe_dist, is_useful);
+                    self.log.record(StrategyEvent::WallScan { side: "SELL", wall_px: lvl.price, dist: dist_pct, safe: is_safe_dist, used: is_useful });
                 }
                 
                 if is_useful {
                      target_sell_price = front_run;
+                     sell_origin = QuoteOrigin::WallFrontRun;
                      break;
                 }
             }
         }
         
-        // Size: Fixed 0.3 for test
-        // let raw_qty: f64 = 12.0 / target_buy_price;
-        // let buy_qty = raw_qty.max(1.0).round();
-        let buy_qty = 0.8;
-        // Assuming RIVER tick size allows... RIVER is typical alt.
+        // JITTER: +-1 tick so our prices don't sit at a fixed offset from the touch / wall
+        let (buy_jitter, sell_jitter) = (self.jitter.price_offset(tick_size), self.jitter.price_offset(tick_size));
+        target_buy_price = self.grid().price(target_buy_price + buy_jitter);
+        target_sell_price = self.grid().price(target_sell_price + sell_jitter);
+
+        // Size: params.quote_qty (or quote_notional at mid) times the regime's factor on the lot grid
+        // (a preset-scaled quote_qty may be off it), or a random lot-rounded size inside the jitter band
+        let (step, min) = (self.qty_step, self.min_qty);
+        let clip = self.grid().clip(self.params.clip_qty(mid_price, step, min) * regime.size_factor);
+        let buy_qty = self.fade.size(self.jitter.size(clip, step, min), step, min);
+        let sell_qty = self.fade.size(self.jitter.size(clip, step, min), step, min);
+        let (buy_qty, sell_qty) = (self.warmup.size(buy_qty, step, min, now), self.warmup.size(sell_qty, step, min, now));
         
         // BUY SIDE
         if !self.has_active_buy {
@@ -478,9 +892,11 @@ impl MarketMaker {
             });
             self.has_active_buy = true; 
             self.active_buy_price = target_buy_price;
+            self.active_buy_origin = buy_origin;
         } else {
-             // Only amend if price changed
-             if (self.active_buy_price - target_buy_price).abs() > 0.0001 {
+             // Only amend if price changed (and the move clears this side's inventory-skewed buffer)
+             let buy_due = is_impulse || edge_decayed || self.requote_pending || change_pct > buy_threshold;
+             if buy_due && (self.active_buy_price - target_buy_price).abs() > 0.0001 {
                  actions.push(Action {
                     action_type: ActionType::AmendOrder {
                         price: target_buy_price,
@@ -490,6 +906,7 @@ impl MarketMaker {
                     }
                 });
                 self.active_buy_price = target_buy_price;
+                self.active_buy_origin = buy_origin;
              }
         }
 
@@ -498,44 +915,176 @@ impl MarketMaker {
              actions.push(Action {
                 action_type: ActionType::CreateOrder {
                     price: target_sell_price,
-                    qty: buy_qty, // Sell same size?
+                    qty: sell_qty,
                     side: "Sell",
                     link_id: self.active_sell_link_id.clone(),
                 }
             });
             self.has_active_sell = true;
             self.active_sell_price = target_sell_price;
+            self.active_sell_origin = sell_origin;
         } else {
-             // Only amend if price changed
-             if (self.active_sell_price - target_sell_price).abs() > 0.0001 {
+             // Only amend if price changed (and the move clears this side's inventory-skewed buffer)
+             let sell_due = is_impulse || edge_decayed || self.requote_pending || change_pct > sell_threshold;
+             if sell_due && (self.active_sell_price - target_sell_price).abs() > 0.0001 {
                  actions.push(Action {
                     action_type: ActionType::AmendOrder {
                         price: target_sell_price,
-                        qty: buy_qty, // Sell same size
+                        qty: sell_qty,
                         side: "Sell",
                         link_id: self.active_sell_link_id.clone(),
                     }
                 });
                 self.active_sell_price = target_sell_price;
+                self.active_sell_origin = sell_origin;
              }
         }
 
-        self.last_update_ts = Instant::now();
+        self.last_update_ts = self.clock.monotonic_now();
         self.last_update_mid = mid_price;
+        self.requote.on_quote(mid_price, final_spread, self.last_update_ts);
+        self.requote_pending = false;
         
         if actions.is_empty() { None } else { Some(actions) }
     }
 
+    /// Graded exit for a position no exit rule closed outright (stages: `inventory.rs`).
+    /// Passive/Join keep one PostOnly exit order for the whole position on the reducing
+    /// side; Cross sends a market close for the lots past `cross_after`, the rest keeps resting.
+    fn aged_exit(&mut self, book: &L2OrderBook, tp_pct: f64, mut actions: Vec<Action>) -> Option<Vec<Action>> {
+        let now = self.clock.monotonic_now();
+        let age = self.inventory.oldest_age(now).unwrap_or_default();
+        let stage = self.aging.stage(age);
+        if self.exit_stage != Some(stage) {
+            self.exit_stage = Some(stage);
+            self.log.record(StrategyEvent::ExitStage { stage: stage.name(), age_ms: age.as_millis() as u64, position: self.position });
+        }
+        let long = self.position > 0.0;
+        let exit_side = if long { "Sell" } else { "Buy" };
+
+        if stage == ExitStage::Cross && self.last_cross_ts.is_none_or(|t| now.saturating_duration_since(t) >= self.aging.cross_retry) {
+            let aged = self.grid().qty_down(self.inventory.qty_older_than(self.aging.cross_after, now));
+            let qty = aged.max(self.min_qty).min(self.position.abs());
+            self.log.record(StrategyEvent::ClosePosition { reason: "Aged inventory (cross)", position: self.position, entry: self.entry_price });
+            actions.push(Action { action_type: ActionType::ClosePosition { qty, side: exit_side } });
+            self.last_cross_ts = Some(now);
+        }
+
+        // Our side's touch: best ask for a long, best bid for a short
+        let touch = if long { book.asks[0].price } else { book.bids[0].price };
+        if touch > 0.0 {
+            let price = if stage == ExitStage::Passive && self.entry_price > 0.0 {
+                // Take-profit price, never through the touch (PostOnly)
+                if long {
+                    self.grid().price_up(self.entry_price * (1.0 + tp_pct)).max(touch)
+                } else {
+                    self.grid().price_down(self.entry_price * (1.0 - tp_pct)).min(touch)
+                }
+            } else {
+                touch
+            };
+            self.rest_exit(self.grid().price(price), &mut actions);
+        }
+
+        if actions.is_empty() { None } else { Some(actions) }
+    }
+
+    /// Creates or amends the resting exit order for the whole position (exit-side slot, PostOnly;
+    /// the Hot Thread sends it reduce-only).
+    fn rest_exit(&mut self, price: f64, actions: &mut Vec<Action>) {
+        let long = self.position > 0.0;
+        let exit_side = if long { "Sell" } else { "Buy" };
+        let qty = self.grid().qty(self.position.abs());
+        let (active, active_price, link_id) = if long {
+            (self.has_active_sell, self.active_sell_price, &self.active_sell_link_id)
+        } else {
+            (self.has_active_buy, self.active_buy_price, &self.active_buy_link_id)
+        };
+        if !active || (active_price - price).abs() > 0.0001 {
+            let link_id = link_id.clone();
+            actions.push(Action {
+                action_type: if active {
+                    ActionType::AmendOrder { price, qty, side: exit_side, link_id }
+                } else {
+                    ActionType::CreateOrder { price, qty, side: exit_side, link_id }
+                },
+            });
+            if long {
+                self.has_active_sell = true;
+                self.active_sell_price = price;
+                self.active_sell_origin = QuoteOrigin::Touch;
+            } else {
+                self.has_active_buy = true;
+                self.active_buy_price = price;
+                self.active_buy_origin = QuoteOrigin::Touch;
+            }
+        }
+    }
+
+    /// Requote buffer for one side, as a mid-move fraction.
+    /// Long inventory: Sell (reducing) requotes faster, Buy (adding) slower; short is the mirror.
+    /// Flat: both sides use `params.requote_buffer_pct`. The skew is maxed at INVENTORY_CLIPS
+    /// of this slot's clip at `mid`, so it follows `quote_qty` / `quote_notional`.
+    pub fn requote_threshold(&self, side: &str, mid: f64) -> f64 {
+        let max_inventory = INVENTORY_CLIPS * self.params.clip_qty(mid, self.qty_step, self.min_qty);
+        let inventory = if max_inventory > 0.0 { (self.position / max_inventory).clamp(-1.0, 1.0) } else { 0.0 };
+        let reduces = (side == "Sell" && inventory > 0.0) || (side == "Buy" && inventory < 0.0);
+        let skew = INVENTORY_SKEW * inventory.abs();
+        let buffer = self.params.requote_buffer_pct * self.regime.params().requote_scale;
+        if reduces {
+            buffer * (1.0 - skew)
+        } else {
+            buffer * (1.0 + skew)
+        }
+    }
+
+    /// Take-profit target as a PnL fraction for the open position.
+    /// Scans the side we exit into (asks for a long, bids for a short) for a wall between
+    /// the current price and the base TP. If one sits within TP_WALL_WINDOW_PCT in front
+    /// of the TP, the target moves to one tick ahead of the wall (floored at TP_MIN_PCT).
+    pub fn take_profit_target(&self, book: &L2OrderBook) -> f64 {
+        let take_profit = self.params.take_profit_pct;
+        if self.entry_price <= 0.0 {
+            return take_profit;
+        }
+        let long = self.position > 0.0;
+        let tp_price = if long {
+            self.entry_price * (1.0 + take_profit)
+        } else {
+            self.entry_price * (1.0 - take_profit)
+        };
+        let window = self.entry_price * TP_WALL_WINDOW_PCT;
+
+        let wall = if long {
+            self.walls.find(Side::Sell, &book.asks, |px| px >= tp_price - window && px <= tp_price)
+        } else {
+            self.walls.find(Side::Buy, &book.bids, |px| px <= tp_price + window && px >= tp_price)
+        };
+
+        match wall {
+            Some(wall_px) => {
+                let exit_px = if long { wall_px - self.tick_size } else { wall_px + self.tick_size };
+                let pct = if long {
+                    (exit_px - self.entry_price) / self.entry_price
+                } else {
+                    (self.entry_price - exit_px) / self.entry_price
+                };
+                pct.max(TP_MIN_PCT).min(take_profit)
+            }
+            None => take_profit,
+        }
+    }
+
     pub fn reset_order(&mut self, side: &str) {
-        let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_micros();
+        let ts = self.clock.now_us();
         if side == "Buy" {
             self.has_active_buy = false;
             self.active_buy_price = 0.0;
-            self.active_buy_link_id = format!("b-{}", ts / 1000); // Millis
+            self.active_buy_link_id = format!("{}b-{}", self.link_tag, ts / 1000); // Millis
         } else if side == "Sell" {
             self.has_active_sell = false;
             self.active_sell_price = 0.0;
-            self.active_sell_link_id = format!("s-{}", ts / 1000); // Millis
+            self.active_sell_link_id = format!("{}s-{}", self.link_tag, ts / 1000); // Millis
         }
         // If reset order is called on critical failure, we should probably reset sl flag too if it was related?
         // But reset_order is side-specific. safely ignore.
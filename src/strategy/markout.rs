@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+// Post-fill markouts: how far the mid moved in our favour (bps) some time after a maker
+// fill, bucketed by where the quote price came from. Answers whether stepping in front
+// of a wall (possibly a spoof) earns more than quoting off the touch, or just gets run over.
+pub const MARKOUT_HORIZONS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+];
+const HORIZON_COUNT: usize = MARKOUT_HORIZONS.len();
+const MAX_PENDING: usize = 64;
+
+/// Where the price of a resting quote came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteOrigin {
+    /// Spread off the touch (TPS spread, inventory skew, jitter).
+    Touch,
+    /// One tick in front of a liquidity wall found by the wall scan.
+    WallFrontRun,
+}
+
+const ORIGIN_COUNT: usize = 2;
+
+impl QuoteOrigin {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            QuoteOrigin::Touch => "touch",
+            QuoteOrigin::WallFrontRun => "wall",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingFill {
+    origin: QuoteOrigin,
+    sign: f64, // +1 buy, -1 sell: a rising mid is good for a buy
+    px: f64,
+    ts: Instant,
+    next_horizon: usize,
+}
+
+/// Markout sums for one origin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkoutStats {
+    pub fills: u64,
+    sum_bps: [f64; HORIZON_COUNT],
+    samples: [u64; HORIZON_COUNT],
+}
+
+impl MarkoutStats {
+    /// Average markout in bps at horizon `h` (index into `MARKOUT_HORIZONS`), None before the first sample.
+    pub fn avg_bps(&self, h: usize) -> Option<f64> {
+        if self.samples[h] == 0 {
+            None
+        } else {
+            Some(self.sum_bps[h] / self.samples[h] as f64)
+        }
+    }
+}
+
+/// Tracks maker fills until every horizon has been measured against the book mid.
+/// Fixed slots, no allocation; fills beyond `MAX_PENDING` in flight are counted as dropped.
+pub struct MarkoutTracker {
+    pending: [Option<PendingFill>; MAX_PENDING],
+    in_flight: usize,
+    stats: [MarkoutStats; ORIGIN_COUNT],
+    pub dropped: u64,
+}
+
+impl MarkoutTracker {
+    pub fn new() -> Self {
+        Self {
+            pending: [None; MAX_PENDING],
+            in_flight: 0,
+            stats: [MarkoutStats::default(); ORIGIN_COUNT],
+            dropped: 0,
+        }
+    }
+
+    /// Records a maker fill of a quote with the given origin.
+    pub fn on_fill(&mut self, side: &str, px: f64, origin: QuoteOrigin, now: Instant) {
+        if px <= 0.0 {
+            return;
+        }
+        self.stats[origin.index()].fills += 1;
+        let sign = if side == "Buy" { 1.0 } else { -1.0 };
+        match self.pending.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(PendingFill { origin, sign, px, ts: now, next_horizon: 0 });
+                self.in_flight += 1;
+            }
+            None => self.dropped += 1,
+        }
+    }
+
+    /// Samples every pending fill whose next horizon has elapsed against `mid`.
+    /// Returns true when at least one fill got its last (longest) markout.
+    pub fn on_mid(&mut self, mid: f64, now: Instant) -> bool {
+        if self.in_flight == 0 || mid <= 0.0 {
+            return false;
+        }
+        let mut completed = false;
+        for slot in self.pending.iter_mut() {
+            let Some(fill) = slot else { continue };
+            let age = now.saturating_duration_since(fill.ts);
+            while fill.next_horizon < HORIZON_COUNT && age >= MARKOUT_HORIZONS[fill.next_horizon] {
+                let bps = fill.sign * (mid - fill.px) / fill.px * 10_000.0;
+                let stats = &mut self.stats[fill.origin.index()];
+                stats.sum_bps[fill.next_horizon] += bps;
+                stats.samples[fill.next_horizon] += 1;
+                fill.next_horizon += 1;
+            }
+            if fill.next_horizon == HORIZON_COUNT {
+                *slot = None;
+                self.in_flight -= 1;
+                completed = true;
+            }
+        }
+        completed
+    }
+
+    pub fn stats(&self, origin: QuoteOrigin) -> &MarkoutStats {
+        &self.stats[origin.index()]
+    }
+
+    /// One line per origin: fill count and average markout at each horizon.
+    pub fn report(&self) {
+        for origin in [QuoteOrigin::Touch, QuoteOrigin::WallFrontRun] {
+            let stats = self.stats(origin);
+            let mut line = format!("MARKOUT: {:<5} fills {:>4} |", origin.name(), stats.fills);
+            for (h, horizon) in MARKOUT_HORIZONS.iter().enumerate() {
+                match stats.avg_bps(h) {
+                    Some(bps) => line.push_str(&format!(" {}s {:+.2}bp", horizon.as_secs(), bps)),
+                    None => line.push_str(&format!(" {}s -", horizon.as_secs())),
+                }
+            }
+            println!("{}", line);
+        }
+    }
+}
+
+impl Default for MarkoutTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,48 @@
-// Strategy logic
+//! Strategy logic: market maker, OMS, risk, send gate and the quoting controllers.
+
 pub mod signal;
 pub mod book_manager;
 pub mod market_maker;
 pub mod risk;
+pub mod order_manager;
+pub mod router;
+pub mod symbols;
+pub mod fees;
+pub mod strategy_log;
+pub mod jitter;
+pub mod markout;
+pub mod walls;
+pub mod experiment;
+pub mod fill;
+pub mod inventory;
+pub mod send_gate;
+pub mod margin;
+pub mod fade;
+pub mod warmup;
+pub mod pnl_audit;
+pub mod paper;
+pub mod requote;
+pub mod private_stream;
+pub mod regime;
+pub mod preset;
+pub mod last_price;
+pub mod side_throttle;
+pub mod position;
+pub mod rest_quoting;
+pub mod obligation;
+pub mod shadow;
+
+#[cfg(test)]
+mod order_manager_tests;
+
+#[cfg(test)]
+mod experiment_tests;
+
+#[cfg(test)]
+mod send_gate_tests;
+
+#[cfg(test)]
+mod risk_tests;
+
+#[cfg(test)]
+mod router_tests;
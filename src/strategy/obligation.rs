@@ -0,0 +1,153 @@
+use crate::strategy::order_manager::OrderRecord;
+
+// Quote obligations of an exchange market-making program. Rebate tiers are paid on uptime:
+// the share of time the account shows both a bid and an ask, each at least `min_qty`, no
+// further apart than `max_spread_bps` of the mid. Programs measure it per UTC hour and per
+// UTC day, so the windows here are aligned to wall-clock hours and days, not to our start.
+// Time is credited between samples with the state seen at the earlier one; a gap longer than
+// `MAX_SAMPLE_GAP_MS` (stalled loop, no samples) counts as not quoting.
+const DEFAULT_MAX_SPREAD_BPS: f64 = 20.0;
+const DEFAULT_MIN_UPTIME: f64 = 0.90;
+const HOUR_MS: u64 = 3_600_000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+const MAX_SAMPLE_GAP_MS: u64 = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObligationConfig {
+    pub enabled: bool,
+    /// Widest bid/ask distance that still counts, bps of the mid.
+    pub max_spread_bps: f64,
+    /// Smallest quote size per side that counts (base coin, confirmed qty).
+    pub min_qty: f64,
+    /// Program threshold: a closed window below it alerts.
+    pub min_uptime: f64,
+}
+
+impl Default for ObligationConfig {
+    fn default() -> Self {
+        Self { enabled: false, max_spread_bps: DEFAULT_MAX_SPREAD_BPS, min_qty: 0.0, min_uptime: DEFAULT_MIN_UPTIME }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UptimePeriod {
+    Hour,
+    Day,
+}
+
+impl UptimePeriod {
+    fn len_ms(self) -> u64 {
+        match self {
+            UptimePeriod::Hour => HOUR_MS,
+            UptimePeriod::Day => DAY_MS,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UptimePeriod::Hour => "hour",
+            UptimePeriod::Day => "day",
+        }
+    }
+}
+
+/// One closed window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UptimeReport {
+    pub period: UptimePeriod,
+    /// Window start, wall ms (UTC hour / day boundary).
+    pub start_ms: u64,
+    /// Compliant share of the window, 0..1.
+    pub uptime: f64,
+    /// Below `min_uptime`: the program would not count this window.
+    pub below: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    period: UptimePeriod,
+    start_ms: u64,
+    compliant_ms: u64,
+}
+
+impl Window {
+    fn new(period: UptimePeriod, now_ms: u64) -> Self {
+        Self { period, start_ms: now_ms - now_ms % period.len_ms(), compliant_ms: 0 }
+    }
+
+    fn end_ms(&self) -> u64 {
+        self.start_ms + self.period.len_ms()
+    }
+}
+
+/// Per-symbol uptime (`SymbolSlot::obligation`), sampled by the Hot Thread every loop.
+#[derive(Debug)]
+pub struct QuoteUptime {
+    pub config: ObligationConfig,
+    // Hour, day
+    windows: [Option<Window>; 2],
+    // Last sample: wall ms and whether it was compliant
+    last: Option<(u64, bool)>,
+    // Windows closed by the last sample, not yet taken
+    closed: [Option<UptimeReport>; 2],
+}
+
+impl QuoteUptime {
+    pub fn new(config: ObligationConfig) -> Self {
+        Self { config, windows: [None; 2], last: None, closed: [None; 2] }
+    }
+
+    /// Resting `bid` and `ask` (confirmed price/qty) meet the obligation around `mid`.
+    pub fn compliant(&self, bid: &OrderRecord, ask: &OrderRecord, mid: f64) -> bool {
+        bid.live && ask.live && mid > 0.0
+            && bid.qty >= self.config.min_qty && ask.qty >= self.config.min_qty
+            && (ask.price - bid.price) / mid * 10_000.0 <= self.config.max_spread_bps
+    }
+
+    /// State at `now_ms`; credits the time since the previous sample and closes the windows
+    /// that ended meanwhile (`take_report`).
+    pub fn on_sample(&mut self, compliant: bool, now_ms: u64) {
+        if !self.config.enabled {
+            return;
+        }
+        let (from, was_compliant) = self.last.unwrap_or((now_ms, compliant));
+        let credited = was_compliant && now_ms.saturating_sub(from) <= MAX_SAMPLE_GAP_MS;
+        for (i, period) in [UptimePeriod::Hour, UptimePeriod::Day].into_iter().enumerate() {
+            let mut window = self.windows[i].unwrap_or_else(|| Window::new(period, from));
+            let mut at = from;
+            while now_ms >= window.end_ms() {
+                if credited {
+                    window.compliant_ms += window.end_ms().saturating_sub(at.max(window.start_ms));
+                }
+                at = window.end_ms();
+                let uptime = window.compliant_ms as f64 / period.len_ms() as f64;
+                self.closed[i] = Some(UptimeReport { period, start_ms: window.start_ms, uptime, below: uptime < self.config.min_uptime });
+                window = Window::new(period, at);
+            }
+            if credited {
+                window.compliant_ms += now_ms.saturating_sub(at.max(window.start_ms));
+            }
+            self.windows[i] = Some(window);
+        }
+        self.last = Some((now_ms, compliant));
+    }
+
+    /// A window closed since the last call (hour first), once each.
+    pub fn take_report(&mut self) -> Option<UptimeReport> {
+        self.closed.iter_mut().find_map(Option::take)
+    }
+
+    /// Uptime of the current window so far (share of the time elapsed in it).
+    pub fn running(&self, period: UptimePeriod) -> Option<f64> {
+        let window = self.windows[period as usize]?;
+        let (now_ms, _) = self.last?;
+        let elapsed = now_ms.saturating_sub(window.start_ms);
+        (elapsed > 0).then(|| window.compliant_ms as f64 / elapsed as f64)
+    }
+}
+
+impl Default for QuoteUptime {
+    fn default() -> Self {
+        Self::new(ObligationConfig::default())
+    }
+}
@@ -0,0 +1,743 @@
+use std::time::{Duration, Instant};
+use crate::core::clock::{Clock, LiveClock};
+use crate::core::orderbook::{L2OrderBook, Side};
+use crate::strategy::market_maker::{Action, ActionType, HEARTBEAT_INTERVAL};
+use crate::net::rest_client::OpenOrders;
+use crate::core::logging::info;
+
+// Minimum time a quote must rest before we touch it again.
+// Bybit resets queue priority on every price amend, so re-pricing a quote
+// that was placed a few ms ago burns a message AND throws away the queue slot.
+const DEFAULT_MIN_QUOTE_LIFETIME_MS: u64 = 250;
+
+// Fill-probability amend policy (see `amend_decision`).
+const DEFAULT_FRONT_QUEUE_FRACTION: f64 = 0.2;  // <= 20% of the level ahead of us = "near the front"
+const DEFAULT_SMALL_MOVE_PCT: f64 = 0.002;      // 0.2% reprice is "small" for a front-of-queue order
+const DEFAULT_BACK_QUEUE_FRACTION: f64 = 0.7;   // >= 70% of the level ahead of us = "back of queue"
+const DEFAULT_STALE_QUOTE_AGE_MS: u64 = 5_000;
+
+// Fat-finger guard: quotes this far from the reference (mark) price never leave the box.
+const DEFAULT_MAX_PRICE_DEVIATION: f64 = 0.03;
+const DEFAULT_REFERENCE_MAX_AGE_MS: u64 = 5_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct OrderManagerConfig {
+    /// Quotes younger than this are not amended/cancelled (risk pulls are exempt).
+    pub min_quote_lifetime: Duration,
+    /// Queue fraction ahead of us below which the order counts as front-of-queue.
+    pub front_queue_fraction: f64,
+    /// Reprice (relative to live price) that is not worth losing a front-of-queue slot for.
+    pub small_move_pct: f64,
+    /// Queue fraction ahead of us above which the order counts as back-of-queue.
+    pub back_queue_fraction: f64,
+    /// Back-of-queue orders older than this are refreshed even without a requote trigger.
+    pub stale_quote_age: Duration,
+    /// How often strategy flags are checked against our records (the strategy heartbeat).
+    pub reconcile_interval: Duration,
+    /// Max |price / reference - 1| for a create/amend (0 disables the band).
+    pub max_price_deviation: f64,
+    /// A reference older than this is not trusted and the band is not applied.
+    pub reference_max_age: Duration,
+}
+
+impl Default for OrderManagerConfig {
+    fn default() -> Self {
+        Self {
+            min_quote_lifetime: Duration::from_millis(DEFAULT_MIN_QUOTE_LIFETIME_MS),
+            front_queue_fraction: DEFAULT_FRONT_QUEUE_FRACTION,
+            small_move_pct: DEFAULT_SMALL_MOVE_PCT,
+            back_queue_fraction: DEFAULT_BACK_QUEUE_FRACTION,
+            stale_quote_age: Duration::from_millis(DEFAULT_STALE_QUOTE_AGE_MS),
+            reconcile_interval: HEARTBEAT_INTERVAL,
+            max_price_deviation: DEFAULT_MAX_PRICE_DEVIATION,
+            reference_max_age: Duration::from_millis(DEFAULT_REFERENCE_MAX_AGE_MS),
+        }
+    }
+}
+
+/// Reason an outgoing action was held back by the OrderManager.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Veto {
+    QuoteTooYoung { side: &'static str, age_ms: u64 },
+    /// Order is near the front of the queue and the reprice is small: keep the slot.
+    HoldQueuePriority { side: &'static str, queue_fraction: f64 },
+    /// Safe mode is on and this quote would open or grow the position.
+    ReduceOnly { side: &'static str },
+    /// The quote would trade against our own resting order on `resting`.
+    SelfMatch { side: &'static str, resting: &'static str },
+    /// Quote price is outside the band around the reference price (math bug / corrupted book).
+    PriceBand { side: &'static str, price: f64, reference: f64 },
+}
+
+impl Veto {
+    /// Whether the strategy should retry the same action on the next tick.
+    /// A young quote becomes amendable by itself; a held queue slot does not.
+    pub fn retry(&self) -> bool {
+        matches!(self, Veto::QuoteTooYoung { .. })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmendDecision {
+    Amend,
+    Hold,
+}
+
+/// Maps an orderLinkId / reqId back to the quote side it belongs to.
+/// Quote link ids are "b-<ms>" / "s-<ms>"; reqIds embed them ("amend-b-...").
+pub fn side_from_link(id: &str) -> Option<&'static str> {
+    if id.contains("bot-buy") || id.contains("-b-") || id.starts_with("b-") {
+        Some("Buy")
+    } else if id.contains("bot-sell") || id.contains("-s-") || id.starts_with("s-") {
+        Some("Sell")
+    } else {
+        None
+    }
+}
+
+/// Prefix that ties link ids and reqIds to a symbol slot of the `BookManager`: none for the
+/// primary (slot 0, ids unchanged), "x<slot>-" otherwise ("x2-b-<ms>", "close-x2-Buy-<ts>").
+pub fn slot_tag(slot: usize) -> String {
+    if slot == 0 { String::new() } else { format!("x{}-", slot) }
+}
+
+/// Symbol slot an orderLinkId / reqId was tagged with (`slot_tag`); 0 when untagged.
+pub fn slot_from_link(id: &str) -> usize {
+    id.split('-').find_map(|part| part.strip_prefix('x').and_then(|n| n.parse().ok())).unwrap_or(0)
+}
+
+/// Quote side an action targets (None for CancelAll/Close/SL, which aren't per-quote).
+pub fn action_side(action: &ActionType) -> Option<&'static str> {
+    match action {
+        ActionType::CreateOrder { side, .. }
+        | ActionType::AmendOrder { side, .. }
+        | ActionType::AmendPrice { side, .. }
+        | ActionType::AmendQty { side, .. } => Some(*side),
+        ActionType::CancelOrder { link_id } => side_from_link(link_id),
+        _ => None,
+    }
+}
+
+/// Send order for a burst of actions (and for frames parked in the Trade pipeline):
+/// whatever takes risk off goes first, new exposure last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EgressPriority {
+    /// Cancels, CancelAll, closes, the server-side stop, quotes on the reducing side.
+    Reduce = 0,
+    /// Moves a resting quote; exposure stays roughly the same.
+    Amend = 1,
+    /// New quote that opens or grows the position.
+    Quote = 2,
+}
+
+fn side_index(side: &str) -> usize {
+    if side == "Buy" { 0 } else { 1 }
+}
+
+/// Side and new price of a create or a price-changing amend.
+fn priced_quote(action: &ActionType) -> Option<(&'static str, f64)> {
+    match action {
+        ActionType::CreateOrder { side, price, .. }
+        | ActionType::AmendOrder { side, price, .. }
+        | ActionType::AmendPrice { side, price, .. } => Some((*side, *price)),
+        _ => None,
+    }
+}
+
+/// Side of our resting quote (`resting` = [buy, sell] as (live, price)) that `action`
+/// would trade against. A market close always takes from the other side of the book;
+/// a limit quote only when its price reaches our opposite quote.
+fn crossed_quote(action: &ActionType, resting: &[(bool, f64); 2]) -> Option<&'static str> {
+    let (side, price) = match action {
+        ActionType::ClosePosition { side, .. } => (*side, None),
+        _ => match priced_quote(action) {
+            Some((side, price)) => (side, Some(price)),
+            None => return None,
+        },
+    };
+    let other = if side == "Buy" { "Sell" } else { "Buy" };
+    let (live, other_px) = resting[side_index(other)];
+    let crosses = match price {
+        _ if !live => false,
+        None => true,
+        Some(px) if side == "Buy" => px >= other_px,
+        Some(px) => px <= other_px,
+    };
+    if crosses { Some(other) } else { None }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceStage {
+    Serialized,
+    Sent,
+    Wire,
+    Acked,
+    Filled,
+}
+
+/// Per-order pipeline timestamps, keyed by the trace id assigned when the
+/// triggering tick was ingested. Lets one slow/rejected order be reconstructed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderTrace {
+    pub trace_id: u64,
+    pub tick_ts: Option<Instant>,       // market data read from socket
+    pub decided_ts: Option<Instant>,    // strategy returned the action
+    pub serialized_ts: Option<Instant>, // request JSON + WS frame built
+    pub sent_ts: Option<Instant>,       // frame handed to TLS
+    pub wire_ts: Option<Instant>,       // ciphertext fully written to the socket
+    pub ack_ts: Option<Instant>,        // exchange response with our reqId
+    pub fill_ts: Option<Instant>,       // execution report
+}
+
+fn micros_between(from: Option<Instant>, to: Option<Instant>) -> i64 {
+    match (from, to) {
+        (Some(a), Some(b)) => b.saturating_duration_since(a).as_micros() as i64,
+        _ => -1,
+    }
+}
+
+impl OrderTrace {
+    /// One-line stage breakdown in microseconds (-1 = stage not reached).
+    pub fn summary(&self, side: &str) {
+        info!(orders, "TRACE #{} {}: tick->decide {}us | decide->ser {}us | ser->send {}us | send->wire {}us | send->ack {}us | send->fill {}us",
+            self.trace_id, side,
+            micros_between(self.tick_ts, self.decided_ts),
+            micros_between(self.decided_ts, self.serialized_ts),
+            micros_between(self.serialized_ts, self.sent_ts),
+            micros_between(self.sent_ts, self.wire_ts),
+            micros_between(self.sent_ts, self.ack_ts),
+            micros_between(self.sent_ts, self.fill_ts));
+    }
+}
+
+/// Amend sent, exchange response outstanding.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingAmend {
+    pub price: f64,
+    pub qty: f64,
+}
+
+/// Exchange-side view of one of our resting quotes.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderRecord {
+    pub live: bool,
+    /// Confirmed price/qty: what the exchange has acknowledged. Risk checks and queue
+    /// estimates use these, never the in-flight amend.
+    pub price: f64,
+    pub qty: f64,
+    /// Latest amend on the wire; promoted to `price`/`qty` by its ack, dropped by a reject.
+    pub pending: Option<PendingAmend>,
+    /// Time of creation or last acked price amend (when our queue position was (re)assigned).
+    pub placed_ts: Instant,
+    /// Estimated resting qty ahead of us at our price. NaN until the first book update
+    /// after (re)placement, when we pessimistically assume we joined at the back.
+    pub queue_ahead: f64,
+    /// Resting qty at our price level on the last book update.
+    pub level_qty: f64,
+    /// Stale-refresh already requested for the current placement.
+    refresh_requested: bool,
+    /// A self-match cancel was queued for this order; it bypasses the lifetime gate.
+    stp_cancel: bool,
+    /// Pipeline trace of the last action sent for this quote.
+    pub trace: OrderTrace,
+}
+
+impl OrderRecord {
+    fn empty(now: Instant) -> Self {
+        Self {
+            live: false,
+            price: 0.0,
+            qty: 0.0,
+            pending: None,
+            placed_ts: now,
+            queue_ahead: f64::NAN,
+            level_qty: 0.0,
+            refresh_requested: false,
+            stp_cancel: false,
+            trace: OrderTrace::default(),
+        }
+    }
+
+    fn placed(price: f64, qty: f64, now: Instant, trace: OrderTrace) -> Self {
+        Self { live: true, price, qty, placed_ts: now, trace, ..Self::empty(now) }
+    }
+
+    /// Share of the level resting ahead of us: 0.0 = front, 1.0 = back.
+    /// Unknown queue position is treated as back-of-queue.
+    pub fn queue_fraction(&self) -> f64 {
+        if self.queue_ahead.is_nan() || self.level_qty <= 0.0 {
+            return 1.0;
+        }
+        (self.queue_ahead / self.level_qty).clamp(0.0, 1.0)
+    }
+}
+
+/// Order Management: sits between the strategy and the serializer.
+/// The strategy decides WHAT it wants; the OrderManager decides whether the
+/// exchange is allowed to see it right now. Single-threaded, owned by the Hot Thread.
+pub struct OrderManager<C: Clock = LiveClock> {
+    config: OrderManagerConfig,
+    pub buy: OrderRecord,
+    pub sell: OrderRecord,
+    // Safe mode: only position-reducing quotes pass. `position` is the strategy's view,
+    // refreshed by the Hot Thread before each batch of actions.
+    reduce_only: bool,
+    position: f64,
+    // Invariant check: strategy "active" flags vs our records, repaired from a REST snapshot.
+    last_reconcile_ts: Instant,
+    reconcile_in_flight: bool,
+    // Independent price for the fat-finger band (Bybit mark price), with its arrival time.
+    reference_px: f64,
+    reference_ts: Instant,
+    clock: C,
+}
+
+impl OrderManager {
+    pub fn new(config: OrderManagerConfig) -> Self {
+        Self::with_clock(config, LiveClock)
+    }
+}
+
+impl<C: Clock> OrderManager<C> {
+    pub fn with_clock(config: OrderManagerConfig, clock: C) -> Self {
+        let now = clock.monotonic_now();
+        Self {
+            config,
+            buy: OrderRecord::empty(now),
+            sell: OrderRecord::empty(now),
+            reduce_only: false,
+            position: 0.0,
+            last_reconcile_ts: now,
+            reconcile_in_flight: false,
+            reference_px: 0.0,
+            reference_ts: now,
+            clock,
+        }
+    }
+
+    /// The clock callers should read `now` from when driving this manager.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn reduce_only(&self) -> bool {
+        self.reduce_only
+    }
+
+    pub fn set_reduce_only(&mut self, on: bool) {
+        if on != self.reduce_only {
+            info!(orders, "OMS: Reduce-only mode {}", if on { "ON" } else { "OFF" });
+        }
+        self.reduce_only = on;
+    }
+
+    pub fn set_position(&mut self, position: f64) {
+        self.position = position;
+    }
+
+    /// Reference price for the price band. Must not come from the book we quote off
+    /// (mark price, or another venue's mid), or a corrupted book passes its own check.
+    pub fn set_reference_price(&mut self, price: f64, now: Instant) {
+        if price > 0.0 {
+            self.reference_px = price;
+            self.reference_ts = now;
+        }
+    }
+
+    /// Price band check: Err with the reference when `price` is too far from a fresh reference.
+    fn price_band(&self, price: f64, now: Instant) -> Result<(), f64> {
+        let fresh = now.saturating_duration_since(self.reference_ts) <= self.config.reference_max_age;
+        if self.config.max_price_deviation <= 0.0 || self.reference_px <= 0.0 || !fresh {
+            return Ok(());
+        }
+        if !price.is_finite() || (price / self.reference_px - 1.0).abs() > self.config.max_price_deviation {
+            return Err(self.reference_px);
+        }
+        Ok(())
+    }
+
+    /// A Buy reduces a short, a Sell reduces a long. Flat: nothing reduces.
+    pub fn reduces_position(&self, side: &str) -> bool {
+        (side == "Buy" && self.position < 0.0) || (side == "Sell" && self.position > 0.0)
+    }
+
+    pub fn egress_priority(&self, action: &ActionType) -> EgressPriority {
+        match action {
+            ActionType::CreateOrder { side, .. } if self.reduces_position(side) => EgressPriority::Reduce,
+            ActionType::CreateOrder { .. } => EgressPriority::Quote,
+            ActionType::AmendOrder { .. } | ActionType::AmendPrice { .. } | ActionType::AmendQty { .. } => EgressPriority::Amend,
+            ActionType::CancelOrder { .. }
+            | ActionType::CancelAll
+            | ActionType::ClosePosition { .. }
+            | ActionType::SetTradingStop { .. }
+            | ActionType::None => EgressPriority::Reduce,
+        }
+    }
+
+    pub fn record(&self, side: &str) -> &OrderRecord {
+        if side == "Buy" { &self.buy } else { &self.sell }
+    }
+
+    fn record_mut(&mut self, side: &str) -> &mut OrderRecord {
+        if side == "Buy" { &mut self.buy } else { &mut self.sell }
+    }
+
+    fn resting(&self) -> [(bool, f64); 2] {
+        [(self.buy.live, self.buy.price), (self.sell.live, self.sell.price)]
+    }
+
+    /// Gate applied to every action before it is serialized.
+    /// CancelAll / ClosePosition / SetTradingStop are risk pulls and always pass.
+    pub fn check(&self, action: &ActionType, now: Instant) -> Result<(), Veto> {
+        if let Some((side, price)) = priced_quote(action) {
+            if let Err(reference) = self.price_band(price, now) {
+                return Err(Veto::PriceBand { side, price, reference });
+            }
+        }
+        if let ActionType::CreateOrder { side, .. }
+        | ActionType::AmendOrder { side, .. }
+        | ActionType::AmendPrice { side, .. }
+        | ActionType::AmendQty { side, .. } = action {
+            if self.reduce_only && !self.reduces_position(side) {
+                return Err(Veto::ReduceOnly { side });
+            }
+            // Last line of defence: `prevent_self_match` should have pulled the resting order already.
+            if let Some(resting) = crossed_quote(action, &self.resting()) {
+                return Err(Veto::SelfMatch { side, resting });
+            }
+        }
+
+        let side = match action {
+            ActionType::AmendOrder { .. }
+            | ActionType::AmendPrice { .. }
+            | ActionType::AmendQty { .. }
+            | ActionType::CancelOrder { .. } => match action_side(action) {
+                Some(side) => side,
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+
+        let rec = self.record(side);
+        if !rec.live || (rec.stp_cancel && matches!(action, ActionType::CancelOrder { .. })) {
+            return Ok(());
+        }
+
+        let age = now.saturating_duration_since(rec.placed_ts);
+        if age < self.config.min_quote_lifetime {
+            return Err(Veto::QuoteTooYoung { side, age_ms: age.as_millis() as u64 });
+        }
+
+        // Qty-only amends keep queue priority: nothing to hold
+        if let ActionType::AmendOrder { price, .. } | ActionType::AmendPrice { price, .. } = action {
+            if self.amend_decision(side, *price, now) == AmendDecision::Hold {
+                return Err(Veto::HoldQueuePriority { side, queue_fraction: rec.queue_fraction() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Self-trade prevention for one batch of strategy actions. Wherever an action
+    /// would cross our own resting order on the other side (a quote priced through it,
+    /// or a market close while it still rests), a cancel of that order is inserted in
+    /// front of it. Orders created or pulled earlier in the batch are taken into account.
+    /// Returns the sides cancelled here so the caller can reset the strategy's quotes.
+    pub fn prevent_self_match(&mut self, actions: &mut Vec<Action>, link_ids: [&str; 2]) -> [Option<&'static str>; 2] {
+        let mut resting = self.resting();
+        let mut cancelled = [None, None];
+        let mut i = 0;
+        while i < actions.len() {
+            if let Some(other) = crossed_quote(&actions[i].action_type, &resting) {
+                let idx = side_index(other);
+                info!(orders, "OMS: Self-match guard -> cancelling resting {} @ {} before {:?}", other, resting[idx].1, actions[i].action_type);
+                actions.insert(i, Action { action_type: ActionType::CancelOrder { link_id: link_ids[idx].to_string() } });
+                self.record_mut(other).stp_cancel = true;
+                cancelled[idx] = Some(other);
+                resting[idx].0 = false;
+                i += 1;
+            }
+            match &actions[i].action_type {
+                ActionType::CreateOrder { side, price, .. }
+                | ActionType::AmendOrder { side, price, .. }
+                | ActionType::AmendPrice { side, price, .. } => {
+                    resting[side_index(side)] = (true, *price);
+                }
+                ActionType::CancelOrder { link_id } => {
+                    if let Some(side) = side_from_link(link_id) {
+                        resting[side_index(side)].0 = false;
+                    }
+                }
+                ActionType::CancelAll | ActionType::ClosePosition { .. } => {
+                    resting[0].0 = false;
+                    resting[1].0 = false;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        cancelled
+    }
+
+    /// Fill-probability-aware amend policy.
+    /// * Front of queue + small reprice + younger than `stale_quote_age` -> Hold (the fill
+    ///   chance we'd give up is worth more than the tick).
+    /// * Everything else -> Amend. A front slot that has not filled within `stale_quote_age`
+    ///   is not worth keeping the old price for. Stale back-of-queue orders are additionally
+    ///   pushed to requote by `refresh_due` even when the strategy sees no trigger.
+    pub fn amend_decision(&self, side: &str, new_price: f64, now: Instant) -> AmendDecision {
+        let rec = self.record(side);
+        if !rec.live || rec.price <= 0.0 {
+            return AmendDecision::Amend;
+        }
+
+        let fraction = rec.queue_fraction();
+        let move_pct = (new_price - rec.price).abs() / rec.price;
+        let age = now.saturating_duration_since(rec.placed_ts);
+        if fraction <= self.config.front_queue_fraction && move_pct < self.config.small_move_pct && age < self.config.stale_quote_age {
+            return AmendDecision::Hold;
+        }
+        AmendDecision::Amend
+    }
+
+    /// Updates queue-position estimates from the latest book.
+    /// Anything that leaves the level is assumed to have been ahead of us
+    /// (trades consume the front), so `queue_ahead` only ever shrinks.
+    pub fn on_book_update(&mut self, book: &L2OrderBook) {
+        for (side, rec) in [(Side::Buy, &mut self.buy), (Side::Sell, &mut self.sell)] {
+            if !rec.live {
+                continue;
+            }
+            let level_qty = book.qty_at(side, rec.price);
+            let others = (level_qty - rec.qty).max(0.0);
+            rec.queue_ahead = if rec.queue_ahead.is_nan() { others } else { rec.queue_ahead.min(others) };
+            rec.level_qty = level_qty;
+        }
+    }
+
+    /// Returns true (once per placement) when a live quote has sat at the back of
+    /// the queue for longer than `stale_quote_age`; the caller forces a requote.
+    pub fn refresh_due(&mut self, now: Instant) -> bool {
+        let mut due = false;
+        for rec in [&mut self.buy, &mut self.sell] {
+            if !rec.live || rec.refresh_requested {
+                continue;
+            }
+            let stale = now.saturating_duration_since(rec.placed_ts) >= self.config.stale_quote_age;
+            if stale && rec.queue_fraction() >= self.config.back_queue_fraction {
+                rec.refresh_requested = true;
+                due = true;
+            }
+        }
+        due
+    }
+
+    /// Records an action that passed `check` and is about to hit the wire.
+    pub fn on_sent(&mut self, action: &ActionType, now: Instant) {
+        match action {
+            ActionType::CreateOrder { price, qty, side, .. } => {
+                let trace = self.record(side).trace;
+                *self.record_mut(side) = OrderRecord::placed(*price, *qty, now, trace);
+            }
+            ActionType::AmendOrder { price, qty, side, .. } => {
+                // Not live until acked: the confirmed price stays in place meanwhile.
+                // A newer amend replaces an older one in flight (the exchange applies them in order).
+                self.record_mut(side).pending = Some(PendingAmend { price: *price, qty: *qty });
+            }
+            ActionType::AmendPrice { price, side, .. } => {
+                let (_, qty) = self.latest(side);
+                self.record_mut(side).pending = Some(PendingAmend { price: *price, qty });
+            }
+            ActionType::AmendQty { qty, side, .. } => {
+                let (price, _) = self.latest(side);
+                self.record_mut(side).pending = Some(PendingAmend { price, qty: *qty });
+            }
+            ActionType::CancelOrder { link_id } => {
+                if let Some(side) = side_from_link(link_id) {
+                    self.on_order_closed(side);
+                }
+            }
+            ActionType::CancelAll | ActionType::ClosePosition { .. } => {
+                self.on_order_closed("Buy");
+                self.on_order_closed("Sell");
+            }
+            _ => {}
+        }
+    }
+
+    /// Price and qty the order will have once everything sent is applied (in-flight amend, else confirmed).
+    pub fn latest(&self, side: &str) -> (f64, f64) {
+        let rec = self.record(side);
+        rec.pending.map_or((rec.price, rec.qty), |p| (p.price, p.qty))
+    }
+
+    /// Rewrites a full amend of a live order to only the fields that change: `AmendPrice`
+    /// or `AmendQty`. Returns false when neither changes (nothing to send).
+    pub fn narrow_amend(&self, action: &mut ActionType) -> bool {
+        let ActionType::AmendOrder { price, qty, side, link_id } = action else { return true };
+        let side = *side;
+        if !self.record(side).live {
+            return true;
+        }
+        let (latest_price, latest_qty) = self.latest(side);
+        let price_changed = (*price - latest_price).abs() > f64::EPSILON;
+        let qty_changed = (*qty - latest_qty).abs() > f64::EPSILON;
+        let narrowed = match (price_changed, qty_changed) {
+            (true, true) => return true,
+            (false, false) => return false,
+            (true, false) => ActionType::AmendPrice { price: *price, side, link_id: std::mem::take(link_id) },
+            (false, true) => ActionType::AmendQty { qty: *qty, side, link_id: std::mem::take(link_id) },
+        };
+        *action = narrowed;
+        true
+    }
+
+    /// Exchange accepted our amend: the pending price becomes the confirmed one.
+    pub fn on_amend_acked(&mut self, side: &str, now: Instant) {
+        if side != "Buy" && side != "Sell" {
+            return;
+        }
+        let rec = self.record_mut(side);
+        let Some(amend) = rec.pending.take() else { return };
+        if !rec.live {
+            return;
+        }
+        // Qty-only amends keep queue priority, price changes do not.
+        if (rec.price - amend.price).abs() > f64::EPSILON {
+            rec.placed_ts = now;
+            rec.queue_ahead = f64::NAN;
+            rec.refresh_requested = false;
+        }
+        rec.price = amend.price;
+        rec.qty = amend.qty;
+    }
+
+    /// Amend rejected or never sent: drops the pending value and returns the confirmed
+    /// price the order still rests at (the strategy rolls back to it).
+    pub fn on_amend_rejected(&mut self, side: &str) -> f64 {
+        if side != "Buy" && side != "Sell" {
+            return 0.0;
+        }
+        let rec = self.record_mut(side);
+        rec.pending = None;
+        rec.price
+    }
+
+    /// Starts a new trace for the quote this action targets (no-op for CancelAll/Close/SL).
+    /// Must be called before `on_sent`, which carries the trace into a new OrderRecord.
+    pub fn begin_trace(&mut self, action: &ActionType, trace_id: u64, tick_ts: Instant, decided_ts: Instant) {
+        if let Some(side) = action_side(action) {
+            self.record_mut(side).trace = OrderTrace {
+                trace_id,
+                tick_ts: Some(tick_ts),
+                decided_ts: Some(decided_ts),
+                ..OrderTrace::default()
+            };
+        }
+    }
+
+    /// Stamps a pipeline stage. Ack and fill close out a leg of the trace, so they print it.
+    pub fn mark(&mut self, side: &str, stage: TraceStage, now: Instant) {
+        if side != "Buy" && side != "Sell" {
+            return;
+        }
+        let trace = &mut self.record_mut(side).trace;
+        match stage {
+            TraceStage::Serialized => trace.serialized_ts = Some(now),
+            TraceStage::Sent => trace.sent_ts = Some(now),
+            TraceStage::Wire => trace.wire_ts = Some(now),
+            TraceStage::Acked => trace.ack_ts = Some(now),
+            TraceStage::Filled => trace.fill_ts = Some(now),
+        }
+        if matches!(stage, TraceStage::Acked | TraceStage::Filled) {
+            trace.summary(side);
+        }
+    }
+
+    /// Egress stamp from the Trade WS connection: every sent quote still waiting for its
+    /// bytes to reach the socket gets `Wire` at `flushed_at`.
+    pub fn mark_egress(&mut self, flushed_at: Instant) {
+        for side in ["Buy", "Sell"] {
+            let trace = self.record(side).trace;
+            if trace.sent_ts.is_some() && trace.wire_ts.is_none() {
+                self.mark(side, TraceStage::Wire, flushed_at);
+            }
+        }
+    }
+
+    /// Heartbeat invariant check. Returns true (caller queries open orders over REST)
+    /// when the strategy thinks a quote is resting but we have no live order for it —
+    /// instead of waiting for the next amend to bounce with 110001.
+    pub fn reconcile_due(&mut self, strategy_buy: bool, strategy_sell: bool, now: Instant) -> bool {
+        if self.reconcile_in_flight
+            || now.saturating_duration_since(self.last_reconcile_ts) < self.config.reconcile_interval {
+            return false;
+        }
+        self.last_reconcile_ts = now;
+
+        let buy_diverged = strategy_buy && !self.buy.live;
+        let sell_diverged = strategy_sell && !self.sell.live;
+        if buy_diverged || sell_diverged {
+            info!(orders, "OMS: Order state divergence (Buy: {}, Sell: {}). Querying open orders.", buy_diverged, sell_diverged);
+            self.reconcile_in_flight = true;
+        }
+        self.reconcile_in_flight
+    }
+
+    /// Applies the exchange snapshot to diverged sides only (strategy active, no live record).
+    /// Orders that exist are re-adopted (queue position unknown, like a fresh placement);
+    /// where the exchange has nothing, returns the side so the caller resets the strategy quote.
+    pub fn on_open_orders(&mut self, orders: &OpenOrders, strategy_buy: bool, strategy_sell: bool, now: Instant) -> [Option<&'static str>; 2] {
+        self.reconcile_in_flight = false;
+        let mut stale = [None, None];
+        for (i, (side, open, active)) in [("Buy", orders.buy, strategy_buy), ("Sell", orders.sell, strategy_sell)].into_iter().enumerate() {
+            // Records that are live already (e.g. a create sent after the snapshot was taken) are left alone.
+            if !active || self.record(side).live {
+                continue;
+            }
+            match open {
+                Some(order) => {
+                    info!(orders, "OMS: RECONCILE -> {} order live on exchange @ {}, re-adopting", side, order.price);
+                    let trace = self.record(side).trace;
+                    *self.record_mut(side) = OrderRecord::placed(order.price, order.qty, now, trace);
+                }
+                None => {
+                    info!(orders, "OMS: RECONCILE -> {} order gone on exchange, resetting quote", side);
+                    stale[i] = Some(side);
+                }
+            }
+        }
+        stale
+    }
+
+    /// Open-order poll while quoting over REST (`strategy::rest_quoting`), queued at `polled_at`.
+    /// Reconciles like `on_open_orders`; on top, a live record placed before the poll that the
+    /// exchange no longer has is closed (filled or cancelled without a private push reaching
+    /// us) and its side returned for a reset.
+    pub fn on_polled_orders(&mut self, orders: &OpenOrders, strategy_buy: bool, strategy_sell: bool, polled_at: Instant, now: Instant) -> [Option<&'static str>; 2] {
+        let mut stale = self.on_open_orders(orders, strategy_buy, strategy_sell, now);
+        for (i, (side, open)) in [("Buy", orders.buy), ("Sell", orders.sell)].into_iter().enumerate() {
+            let record = self.record(side);
+            if open.is_none() && record.live && record.placed_ts < polled_at {
+                info!(orders, "OMS: POLL -> {} order no longer on exchange, resetting quote", side);
+                self.on_order_closed(side);
+                stale[i] = Some(side);
+            }
+        }
+        stale
+    }
+
+    /// REST query failed: allow the next heartbeat to try again.
+    pub fn on_reconcile_failed(&mut self) {
+        self.reconcile_in_flight = false;
+    }
+
+    /// Order left the book (fully filled, cancelled, rejected or reset after an error).
+    pub fn on_order_closed(&mut self, side: &str) {
+        if side == "Buy" || side == "Sell" {
+            let rec = self.record_mut(side);
+            rec.live = false;
+            rec.stp_cancel = false;
+            rec.pending = None;
+        }
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::core::orderbook::{L2OrderBook, Side};
+    use crate::strategy::market_maker::ActionType;
+    use crate::strategy::order_manager::{AmendDecision, OrderManager, OrderManagerConfig, Veto};
+
+    fn create(side: &'static str, price: f64) -> ActionType {
+        ActionType::CreateOrder { price, qty: 5.0, side, link_id: format!("{}-1", if side == "Buy" { "b" } else { "s" }) }
+    }
+
+    fn amend_price(side: &'static str, price: f64) -> ActionType {
+        ActionType::AmendPrice { price, side, link_id: format!("{}-1", if side == "Buy" { "b" } else { "s" }) }
+    }
+
+    // 5 resting at 100.0 on the bid: the whole level when our 5 is there
+    fn book(bid_qty: f64) -> L2OrderBook {
+        let mut book = L2OrderBook::new();
+        book.update(Side::Buy, 100.0, bid_qty);
+        book.update(Side::Sell, 100.5, 5.0);
+        book.snapshot_applied = true;
+        book
+    }
+
+    #[test]
+    fn young_quotes_are_not_touched_but_creates_and_pulls_pass() {
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        // Nothing resting: nothing to protect
+        assert!(oms.check(&amend_price("Buy", 99.0), t0).is_ok());
+
+        oms.on_sent(&create("Buy", 100.0), t0);
+        let cancel = ActionType::CancelOrder { link_id: "b-1".to_string() };
+        let veto = oms.check(&cancel, at(100)).unwrap_err();
+        assert_eq!(veto, Veto::QuoteTooYoung { side: "Buy", age_ms: 100 });
+        assert!(veto.retry());
+        assert!(matches!(oms.check(&amend_price("Buy", 99.0), at(100)), Err(Veto::QuoteTooYoung { .. })));
+        // Risk pulls and the other side are not gated by this quote's age
+        assert!(oms.check(&ActionType::CancelAll, at(100)).is_ok());
+        assert!(oms.check(&ActionType::ClosePosition { qty: 1.0, side: "Buy" }, at(100)).is_ok());
+        assert!(oms.check(&create("Sell", 101.0), at(100)).is_ok());
+
+        assert!(oms.check(&cancel, at(250)).is_ok());
+        assert!(oms.check(&amend_price("Buy", 99.0), at(250)).is_ok());
+    }
+
+    #[test]
+    fn front_of_queue_holds_a_small_reprice_through_the_gate() {
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let t0 = Instant::now();
+        let later = t0 + Duration::from_secs(1);
+        oms.on_sent(&create("Buy", 100.0), t0);
+
+        // Queue position unknown until a book update: treated as the back, amend freely
+        assert_eq!(oms.buy.queue_fraction(), 1.0);
+        assert_eq!(oms.amend_decision("Buy", 100.01, later), AmendDecision::Amend);
+
+        oms.on_book_update(&book(5.0));
+        assert_eq!(oms.buy.queue_fraction(), 0.0);
+        let veto = oms.check(&amend_price("Buy", 100.01), later).unwrap_err();
+        assert_eq!(veto, Veto::HoldQueuePriority { side: "Buy", queue_fraction: 0.0 });
+        assert!(!veto.retry());
+        // Qty-only amends keep the slot; a big move is worth losing it
+        let qty = ActionType::AmendQty { qty: 6.0, side: "Buy", link_id: "b-1".to_string() };
+        assert!(oms.check(&qty, later).is_ok());
+        assert!(oms.check(&amend_price("Buy", 99.0), later).is_ok());
+    }
+
+    #[test]
+    fn queue_ahead_only_shrinks_and_stale_back_quotes_refresh_once() {
+        let config = OrderManagerConfig { stale_quote_age: Duration::from_secs(5), ..OrderManagerConfig::default() };
+        let mut oms = OrderManager::new(config);
+        let t0 = Instant::now();
+        oms.on_sent(&create("Buy", 100.0), t0);
+
+        // Joined behind 15: 15 of 20 ahead
+        oms.on_book_update(&book(20.0));
+        assert_eq!(oms.buy.queue_ahead, 15.0);
+        // New orders join behind us; cancels and trades consume the front
+        oms.on_book_update(&book(30.0));
+        assert_eq!(oms.buy.queue_ahead, 15.0);
+        oms.on_book_update(&book(19.0));
+        assert_eq!(oms.buy.queue_ahead, 14.0);
+        assert!(oms.buy.queue_fraction() >= config.back_queue_fraction);
+
+        assert!(!oms.refresh_due(t0 + Duration::from_secs(4)));
+        assert!(oms.refresh_due(t0 + Duration::from_secs(5)));
+        assert!(!oms.refresh_due(t0 + Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn price_band_needs_a_fresh_reference() {
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let t0 = Instant::now();
+        // No reference yet: not applied
+        assert!(oms.check(&create("Buy", 150.0), t0).is_ok());
+
+        oms.set_reference_price(100.0, t0);
+        assert!(oms.check(&create("Buy", 102.0), t0).is_ok());
+        let veto = oms.check(&create("Buy", 104.0), t0).unwrap_err();
+        assert_eq!(veto, Veto::PriceBand { side: "Buy", price: 104.0, reference: 100.0 });
+        assert!(!veto.retry());
+        assert!(matches!(oms.check(&create("Sell", f64::NAN), t0), Err(Veto::PriceBand { .. })));
+        // A reference older than `reference_max_age` is not trusted
+        assert!(oms.check(&create("Buy", 104.0), t0 + Duration::from_secs(6)).is_ok());
+    }
+
+    #[test]
+    fn reduce_only_lets_through_the_reducing_side() {
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let now = Instant::now();
+        oms.set_reduce_only(true);
+
+        // Flat: nothing reduces
+        assert_eq!(oms.check(&create("Buy", 100.0), now), Err(Veto::ReduceOnly { side: "Buy" }));
+        assert_eq!(oms.check(&create("Sell", 101.0), now), Err(Veto::ReduceOnly { side: "Sell" }));
+        oms.set_position(-2.0);
+        assert!(oms.check(&create("Buy", 100.0), now).is_ok());
+        assert_eq!(oms.check(&create("Sell", 101.0), now), Err(Veto::ReduceOnly { side: "Sell" }));
+        assert!(oms.check(&ActionType::CancelAll, now).is_ok());
+        assert!(oms.check(&ActionType::ClosePosition { qty: 2.0, side: "Buy" }, now).is_ok());
+
+        oms.set_reduce_only(false);
+        assert!(oms.check(&create("Sell", 101.0), now).is_ok());
+    }
+
+    #[test]
+    fn a_quote_through_our_own_resting_order_is_vetoed() {
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let now = Instant::now();
+        oms.on_sent(&create("Buy", 100.0), now);
+
+        assert_eq!(oms.check(&create("Sell", 100.0), now), Err(Veto::SelfMatch { side: "Sell", resting: "Buy" }));
+        assert!(oms.check(&create("Sell", 100.001), now).is_ok());
+        oms.on_order_closed("Buy");
+        assert!(oms.check(&create("Sell", 99.0), now).is_ok());
+    }
+
+    #[test]
+    fn a_pending_amend_is_confirmed_by_its_ack_or_dropped_by_a_reject() {
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let t0 = Instant::now();
+        let t1 = t0 + Duration::from_secs(1);
+        oms.on_sent(&create("Buy", 100.0), t0);
+
+        oms.on_sent(&amend_price("Buy", 99.0), t1);
+        // Risk and queue checks keep using the confirmed price
+        assert_eq!((oms.buy.price, oms.latest("Buy")), (100.0, (99.0, 5.0)));
+        assert_eq!(oms.on_amend_rejected("Buy"), 100.0);
+        assert_eq!(oms.latest("Buy"), (100.0, 5.0));
+
+        oms.on_sent(&amend_price("Buy", 99.0), t1);
+        oms.on_amend_acked("Buy", t1);
+        assert_eq!((oms.buy.price, oms.buy.placed_ts), (99.0, t1));
+        assert!(oms.buy.queue_ahead.is_nan());
+        // A qty-only amend keeps the placement time (and the queue slot)
+        let t2 = t1 + Duration::from_secs(1);
+        oms.on_sent(&ActionType::AmendQty { qty: 7.0, side: "Buy", link_id: "b-1".to_string() }, t2);
+        oms.on_amend_acked("Buy", t2);
+        assert_eq!((oms.buy.qty, oms.buy.placed_ts), (7.0, t1));
+    }
+}
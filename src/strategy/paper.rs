@@ -0,0 +1,284 @@
+use crate::core::clock::Clock;
+use crate::core::orderbook::{L2OrderBook, Level, Side};
+use crate::core::parser::TradeEvent;
+use crate::strategy::fees::FEE_TIERS;
+use crate::strategy::fill::Fill;
+use crate::strategy::market_maker::{ActionType, MarketMaker};
+use crate::strategy::order_manager::{side_from_link, OrderManager};
+
+// Paper exchange for the paper runner and offline replays: holds the strategy's resting
+// quotes (one per side, as on Bybit) and decides when public data would have filled them.
+// Whether a passive order fills is the one thing a simulator cannot observe, so the rule is
+// selectable and a run can be repeated under each to bracket the result:
+//   optimistic  - fills as soon as the opposite touch or a trade reaches our price;
+//   queue       - joins the back of the visible level; trades at our price eat the queue
+//                 ahead first, a trade or touch through our price fills the rest;
+//   pessimistic - fills only when the market trades or quotes strictly through our price.
+// Passive fills are at our price with the maker fee; ClosePosition crosses the spread at
+// the opposite touch with the taker fee. `closed_pnl` is gross, as Bybit reports it.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillModel {
+    Optimistic,
+    Queue,
+    Pessimistic,
+}
+
+impl FillModel {
+    /// `optimistic` | `queue` | `pessimistic`.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "optimistic" => Some(FillModel::Optimistic),
+            "queue" => Some(FillModel::Queue),
+            "pessimistic" => Some(FillModel::Pessimistic),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FillModel::Optimistic => "optimistic",
+            FillModel::Queue => "queue",
+            FillModel::Pessimistic => "pessimistic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PaperConfig {
+    pub model: FillModel,
+    pub maker_fee: f64,
+    pub taker_fee: f64,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self { model: FillModel::Queue, maker_fee: FEE_TIERS[0].maker_fee, taker_fee: FEE_TIERS[0].taker_fee }
+    }
+}
+
+impl PaperConfig {
+    /// Default with the model from `HFT_FILL_MODEL` (a bad value is reported and ignored).
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(spec) = std::env::var("HFT_FILL_MODEL") {
+            match FillModel::from_spec(&spec) {
+                Some(model) => config.model = model,
+                None => eprintln!("PAPER: Unknown HFT_FILL_MODEL '{}', using {}", spec, config.model.name()),
+            }
+        }
+        config
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PaperOrder {
+    price: f64,
+    // Unfilled remainder
+    qty: f64,
+    // Visible size in front of us at our price (queue model only)
+    queue_ahead: f64,
+}
+
+/// Simulated resting orders and position. Feed it the strategy actions, the book and the
+/// public trades; it returns the fills those would have produced.
+pub struct PaperExchange {
+    pub config: PaperConfig,
+    buy: Option<PaperOrder>,
+    sell: Option<PaperOrder>,
+    pub position: f64,
+    entry: f64,
+    /// Session totals.
+    pub fills: u64,
+    pub fees: f64,
+    pub realized: f64,
+}
+
+/// A paper fill, handled like an execution in the Hot Thread; a side that no longer rests
+/// is closed on both the strategy and the OMS.
+pub fn settle<C: Clock>(strategy: &mut MarketMaker<C>, oms: &mut OrderManager<C>, paper: &PaperExchange, fill: &Fill) {
+    strategy.on_fill(fill);
+    oms.set_position(strategy.positions.size());
+    if !paper.is_resting(fill.side) {
+        strategy.on_order_cancel(fill.side);
+        oms.on_order_closed(fill.side);
+    }
+}
+
+fn level_qty(levels: &[Level], price: f64) -> f64 {
+    levels.iter().find(|l| (l.price - price).abs() < 1e-9).map(|l| l.qty).unwrap_or(0.0)
+}
+
+impl PaperExchange {
+    pub fn new(config: PaperConfig) -> Self {
+        Self { config, buy: None, sell: None, position: 0.0, entry: 0.0, fills: 0, fees: 0.0, realized: 0.0 }
+    }
+
+    /// Average entry of the open position (0 when flat).
+    pub fn entry(&self) -> f64 {
+        self.entry
+    }
+
+    /// True while an order rests on `side`.
+    pub fn is_resting(&self, side: &str) -> bool {
+        if side == "Buy" { self.buy.is_some() } else { self.sell.is_some() }
+    }
+
+    fn slot(&mut self, side: &str) -> &mut Option<PaperOrder> {
+        if side == "Buy" { &mut self.buy } else { &mut self.sell }
+    }
+
+    // Size already resting at `price` on our side of the book
+    fn queue_at(&self, book: &L2OrderBook, side: &str, price: f64) -> f64 {
+        if self.config.model != FillModel::Queue {
+            return 0.0;
+        }
+        if side == "Buy" { level_qty(&book.bids, price) } else { level_qty(&book.asks, price) }
+    }
+
+    /// One strategy action. A market close fills at once (taker); everything else only
+    /// changes what rests.
+    pub fn on_action(&mut self, action: &ActionType, book: &L2OrderBook, ts_ms: u64) -> Option<Fill> {
+        match action {
+            ActionType::CreateOrder { price, qty, side, .. } => {
+                let queue_ahead = self.queue_at(book, side, *price);
+                *self.slot(side) = Some(PaperOrder { price: *price, qty: *qty, queue_ahead });
+            }
+            ActionType::AmendOrder { price, qty, side, .. } => {
+                let queue_ahead = self.queue_at(book, side, *price);
+                if let Some(order) = self.slot(side).as_mut() {
+                    if (order.price - price).abs() > 1e-9 {
+                        order.queue_ahead = queue_ahead;
+                    }
+                    order.price = *price;
+                    order.qty = *qty;
+                }
+            }
+            ActionType::AmendPrice { price, side, .. } => {
+                let queue_ahead = self.queue_at(book, side, *price);
+                if let Some(order) = self.slot(side).as_mut() {
+                    order.price = *price;
+                    order.queue_ahead = queue_ahead;
+                }
+            }
+            ActionType::AmendQty { qty, side, .. } => {
+                if let Some(order) = self.slot(side).as_mut() {
+                    order.qty = *qty;
+                }
+            }
+            ActionType::CancelOrder { link_id } => {
+                if let Some(side) = side_from_link(link_id) {
+                    *self.slot(side) = None;
+                }
+            }
+            ActionType::CancelAll => {
+                self.buy = None;
+                self.sell = None;
+            }
+            ActionType::ClosePosition { qty, side } => {
+                let px = if *side == "Buy" { book.asks[0].price } else { book.bids[0].price };
+                if px > 0.0 && *qty > 0.0 {
+                    return Some(self.execute(side, *qty, px, false, ts_ms));
+                }
+            }
+            ActionType::SetTradingStop { .. } | ActionType::None => {}
+        }
+        None
+    }
+
+    /// Book update: a touch at or through a resting price (per model) fills it; in the
+    /// queue model the queue ahead can only shrink to what is still visible at our level.
+    pub fn on_book(&mut self, book: &L2OrderBook, ts_ms: u64) -> [Option<Fill>; 2] {
+        let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+        let mut fills = [None, None];
+        if bid <= 0.0 || ask <= 0.0 {
+            return fills;
+        }
+        if let Some(order) = self.buy.as_mut() {
+            order.queue_ahead = order.queue_ahead.min(level_qty(&book.bids, order.price));
+        }
+        if let Some(order) = self.sell.as_mut() {
+            order.queue_ahead = order.queue_ahead.min(level_qty(&book.asks, order.price));
+        }
+        let model = self.config.model;
+        let touches = |through: bool| match model {
+            FillModel::Optimistic => true,
+            FillModel::Queue | FillModel::Pessimistic => through,
+        };
+        if let Some(order) = self.buy.filter(|o| ask <= o.price && touches(ask < o.price)) {
+            self.buy = None;
+            fills[0] = Some(self.execute("Buy", order.qty, order.price, true, ts_ms));
+        }
+        if let Some(order) = self.sell.filter(|o| bid >= o.price && touches(bid > o.price)) {
+            self.sell = None;
+            fills[1] = Some(self.execute("Sell", order.qty, order.price, true, ts_ms));
+        }
+        fills
+    }
+
+    /// Public trade: a taker sell can fill our bid, a taker buy our ask.
+    pub fn on_trade(&mut self, trade: &TradeEvent) -> Option<Fill> {
+        let side = if trade.side == Side::Sell { "Buy" } else { "Sell" };
+        let model = self.config.model;
+        let order = self.slot(side).as_mut()?;
+        let (at, through) = if side == "Buy" {
+            (trade.price <= order.price, trade.price < order.price)
+        } else {
+            (trade.price >= order.price, trade.price > order.price)
+        };
+        let qty = match model {
+            FillModel::Optimistic if at => order.qty,
+            FillModel::Pessimistic if through => order.qty,
+            FillModel::Queue if through => order.qty,
+            FillModel::Queue if at => {
+                let left = trade.qty - order.queue_ahead;
+                order.queue_ahead = (-left).max(0.0);
+                left.min(order.qty)
+            }
+            _ => 0.0,
+        };
+        if qty <= 0.0 {
+            return None;
+        }
+        let price = order.price;
+        order.qty -= qty;
+        if order.qty <= 1e-9 {
+            *self.slot(side) = None;
+        }
+        Some(self.execute(side, qty, price, true, trade.ts))
+    }
+
+    // Position, entry and realized PnL as the exchange would book them
+    fn execute(&mut self, side: &'static str, qty: f64, px: f64, is_maker: bool, ts_ms: u64) -> Fill {
+        let signed = if side == "Buy" { qty } else { -qty };
+        let mut closed_pnl = 0.0;
+        if self.position != 0.0 && self.position.signum() != signed.signum() {
+            let closed = qty.min(self.position.abs());
+            closed_pnl = closed * (px - self.entry) * self.position.signum();
+            if qty > self.position.abs() {
+                self.entry = px;
+            }
+        } else {
+            let total = self.position.abs() + qty;
+            self.entry = (self.position.abs() * self.entry + qty * px) / total;
+        }
+        self.position += signed;
+        if self.position.abs() < 1e-9 {
+            self.position = 0.0;
+            self.entry = 0.0;
+        }
+        let fee_rate = if is_maker { self.config.maker_fee } else { self.config.taker_fee };
+        let fee = qty * px * fee_rate;
+        self.fills += 1;
+        self.fees += fee;
+        self.realized += closed_pnl;
+        Fill { side, qty, px, fee, fee_rate, is_maker, closed_pnl, exec_time_ms: ts_ms, seq: 0 }
+    }
+
+    /// Session line: model, position and PnL net of fees.
+    pub fn report(&self) {
+        println!("PAPER [{}]: fills {} | pos {:.4} @ {:.2} | realized {:.6} | fees {:.6} | net {:.6}",
+            self.config.model.name(), self.fills, self.position, self.entry,
+            self.realized, self.fees, self.realized - self.fees);
+    }
+}
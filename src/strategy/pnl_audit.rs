@@ -0,0 +1,166 @@
+use crate::net::rest_client::Statement;
+use crate::strategy::fill::Fill;
+
+// Daily PnL audit against the exchange statement. Executions are bucketed by exchange
+// time (execTime) into fixed windows of `interval`; once a window has closed and
+// `settle_lag` has passed (the transaction log lags the execution stream), the REST
+// thread pulls the transaction log for it and the two are compared component by
+// component: trade count, fees, realized PnL. Funding never reaches the execution stream,
+// so it is attributed from the statement only. Any component off by more than
+// `tolerance` (settle coin) is a discrepancy and raises an alert.
+const DEFAULT_INTERVAL_MS: u64 = 24 * 60 * 60 * 1000;
+const DEFAULT_SETTLE_LAG_MS: u64 = 2 * 60 * 1000;
+const DEFAULT_RETRY_MS: u64 = 5 * 60 * 1000;
+const DEFAULT_TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PnlAuditConfig {
+    pub enabled: bool,
+    /// Window length; one statement pull per window.
+    pub interval_ms: u64,
+    /// Wait after the window closes before pulling it.
+    pub settle_lag_ms: u64,
+    /// Wait before pulling again after a failed query.
+    pub retry_ms: u64,
+    /// Largest difference per component (settle coin) that still counts as a match.
+    pub tolerance: f64,
+}
+
+impl Default for PnlAuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: DEFAULT_INTERVAL_MS,
+            settle_lag_ms: DEFAULT_SETTLE_LAG_MS,
+            retry_ms: DEFAULT_RETRY_MS,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+/// Our side of one window, from the execution stream.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PnlTotals {
+    pub fills: u32,
+    /// execFee: positive = paid, negative = rebate.
+    pub fees: f64,
+    /// closedPnl.
+    pub realized: f64,
+}
+
+impl PnlTotals {
+    fn add(&mut self, fill: &Fill) {
+        self.fills += 1;
+        self.fees += fill.fee;
+        self.realized += fill.closed_pnl;
+    }
+}
+
+// A closed window waiting for (or retrying) its statement
+#[derive(Debug, Clone, Copy)]
+struct ClosedWindow {
+    start_ms: u64,
+    end_ms: u64,
+    ours: PnlTotals,
+    // Next query time; u64::MAX while a query is in flight
+    query_at_ms: u64,
+}
+
+/// Execution-stream PnL per window, reconciled against the exchange statement. Hot Thread.
+pub struct PnlAudit {
+    pub config: PnlAuditConfig,
+    window_start_ms: u64,
+    window_end_ms: u64,
+    current: PnlTotals,
+    // Fills already past the current window while it waits out the settle lag
+    next: PnlTotals,
+    closed: Option<ClosedWindow>,
+    /// Windows reconciled / windows with a discrepancy.
+    pub audited: u64,
+    pub mismatches: u64,
+}
+
+impl PnlAudit {
+    /// The first window starts now: fills before the session are not ours to explain.
+    pub fn new(config: PnlAuditConfig, now_ms: u64) -> Self {
+        Self {
+            config,
+            window_start_ms: now_ms,
+            window_end_ms: now_ms + config.interval_ms,
+            current: PnlTotals::default(),
+            next: PnlTotals::default(),
+            closed: None,
+            audited: 0,
+            mismatches: 0,
+        }
+    }
+
+    pub fn on_fill(&mut self, fill: &Fill) {
+        // No execTime: count it where it arrived
+        let ts = if fill.exec_time_ms > 0 { fill.exec_time_ms } else { self.window_start_ms };
+        match self.closed.as_mut() {
+            Some(closed) if ts < closed.end_ms => closed.ours.add(fill),
+            _ if ts < self.window_end_ms => self.current.add(fill),
+            _ => self.next.add(fill),
+        }
+    }
+
+    /// Statement window to query now, if any: the last window once it has settled,
+    /// or a retry after a failed query.
+    pub fn poll(&mut self, now_ms: u64) -> Option<(u64, u64)> {
+        if !self.config.enabled {
+            return None;
+        }
+        if self.closed.is_none() && now_ms >= self.window_end_ms + self.config.settle_lag_ms {
+            self.closed = Some(ClosedWindow {
+                start_ms: self.window_start_ms,
+                end_ms: self.window_end_ms,
+                ours: self.current,
+                query_at_ms: now_ms,
+            });
+            self.current = self.next;
+            self.next = PnlTotals::default();
+            self.window_start_ms = self.window_end_ms;
+            self.window_end_ms += self.config.interval_ms;
+        }
+        let closed = self.closed.as_mut()?;
+        if now_ms < closed.query_at_ms {
+            return None;
+        }
+        closed.query_at_ms = u64::MAX;
+        Some((closed.start_ms, closed.end_ms))
+    }
+
+    /// Query failed: same window again after `retry_ms`.
+    pub fn on_failed(&mut self, now_ms: u64) {
+        if let Some(closed) = self.closed.as_mut() {
+            closed.query_at_ms = now_ms + self.config.retry_ms;
+        }
+    }
+
+    /// Compares the statement with our totals for its window and prints the attribution.
+    /// Returns true on a discrepancy.
+    pub fn on_statement(&mut self, statement: &Statement) -> bool {
+        let Some(closed) = self.closed.filter(|c| c.start_ms == statement.start_ms && c.end_ms == statement.end_ms) else {
+            eprintln!("PNL AUDIT: Statement for an unknown window {}..{} ignored", statement.start_ms, statement.end_ms);
+            return false;
+        };
+        self.closed = None;
+        self.audited += 1;
+        let ours = closed.ours;
+        let fee_diff = ours.fees - statement.fees;
+        let realized_diff = ours.realized - statement.realized;
+        println!("PNL AUDIT: {}..{} | fills {} ours / {} exch | fees {:.6} / {:.6} ({:+.6}) | realized {:.6} / {:.6} ({:+.6}) | funding {:+.6} (exch only) | net change {:+.6}",
+            closed.start_ms, closed.end_ms, ours.fills, statement.trades, ours.fees, statement.fees, fee_diff,
+            ours.realized, statement.realized, realized_diff, statement.funding, statement.change);
+        let mismatch = ours.fills != statement.trades
+            || fee_diff.abs() > self.config.tolerance
+            || realized_diff.abs() > self.config.tolerance;
+        if mismatch {
+            self.mismatches += 1;
+            eprintln!("ALERT: [PNL AUDIT] Session PnL does not match the exchange statement for {}..{} (fills {} vs {}, fees {:+.6}, realized {:+.6}; {} of {} windows off)",
+                closed.start_ms, closed.end_ms, ours.fills, statement.trades, fee_diff, realized_diff, self.mismatches, self.audited);
+        }
+        mismatch
+    }
+}
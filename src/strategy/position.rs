@@ -0,0 +1,92 @@
+use crate::strategy::fill::Fill;
+
+// One position per symbol, fed by two streams that race. `execution` fills move it a trade
+// at a time; the `position` topic is the exchange's own snapshot. They come down different
+// push paths, so a snapshot can overtake the fill it already contains (applying the fill on
+// top double counts it) and a late snapshot can miss fills already booked (applying it undoes
+// them). Bybit stamps both with the account's cross sequence (`seq`): a snapshot covers every
+// fill up to its seq, so whichever side is older gives way. Payloads without a seq (0: older
+// formats, the paper exchange) are ordered by exchange ms instead.
+const FLAT_EPSILON: f64 = 0.0001;
+
+/// Authoritative size and entry of one symbol; owned by the strategy (`MarketMaker::positions`),
+/// the OMS reads it every tick.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PositionTracker {
+    // Base coin, signed
+    size: f64,
+    entry: f64,
+    // Highest seq folded in, from either source
+    seq: u64,
+    // Exchange ms of the last snapshot / the last fill applied (fallback without seq)
+    snapshot_ms: u64,
+    fill_ms: u64,
+    /// Fills a snapshot already contained (or redelivered), not applied.
+    pub skipped_fills: u64,
+    /// Snapshots older than fills already booked, not applied.
+    pub stale_snapshots: u64,
+}
+
+impl PositionTracker {
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+
+    pub fn entry_price(&self) -> f64 {
+        self.entry
+    }
+
+    /// Books `fill` unless the position already contains it. Returns the signed qty applied,
+    /// None when skipped.
+    pub fn on_fill(&mut self, fill: &Fill) -> Option<f64> {
+        let covered = if fill.seq > 0 && self.seq > 0 {
+            fill.seq <= self.seq
+        } else {
+            fill.exec_time_ms > 0 && fill.exec_time_ms <= self.snapshot_ms
+        };
+        if covered {
+            self.skipped_fills += 1;
+            return None;
+        }
+        let signed = if fill.side == "Buy" { fill.qty } else { -fill.qty };
+        let after = self.size + signed;
+        if self.size.abs() < FLAT_EPSILON || (self.size > 0.0) == (signed > 0.0) {
+            // Opening or adding: weighted average entry
+            self.entry = (self.size.abs() * self.entry + fill.qty * fill.px) / after.abs().max(f64::MIN_POSITIVE);
+        } else if after.abs() >= FLAT_EPSILON && (after > 0.0) != (self.size > 0.0) {
+            // Flipped through zero: the remainder was opened at this price
+            self.entry = fill.px;
+        }
+        self.size = after;
+        if self.size.abs() < FLAT_EPSILON {
+            self.entry = 0.0;
+        }
+        self.seq = self.seq.max(fill.seq);
+        self.fill_ms = self.fill_ms.max(fill.exec_time_ms);
+        Some(signed)
+    }
+
+    /// The exchange's size (base coin, signed) and entry as of `seq` / `updated_ms`. False when
+    /// fills newer than the snapshot are already booked.
+    pub fn on_snapshot(&mut self, size: f64, entry: f64, seq: u64, updated_ms: u64) -> bool {
+        let stale = if seq > 0 && self.seq > 0 {
+            seq < self.seq
+        } else {
+            updated_ms > 0 && updated_ms < self.fill_ms
+        };
+        if stale {
+            self.stale_snapshots += 1;
+            return false;
+        }
+        self.set(size, entry);
+        self.seq = self.seq.max(seq);
+        self.snapshot_ms = self.snapshot_ms.max(updated_ms);
+        true
+    }
+
+    /// Local override with no ordering (forced flatten after a reject, startup sync).
+    pub fn set(&mut self, size: f64, entry: f64) {
+        self.size = size;
+        self.entry = if size.abs() < FLAT_EPSILON { 0.0 } else { entry };
+    }
+}
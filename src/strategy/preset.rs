@@ -0,0 +1,105 @@
+use std::time::Duration;
+use serde::Deserialize;
+use crate::strategy::inventory::AgingConfig;
+use crate::strategy::market_maker::QuoteParams;
+
+// Risk posture in one switch. A preset owns the spread band, the take-profit target and the
+// inventory hold times (exit joins the touch / crosses at market); the clip stays the
+// configured one (quote_qty or quote_notional, per symbol) scaled by `size_factor`, so one
+// preset fits symbols of very different prices. `normal` is the built-in defaults.
+// Selected by `preset = "..."` in the config, HFT_PRESET or --preset (that order of precedence);
+// the per-field HFT_* overrides (HFT_EXIT_JOIN_MS, HFT_EXIT_CROSS_MS, ...) still apply on top.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// Wider, smaller, out sooner.
+    Conservative,
+    Normal,
+    /// Tighter, larger, holds longer for a bigger target.
+    Aggressive,
+}
+
+/// What a preset sets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresetParams {
+    pub min_spread: f64,
+    pub max_spread: f64,
+    /// Multiplier on the configured clip.
+    pub size_factor: f64,
+    pub take_profit_pct: f64,
+    pub join_after_ms: u64,
+    pub cross_after_ms: u64,
+}
+
+const CONSERVATIVE: PresetParams = PresetParams {
+    min_spread: 0.006,
+    max_spread: 0.015,
+    size_factor: 0.5,
+    take_profit_pct: 0.004,
+    join_after_ms: 1_500,
+    cross_after_ms: 4_000,
+};
+
+const NORMAL: PresetParams = PresetParams {
+    min_spread: 0.004,
+    max_spread: 0.010,
+    size_factor: 1.0,
+    take_profit_pct: 0.005,
+    join_after_ms: 3_000,
+    cross_after_ms: 8_000,
+};
+
+const AGGRESSIVE: PresetParams = PresetParams {
+    min_spread: 0.003,
+    max_spread: 0.008,
+    size_factor: 1.5,
+    take_profit_pct: 0.007,
+    join_after_ms: 5_000,
+    cross_after_ms: 15_000,
+};
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Conservative, Preset::Normal, Preset::Aggressive];
+
+    /// `conservative` | `normal` | `aggressive` (HFT_PRESET, --preset).
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == spec.trim().to_ascii_lowercase())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Conservative => "conservative",
+            Preset::Normal => "normal",
+            Preset::Aggressive => "aggressive",
+        }
+    }
+
+    pub fn params(self) -> PresetParams {
+        match self {
+            Preset::Conservative => CONSERVATIVE,
+            Preset::Normal => NORMAL,
+            Preset::Aggressive => AGGRESSIVE,
+        }
+    }
+
+    /// `quote` with the preset's spread band and target and its clip scaled.
+    pub fn apply(self, quote: &mut QuoteParams) {
+        let params = self.params();
+        quote.min_spread = params.min_spread;
+        quote.max_spread = params.max_spread;
+        quote.take_profit_pct = params.take_profit_pct;
+        quote.quote_qty *= params.size_factor;
+        quote.quote_notional = quote.quote_notional.map(|notional| notional * params.size_factor);
+    }
+
+    /// Hold times; the market-close retry stays the default.
+    pub fn aging(self) -> AgingConfig {
+        let params = self.params();
+        AgingConfig {
+            join_after: Duration::from_millis(params.join_after_ms),
+            cross_after: Duration::from_millis(params.cross_after_ms),
+            ..AgingConfig::default()
+        }
+    }
+}
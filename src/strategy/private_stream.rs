@@ -0,0 +1,198 @@
+use std::time::Instant;
+use simd_json::prelude::*;
+use simd_json::BorrowedValue;
+use crate::strategy::book_manager::{BookManager, SymbolSlot};
+use crate::strategy::fill::Fill;
+use crate::strategy::order_manager::{self, TraceStage};
+use crate::strategy::side_throttle::ThrottleTrip;
+
+// What private and Trade WS messages do to the per-symbol state (strategy flags, position,
+// OrderManager records). The Hot Thread parses a frame and hands it here; I/O, alerts and
+// account-wide trackers (fees, markouts, drop copy, error history) stay in main. Kept apart
+// so the recovery rules can be replayed against captured payloads (tests/fixtures/private).
+
+/// Order was never placed / no longer exists.
+pub const ORDER_NOT_EXISTS: i64 = 110001;
+/// Position mode mismatch or a bad parameter.
+pub const PARAMS_ERROR: i64 = 10001;
+/// orderLinkId already used.
+pub const DUPLICATE_LINK_ID: i64 = 110072;
+/// reduceOnly order while the position is already 0.
+pub const REDUCE_ONLY_NO_POSITION: i64 = 110017;
+/// Invalid qty/price, mostly on a close.
+pub const INVALID_REQUEST: i64 = 10404;
+/// Too many requests.
+pub const RATE_LIMIT: i64 = 10006;
+
+/// Envelope of a response to one of our requests (op + reqId + retCode).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Response<'v> {
+    pub op: &'v str,
+    pub req_id: &'v str,
+    pub ret_code: Option<i64>,
+    pub ret_msg: &'v str,
+    pub success: bool,
+}
+
+impl<'v> Response<'v> {
+    pub fn of(json: &'v BorrowedValue<'v>) -> Self {
+        Self {
+            op: json.get("op").and_then(|v| v.as_str()).unwrap_or(""),
+            req_id: json.get("reqId").and_then(|v| v.as_str()).unwrap_or(""),
+            ret_code: json.get("retCode").and_then(|v| v.as_i64()),
+            ret_msg: json.get("retMsg").and_then(|v| v.as_str()).unwrap_or(""),
+            success: json.get("success").and_then(|v| v.as_bool()).unwrap_or(false),
+        }
+    }
+
+    /// Non-zero retCode.
+    pub fn error(&self) -> Option<i64> {
+        self.ret_code.filter(|&code| code != 0)
+    }
+
+    pub fn is_auth_ok(&self) -> bool {
+        self.op == "auth" && (self.success || self.ret_code == Some(0))
+    }
+}
+
+/// State repaired after a reject; main logs and does the rest (rate-limit pause).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Recovery {
+    /// Symbol slot and side whose order state was reset (new link id, record closed).
+    pub reset: Option<(usize, &'static str)>,
+    /// Slot whose position was forced to 0.
+    pub flattened: Option<usize>,
+    pub rate_limited: bool,
+}
+
+fn reset_order(books: &mut BookManager, req_id: &str) -> Option<(usize, &'static str)> {
+    let side = order_manager::side_from_link(req_id)?;
+    let slot = order_manager::slot_from_link(req_id);
+    let SymbolSlot { strategy, order_manager, .. } = books.slots.get_mut(slot)?;
+    strategy.reset_order(side);
+    order_manager.on_order_closed(side);
+    Some((slot, side))
+}
+
+fn flatten(books: &mut BookManager, req_id: &str) -> Option<usize> {
+    let slot = order_manager::slot_from_link(req_id);
+    let strategy = &mut books.slots.get_mut(slot)?.strategy;
+    strategy.sync_position(0.0, 0.0);
+    strategy.has_active_buy = false;
+    strategy.has_active_sell = false;
+    Some(slot)
+}
+
+/// Non-zero retCode on the private WS.
+/// Failed creates, gone orders, mode mismatches and duplicate ids reset the side (a new
+/// link id, not a restore); "not modified" amends are benign. 110017 / 10404 mean the
+/// position we try to close is not there: flatten. 10006: the caller pauses.
+pub fn on_private_error(books: &mut BookManager, response: &Response) -> Recovery {
+    let Some(code) = response.error() else { return Recovery::default() };
+    let mut recovery = Recovery::default();
+    let resets = response.op == "order.create" || matches!(code, ORDER_NOT_EXISTS | PARAMS_ERROR | DUPLICATE_LINK_ID);
+    if resets && !response.ret_msg.contains("not modified") {
+        recovery.reset = reset_order(books, response.req_id);
+    }
+    if code == REDUCE_ONLY_NO_POSITION || code == INVALID_REQUEST {
+        recovery.flattened = flatten(books, response.req_id);
+    }
+    recovery.rate_limited = code == RATE_LIMIT;
+    recovery
+}
+
+/// Any Trade WS response carrying a reqId: marks the ack and settles a pending amend
+/// (confirmed on success; on a reject the order still rests at the confirmed price and
+/// the strategy rolls back to it).
+pub fn on_trade_response(books: &mut BookManager, response: &Response, now: Instant) {
+    let Some(side) = order_manager::side_from_link(response.req_id) else { return };
+    let Some(slot) = books.by_link(response.req_id) else { return };
+    slot.order_manager.mark(side, TraceStage::Acked, now);
+    if response.req_id.starts_with("amend-") {
+        if response.ret_code == Some(0) {
+            slot.order_manager.on_amend_acked(side, now);
+        } else {
+            let live = slot.order_manager.on_amend_rejected(side);
+            slot.strategy.on_amend_deferred(side, live, true);
+        }
+    }
+}
+
+/// Non-zero retCode on the Trade WS: 110017 flattens, 110001 resets the side.
+pub fn on_trade_error(books: &mut BookManager, response: &Response) -> Recovery {
+    match response.error() {
+        Some(REDUCE_ONLY_NO_POSITION) => Recovery { flattened: flatten(books, response.req_id), ..Recovery::default() },
+        Some(ORDER_NOT_EXISTS) => Recovery { reset: reset_order(books, response.req_id), ..Recovery::default() },
+        _ => Recovery::default(),
+    }
+}
+
+/// Any rejected request of a quote side (private or Trade WS) counts towards that side's
+/// loop breaker; a rate limit is account-wide and handled by the caller. Returns the slot and
+/// the trip when this reject benched the side.
+pub fn on_reject(books: &mut BookManager, response: &Response, now: Instant) -> Option<(usize, ThrottleTrip)> {
+    let code = response.error().filter(|&code| code != RATE_LIMIT)?;
+    let side = order_manager::side_from_link(response.req_id)?;
+    let slot = order_manager::slot_from_link(response.req_id);
+    let throttle = &mut books.slots.get_mut(slot)?.send_gate.throttle;
+    throttle.on_reject(side, code, now);
+    throttle.take_trip().map(|trip| (slot, trip))
+}
+
+/// The REST thread's answer to a quote request (REST quoting, `RestReply::Order`), settled
+/// like the Trade WS response it stands in for: an amend is confirmed or rolled back, a failed
+/// create or a gone order resets the side, a reject counts towards the side's loop breaker.
+pub fn on_rest_order(books: &mut BookManager, op: &str, link_id: &str, code: i64, now: Instant) -> (Recovery, Option<(usize, ThrottleTrip)>) {
+    // Amend reqIds carry the prefix `on_trade_response` keys on
+    let req_id = if op == "order.amend" { format!("amend-{}", link_id) } else { link_id.to_string() };
+    let response = Response { op, req_id: &req_id, ret_code: Some(code), ret_msg: "", success: code == 0 };
+    on_trade_response(books, &response, now);
+    (on_private_error(books, &response), on_reject(books, &response, now))
+}
+
+/// A `Trade` execution already applied by the strategy's caller (drop copy, incidents):
+/// the strategy books it, the record closes when the order is fully filled.
+pub fn on_fill(slot: &mut SymbolSlot, fill: &Fill, link_id: &str, order_status: &str, now: Instant) {
+    if let Some(side) = order_manager::side_from_link(link_id) {
+        slot.order_manager.mark(side, TraceStage::Filled, now);
+    }
+    slot.strategy.on_fill(fill);
+    if order_status == "Filled" {
+        slot.order_manager.on_order_closed(fill.side);
+    }
+}
+
+/// Non-trade execution item: a cancelled / rejected / deactivated order frees its side.
+/// False for any other status.
+pub fn on_order_status(slot: &mut SymbolSlot, side: &str, order_status: &str) -> bool {
+    if !matches!(order_status, "Cancelled" | "Rejected" | "Deactivated") {
+        return false;
+    }
+    slot.strategy.on_order_cancel(side);
+    slot.order_manager.on_order_closed(side);
+    true
+}
+
+/// One `position` item: the exchange's size (base coin, signed) becomes the strategy's
+/// position unless fills newer than it are already booked, its leverage the margin estimate's. Returns the slot, None when not quoted.
+pub fn on_position(books: &mut BookManager, item: &BorrowedValue) -> Option<usize> {
+    let symbol = item.get("symbol").and_then(|v| v.as_str()).unwrap_or("UNKNOWN");
+    let slot = books.slot_of(symbol)?;
+    let size = item.get("size").and_then(|v| v.as_str()).and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+    let entry_price = item.get("avgPrice").and_then(|v| v.as_str()).and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+    let slot_state = &mut books.slots[slot];
+    let size = slot_state.instrument.base_qty(size, entry_price);
+    let signed_qty = match item.get("side").and_then(|v| v.as_str()) {
+        Some("Buy") => size,
+        Some("Sell") => -size,
+        _ => 0.0,
+    };
+    // Ordered against the fills by the cross sequence
+    let seq = item.get("seq").and_then(|v| v.as_u64()).unwrap_or(0);
+    let updated_ms = item.get("updatedTime").and_then(|v| v.as_str()).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    slot_state.strategy.on_position_snapshot(signed_qty, entry_price, seq, updated_ms);
+    if let Some(leverage) = item.get("leverage").and_then(|v| v.as_str()).and_then(|v| v.parse::<f64>().ok()).filter(|l| *l > 0.0) {
+        slot_state.send_gate.leverage = Some(leverage);
+    }
+    Some(slot)
+}
@@ -0,0 +1,199 @@
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+
+// Discrete quoting regimes. Instead of one continuous TPS -> spread line, the market is
+// classified into calm / normal / volatile / extreme and each regime carries a full parameter
+// set, so every quote can be traced back to a named state. Three inputs:
+//   volatility - EMA of the relative mid move per book update, bps
+//   tps        - book updates per second (the strategy's tick-interval EMA)
+//   toxicity   - our own fills inside the fade window (being run over)
+// Each input maps to a level through three ascending thresholds (normal, volatile, extreme);
+// the target regime is the highest level of the three. Hysteresis: a step up is taken at once
+// (risk first), a step down only once every input has stayed below `exit_ratio` of its
+// threshold for `min_dwell_ms`. Every switch is logged with the inputs that caused it.
+const DEFAULT_VOLATILITY_BPS: [f64; 3] = [0.5, 2.0, 5.0];
+const DEFAULT_TPS: [f64; 3] = [20.0, 60.0, 100.0];
+const DEFAULT_TOXICITY: [f64; 3] = [2.0, 4.0, 8.0];
+const DEFAULT_EXIT_RATIO: f64 = 0.8;
+const DEFAULT_MIN_DWELL_MS: u64 = 5_000;
+// Weight of the newest mid move in the volatility EMA
+const VOLATILITY_ALPHA: f64 = 0.1;
+
+pub const REGIME_COUNT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Regime {
+    Calm,
+    Normal,
+    Volatile,
+    Extreme,
+}
+
+impl Regime {
+    pub const ALL: [Regime; REGIME_COUNT] = [Regime::Calm, Regime::Normal, Regime::Volatile, Regime::Extreme];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Regime::Calm => "calm",
+            Regime::Normal => "normal",
+            Regime::Volatile => "volatile",
+            Regime::Extreme => "extreme",
+        }
+    }
+}
+
+/// What a regime quotes with.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegimeParams {
+    /// Distance of the quote from the touch, fraction of price; kept inside the [quote]
+    /// min_spread (less the fee-tier discount) .. max_spread band.
+    pub spread: f64,
+    /// Multiplier on [quote] requote_buffer_pct.
+    pub requote_scale: f64,
+    /// Mid move since the last quote that requotes at once.
+    pub impulse_threshold: f64,
+    /// Multiplier on the clip size.
+    pub size_factor: f64,
+}
+
+impl RegimeParams {
+    const fn new(spread: f64, requote_scale: f64, impulse_threshold: f64, size_factor: f64) -> Self {
+        Self { spread, requote_scale, impulse_threshold, size_factor }
+    }
+}
+
+const CALM: RegimeParams = RegimeParams::new(0.004, 1.0, 0.003, 1.0);
+const NORMAL: RegimeParams = RegimeParams::new(0.006, 1.0, 0.003, 1.0);
+const VOLATILE: RegimeParams = RegimeParams::new(0.008, 1.5, 0.005, 0.75);
+const EXTREME: RegimeParams = RegimeParams::new(0.010, 2.0, 0.005, 0.5);
+
+impl Default for RegimeParams {
+    fn default() -> Self {
+        NORMAL
+    }
+}
+
+/// The `[regimes]` table of the engine config.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegimeConfig {
+    pub calm: RegimeParams,
+    pub normal: RegimeParams,
+    pub volatile: RegimeParams,
+    pub extreme: RegimeParams,
+    /// Entry thresholds of normal, volatile and extreme, per input.
+    pub volatility_bps: [f64; 3],
+    pub tps: [f64; 3],
+    pub toxicity: [f64; 3],
+    /// A step down needs every input below this fraction of its threshold...
+    pub exit_ratio: f64,
+    /// ...for this long.
+    pub min_dwell_ms: u64,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            calm: CALM,
+            normal: NORMAL,
+            volatile: VOLATILE,
+            extreme: EXTREME,
+            volatility_bps: DEFAULT_VOLATILITY_BPS,
+            tps: DEFAULT_TPS,
+            toxicity: DEFAULT_TOXICITY,
+            exit_ratio: DEFAULT_EXIT_RATIO,
+            min_dwell_ms: DEFAULT_MIN_DWELL_MS,
+        }
+    }
+}
+
+impl RegimeConfig {
+    pub fn params(&self, regime: Regime) -> RegimeParams {
+        match regime {
+            Regime::Calm => self.calm,
+            Regime::Normal => self.normal,
+            Regime::Volatile => self.volatile,
+            Regime::Extreme => self.extreme,
+        }
+    }
+
+    /// Regime the inputs point to, thresholds scaled by `scale`.
+    fn target(&self, inputs: &RegimeInputs, scale: f64) -> Regime {
+        let level = |value: f64, thresholds: &[f64; 3]| thresholds.iter().filter(|&&t| value >= t * scale).count();
+        let level = level(inputs.volatility_bps, &self.volatility_bps)
+            .max(level(inputs.tps, &self.tps))
+            .max(level(inputs.toxicity, &self.toxicity));
+        Regime::ALL[level]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RegimeInputs {
+    pub volatility_bps: f64,
+    pub tps: f64,
+    pub toxicity: f64,
+}
+
+/// Owned by the strategy, updated on every tick it quotes on.
+pub struct RegimeClassifier {
+    pub config: RegimeConfig,
+    current: Regime,
+    // Mid of the previous tick and the EMA of the moves between ticks (bps)
+    last_mid: f64,
+    volatility_bps: f64,
+    // Since when the inputs have pointed below the current regime
+    below_since: Option<Instant>,
+    pub switches: u64,
+}
+
+impl RegimeClassifier {
+    /// Starts in `Normal`: neither the tightest nor the widest quote before any data.
+    pub fn new(config: RegimeConfig) -> Self {
+        Self { config, current: Regime::Normal, last_mid: 0.0, volatility_bps: 0.0, below_since: None, switches: 0 }
+    }
+
+    pub fn regime(&self) -> Regime {
+        self.current
+    }
+
+    pub fn params(&self) -> RegimeParams {
+        self.config.params(self.current)
+    }
+
+    /// Feeds the book mid of one tick into the volatility EMA.
+    pub fn on_mid(&mut self, mid: f64) {
+        if self.last_mid > 0.0 && mid > 0.0 {
+            let move_bps = (mid - self.last_mid).abs() / self.last_mid * 10_000.0;
+            self.volatility_bps = VOLATILITY_ALPHA * move_bps + (1.0 - VOLATILITY_ALPHA) * self.volatility_bps;
+        }
+        self.last_mid = mid;
+    }
+
+    pub fn volatility_bps(&self) -> f64 {
+        self.volatility_bps
+    }
+
+    /// Classifies the inputs; Some((from, to)) when the regime switched.
+    pub fn update(&mut self, inputs: &RegimeInputs, now: Instant) -> Option<(Regime, Regime)> {
+        let up = self.config.target(inputs, 1.0);
+        let to = if up > self.current {
+            up
+        } else {
+            let down = self.config.target(inputs, self.config.exit_ratio);
+            if down >= self.current {
+                self.below_since = None;
+                return None;
+            }
+            let since = *self.below_since.get_or_insert(now);
+            if now.saturating_duration_since(since) < Duration::from_millis(self.config.min_dwell_ms) {
+                return None;
+            }
+            down
+        };
+        let from = std::mem::replace(&mut self.current, to);
+        self.below_since = None;
+        self.switches += 1;
+        Some((from, to))
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+// Edge-decay requote timer (replaces the fixed 30 s heartbeat). A resting quote is worth
+// its distance from mid (the quoted spread) plus its place in the queue; amending throws
+// the queue away, so a young quote is only refreshed when the mid has eaten a real part of
+// its edge, and an old one on ever smaller drifts:
+//   Drift   - |mid - quoted mid| / mid >= decay * quoted spread * (1 - age / max_age)
+//   Regime  - the spread we would quote now differs from the quoted one by `regime_shift`
+//             (TPS / fade regime changed under a resting quote)
+//   MaxAge  - backstop, the quote is `max_age` old whatever the market did
+// Drift and Regime wait for `min_age` so a fresh quote keeps its queue through the noise.
+// Moves above the requote buffer (Rule 1) and impulses still requote on their own.
+const DEFAULT_DECAY: f64 = 0.5;
+const DEFAULT_REGIME_SHIFT: f64 = 0.25;
+const DEFAULT_MIN_AGE_MS: u64 = 1_000;
+const DEFAULT_MAX_AGE_MS: u64 = 30_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshReason {
+    Drift,
+    Regime,
+    MaxAge,
+}
+
+impl RefreshReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            RefreshReason::Drift => "Edge decay",
+            RefreshReason::Regime => "Regime",
+            RefreshReason::MaxAge => "Max age",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequoteConfig {
+    /// Fraction of the quoted spread the mid may drift before a fresh quote is refreshed.
+    pub decay: f64,
+    /// Relative change of the target spread that counts as a regime change.
+    pub regime_shift: f64,
+    /// Drift and regime refreshes wait this long after the last quote.
+    pub min_age: Duration,
+    /// Oldest a quote may get; the drift threshold reaches zero here.
+    pub max_age: Duration,
+}
+
+impl Default for RequoteConfig {
+    fn default() -> Self {
+        Self {
+            decay: DEFAULT_DECAY,
+            regime_shift: DEFAULT_REGIME_SHIFT,
+            min_age: Duration::from_millis(DEFAULT_MIN_AGE_MS),
+            max_age: Duration::from_millis(DEFAULT_MAX_AGE_MS),
+        }
+    }
+}
+
+/// Mid, spread and time of the last requote. Owned by the strategy.
+pub struct EdgeDecay {
+    pub config: RequoteConfig,
+    quoted_mid: f64,
+    quoted_spread: f64,
+    quoted_at: Instant,
+}
+
+impl EdgeDecay {
+    /// `now` starts the age clock, as the old heartbeat did from construction.
+    pub fn new(config: RequoteConfig, now: Instant) -> Self {
+        Self { config, quoted_mid: 0.0, quoted_spread: 0.0, quoted_at: now }
+    }
+
+    /// A requote went out at `mid` with `spread`.
+    pub fn on_quote(&mut self, mid: f64, spread: f64, now: Instant) {
+        self.quoted_mid = mid;
+        self.quoted_spread = spread;
+        self.quoted_at = now;
+    }
+
+    /// Whether the resting quote has lost enough edge to refresh; `spread` is what we would
+    /// quote now.
+    pub fn due(&self, mid: f64, spread: f64, now: Instant) -> Option<RefreshReason> {
+        let age = now.saturating_duration_since(self.quoted_at);
+        let max_age = self.config.max_age.as_secs_f64();
+        if max_age <= 0.0 || age >= self.config.max_age {
+            return Some(RefreshReason::MaxAge);
+        }
+        // Nothing quoted yet: only the backstop applies
+        if self.quoted_mid <= 0.0 || self.quoted_spread <= 0.0 || age < self.config.min_age {
+            return None;
+        }
+        let drift = (mid - self.quoted_mid).abs() / self.quoted_mid;
+        let left = 1.0 - age.as_secs_f64() / max_age;
+        if drift >= self.config.decay * self.quoted_spread * left {
+            return Some(RefreshReason::Drift);
+        }
+        if (spread - self.quoted_spread).abs() >= self.config.regime_shift * self.quoted_spread {
+            return Some(RefreshReason::Regime);
+        }
+        None
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+use crate::strategy::market_maker::ActionType;
+
+// Minimal order entry without the Trade WS. During an exchange WS incident (`fallback`: while
+// the Trade WS is down) or for a symbol too slow to be worth a session (`always`) quotes go
+// out as signed REST calls from the REST thread. REST costs a round trip per request and the
+// same rate limit as everything else, so the loop is slow on purpose: one create or amend
+// per side per `interval`; cancels, closes and cancel-alls are never held back. The book keeps
+// coming over the public WS and fills over the private WS; open orders are polled every `poll`
+// so an order that left the book without a private push still frees its side.
+const DEFAULT_INTERVAL_MS: u64 = 1_000;
+const DEFAULT_POLL_MS: u64 = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestQuotingMode {
+    /// Quotes only over the Trade WS (REST carries risk pulls, as before).
+    Off,
+    /// Quotes over REST while the Trade WS is down.
+    Fallback,
+    /// Quotes over REST, Trade WS or not.
+    Always,
+}
+
+impl RestQuotingMode {
+    /// `off` | `fallback` | `always` (HFT_REST_QUOTING).
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec.trim() {
+            "off" => Some(RestQuotingMode::Off),
+            "fallback" => Some(RestQuotingMode::Fallback),
+            "always" => Some(RestQuotingMode::Always),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RestQuotingConfig {
+    pub mode: RestQuotingMode,
+    /// Minimum time between two creates/amends of one side.
+    pub interval: Duration,
+    /// Open-order poll period while quoting over REST.
+    pub poll: Duration,
+}
+
+impl Default for RestQuotingConfig {
+    fn default() -> Self {
+        Self {
+            mode: RestQuotingMode::Off,
+            interval: Duration::from_millis(DEFAULT_INTERVAL_MS),
+            poll: Duration::from_millis(DEFAULT_POLL_MS),
+        }
+    }
+}
+
+// Create/amend side the pacing applies to
+fn paced_side(action: &ActionType) -> Option<usize> {
+    match action {
+        ActionType::CreateOrder { side, .. }
+        | ActionType::AmendOrder { side, .. }
+        | ActionType::AmendPrice { side, .. }
+        | ActionType::AmendQty { side, .. } => Some(if *side == "Buy" { 0 } else { 1 }),
+        _ => None,
+    }
+}
+
+/// Owned by the Hot Thread, shared by every quoted symbol (one REST thread, one rate limit).
+#[derive(Debug)]
+pub struct RestQuoting {
+    pub config: RestQuotingConfig,
+    active: bool,
+    last_sent: [Option<Instant>; 2],
+    last_poll: Option<Instant>,
+    /// Quote requests handed to the REST thread.
+    pub sent: u64,
+    /// Creates/amends held back by the pacing.
+    pub paced: u64,
+}
+
+impl RestQuoting {
+    pub fn new(config: RestQuotingConfig) -> Self {
+        Self { config, active: config.mode == RestQuotingMode::Always, last_sent: [None; 2], last_poll: None, sent: 0, paced: 0 }
+    }
+
+    /// Quotes go over REST right now.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Re-evaluated every loop with the Trade WS health; Some(new state) on a switch.
+    pub fn update(&mut self, trade_ws_down: bool) -> Option<bool> {
+        let active = match self.config.mode {
+            RestQuotingMode::Off => false,
+            RestQuotingMode::Fallback => trade_ws_down,
+            RestQuotingMode::Always => true,
+        };
+        if active == self.active {
+            return None;
+        }
+        self.active = active;
+        self.last_poll = None;
+        Some(active)
+    }
+
+    /// `action` may go out now. False for a create/amend whose side sent one less than
+    /// `interval` ago: the caller defers it like a gate veto and the strategy retries.
+    pub fn ready(&mut self, action: &ActionType, now: Instant) -> bool {
+        let Some(idx) = paced_side(action) else { return true };
+        let ready = self.last_sent[idx].is_none_or(|ts| now.saturating_duration_since(ts) >= self.config.interval);
+        if !ready {
+            self.paced += 1;
+        }
+        ready
+    }
+
+    /// `action` was handed to the REST thread.
+    pub fn on_sent(&mut self, action: &ActionType, now: Instant) {
+        self.sent += 1;
+        if let Some(idx) = paced_side(action) {
+            self.last_sent[idx] = Some(now);
+        }
+    }
+
+    /// Time for an open-order poll (only while active).
+    pub fn poll_due(&mut self, now: Instant) -> bool {
+        if !self.active || self.last_poll.is_some_and(|ts| now.saturating_duration_since(ts) < self.config.poll) {
+            return false;
+        }
+        self.last_poll = Some(now);
+        true
+    }
+}
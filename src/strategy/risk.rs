@@ -1,34 +1,70 @@
-use std::time::{Instant, Duration};
+use std::time::Instant;
+use serde::Deserialize;
+use crate::core::clock::{Clock, LiveClock};
 
 // HFT Rules:
-// DEV_MODE = true  -> Relaxed Latency Checks (Windows/Test)
-// DEV_MODE = false -> Strict HFT Rules (Linux/AWS/Prod) -> Panic on >50us latency
+// dev_mode = true  -> Relaxed Latency Checks (Windows/Test)
+// dev_mode = false -> Strict HFT Rules (Linux/AWS/Prod) -> Panic on >50us latency
 
-// Set to FALSE only before deploying to AWS Singapore!
+// Set to FALSE only before deploying to AWS Singapore! (`[risk] dev_mode` in the config file)
 const DEV_MODE: bool = true;
 
-const MAX_INTERNAL_LATENCY_MICROS: u128 = 50;
-const MAX_NETWORK_LATENCY_MS: u128 = 300;
+const MAX_INTERNAL_LATENCY_MICROS: u64 = 50;
+const DEV_INTERNAL_LATENCY_MICROS: u64 = 5000;
+const MAX_NETWORK_LATENCY_MS: u64 = 300;
 
-pub struct RiskEngine {
+/// Latency limits. The `[risk]` table of the engine config.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskConfig {
+    pub dev_mode: bool,
+    /// Tick processing limit in strict mode (panics above it).
+    pub max_internal_latency_us: u64,
+    /// Tick processing limit in dev mode (logged only).
+    pub dev_internal_latency_us: u64,
+    pub max_network_latency_ms: u64,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            dev_mode: DEV_MODE,
+            max_internal_latency_us: MAX_INTERNAL_LATENCY_MICROS,
+            dev_internal_latency_us: DEV_INTERNAL_LATENCY_MICROS,
+            max_network_latency_ms: MAX_NETWORK_LATENCY_MS,
+        }
+    }
+}
+
+pub struct RiskEngine<C: Clock = LiveClock> {
+    pub config: RiskConfig,
     pub consecutive_errors: u32,
     pub last_packet_ts: Instant,
+    clock: C,
 }
 
 impl RiskEngine {
     pub fn new() -> Self {
+        Self::with_clock(LiveClock)
+    }
+}
+
+impl<C: Clock> RiskEngine<C> {
+    pub fn with_clock(clock: C) -> Self {
         Self {
+            config: RiskConfig::default(),
             consecutive_errors: 0,
-            last_packet_ts: Instant::now(),
+            last_packet_ts: clock.monotonic_now(),
+            clock,
         }
     }
 
     pub fn update_packet_time(&mut self) {
-        let now = Instant::now();
-        if !DEV_MODE {
+        let now = self.clock.monotonic_now();
+        if !self.config.dev_mode {
              // In real env, check if socket was silent for too long
              let diff = now.duration_since(self.last_packet_ts).as_millis();
-             if diff > MAX_NETWORK_LATENCY_MS {
+             if diff > self.config.max_network_latency_ms as u128 {
                  // For now just log warning, in prod -> disconnect
                  // eprintln!("RISK: Network Lag detected: {}ms", diff);
              }
@@ -37,14 +73,14 @@ impl RiskEngine {
     }
 
     pub fn check_internal_latency(&self, start: Instant) {
-        let elapsed = start.elapsed().as_micros();
-        let limit = if DEV_MODE { 5000 } else { MAX_INTERNAL_LATENCY_MICROS };
+        let elapsed = self.clock.monotonic_now().saturating_duration_since(start).as_micros();
+        let limit = if self.config.dev_mode { self.config.dev_internal_latency_us } else { self.config.max_internal_latency_us } as u128;
 
         if elapsed > limit {
             eprintln!("RISK CRITICAL: Tick processing took {}us (Limit: {}us)", elapsed, limit);
             // In strict mode -> PANIC to kill the process and strict restart
             // On Windows (Dev Mode), we just log it.
-            if !DEV_MODE {
+            if !self.config.dev_mode {
                 panic!("Latency violation");
             }
         }
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::core::clock::{Clock, SimClock};
+    use crate::strategy::risk::{RiskConfig, RiskEngine};
+
+    fn strict(clock: &SimClock) -> RiskEngine<&SimClock> {
+        let mut risk = RiskEngine::with_clock(clock);
+        risk.config = RiskConfig { dev_mode: false, ..RiskConfig::default() };
+        risk
+    }
+
+    #[test]
+    fn packet_time_follows_the_clock() {
+        let clock = SimClock::new(1_700_000_000_000);
+        let mut risk = strict(&clock);
+        assert_eq!(risk.last_packet_ts, clock.monotonic_now());
+        // A silent socket is only noted, the timestamp still moves
+        clock.advance(Duration::from_secs(2));
+        risk.update_packet_time();
+        assert_eq!(risk.last_packet_ts, clock.monotonic_now());
+    }
+
+    #[test]
+    fn dev_mode_only_logs_a_slow_tick() {
+        let clock = SimClock::new(1_700_000_000_000);
+        let risk = RiskEngine::with_clock(&clock);
+        assert!(risk.config.dev_mode);
+        let start = clock.monotonic_now();
+        clock.advance(Duration::from_micros(risk.config.dev_internal_latency_us * 2));
+        risk.check_internal_latency(start);
+    }
+
+    #[test]
+    fn strict_mode_passes_a_tick_within_the_limit() {
+        let clock = SimClock::new(1_700_000_000_000);
+        let risk = strict(&clock);
+        let start = clock.monotonic_now();
+        clock.advance(Duration::from_micros(risk.config.max_internal_latency_us));
+        risk.check_internal_latency(start);
+    }
+
+    #[test]
+    #[should_panic(expected = "Latency violation")]
+    fn strict_mode_panics_on_a_slow_tick() {
+        let clock = SimClock::new(1_700_000_000_000);
+        let risk = strict(&clock);
+        let start = clock.monotonic_now();
+        clock.advance(Duration::from_micros(risk.config.max_internal_latency_us + 1));
+        risk.check_internal_latency(start);
+    }
+}
@@ -0,0 +1,163 @@
+// Taker fees (market exits pay taker). Bybit linear non-VIP / Binance USDⓈ-M non-VIP / OKX SWAP Lv1.
+const BYBIT_TAKER_FEE: f64 = 0.00055;
+const BINANCE_TAKER_FEE: f64 = 0.0005;
+const OKX_TAKER_FEE: f64 = 0.0005;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Venue {
+    Bybit,
+    Binance,
+    Okx,
+}
+
+pub(crate) const VENUE_COUNT: usize = 3;
+
+impl Venue {
+    pub(crate) fn idx(self) -> usize {
+        match self {
+            Venue::Bybit => 0,
+            Venue::Binance => 1,
+            Venue::Okx => 2,
+        }
+    }
+}
+
+/// Top of book + cost of crossing on one venue.
+#[derive(Debug, Clone, Copy)]
+pub struct VenueQuote {
+    pub venue: Venue,
+    pub bid: f64,
+    pub ask: f64,
+    pub taker_fee: f64,
+    /// We hold an order-entry session on this venue. Market data alone isn't enough to route.
+    pub tradable: bool,
+}
+
+/// A venue and what a market order there is expected to cost, fees included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Route {
+    pub venue: Venue,
+    pub effective_px: f64,
+}
+
+/// orderLinkId prefix of the Bybit hedge unwinds; their own executions are not hedged again.
+pub const HEDGE_UNWIND_TAG: &str = "hu-";
+
+/// How the hedge of one Bybit fill is executed: `side`, base coin per venue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HedgePlan {
+    pub side: &'static str,
+    /// Market order on Binance: opens or unwinds the Binance leg.
+    pub binance: f64,
+    /// Reduce-only market order on Bybit: the fill is unwound where it happened.
+    pub bybit: f64,
+}
+
+/// Best-execution router for position-reducing (hedge/unwind) orders of one symbol.
+/// Compares the fee-adjusted executable price on every tradable venue with a live quote
+/// (`route`) and sends the hedge of a Bybit fill to the best one (`plan_hedge`); OKX quotes
+/// count for `best` only, there is no order entry there. Keeps the Binance leg the hedges
+/// built, so shrinking the Bybit position unwinds it there. Fixed-size, no allocation: safe
+/// on the Hot Thread.
+pub struct ExecutionRouter {
+    quotes: [VenueQuote; VENUE_COUNT],
+    /// Signed base coin sent to Binance by hedges (positive = long there).
+    binance_leg: f64,
+}
+
+impl ExecutionRouter {
+    pub fn new() -> Self {
+        Self {
+            quotes: [
+                VenueQuote { venue: Venue::Bybit, bid: 0.0, ask: 0.0, taker_fee: BYBIT_TAKER_FEE, tradable: true },
+                VenueQuote { venue: Venue::Binance, bid: 0.0, ask: 0.0, taker_fee: BINANCE_TAKER_FEE, tradable: false },
+                VenueQuote { venue: Venue::Okx, bid: 0.0, ask: 0.0, taker_fee: OKX_TAKER_FEE, tradable: false },
+            ],
+            binance_leg: 0.0,
+        }
+    }
+
+    /// Binance becomes tradable once the HEDGE thread holds a `BinanceClient` for the symbol.
+    pub fn set_tradable(&mut self, venue: Venue, tradable: bool) {
+        self.quotes[venue.idx()].tradable = tradable;
+    }
+
+    pub fn binance_leg(&self) -> f64 {
+        self.binance_leg
+    }
+
+    pub fn update_quote(&mut self, venue: Venue, bid: f64, ask: f64) {
+        let q = &mut self.quotes[venue.idx()];
+        q.bid = bid;
+        q.ask = ask;
+    }
+
+    /// Fee-adjusted price of a market order on `side` at `venue` ("Buy" lifts the ask,
+    /// "Sell" hits the bid). None without a live quote.
+    pub fn effective_px(&self, venue: Venue, side: &str) -> Option<f64> {
+        Self::cost(&self.quotes[venue.idx()], side == "Buy")
+    }
+
+    fn cost(q: &VenueQuote, buying: bool) -> Option<f64> {
+        (q.bid > 0.0 && q.ask > 0.0).then_some(if buying { q.ask * (1.0 + q.taker_fee) } else { q.bid * (1.0 - q.taker_fee) })
+    }
+
+    /// Cheapest venue for a market order on `side` among those with a live quote.
+    pub fn best(&self, side: &str) -> Option<Route> {
+        self.cheapest(side, false)
+    }
+
+    /// Where a reducing market order on `side` goes: the cheapest tradable venue with a live
+    /// quote. None before any tradable venue has one.
+    pub fn route(&self, side: &str) -> Option<Route> {
+        self.cheapest(side, true)
+    }
+
+    /// Hedge of a Bybit fill of `qty` base coin on `fill_side`, `ratio` of it, on the opposite
+    /// side. A fill that grows the Bybit position (`opening`) is hedged on the `route` venue:
+    /// Binance when it is cheaper (or no quote is live yet), else Bybit itself. A fill that
+    /// shrinks it only unwinds what the Binance leg holds. A symbol Binance can't trade is
+    /// not hedged at all.
+    pub fn plan_hedge(&mut self, fill_side: &str, qty: f64, ratio: f64, opening: bool) -> HedgePlan {
+        let side = if fill_side == "Buy" { "Sell" } else { "Buy" };
+        let sign = if side == "Buy" { 1.0 } else { -1.0 };
+        let wanted = qty * ratio;
+        let mut plan = HedgePlan { side, binance: 0.0, bybit: 0.0 };
+        if !self.quotes[Venue::Binance.idx()].tradable {
+            return plan;
+        }
+        if !opening {
+            // Only a leg on the other side can be unwound by this order
+            plan.binance = if self.binance_leg * sign < 0.0 { wanted.min(self.binance_leg.abs()) } else { 0.0 };
+        } else {
+            match self.route(side).map(|route| route.venue) {
+                Some(Venue::Bybit) => plan.bybit = wanted,
+                _ => plan.binance = wanted,
+            }
+        }
+        self.binance_leg += sign * plan.binance;
+        plan
+    }
+
+    fn cheapest(&self, side: &str, tradable_only: bool) -> Option<Route> {
+        let buying = side == "Buy";
+        let mut best: Option<Route> = None;
+        for q in self.quotes.iter().filter(|q| q.tradable || !tradable_only) {
+            let Some(effective_px) = Self::cost(q, buying) else { continue };
+            let better = match best {
+                Some(b) => if buying { effective_px < b.effective_px } else { effective_px > b.effective_px },
+                None => true,
+            };
+            if better {
+                best = Some(Route { venue: q.venue, effective_px });
+            }
+        }
+        best
+    }
+}
+
+impl Default for ExecutionRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
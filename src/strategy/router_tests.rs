@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::strategy::router::{ExecutionRouter, HedgePlan, Venue};
+
+    fn router(binance_tradable: bool) -> ExecutionRouter {
+        let mut router = ExecutionRouter::new();
+        router.set_tradable(Venue::Binance, binance_tradable);
+        router.update_quote(Venue::Bybit, 100.0, 100.1);
+        router
+    }
+
+    #[test]
+    fn route_skips_venues_without_order_entry() {
+        let mut router = router(false);
+        router.update_quote(Venue::Binance, 100.2, 100.3);
+        router.update_quote(Venue::Okx, 100.4, 100.5);
+        // OKX pays the most for a sell but is never tradable; Binance not yet either
+        assert_eq!(router.best("Sell").map(|r| r.venue), Some(Venue::Okx));
+        assert_eq!(router.route("Sell").map(|r| r.venue), Some(Venue::Bybit));
+        router.set_tradable(Venue::Binance, true);
+        assert_eq!(router.route("Sell").map(|r| r.venue), Some(Venue::Binance));
+        // Buying is cheaper on Bybit even after its higher taker fee
+        assert_eq!(router.route("Buy").map(|r| r.venue), Some(Venue::Bybit));
+    }
+
+    #[test]
+    fn opening_fill_is_hedged_on_the_cheaper_venue() {
+        let mut router = router(true);
+        router.update_quote(Venue::Binance, 100.2, 100.3);
+        // Bought on Bybit: selling pays more on Binance
+        assert_eq!(router.plan_hedge("Buy", 2.0, 0.5, true), HedgePlan { side: "Sell", binance: 1.0, bybit: 0.0 });
+        assert_eq!(router.binance_leg(), -1.0);
+        // Sold on Bybit: buying back there beats Binance's ask, the leg is untouched
+        assert_eq!(router.plan_hedge("Sell", 1.0, 1.0, true), HedgePlan { side: "Buy", binance: 0.0, bybit: 1.0 });
+        assert_eq!(router.binance_leg(), -1.0);
+    }
+
+    #[test]
+    fn reducing_fill_only_unwinds_the_binance_leg() {
+        let mut router = router(true);
+        router.update_quote(Venue::Binance, 100.2, 100.3);
+        router.plan_hedge("Buy", 1.0, 1.0, true);
+        // Selling off the Bybit long buys back at most the 1.0 short on Binance
+        assert_eq!(router.plan_hedge("Sell", 1.5, 1.0, false), HedgePlan { side: "Buy", binance: 1.0, bybit: 0.0 });
+        assert_eq!(router.binance_leg(), 0.0);
+        assert_eq!(router.plan_hedge("Sell", 1.0, 1.0, false), HedgePlan { side: "Buy", binance: 0.0, bybit: 0.0 });
+    }
+
+    #[test]
+    fn without_live_quotes_the_hedge_goes_to_binance() {
+        let mut hedged = ExecutionRouter::new();
+        hedged.set_tradable(Venue::Binance, true);
+        assert_eq!(hedged.plan_hedge("Buy", 1.0, 1.0, true).binance, 1.0);
+    }
+
+    #[test]
+    fn symbol_without_binance_order_entry_is_not_hedged() {
+        let mut router = router(false);
+        router.update_quote(Venue::Binance, 100.2, 100.3);
+        assert_eq!(router.plan_hedge("Buy", 1.0, 1.0, true), HedgePlan { side: "Sell", binance: 0.0, bybit: 0.0 });
+        assert_eq!(router.binance_leg(), 0.0);
+    }
+}
@@ -0,0 +1,343 @@
+use std::time::{Duration, Instant};
+use crate::core::clock::Clock;
+use crate::core::rounding::on_grid;
+use crate::strategy::margin::{self, MarginTracker};
+use crate::strategy::market_maker::ActionType;
+use crate::strategy::order_manager::{self, OrderManager, Veto};
+use crate::strategy::side_throttle::{SideThrottle, ThrottleConfig};
+use crate::strategy::symbols::Instrument;
+
+// Two-phase submission. Every action the strategy produces goes through `pre_send`
+// before it is serialized: a fixed chain of hooks, run in `GateStage` order, the first
+// veto wins. Once the action is committed to a route (Trade WS, queue or REST),
+// `post_send` does the bookkeeping (OMS order state, rate window) and returns what it
+// committed; an action dropped on the way (queue full, send error, expired in the queue)
+// is handed back to `on_dropped`, which undoes the OMS state and the margin reservation.
+// Risk pulls (CancelAll / CancelOrder / ClosePosition / SetTradingStop) skip the kill
+// switch and the rate limiter: a broken request is useless, a throttled pull is dangerous.
+// Bybit's default order-entry limit is 10 requests per second.
+const DEFAULT_MAX_ORDERS_PER_SEC: usize = 10;
+const RATE_SLOTS: usize = 64;
+// Bybit caps orderLinkId at 36 characters.
+const MAX_LINK_ID_LEN: usize = 36;
+
+/// Hook order of the pre-send chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateStage {
+    KillSwitch,
+    Validator,
+    Throttle,
+    Risk,
+    Margin,
+    RateLimit,
+}
+
+const STAGE_COUNT: usize = 6;
+
+impl GateStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            GateStage::KillSwitch => "kill-switch",
+            GateStage::Validator => "validator",
+            GateStage::Throttle => "side-throttle",
+            GateStage::Risk => "risk",
+            GateStage::Margin => "margin",
+            GateStage::RateLimit => "rate-limit",
+        }
+    }
+}
+
+/// Why an action was not sent, by the hook that stopped it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GateVeto {
+    /// Kill switch engaged: nothing may add or move a quote.
+    KillSwitch,
+    /// Malformed request (bad price/qty, off the instrument grid, bad link id).
+    Invalid { reason: &'static str },
+    /// The side is cooling down after a create/reject loop (`side_throttle.rs`).
+    Throttled { side: &'static str, remaining_ms: u64 },
+    /// OrderManager gate (price band, reduce-only, self-match, quote age, queue priority).
+    Risk(Veto),
+    /// The order's estimated initial margin does not fit the available balance (settle coin).
+    Margin { side: &'static str, required: f64, headroom: f64 },
+    /// Too many requests in the last second.
+    RateLimited { sent: usize, limit: usize },
+}
+
+impl GateVeto {
+    pub fn stage(&self) -> GateStage {
+        match self {
+            GateVeto::KillSwitch => GateStage::KillSwitch,
+            GateVeto::Invalid { .. } => GateStage::Validator,
+            GateVeto::Throttled { .. } => GateStage::Throttle,
+            GateVeto::Risk(_) => GateStage::Risk,
+            GateVeto::Margin { .. } => GateStage::Margin,
+            GateVeto::RateLimited { .. } => GateStage::RateLimit,
+        }
+    }
+
+    /// Whether the strategy should retry the same action on the next tick.
+    pub fn retry(&self) -> bool {
+        match self {
+            GateVeto::Risk(veto) => veto.retry(),
+            // A fill, cancel or wallet update frees margin by itself
+            GateVeto::Throttled { .. } | GateVeto::Margin { .. } | GateVeto::RateLimited { .. } => true,
+            GateVeto::KillSwitch | GateVeto::Invalid { .. } => false,
+        }
+    }
+
+    /// Vetoes that point at a bug or an operator action, not normal flow control.
+    pub fn loud(&self) -> bool {
+        matches!(self, GateVeto::Invalid { .. } | GateVeto::Risk(Veto::PriceBand { .. }))
+    }
+}
+
+/// What `post_send` committed for an action, kept until the request is on the wire.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Committed {
+    /// The OMS counts a new order on `side`; `reserved` margin is taken.
+    Create { side: &'static str, reserved: f64 },
+    /// The OMS holds a pending amend on `side`; `reserved` margin is taken.
+    Amend { side: &'static str, reserved: f64 },
+    /// CancelAll / CancelOrder / ClosePosition / SetTradingStop: the OMS already counts the
+    /// orders as gone, so losing one leaves risk the engine no longer sees.
+    RiskPull,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SendGateConfig {
+    /// Quote requests (create/amend) per rolling second; clamped to RATE_SLOTS.
+    pub max_orders_per_sec: usize,
+    /// Per-side loop breaker.
+    pub throttle: ThrottleConfig,
+}
+
+impl Default for SendGateConfig {
+    fn default() -> Self {
+        Self { max_orders_per_sec: DEFAULT_MAX_ORDERS_PER_SEC, throttle: ThrottleConfig::default() }
+    }
+}
+
+fn is_risk_pull(action: &ActionType) -> bool {
+    matches!(action, ActionType::CancelAll | ActionType::CancelOrder { .. } | ActionType::ClosePosition { .. } | ActionType::SetTradingStop { .. })
+}
+
+fn invalid(reason: &'static str) -> Result<(), GateVeto> {
+    Err(GateVeto::Invalid { reason })
+}
+
+fn check_link_id(link_id: &str) -> Result<(), GateVeto> {
+    if link_id.is_empty() || link_id.len() > MAX_LINK_ID_LEN {
+        return invalid("bad orderLinkId");
+    }
+    Ok(())
+}
+
+/// Owned by the Hot Thread. Fixed arrays only.
+pub struct SendGate {
+    pub config: SendGateConfig,
+    /// Set by the Hot Thread from the admin `kill` command.
+    pub kill_switch: bool,
+    /// This symbol's leverage from the position topic; None = `MarginConfig::leverage`.
+    pub leverage: Option<f64>,
+    /// Benches a side stuck in a create/reject loop; rejects are fed by the Hot Thread.
+    pub throttle: SideThrottle,
+    instrument: Instrument,
+    // Send times of the last requests (ring), for the rolling one-second window
+    sent_ts: [Option<Instant>; RATE_SLOTS],
+    next_slot: usize,
+    vetoes: [u64; STAGE_COUNT],
+}
+
+impl SendGate {
+    pub fn new(config: SendGateConfig, instrument: Instrument) -> Self {
+        Self {
+            config,
+            kill_switch: false,
+            leverage: None,
+            throttle: SideThrottle::new(config.throttle),
+            instrument,
+            sent_ts: [None; RATE_SLOTS],
+            next_slot: 0,
+            vetoes: [0; STAGE_COUNT],
+        }
+    }
+
+    /// Vetoes so far by the hook at `stage`.
+    pub fn vetoes(&self, stage: GateStage) -> u64 {
+        self.vetoes[stage as usize]
+    }
+
+    /// Phase 1: kill switch -> validator -> side throttle -> risk -> margin -> rate limiter. Err = do not send.
+    pub fn pre_send<C: Clock>(&mut self, action: &ActionType, oms: &OrderManager<C>, margin: &MarginTracker, now: Instant) -> Result<(), GateVeto> {
+        let result = self.kill_switch_hook(action)
+            .and_then(|_| self.validate(action, oms))
+            .and_then(|_| self.throttle_hook(action, now))
+            .and_then(|_| oms.check(action, now).map_err(GateVeto::Risk))
+            .and_then(|_| self.margin_hook(action, oms, margin))
+            .and_then(|_| self.rate_limit(action, now));
+        if let Err(veto) = result {
+            self.vetoes[veto.stage() as usize] += 1;
+        }
+        result
+    }
+
+    /// Phase 2: the action left the gate on some route. Tracks the order state, the margin it
+    /// takes and the rate window.
+    pub fn post_send<C: Clock>(&mut self, action: &ActionType, oms: &mut OrderManager<C>, margin: &mut MarginTracker, now: Instant) -> Committed {
+        let reserved = self.required_margin(action, oms, margin).map_or(0.0, |(_, required)| required);
+        margin.reserve(reserved);
+        oms.on_sent(action, now);
+        if let ActionType::CreateOrder { side, .. } = action {
+            self.throttle.on_create(side, now);
+        }
+        let committed = match *action {
+            ActionType::CreateOrder { side, .. } => Committed::Create { side, reserved },
+            ActionType::AmendOrder { side, .. } | ActionType::AmendPrice { side, .. } | ActionType::AmendQty { side, .. } => {
+                Committed::Amend { side, reserved }
+            }
+            ref pull if is_risk_pull(pull) => Committed::RiskPull,
+            _ => Committed::Other,
+        };
+        if !matches!(action, ActionType::None) {
+            self.sent_ts[self.next_slot] = Some(now);
+            self.next_slot = (self.next_slot + 1) % RATE_SLOTS;
+        }
+        committed
+    }
+
+    /// The action behind `committed` never reached the exchange: its margin is freed and the
+    /// OMS forgets the create (the order never rested) or the pending amend. Returns the price
+    /// an amended order still rests at (None for anything else). A lost risk pull has nothing
+    /// to undo here: the caller must retry it or stop trading.
+    pub fn on_dropped<C: Clock>(&mut self, committed: Committed, oms: &mut OrderManager<C>, margin: &mut MarginTracker) -> Option<f64> {
+        match committed {
+            Committed::Create { side, reserved } => {
+                margin.release(reserved);
+                oms.on_order_closed(side);
+                None
+            }
+            Committed::Amend { side, reserved } => {
+                margin.release(reserved);
+                Some(oms.on_amend_rejected(side))
+            }
+            Committed::RiskPull | Committed::Other => None,
+        }
+    }
+
+    fn kill_switch_hook(&self, action: &ActionType) -> Result<(), GateVeto> {
+        if self.kill_switch && !is_risk_pull(action) {
+            return Err(GateVeto::KillSwitch);
+        }
+        Ok(())
+    }
+
+    fn throttle_hook(&self, action: &ActionType, now: Instant) -> Result<(), GateVeto> {
+        if is_risk_pull(action) {
+            return Ok(());
+        }
+        let Some(side) = order_manager::action_side(action) else { return Ok(()) };
+        match self.throttle.cooling(side, now) {
+            Some(left) => Err(GateVeto::Throttled { side, remaining_ms: left.as_millis() as u64 }),
+            None => Ok(()),
+        }
+    }
+
+    fn validate<C: Clock>(&self, action: &ActionType, oms: &OrderManager<C>) -> Result<(), GateVeto> {
+        match action {
+            ActionType::CreateOrder { price, qty, link_id, .. } | ActionType::AmendOrder { price, qty, link_id, .. } => {
+                self.check_price(*price)?;
+                self.check_qty(*qty, *price)?;
+                check_link_id(link_id)?;
+            }
+            ActionType::AmendPrice { price, link_id, .. } => {
+                self.check_price(*price)?;
+                check_link_id(link_id)?;
+            }
+            ActionType::AmendQty { qty, side, link_id } => {
+                // The order keeps its price: that is what converts the qty (inverse)
+                self.check_qty(*qty, oms.latest(side).0)?;
+                check_link_id(link_id)?;
+            }
+            ActionType::CancelOrder { link_id } => check_link_id(link_id)?,
+            ActionType::ClosePosition { qty, .. } => {
+                if !qty.is_finite() || *qty <= 0.0 {
+                    return invalid("close qty not positive");
+                }
+            }
+            ActionType::SetTradingStop { price, .. } => {
+                if !price.is_finite() || *price <= 0.0 {
+                    return invalid("stop price not positive");
+                }
+            }
+            ActionType::CancelAll => {}
+            ActionType::None => return invalid("empty action"),
+        }
+        Ok(())
+    }
+
+    fn check_price(&self, price: f64) -> Result<(), GateVeto> {
+        if !price.is_finite() || price <= 0.0 {
+            return invalid("price not positive");
+        }
+        if !on_grid(price, self.instrument.tick_size) {
+            return invalid("price off tick grid");
+        }
+        Ok(())
+    }
+
+    fn check_qty(&self, qty: f64, price: f64) -> Result<(), GateVeto> {
+        // Checked in the exchange's unit (USD contracts for inverse)
+        let qty = self.instrument.order_qty(qty, price);
+        if !qty.is_finite() || qty < self.instrument.min_qty {
+            return invalid("qty below instrument minimum");
+        }
+        if !on_grid(qty, self.instrument.qty_step) {
+            return invalid("qty off lot grid");
+        }
+        Ok(())
+    }
+
+    fn margin_hook<C: Clock>(&self, action: &ActionType, oms: &OrderManager<C>, margin: &MarginTracker) -> Result<(), GateVeto> {
+        let Some((side, required)) = self.required_margin(action, oms, margin) else { return Ok(()) };
+        margin.check(required).map_err(|headroom| GateVeto::Margin { side, required, headroom })
+    }
+
+    /// Extra initial margin a create/amend that grows the position takes over what the order
+    /// already holds. None for reducing quotes, risk pulls and symbols settled in another coin.
+    fn required_margin<C: Clock>(&self, action: &ActionType, oms: &OrderManager<C>, margin: &MarginTracker) -> Option<(&'static str, f64)> {
+        let (side, new_px, new_qty, amend) = match *action {
+            ActionType::CreateOrder { side, price, qty, .. } => (side, price, qty, false),
+            ActionType::AmendOrder { side, price, qty, .. } => (side, price, qty, true),
+            ActionType::AmendPrice { side, price, .. } => (side, price, oms.latest(side).1, true),
+            ActionType::AmendQty { side, qty, .. } => (side, oms.latest(side).0, qty, true),
+            _ => return None,
+        };
+        if oms.reduces_position(side) || self.instrument.settle_coin() != margin.coin {
+            return None;
+        }
+        let leverage = self.leverage.unwrap_or(margin.config.leverage);
+        let (old_px, old_qty) = if amend { oms.latest(side) } else { (0.0, 0.0) };
+        let required = margin::initial_margin(&self.instrument, new_qty, new_px, leverage)
+            - margin::initial_margin(&self.instrument, old_qty, old_px, leverage);
+        (required > 0.0).then_some((side, required))
+    }
+
+    fn rate_limit(&self, action: &ActionType, now: Instant) -> Result<(), GateVeto> {
+        if is_risk_pull(action) {
+            return Ok(());
+        }
+        let limit = self.config.max_orders_per_sec.min(RATE_SLOTS);
+        let sent = self
+            .sent_ts
+            .iter()
+            .flatten()
+            .filter(|&&ts| now.saturating_duration_since(ts) < Duration::from_secs(1))
+            .count();
+        if sent >= limit {
+            return Err(GateVeto::RateLimited { sent, limit });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::strategy::margin::{MarginConfig, MarginTracker};
+    use crate::strategy::market_maker::ActionType;
+    use crate::strategy::order_manager::{OrderManager, OrderManagerConfig};
+    use crate::strategy::send_gate::{Committed, GateStage, GateVeto, SendGate, SendGateConfig};
+    use crate::strategy::symbols::{SymbolRegistry, PRIMARY_SYMBOL};
+
+    fn gate() -> SendGate {
+        SendGate::new(SendGateConfig::default(), *SymbolRegistry::default().get(PRIMARY_SYMBOL).unwrap())
+    }
+
+    // 10 USDT at 10x, 9 usable
+    fn margin() -> MarginTracker {
+        let mut margin = MarginTracker::new(MarginConfig { leverage: 10.0, buffer: 0.1 }, "USDT");
+        margin.on_balance(10.0);
+        margin
+    }
+
+    fn create(side: &'static str, price: f64) -> ActionType {
+        ActionType::CreateOrder { price, qty: 10.0, side, link_id: format!("{}-1", if side == "Buy" { "b" } else { "s" }) }
+    }
+
+    fn headroom(margin: &MarginTracker) -> f64 {
+        margin.headroom().unwrap()
+    }
+
+    #[test]
+    fn kill_switch_stops_quotes_but_not_risk_pulls() {
+        let mut gate = gate();
+        let oms = OrderManager::new(OrderManagerConfig::default());
+        let margin = margin();
+        let now = Instant::now();
+        gate.kill_switch = true;
+
+        let veto = gate.pre_send(&create("Buy", 5.0), &oms, &margin, now).unwrap_err();
+        assert_eq!(veto, GateVeto::KillSwitch);
+        assert!(!veto.retry());
+        for pull in [
+            ActionType::CancelAll,
+            ActionType::CancelOrder { link_id: "b-1".to_string() },
+            ActionType::ClosePosition { qty: 1.0, side: "Sell" },
+            ActionType::SetTradingStop { price: 4.5, side: "Buy" },
+        ] {
+            assert!(gate.pre_send(&pull, &oms, &margin, now).is_ok(), "{:?}", pull);
+        }
+        assert_eq!(gate.vetoes(GateStage::KillSwitch), 1);
+        assert_eq!(gate.vetoes(GateStage::Validator), 0);
+    }
+
+    #[test]
+    fn validator_rejects_malformed_requests_loudly() {
+        let mut gate = gate();
+        let oms = OrderManager::new(OrderManagerConfig::default());
+        let margin = margin();
+        let now = Instant::now();
+        let reason = |gate: &mut SendGate, action: &ActionType| match gate.pre_send(action, &oms, &margin, now) {
+            Err(veto @ GateVeto::Invalid { reason }) => {
+                assert!(veto.loud() && !veto.retry());
+                reason
+            }
+            other => panic!("expected an invalid veto, got {:?}", other),
+        };
+
+        assert_eq!(reason(&mut gate, &create("Buy", 5.0005)), "price off tick grid");
+        assert_eq!(reason(&mut gate, &create("Buy", -5.0)), "price not positive");
+        let small = ActionType::CreateOrder { price: 5.0, qty: 0.05, side: "Buy", link_id: "b-1".to_string() };
+        assert_eq!(reason(&mut gate, &small), "qty below instrument minimum");
+        let off_lot = ActionType::CreateOrder { price: 5.0, qty: 1.05, side: "Buy", link_id: "b-1".to_string() };
+        assert_eq!(reason(&mut gate, &off_lot), "qty off lot grid");
+        let long_id = ActionType::CreateOrder { price: 5.0, qty: 1.0, side: "Buy", link_id: "b-".repeat(19) };
+        assert_eq!(reason(&mut gate, &long_id), "bad orderLinkId");
+        assert_eq!(reason(&mut gate, &ActionType::ClosePosition { qty: 0.0, side: "Sell" }), "close qty not positive");
+        assert_eq!(reason(&mut gate, &ActionType::None), "empty action");
+        assert_eq!(gate.vetoes(GateStage::Validator), 7);
+    }
+
+    #[test]
+    fn rate_limit_counts_a_rolling_second_and_lets_pulls_through() {
+        let mut gate = gate();
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let mut margin = MarginTracker::new(MarginConfig::default(), "USDT");
+        let limit = gate.config.max_orders_per_sec;
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        for i in 0..limit as u64 {
+            let action = create(if i % 2 == 0 { "Buy" } else { "Sell" }, 5.0);
+            assert!(gate.pre_send(&action, &oms, &margin, at(i * 10)).is_ok());
+            gate.post_send(&action, &mut oms, &mut margin, at(i * 10));
+            oms.on_order_closed("Buy");
+            oms.on_order_closed("Sell");
+        }
+        let veto = gate.pre_send(&create("Buy", 5.0), &oms, &margin, at(500)).unwrap_err();
+        assert_eq!(veto, GateVeto::RateLimited { sent: limit, limit });
+        assert!(veto.retry());
+        assert!(gate.pre_send(&ActionType::CancelAll, &oms, &margin, at(500)).is_ok());
+        // The first request leaves the window one second after it was sent
+        assert!(gate.pre_send(&create("Buy", 5.0), &oms, &margin, at(1_000)).is_ok());
+        assert_eq!(gate.vetoes(GateStage::RateLimit), 1);
+    }
+
+    #[test]
+    fn post_send_reports_what_it_committed() {
+        let mut gate = gate();
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let mut margin = margin();
+        let now = Instant::now();
+
+        // 10 @ 5.0 at 10x: 5 USDT
+        assert_eq!(gate.post_send(&create("Buy", 5.0), &mut oms, &mut margin, now), Committed::Create { side: "Buy", reserved: 5.0 });
+        // Moving it to 6.0 takes the difference only
+        let amend = ActionType::AmendPrice { price: 6.0, side: "Buy", link_id: "b-1".to_string() };
+        assert_eq!(gate.post_send(&amend, &mut oms, &mut margin, now), Committed::Amend { side: "Buy", reserved: 1.0 });
+        assert!((headroom(&margin) - 3.0).abs() < 1e-9);
+        // A reducing quote takes nothing
+        oms.set_position(10.0);
+        assert_eq!(gate.post_send(&create("Sell", 5.0), &mut oms, &mut margin, now), Committed::Create { side: "Sell", reserved: 0.0 });
+        assert_eq!(gate.post_send(&ActionType::CancelAll, &mut oms, &mut margin, now), Committed::RiskPull);
+        let stop = ActionType::SetTradingStop { price: 4.5, side: "Buy" };
+        assert_eq!(gate.post_send(&stop, &mut oms, &mut margin, now), Committed::RiskPull);
+    }
+
+    #[test]
+    fn a_dropped_create_frees_its_margin_and_the_order() {
+        let mut gate = gate();
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let mut margin = margin();
+        let now = Instant::now();
+
+        let committed = gate.post_send(&create("Buy", 5.0), &mut oms, &mut margin, now);
+        assert!(oms.buy.live);
+        assert!((headroom(&margin) - 4.0).abs() < 1e-9);
+
+        assert_eq!(gate.on_dropped(committed, &mut oms, &mut margin), None);
+        assert!(!oms.buy.live);
+        assert!((headroom(&margin) - 9.0).abs() < 1e-9);
+        // The sell that did not fit before fits again
+        assert!(gate.pre_send(&create("Sell", 8.0), &oms, &margin, now).is_ok());
+    }
+
+    #[test]
+    fn a_dropped_amend_rolls_back_to_the_resting_price() {
+        let mut gate = gate();
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let mut margin = margin();
+        let now = Instant::now();
+        gate.post_send(&create("Buy", 5.0), &mut oms, &mut margin, now);
+
+        let amend = ActionType::AmendPrice { price: 6.0, side: "Buy", link_id: "b-1".to_string() };
+        let committed = gate.post_send(&amend, &mut oms, &mut margin, now);
+        assert_eq!(oms.latest("Buy"), (6.0, 10.0));
+
+        assert_eq!(gate.on_dropped(committed, &mut oms, &mut margin), Some(5.0));
+        assert!(oms.buy.live && oms.buy.pending.is_none());
+        assert_eq!(oms.latest("Buy"), (5.0, 10.0));
+        assert!((headroom(&margin) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_dropped_risk_pull_leaves_the_state_to_the_caller() {
+        let mut gate = gate();
+        let mut oms = OrderManager::new(OrderManagerConfig::default());
+        let mut margin = margin();
+        let now = Instant::now();
+        gate.post_send(&create("Buy", 5.0), &mut oms, &mut margin, now);
+
+        let committed = gate.post_send(&ActionType::CancelAll, &mut oms, &mut margin, now);
+        assert_eq!(committed, Committed::RiskPull);
+        // The OMS counted the orders gone on send; a lost pull changes nothing here
+        assert_eq!(gate.on_dropped(committed, &mut oms, &mut margin), None);
+        assert!(!oms.buy.live);
+        assert!((headroom(&margin) - 4.0).abs() < 1e-9);
+        assert_eq!(gate.on_dropped(Committed::Other, &mut oms, &mut margin), None);
+    }
+}
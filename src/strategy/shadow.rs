@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+use crate::core::orderbook::L2OrderBook;
+use crate::core::parser::{BookUpdate, TradeEvent};
+use crate::strategy::fill::Fill;
+use crate::strategy::market_maker::MarketMaker;
+use crate::strategy::order_manager::{OrderManager, OrderManagerConfig};
+use crate::strategy::paper::{self, PaperConfig, PaperExchange};
+
+// Shadow strategy: a second MarketMaker (candidate parameters) fed the same book updates,
+// trades and health as the live one, with its actions resting on a PaperExchange instead of
+// the wire. Nothing it produces is sent. Every `report_every` the shadow's simulated PnL is
+// printed next to what the live strategy actually made over the same run, marked to the
+// same mid, so a candidate can be judged on today's market before it is switched in.
+// Simulated fills are only as good as the fill model (`HFT_FILL_MODEL`, queue by default):
+// compare fill counts before trusting a PnL gap.
+/// Period of the side-by-side report unless `report_every` is changed.
+pub const DEFAULT_REPORT_SECS: u64 = 5 * 60;
+
+/// Cash/position ledger of the live fills, for the side-by-side report.
+#[derive(Debug, Clone, Copy, Default)]
+struct LiveLedger {
+    fills: u64,
+    cash: f64,
+    position: f64,
+    fees: f64,
+}
+
+impl LiveLedger {
+    fn on_fill(&mut self, fill: &Fill) {
+        let signed = if fill.side == "Buy" { fill.qty } else { -fill.qty };
+        self.fills += 1;
+        self.position += signed;
+        self.cash -= signed * fill.px + fill.fee;
+        self.fees += fill.fee;
+    }
+
+    fn pnl(&self, mid: f64) -> f64 {
+        self.cash + self.position * mid
+    }
+}
+
+/// Owned by the Hot Thread next to the primary symbol's slot.
+pub struct ShadowStrategy {
+    pub strategy: MarketMaker,
+    pub oms: OrderManager,
+    pub paper: PaperExchange,
+    pub report_every: Duration,
+    /// Actions the shadow produced (never sent) and the live strategy's over the same ticks.
+    pub actions: u64,
+    pub live_actions: u64,
+    live: LiveLedger,
+    book_ts: u64,
+    last_report: Instant,
+}
+
+impl ShadowStrategy {
+    /// `oms`: the live slot's config, so both sides of the comparison hold quotes alike.
+    pub fn new(strategy: MarketMaker, oms: OrderManagerConfig, paper: PaperConfig, now: Instant) -> Self {
+        Self {
+            strategy,
+            oms: OrderManager::new(oms),
+            paper: PaperExchange::new(paper),
+            report_every: Duration::from_secs(DEFAULT_REPORT_SECS),
+            actions: 0,
+            live_actions: 0,
+            live: LiveLedger::default(),
+            book_ts: 0,
+            last_report: now,
+        }
+    }
+
+    /// One public trade: last-price guard and paper fills.
+    pub fn on_trade(&mut self, trade: &TradeEvent, now: Instant) {
+        self.strategy.last_price.on_trade(trade.price, now);
+        if let Some(fill) = self.paper.on_trade(trade) {
+            paper::settle(&mut self.strategy, &mut self.oms, &self.paper, &fill);
+        }
+    }
+
+    /// The book update the live strategy just saw (`fault`: the live side pulled its quotes).
+    /// `healthy`, halts and reduce-only are the live strategy's, so both face the same gates.
+    pub fn on_book(&mut self, book: &L2OrderBook, update: &BookUpdate, healthy: bool, fault: bool) {
+        self.book_ts = self.book_ts.max(update.ts);
+        self.oms.on_book_update(book);
+        for fill in self.paper.on_book(book, update.ts).into_iter().flatten() {
+            paper::settle(&mut self.strategy, &mut self.oms, &self.paper, &fill);
+        }
+        self.strategy.on_health(healthy);
+        let actions = if fault { self.strategy.pull_quotes() } else { self.strategy.on_tick(book, update) };
+        let Some(actions) = actions else { return };
+        self.oms.set_position(self.strategy.positions.size());
+        self.actions += actions.len() as u64;
+        for action in &actions {
+            if let Some(fill) = self.paper.on_action(&action.action_type, book, self.book_ts) {
+                paper::settle(&mut self.strategy, &mut self.oms, &self.paper, &fill);
+            }
+        }
+    }
+
+    /// Actions the live strategy produced on the same tick.
+    pub fn on_live_actions(&mut self, n: usize) {
+        self.live_actions += n as u64;
+    }
+
+    /// A real execution of the live strategy.
+    pub fn on_live_fill(&mut self, fill: &Fill) {
+        self.live.on_fill(fill);
+    }
+
+    /// Shadow net PnL (paper, after fees) marked to `mid`.
+    pub fn pnl(&self, mid: f64) -> f64 {
+        let open = if self.paper.position != 0.0 { self.paper.position * (mid - self.paper.entry()) } else { 0.0 };
+        self.paper.realized - self.paper.fees + open
+    }
+
+    /// Side-by-side line once `report_every` has passed; true if printed.
+    pub fn report_due(&mut self, mid: f64, now: Instant) -> bool {
+        if now.saturating_duration_since(self.last_report) < self.report_every || mid <= 0.0 {
+            return false;
+        }
+        self.last_report = now;
+        let (shadow, live) = (self.pnl(mid), self.live.pnl(mid));
+        println!("SHADOW [{}]: {:?} | shadow fills {} pos {:.4} fees {:+.4} PnL {:+.4} | live fills {} pos {:.4} fees {:+.4} PnL {:+.4} | diff {:+.4} | actions {} vs live {}",
+            self.paper.config.model.name(), self.strategy.params, self.paper.fills, self.paper.position, self.paper.fees, shadow,
+            self.live.fills, self.live.position, self.live.fees, live, shadow - live, self.actions, self.live_actions);
+        true
+    }
+}
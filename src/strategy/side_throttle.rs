@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+// Per-side loop breaker. A side can get stuck in create -> reject -> reset -> create (a
+// PostOnly price that always crosses, a qty the exchange keeps refusing, insufficient
+// balance) or create -> cancel -> create, each turn a few ms apart. Every turn passes the
+// gate on its own and the loop only ends when the account-wide rate limit does, taking the
+// other side and every other symbol with it. The throttle counts creates and rejects per side
+// inside `window`; past either limit the side is benched for a cooldown that doubles with each
+// trip in a row (up to `max_cooldown`) and resets once the side has been `calm` since the last
+// cooldown ended.
+const DEFAULT_WINDOW_MS: u64 = 2_000;
+const DEFAULT_MAX_CREATES: usize = 8;
+const DEFAULT_MAX_REJECTS: usize = 3;
+const DEFAULT_COOLDOWN_MS: u64 = 1_000;
+const DEFAULT_MAX_COOLDOWN_MS: u64 = 60_000;
+const DEFAULT_CALM_MS: u64 = 30_000;
+// Ring size per side; the limits are clamped to it
+const EVENT_SLOTS: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottleConfig {
+    pub enabled: bool,
+    pub window: Duration,
+    /// Creates on one side inside `window` that count as a loop.
+    pub max_creates: usize,
+    /// Rejected requests on one side inside `window` that count as a loop.
+    pub max_rejects: usize,
+    /// First cooldown; doubled per trip in a row.
+    pub cooldown: Duration,
+    pub max_cooldown: Duration,
+    /// Quiet time after a cooldown after which the next trip starts from `cooldown` again.
+    pub calm: Duration,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: Duration::from_millis(DEFAULT_WINDOW_MS),
+            max_creates: DEFAULT_MAX_CREATES,
+            max_rejects: DEFAULT_MAX_REJECTS,
+            cooldown: Duration::from_millis(DEFAULT_COOLDOWN_MS),
+            max_cooldown: Duration::from_millis(DEFAULT_MAX_COOLDOWN_MS),
+            calm: Duration::from_millis(DEFAULT_CALM_MS),
+        }
+    }
+}
+
+/// What tripped the throttle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopKind {
+    /// Too many creates (create/cancel churn).
+    Churn { creates: usize },
+    /// Too many rejects; `code` is the last retCode, `repeated` = every one in the window had it.
+    Rejects { count: usize, code: i64, repeated: bool },
+}
+
+/// A side benched; the Hot Thread alerts on it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThrottleTrip {
+    pub side: &'static str,
+    pub kind: LoopKind,
+    pub cooldown: Duration,
+    /// Trips in a row before this one (0 = first after a calm period).
+    pub level: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SideState {
+    creates: [Option<Instant>; EVENT_SLOTS],
+    next_create: usize,
+    rejects: [Option<(Instant, i64)>; EVENT_SLOTS],
+    next_reject: usize,
+    // End of the current / last cooldown
+    until: Option<Instant>,
+    level: u32,
+}
+
+fn side_idx(side: &str) -> usize {
+    if side == "Buy" { 0 } else { 1 }
+}
+
+fn side_name(idx: usize) -> &'static str {
+    if idx == 0 { "Buy" } else { "Sell" }
+}
+
+/// Owned by the symbol's `SendGate`. Fixed arrays only.
+#[derive(Debug)]
+pub struct SideThrottle {
+    pub config: ThrottleConfig,
+    sides: [SideState; 2],
+    // Trip not yet reported to the Hot Thread
+    pending: Option<ThrottleTrip>,
+    /// Trips so far (both sides).
+    pub trips: u64,
+}
+
+impl SideThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self { config, sides: [SideState::default(); 2], pending: None, trips: 0 }
+    }
+
+    /// Time left on `side`'s cooldown, None when it may send.
+    pub fn cooling(&self, side: &str, now: Instant) -> Option<Duration> {
+        let until = self.sides[side_idx(side)].until?;
+        (now < until).then(|| until - now)
+    }
+
+    /// A create for `side` left the gate.
+    pub fn on_create(&mut self, side: &str, now: Instant) {
+        if !self.config.enabled || self.cooling(side, now).is_some() {
+            return;
+        }
+        let idx = side_idx(side);
+        let state = &mut self.sides[idx];
+        state.creates[state.next_create] = Some(now);
+        state.next_create = (state.next_create + 1) % EVENT_SLOTS;
+        let creates = self.recent_creates(idx, now);
+        if creates >= self.config.max_creates.clamp(1, EVENT_SLOTS) {
+            self.trip(idx, LoopKind::Churn { creates }, now);
+        }
+    }
+
+    /// The exchange rejected a request for `side` with `code`. Late answers to requests sent
+    /// before the trip don't count towards the next one.
+    pub fn on_reject(&mut self, side: &str, code: i64, now: Instant) {
+        if !self.config.enabled || self.cooling(side, now).is_some() {
+            return;
+        }
+        let idx = side_idx(side);
+        let state = &mut self.sides[idx];
+        state.rejects[state.next_reject] = Some((now, code));
+        state.next_reject = (state.next_reject + 1) % EVENT_SLOTS;
+        let window = self.config.window;
+        let recent = || self.sides[idx].rejects.iter().flatten().filter(move |(ts, _)| now.saturating_duration_since(*ts) < window);
+        let count = recent().count();
+        if count >= self.config.max_rejects.clamp(1, EVENT_SLOTS) {
+            let repeated = recent().all(|&(_, c)| c == code);
+            self.trip(idx, LoopKind::Rejects { count, code, repeated }, now);
+        }
+    }
+
+    /// The last trip, once.
+    pub fn take_trip(&mut self) -> Option<ThrottleTrip> {
+        self.pending.take()
+    }
+
+    fn recent_creates(&self, idx: usize, now: Instant) -> usize {
+        self.sides[idx].creates.iter().flatten().filter(|&&ts| now.saturating_duration_since(ts) < self.config.window).count()
+    }
+
+    fn trip(&mut self, idx: usize, kind: LoopKind, now: Instant) {
+        let config = self.config;
+        let state = &mut self.sides[idx];
+        state.level = match state.until {
+            Some(until) if now.saturating_duration_since(until) < config.calm => state.level.saturating_add(1),
+            _ => 0,
+        };
+        let cooldown = config.cooldown.saturating_mul(1u32 << state.level.min(16)).min(config.max_cooldown);
+        state.until = Some(now + cooldown);
+        // The loop is broken: the next trip has to be earned from scratch
+        state.creates = [None; EVENT_SLOTS];
+        state.rejects = [None; EVENT_SLOTS];
+        self.trips += 1;
+        self.pending = Some(ThrottleTrip { side: side_name(idx), kind, cooldown, level: state.level });
+    }
+}
@@ -0,0 +1,247 @@
+// Sampled strategy diagnostics.
+// The strategy records `StrategyEvent`s (Copy, no strings built on the hot path);
+// the Hot Thread drains them into the LogMessage ring and the Cold Thread prints them.
+
+use crate::core::logging::info;
+
+const QUEUE_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogCategory {
+    Batch,      // skipped batch updates (same exchange TS)
+    Requote,
+    Impulse,
+    WallScan,
+    Position,   // fills, position sync, closes, breakeven stop
+    Mode,       // reduce-only / halt quote pulls, fade, warmup, regime switches, price guard
+}
+
+pub const CATEGORY_COUNT: usize = 6;
+
+impl LogCategory {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "batch" => Some(LogCategory::Batch),
+            "requote" => Some(LogCategory::Requote),
+            "impulse" => Some(LogCategory::Impulse),
+            "wall" => Some(LogCategory::WallScan),
+            "position" => Some(LogCategory::Position),
+            "mode" => Some(LogCategory::Mode),
+            _ => None,
+        }
+    }
+}
+
+/// 1-in-N sampling per category (indexed by `LogCategory`). 0 = category off,
+/// 1 = log everything. State changes (see `StrategyEvent::state_key`) bypass sampling.
+#[derive(Debug, Clone, Copy)]
+pub struct LogSampling {
+    pub every: [u32; CATEGORY_COUNT],
+}
+
+impl LogSampling {
+    pub fn set(&mut self, category: LogCategory, every: u32) {
+        self.every[category.index()] = every;
+    }
+}
+
+impl Default for LogSampling {
+    fn default() -> Self {
+        let mut s = Self { every: [1; CATEGORY_COUNT] };
+        s.set(LogCategory::Batch, 100);
+        s.set(LogCategory::Requote, 20);
+        s.set(LogCategory::WallScan, 50);
+        s
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrategyEvent {
+    SkippedBatch { exch_ts: u64 },
+    Requote { reason: &'static str, tps: f64, spread: f64, change: f64, regime: &'static str },
+    Impulse { change: f64, threshold: f64, tps: f64 },
+    WallScan { side: &'static str, wall_px: f64, dist: f64, safe: bool, used: bool },
+    Fill { side: &'static str, qty: f64, px: f64, position: f64, entry: f64, fee: f64, fee_rate: f64, maker: bool },
+    PositionSync { old: f64, new: f64 },
+    // Fill a position snapshot already contained / snapshot older than booked fills (`position.rs`)
+    FillCovered { side: &'static str, qty: f64, position: f64 },
+    StaleSnapshot { position: f64, snapshot: f64 },
+    BreakevenStop { pnl: f64 },
+    ClosePosition { reason: &'static str, position: f64, entry: f64 },
+    ExitStage { stage: &'static str, age_ms: u64, position: f64 },
+    QuotePull { halted: bool },
+    Fade { mode: &'static str, buys: usize, sells: usize },
+    Warmup { phase: &'static str, factor: f64 },
+    Regime { from: &'static str, to: &'static str, volatility_bps: f64, tps: f64, toxicity: f64 },
+    // Touch vs the last publicTrade price; `deviation` is 0 once the book is back in the band
+    PriceGuard { blocked: bool, bid: f64, ask: f64, last: f64, deviation: f64 },
+}
+
+impl StrategyEvent {
+    pub fn category(&self) -> LogCategory {
+        match self {
+            StrategyEvent::SkippedBatch { .. } => LogCategory::Batch,
+            StrategyEvent::Requote { .. } => LogCategory::Requote,
+            StrategyEvent::Impulse { .. } => LogCategory::Impulse,
+            StrategyEvent::WallScan { .. } => LogCategory::WallScan,
+            StrategyEvent::Fill { .. }
+            | StrategyEvent::PositionSync { .. }
+            | StrategyEvent::FillCovered { .. }
+            | StrategyEvent::StaleSnapshot { .. }
+            | StrategyEvent::BreakevenStop { .. }
+            | StrategyEvent::ClosePosition { .. }
+            | StrategyEvent::ExitStage { .. } => LogCategory::Position,
+            StrategyEvent::QuotePull { .. }
+            | StrategyEvent::Fade { .. }
+            | StrategyEvent::Warmup { .. }
+            | StrategyEvent::Regime { .. }
+            | StrategyEvent::PriceGuard { .. } => LogCategory::Mode,
+        }
+    }
+
+    /// Discrete state carried by the event. A key different from the last one seen in
+    /// the same category is a state change and is always logged (e.g. requote reason
+    /// flipping from Heartbeat to IMPULSE, a wall becoming usable).
+    fn state_key(&self) -> u64 {
+        match self {
+            StrategyEvent::Requote { reason, .. } => str_key(reason),
+            StrategyEvent::WallScan { side, safe, used, .. } => str_key(side) << 2 | (*safe as u64) << 1 | *used as u64,
+            StrategyEvent::QuotePull { halted } => *halted as u64,
+            StrategyEvent::Fade { mode, .. } => str_key(mode),
+            StrategyEvent::Warmup { phase, .. } => str_key(phase),
+            StrategyEvent::Regime { to, .. } => str_key(to),
+            StrategyEvent::PriceGuard { blocked, .. } => *blocked as u64,
+            _ => 0,
+        }
+    }
+
+    pub fn print(&self) {
+        match *self {
+            StrategyEvent::SkippedBatch { exch_ts } => {
+                info!(strategy, "STRATEGY: Skipping Batch Update (TS: {})", exch_ts);
+            }
+            StrategyEvent::Requote { reason, tps, spread, change, regime } => {
+                info!(strategy, "STRATEGY: >>> REQUOTE (Reason: {}) | TPS: {:.1} | Spread: {:.2}% ({}) | Change: {:.4}%",
+                    reason, tps, spread * 100.0, regime, change * 100.0);
+            }
+            StrategyEvent::Impulse { change, threshold, tps } => {
+                info!(strategy, "STRATEGY: IMPULSE DETECTED! Change: {:.4}% > Threshold: {:.4}% (TPS: {:.1})",
+                    change * 100.0, threshold * 100.0, tps);
+            }
+            StrategyEvent::WallScan { side, wall_px, dist, safe, used } => {
+                info!(strategy, "STRATEGY: [WALL SCAN] {} | WallPx: {} | Dist: {:.2}% | Safe: {} | USE: {}",
+                    side, wall_px, dist * 100.0, safe, used);
+            }
+            StrategyEvent::Fill { side, qty, px, position, entry, fee, fee_rate, maker } => {
+                info!(strategy, "STRATEGY: Fill detected! Side: {}, Qty: {}, Px: {}, {} Fee: {} ({:.4}%), New Pos: {}, AvgEntry: {}",
+                    side, qty, px, if maker { "Maker" } else { "Taker" }, fee, fee_rate * 100.0, position, entry);
+            }
+            StrategyEvent::PositionSync { old, new } => {
+                info!(strategy, "STRATEGY: Syncing Position State! Old: {}, New: {}", old, new);
+            }
+            StrategyEvent::FillCovered { side, qty, position } => {
+                info!(strategy, "STRATEGY: Fill {} {} already in the position snapshot, not re-applied (Pos: {})", side, qty, position);
+            }
+            StrategyEvent::StaleSnapshot { position, snapshot } => {
+                info!(strategy, "STRATEGY: Position snapshot {} predates booked fills, kept {}", snapshot, position);
+            }
+            StrategyEvent::BreakevenStop { pnl } => {
+                info!(strategy, "STRATEGY: Setting Server-Side Breakeven Stop! PnL: {:.4}%", pnl * 100.0);
+            }
+            StrategyEvent::ClosePosition { reason, position, entry } => {
+                info!(strategy, "STRATEGY: Closing Position! Reason: {} | Pos: {} | Entry: {}", reason, position, entry);
+            }
+            StrategyEvent::ExitStage { stage, age_ms, position } => {
+                info!(strategy, "STRATEGY: Exit stage -> {} | Oldest lot {}ms | Pos: {}", stage, age_ms, position);
+            }
+            StrategyEvent::QuotePull { halted } => {
+                info!(strategy, "STRATEGY: {} mode, pulling resting quotes", if halted { "Halt" } else { "Reduce-only" });
+            }
+            StrategyEvent::Fade { mode, buys, sells } => {
+                info!(strategy, "STRATEGY: Quote fade -> {} | Recent fills: {} buy / {} sell", mode, buys, sells);
+            }
+            StrategyEvent::Warmup { phase, factor } => {
+                info!(strategy, "STRATEGY: Quote warmup -> {} | Size: {:.0}%", phase, factor * 100.0);
+            }
+            StrategyEvent::Regime { from, to, volatility_bps, tps, toxicity } => {
+                info!(strategy, "STRATEGY: Regime {} -> {} | Volatility: {:.2} bps/tick | TPS: {:.1} | Toxicity: {} fills",
+                    from, to, volatility_bps, tps, toxicity);
+            }
+            StrategyEvent::PriceGuard { blocked, bid, ask, last, deviation } => {
+                if blocked {
+                    info!(strategy, "STRATEGY: [PRICE GUARD] Book {} / {} is {:.2}% off the last trade {}, not acting on it",
+                        bid, ask, deviation * 100.0, last);
+                } else {
+                    info!(strategy, "STRATEGY: [PRICE GUARD] Book {} / {} back in line with the last trade {}", bid, ask, last);
+                }
+            }
+        }
+    }
+}
+
+// FNV-1a over a short static label
+fn str_key(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Fixed-size sampled event queue owned by the strategy. No allocation, no locks.
+pub struct StrategyLog {
+    pub sampling: LogSampling,
+    seen: [u64; CATEGORY_COUNT],
+    last_key: [u64; CATEGORY_COUNT],
+    queue: [Option<StrategyEvent>; QUEUE_LEN],
+    len: usize,
+    /// Events that passed sampling but found the queue full.
+    pub dropped: u64,
+}
+
+impl StrategyLog {
+    pub fn new(sampling: LogSampling) -> Self {
+        Self {
+            sampling,
+            seen: [0; CATEGORY_COUNT],
+            last_key: [0; CATEGORY_COUNT],
+            queue: [None; QUEUE_LEN],
+            len: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Samples the event: the first of each category, every N-th after that,
+    /// and any state change are queued; the rest are only counted.
+    pub fn record(&mut self, event: StrategyEvent) {
+        let cat = event.category().index();
+        let every = self.sampling.every[cat];
+        if every == 0 {
+            return;
+        }
+        let key = event.state_key();
+        let changed = key != self.last_key[cat];
+        self.last_key[cat] = key;
+        let n = self.seen[cat];
+        self.seen[cat] += 1;
+        if !changed && !n.is_multiple_of(every as u64) {
+            return;
+        }
+        if self.len == QUEUE_LEN {
+            self.dropped += 1;
+            return;
+        }
+        self.queue[self.len] = Some(event);
+        self.len += 1;
+    }
+
+    /// Hands queued events to `f` in record order and empties the queue.
+    pub fn drain<F: FnMut(StrategyEvent)>(&mut self, mut f: F) {
+        for slot in self.queue[..self.len].iter_mut() {
+            if let Some(event) = slot.take() {
+                f(event);
+            }
+        }
+        self.len = 0;
+    }
+}
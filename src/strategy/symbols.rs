@@ -0,0 +1,207 @@
+use crate::core::rounding::Grid;
+use crate::net::rest_client::CancelTarget;
+use crate::strategy::router::{Venue, VENUE_COUNT};
+
+/// Instrument the bot trades by default (internal name, see `SymbolRegistry`).
+pub const PRIMARY_SYMBOL: &str = "RIVER-PERP";
+
+/// Bybit product category. Decides the shape of every order request and what `qty` means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// USDT/USDC perpetuals and futures: qty in base coin.
+    Linear,
+    /// Coin-margined contracts: qty in USD contracts (1 contract = 1 quote unit), settled in the base coin.
+    Inverse,
+    /// qty in base coin; no positions (no reduceOnly, positionIdx or trading stop).
+    Spot,
+    /// qty in base-coin contracts.
+    Option,
+}
+
+impl Category {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Linear => "linear",
+            Category::Inverse => "inverse",
+            Category::Spot => "spot",
+            Category::Option => "option",
+        }
+    }
+
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec {
+            "linear" => Some(Category::Linear),
+            "inverse" => Some(Category::Inverse),
+            "spot" => Some(Category::Spot),
+            "option" => Some(Category::Option),
+            _ => None,
+        }
+    }
+
+    /// Orders can be reduce-only (there is a position to reduce).
+    pub fn has_positions(self) -> bool {
+        self != Category::Spot
+    }
+
+    /// `positionIdx`, `position.trading-stop` and settle-coin bulk cancels: linear and inverse only.
+    pub fn is_futures(self) -> bool {
+        matches!(self, Category::Linear | Category::Inverse)
+    }
+}
+
+/// One tradable contract, venue-independent. `name` is ours; every venue spells the
+/// ticker its own way (RIVERUSDT / RIVER-USDT-SWAP / RIVER/USDT:USDT), so code that
+/// talks to an exchange asks `venue_symbol` instead of hardcoding the string.
+#[derive(Debug, Clone, Copy)]
+pub struct Instrument {
+    pub name: &'static str,
+    pub base: &'static str,
+    pub quote: &'static str,
+    pub category: Category,
+    pub tick_size: f64,
+    /// Lot grid and minimum, in the exchange's qty unit (USD contracts for inverse).
+    pub qty_step: f64,
+    pub min_qty: f64,
+    /// Per-venue symbol, indexed like `Venue`. None = not listed / not used there.
+    pub venue_symbols: [Option<&'static str>; VENUE_COUNT],
+}
+
+impl Instrument {
+    pub fn venue_symbol(&self, venue: Venue) -> Option<&'static str> {
+        self.venue_symbols[venue.idx()]
+    }
+
+    /// Coin the contract settles in: inverse settles in the base coin, the rest in the quote.
+    pub fn settle_coin(&self) -> &'static str {
+        if self.category == Category::Inverse { self.base } else { self.quote }
+    }
+
+    /// Exchange qty for a base-coin amount at `price`. The strategy thinks in base coin;
+    /// inverse orders are in USD contracts, so the amount is converted and put on the lot grid.
+    #[inline(always)]
+    pub fn order_qty(&self, base_qty: f64, price: f64) -> f64 {
+        match self.category {
+            Category::Inverse => self.grid().clip(base_qty * price),
+            _ => base_qty,
+        }
+    }
+
+    /// Base-coin amount of an exchange qty (fills, position size); inverse of `order_qty`.
+    #[inline(always)]
+    pub fn base_qty(&self, qty: f64, price: f64) -> f64 {
+        match self.category {
+            Category::Inverse if price > 0.0 => qty / price,
+            Category::Inverse => 0.0,
+            _ => qty,
+        }
+    }
+
+    /// Decimals to print a qty with (from `qty_step`).
+    pub fn qty_decimals(&self) -> usize {
+        self.grid().qty_decimals()
+    }
+
+    /// Decimals to print a price with (from `tick_size`).
+    pub fn price_decimals(&self) -> usize {
+        self.grid().price_decimals()
+    }
+
+    /// Price and lot grid, in the exchange's qty unit (`core::rounding`).
+    #[inline(always)]
+    pub fn grid(&self) -> Grid {
+        Grid::new(self.tick_size, self.qty_step, self.min_qty)
+    }
+}
+
+// Order entry formats prices with 3 decimals and qty with 1.
+const DEFAULT_INSTRUMENTS: [Instrument; 1] = [
+    Instrument {
+        name: PRIMARY_SYMBOL,
+        base: "RIVER",
+        quote: "USDT",
+        category: Category::Linear,
+        tick_size: 0.001,
+        qty_step: 0.1,
+        min_qty: 0.1,
+        venue_symbols: [Some("RIVERUSDT"), Some("RIVERUSDT"), None],
+    },
+];
+
+/// How wide a bulk cancel reaches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelScope {
+    /// Every configured symbol on its own.
+    Symbols,
+    /// Whole settle coin per category (also catches orders on symbols we don't trade,
+    /// e.g. placed by hand). Linear/inverse only; spot and options fall back to symbols.
+    SettleCoin,
+}
+
+impl CancelScope {
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec {
+            "symbols" => Some(CancelScope::Symbols),
+            "settle" | "settle-coin" => Some(CancelScope::SettleCoin),
+            _ => None,
+        }
+    }
+}
+
+/// Internal symbol -> instrument metadata and per-venue tickers.
+/// Built once at startup; lookups are linear over a handful of entries.
+pub struct SymbolRegistry {
+    instruments: Vec<Instrument>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self { instruments: Vec::new() }
+    }
+
+    /// Adds or replaces (same `name`) an instrument.
+    pub fn register(&mut self, instrument: Instrument) {
+        match self.instruments.iter_mut().find(|i| i.name == instrument.name) {
+            Some(existing) => *existing = instrument,
+            None => self.instruments.push(instrument),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Instrument> {
+        self.instruments.iter().find(|i| i.name == name)
+    }
+
+    pub fn venue_symbol(&self, name: &str, venue: Venue) -> Option<&'static str> {
+        self.get(name).and_then(|i| i.venue_symbol(venue))
+    }
+
+    /// Bybit cancel-all requests covering every registered instrument, deduplicated.
+    pub fn cancel_targets(&self, scope: CancelScope) -> Vec<CancelTarget> {
+        let mut targets = Vec::new();
+        for inst in &self.instruments {
+            let Some(symbol) = inst.venue_symbol(Venue::Bybit) else { continue };
+            let target = match scope {
+                CancelScope::SettleCoin if inst.category.is_futures() => CancelTarget::SettleCoin { category: inst.category, coin: inst.settle_coin() },
+                _ => CancelTarget::Symbol { category: inst.category, symbol },
+            };
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        targets
+    }
+
+    /// Reverse lookup: which of our instruments is `symbol` on `venue` (e.g. from a topic or position update).
+    pub fn by_venue_symbol(&self, venue: Venue, symbol: &str) -> Option<&Instrument> {
+        self.instruments.iter().find(|i| i.venue_symbol(venue) == Some(symbol))
+    }
+}
+
+impl Default for SymbolRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        for instrument in DEFAULT_INSTRUMENTS {
+            registry.register(instrument);
+        }
+        registry
+    }
+}
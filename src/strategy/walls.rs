@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+use crate::core::orderbook::{L2OrderBook, Level, Side};
+
+// A big level that appears and vanishes within a few updates is most likely a spoof:
+// quoting one tick in front of it leaves us exposed the moment it is pulled.
+// Levels only count as walls after staying above the threshold for a while.
+const DEFAULT_WALL_THRESHOLD: f64 = 1000.0;
+const DEFAULT_MIN_UPDATES: u32 = 5;
+const DEFAULT_MIN_AGE_MS: u64 = 500;
+const DEPTH: usize = 20; // L2OrderBook depth
+
+#[derive(Debug, Clone, Copy)]
+pub struct WallConfig {
+    /// Resting qty at which a level is a wall candidate.
+    pub threshold: f64,
+    /// Consecutive book updates the level must stay above `threshold`.
+    pub min_updates: u32,
+    /// ... and for at least this long (both must hold; 0 disables either).
+    pub min_age: Duration,
+}
+
+impl Default for WallConfig {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_WALL_THRESHOLD,
+            min_updates: DEFAULT_MIN_UPDATES,
+            min_age: Duration::from_millis(DEFAULT_MIN_AGE_MS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    price: f64,
+    first_seen: Instant,
+    updates: u32,
+}
+
+/// Per-price-level persistence of wall candidates on both sides of the book.
+/// Fixed arrays (one slot per book level), rebuilt on every book update.
+pub struct WallTracker {
+    pub config: WallConfig,
+    bids: [Option<Candidate>; DEPTH],
+    asks: [Option<Candidate>; DEPTH],
+    last_update: Option<Instant>,
+}
+
+impl WallTracker {
+    pub fn new(config: WallConfig) -> Self {
+        Self { config, bids: [None; DEPTH], asks: [None; DEPTH], last_update: None }
+    }
+
+    /// Feeds one book update. A level that drops below the threshold (or leaves the
+    /// book) loses its history; the next time it shows up it starts from scratch.
+    pub fn on_book_update(&mut self, book: &L2OrderBook, now: Instant) {
+        let threshold = self.config.threshold;
+        Self::track(&mut self.bids, &book.bids, threshold, now);
+        Self::track(&mut self.asks, &book.asks, threshold, now);
+        self.last_update = Some(now);
+    }
+
+    fn track(slots: &mut [Option<Candidate>; DEPTH], levels: &[Level; DEPTH], threshold: f64, now: Instant) {
+        let prev = *slots;
+        for (slot, lvl) in slots.iter_mut().zip(levels.iter()) {
+            *slot = if lvl.price != 0.0 && lvl.qty >= threshold {
+                let seen = prev.iter().flatten().find(|c| (c.price - lvl.price).abs() < f64::EPSILON);
+                Some(match seen {
+                    Some(c) => Candidate { updates: c.updates.saturating_add(1), ..*c },
+                    None => Candidate { price: lvl.price, first_seen: now, updates: 1 },
+                })
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Whether the level at `price` on `side` is currently a persistent wall.
+    pub fn is_wall(&self, side: Side, price: f64) -> bool {
+        let Some(now) = self.last_update else { return false };
+        let slots = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        slots.iter().flatten().any(|c| {
+            (c.price - price).abs() < f64::EPSILON
+                && c.updates >= self.config.min_updates
+                && now.saturating_duration_since(c.first_seen) >= self.config.min_age
+        })
+    }
+
+    /// First persistent wall (best price first) on `side` whose price passes `in_range`.
+    pub fn find<F: Fn(f64) -> bool>(&self, side: Side, levels: &[Level], in_range: F) -> Option<f64> {
+        levels
+            .iter()
+            .take_while(|lvl| lvl.price != 0.0)
+            .find(|lvl| in_range(lvl.price) && self.is_wall(side, lvl.price))
+            .map(|lvl| lvl.price)
+    }
+}
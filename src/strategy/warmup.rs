@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+use crate::core::rounding;
+
+// Cold-start warmup. After startup, a reconnect or a book resync the quote size starts at
+// `start_fraction` of normal and ramps linearly to full size over `duration`. The ramp only
+// runs while the Hot Thread reports the tick healthy (book clean, Trade WS up and not
+// degraded, tick processing inside `max_tick_latency`); an unhealthy tick puts it back to
+// the start, so full size is only reached after `duration` of clean ticks in a row.
+const DEFAULT_DURATION_MS: u64 = 30_000;
+const DEFAULT_START_FRACTION: f64 = 0.25;
+const DEFAULT_MAX_TICK_LATENCY_US: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmupPhase {
+    /// Unhealthy tick: size held at the start fraction.
+    Holding,
+    /// Healthy: size ramping up.
+    Ramping,
+    /// Full size reached; warmup off until the next restart.
+    Done,
+}
+
+impl WarmupPhase {
+    pub fn name(self) -> &'static str {
+        match self {
+            WarmupPhase::Holding => "holding",
+            WarmupPhase::Ramping => "ramping",
+            WarmupPhase::Done => "done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WarmupConfig {
+    pub enabled: bool,
+    /// Healthy time from start fraction to full size.
+    pub duration: Duration,
+    /// Size fraction right after a restart.
+    pub start_fraction: f64,
+    /// Tick processing slower than this counts as unhealthy.
+    pub max_tick_latency: Duration,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration: Duration::from_millis(DEFAULT_DURATION_MS),
+            start_fraction: DEFAULT_START_FRACTION,
+            max_tick_latency: Duration::from_micros(DEFAULT_MAX_TICK_LATENCY_US),
+        }
+    }
+}
+
+/// Size ramp after a cold start. Owned by the strategy.
+pub struct QuoteWarmup {
+    pub config: WarmupConfig,
+    active: bool,
+    // Start of the current run of healthy ticks
+    ramp_start: Option<Instant>,
+}
+
+impl QuoteWarmup {
+    /// Starts active: the process start is a cold start.
+    pub fn new(config: WarmupConfig) -> Self {
+        Self { config, active: config.enabled, ramp_start: None }
+    }
+
+    /// Startup, reconnect or resync: back to the start fraction.
+    pub fn restart(&mut self) {
+        self.active = self.config.enabled;
+        self.ramp_start = None;
+    }
+
+    /// Health of this tick. Some(phase) on a phase change.
+    pub fn on_health(&mut self, healthy: bool, now: Instant) -> Option<WarmupPhase> {
+        if !self.active {
+            return None;
+        }
+        match self.ramp_start {
+            Some(_) if !healthy => {
+                self.ramp_start = None;
+                Some(WarmupPhase::Holding)
+            }
+            Some(start) if now.saturating_duration_since(start) >= self.config.duration => {
+                self.active = false;
+                self.ramp_start = None;
+                Some(WarmupPhase::Done)
+            }
+            None if healthy => {
+                self.ramp_start = Some(now);
+                Some(WarmupPhase::Ramping)
+            }
+            _ => None,
+        }
+    }
+
+    /// Size multiplier: start fraction -> 1.0 along the ramp, 1.0 once done.
+    pub fn factor(&self, now: Instant) -> f64 {
+        let start = self.config.start_fraction.clamp(0.0, 1.0);
+        if !self.active {
+            return 1.0;
+        }
+        match self.ramp_start {
+            None => start,
+            Some(t) => {
+                let progress = now.saturating_duration_since(t).as_secs_f64() / self.config.duration.as_secs_f64().max(f64::EPSILON);
+                start + (1.0 - start) * progress.min(1.0)
+            }
+        }
+    }
+
+    /// Warmup size, on the lot grid and never below `min_qty`.
+    pub fn size(&self, qty: f64, qty_step: f64, min_qty: f64, now: Instant) -> f64 {
+        if !self.active || qty_step <= 0.0 {
+            return qty;
+        }
+        rounding::floor_to(qty * self.factor(now), qty_step).max(min_qty)
+    }
+}
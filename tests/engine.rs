@@ -0,0 +1,403 @@
+// Drives the engine through the library API only, the way an embedding binary
+// (backtester, research tool) would: own book, simulated clock, strategy.
+
+use std::time::{Duration, Instant};
+use hft_rust::core::clock::{Clock, SimClock};
+use hft_rust::core::orderbook::{L2OrderBook, Side};
+use hft_rust::core::parser::{BookUpdate, TradeEvent};
+use hft_rust::strategy::margin::{MarginConfig, MarginTracker};
+use hft_rust::strategy::market_maker::{ActionType, MarketMaker, QuoteParams};
+use hft_rust::strategy::order_manager::{self, AmendDecision, OrderManager, OrderManagerConfig};
+use hft_rust::strategy::send_gate::{GateVeto, SendGate, SendGateConfig};
+use hft_rust::strategy::side_throttle::LoopKind;
+use hft_rust::strategy::symbols::{SymbolRegistry, PRIMARY_SYMBOL};
+use hft_rust::strategy::paper::{FillModel, PaperConfig, PaperExchange};
+use hft_rust::strategy::requote::{EdgeDecay, RefreshReason, RequoteConfig};
+use hft_rust::strategy::regime::{Regime, RegimeClassifier, RegimeConfig, RegimeInputs};
+use hft_rust::strategy::obligation::{QuoteUptime, UptimePeriod};
+use hft_rust::strategy::shadow::ShadowStrategy;
+use hft_rust::strategy::rest_quoting::{RestQuoting, RestQuotingMode};
+use hft_rust::config::{ObligationSection, RestQuotingSection};
+use hft_rust::net::rest_client::{LinkId, OpenOrder, OpenOrders};
+
+fn book() -> L2OrderBook {
+    let mut book = L2OrderBook::new();
+    for i in 0..10 {
+        book.update(Side::Buy, 100.0 - i as f64 * 0.1, 5.0);
+        book.update(Side::Sell, 100.1 + i as f64 * 0.1, 5.0);
+    }
+    book.snapshot_applied = true;
+    book
+}
+
+#[test]
+fn quotes_at_warmup_size_after_start() {
+    let clock = SimClock::new(1_700_000_000_000);
+    let mut strategy = MarketMaker::with_clock(0.01, &clock);
+    let full = strategy.params.quote_qty;
+    let book = book();
+    let update = BookUpdate { ts: 0, update_id: 1, snapshot: true, levels: 20 };
+
+    clock.advance(Duration::from_millis(10));
+    strategy.on_health(true);
+    strategy.requote_pending = true;
+    let actions = strategy.on_tick(&book, &update).expect("first healthy tick quotes");
+    let qty: Vec<f64> = actions.iter().filter_map(|a| match a.action_type {
+        ActionType::CreateOrder { qty, .. } => Some(qty),
+        _ => None,
+    }).collect();
+    assert!(!qty.is_empty());
+    assert!(qty.iter().all(|&q| q < full), "warmup size {:?} vs full {}", qty, full);
+
+    // Healthy for the whole ramp: full size again
+    clock.advance(strategy.warmup.config.duration);
+    strategy.on_health(true);
+    assert_eq!(strategy.warmup.factor(clock.monotonic_now()), 1.0);
+}
+
+#[test]
+fn fill_models_bracket_a_touch() {
+    let book = book();
+    let bid = ActionType::CreateOrder { price: 100.0, qty: 1.0, side: "Buy", link_id: "b-1".to_string() };
+    // A taker sell of 3 at our price: 5 was visible ahead of us
+    let trade = TradeEvent { ts: 1, side: Side::Sell, price: 100.0, qty: 3.0, block_trade: false };
+    let filled = |model| {
+        let mut paper = PaperExchange::new(PaperConfig { model, ..PaperConfig::default() });
+        paper.on_action(&bid, &book, 0);
+        paper.on_trade(&trade).map(|f| f.qty).unwrap_or(0.0)
+    };
+    assert_eq!(filled(FillModel::Optimistic), 1.0);
+    assert_eq!(filled(FillModel::Queue), 0.0);
+    assert_eq!(filled(FillModel::Pessimistic), 0.0);
+
+    // Queue model: the next 3 clear the 2 still ahead and fill us
+    let mut paper = PaperExchange::new(PaperConfig::default());
+    paper.on_action(&bid, &book, 0);
+    assert!(paper.on_trade(&trade).is_none());
+    let fill = paper.on_trade(&trade).expect("queue ahead consumed");
+    assert_eq!(fill.qty, 1.0);
+    assert!(fill.is_maker);
+    assert_eq!(paper.position, 1.0);
+}
+
+#[test]
+fn edge_decay_refreshes_older_quotes_on_smaller_drift() {
+    let clock = SimClock::new(1_700_000_000_000);
+    let mut decay = EdgeDecay::new(RequoteConfig::default(), clock.monotonic_now());
+    decay.on_quote(100.0, 0.004, clock.monotonic_now());
+
+    // 0.05% drift against a 0.4% spread: kept while young (queue is worth more), refreshed later
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(decay.due(100.05, 0.004, clock.monotonic_now()), None);
+    clock.advance(Duration::from_secs(24));
+    assert_eq!(decay.due(100.05, 0.004, clock.monotonic_now()), Some(RefreshReason::Drift));
+
+    // No drift: only a regime change or the backstop refreshes
+    assert_eq!(decay.due(100.0, 0.004, clock.monotonic_now()), None);
+    assert_eq!(decay.due(100.0, 0.006, clock.monotonic_now()), Some(RefreshReason::Regime));
+    clock.advance(Duration::from_secs(4));
+    assert_eq!(decay.due(100.0, 0.004, clock.monotonic_now()), Some(RefreshReason::MaxAge));
+}
+
+#[test]
+fn slot_tagged_link_ids_route_back_to_their_symbol() {
+    let clock = SimClock::new(1_700_000_000_000);
+    let mut strategy = MarketMaker::with_clock(0.01, &clock);
+    strategy.set_slot(2);
+    let buy = strategy.active_buy_link_id.clone();
+    assert_eq!(order_manager::slot_from_link(&buy), 2);
+    assert_eq!(order_manager::side_from_link(&buy), Some("Buy"));
+    // reqIds embed the link id or the tag
+    assert_eq!(order_manager::slot_from_link(&format!("amend-{}-1700000000001", buy)), 2);
+    assert_eq!(order_manager::slot_from_link("close-x2-Sell-1700000000001"), 2);
+
+    strategy.reset_order("Sell");
+    assert_eq!(order_manager::slot_from_link(&strategy.active_sell_link_id), 2);
+    assert_eq!(order_manager::side_from_link(&strategy.active_sell_link_id), Some("Sell"));
+    // The primary keeps untagged ids
+    assert_eq!(order_manager::slot_from_link("b-1700000000000"), 0);
+    assert_eq!(order_manager::slot_from_link("cancel-all-1700000000000"), 0);
+}
+
+#[test]
+fn notional_clip_follows_price_on_the_lot_grid() {
+    let mut params = QuoteParams::default();
+    params.apply_spec("notional=50").unwrap();
+    assert_eq!(params.clip_qty(100.0, 0.1, 0.1), 0.5);
+    assert!((params.clip_qty(3.3, 0.1, 0.1) - 15.2).abs() < 1e-9);
+    // Never below the minimum lot; base quote_qty until there is a mid
+    assert_eq!(params.clip_qty(1_000_000.0, 0.1, 0.1), 0.1);
+    assert_eq!(params.clip_qty(0.0, 0.1, 0.1), params.quote_qty);
+}
+
+#[test]
+fn inventory_skew_is_measured_in_clips_at_the_mid() {
+    let mut strategy = MarketMaker::new(0.01);
+    strategy.params.apply_spec("notional=50").unwrap();
+    let flat = strategy.requote_threshold("Sell", 100.0);
+    assert_eq!(strategy.requote_threshold("Buy", 100.0), flat);
+
+    // 1.5 long is three 0.5 clips at mid 100: the skew is maxed
+    strategy.position = 1.5;
+    assert!((strategy.requote_threshold("Sell", 100.0) - flat * 0.5).abs() < 1e-12);
+    assert!((strategy.requote_threshold("Buy", 100.0) - flat * 1.5).abs() < 1e-12);
+    // At mid 50 the clip is 1.0: the same position is half the cap
+    assert!((strategy.requote_threshold("Sell", 50.0) - flat * 0.75).abs() < 1e-12);
+}
+
+#[test]
+fn margin_gate_stops_a_burst_the_balance_cannot_fund() {
+    let instrument = *SymbolRegistry::default().get(PRIMARY_SYMBOL).unwrap();
+    let mut gate = SendGate::new(SendGateConfig::default(), instrument);
+    let mut oms = OrderManager::new(OrderManagerConfig::default());
+    let mut margin = MarginTracker::new(MarginConfig { leverage: 10.0, buffer: 0.1 }, "USDT");
+    let create = |side, price| ActionType::CreateOrder { price, qty: 10.0, side, link_id: format!("{}-1", side) };
+    let now = Instant::now();
+
+    // No balance yet: nothing is blocked
+    assert!(gate.pre_send(&create("Buy", 5.0), &oms, &margin, now).is_ok());
+    // 10 USDT, 9 usable: the buy takes 5, the sell's 5.1 no longer fits
+    margin.on_balance(10.0);
+    gate.post_send(&create("Buy", 5.0), &mut oms, &mut margin, now);
+    match gate.pre_send(&create("Sell", 5.1), &oms, &margin, now) {
+        Err(GateVeto::Margin { side, required, headroom }) => {
+            assert_eq!(side, "Sell");
+            assert!((required - 5.1).abs() < 1e-9 && (headroom - 4.0).abs() < 1e-9);
+        }
+        other => panic!("expected a margin veto, got {:?}", other),
+    }
+    // Reducing the position needs no margin; a wallet update drops the reservations
+    oms.set_position(10.0);
+    assert!(gate.pre_send(&create("Sell", 5.1), &oms, &margin, now).is_ok());
+    oms.set_position(0.0);
+    margin.on_balance(10.0);
+    assert!(gate.pre_send(&create("Sell", 5.1), &oms, &margin, now).is_ok());
+}
+
+#[test]
+fn front_of_queue_holds_small_reprices_until_the_quote_goes_stale() {
+    let config = OrderManagerConfig { stale_quote_age: Duration::from_secs(5), ..OrderManagerConfig::default() };
+    let mut oms = OrderManager::new(config);
+    let placed = Instant::now();
+    oms.on_sent(&ActionType::CreateOrder { price: 100.0, qty: 5.0, side: "Buy", link_id: "b-1".to_string() }, placed);
+    // Alone at the level: front of the queue
+    oms.on_book_update(&book());
+    assert_eq!(oms.buy.queue_fraction(), 0.0);
+
+    let at = |secs| placed + Duration::from_secs(secs);
+    assert_eq!(oms.amend_decision("Buy", 100.1, at(1)), AmendDecision::Hold);
+    // A bigger move is worth the slot
+    assert_eq!(oms.amend_decision("Buy", 100.5, at(1)), AmendDecision::Amend);
+    // Five seconds at the front without a fill: the slot no longer holds the price
+    assert_eq!(oms.amend_decision("Buy", 100.1, at(5)), AmendDecision::Amend);
+}
+
+#[test]
+fn create_reject_loop_benches_the_side_with_growing_cooldowns() {
+    let instrument = *SymbolRegistry::default().get(PRIMARY_SYMBOL).unwrap();
+    let mut gate = SendGate::new(SendGateConfig::default(), instrument);
+    let mut oms = OrderManager::new(OrderManagerConfig::default());
+    let mut margin = MarginTracker::new(MarginConfig::default(), "USDT");
+    let create = |side| ActionType::CreateOrder { price: 100.0, qty: 1.0, side, link_id: format!("{}-1", side) };
+    let config = gate.throttle.config;
+    let t0 = Instant::now();
+
+    // create -> insufficient balance -> reset -> create, 5ms a turn
+    let turn = |gate: &mut SendGate, oms: &mut OrderManager, margin: &mut MarginTracker, at: Instant| {
+        let sent = gate.pre_send(&create("Buy"), oms, margin, at).is_ok();
+        if sent {
+            gate.post_send(&create("Buy"), oms, margin, at);
+            oms.on_order_closed("Buy");
+            gate.throttle.on_reject("Buy", 110007, at + Duration::from_millis(2));
+        }
+        sent
+    };
+    for i in 0..config.max_rejects as u64 {
+        assert!(turn(&mut gate, &mut oms, &mut margin, t0 + Duration::from_millis(5 * i)));
+    }
+    let trip = gate.throttle.take_trip().expect("the loop trips the throttle");
+    assert_eq!((trip.side, trip.cooldown, trip.level), ("Buy", config.cooldown, 0));
+    assert_eq!(trip.kind, LoopKind::Rejects { count: config.max_rejects, code: 110007, repeated: true });
+
+    // Buy is benched (retried later, not sent); Sell and risk pulls are not
+    let t1 = t0 + Duration::from_millis(20);
+    assert!(matches!(gate.pre_send(&create("Buy"), &oms, &margin, t1), Err(GateVeto::Throttled { side: "Buy", .. })));
+    assert!(gate.pre_send(&create("Sell"), &oms, &margin, t1).is_ok());
+    assert!(gate.pre_send(&ActionType::CancelAll, &oms, &margin, t1).is_ok());
+
+    // Straight back into the loop: twice the cooldown
+    let t2 = t0 + config.cooldown + Duration::from_millis(50);
+    for i in 0..config.max_rejects as u64 {
+        assert!(turn(&mut gate, &mut oms, &mut margin, t2 + Duration::from_millis(5 * i)));
+    }
+    let trip = gate.throttle.take_trip().unwrap();
+    assert_eq!((trip.cooldown, trip.level), (config.cooldown * 2, 1));
+
+    // A calm spell resets the escalation
+    let t3 = t2 + config.cooldown * 2 + config.calm + Duration::from_secs(1);
+    for i in 0..config.max_rejects as u64 {
+        assert!(turn(&mut gate, &mut oms, &mut margin, t3 + Duration::from_millis(5 * i)));
+    }
+    assert_eq!(gate.throttle.take_trip().unwrap().level, 0);
+    assert_eq!(gate.throttle.trips, 3);
+}
+
+#[test]
+fn regimes_step_up_at_once_and_down_after_the_dwell() {
+    let config = RegimeConfig::default();
+    let dwell = Duration::from_millis(config.min_dwell_ms);
+    let mut regimes = RegimeClassifier::new(config);
+    let t0 = Instant::now();
+    let quiet = RegimeInputs { volatility_bps: 0.1, tps: 5.0, toxicity: 0.0 };
+
+    // Quiet market: Normal -> Calm only after the dwell
+    assert_eq!(regimes.update(&quiet, t0), None);
+    assert_eq!(regimes.update(&quiet, t0 + dwell), Some((Regime::Normal, Regime::Calm)));
+    // Any one input past the extreme threshold jumps straight up
+    let toxic = RegimeInputs { toxicity: config.toxicity[2], ..quiet };
+    assert_eq!(regimes.update(&toxic, t0 + dwell), Some((Regime::Calm, Regime::Extreme)));
+    assert!(regimes.params().spread > config.calm.spread && regimes.params().size_factor < 1.0);
+
+    // Just under the threshold (inside the exit band) holds; below it, down after the dwell
+    let t1 = t0 + dwell * 2;
+    let easing = RegimeInputs { toxicity: config.toxicity[2] * 0.9, ..quiet };
+    assert_eq!(regimes.update(&easing, t1), None);
+    assert_eq!(regimes.update(&easing, t1 + dwell * 2), None);
+    let busy = RegimeInputs { tps: config.tps[1], ..quiet };
+    let t2 = t1 + dwell * 3;
+    assert_eq!(regimes.update(&busy, t2), None);
+    assert_eq!(regimes.update(&busy, t2 + dwell / 2), None);
+    assert_eq!(regimes.update(&busy, t2 + dwell), Some((Regime::Extreme, Regime::Volatile)));
+    assert_eq!(regimes.switches, 3);
+}
+
+#[test]
+fn book_off_the_last_trade_is_not_acted_on() {
+    let clock = SimClock::new(1_700_000_000_000);
+    let mut strategy = MarketMaker::with_clock(0.01, &clock);
+    let update = BookUpdate { ts: 0, update_id: 1, snapshot: true, levels: 20 };
+    clock.advance(Duration::from_millis(10));
+    strategy.on_health(true);
+    strategy.requote_pending = true;
+
+    // Last trade 5% below the touch: a glitch, no quotes
+    strategy.last_price.on_trade(95.0, clock.monotonic_now());
+    assert!(strategy.on_tick(&book(), &update).is_none());
+    assert!(strategy.last_price.blocked());
+
+    // Trades print at the touch again: quoting resumes
+    strategy.last_price.on_trade(100.05, clock.monotonic_now());
+    assert!(strategy.on_tick(&book(), &update).is_some_and(|actions| !actions.is_empty()));
+    assert!(!strategy.last_price.blocked());
+    assert_eq!(strategy.last_price.rejected, 1);
+
+    // A last price older than max_age is not trusted: the guard stays open
+    strategy.last_price.on_trade(95.0, clock.monotonic_now());
+    clock.advance(strategy.last_price.config.max_age + Duration::from_millis(1));
+    assert_eq!(strategy.last_price.check(100.0, 100.1, clock.monotonic_now()), None);
+}
+
+#[test]
+fn rest_quoting_paces_quotes_and_polls_out_vanished_orders() {
+    let mut section = RestQuotingSection::default();
+    section.apply_spec("fallback,interval_ms=500,poll_ms=1000").unwrap();
+    let config = section.rest_quoting();
+    assert_eq!((config.mode, config.interval, config.poll), (RestQuotingMode::Fallback, Duration::from_millis(500), Duration::from_secs(1)));
+    let mut rest = RestQuoting::new(config);
+    let t0 = Instant::now();
+    let create = |side| ActionType::CreateOrder { price: 100.0, qty: 1.0, side, link_id: format!("bot-{}-1", side.to_lowercase()) };
+    let amend = ActionType::AmendPrice { price: 100.1, side: "Buy", link_id: "bot-buy-1".to_string() };
+
+    // Off until the Trade WS goes down, then back when it returns
+    assert!(!rest.active() && !rest.poll_due(t0));
+    assert_eq!(rest.update(true), Some(true));
+    assert_eq!(rest.update(true), None);
+
+    // One create/amend per side per interval; the other side and cancels are not held back
+    assert!(rest.ready(&create("Buy"), t0));
+    rest.on_sent(&create("Buy"), t0);
+    assert!(!rest.ready(&amend, t0 + Duration::from_millis(100)));
+    assert!(rest.ready(&create("Sell"), t0 + Duration::from_millis(100)));
+    assert!(rest.ready(&ActionType::CancelOrder { link_id: "bot-buy-1".to_string() }, t0 + Duration::from_millis(100)));
+    assert!(rest.ready(&amend, t0 + Duration::from_millis(500)));
+    assert_eq!((rest.sent, rest.paced), (1, 1));
+
+    // Polls while active, every `poll`
+    assert!(rest.poll_due(t0));
+    assert!(!rest.poll_due(t0 + Duration::from_millis(900)));
+    assert!(rest.poll_due(t0 + Duration::from_millis(1000)));
+
+    // A Buy placed before the poll and missing from it is gone; a Sell placed after stays
+    let mut oms = OrderManager::new(OrderManagerConfig::default());
+    oms.on_sent(&create("Buy"), t0);
+    let polled_at = t0 + Duration::from_millis(200);
+    oms.on_sent(&create("Sell"), polled_at + Duration::from_millis(1));
+    let on_exchange = OpenOrders { buy: None, sell: None };
+    let stale = oms.on_polled_orders(&on_exchange, true, true, polled_at, polled_at + Duration::from_millis(50));
+    assert_eq!(stale, [Some("Buy"), None]);
+    assert!(!oms.record("Buy").live && oms.record("Sell").live);
+    let resting = OpenOrders { buy: Some(OpenOrder { price: 99.9, qty: 1.0 }), sell: None };
+    assert_eq!(oms.on_polled_orders(&resting, false, true, polled_at, polled_at), [None, None]);
+
+    // Link ids ride the ring inline, cut at Bybit's 36 characters
+    assert_eq!(LinkId::new("bot-buy-1").as_str(), "bot-buy-1");
+    assert_eq!(LinkId::new(&"x".repeat(40)).as_str().len(), 36);
+    assert_eq!(rest.update(false), Some(false));
+}
+
+#[test]
+fn quote_uptime_is_credited_per_utc_hour_and_alerts_below_the_threshold() {
+    let mut section = ObligationSection::default();
+    section.apply_spec("spread_bps=20,uptime=0.9").unwrap();
+    let config = section.obligation();
+    let mut uptime = QuoteUptime::new(config);
+    // Nothing resting: never compliant
+    let oms = OrderManager::new(OrderManagerConfig::default());
+    assert!(!uptime.compliant(oms.record("Buy"), oms.record("Sell"), 100.0));
+
+    // 10:30 -> 11:30 UTC on day 1, sampled every second: quoting until 11:15, then not
+    let hour = 3_600_000;
+    let start = 24 * hour + 10 * hour + hour / 2;
+    let mut now = start;
+    while now <= start + hour {
+        uptime.on_sample(now < 24 * hour + 11 * hour + hour / 4, now);
+        now += 1_000;
+    }
+    // The 10:00 window only saw 30 quoting minutes out of 60
+    let report = uptime.take_report().expect("10:00 closed");
+    assert_eq!((report.period, report.start_ms), (UptimePeriod::Hour, 24 * hour + 10 * hour));
+    assert!((report.uptime - 0.5).abs() < 1e-3 && report.below);
+    assert!(uptime.take_report().is_none()); // the day is still open
+    // 11:00 so far: 15 of 30 minutes
+    assert!((uptime.running(UptimePeriod::Hour).unwrap() - 0.5).abs() < 1e-3);
+
+    // A stalled loop is not credited even if quotes were up before and after it
+    uptime.on_sample(true, now);
+    uptime.on_sample(true, now + 60_000);
+    assert!((uptime.running(UptimePeriod::Hour).unwrap() - 0.5 * 1_800.0 / 1_860.0).abs() < 1e-3);
+}
+
+#[test]
+fn shadow_quotes_on_paper_and_reports_against_live_fills() {
+    let book = book();
+    let update = BookUpdate { ts: 1, update_id: 1, snapshot: true, levels: 20 };
+    let mut candidate = MarketMaker::new(0.01);
+    candidate.requote_pending = true;
+    let mut shadow = ShadowStrategy::new(candidate, OrderManagerConfig::default(), PaperConfig { model: FillModel::Optimistic, ..PaperConfig::default() }, Instant::now());
+    shadow.on_live_actions(0);
+    shadow.on_book(&book, &update, true, false);
+    assert!(shadow.actions > 0, "candidate quotes");
+    assert!(shadow.paper.is_resting("Buy") && shadow.paper.is_resting("Sell"));
+
+    // A taker sweeping the bids fills the shadow bid on paper; the live side got nothing
+    let trade = TradeEvent { ts: 2, side: Side::Sell, price: 99.0, qty: 50.0, block_trade: false };
+    shadow.on_trade(&trade, Instant::now());
+    assert_eq!(shadow.paper.fills, 1);
+    assert!(shadow.strategy.positions.size() > 0.0, "paper fill settled into the candidate");
+
+    // Reported side by side at the same mid, never before `report_every`
+    assert!(!shadow.report_due(100.05, Instant::now()));
+    shadow.report_every = Duration::ZERO;
+    assert!(shadow.report_due(100.05, Instant::now()));
+    assert!(shadow.pnl(100.05).is_finite());
+}
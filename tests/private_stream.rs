@@ -0,0 +1,269 @@
+// Replays captured private / Trade WS payloads (tests/fixtures/private) through the same
+// library calls the Hot Thread makes and checks what they do to each symbol slot.
+
+use std::time::Instant;
+use simd_json::prelude::*;
+use hft_rust::strategy::book_manager::BookManager;
+use hft_rust::strategy::fill::Fill;
+use hft_rust::strategy::market_maker::ActionType;
+use hft_rust::strategy::order_manager::OrderManagerConfig;
+use hft_rust::strategy::private_stream::{self, Recovery, Response};
+use hft_rust::strategy::send_gate::SendGateConfig;
+use hft_rust::strategy::symbols::{SymbolRegistry, PRIMARY_SYMBOL};
+
+macro_rules! fixture {
+    ($name:literal) => {
+        ($name, include_str!(concat!("fixtures/private/", $name, ".json")))
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stream {
+    Private,
+    Trade,
+}
+
+/// What a message may change, per symbol slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SlotState {
+    position: f64,
+    leverage: Option<f64>,
+    buy_active: bool,
+    sell_active: bool,
+    // OrderManager records
+    buy_live: bool,
+    sell_live: bool,
+    buy_price: f64,
+    buy_pending: bool,
+    // Strategy generated a new link id (reset, not restored)
+    buy_relinked: bool,
+    sell_relinked: bool,
+}
+
+const LINK_MS: u64 = 1_700_000_000_000;
+
+// Slot 0 RIVERUSDT long 3 with both quotes resting and a buy amend in flight,
+// slot 1 BTCUSDT flat with both quotes resting.
+fn books() -> BookManager {
+    let primary = *SymbolRegistry::default().get(PRIMARY_SYMBOL).unwrap();
+    let mut second = primary;
+    second.name = "BTC-PERP";
+    second.venue_symbols = [Some("BTCUSDT"), Some("BTCUSDT"), None];
+    let mut books = BookManager::new();
+    books.add(primary, SendGateConfig::default(), OrderManagerConfig::default()).unwrap();
+    books.add(second, SendGateConfig::default(), OrderManagerConfig::default()).unwrap();
+    let now = Instant::now();
+    for slot in &mut books.slots {
+        let tag = slot.strategy.link_tag.clone();
+        for (side, price) in [("Buy", 5.0), ("Sell", 5.01)] {
+            let link_id = format!("{}{}-{}", tag, &side[..1].to_ascii_lowercase(), LINK_MS);
+            slot.order_manager.on_sent(&ActionType::CreateOrder { price, qty: 10.0, side, link_id: link_id.clone() }, now);
+            if side == "Buy" {
+                slot.strategy.has_active_buy = true;
+                slot.strategy.active_buy_link_id = link_id;
+            } else {
+                slot.strategy.has_active_sell = true;
+                slot.strategy.active_sell_link_id = link_id;
+            }
+        }
+    }
+    books.slots[0].strategy.sync_position(3.0, 4.9);
+    books.slots[0].order_manager.on_sent(&ActionType::AmendPrice { price: 5.002, side: "Buy", link_id: format!("b-{}", LINK_MS) }, now);
+    books
+}
+
+fn state(books: &BookManager) -> [SlotState; 2] {
+    [0, 1].map(|i| {
+        let slot = &books.slots[i];
+        let (strategy, oms) = (&slot.strategy, &slot.order_manager);
+        SlotState {
+            position: strategy.position,
+            leverage: slot.send_gate.leverage,
+            buy_active: strategy.has_active_buy,
+            sell_active: strategy.has_active_sell,
+            buy_live: oms.buy.live,
+            sell_live: oms.sell.live,
+            buy_price: oms.buy.price,
+            buy_pending: oms.buy.pending.is_some(),
+            buy_relinked: !strategy.active_buy_link_id.ends_with(&LINK_MS.to_string()),
+            sell_relinked: !strategy.active_sell_link_id.ends_with(&LINK_MS.to_string()),
+        }
+    })
+}
+
+// The Hot Thread's dispatch, minus I/O and account-wide trackers. Returns (auth ok, recovery).
+fn feed(books: &mut BookManager, stream: Stream, payload: &str) -> (bool, Recovery) {
+    let mut bytes = payload.trim().as_bytes().to_vec();
+    let json = simd_json::to_borrowed_value(&mut bytes).expect("fixture is JSON");
+    let response = Response::of(&json);
+    let now = Instant::now();
+    let recovery = match stream {
+        Stream::Trade => {
+            if json.get("reqId").is_some() {
+                private_stream::on_trade_response(books, &response, now);
+            }
+            private_stream::on_trade_error(books, &response)
+        }
+        Stream::Private => private_stream::on_private_error(books, &response),
+    };
+    let items = json.get("data").and_then(|v| v.as_array()).into_iter().flatten();
+    match json.get("topic").and_then(|v| v.as_str()) {
+        Some("execution") => for item in items {
+            let Some(slot) = books.slot_of(item.get("symbol").and_then(|v| v.as_str()).unwrap_or("")) else { continue };
+            let slot = &mut books.slots[slot];
+            let status = item.get("orderStatus").and_then(|v| v.as_str()).unwrap_or("");
+            if item.get("execType").and_then(|v| v.as_str()) == Some("Trade") {
+                let mut fill = Fill::from_execution(item).expect("fill side");
+                fill.qty = slot.instrument.base_qty(fill.qty, fill.px);
+                let link_id = item.get("orderLinkId").and_then(|v| v.as_str()).unwrap_or("");
+                private_stream::on_fill(slot, &fill, link_id, status, now);
+            } else {
+                private_stream::on_order_status(slot, item.get("side").and_then(|v| v.as_str()).unwrap_or(""), status);
+            }
+        },
+        Some("position") => for item in items {
+            private_stream::on_position(books, item);
+        },
+        _ => {}
+    }
+    (response.is_auth_ok(), recovery)
+}
+
+type Expect = fn(&mut [SlotState; 2]);
+// Stream, (name, payload), auth ok, recovery, change to the starting state
+type Case = (Stream, (&'static str, &'static str), bool, Recovery, Expect);
+
+#[test]
+fn captured_messages_drive_the_expected_state_transitions() {
+    let reset = |slot| Recovery { reset: Some(slot), ..Recovery::default() };
+    let flattened = |slot| Recovery { flattened: Some(slot), ..Recovery::default() };
+    let none = Recovery::default();
+    let cases: [Case; 20] = [
+        // Auth acks: private (success flag) and Trade (retCode) shapes; a failure changes nothing
+        (Stream::Private, fixture!("auth_ok"), true, none, |_| {}),
+        (Stream::Private, fixture!("auth_failed"), false, none, |_| {}),
+        (Stream::Trade, fixture!("trade_auth_ok"), true, none, |_| {}),
+        // Executions: a partial fill keeps the order live, a full fill closes it
+        (Stream::Private, fixture!("execution_partial_buy"), false, none, |s| s[0].position = 7.0),
+        (Stream::Private, fixture!("execution_filled_sell"), false, none, |s| {
+            s[0].position = -7.0;
+            s[0].sell_live = false;
+        }),
+        (Stream::Private, fixture!("execution_cancelled_buy"), false, none, |s| {
+            s[0].buy_active = false;
+            s[0].buy_live = false;
+            s[0].buy_pending = false;
+        }),
+        (Stream::Private, fixture!("execution_unquoted_symbol"), false, none, |_| {}),
+        // Positions: exchange size and leverage win, per symbol
+        (Stream::Private, fixture!("position_long"), false, none, |s| {
+            s[0].position = 12.3;
+            s[0].leverage = Some(5.0);
+        }),
+        (Stream::Private, fixture!("position_short_second_symbol"), false, none, |s| {
+            s[1].position = -0.5;
+            s[1].leverage = Some(20.0);
+        }),
+        // Private rejects: failed creates, gone orders, mode mismatches and duplicates reset the side
+        (Stream::Private, fixture!("reject_create_insufficient_balance"), false, reset((0, "Buy")), |s| {
+            s[0].buy_active = false;
+            s[0].buy_live = false;
+            s[0].buy_pending = false;
+            s[0].buy_relinked = true;
+        }),
+        (Stream::Private, fixture!("reject_amend_order_not_exists"), false, reset((0, "Buy")), |s| {
+            s[0].buy_active = false;
+            s[0].buy_live = false;
+            s[0].buy_pending = false;
+            s[0].buy_relinked = true;
+        }),
+        (Stream::Private, fixture!("reject_amend_not_modified"), false, none, |_| {}),
+        (Stream::Private, fixture!("reject_params_error_second_symbol"), false, reset((1, "Sell")), |s| {
+            s[1].sell_active = false;
+            s[1].sell_live = false;
+            s[1].sell_relinked = true;
+        }),
+        (Stream::Private, fixture!("reject_duplicate_link_id"), false, reset((0, "Sell")), |s| {
+            s[0].sell_active = false;
+            s[0].sell_live = false;
+            s[0].sell_relinked = true;
+        }),
+        // Closing a position that is not there: flatten, drop both flags (records stay)
+        (Stream::Private, fixture!("reject_reduce_only_no_position"), false, flattened(0), |s| {
+            s[0].position = 0.0;
+            s[0].buy_active = false;
+            s[0].sell_active = false;
+        }),
+        (Stream::Private, fixture!("reject_invalid_request_second_symbol"), false, flattened(1), |s| {
+            s[1].buy_active = false;
+            s[1].sell_active = false;
+        }),
+        (Stream::Private, fixture!("reject_rate_limit"), false, Recovery { rate_limited: true, ..none }, |_| {}),
+        // Trade WS: amend ack promotes the pending price, a reject rolls it back
+        (Stream::Trade, fixture!("amend_ack"), false, none, |s| {
+            s[0].buy_price = 5.002;
+            s[0].buy_pending = false;
+        }),
+        (Stream::Trade, fixture!("reject_amend_not_modified"), false, none, |s| s[0].buy_pending = false),
+        (Stream::Trade, fixture!("trade_cancel_order_not_exists"), false, reset((0, "Sell")), |s| {
+            s[0].sell_active = false;
+            s[0].sell_live = false;
+            s[0].sell_relinked = true;
+        }),
+    ];
+
+    for (stream, (name, payload), auth, recovery, expect) in cases {
+        let mut books = books();
+        let mut expected = state(&books);
+        expect(&mut expected);
+        let (auth_ok, got) = feed(&mut books, stream, payload);
+        assert_eq!((auth_ok, got), (auth, recovery), "{} ({:?}): auth / recovery", name, stream);
+        assert_eq!(state(&books), expected, "{} ({:?}): slot state", name, stream);
+    }
+}
+
+#[test]
+fn trade_reject_of_a_lost_amend_rolls_back_then_resets() {
+    let mut books = books();
+    let (_, payload) = fixture!("reject_amend_order_not_exists");
+    let (_, recovery) = feed(&mut books, Stream::Trade, payload);
+    assert_eq!(recovery.reset, Some((0, "Buy")));
+    let slot = &books.slots[0];
+    // Amend dropped (confirmed price kept), then the order itself is gone
+    assert_eq!(slot.order_manager.buy.price, 5.0);
+    assert!(slot.order_manager.buy.pending.is_none());
+    assert!(!slot.order_manager.buy.live && !slot.strategy.has_active_buy);
+    // The other side and the other symbol are untouched
+    assert!(slot.order_manager.sell.live && books.slots[1].order_manager.buy.live);
+}
+
+#[test]
+fn position_snapshot_and_fills_settle_on_the_cross_sequence() {
+    let (_, position) = fixture!("position_long");
+    let (_, partial) = fixture!("execution_partial_buy");
+    let (_, filled) = fixture!("execution_filled_sell");
+
+    // The snapshot (seq ...129) overtakes both fills it contains (...127, ...128)
+    let mut manager = books();
+    for payload in [position, partial, filled] {
+        feed(&mut manager, Stream::Private, payload);
+    }
+    let strategy = &manager.slots[0].strategy;
+    assert_eq!((strategy.position, strategy.positions.skipped_fills), (12.3, 2));
+    assert_eq!(strategy.entry_price, 5.123);
+
+    // A snapshot older than a booked fill is dropped; the newer one still lands
+    let mut manager = books();
+    feed(&mut manager, Stream::Private, partial);
+    let stale = position.replace("4688002129", "4688002126").replace(r#""size":"12.3""#, r#""size":"3.0""#);
+    feed(&mut manager, Stream::Private, &stale);
+    assert_eq!((manager.slots[0].strategy.position, manager.slots[0].strategy.positions.stale_snapshots), (7.0, 1));
+    feed(&mut manager, Stream::Private, position);
+    feed(&mut manager, Stream::Private, filled);
+    assert_eq!(manager.slots[0].strategy.position, 12.3);
+
+    // Entry of the booked fills: 3 @ 4.9 plus 4 @ 5.0
+    let mut manager = books();
+    feed(&mut manager, Stream::Private, partial);
+    assert!((manager.slots[0].strategy.entry_price - (3.0 * 4.9 + 4.0 * 5.0) / 7.0).abs() < 1e-9);
+}